@@ -21,7 +21,7 @@ fn get_event_type(event_name: &str) -> Option<proc_macro2::TokenStream> {
         "focus" | "blur" => quote!(web_sys::FocusEvent),
         "change" | "submit" => quote!(web_sys::Event),
         "input" => quote!(web_sys::InputEvent),
-        "scroll" | "resize" => quote!(web_sys::UIEvent),
+        "scroll" | "resize" => quote!(web_sys::UiEvent),
         "drag" | "dragstart" | "dragend" | "dragenter" | "dragleave" | "dragover" | "drop" => {
             quote!(web_sys::DragEvent)
         }
@@ -0,0 +1,74 @@
+use std::os::unix::process::ExitStatusExt as _;
+
+use tokio::process::Child;
+use tokio::sync::watch;
+use tracing::debug;
+use tracing::warn;
+
+/// How the child process of a [ProcessIO](crate::ProcessIO) terminated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// The process exited with the given code.
+    Code(i32),
+
+    /// The process was terminated by the given signal.
+    Signal(i32),
+}
+
+impl From<std::process::ExitStatus> for ExitStatus {
+    fn from(status: std::process::ExitStatus) -> Self {
+        if let Some(code) = status.code() {
+            Self::Code(code)
+        } else {
+            Self::Signal(status.signal().unwrap_or_default())
+        }
+    }
+}
+
+/// Receives the [ExitStatus] once the child process has terminated.
+#[derive(Clone)]
+pub struct ExitStatusRx(watch::Receiver<Option<ExitStatus>>);
+
+impl ExitStatusRx {
+    /// Reaps the child process in the background.
+    pub(crate) fn new(mut child_process: Child) -> Self {
+        Self::spawn(async move {
+            match child_process.wait().await {
+                Ok(status) => {
+                    let status = ExitStatus::from(status);
+                    debug!("The child process terminated with {status:?}");
+                    Some(status)
+                }
+                Err(error) => {
+                    warn!("Failed to wait for the child process: {error}");
+                    None
+                }
+            }
+        })
+    }
+
+    /// Collects the [ExitStatus] from a future, e.g. when the process is not our child.
+    pub fn spawn(exit_status: impl Future<Output = Option<ExitStatus>> + Send + 'static) -> Self {
+        let (tx, rx) = watch::channel(None);
+        tokio::spawn(async move {
+            if let Some(exit_status) = exit_status.await {
+                let _ = tx.send(Some(exit_status));
+            }
+        });
+        Self(rx)
+    }
+
+    /// Returns the [ExitStatus] if the child process has already terminated.
+    pub fn get(&self) -> Option<ExitStatus> {
+        *self.0.borrow()
+    }
+
+    /// Waits for the child process to terminate.
+    ///
+    /// Returns `None` if the exit status could not be collected.
+    pub async fn wait(&self) -> Option<ExitStatus> {
+        let mut rx = self.0.clone();
+        let status = rx.wait_for(Option::is_some).await.ok()?;
+        *status
+    }
+}
@@ -0,0 +1,49 @@
+//! How to start the process of a terminal.
+
+use std::path::PathBuf;
+
+use super::command::Command;
+
+/// The command, working directory and environment of a new terminal.
+#[derive(Clone, Debug, Default)]
+pub struct Launch {
+    /// The program to run, or an interactive `$SHELL` if not set.
+    pub program: Option<String>,
+    pub args: Vec<String>,
+    pub working_directory: Option<PathBuf>,
+
+    /// Extra environment variables, set after [TERRAZZO_CLIENT_NAME](crate::TERRAZZO_CLIENT_NAME).
+    pub env: Vec<(String, String)>,
+}
+
+impl Launch {
+    /// Runs a command line with `/bin/bash -lc`.
+    pub fn bash(command_line: impl Into<String>) -> Self {
+        Self {
+            program: Some("/bin/bash".into()),
+            args: vec!["-lc".into(), command_line.into()],
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn command(&self, client_name: Option<&str>) -> Command {
+        let mut command = if let Some(program) = &self.program {
+            let mut command = Command::new(program);
+            command.args(&self.args);
+            command
+        } else {
+            let mut command =
+                std::env::var("SHELL").map_or_else(|_| Command::new("/bin/bash"), Command::new);
+            command.arg("-i");
+            command
+        };
+        if let Some(working_directory) = &self.working_directory {
+            command.current_dir(working_directory);
+        }
+        if let Some(client_name) = client_name {
+            command.env(crate::TERRAZZO_CLIENT_NAME, client_name);
+        }
+        command.envs(self.env.iter().map(|(key, value)| (key, value)));
+        command
+    }
+}
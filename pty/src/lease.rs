@@ -13,6 +13,8 @@ use nameth::NamedEnumValues as _;
 use nameth::NamedType as _;
 use nameth::nameth;
 use scopeguard::defer;
+use tokio_util::sync::CancellationToken;
+use tokio_util::sync::WaitForCancellationFutureOwned;
 use tracing::debug;
 use tracing::debug_span;
 use tracing::info;
@@ -21,28 +23,47 @@ use tracing::trace;
 use crate::ProcessIO;
 use crate::ProcessInput;
 use crate::ProcessOutput;
+use crate::exit_status::ExitStatusRx;
 use crate::release_on_drop::ReleaseOnDrop;
+use crate::tail::TailStream;
 
 #[nameth]
 pub struct ProcessIoEntry {
     input: Mutex<ProcessInput>,
     output: Mutex<Option<ProcessOutputExchange>>,
+    tail: TailStream,
+    exit_status: ExitStatusRx,
+
+    /// Ends the shared leases and the watchers when the entry is dropped.
+    closed: CancellationToken,
 }
 
+/// The output read alongside the exclusive lease, until the [ProcessIoEntry] is dropped.
+pub type SharedOutput = TakeUntil<ProcessOutput, Pin<Box<WaitForCancellationFutureOwned>>>;
+
 impl ProcessIoEntry {
     pub fn new(process_io: ProcessIO) -> Arc<Self> {
         info!("Create {}", Self::type_name());
+        let exit_status = process_io.exit_status().clone();
         let (input, output) = process_io.split();
+        let tail = output.0.clone();
         Arc::new(Self {
             input: Mutex::new(input),
             output: Mutex::new(Some(ProcessOutputExchange::new(output))),
+            tail,
+            exit_status,
+            closed: CancellationToken::new(),
         })
     }
 
     pub async fn lease_output(
         self: &Arc<Self>,
-        rewind: bool,
+        mode: LeaseMode,
+        rewind: Rewind,
     ) -> Result<ProcessOutputLease, LeaseProcessOutputError> {
+        if mode == LeaseMode::Shared {
+            return Ok(self.share_output(rewind));
+        }
         let mut lock = self.output.lock().await;
         let exchange = lock.take().ok_or(LeaseProcessOutputError::OutputNotSet)?;
         let (lease, exchange) = exchange.lease(rewind).await?;
@@ -50,14 +71,53 @@ impl ProcessIoEntry {
         return Ok(lease);
     }
 
+    /// Reads the output with its own position,
+    /// alongside the exclusive lease and the other shared leases.
+    fn share_output(&self, rewind: Rewind) -> ProcessOutputLease {
+        let mut tail = self.tail.clone();
+        match rewind {
+            Rewind::No => tail.skip_to_end(),
+            Rewind::Scrollback => tail.rewind(),
+            Rewind::History => tail.rewind_history(),
+        }
+        ProcessOutputLease::Shared(self.until_closed(tail))
+    }
+
+    /// Follows the output from now on, without revoking the current lease.
+    pub fn follow_output(&self) -> ProcessOutput {
+        let mut tail = self.tail.clone();
+        tail.skip_to_end();
+        ProcessOutput(tail)
+    }
+
+    /// Reads the retained output and follows it, without revoking the current lease.
+    pub fn watch_output(&self) -> SharedOutput {
+        self.until_closed(self.tail.clone())
+    }
+
+    fn until_closed(&self, tail: TailStream) -> SharedOutput {
+        ProcessOutput(tail).take_until(Box::pin(self.closed.clone().cancelled_owned()))
+    }
+
+    /// Copies the retained output, including the last `max_spill` bytes spilled to disk.
+    pub fn scrollback(&self, max_spill: u64) -> Vec<Bytes> {
+        self.tail.snapshot(max_spill)
+    }
+
     pub async fn input(&self) -> futures::lock::MutexGuard<'_, ProcessInput> {
         self.input.lock().await
     }
+
+    /// Tracks the [ExitStatus](crate::exit_status::ExitStatus) of the process.
+    pub fn exit_status(&self) -> &ExitStatusRx {
+        &self.exit_status
+    }
 }
 
 impl Drop for ProcessIoEntry {
     fn drop(&mut self) {
         info!("Drop {}", Self::type_name());
+        self.closed.cancel();
     }
 }
 
@@ -71,6 +131,34 @@ pub enum LeaseProcessOutputError {
     LeaseError(#[from] LeaseError),
 }
 
+/// Whether a new lease takes the output away from the current lease.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LeaseMode {
+    /// Revokes the current lease.
+    #[default]
+    Exclusive,
+
+    /// Reads the output alongside the current lease.
+    ///
+    /// Shared leases are never revoked, they end when the process is closed.
+    Shared,
+}
+
+/// Where a new lease starts reading the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Rewind {
+    /// Only the output that was not read by the previous lease,
+    /// or only the output to come for a shared lease.
+    #[default]
+    No,
+
+    /// The scrollback kept in memory.
+    Scrollback,
+
+    /// The whole history, including the output spilled to disk.
+    History,
+}
+
 struct ProcessOutputExchange {
     signal_tx: oneshot::Sender<()>,
     process_output_rx: oneshot::Receiver<ProcessOutput>,
@@ -85,15 +173,17 @@ impl ProcessOutputExchange {
         }
     }
 
-    async fn lease(self, rewind: bool) -> Result<(ProcessOutputLease, Self), LeaseError> {
+    async fn lease(self, rewind: Rewind) -> Result<(ProcessOutputLease, Self), LeaseError> {
         match self.signal_tx.send(()) {
             Ok(()) => debug!("Current lease was stopped"),
             Err(()) => debug!("The process was not leased"),
         }
         debug!("Getting new lease...");
         let mut process_output = self.process_output_rx.await?;
-        if rewind {
-            process_output.0.rewind();
+        match rewind {
+            Rewind::No => {}
+            Rewind::Scrollback => process_output.0.rewind(),
+            Rewind::History => process_output.0.rewind_history(),
         }
         debug!("Getting new lease: Done");
         let (lease, signal_tx, process_output_rx) = ProcessOutputLease::new(process_output);
@@ -119,6 +209,9 @@ pub enum ProcessOutputLease {
     /// The process is active and this is the current lease.
     Leased(TakeUntil<ReleaseOnDrop<ProcessOutput>, oneshot::Receiver<()>>),
 
+    /// A shared lease: it can't be revoked, it ends when the process is closed.
+    Shared(SharedOutput),
+
     /// The process is still active but another client is consuming the stream.
     Revoked,
 
@@ -153,28 +246,36 @@ impl Stream for ProcessOutputLease {
         cx: &mut std::task::Context<'_>,
     ) -> Poll<Option<Self::Item>> {
         trace!("Poll next: state={}", self.name());
-        let next = {
-            let process_io = match &mut *self {
-                ProcessOutputLease::Leased(process_io) => process_io,
-                ProcessOutputLease::Revoked => return None.into(),
-                ProcessOutputLease::Closed => {
+        let next = match &mut *self {
+            ProcessOutputLease::Leased(process_io) => {
+                let next = ready!(process_io.poll_next_unpin(cx));
+                if next.is_none() && process_io.is_stopped() {
+                    match process_io.take_result() {
+                        Some(Err(oneshot::Canceled)) | None => {
+                            debug!("The process ended");
+                            self.revoke();
+                            return Some(LeaseItem::EOS).into();
+                        }
+                        Some(Ok(())) => debug!("The lease was revoked"),
+                    }
+                }
+                trace! { "next.is_none={} process_io.is_stopped={}", next.is_none(), process_io.is_stopped() };
+                next
+            }
+            ProcessOutputLease::Shared(process_output) => {
+                let next = ready!(process_output.poll_next_unpin(cx));
+                if next.is_none() && process_output.is_stopped() {
+                    debug!("The process was closed");
                     self.revoke();
                     return Some(LeaseItem::EOS).into();
                 }
-            };
-            let next = ready!(process_io.poll_next_unpin(cx));
-            if next.is_none() && process_io.is_stopped() {
-                match process_io.take_result() {
-                    Some(Err(oneshot::Canceled)) | None => {
-                        debug!("The process ended");
-                        self.revoke();
-                        return Some(LeaseItem::EOS).into();
-                    }
-                    Some(Ok(())) => debug!("The lease was revoked"),
-                }
+                next
+            }
+            ProcessOutputLease::Revoked => return None.into(),
+            ProcessOutputLease::Closed => {
+                self.revoke();
+                return Some(LeaseItem::EOS).into();
             }
-            trace! { "next.is_none={} process_io.is_stopped={}", next.is_none(), process_io.is_stopped() };
-            next
         };
 
         Some(match next {
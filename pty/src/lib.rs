@@ -1,5 +1,6 @@
 #![doc = include_str!("../README.md")]
 
+use std::path::Path;
 use std::task::Poll;
 use std::task::ready;
 
@@ -8,16 +9,21 @@ use futures::Stream;
 use nameth::NamedEnumValues as _;
 use nameth::nameth;
 use pin_project::pin_project;
+use tokio::io::AsyncWrite;
 use tokio_util::io::ReaderStream;
 
-use self::command::Command;
 use self::command::SpawnError;
+use self::exit_status::ExitStatusRx;
+use self::launch::Launch;
 use self::pty::OwnedWritePty;
 use self::pty::Pty;
 use self::pty::PtyError;
+use self::size::Size;
 use self::tail::TailStream;
 
 mod command;
+pub mod exit_status;
+pub mod launch;
 pub mod lease;
 pub mod pty;
 mod raw_pts;
@@ -29,14 +35,23 @@ mod tail;
 pub const TERRAZZO_CLIENT_NAME: &str = "TERRAZZO_CLIENT_NAME";
 
 pub struct ProcessIO {
-    input: OwnedWritePty,
+    input: Box<dyn PtyInput>,
     output: TailStream,
-    #[expect(unused)]
-    child_process: tokio::process::Child,
+    exit_status: ExitStatusRx,
 }
 
-#[pin_project]
-pub struct ProcessInput(#[pin] pub OwnedWritePty);
+/// The writing end of a pty, which may be held by another process.
+pub trait PtyInput: AsyncWrite + Send + Unpin + 'static {
+    fn resize(&self, size: Size) -> Result<(), PtyError>;
+}
+
+impl PtyInput for OwnedWritePty {
+    fn resize(&self, size: Size) -> Result<(), PtyError> {
+        OwnedWritePty::resize(self, size)
+    }
+}
+
+pub struct ProcessInput(pub Box<dyn PtyInput>);
 
 #[pin_project]
 pub struct ProcessOutput(#[pin] pub TailStream);
@@ -58,22 +73,10 @@ impl ProcessIO {
     pub async fn open(
         client_name: Option<impl AsRef<str>>,
         scrollback: usize,
-        terminal_shell: Option<String>,
+        launch: &Launch,
     ) -> Result<Self, OpenProcessError> {
         let pty = Pty::new()?;
-        let mut command = if let Some(terminal_shell) = terminal_shell {
-            let mut command = Command::new("/bin/bash");
-            command.arg("-lc").arg(terminal_shell);
-            command
-        } else {
-            let mut command =
-                std::env::var("SHELL").map_or_else(|_| Command::new("/bin/bash"), Command::new);
-            command.arg("-i");
-            command
-        };
-        if let Some(client_name) = client_name {
-            command.env(TERRAZZO_CLIENT_NAME, client_name.as_ref());
-        }
+        let mut command = launch.command(client_name.as_ref().map(AsRef::as_ref));
         let child = command.spawn(&pty.pts()?)?;
 
         // https://forums.developer.apple.com/forums/thread/734230
@@ -85,48 +88,75 @@ impl ProcessIO {
     fn new(pty: Pty, child_process: tokio::process::Child, scrollback: usize) -> Self {
         let (output, input) = pty.into_split();
         let output = ReaderStream::with_capacity(output, scrollback);
-        let output = TailStream::new(output, scrollback);
+        Self::from_parts(input, output, scrollback, ExitStatusRx::new(child_process))
+    }
+
+    /// Assembles a [ProcessIO] whose pty is not owned by this process.
+    pub fn from_parts<S>(
+        input: impl PtyInput,
+        output: S,
+        scrollback: usize,
+        exit_status: ExitStatusRx,
+    ) -> Self
+    where
+        S: Stream<Item = std::io::Result<Bytes>> + Send + 'static,
+    {
         Self {
-            input,
-            output,
-            child_process,
+            input: Box::new(input),
+            output: TailStream::new(output, scrollback),
+            exit_status,
         }
     }
 
+    pub fn exit_status(&self) -> &ExitStatusRx {
+        &self.exit_status
+    }
+
+    /// Keeps the output that no longer fits in the scrollback in a file.
+    pub fn spill_to(&self, path: &Path, limit: u64) -> std::io::Result<()> {
+        self.output.spill_to(path, limit)
+    }
+
     pub fn split(self) -> (ProcessInput, ProcessOutput) {
         (ProcessInput(self.input), ProcessOutput(self.output))
     }
 }
 
+impl ProcessInput {
+    pub fn resize(&self, size: Size) -> Result<(), PtyError> {
+        self.0.resize(size)
+    }
+}
+
 impl tokio::io::AsyncWrite for ProcessInput {
     fn poll_write(
-        self: std::pin::Pin<&mut Self>,
+        mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
         buf: &[u8],
     ) -> Poll<std::io::Result<usize>> {
-        self.project().0.poll_write(cx, buf)
+        std::pin::Pin::new(&mut *self.0).poll_write(cx, buf)
     }
 
     fn poll_flush(
-        self: std::pin::Pin<&mut Self>,
+        mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> Poll<std::io::Result<()>> {
-        self.project().0.poll_flush(cx)
+        std::pin::Pin::new(&mut *self.0).poll_flush(cx)
     }
 
     fn poll_shutdown(
-        self: std::pin::Pin<&mut Self>,
+        mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> Poll<Result<(), std::io::Error>> {
-        self.project().0.poll_shutdown(cx)
+        std::pin::Pin::new(&mut *self.0).poll_shutdown(cx)
     }
 
     fn poll_write_vectored(
-        self: std::pin::Pin<&mut Self>,
+        mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
         bufs: &[std::io::IoSlice<'_>],
     ) -> Poll<Result<usize, std::io::Error>> {
-        self.project().0.poll_write_vectored(cx, bufs)
+        std::pin::Pin::new(&mut *self.0).poll_write_vectored(cx, bufs)
     }
 
     fn is_write_vectored(&self) -> bool {
@@ -152,10 +182,124 @@ impl Stream for ProcessOutput {
 
 #[cfg(test)]
 mod tests {
+    use futures::StreamExt as _;
+
+    use crate::exit_status::ExitStatus;
+    use crate::launch::Launch;
+    use crate::lease::LeaseItem;
+    use crate::lease::LeaseMode;
+    use crate::lease::ProcessIoEntry;
+    use crate::lease::Rewind;
+
     #[tokio::test]
     async fn open() {
-        super::ProcessIO::open(Option::<String>::None, 1000, None)
+        super::ProcessIO::open(Option::<String>::None, 1000, &Launch::default())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn launch() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let launch = Launch {
+            program: Some("/bin/sh".into()),
+            args: vec![
+                "-c".into(),
+                "echo $PWD $TERRAZZO_CLIENT_NAME $GREETING".into(),
+            ],
+            working_directory: Some(tempdir.path().canonicalize().unwrap()),
+            env: vec![("GREETING".into(), "hello".into())],
+        };
+        let process_io = super::ProcessIO::open(Some("client"), 1000, &launch)
+            .await
+            .unwrap();
+        let (_input, output) = process_io.split();
+        let output = output
+            .take_until(tokio::time::sleep(std::time::Duration::from_secs(5)))
+            .filter_map(async |data| data.ok().map(Vec::from))
+            .concat()
+            .await;
+        let expected = format!(
+            "{} client hello",
+            tempdir.path().canonicalize().unwrap().display()
+        );
+        assert_eq!(expected, String::from_utf8_lossy(&output).trim());
+    }
+
+    #[tokio::test]
+    async fn exit_code() {
+        let process_io =
+            super::ProcessIO::open(Option::<String>::None, 1000, &Launch::bash("exit 3"))
+                .await
+                .unwrap();
+        let exit_status = process_io.exit_status().clone();
+        let _output = process_io.split();
+        assert_eq!(Some(ExitStatus::Code(3)), exit_status.wait().await);
+        assert_eq!(Some(ExitStatus::Code(3)), exit_status.get());
+    }
+
+    #[tokio::test]
+    async fn exit_signal() {
+        let process_io =
+            super::ProcessIO::open(Option::<String>::None, 1000, &Launch::bash("kill -TERM $$"))
+                .await
+                .unwrap();
+        let exit_status = process_io.exit_status().clone();
+        assert_eq!(
+            Some(ExitStatus::Signal(libc::SIGTERM)),
+            exit_status.wait().await
+        );
+    }
+
+    #[tokio::test]
+    async fn shared_leases() {
+        let process_io = super::ProcessIO::open(
+            Option::<String>::None,
+            1000,
+            &Launch::bash("echo hello; sleep 5"),
+        )
+        .await
+        .unwrap();
+        let entry = ProcessIoEntry::new(process_io);
+        let mut leases = vec![];
+        for mode in [LeaseMode::Exclusive, LeaseMode::Shared, LeaseMode::Shared] {
+            let lease = entry.lease_output(mode, Rewind::Scrollback).await.unwrap();
+            leases.push(lease);
+        }
+        for lease in leases {
+            let output = lease
+                .take_until(tokio::time::sleep(std::time::Duration::from_secs(2)))
+                .filter_map(async |item| match item {
+                    LeaseItem::Data(data) => Some(Vec::from(data)),
+                    _ => None,
+                })
+                .concat()
+                .await;
+            assert_eq!("hello", String::from_utf8_lossy(&output).trim());
+        }
+    }
+
+    #[tokio::test]
+    async fn shared_lease_ends_when_closed() {
+        let process_io =
+            super::ProcessIO::open(Option::<String>::None, 1000, &Launch::bash("sleep 30"))
+                .await
+                .unwrap();
+        let entry = ProcessIoEntry::new(process_io);
+        let lease = entry
+            .lease_output(LeaseMode::Shared, Rewind::No)
+            .await
+            .unwrap();
+        let watch = entry.watch_output();
+        drop(entry);
+        let items =
+            tokio::time::timeout(std::time::Duration::from_secs(5), lease.collect::<Vec<_>>())
+                .await
+                .unwrap();
+        assert!(matches!(items.last(), Some(LeaseItem::EOS)));
+        let watched = tokio::time::timeout(std::time::Duration::from_secs(5), watch.count())
             .await
             .unwrap();
+        assert_eq!(0, watched);
     }
 }
@@ -32,6 +32,9 @@ pub enum PtyError {
 
     #[error("[{n}] {0}", n = self.name())]
     PtsError(#[from] PtsError),
+
+    #[error("[{n}] The pty is held by another process: {0}", n = self.name())]
+    Remote(std::io::Error),
 }
 
 impl Pty {
@@ -26,6 +26,16 @@ impl Size {
             ypixel,
         }
     }
+
+    #[must_use]
+    pub fn rows(&self) -> u16 {
+        self.row
+    }
+
+    #[must_use]
+    pub fn cols(&self) -> u16 {
+        self.col
+    }
 }
 
 impl From<Size> for libc::winsize {
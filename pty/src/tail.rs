@@ -1,5 +1,6 @@
 use std::collections::VecDeque;
 use std::io::ErrorKind;
+use std::path::Path;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -25,7 +26,15 @@ use tracing::trace;
 use tracing::trace_span;
 use tracing::warn;
 
+use self::boundary::BoundaryScanner;
+use self::spill::Spill;
+
+mod boundary;
+mod spill;
+
 /// A stream that only remembers the last few elements.
+///
+/// Old elements are trimmed at line boundaries, and can be spilled to a file.
 #[nameth]
 #[derive(Clone)]
 pub struct TailStream {
@@ -49,6 +58,8 @@ impl TailStream {
             buffer_state: state,
             stream_state: StreamState {
                 pos: 0,
+                history: None,
+                history_read: None,
                 future_rx: None,
                 worker_handle: AbortOnDrop(worker).into(),
             },
@@ -57,8 +68,75 @@ impl TailStream {
 
     pub fn rewind(&mut self) {
         self.stream_state.pos = 0;
+        self.stream_state.history = None;
+        self.stream_state.history_read = None;
         self.stream_state.future_rx = None;
     }
+
+    /// Skips the elements that are already buffered.
+    pub fn skip_to_end(&mut self) {
+        self.rewind();
+        let buffer_state = self.buffer_state.lock().expect("state");
+        self.stream_state.pos = buffer_state.start + buffer_state.lines.len();
+    }
+
+    /// Rewinds to the oldest element that was spilled to disk and not rotated out.
+    pub fn rewind_history(&mut self) {
+        self.rewind();
+        self.stream_state.history = Some(0);
+    }
+
+    /// Keeps the elements that are trimmed from the scrollback in a file.
+    ///
+    /// At most `limit` bytes are kept, the oldest history is rotated out.
+    pub fn spill_to(&self, path: &Path, limit: u64) -> std::io::Result<()> {
+        let spill = Spill::create(path, limit)?;
+        self.buffer_state.lock().expect("state").spill = Some(spill.into());
+        Ok(())
+    }
+
+    /// Copies all the retained elements, starting with the last `max_spill` bytes of the spilled
+    /// history.
+    ///
+    /// The spilled history is read after the lock on the buffer is released.
+    pub fn snapshot(&self, max_spill: u64) -> Vec<Bytes> {
+        let (spill, lines) = {
+            let buffer_state = self.buffer_state.lock().expect("state");
+            let spill = buffer_state.spill.clone().map(|spill| {
+                let end = spill.len();
+                (spill, end)
+            });
+            let lines = buffer_state.lines.iter();
+            let lines = lines.filter_map(|line| match line {
+                Some(Ok(bytes)) => Some(bytes.clone()),
+                _ => None,
+            });
+            (spill, lines.collect::<Vec<_>>())
+        };
+        let mut snapshot = vec![];
+        if let Some((spill, end)) = spill {
+            let mut offset = end.saturating_sub(max_spill);
+            while offset < end {
+                match spill.read(offset) {
+                    Ok((next, mut bytes)) => {
+                        let from = next - bytes.len() as u64;
+                        bytes.truncate(end.saturating_sub(from).min(bytes.len() as u64) as usize);
+                        if bytes.is_empty() {
+                            break;
+                        }
+                        offset = next;
+                        snapshot.push(bytes);
+                    }
+                    Err(error) => {
+                        warn!("Failed to read spilled history: {error}");
+                        break;
+                    }
+                }
+            }
+        }
+        snapshot.extend(lines);
+        snapshot
+    }
 }
 
 /// Runs the worker that keeps reading and buffering elements.
@@ -72,67 +150,110 @@ where
     defer!(trace!("Stop"));
     pin!(stream);
     let mut size = 0;
+    let mut scanner = BoundaryScanner::default();
     loop {
-        let item = stream.next().await;
+        let mut item = stream.next().await;
         trace!("Next: {item:?}");
         let end = item.is_none();
         let Some(state) = state.upgrade() else {
             trace!("All the readers have dropped");
             return;
         };
-        let mut lock = state.lock().expect("state");
-
-        // [ C0 = oldest, C1, C2, ... Cp, ..., C(n-1) ]
-        let BufferState {
-            lines,
-            start,
-            pending,
-            ..
-        } = &mut *lock;
-
-        let item_len = if let Some(Ok(bytes)) = &item {
-            bytes.len()
-        } else {
-            0
-        };
+        let dirty = {
+            let mut lock = state.lock().expect("state");
+
+            // [ C0 = oldest, C1, C2, ... Cp, ..., C(n-1) ]
+            let BufferState {
+                lines,
+                boundaries,
+                start,
+                spill,
+                pending,
+            } = &mut *lock;
+
+            let item_len = if let Some(Ok(bytes)) = &item {
+                bytes.len().min(scrollback)
+            } else {
+                0
+            };
+
+            let mut evicted = false;
+            while size + item_len > scrollback && !lines.is_empty() {
+                trace!("size:{size} > scrollback:{scrollback}");
+                // [ C1 = new oldest, C2, ... Cp, ..., C(n-1) ]
+                // --> Cp becomes the (p-1) element
+                size -= evict(lines, boundaries, start, spill);
+                evicted = true;
+            }
 
-        while size + item_len > scrollback {
-            trace!("size:{size} > scrollback:{scrollback}");
-            // [ C1 = new oldest, C2, ... Cp, ..., C(n-1) ]
-            // --> Cp becomes the (p-1) element
-            let oldest = lines.drain(..1).next().unwrap();
-            if let Some(Ok(bytes)) = oldest {
-                *start += 1;
-                trace! { start, "Buffer full, the oldest item was dropped (item={bytes:?})" }
-                size -= bytes.len();
+            // An item larger than the whole scrollback only keeps its end,
+            // e.g. when a session is restored with a smaller scrollback.
+            if let Some(Ok(bytes)) = &mut item
+                && bytes.len() > scrollback
+            {
+                let head = bytes.split_to(bytes.len() - scrollback);
+                trace! { "The item is larger than the scrollback, dropped {} bytes", head.len() }
+                scanner.scan(&head);
+                if let Some(spill) = spill {
+                    spill.push(head);
+                }
             }
-        }
 
-        // item becomes Cb
-        // [ C1 = new oldest, C2, ... Cp, ..., C(n-1), Cn = item ]
-        if let Some(Ok(bytes)) = &item {
-            size += bytes.len();
-        }
-        lines.push_back(item);
-
-        trace!("size:{size} <= scrollback:{scrollback} lines={lines:?}");
-        debug_assert!(size <= scrollback);
-
-        if let Some(PendingBufferState { worker, .. }) = pending {
-            trace! { "The stream is waiting for the next item" };
-            if let Some(future_tx) = worker.take() {
-                trace! { "The stream is waking up" };
-                let Ok(()) = future_tx.send(()) else {
-                    warn! { "The {}'s future_rx was dropped", TailStream::type_name() };
-                    return;
-                };
+            // item becomes Cb
+            // [ C1 = new oldest, C2, ... Cp, ..., C(n-1), Cn = item ]
+            let boundary = if let Some(Ok(bytes)) = &item {
+                size += bytes.len();
+                scanner.scan(bytes)
+            } else {
+                Some(0)
+            };
+            lines.push_back(item);
+            boundaries.push_back(boundary);
+
+            // The oldest item must not start in the middle of a line or an escape sequence.
+            // The newest item is never trimmed, readers may be waiting for it.
+            while evicted && lines.len() > 1 {
+                match boundaries[0] {
+                    Some(0) => break,
+                    Some(offset) => {
+                        let Some(Ok(bytes)) = &mut lines[0] else {
+                            unreachable!()
+                        };
+                        trace! { start, "Trimmed the oldest item at {offset}" }
+                        if let Some(spill) = spill {
+                            spill.push(bytes.slice(..offset));
+                        }
+                        *bytes = bytes.slice(offset..);
+                        boundaries[0] = Some(0);
+                        size -= offset;
+                    }
+                    None => size -= evict(lines, boundaries, start, spill),
+                }
             }
-        } else {
-            trace! { "The stream was not waiting on the worker to produce some data" };
-            if end {
-                break; // i.e. return
+
+            trace!("size:{size} <= scrollback:{scrollback} lines={lines:?}");
+            debug_assert!(size <= scrollback);
+
+            if let Some(PendingBufferState { worker, .. }) = pending {
+                trace! { "The stream is waiting for the next item" };
+                if let Some(future_tx) = worker.take() {
+                    trace! { "The stream is waking up" };
+                    let Ok(()) = future_tx.send(()) else {
+                        warn! { "The {}'s future_rx was dropped", TailStream::type_name() };
+                        return;
+                    };
+                }
             } else {
-                continue;
+                trace! { "The stream was not waiting on the worker to produce some data" };
+            }
+            spill.clone().filter(|spill| spill.is_dirty())
+        };
+
+        // The spilled history is written to disk after the lock is released.
+        if let Some(spill) = dirty {
+            drop(state);
+            if let Err(error) = tokio::task::spawn_blocking(move || spill.flush()).await {
+                warn!("Failed to spill history: {error}");
             }
         }
 
@@ -143,6 +264,29 @@ where
     }
 }
 
+/// Drops the oldest item and returns its size.
+fn evict(
+    lines: &mut VecDeque<Option<std::io::Result<Bytes>>>,
+    boundaries: &mut VecDeque<Option<usize>>,
+    start: &mut usize,
+    spill: &Option<Arc<Spill>>,
+) -> usize {
+    let Some(oldest) = lines.pop_front() else {
+        return 0;
+    };
+    boundaries.pop_front();
+    *start += 1;
+    let Some(Ok(bytes)) = oldest else {
+        return 0;
+    };
+    trace! { start, "Buffer full, the oldest item was dropped (item={bytes:?})" }
+    let len = bytes.len();
+    if let Some(spill) = spill {
+        spill.push(bytes);
+    }
+    len
+}
+
 impl Stream for TailStream {
     type Item = std::io::Result<Bytes>;
 
@@ -168,6 +312,33 @@ fn process_state(
         return handle_pending_state(cx, buffer_state, stream_state);
     }
 
+    if let Some(offset) = stream_state.history {
+        match &buffer_state.spill {
+            Some(spill) if offset < spill.len() => {
+                // The spilled history is read on a blocking thread.
+                let history_read = stream_state.history_read.get_or_insert_with(|| {
+                    let spill = spill.clone();
+                    tokio::task::spawn_blocking(move || spill.read(offset))
+                });
+                let Poll::Ready(item) = history_read.poll_unpin(cx) else {
+                    return Some(Poll::Pending);
+                };
+                stream_state.history_read = None;
+                let item = item.unwrap_or_else(|error| Err(std::io::Error::other(error)));
+                stream_state.history = match &item {
+                    Ok((next, _)) => Some(*next),
+                    Err(_) => None,
+                };
+                return Some(Poll::Ready(Some(item.map(|(_, bytes)| bytes))));
+            }
+            _ => {
+                trace! { "Replayed the spilled history" };
+                stream_state.history = None;
+                stream_state.pos = buffer_state.start;
+            }
+        }
+    }
+
     if stream_state.pos < buffer_state.start {
         trace!("Skipped {} lines", buffer_state.start - stream_state.pos);
         stream_state.pos = buffer_state.start;
@@ -246,15 +417,28 @@ fn handle_pending_state(
 struct BufferState {
     lines: VecDeque<Option<std::io::Result<Bytes>>>,
 
+    /// Where each line can be trimmed, see [BoundaryScanner::scan].
+    boundaries: VecDeque<Option<usize>>,
+
     /// The line number at which 'lines' starts, ie, the number of lines that were discarded.
     start: usize,
 
+    /// Everything that was dropped from 'lines'.
+    spill: Option<Arc<Spill>>,
+
     /// Waiting for some lines to be read
     pending: Option<PendingBufferState>,
 }
 
 struct StreamState {
     pos: usize,
+
+    /// The offset in the spilled history, while it is being replayed.
+    history: Option<u64>,
+
+    /// The block of spilled history that is being read.
+    history_read: Option<JoinHandle<std::io::Result<(u64, Bytes)>>>,
+
     future_rx: Option<Shared<oneshot::Receiver<()>>>,
     worker_handle: Arc<AbortOnDrop<()>>,
 }
@@ -263,6 +447,8 @@ impl Clone for StreamState {
     fn clone(&self) -> Self {
         Self {
             pos: 0,
+            history: None,
+            history_read: None,
             future_rx: None,
             worker_handle: self.worker_handle.clone(),
         }
@@ -435,6 +621,147 @@ mod tests {
         .await
     }
 
+    #[tokio::test]
+    async fn skip_to_end() {
+        enable_tracing_for_tests();
+        async {
+            let (tx, rx) = mpsc::unbounded_channel();
+            let stream = UnboundedReceiverStream::new(rx)
+                .map(|i: i32| Ok(Bytes::from(i.to_string().into_bytes())));
+            let mut tail_stream = TailStream::new(stream, 3);
+            let () = tx.send(1).unwrap();
+            let () = tx.send(2).unwrap();
+            tokio::time::sleep(TIMEOUT).await;
+
+            let mut follower = tail_stream.clone();
+            follower.skip_to_end();
+            let () = tx.send(3).unwrap();
+            assert_eq!(vec!["3"], follower.data(3).await);
+            assert_eq!(vec!["1", "2", "3"], tail_stream.data(3).await);
+        }
+        .instrument(info_span!("Test"))
+        .await
+    }
+
+    #[tokio::test]
+    async fn trim_at_line_boundary() {
+        enable_tracing_for_tests();
+        async {
+            let (tx, rx) = mpsc::unbounded_channel();
+            let stream = UnboundedReceiverStream::new(rx).map(|s: &str| Ok(Bytes::from(s)));
+            let mut tail_stream = TailStream::new(stream, 12);
+            for chunk in ["abc\nd", "ef\ngh", "i\x1b[3", "1mjk"] {
+                let () = tx.send(chunk).unwrap();
+            }
+            tokio::time::sleep(TIMEOUT).await;
+            assert_eq!(vec!["gh", "i\x1b[3", "1mjk"], tail_stream.data(10).await);
+        }
+        .instrument(info_span!("Test"))
+        .await
+    }
+
+    #[tokio::test]
+    async fn item_larger_than_scrollback() {
+        enable_tracing_for_tests();
+        async {
+            let tempdir = tempfile::tempdir().unwrap();
+            let path = tempdir.path().join("spill.log");
+            let (tx, rx) = mpsc::unbounded_channel();
+            let stream = UnboundedReceiverStream::new(rx).map(|s: &str| Ok(Bytes::from(s)));
+            let mut tail_stream = TailStream::new(stream, 4);
+            tail_stream.spill_to(&path, 1024).unwrap();
+            for chunk in ["ab", "0123456789"] {
+                let () = tx.send(chunk).unwrap();
+            }
+            tokio::time::sleep(TIMEOUT).await;
+            assert_eq!(vec!["6789"], tail_stream.data(10).await);
+
+            let () = tx.send("x").unwrap();
+            tokio::time::sleep(TIMEOUT).await;
+            assert_eq!(vec!["x"], tail_stream.data(10).await);
+            assert_eq!(
+                b"ab0123456789x".as_slice(),
+                tail_stream.snapshot(u64::MAX).concat()
+            );
+        }
+        .instrument(info_span!("Test"))
+        .await
+    }
+
+    #[tokio::test]
+    async fn spill() {
+        enable_tracing_for_tests();
+        async {
+            let tempdir = tempfile::tempdir().unwrap();
+            let path = tempdir.path().join("spill.log");
+            let (tx, rx) = mpsc::unbounded_channel();
+            let stream = UnboundedReceiverStream::new(rx)
+                .map(|i: i32| Ok(Bytes::from(format!("{i}\n").into_bytes())));
+            let mut tail_stream = TailStream::new(stream, 6);
+            tail_stream.spill_to(&path, 1024).unwrap();
+            for i in 1..10 {
+                let () = tx.send(i).unwrap();
+            }
+            tokio::time::sleep(TIMEOUT).await;
+            assert_eq!(vec!["7\n", "8\n", "9\n"], tail_stream.data(10).await);
+            assert_eq!(
+                b"1\n2\n3\n4\n5\n6\n7\n8\n9\n".as_slice(),
+                tail_stream.snapshot(u64::MAX).concat()
+            );
+            assert_eq!(
+                b"5\n6\n7\n8\n9\n".as_slice(),
+                tail_stream.snapshot(4).concat()
+            );
+
+            tail_stream.rewind_history();
+            assert_eq!(
+                vec!["1\n2\n3\n4\n5\n6\n", "7\n", "8\n", "9\n"],
+                tail_stream.data(10).await
+            );
+
+            drop(tail_stream);
+            assert!(!path.exists());
+        }
+        .instrument(info_span!("Test"))
+        .await
+    }
+
+    #[tokio::test]
+    async fn spill_rotation() {
+        enable_tracing_for_tests();
+        async {
+            let tempdir = tempfile::tempdir().unwrap();
+            let path = tempdir.path().join("spill.log");
+            let rotated_path = tempdir.path().join("spill.log.1");
+            let (tx, rx) = mpsc::unbounded_channel();
+            let stream = UnboundedReceiverStream::new(rx)
+                .map(|i: i32| Ok(Bytes::from(format!("{i}\n").into_bytes())));
+            let mut tail_stream = TailStream::new(stream, 6);
+            tail_stream.spill_to(&path, 8).unwrap();
+            for i in 1..10 {
+                let () = tx.send(i).unwrap();
+            }
+            tokio::time::sleep(TIMEOUT).await;
+            assert!(rotated_path.exists());
+            assert_eq!(
+                b"5\n6\n7\n8\n9\n".as_slice(),
+                tail_stream.snapshot(u64::MAX).concat()
+            );
+
+            tail_stream.rewind_history();
+            assert_eq!(
+                vec!["5\n6\n", "7\n", "8\n", "9\n"],
+                tail_stream.data(10).await
+            );
+
+            drop(tail_stream);
+            assert!(!path.exists());
+            assert!(!rotated_path.exists());
+        }
+        .instrument(info_span!("Test"))
+        .await
+    }
+
     impl TailStream {
         fn data(&mut self, n: usize) -> impl Future<Output = Vec<String>> {
             self.take(n)
@@ -0,0 +1,112 @@
+//! Finds where the output of a terminal can be cut without breaking a line,
+//! an escape sequence or a UTF-8 character.
+
+/// The state of the escape sequence parser at the end of the bytes scanned so far.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoundaryScanner {
+    state: State,
+    at_line_start: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    Csi,
+    /// OSC, DCS, APC, PM and SOS sequences, terminated by BEL or ST.
+    String,
+    StringEscape,
+}
+
+impl Default for BoundaryScanner {
+    fn default() -> Self {
+        Self {
+            state: State::Ground,
+            at_line_start: true,
+        }
+    }
+}
+
+impl BoundaryScanner {
+    /// Scans the next chunk and returns the first offset where it is safe to cut it.
+    ///
+    /// The start of a line is preferred. Otherwise any character boundary outside
+    /// an escape sequence will do. Returns `None` if the whole chunk is unsafe.
+    pub fn scan(&mut self, bytes: &[u8]) -> Option<usize> {
+        let mut line_start = self.at_line_start.then_some(0);
+        let mut ground = (self.state == State::Ground
+            && !bytes.first().copied().is_some_and(is_continuation))
+        .then_some(0);
+        for (i, &byte) in bytes.iter().enumerate() {
+            self.at_line_start = false;
+            self.state = match (self.state, byte) {
+                (State::Ground, 0x1b) => State::Escape,
+                (State::Ground, b'\n') => {
+                    self.at_line_start = true;
+                    State::Ground
+                }
+                (State::Ground, _) => State::Ground,
+                (State::Escape, b'[') => State::Csi,
+                (State::Escape, b']' | b'P' | b'X' | b'^' | b'_') => State::String,
+                (State::Escape, _) => State::Ground,
+                (State::Csi, 0x40..=0x7e) => State::Ground,
+                (State::Csi, _) => State::Csi,
+                (State::String, 0x07) => State::Ground,
+                (State::String, 0x1b) => State::StringEscape,
+                (State::String, _) => State::String,
+                (State::StringEscape, b'\\') => State::Ground,
+                (State::StringEscape, _) => State::String,
+            };
+            let next = i + 1;
+            if self.at_line_start && line_start.is_none() {
+                line_start = Some(next);
+            }
+            if self.state == State::Ground
+                && ground.is_none()
+                && bytes.get(next).is_some_and(|&byte| !is_continuation(byte))
+            {
+                ground = Some(next);
+            }
+        }
+        line_start.or(ground)
+    }
+}
+
+fn is_continuation(byte: u8) -> bool {
+    byte & 0b1100_0000 == 0b1000_0000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoundaryScanner;
+
+    #[test]
+    fn line_start() {
+        let mut scanner = BoundaryScanner::default();
+        assert_eq!(Some(0), scanner.scan(b"abc\ndef"));
+        assert_eq!(Some(4), scanner.scan(b"ghi\njkl"));
+        assert_eq!(Some(1), scanner.scan(b"\n"));
+        assert_eq!(Some(0), scanner.scan(b"mno"));
+    }
+
+    #[test]
+    fn escape_sequences() {
+        let mut scanner = BoundaryScanner::default();
+        assert_eq!(Some(0), scanner.scan(b"abc"));
+        assert_eq!(Some(0), scanner.scan(b"\x1b[3"));
+        assert_eq!(Some(4), scanner.scan(b"1;2mxyz"));
+        assert_eq!(Some(0), scanner.scan(b"\x1b]0;title"));
+        assert_eq!(None, scanner.scan(b"still title"));
+        assert_eq!(Some(2), scanner.scan(b"\x1b\\abc"));
+        assert_eq!(Some(0), scanner.scan(b"\x1b]2;title"));
+        assert_eq!(Some(3), scanner.scan(b"\x07a\nb"));
+    }
+
+    #[test]
+    fn utf8() {
+        let mut scanner = BoundaryScanner::default();
+        assert_eq!(Some(0), scanner.scan(b"abc"));
+        assert_eq!(Some(1), scanner.scan(&[0xa9, b'a']));
+        assert_eq!(Some(0), scanner.scan("\u{e9}".as_bytes()));
+    }
+}
@@ -0,0 +1,219 @@
+//! Keeps the history that no longer fits in the scrollback in a file.
+
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::ErrorKind;
+use std::os::unix::fs::FileExt as _;
+use std::os::unix::fs::OpenOptionsExt as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use bytes::Bytes;
+use tracing::debug;
+use tracing::warn;
+
+/// How much history is replayed at a time.
+const READ_SIZE: u64 = 64 * 1024;
+
+/// An append-only history of everything that was trimmed from the scrollback.
+///
+/// The history is kept in two files: the current segment, and the previous segment that was
+/// rotated to `<path>.1` when the current segment reached half the size limit.
+///
+/// Offsets are counted from the start of the stream, so they remain valid across rotations.
+/// The mutex is never held during I/O: appends are written by [Spill::flush] and reads happen
+/// on cloned file handles.
+///
+/// The files are deleted when the [TailStream](super::TailStream) is dropped.
+pub struct Spill {
+    path: PathBuf,
+    rotated_path: PathBuf,
+    limit: u64,
+    state: Mutex<SpillState>,
+}
+
+struct SpillState {
+    /// The previous segment, from `start` to `split`.
+    previous: Option<Arc<File>>,
+
+    /// The current segment, from `split` to `written`.
+    current: Arc<File>,
+
+    /// The offset of the oldest byte that is still retained.
+    start: u64,
+
+    /// The offset where the current segment starts.
+    split: u64,
+
+    /// The offset up to which the history was written to disk.
+    written: u64,
+
+    /// The history that was spilled but not written to disk yet, from `written` to `end`.
+    unwritten: VecDeque<Bytes>,
+
+    /// The offset after the newest spilled byte.
+    end: u64,
+}
+
+impl Spill {
+    /// Spills to `path`, and keeps at most `limit` bytes of history.
+    pub fn create(path: &Path, limit: u64) -> std::io::Result<Self> {
+        let rotated_path = {
+            let mut rotated_path = OsString::from(path);
+            rotated_path.push(".1");
+            PathBuf::from(rotated_path)
+        };
+        let current = open(path)?;
+        debug!(?path, limit, "Spilling history");
+        Ok(Self {
+            path: path.to_owned(),
+            rotated_path,
+            limit,
+            state: Mutex::new(SpillState {
+                previous: None,
+                current: current.into(),
+                start: 0,
+                split: 0,
+                written: 0,
+                unwritten: VecDeque::new(),
+                end: 0,
+            }),
+        })
+    }
+
+    /// The offset after the newest spilled byte.
+    pub fn len(&self) -> u64 {
+        self.state.lock().expect("spill").end
+    }
+
+    /// Whether some of the history still has to be written by [Spill::flush].
+    pub fn is_dirty(&self) -> bool {
+        !self.state.lock().expect("spill").unwritten.is_empty()
+    }
+
+    /// Adds to the history, without blocking on I/O.
+    pub fn push(&self, bytes: Bytes) {
+        if bytes.is_empty() {
+            return;
+        }
+        let mut state = self.state.lock().expect("spill");
+        state.end += bytes.len() as u64;
+        state.unwritten.push_back(bytes);
+    }
+
+    /// Writes the pending history to disk, and rotates the files when they are too large.
+    ///
+    /// Does blocking I/O, and must only be called by one writer at a time.
+    pub fn flush(&self) {
+        let (current, offset, chunks) = {
+            let state = self.state.lock().expect("spill");
+            let chunks = state.unwritten.iter().cloned().collect::<Vec<_>>();
+            (state.current.clone(), state.written - state.split, chunks)
+        };
+
+        let mut written = 0;
+        let mut failed = false;
+        for chunk in &chunks {
+            if let Err(error) = current.write_all_at(chunk, offset + written) {
+                warn!(path = ?self.path, "Failed to spill history: {error}");
+                failed = true;
+                break;
+            }
+            written += chunk.len() as u64;
+        }
+
+        let rotate = {
+            let mut state = self.state.lock().expect("spill");
+            state.unwritten.drain(..chunks.len());
+            if failed {
+                // The history can't have holes: drop what was written so far.
+                state.previous = None;
+                state.start = state.end;
+                state.split = state.end;
+                state.written = state.end;
+                return;
+            }
+            state.written += written;
+            state.written - state.split >= (self.limit / 2).max(1)
+        };
+        if rotate {
+            self.rotate();
+        }
+    }
+
+    /// Moves the current segment to `<path>.1`, discarding the previous segment.
+    fn rotate(&self) {
+        let current =
+            std::fs::rename(&self.path, &self.rotated_path).and_then(|()| open(&self.path));
+        let current = match current {
+            Ok(current) => current,
+            Err(error) => return warn!(path = ?self.path, "Failed to rotate history: {error}"),
+        };
+        debug!(path = ?self.path, "Rotated history");
+        let mut state = self.state.lock().expect("spill");
+        state.previous = Some(std::mem::replace(&mut state.current, current.into()));
+        state.start = state.split;
+        state.split = state.written;
+    }
+
+    /// Reads the next block of history starting at `offset`.
+    ///
+    /// Returns the offset after the block: the history before `offset` might have been
+    /// rotated out already, in which case the oldest retained history is returned.
+    pub fn read(&self, offset: u64) -> std::io::Result<(u64, Bytes)> {
+        let (file, position, offset, len) = {
+            let state = self.state.lock().expect("spill");
+            let offset = offset.max(state.start);
+            if offset >= state.written {
+                let mut chunk_start = state.written;
+                for chunk in &state.unwritten {
+                    let chunk_end = chunk_start + chunk.len() as u64;
+                    if offset < chunk_end {
+                        let bytes = chunk.slice((offset - chunk_start) as usize..);
+                        return Ok((chunk_end, bytes));
+                    }
+                    chunk_start = chunk_end;
+                }
+                return Ok((offset, Bytes::new()));
+            }
+            let (file, base, end) = match &state.previous {
+                Some(previous) if offset < state.split => (previous, state.start, state.split),
+                _ => (&state.current, state.split, state.written),
+            };
+            let len = READ_SIZE.min(end - offset);
+            (file.clone(), offset - base, offset, len)
+        };
+        let mut buffer = vec![0; len as usize];
+        file.read_exact_at(&mut buffer, position)?;
+        Ok((offset + len, buffer.into()))
+    }
+}
+
+fn open(path: &Path) -> std::io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        if let Err(error) = std::fs::remove_file(&self.path) {
+            warn!(path = ?self.path, "Failed to delete spilled history: {error}");
+        }
+        match std::fs::remove_file(&self.rotated_path) {
+            Ok(()) => {}
+            Err(error) if error.kind() == ErrorKind::NotFound => {}
+            Err(error) => {
+                warn!(path = ?self.rotated_path, "Failed to delete spilled history: {error}")
+            }
+        }
+    }
+}
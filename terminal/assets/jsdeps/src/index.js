@@ -22,9 +22,9 @@ import '@milkdown/crepe/theme/common/diff.css';
 import '@milkdown/crepe/theme/frame-dark.css';
 
 import { basicSetup } from "codemirror";
-import { EditorState } from '@codemirror/state';
-import { EditorView, tooltips } from "@codemirror/view";
-import { MergeView } from "@codemirror/merge";
+import { EditorState, Prec, StateEffect, StateField } from '@codemirror/state';
+import { Decoration, EditorView, WidgetType, hoverTooltip, keymap, tooltips } from "@codemirror/view";
+import { MergeView, unifiedMergeView } from "@codemirror/merge";
 import { search } from "@codemirror/search";
 
 import { oneDark } from '@codemirror/theme-one-dark';
@@ -76,11 +76,19 @@ export {
     CodeMirrorJsImpl,
 
     basicSetup,
+    Decoration,
     EditorState,
     EditorView,
     MergeView,
+    Prec,
+    StateEffect,
+    StateField,
+    WidgetType,
+    hoverTooltip,
+    keymap,
     search,
     tooltips,
+    unifiedMergeView,
 
     oneDark,
 
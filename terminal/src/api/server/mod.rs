@@ -24,6 +24,9 @@ pub fn api_routes(
         .merge(login_routes(config, auth_config))
         .merge(remotes_routes(config, auth_config, server));
 
+    #[cfg(feature = "terminal")]
+    let router = router.merge(crate::terminal::share::share_routes(auth_config));
+
     #[cfg(feature = "text-editor")]
     let router = router.merge(crate::text_editor::fsio::api::fsio_routes(
         auth_config,
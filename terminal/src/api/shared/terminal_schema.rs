@@ -21,7 +21,7 @@ pub struct Size {
     pub cols: i32,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct TerminalAddress {
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "t"))]
     pub id: TerminalId,
@@ -49,6 +49,11 @@ pub struct TerminalDefImpl<T> {
     pub order: i32,
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "i"))]
     pub tile: TileId,
+
+    /// The launch profile of the terminal, if not the default one.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "p"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -77,6 +82,61 @@ pub enum RegisterTerminalMode {
     Create,
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "R"))]
     Reopen,
+
+    /// Reopens the terminal and replays its whole history, including what was spilled to disk.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "H"))]
+    History,
+
+    /// Follows the output alongside the client that holds the lease, without revoking it.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "F"))]
+    Follow,
+}
+
+/// How the shell of a terminal terminated.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ExitStatus {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "C"))]
+    Code(i32),
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "S"))]
+    Signal(i32),
+}
+
+mod display_exit_status {
+    use std::fmt::Display;
+
+    impl Display for super::ExitStatus {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Self::Code(code) => write!(f, "code {code}"),
+                Self::Signal(signal) => write!(f, "signal {signal}"),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+mod pty_exit_status {
+    use terrazzo_pty::exit_status::ExitStatus as PtyExitStatus;
+
+    use super::ExitStatus;
+
+    impl From<PtyExitStatus> for ExitStatus {
+        fn from(exit_status: PtyExitStatus) -> Self {
+            match exit_status {
+                PtyExitStatus::Code(code) => Self::Code(code),
+                PtyExitStatus::Signal(signal) => Self::Signal(signal),
+            }
+        }
+    }
+
+    impl From<ExitStatus> for PtyExitStatus {
+        fn from(exit_status: ExitStatus) -> Self {
+            match exit_status {
+                ExitStatus::Code(code) => Self::Code(code),
+                ExitStatus::Signal(signal) => Self::Signal(signal),
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -87,6 +147,11 @@ pub struct ResizeRequest<T = TerminalAddress> {
     pub size: Size,
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "f"))]
     pub force: bool,
+
+    /// The reader of the terminal stream that asks for the new size.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "r"))]
+    #[serde(default)]
+    pub reader: Option<usize>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -96,3 +161,81 @@ pub struct SetTitleRequest<T = TerminalAddress> {
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "v"))]
     pub title: TabTitle<String>,
 }
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StartRecordingRequest {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "t"))]
+    pub terminal: TerminalAddress,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "s"))]
+    pub size: Size,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "n"))]
+    pub title: Option<String>,
+}
+
+/// A terminal recording in asciicast v2 format.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RecordingInfo {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "n"))]
+    pub name: String,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "t"))]
+    pub title: Option<String>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "s"))]
+    pub timestamp: Option<i64>,
+}
+
+/// A line of scrollback that matches a search.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScrollbackMatch {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "t"))]
+    pub terminal: TerminalAddress,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "n"))]
+    pub title: String,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "i"))]
+    pub line_number: usize,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "b"))]
+    pub before: Vec<String>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "l"))]
+    pub line: String,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "a"))]
+    pub after: Vec<String>,
+}
+
+/// What the shell reported through OSC 7 and OSC 133.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShellState {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "d"))]
+    pub cwd: Option<String>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "c"))]
+    pub commands: Vec<ShellCommand>,
+}
+
+/// A command that ran in the shell.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShellCommand {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "c"))]
+    pub command: String,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "d"))]
+    pub cwd: Option<String>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "x"))]
+    pub exit_code: Option<i32>,
+}
+
+/// What the holder of a share link can do with the terminal.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ShareAccess {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "V"))]
+    View,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "I"))]
+    Input,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShareRequest {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "t"))]
+    pub terminal: TerminalAddress,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "a"))]
+    pub access: ShareAccess,
+    /// How long the link is valid, in seconds.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "l"))]
+    pub lifetime: u64,
+}
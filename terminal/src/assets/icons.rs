@@ -32,6 +32,7 @@ pub type Icon = &'static str;
 
 declare_icon!(add_port_forward,"/icons/add-port-forward.svg"; feature = "port-forward");
 declare_icon!(add_tab, "/icons/plus-square.svg");
+declare_icon!(broadcast, "/icons/broadcast.svg"; feature = "terminal");
 declare_icon!(chevron_bar_down, "/icons/chevron-bar-down.svg"; feature = "logs-panel");
 declare_icon!(chevron_bar_up, "/icons/chevron-bar-up.svg"; feature = "logs-panel");
 declare_icon!(chevron_double_right, "/icons/chevron-double-right.svg"; feature = "text-editor");
@@ -47,7 +48,10 @@ declare_icon!(download, "/icons/download.svg"; feature = "text-editor");
 declare_icon!(drag_handle_corner, "/icons/drag-handle-corner.svg");
 declare_icon!(file, "/icons/file-earmark-text.svg"; feature = "text-editor");
 declare_icon!(folder, "/icons/folder2-open.svg"; feature = "text-editor");
+declare_icon!(git, "/icons/git.svg"; feature = "text-editor");
+declare_icon!(history, "/icons/clock-history.svg"; feature = "terminal");
 declare_icon!(hub, "/icons/hub.svg"; feature = "port-forward");
+declare_icon!(inline_diff, "/icons/layout-text-window.svg"; feature = "text-editor");
 declare_icon!(key_icon, "/icons/key.svg");
 declare_icon!(loading, "/icons/loading2.svg"; feature = "text-editor");
 declare_icon!(menu, "/icons/signpost-split.svg");
@@ -56,16 +60,29 @@ declare_icon!(mic_mute_fill, "/icons/mic-mute-fill.svg"; any(feature = "terminal
 declare_icon!(new_file, "/icons/file-earmark-plus.svg"; feature = "text-editor");
 declare_icon!(new_folder, "/icons/folder-plus.svg"; feature = "text-editor");
 declare_icon!(paragraph, "/icons/paragraph.svg"; any(feature = "terminal", feature = "text-editor"));
+declare_icon!(pause, "/icons/pause-fill.svg"; feature = "terminal");
+declare_icon!(play, "/icons/play-fill.svg"; feature = "terminal");
+declare_icon!(problems, "/icons/exclamation-triangle.svg"; feature = "text-editor");
+declare_icon!(properties, "/icons/info-circle.svg"; feature = "text-editor");
 declare_icon!(port_forward_loading,"/icons/port-forward-loading.svg"; feature = "port-forward");
 declare_icon!(port_forward_pending,"/icons/port-forward-pending.svg"; feature = "port-forward");
 declare_icon!(port_forward_synchronized,"/icons/port-forward-synchronized.svg"; feature = "port-forward");
+declare_icon!(record, "/icons/record-circle.svg"; feature = "terminal");
 declare_icon!(refresh, "/icons/arrow-counterclockwise.svg"; feature = "text-editor");
-declare_icon!(search, "/icons/search.svg"; feature = "text-editor");
+declare_icon!(replace, "/icons/arrow-left-right.svg"; feature = "text-editor");
+declare_icon!(replay, "/icons/film.svg"; feature = "terminal");
+declare_icon!(search, "/icons/search.svg"; any(feature = "terminal", feature = "text-editor"));
 declare_icon!(send_fill, "/icons/send-fill.svg"; any(feature = "terminal", feature = "text-editor"));
+declare_icon!(share, "/icons/share.svg"; feature = "terminal");
 declare_icon!(slash, "/icons/slash.svg"; feature = "text-editor");
 declare_icon!(split_horz, "/icons/arrows-expand-vertical.svg");
 declare_icon!(split_vert, "/icons/arrows-expand.svg");
+declare_icon!(stop_recording, "/icons/stop-circle-fill.svg"; feature = "terminal");
+declare_icon!(symlink, "/icons/box-arrow-up-right.svg"; feature = "text-editor");
+declare_icon!(tail, "/icons/activity.svg"; feature = "text-editor");
+declare_icon!(tasks, "/icons/hammer.svg"; feature = "text-editor");
 declare_icon!(terminal, "/icons/terminal-dash.svg"; feature = "terminal");
 declare_icon!(text_editor, "/icons/layout-text-sidebar-reverse.svg"; feature = "text-editor");
 declare_icon!(trash, "/icons/trash3.svg"; any(feature = "port-forward", feature = "text-editor"));
+declare_icon!(trash_bin, "/icons/trash2.svg"; feature = "text-editor");
 declare_icon!(window_stack, "/icons/window-stack.svg");
@@ -37,11 +37,22 @@ fn install_icons() {
     install_icon(super::icons::add_tab());
 
     #[cfg(feature = "terminal")]
-    install_icon(super::icons::terminal());
+    {
+        install_icon(super::icons::broadcast());
+        install_icon(super::icons::history());
+        install_icon(super::icons::pause());
+        install_icon(super::icons::play());
+        install_icon(super::icons::record());
+        install_icon(super::icons::replay());
+        install_icon(super::icons::share());
+        install_icon(super::icons::stop_recording());
+        install_icon(super::icons::terminal());
+    }
 
     #[cfg(any(feature = "terminal", feature = "text-editor"))]
     {
         install_icon(super::icons::paragraph());
+        install_icon(super::icons::search());
         install_icon(super::icons::mic_fill());
         install_icon(super::icons::mic_mute_fill());
         install_icon(super::icons::send_fill());
@@ -55,13 +66,21 @@ fn install_icons() {
         install_icon(super::icons::download());
         install_icon(super::icons::file());
         install_icon(super::icons::folder());
+        install_icon(super::icons::git());
+        install_icon(super::icons::inline_diff());
         install_icon(super::icons::loading());
         install_icon(super::icons::new_file());
         install_icon(super::icons::new_folder());
+        install_icon(super::icons::problems());
+        install_icon(super::icons::properties());
         install_icon(super::icons::refresh());
-        install_icon(super::icons::search());
+        install_icon(super::icons::replace());
         install_icon(super::icons::slash());
+        install_icon(super::icons::symlink());
+        install_icon(super::icons::tail());
+        install_icon(super::icons::tasks());
         install_icon(super::icons::text_editor());
+        install_icon(super::icons::trash_bin());
     }
 
     #[cfg(feature = "converter")]
@@ -96,3 +96,59 @@ fn refresh_auth_token(
     let cookies = CookieJar::from_headers(response.headers()).add(token);
     return (cookies, response).into_response();
 }
+
+/// Accepts the tokens of share links and exposes their [ShareClaims](crate::backend::auth::share::ShareClaims) as a request extension.
+#[cfg(feature = "terminal")]
+#[derive(Clone)]
+pub struct ShareLayer {
+    pub auth_config: DiffArc<DynamicConfig<DiffArc<AuthConfig>, mode::RO>>,
+}
+
+#[cfg(feature = "terminal")]
+impl<S> Layer<S> for ShareLayer {
+    type Service = ShareService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ShareService {
+            layer: self.clone(),
+            inner,
+        }
+    }
+}
+
+#[cfg(feature = "terminal")]
+#[derive(Clone)]
+pub struct ShareService<S> {
+    layer: ShareLayer,
+    inner: S,
+}
+
+#[cfg(feature = "terminal")]
+impl<S> Service<Request<Body>> for ShareService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        let mut inner = self.inner.clone();
+        let auth_config = self.layer.auth_config.clone();
+        Box::pin(async move {
+            let token_data = match auth_config
+                .with(|auth_config| auth_config.validate_share(request.headers()))
+            {
+                Ok(token_data) => token_data,
+                Err(error) => return Ok(error.into_response()),
+            };
+            request.extensions_mut().insert(token_data.claims);
+            inner.call(request).await
+        })
+    }
+}
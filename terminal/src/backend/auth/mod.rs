@@ -20,6 +20,7 @@ use super::config::server::ServerConfig;
 
 mod jwt_timestamp;
 pub mod layer;
+pub mod share;
 mod tests;
 
 static TOKEN_COOKIE_NAME: &str = "slt";
@@ -44,6 +45,8 @@ pub struct AuthConfig {
     validation: Validation,
     token_lifetime: Duration,
     token_refresh: Duration,
+    #[cfg(feature = "terminal")]
+    share_keys: share::ShareKeys,
 }
 
 #[derive(Debug, serde::Serialize, serde:: Deserialize)]
@@ -76,6 +79,8 @@ impl AuthConfig {
             validation,
             token_lifetime: DEFAULT_TOKEN_LIFETIME,
             token_refresh: DEFAULT_TOKEN_REFRESH,
+            #[cfg(feature = "terminal")]
+            share_keys: share::ShareKeys::from_secret(secret),
         }
     }
 
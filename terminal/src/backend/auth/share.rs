@@ -0,0 +1,75 @@
+#![cfg(feature = "terminal")]
+
+//! Tokens that give access to a single terminal.
+
+use std::time::Duration;
+use std::time::SystemTime;
+
+use jsonwebtoken::DecodingKey;
+use jsonwebtoken::EncodingKey;
+use jsonwebtoken::Header;
+use jsonwebtoken::TokenData;
+use terrazzo::http::HeaderMap;
+use terrazzo::http::StatusCode;
+
+use super::AuthConfig;
+use super::extract_token;
+use super::jwt_timestamp::Timestamp;
+use crate::api::shared::terminal_schema::ShareAccess;
+use crate::api::shared::terminal_schema::TerminalAddress;
+
+/// Share links can't be valid for longer than a week.
+pub static MAX_SHARE_LIFETIME: Duration = Duration::from_secs(3600 * 24 * 7);
+
+/// Share tokens are signed with a different key than session tokens,
+/// so that one can never be used in place of the other.
+pub(super) struct ShareKeys {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+}
+
+impl ShareKeys {
+    pub(super) fn from_secret(secret: &[u8]) -> Self {
+        let secret = [secret, b"/share"].concat();
+        Self {
+            encoding_key: EncodingKey::from_secret(&secret),
+            decoding_key: DecodingKey::from_secret(&secret),
+        }
+    }
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct ShareClaims {
+    exp: Timestamp,
+    nbf: Timestamp,
+    pub terminal: TerminalAddress,
+    pub access: ShareAccess,
+}
+
+impl AuthConfig {
+    pub fn make_share_token(
+        &self,
+        terminal: TerminalAddress,
+        access: ShareAccess,
+        lifetime: Duration,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
+        let now = SystemTime::now();
+        let claims = ShareClaims {
+            exp: (now + lifetime.min(MAX_SHARE_LIFETIME)).into(),
+            nbf: (now - Duration::from_secs(60)).into(),
+            terminal,
+            access,
+        };
+        jsonwebtoken::encode(&Header::default(), &claims, &self.share_keys.encoding_key)
+    }
+
+    pub fn validate_share(
+        &self,
+        headers: &HeaderMap,
+    ) -> Result<TokenData<ShareClaims>, (StatusCode, String)> {
+        let token = extract_token(headers)?;
+        let validation =
+            jsonwebtoken::decode(&token, &self.share_keys.decoding_key, &self.validation);
+        validation.map_err(|error| (StatusCode::UNAUTHORIZED, format!("{error}")))
+    }
+}
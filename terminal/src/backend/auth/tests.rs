@@ -145,6 +145,91 @@ async fn bad_signature_token() {
     assert_eq!("InvalidSignature", get_body(response).await.unwrap());
 }
 
+#[cfg(feature = "terminal")]
+#[tokio::test]
+async fn share_token() {
+    use crate::api::shared::terminal_schema::ShareAccess;
+    use crate::api::shared::terminal_schema::TerminalAddress;
+
+    let auth_config = AuthConfig::random();
+    let terminal = TerminalAddress {
+        id: "T1".into(),
+        via: Default::default(),
+    };
+    let token = auth_config
+        .make_share_token(terminal.clone(), ShareAccess::View, Duration::from_secs(60))
+        .unwrap();
+
+    let request = make_request(|b| b.header(AUTHORIZATION, format!("Bearer {token}")));
+    let token_data = auth_config.validate_share(request.headers()).unwrap();
+    assert_eq!(terminal, token_data.claims.terminal);
+    assert_eq!(ShareAccess::View, token_data.claims.access);
+
+    let response = auth_config
+        .validate(request.headers())
+        .unwrap_err()
+        .into_response();
+    assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+    assert_eq!("InvalidSignature", get_body(response).await.unwrap());
+}
+
+#[cfg(feature = "terminal")]
+#[tokio::test]
+async fn session_token_is_not_a_share_token() {
+    let auth_config = AuthConfig::random();
+    let token = auth_config.make_token().unwrap();
+
+    let token = token.value();
+    let request = make_request(|b| b.header(AUTHORIZATION, format!("Bearer {token}")));
+    let response = auth_config
+        .validate_share(request.headers())
+        .unwrap_err()
+        .into_response();
+    assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+    assert_eq!("InvalidSignature", get_body(response).await.unwrap());
+}
+
+#[cfg(feature = "terminal")]
+#[tokio::test]
+async fn shared_stream_ends_when_closed() {
+    use futures::StreamExt as _;
+    use terrazzo_pty::ProcessIO;
+    use terrazzo_pty::launch::Launch;
+    use terrazzo_pty::lease::ProcessIoEntry;
+
+    use crate::api::shared::terminal_schema::TabTitle;
+    use crate::api::shared::terminal_schema::TerminalAddress;
+    use crate::api::shared::terminal_schema::TerminalDef;
+    use crate::processes;
+    use crate::tiles::id::TileId;
+
+    let terminal_def = TerminalDef {
+        address: TerminalAddress {
+            id: "T-shared-stream".into(),
+            via: Default::default(),
+        },
+        title: TabTitle {
+            shell_title: "Shared".into(),
+            override_title: None,
+        },
+        order: 1,
+        tile: TileId::for_test(1),
+        profile: None,
+    };
+    let terminal_id = terminal_def.address.id.clone();
+    let process_io = ProcessIO::open(Option::<String>::None, 1000, &Launch::bash("sleep 30"))
+        .await
+        .unwrap();
+    let entry = ProcessIoEntry::new(process_io);
+    processes::get_processes().insert(terminal_id.clone(), (terminal_def, entry));
+
+    let stream = processes::watch::watch(&terminal_id).unwrap();
+    processes::close::close(&terminal_id).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), stream.count())
+        .await
+        .expect("The shared stream should end when the terminal is closed");
+}
+
 fn make_request(f: impl FnOnce(http::request::Builder) -> http::request::Builder) -> Request<Body> {
     f(Request::builder()
         .method("GET")
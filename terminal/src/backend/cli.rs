@@ -34,6 +34,22 @@ pub struct Cli {
     #[arg(long)]
     pub terminal_shell: Option<String>,
 
+    /// The Unix socket of the session holder that keeps the shells alive across restarts.
+    #[arg(long)]
+    pub session_holder: Option<PathBuf>,
+
+    /// The folder where the terminal output that no longer fits in the scrollback is kept.
+    #[arg(long)]
+    pub scrollback_spill: Option<PathBuf>,
+
+    /// How many bytes of spilled output to keep per terminal.
+    #[arg(long)]
+    pub scrollback_spill_limit: Option<u64>,
+
+    /// The folder where terminal recordings are stored.
+    #[arg(long)]
+    pub recordings: Option<PathBuf>,
+
     /// The folder where deleted text-editor files are moved.
     #[arg(long)]
     pub trash: Option<PathBuf>,
@@ -104,4 +120,9 @@ pub enum Action {
     /// Lists all installed asset source paths
     #[cfg(feature = "debug")]
     ListAssets,
+
+    /// Runs the session holder that owns the shells
+    #[cfg(feature = "terminal")]
+    #[value(hide = true)]
+    SessionHolder,
 }
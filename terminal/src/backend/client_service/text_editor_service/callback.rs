@@ -16,8 +16,10 @@ use tracing::Instrument;
 use tracing::debug;
 use tracing::debug_span;
 
+use super::DownloadOptions;
 use super::DownloadStream;
 use super::TextEditorFsioError;
+use super::UploadOptions;
 use super::download_local;
 use super::upload_local;
 use crate::backend::Server;
@@ -25,6 +27,7 @@ use crate::backend::client_service::routing::DistributedCallback;
 use crate::backend::client_service::routing::DistributedCallbackError;
 use crate::backend::protos::terrazzo::shared::ClientAddress as ClientAddressProto;
 use crate::backend::protos::terrazzo::texteditor::DownloadRequest;
+use crate::backend::protos::terrazzo::texteditor::OnConflict as OnConflictProto;
 use crate::backend::protos::terrazzo::texteditor::UploadRequest;
 use crate::backend::protos::terrazzo::texteditor::text_editor_service_client::TextEditorServiceClient;
 use crate::backend::protos::terrazzo::texteditor::upload_request;
@@ -37,31 +40,32 @@ impl DownloadCallback {
         server: &Arc<Server>,
         client_address: &[impl AsRef<str>],
         path: FilePath<std::path::PathBuf>,
+        options: DownloadOptions,
     ) -> Result<DownloadStream, TextEditorFsioError> {
-        Self::process(server, client_address, path)
+        Self::process(server, client_address, (path, options))
             .await
             .map_err(map_distributed_error)
     }
 }
 
 impl DistributedCallback for DownloadCallback {
-    type Request = FilePath<std::path::PathBuf>;
+    type Request = (FilePath<std::path::PathBuf>, DownloadOptions);
     type Response = DownloadStream;
     type LocalError = TextEditorFsioError;
     type RemoteError = tonic::Status;
 
     async fn local(
         _server: Option<&Arc<Server>>,
-        path: Self::Request,
+        (path, options): Self::Request,
     ) -> Result<Self::Response, Self::LocalError> {
-        debug!("Downloading file {path:?}");
-        download_local(path).await
+        debug!("Downloading file {path:?} {options:?}");
+        download_local(path, options).await
     }
 
     async fn remote<T>(
         channel: T,
         client_address: &[impl AsRef<str>],
-        path: Self::Request,
+        (path, options): Self::Request,
     ) -> Result<Self::Response, Self::RemoteError>
     where
         T: GrpcService<BoxBody>,
@@ -70,11 +74,11 @@ impl DistributedCallback for DownloadCallback {
         <T::ResponseBody as Body>::Error: Into<StdError> + Send,
     {
         let address = ClientAddressProto::of(client_address);
-        let span = debug_span!("Downloading file", ?path, ?address);
+        let span = debug_span!("Downloading file", ?path, ?options, ?address);
         async move {
             debug!("Start");
             defer!(debug!("Done"));
-            remote_download_impl(channel, address, path).await
+            remote_download_impl(channel, address, path, options).await
         }
         .instrument(span)
         .await
@@ -85,6 +89,7 @@ async fn remote_download_impl<T>(
     channel: T,
     address: ClientAddressProto,
     path: FilePath<std::path::PathBuf>,
+    options: DownloadOptions,
 ) -> Result<DownloadStream, tonic::Status>
 where
     T: GrpcService<BoxBody>,
@@ -97,6 +102,9 @@ where
         .download(DownloadRequest {
             address: Some(address),
             path: Some(path.into()),
+            offset: options.offset,
+            length: options.length,
+            archive: options.archive,
         })
         .await?
         .into_inner()
@@ -115,9 +123,10 @@ where
         server: &Arc<Server>,
         client_address: &[impl AsRef<str>],
         path: FilePath<std::path::PathBuf>,
+        options: UploadOptions,
         content: S,
     ) -> Result<(), TextEditorFsioError> {
-        Self::process(server, client_address, (path, content))
+        Self::process(server, client_address, (path, options, content))
             .await
             .map_err(map_distributed_error)
     }
@@ -127,23 +136,23 @@ impl<S> DistributedCallback for UploadCallback<S>
 where
     S: Stream<Item = Result<Bytes, TextEditorFsioError>> + Send + 'static,
 {
-    type Request = (FilePath<std::path::PathBuf>, S);
+    type Request = (FilePath<std::path::PathBuf>, UploadOptions, S);
     type Response = ();
     type LocalError = TextEditorFsioError;
     type RemoteError = tonic::Status;
 
     async fn local(
         _server: Option<&Arc<Server>>,
-        (path, content): Self::Request,
+        (path, options, content): Self::Request,
     ) -> Result<Self::Response, Self::LocalError> {
-        debug!("Uploading file {path:?}");
-        upload_local(path, content).await
+        debug!("Uploading file {path:?} {options:?}");
+        upload_local(path, options, content).await
     }
 
     async fn remote<T>(
         channel: T,
         client_address: &[impl AsRef<str>],
-        (path, content): Self::Request,
+        (path, options, content): Self::Request,
     ) -> Result<Self::Response, Self::RemoteError>
     where
         T: GrpcService<BoxBody>,
@@ -152,11 +161,11 @@ where
         <T::ResponseBody as Body>::Error: Into<StdError> + Send,
     {
         let address = ClientAddressProto::of(client_address);
-        let span = debug_span!("Downlaoding file", ?path, ?address);
+        let span = debug_span!("Uploading file", ?path, ?options, ?address);
         async move {
             debug!("Start");
             defer!(debug!("Done"));
-            remote_upload_impl(channel, address, (path, content)).await
+            remote_upload_impl(channel, address, (path, options, content)).await
         }
         .instrument(span)
         .await
@@ -166,7 +175,7 @@ where
 async fn remote_upload_impl<S, T>(
     channel: T,
     address: ClientAddressProto,
-    (path, content): (FilePath<std::path::PathBuf>, S),
+    (path, options, content): (FilePath<std::path::PathBuf>, UploadOptions, S),
 ) -> Result<(), tonic::Status>
 where
     S: Stream<Item = Result<Bytes, TextEditorFsioError>> + Send + 'static,
@@ -177,12 +186,17 @@ where
 {
     let first = UploadRequest {
         kind: Some(upload_request::Kind::Address(address)),
+        ..UploadRequest::default()
     };
     let second = UploadRequest {
         kind: Some(upload_request::Kind::Path(path.into())),
+        offset: options.offset,
+        extract: options.extract.is_some(),
+        on_conflict: OnConflictProto::from(options.extract.unwrap_or_default()).into(),
     };
     let content = content.map_ok(|data| UploadRequest {
         kind: Some(upload_request::Kind::Data(data)),
+        ..UploadRequest::default()
     });
     let content = stream::iter([Ok(first), Ok(second)])
         .chain(content)
@@ -6,12 +6,15 @@ use tonic::Streaming;
 use tonic::async_trait;
 
 use crate::backend::client_service::ClientServiceImpl;
+use crate::backend::client_service::text_editor_service::DownloadOptions;
 use crate::backend::client_service::text_editor_service::TextEditorFsioError;
+use crate::backend::client_service::text_editor_service::UploadOptions;
 use crate::backend::client_service::text_editor_service::download;
 use crate::backend::client_service::text_editor_service::upload;
 use crate::backend::protos::terrazzo::shared::Empty;
 use crate::backend::protos::terrazzo::texteditor::DownloadRequest;
 use crate::backend::protos::terrazzo::texteditor::DownloadResponse;
+use crate::backend::protos::terrazzo::texteditor::OnConflict;
 use crate::backend::protos::terrazzo::texteditor::UploadRequest;
 use crate::backend::protos::terrazzo::texteditor::text_editor_service_server::TextEditorService;
 use crate::backend::protos::terrazzo::texteditor::upload_request;
@@ -25,10 +28,21 @@ impl TextEditorService for ClientServiceImpl {
         &self,
         request: Request<DownloadRequest>,
     ) -> Result<Response<Self::DownloadStream>, tonic::Status> {
-        let DownloadRequest { address, path } = request.into_inner();
+        let DownloadRequest {
+            address,
+            path,
+            offset,
+            length,
+            archive,
+        } = request.into_inner();
         let address = address.ok_or(TextEditorFsioError::MissingAddress)?;
         let path = path.ok_or(TextEditorFsioError::MissingPath)?;
-        let stream = download(&self.server, &address.via, path.into()).await?;
+        let options = DownloadOptions {
+            offset,
+            length,
+            archive,
+        };
+        let stream = download(&self.server, &address.via, path.into(), options).await?;
         let stream = stream
             .inspect_err(crate::backend::client_service::text_editor_service::warn_stream_error)
             .map_ok(|data| DownloadResponse { data })
@@ -58,14 +72,20 @@ impl TextEditorService for ClientServiceImpl {
             Some(upload_request::Kind::Path(path)) => path.into(),
             _ => return Err(TextEditorFsioError::MissingPath.into()),
         };
+        let on_conflict = OnConflict::try_from(second.on_conflict).unwrap_or_default();
+        let options = UploadOptions {
+            offset: second.offset,
+            extract: second.extract.then(|| on_conflict.into()),
+        };
         let content = request.map(|request| match request {
             Ok(UploadRequest {
                 kind: Some(upload_request::Kind::Data(data)),
+                ..
             }) => Ok(data),
             Ok(_) => Err(TextEditorFsioError::UnexpectedUploadMessage),
             Err(error) => Err(error.into()),
         });
-        upload(&self.server, &address.via, path, content).await?;
+        upload(&self.server, &address.via, path, options, content).await?;
         Ok(Response::new(Empty {}))
     }
 }
@@ -1,6 +1,8 @@
 mod callback;
 mod grpc;
+mod tar;
 
+use std::io::SeekFrom;
 use std::path::Path;
 use std::path::PathBuf;
 use std::pin::Pin;
@@ -14,6 +16,7 @@ use nameth::nameth;
 use prost::bytes::Bytes;
 use terrazzo::http::StatusCode;
 use tokio::io::AsyncReadExt as _;
+use tokio::io::AsyncSeekExt as _;
 use tokio::io::AsyncWriteExt as _;
 use tonic::Code;
 use tracing::warn;
@@ -23,39 +26,75 @@ use trz_gateway_common::id::ClientName;
 use crate::backend::Server;
 use crate::backend::client_service::grpc_error::IsGrpcError;
 use crate::backend::protos::terrazzo::texteditor::FilePath as FilePathProto;
+use crate::backend::protos::terrazzo::texteditor::OnConflict as OnConflictProto;
 use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio::OnConflict;
 
 pub(super) const DOWNLOAD_CHUNK_SIZE: usize = 64 * 1024;
 
 pub type DownloadStream =
     Pin<Box<dyn Stream<Item = Result<Bytes, TextEditorFsioError>> + Send + 'static>>;
 
+/// The bytes to download, the whole file by default.
+#[derive(Clone, Copy, Debug, Default, serde::Deserialize)]
+pub struct DownloadOptions {
+    #[serde(default)]
+    pub offset: u64,
+    pub length: Option<u64>,
+
+    /// Downloads a folder, or a file, as a tar archive.
+    #[serde(default)]
+    pub archive: bool,
+}
+
+/// Where the uploaded bytes are written: a non-zero offset resumes an upload.
+#[derive(Clone, Copy, Debug, Default, serde::Deserialize)]
+pub struct UploadOptions {
+    #[serde(default)]
+    pub offset: u64,
+
+    /// Extracts the uploaded tar archive into the folder.
+    #[serde(default)]
+    pub extract: Option<OnConflict>,
+}
+
 pub async fn download(
     server: &Arc<Server>,
     client_address: &[impl AsRef<str>],
     path: FilePath<PathBuf>,
+    options: DownloadOptions,
 ) -> Result<DownloadStream, TextEditorFsioError> {
-    callback::DownloadCallback::download(server, client_address, path).await
+    callback::DownloadCallback::download(server, client_address, path, options).await
 }
 
 pub async fn upload<S>(
     server: &Arc<Server>,
     client_address: &[impl AsRef<str>],
     path: FilePath<PathBuf>,
+    options: UploadOptions,
     content: S,
 ) -> Result<(), TextEditorFsioError>
 where
     S: Stream<Item = Result<Bytes, TextEditorFsioError>> + Send + 'static,
 {
-    callback::UploadCallback::<S>::upload(server, client_address, path, content).await
+    callback::UploadCallback::<S>::upload(server, client_address, path, options, content).await
 }
 
 pub(super) async fn download_local(
     path: FilePath<PathBuf>,
+    options: DownloadOptions,
 ) -> Result<DownloadStream, TextEditorFsioError> {
     let path = path.full_path();
+    if options.archive {
+        validate_archive_path(&path)?;
+        return Ok(tar::tar_folder(path));
+    }
     validate_download_path(&path)?;
-    let file = tokio::fs::File::open(path).await?;
+    let mut file = tokio::fs::File::open(path).await?;
+    if options.offset > 0 {
+        file.seek(SeekFrom::Start(options.offset)).await?;
+    }
+    let file = file.take(options.length.unwrap_or(u64::MAX));
     Ok(Box::pin(stream::unfold(file, |mut file| async {
         let mut buffer = vec![0; DOWNLOAD_CHUNK_SIZE];
         match file.read(&mut buffer).await {
@@ -71,18 +110,44 @@ pub(super) async fn download_local(
 
 pub(super) async fn upload_local<S>(
     path: FilePath<PathBuf>,
+    options: UploadOptions,
     content: S,
 ) -> Result<(), TextEditorFsioError>
 where
     S: Stream<Item = Result<Bytes, TextEditorFsioError>> + Send + 'static,
 {
     let path = path.full_path();
+    if let Some(on_conflict) = options.extract {
+        validate_extract_path(&path)?;
+        return tar::untar(path, content, on_conflict).await;
+    }
     validate_upload_path(&path)?;
-    let mut file = tokio::fs::File::create(path).await?;
+    let mut file = if options.offset == 0 {
+        tokio::fs::File::create(path).await?
+    } else {
+        let mut file = tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .await?;
+        let size = file.metadata().await?.len();
+        if size < options.offset {
+            return Err(TextEditorFsioError::UploadGap {
+                path,
+                size,
+                offset: options.offset,
+            });
+        }
+        // Drops what was written after the offset, it is uploaded again.
+        file.set_len(options.offset).await?;
+        file.seek(SeekFrom::Start(options.offset)).await?;
+        file
+    };
     futures::pin_mut!(content);
     while let Some(chunk) = content.next().await {
         file.write_all(&chunk?).await?;
     }
+    // Resumed uploads rely on the size of the file once the upload returns.
+    file.flush().await?;
     Ok(())
 }
 
@@ -100,6 +165,24 @@ fn validate_download_path(path: &Path) -> Result<(), TextEditorFsioError> {
     Ok(())
 }
 
+fn validate_archive_path(path: &Path) -> Result<(), TextEditorFsioError> {
+    if !path.exists() {
+        return Err(TextEditorFsioError::PathNotFound {
+            path: path.to_owned(),
+        });
+    }
+    Ok(())
+}
+
+fn validate_extract_path(path: &Path) -> Result<(), TextEditorFsioError> {
+    if !path.is_dir() {
+        return Err(TextEditorFsioError::PathNotDirectory {
+            path: path.to_owned(),
+        });
+    }
+    Ok(())
+}
+
 fn validate_upload_path(path: &Path) -> Result<(), TextEditorFsioError> {
     if path.is_dir() {
         return Err(TextEditorFsioError::PathIsDirectory {
@@ -134,6 +217,26 @@ impl From<FilePath<PathBuf>> for FilePathProto {
     }
 }
 
+impl From<OnConflictProto> for OnConflict {
+    fn from(proto: OnConflictProto) -> Self {
+        match proto {
+            OnConflictProto::Fail => Self::Fail,
+            OnConflictProto::Overwrite => Self::Overwrite,
+            OnConflictProto::Skip => Self::Skip,
+        }
+    }
+}
+
+impl From<OnConflict> for OnConflictProto {
+    fn from(on_conflict: OnConflict) -> Self {
+        match on_conflict {
+            OnConflict::Fail => Self::Fail,
+            OnConflict::Overwrite => Self::Overwrite,
+            OnConflict::Skip => Self::Skip,
+        }
+    }
+}
+
 #[nameth]
 #[derive(thiserror::Error, Debug)]
 pub enum TextEditorFsioError {
@@ -152,9 +255,35 @@ pub enum TextEditorFsioError {
     #[error("[{n}] Path is a directory: {path}", n = self.name(), path = path.display())]
     PathIsDirectory { path: PathBuf },
 
+    #[error("[{n}] Path is not a directory: {path}", n = self.name(), path = path.display())]
+    PathNotDirectory { path: PathBuf },
+
+    #[error(
+        "[{n}] Can't resume the upload of {path} at {offset}, only {size} bytes were uploaded",
+        n = self.name(),
+        path = path.display(),
+    )]
+    UploadGap {
+        path: PathBuf,
+        size: u64,
+        offset: u64,
+    },
+
     #[error("[{n}] Parent directory not found: {path}", n = self.name(), path = path.display())]
     ParentDirectoryNotFound { path: PathBuf },
 
+    #[error("[{n}] Destination already exists: {path}", n = self.name(), path = path.display())]
+    DestinationExists { path: PathBuf },
+
+    #[error("[{n}] Archive entry is outside the folder: {path}", n = self.name())]
+    UnsafeArchivePath { path: String },
+
+    #[error("[{n}] Invalid archive header: {reason}", n = self.name())]
+    InvalidArchive { reason: String },
+
+    #[error("[{n}] The archive ended before its last entry", n = self.name())]
+    TruncatedArchive,
+
     #[error("[{n}] Missing path message", n = self.name())]
     MissingPath,
 
@@ -179,10 +308,16 @@ impl IsGrpcError for TextEditorFsioError {
             Self::PathNotFound { .. } => Code::NotFound,
             Self::PathNotFile { .. }
             | Self::PathIsDirectory { .. }
+            | Self::PathNotDirectory { .. }
+            | Self::UploadGap { .. }
             | Self::ParentDirectoryNotFound { .. } => Code::FailedPrecondition,
-            Self::MissingPath | Self::MissingAddress | Self::UnexpectedUploadMessage => {
-                Code::InvalidArgument
-            }
+            Self::DestinationExists { .. } => Code::AlreadyExists,
+            Self::MissingPath
+            | Self::MissingAddress
+            | Self::UnexpectedUploadMessage
+            | Self::UnsafeArchivePath { .. }
+            | Self::InvalidArchive { .. }
+            | Self::TruncatedArchive => Code::InvalidArgument,
             Self::RemoteClientNotFound { .. } => Code::NotFound,
             Self::ServerNotSet => Code::Internal,
         }
@@ -207,11 +342,16 @@ impl IsHttpError for TextEditorFsioError {
             Self::PathNotFound { .. }
             | Self::ParentDirectoryNotFound { .. }
             | Self::RemoteClientNotFound { .. } => StatusCode::NOT_FOUND,
+            Self::UploadGap { .. } | Self::DestinationExists { .. } => StatusCode::CONFLICT,
             Self::PathNotFile { .. }
             | Self::PathIsDirectory { .. }
+            | Self::PathNotDirectory { .. }
             | Self::MissingPath
             | Self::MissingAddress
-            | Self::UnexpectedUploadMessage => StatusCode::BAD_REQUEST,
+            | Self::UnexpectedUploadMessage
+            | Self::UnsafeArchivePath { .. }
+            | Self::InvalidArchive { .. }
+            | Self::TruncatedArchive => StatusCode::BAD_REQUEST,
         }
     }
 }
@@ -219,3 +359,51 @@ impl IsHttpError for TextEditorFsioError {
 pub fn warn_stream_error(error: &TextEditorFsioError) {
     warn!("Text editor fsio stream error: {error}");
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+    use prost::bytes::Bytes;
+
+    use super::TextEditorFsioError;
+    use super::UploadOptions;
+    use crate::text_editor::file_path::FilePath;
+
+    async fn upload(
+        path: &std::path::Path,
+        offset: u64,
+        data: &'static str,
+    ) -> Result<(), TextEditorFsioError> {
+        let path = FilePath {
+            base: path.parent().unwrap().to_owned(),
+            file: path.file_name().unwrap().into(),
+        };
+        let content = stream::iter([Ok(Bytes::from_static(data.as_bytes()))]);
+        let options = UploadOptions {
+            offset,
+            ..UploadOptions::default()
+        };
+        super::upload_local(path, options, content).await
+    }
+
+    #[tokio::test]
+    async fn resume_upload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        upload(&path, 0, "hello wo").await.unwrap();
+        assert_eq!("hello wo", std::fs::read_to_string(&path).unwrap());
+
+        // The bytes after the offset are replaced.
+        upload(&path, 6, "world").await.unwrap();
+        assert_eq!("hello world", std::fs::read_to_string(&path).unwrap());
+
+        let error = upload(&path, 20, "!").await.unwrap_err();
+        assert!(matches!(
+            error,
+            TextEditorFsioError::UploadGap { size: 11, .. }
+        ));
+
+        upload(&path, 0, "new").await.unwrap();
+        assert_eq!("new", std::fs::read_to_string(&path).unwrap());
+    }
+}
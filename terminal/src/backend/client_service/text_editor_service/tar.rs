@@ -0,0 +1,748 @@
+//! Streams a folder as a tar archive, built while it is downloaded,
+//! and extracts the tar archives that are uploaded.
+
+use std::os::unix::fs::MetadataExt as _;
+use std::os::unix::fs::PermissionsExt as _;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+
+use futures::Stream;
+use futures::StreamExt as _;
+use futures::stream;
+use prost::bytes::Bytes;
+use tokio::io::AsyncReadExt as _;
+use tokio::io::AsyncWriteExt as _;
+use tokio::sync::mpsc;
+use tracing::Instrument as _;
+use tracing::debug;
+use tracing::warn;
+
+use super::DOWNLOAD_CHUNK_SIZE;
+use super::DownloadStream;
+use super::TextEditorFsioError;
+use crate::text_editor::fsio::OnConflict;
+
+const BLOCK_SIZE: usize = 512;
+
+/// Lengths above the limits of the ustar header are written in a PAX header.
+const MAX_NAME_LEN: usize = 100;
+const MAX_SIZE: u64 = 0o77777777777;
+
+/// PAX headers are read in memory, larger ones are rejected.
+const MAX_PAX_SIZE: u64 = 1 << 20;
+
+type ArchiveSender = mpsc::Sender<Result<Bytes, TextEditorFsioError>>;
+
+/// Streams the folder, its entries are inside a folder with the same name.
+pub(super) fn tar_folder(folder: PathBuf) -> DownloadStream {
+    let (tx, rx) = mpsc::channel(4);
+    let task = async move {
+        let mut writer = TarWriter {
+            tx,
+            buffer: Vec::with_capacity(DOWNLOAD_CHUNK_SIZE),
+        };
+        match writer.write_folder(&folder).await {
+            Ok(()) => debug!("Archived {folder:?}"),
+            Err(error) => {
+                warn!("Failed to archive {folder:?}: {error}");
+                let _ = writer.tx.send(Err(error)).await;
+            }
+        }
+    };
+    tokio::spawn(task.in_current_span());
+    Box::pin(stream::unfold(rx, |mut rx| async {
+        let chunk = rx.recv().await?;
+        Some((chunk, rx))
+    }))
+}
+
+struct TarWriter {
+    tx: ArchiveSender,
+    buffer: Vec<u8>,
+}
+
+impl TarWriter {
+    async fn write_folder(&mut self, folder: &Path) -> Result<(), TextEditorFsioError> {
+        let root_name = folder
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "root".to_owned());
+        let mut stack = vec![(folder.to_owned(), root_name)];
+        while let Some((path, name)) = stack.pop() {
+            let metadata = match tokio::fs::symlink_metadata(&path).await {
+                Ok(metadata) => metadata,
+                Err(error) => {
+                    warn!("Skipping {path:?}: {error}");
+                    continue;
+                }
+            };
+            let entry = Entry {
+                name: &name,
+                mode: metadata.mode() & 0o7777,
+                mtime: metadata.mtime().max(0) as u64,
+                ..Entry::default()
+            };
+            if metadata.is_dir() {
+                let mut children = vec![];
+                match tokio::fs::read_dir(&path).await {
+                    Ok(mut read_dir) => {
+                        while let Some(child) = read_dir.next_entry().await? {
+                            children.push(child.file_name().to_string_lossy().into_owned());
+                        }
+                    }
+                    Err(error) => warn!("Failed to list {path:?}: {error}"),
+                }
+                // The stack is a LIFO: push in reverse to archive in order.
+                children.sort_unstable_by(|a, b| b.cmp(a));
+                stack.extend(
+                    children
+                        .into_iter()
+                        .map(|child| (path.join(&child), format!("{name}/{child}"))),
+                );
+                let name = format!("{name}/");
+                let entry = Entry {
+                    name: &name,
+                    kind: EntryKind::Directory,
+                    ..entry
+                };
+                self.write(&entry.headers()).await?;
+            } else if metadata.is_symlink() {
+                let link = tokio::fs::read_link(&path).await?;
+                let link = link.to_string_lossy();
+                let entry = Entry {
+                    kind: EntryKind::Symlink,
+                    link: &link,
+                    ..entry
+                };
+                self.write(&entry.headers()).await?;
+            } else if metadata.is_file() {
+                let file = match tokio::fs::File::open(&path).await {
+                    Ok(file) => file,
+                    Err(error) => {
+                        warn!("Skipping {path:?}: {error}");
+                        continue;
+                    }
+                };
+                let size = metadata.len();
+                let entry = Entry { size, ..entry };
+                self.write(&entry.headers()).await?;
+                self.write_content(file, size).await?;
+            }
+        }
+        self.write(&[0; 2 * BLOCK_SIZE]).await?;
+        self.flush().await
+    }
+
+    /// Writes exactly `size` bytes, even if the file changed since its header was written.
+    async fn write_content(
+        &mut self,
+        file: tokio::fs::File,
+        size: u64,
+    ) -> Result<(), TextEditorFsioError> {
+        let mut file = file.take(size);
+        let mut written = 0;
+        let mut chunk = vec![0; DOWNLOAD_CHUNK_SIZE];
+        loop {
+            let len = file.read(&mut chunk).await?;
+            if len == 0 {
+                break;
+            }
+            self.write(&chunk[..len]).await?;
+            written += len as u64;
+        }
+        // Zeros replace what was truncated, then complete the last block.
+        let mut zeros = size - written + padding(size) as u64;
+        chunk.fill(0);
+        while zeros > 0 {
+            let len = zeros.min(chunk.len() as u64) as usize;
+            self.write(&chunk[..len]).await?;
+            zeros -= len as u64;
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), TextEditorFsioError> {
+        self.buffer.extend_from_slice(bytes);
+        if self.buffer.len() >= DOWNLOAD_CHUNK_SIZE {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), TextEditorFsioError> {
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(DOWNLOAD_CHUNK_SIZE));
+        self.tx
+            .send(Ok(Bytes::from(chunk)))
+            .await
+            .map_err(|_| std::io::Error::other("The download was canceled").into())
+    }
+}
+
+/// Extracts the archive into the folder.
+///
+/// Folders are merged, `on_conflict` applies to the other entries that already exist.
+/// With [OnConflict::Fail], the top-level entries must not exist at all.
+pub(super) async fn untar<S>(
+    folder: PathBuf,
+    content: S,
+    on_conflict: OnConflict,
+) -> Result<(), TextEditorFsioError>
+where
+    S: Stream<Item = Result<Bytes, TextEditorFsioError>> + Send + 'static,
+{
+    let mut reader = TarReader {
+        content: Box::pin(content),
+        buffer: Bytes::new(),
+    };
+    let mut extractor = TarExtractor {
+        folder,
+        on_conflict,
+        folders: vec![],
+        links: vec![],
+        skipped: vec![],
+    };
+    let mut pax = PaxHeader::default();
+    loop {
+        let block = reader.read_exact(BLOCK_SIZE).await?;
+        if block.iter().all(|b| *b == 0) {
+            break;
+        }
+        let header = Header::parse(&block)?;
+        let overrides = std::mem::take(&mut pax);
+        let size = overrides.size.unwrap_or(header.size);
+        let kind = match header.typeflag {
+            b'x' => {
+                if size > MAX_PAX_SIZE {
+                    return Err(TextEditorFsioError::InvalidArchive {
+                        reason: format!("PAX header of {size} bytes"),
+                    });
+                }
+                let records = reader.read_exact(size as usize).await?;
+                reader.skip(padding(size) as u64).await?;
+                pax = PaxHeader::parse(&records)?;
+                continue;
+            }
+            b'0' | b'\0' => EntryKind::File,
+            b'5' => EntryKind::Directory,
+            b'2' => EntryKind::Symlink,
+            typeflag => {
+                debug!("Skipping archive entry of type {:?}", typeflag as char);
+                reader.skip(size + padding(size) as u64).await?;
+                continue;
+            }
+        };
+        let name = overrides.path.unwrap_or(header.name);
+        let path = extractor.prepare(&name, kind).await?;
+        match (kind, path) {
+            (EntryKind::File, Some(path)) => {
+                let file = tokio::fs::File::create(&path).await?;
+                reader.copy(Some(file), size).await?;
+                let permissions = std::fs::Permissions::from_mode(header.mode);
+                tokio::fs::set_permissions(&path, permissions).await?;
+            }
+            (EntryKind::Directory, Some(path)) => {
+                tokio::fs::create_dir_all(&path).await?;
+                extractor.folders.push((path, header.mode));
+            }
+            (EntryKind::Symlink, Some(path)) => {
+                let link = overrides.linkpath.unwrap_or(header.link);
+                tokio::fs::symlink(link, &path).await?;
+                extractor.links.push(path);
+            }
+            (_, None) => reader.copy(None, size).await?,
+        }
+        reader.skip(padding(size) as u64).await?;
+    }
+    // Last, in case some folders are read-only.
+    for (folder, mode) in extractor.folders.into_iter().rev() {
+        let permissions = std::fs::Permissions::from_mode(mode);
+        tokio::fs::set_permissions(folder, permissions).await?;
+    }
+    Ok(())
+}
+
+struct TarReader<S> {
+    content: Pin<Box<S>>,
+    buffer: Bytes,
+}
+
+impl<S> TarReader<S>
+where
+    S: Stream<Item = Result<Bytes, TextEditorFsioError>>,
+{
+    /// Returns the next bytes, at most `max`.
+    async fn read(&mut self, max: usize) -> Result<Bytes, TextEditorFsioError> {
+        while self.buffer.is_empty() {
+            self.buffer = self
+                .content
+                .next()
+                .await
+                .ok_or(TextEditorFsioError::TruncatedArchive)??;
+        }
+        let len = max.min(self.buffer.len());
+        Ok(self.buffer.split_to(len))
+    }
+
+    async fn read_exact(&mut self, len: usize) -> Result<Vec<u8>, TextEditorFsioError> {
+        let mut bytes = Vec::with_capacity(len);
+        while bytes.len() < len {
+            bytes.extend_from_slice(&self.read(len - bytes.len()).await?);
+        }
+        Ok(bytes)
+    }
+
+    async fn skip(&mut self, len: u64) -> Result<(), TextEditorFsioError> {
+        self.copy(None, len).await
+    }
+
+    /// Writes the next `len` bytes to the file, or drops them.
+    async fn copy(
+        &mut self,
+        mut file: Option<tokio::fs::File>,
+        mut len: u64,
+    ) -> Result<(), TextEditorFsioError> {
+        while len > 0 {
+            let max = len.min(DOWNLOAD_CHUNK_SIZE as u64) as usize;
+            let chunk = self.read(max).await?;
+            if let Some(file) = &mut file {
+                file.write_all(&chunk).await?;
+            }
+            len -= chunk.len() as u64;
+        }
+        if let Some(file) = &mut file {
+            file.flush().await?;
+        }
+        Ok(())
+    }
+}
+
+struct TarExtractor {
+    folder: PathBuf,
+    on_conflict: OnConflict,
+
+    /// The extracted folders and their mode.
+    folders: Vec<(PathBuf, u32)>,
+
+    /// Entries are never extracted through a symlink of the archive.
+    links: Vec<PathBuf>,
+
+    /// The entries inside a skipped entry are skipped too.
+    skipped: Vec<PathBuf>,
+}
+
+impl TarExtractor {
+    /// Where the entry is extracted, or `None` if it is skipped.
+    async fn prepare(
+        &mut self,
+        name: &str,
+        kind: EntryKind,
+    ) -> Result<Option<PathBuf>, TextEditorFsioError> {
+        let path = self.folder.join(safe_relative_path(name)?);
+        if path == self.folder || self.skipped.iter().any(|skipped| path.starts_with(skipped)) {
+            return Ok(None);
+        }
+        if self.links.iter().any(|link| path.starts_with(link)) {
+            return Err(TextEditorFsioError::UnsafeArchivePath {
+                path: name.to_owned(),
+            });
+        }
+        let Ok(existing) = tokio::fs::symlink_metadata(&path).await else {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            return Ok(Some(path));
+        };
+        let is_top_level = path.parent() == Some(self.folder.as_path());
+        let is_merge = kind == EntryKind::Directory && existing.is_dir();
+        if is_merge && !(is_top_level && self.on_conflict == OnConflict::Fail) {
+            return Ok(Some(path));
+        }
+        match self.on_conflict {
+            OnConflict::Fail => Err(TextEditorFsioError::DestinationExists { path }),
+            OnConflict::Skip => {
+                debug!("Skipping {path:?}: it already exists");
+                self.skipped.push(path);
+                Ok(None)
+            }
+            OnConflict::Overwrite => {
+                if existing.is_dir() {
+                    tokio::fs::remove_dir_all(&path).await?;
+                } else {
+                    tokio::fs::remove_file(&path).await?;
+                }
+                Ok(Some(path))
+            }
+        }
+    }
+}
+
+/// The name of the entry, without `.` and rejected if it could be outside the folder.
+fn safe_relative_path(name: &str) -> Result<PathBuf, TextEditorFsioError> {
+    let mut path = PathBuf::new();
+    for component in Path::new(name).components() {
+        match component {
+            Component::Normal(component) => path.push(component),
+            Component::CurDir => {}
+            Component::RootDir | Component::Prefix(_) | Component::ParentDir => {
+                return Err(TextEditorFsioError::UnsafeArchivePath {
+                    path: name.to_owned(),
+                });
+            }
+        }
+    }
+    Ok(path)
+}
+
+/// The fields of a ustar header that are extracted.
+struct Header {
+    name: String,
+    mode: u32,
+    size: u64,
+    typeflag: u8,
+    link: String,
+}
+
+impl Header {
+    fn parse(block: &[u8]) -> Result<Self, TextEditorFsioError> {
+        let invalid = |reason: &str| TextEditorFsioError::InvalidArchive {
+            reason: reason.to_owned(),
+        };
+        let checksum = read_octal(&block[148..156]).ok_or_else(|| invalid("checksum"))?;
+        let actual = 8 * b' ' as u64
+            + [&block[..148], &block[156..]]
+                .iter()
+                .flat_map(|field| field.iter())
+                .map(|b| *b as u64)
+                .sum::<u64>();
+        if checksum != actual {
+            return Err(invalid("wrong checksum"));
+        }
+        let mut name = read_str(&block[0..100]);
+        let prefix = read_str(&block[345..500]);
+        if &block[257..262] == b"ustar" && !prefix.is_empty() {
+            name = format!("{prefix}/{name}");
+        }
+        Ok(Self {
+            name,
+            mode: read_octal(&block[100..108]).ok_or_else(|| invalid("mode"))? as u32 & 0o7777,
+            size: read_octal(&block[124..136]).ok_or_else(|| invalid("size"))?,
+            typeflag: block[156],
+            link: read_str(&block[157..257]),
+        })
+    }
+}
+
+/// The PAX records that override the fields of the next header.
+#[derive(Default)]
+struct PaxHeader {
+    path: Option<String>,
+    linkpath: Option<String>,
+    size: Option<u64>,
+}
+
+impl PaxHeader {
+    fn parse(mut records: &[u8]) -> Result<Self, TextEditorFsioError> {
+        let invalid = || TextEditorFsioError::InvalidArchive {
+            reason: "PAX record".to_owned(),
+        };
+        let mut pax = Self::default();
+        while !records.is_empty() {
+            let space = records
+                .iter()
+                .position(|b| *b == b' ')
+                .ok_or_else(invalid)?;
+            let length: usize = std::str::from_utf8(&records[..space])
+                .ok()
+                .and_then(|length| length.parse().ok())
+                .filter(|length| space < *length && *length <= records.len())
+                .ok_or_else(invalid)?;
+            let record = String::from_utf8_lossy(&records[space + 1..length - 1]);
+            let (key, value) = record.split_once('=').ok_or_else(invalid)?;
+            match key {
+                "path" => pax.path = Some(value.to_owned()),
+                "linkpath" => pax.linkpath = Some(value.to_owned()),
+                "size" => pax.size = Some(value.parse().map_err(|_| invalid())?),
+                _ => {}
+            }
+            records = &records[length..];
+        }
+        Ok(pax)
+    }
+}
+
+fn read_str(field: &[u8]) -> String {
+    let len = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..len]).into_owned()
+}
+
+fn read_octal(field: &[u8]) -> Option<u64> {
+    let value = read_str(field);
+    let value = value.trim_matches(|c: char| c == ' ' || c == '\0');
+    if value.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(value, 8).ok()
+}
+
+/// The zeros that complete the last block of a file.
+fn padding(size: u64) -> usize {
+    (BLOCK_SIZE - (size % BLOCK_SIZE as u64) as usize) % BLOCK_SIZE
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum EntryKind {
+    #[default]
+    File,
+    Directory,
+    Symlink,
+}
+
+impl EntryKind {
+    fn typeflag(self) -> u8 {
+        match self {
+            Self::File => b'0',
+            Self::Directory => b'5',
+            Self::Symlink => b'2',
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Entry<'t> {
+    name: &'t str,
+    kind: EntryKind,
+    mode: u32,
+    size: u64,
+    mtime: u64,
+    link: &'t str,
+}
+
+impl Entry<'_> {
+    /// The ustar header, preceded by a PAX header if the entry doesn't fit in it.
+    fn headers(&self) -> Vec<u8> {
+        let mut records = String::new();
+        if self.name.len() > MAX_NAME_LEN {
+            records += &pax_record("path", self.name);
+        }
+        if self.link.len() > MAX_NAME_LEN {
+            records += &pax_record("linkpath", self.link);
+        }
+        if self.size > MAX_SIZE {
+            records += &pax_record("size", &self.size.to_string());
+        }
+        let mut headers = vec![];
+        if !records.is_empty() {
+            let pax = Entry {
+                name: "././@PaxHeader",
+                kind: EntryKind::File,
+                mode: 0o644,
+                size: records.len() as u64,
+                mtime: self.mtime,
+                link: "",
+            };
+            headers.extend_from_slice(&pax.ustar_header(b'x'));
+            headers.extend_from_slice(records.as_bytes());
+            headers.resize(headers.len() + padding(records.len() as u64), 0);
+        }
+        headers.extend_from_slice(&self.ustar_header(self.kind.typeflag()));
+        headers
+    }
+
+    fn ustar_header(&self, typeflag: u8) -> [u8; BLOCK_SIZE] {
+        let mut header = [0; BLOCK_SIZE];
+        write_str(&mut header[0..100], self.name);
+        write_octal(&mut header[100..108], self.mode.into());
+        write_octal(&mut header[108..116], 0);
+        write_octal(&mut header[116..124], 0);
+        write_octal(&mut header[124..136], self.size.min(MAX_SIZE));
+        write_octal(&mut header[136..148], self.mtime.min(MAX_SIZE));
+        header[156] = typeflag;
+        write_str(&mut header[157..257], self.link);
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+
+        // The checksum is computed with its own field filled with spaces.
+        header[148..156].fill(b' ');
+        let checksum = header.iter().map(|b| *b as u64).sum::<u64>();
+        write_octal(&mut header[148..155], checksum);
+        header
+    }
+}
+
+/// Writes the string, truncated to the field: the full value is in the PAX header.
+fn write_str(field: &mut [u8], value: &str) {
+    let len = value.len().min(field.len());
+    field[..len].copy_from_slice(&value.as_bytes()[..len]);
+}
+
+/// Writes the number in octal, padded with zeros and terminated by a NUL.
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    let value = format!("{value:0digits$o}");
+    field[..digits].copy_from_slice(&value.as_bytes()[value.len() - digits..]);
+    field[digits] = 0;
+}
+
+/// A `"<length> <key>=<value>\n"` record, where the length counts itself.
+fn pax_record(key: &str, value: &str) -> String {
+    let rest = format!(" {key}={value}\n");
+    let mut length = rest.len() + 1;
+    while length.to_string().len() + rest.len() > length {
+        length += 1;
+    }
+    format!("{length}{rest}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BLOCK_SIZE;
+    use super::Entry;
+    use super::EntryKind;
+    use super::TextEditorFsioError;
+    use crate::text_editor::fsio::OnConflict;
+
+    #[test]
+    fn pax_record() {
+        assert_eq!("12 path=abc\n", super::pax_record("path", "abc"));
+        let record = super::pax_record("path", &"a".repeat(93));
+        assert_eq!("103 path=", &record[..9]);
+        assert_eq!(103, record.len());
+    }
+
+    #[test]
+    fn headers() {
+        let entry = Entry {
+            name: "folder/file.txt",
+            mode: 0o644,
+            size: 5,
+            mtime: 1_700_000_000,
+            ..Entry::default()
+        };
+        let header = entry.headers();
+        assert_eq!(BLOCK_SIZE, header.len());
+        assert_eq!(b"folder/file.txt\0", &header[..16]);
+        assert_eq!(b"0000644\0", &header[100..108]);
+        assert_eq!(b"00000000005\0", &header[124..136]);
+        assert_eq!(b'0', header[156]);
+        let fields = [&header[..148], &header[156..]];
+        let checksum = 8 * 32 + fields.concat().iter().map(|b| *b as u64).sum::<u64>();
+        let expected = format!("{checksum:06o}\0");
+        assert_eq!(expected.as_bytes(), &header[148..155]);
+
+        let name = format!("folder/{}", "a".repeat(120));
+        let entry = Entry {
+            name: &name,
+            kind: EntryKind::Directory,
+            ..entry
+        };
+        let headers = entry.headers();
+        assert_eq!(3 * BLOCK_SIZE, headers.len());
+        assert_eq!(b'x', headers[156]);
+        assert_eq!(b'5', headers[2 * BLOCK_SIZE + 156]);
+    }
+
+    #[tokio::test]
+    async fn untar() {
+        let source = tempfile::tempdir().unwrap();
+        let folder = source.path().join("folder");
+        let long_name = "b".repeat(150);
+        std::fs::create_dir_all(folder.join("nested")).unwrap();
+        std::fs::write(folder.join("nested/a.txt"), "hello").unwrap();
+        std::fs::write(folder.join(&long_name), "long").unwrap();
+        std::os::unix::fs::symlink("nested/a.txt", folder.join("link")).unwrap();
+        let archive = || super::tar_folder(folder.clone());
+
+        let destination = tempfile::tempdir().unwrap();
+        let extract = |on_conflict| {
+            let archive = archive();
+            let path = destination.path().to_owned();
+            async move { super::untar(path, archive, on_conflict).await }
+        };
+        extract(OnConflict::Fail).await.unwrap();
+        let copy = destination.path().join("folder");
+        let read = |file: &str| std::fs::read_to_string(copy.join(file)).unwrap();
+        assert_eq!("hello", read("nested/a.txt"));
+        assert_eq!("long", read(&long_name));
+        assert_eq!(
+            std::path::Path::new("nested/a.txt"),
+            std::fs::read_link(copy.join("link")).unwrap()
+        );
+
+        let error = extract(OnConflict::Fail).await.unwrap_err();
+        assert!(matches!(
+            error,
+            TextEditorFsioError::DestinationExists { .. }
+        ));
+
+        std::fs::write(copy.join("nested/a.txt"), "changed").unwrap();
+        extract(OnConflict::Skip).await.unwrap();
+        assert_eq!("changed", read("nested/a.txt"));
+        extract(OnConflict::Overwrite).await.unwrap();
+        assert_eq!("hello", read("nested/a.txt"));
+    }
+
+    #[tokio::test]
+    async fn untar_rejects_unsafe_paths() {
+        let mut archive = Entry {
+            name: "../escape.txt",
+            mode: 0o644,
+            ..Entry::default()
+        }
+        .headers();
+        archive.extend_from_slice(&[0; 2 * BLOCK_SIZE]);
+        let destination = tempfile::tempdir().unwrap();
+        let content = futures::stream::iter([Ok(archive.into())]);
+
+        let error = super::untar(destination.path().to_owned(), content, OnConflict::Fail)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            TextEditorFsioError::UnsafeArchivePath { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn untar_rejects_large_pax_header() {
+        let mut archive = Entry {
+            name: "././@PaxHeader",
+            mode: 0o644,
+            size: 1 << 32,
+            ..Entry::default()
+        }
+        .ustar_header(b'x')
+        .to_vec();
+        archive.extend_from_slice(b"12 path=abc\n");
+        let destination = tempfile::tempdir().unwrap();
+        let content = futures::stream::iter([Ok(archive.into())]);
+
+        let error = super::untar(destination.path().to_owned(), content, OnConflict::Fail)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, TextEditorFsioError::InvalidArchive { .. }));
+    }
+
+    #[tokio::test]
+    async fn untar_truncated() {
+        let mut archive = Entry {
+            name: "file.txt",
+            mode: 0o644,
+            size: 5,
+            ..Entry::default()
+        }
+        .headers();
+        archive.extend_from_slice(b"hel");
+        let destination = tempfile::tempdir().unwrap();
+        let content = futures::stream::iter([Ok(archive.into())]);
+
+        let error = super::untar(destination.path().to_owned(), content, OnConflict::Fail)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, TextEditorFsioError::TruncatedArchive));
+    }
+}
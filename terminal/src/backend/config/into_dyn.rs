@@ -318,6 +318,31 @@ fn apply_server_config(config: &DiffArc<DynConfig>, new: &ServerConfig) {
             let result = get_or_init(old, &mut result);
             result.terminal_shell = new.terminal_shell.clone();
         }
+        if new.profiles != old.profiles {
+            info!("Changed: profiles");
+            let result = get_or_init(old, &mut result);
+            result.profiles = new.profiles.clone();
+        }
+        if new.scrollback_spill != old.scrollback_spill {
+            info!("Changed: scrollback_spill");
+            let result = get_or_init(old, &mut result);
+            result.scrollback_spill = new.scrollback_spill.clone();
+        }
+        if new.scrollback_spill_limit != old.scrollback_spill_limit {
+            info!("Changed: scrollback_spill_limit");
+            let result = get_or_init(old, &mut result);
+            result.scrollback_spill_limit = new.scrollback_spill_limit;
+        }
+        if new.size_policy != old.size_policy {
+            info!("Changed: size_policy");
+            let result = get_or_init(old, &mut result);
+            result.size_policy = new.size_policy;
+        }
+        if new.recordings != old.recordings {
+            info!("Changed: recordings");
+            let result = get_or_init(old, &mut result);
+            result.recordings = new.recordings.clone();
+        }
         if new.trash != old.trash {
             info!("Changed: trash");
             let result = get_or_init(old, &mut result);
@@ -328,6 +353,16 @@ fn apply_server_config(config: &DiffArc<DynConfig>, new: &ServerConfig) {
             let result = get_or_init(old, &mut result);
             result.git_trash = new.git_trash.clone();
         }
+        if new.trash_max_age != old.trash_max_age {
+            info!("Changed: trash_max_age");
+            let result = get_or_init(old, &mut result);
+            result.trash_max_age = new.trash_max_age;
+        }
+        if new.language_servers != old.language_servers {
+            info!("Changed: language_servers");
+            let result = get_or_init(old, &mut result);
+            result.language_servers = new.language_servers.clone();
+        }
         if new.tantivy_cache != old.tantivy_cache {
             info!("Changed: tantivy_cache");
             let result = get_or_init(old, &mut result);
@@ -0,0 +1,54 @@
+//! The language servers that the text editor starts for source files.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct LanguageServer {
+    /// The program that talks LSP over its stdin and stdout.
+    pub command: String,
+    pub args: Vec<String>,
+
+    /// The extensions of the files the server handles, like `rs`.
+    pub extensions: Vec<String>,
+
+    /// The files that mark the root of a workspace, like `Cargo.toml`.
+    pub root_markers: Vec<String>,
+}
+
+impl LanguageServer {
+    /// The language servers to use when none are configured.
+    pub fn defaults() -> BTreeMap<String, Self> {
+        let server = |command: &str, args: &[&str], extensions: &[&str], root_markers: &[&str]| {
+            let to_vec = |strings: &[&str]| strings.iter().map(|s| s.to_string()).collect();
+            Self {
+                command: command.to_owned(),
+                args: to_vec(args),
+                extensions: to_vec(extensions),
+                root_markers: to_vec(root_markers),
+            }
+        };
+        [
+            (
+                "rust",
+                server("rust-analyzer", &[], &["rs"], &["Cargo.toml"]),
+            ),
+            (
+                "python",
+                server(
+                    "pyright-langserver",
+                    &["--stdio"],
+                    &["py"],
+                    &["pyproject.toml", "setup.py"],
+                ),
+            ),
+            ("go", server("gopls", &[], &["go"], &["go.mod"])),
+        ]
+        .into_iter()
+        .map(|(language, server)| (language.to_owned(), server))
+        .collect()
+    }
+}
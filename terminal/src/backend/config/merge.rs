@@ -45,8 +45,16 @@ impl Config {
                 host: Some(server.host.clone()),
                 ports: server.ports.clone(),
                 terminal_shell: server.terminal_shell.clone(),
+                profiles: server.profiles.clone(),
+                session_holder: server.session_holder.as_ref().map(collapse_tilde),
+                scrollback_spill: server.scrollback_spill.as_ref().map(collapse_tilde),
+                scrollback_spill_limit: server.scrollback_spill_limit,
+                size_policy: server.size_policy,
+                recordings: Some(collapse_tilde(&server.recordings)),
                 trash: Some(collapse_tilde(&server.trash)),
                 git_trash: server.git_trash.as_ref().map(collapse_tilde),
+                trash_max_age: Some(humantime::format_duration(server.trash_max_age).to_string()),
+                language_servers: server.language_servers.clone(),
                 tantivy_cache: Some(server.tantivy_cache.clone()),
                 search_index_refresh: Some(
                     humantime::format_duration(server.search_index_refresh).to_string(),
@@ -107,6 +115,31 @@ fn merge_server_config(
             .terminal_shell
             .clone()
             .or_else(|| server.terminal_shell.clone()),
+        profiles: server.profiles.clone(),
+        session_holder: {
+            let session_holder = cli.session_holder.as_deref();
+            session_holder
+                .or(server.session_holder.as_deref())
+                .map(expand_tilde)
+        }
+        .map(Arc::from),
+        scrollback_spill: {
+            let scrollback_spill = cli.scrollback_spill.as_deref();
+            scrollback_spill
+                .or(server.scrollback_spill.as_deref())
+                .map(expand_tilde)
+        }
+        .map(Arc::from),
+        scrollback_spill_limit: cli.scrollback_spill_limit.or(server.scrollback_spill_limit),
+        size_policy: server.size_policy,
+        recordings: {
+            let recordings = cli.recordings.as_deref();
+            let recordings = recordings
+                .or(server.recordings.as_deref())
+                .map(expand_tilde);
+            recordings.unwrap_or_else(|| terrazzo_home().join("recordings"))
+        }
+        .into(),
         trash: {
             let trash = cli.trash.as_deref();
             let trash = trash.or(server.trash.as_deref()).map(expand_tilde);
@@ -118,6 +151,9 @@ fn merge_server_config(
             git_trash.or(server.git_trash.as_deref()).map(expand_tilde)
         }
         .map(Arc::from),
+        trash_max_age: parse_duration(server.trash_max_age.as_deref())
+            .unwrap_or(Duration::from_secs(30 * 24 * 60 * 60)),
+        language_servers: server.language_servers.clone(),
         tantivy_cache: cli
             .tantivy_cache
             .as_deref()
@@ -225,6 +261,9 @@ mod tests {
     use super::ConfigImpl;
     use super::ServerConfig;
     use super::parse_duration;
+    use crate::backend::config::language_server::LanguageServer;
+    use crate::backend::config::profile::LaunchProfile;
+    use crate::backend::config::server::SizePolicy;
     use crate::backend::config::types::RuntimeTypes;
     use crate::backend::home;
     use crate::backend::terrazzo_home;
@@ -277,8 +316,27 @@ mod tests {
                 host: "localhost".into(),
                 ports: vec![3000],
                 terminal_shell: Some("echo test; exec /bin/bash -i".into()),
+                profiles: [(
+                    "dev".to_owned(),
+                    LaunchProfile {
+                        command: Some("/bin/zsh".into()),
+                        args: vec!["-l".into()],
+                        working_directory: Some("/tmp".into()),
+                        env: [("EDITOR".to_owned(), "vim".to_owned())].into(),
+                        scrollback: Some(1000),
+                        title: Some("Dev".into()),
+                    },
+                )]
+                .into(),
+                session_holder: Some(terrazzo_home().join("sessions.sock").into()),
+                scrollback_spill: Some(terrazzo_home().join("scrollback").into()),
+                scrollback_spill_limit: Some(1 << 20),
+                size_policy: SizePolicy::Exclusive,
+                recordings: terrazzo_home().join("recordings").into(),
                 trash: terrazzo_home().join("trash").into(),
                 git_trash: Some(Path::new(".trash").into()),
+                trash_max_age: parse_duration(Some("7days")).unwrap(),
+                language_servers: LanguageServer::defaults(),
                 tantivy_cache: Path::new(".search-cache").into(),
                 search_index_refresh: Duration::from_secs(7200),
                 search_index_stale_after: Duration::from_secs(600),
@@ -302,11 +360,34 @@ mod tests {
             round_trip.server.terminal_shell.as_deref(),
             Some("echo test; exec /bin/bash -i")
         );
+        assert_eq!(round_trip.server.profiles, config.server.profiles);
+        assert_eq!(
+            round_trip.server.language_servers,
+            config.server.language_servers
+        );
+        assert_eq!(
+            round_trip.server.session_holder.as_deref(),
+            Some(terrazzo_home().join("sessions.sock").as_path())
+        );
+        assert_eq!(
+            round_trip.server.scrollback_spill.as_deref(),
+            Some(terrazzo_home().join("scrollback").as_path())
+        );
+        assert_eq!(round_trip.server.scrollback_spill_limit, Some(1 << 20));
+        assert_eq!(round_trip.server.size_policy, SizePolicy::Exclusive);
+        assert_eq!(
+            &*round_trip.server.recordings,
+            terrazzo_home().join("recordings")
+        );
         assert_eq!(&*round_trip.server.trash, terrazzo_home().join("trash"));
         assert_eq!(
             round_trip.server.git_trash.as_deref(),
             Some(Path::new(".trash"))
         );
+        assert_eq!(
+            round_trip.server.trash_max_age,
+            parse_duration(Some("7days")).unwrap()
+        );
         assert_eq!(
             &*round_trip.server.tantivy_cache,
             Path::new(".search-cache")
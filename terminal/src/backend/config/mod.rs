@@ -22,10 +22,12 @@ use self::types::RuntimeTypes;
 mod into_dyn;
 pub(in crate::backend) mod io;
 pub(in crate::backend) mod kill;
+pub mod language_server;
 mod merge;
 pub mod mesh;
 pub(in crate::backend) mod password;
 pub(in crate::backend) mod pidfile;
+pub mod profile;
 pub mod server;
 pub(in crate::backend) mod types;
 
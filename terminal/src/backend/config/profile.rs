@@ -0,0 +1,100 @@
+//! Named launch profiles for new terminals.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use terrazzo_pty::launch::Launch;
+
+use super::server::ServerConfig;
+use crate::api::shared::terminal_schema::STREAMING_WINDOW_SIZE;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct LaunchProfile {
+    /// The program to run, or an interactive `$SHELL` if not set.
+    pub command: Option<String>,
+    pub args: Vec<String>,
+    pub working_directory: Option<PathBuf>,
+
+    /// Extra environment variables.
+    pub env: BTreeMap<String, String>,
+
+    /// How many bytes of output to keep in the scrollback.
+    pub scrollback: Option<usize>,
+
+    /// The initial title of the terminal tab.
+    pub title: Option<String>,
+}
+
+impl LaunchProfile {
+    /// Runs a command line with `/bin/bash -lc`.
+    pub fn bash(command_line: impl Into<String>) -> Self {
+        let Launch { program, args, .. } = Launch::bash(command_line);
+        Self {
+            command: program,
+            args,
+            ..Self::default()
+        }
+    }
+
+    /// How many bytes of output to keep in the scrollback, defaulting to the streaming window.
+    pub fn scrollback_size(&self) -> usize {
+        self.scrollback.unwrap_or(STREAMING_WINDOW_SIZE)
+    }
+
+    pub fn launch(&self) -> Launch {
+        Launch {
+            program: self.command.clone(),
+            args: self.args.clone(),
+            working_directory: self.working_directory.clone(),
+            env: self.env.clone().into_iter().collect(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// The profile to launch a terminal with, defaulting to the `terminal-shell`.
+    pub fn launch_profile(&self, name: Option<&str>) -> Option<LaunchProfile> {
+        if let Some(name) = name {
+            return self.profiles.get(name).cloned();
+        }
+        let profile = self.terminal_shell.as_deref().map(LaunchProfile::bash);
+        Some(profile.unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LaunchProfile;
+    use crate::backend::config::ConfigFile;
+    use crate::backend::config::ServerConfig;
+
+    fn test_config() -> ServerConfig {
+        let config = ConfigFile::default().merge(&Default::default());
+        (*config.server).clone()
+    }
+
+    #[test]
+    fn launch_profile() {
+        let mut config = test_config();
+        config.terminal_shell = None;
+        assert_eq!(Some(LaunchProfile::default()), config.launch_profile(None));
+
+        config.terminal_shell = Some("fish".into());
+        assert_eq!(
+            Some(LaunchProfile::bash("fish")),
+            config.launch_profile(None)
+        );
+
+        let dev = LaunchProfile {
+            working_directory: Some("/tmp".into()),
+            title: Some("Dev".into()),
+            ..LaunchProfile::bash("make dev")
+        };
+        config.profiles.insert("dev".into(), dev.clone());
+        assert_eq!(Some(dev), config.launch_profile(Some("dev")));
+        assert_eq!(None, config.launch_profile(Some("unknown")));
+    }
+}
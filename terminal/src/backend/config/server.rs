@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::ops::Deref;
 use std::sync::Arc;
 
@@ -6,6 +7,8 @@ use serde::Serialize;
 use trz_gateway_common::dynamic_config::DynamicConfig;
 use trz_gateway_common::dynamic_config::has_diff::DiffArc;
 
+use super::language_server::LanguageServer;
+use super::profile::LaunchProfile;
 use super::types::ConfigTypes;
 use super::types::Password;
 use super::types::RuntimeTypes;
@@ -22,12 +25,40 @@ pub struct ServerConfig<T: ConfigTypes = RuntimeTypes> {
     #[serde(rename = "terminal-shell", alias = "terminal_shell")]
     pub terminal_shell: Option<String>,
 
+    /// The named profiles to launch new terminals with.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, LaunchProfile>,
+
+    /// The Unix socket of the session holder that keeps the shells alive across restarts.
+    pub session_holder: T::MaybePath,
+
+    /// The folder where the terminal output that no longer fits in the scrollback is kept.
+    pub scrollback_spill: T::MaybePath,
+
+    /// How many bytes of spilled output to keep per terminal, the oldest output is rotated out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrollback_spill_limit: Option<u64>,
+
+    /// Which clients can resize a terminal that several clients are following.
+    #[serde(default)]
+    pub size_policy: SizePolicy,
+
+    /// The folder where terminal recordings are stored.
+    pub recordings: T::Path,
+
     /// The folder where deleted text-editor files are moved.
     pub trash: T::Path,
 
     /// The folder, relative to a Git repository root, where deleted Git files are moved.
     pub git_trash: T::MaybePath,
 
+    /// How long deleted text-editor files stay in the trash before they can be purged.
+    pub trash_max_age: T::Duration,
+
+    /// The language servers of the text editor, by language.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub language_servers: BTreeMap<String, LanguageServer>,
+
     /// The folder, relative to a Git repository root, where Tantivy indexes are stored.
     pub tantivy_cache: T::Path,
 
@@ -60,6 +91,18 @@ pub struct ServerConfig<T: ConfigTypes = RuntimeTypes> {
     pub certificate_renewal_threshold: T::Duration,
 }
 
+/// Decides which clients can change the size of the pty.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SizePolicy {
+    /// The last client that asked for a size wins.
+    #[default]
+    Latest,
+
+    /// Only the client that holds the exclusive lease can resize the pty.
+    Exclusive,
+}
+
 #[derive(Clone)]
 pub struct DynamicServerConfig(pub(super) Arc<DynamicConfig<DiffArc<ServerConfig>>>);
 
@@ -152,6 +152,16 @@ pub fn run_server() -> Result<(), RunServerError> {
     }
 
     std::env::set_current_dir(home()).map_err(RunServerError::SetCurrentDir)?;
+
+    #[cfg(feature = "terminal")]
+    if cli.action == Action::SessionHolder {
+        let session_holder = config.server.session_holder.as_deref();
+        let session_holder = session_holder.ok_or(RunServerError::SessionHolderNotConfigured)?;
+        return Ok(crate::processes::session_holder::server::run(
+            session_holder,
+        )?);
+    }
+
     if cli.action == Action::Start {
         self::daemonize::daemonize(&config.server)?;
     }
@@ -240,6 +250,16 @@ async fn run_server_async(cli: Cli, config: Config) -> Result<(), RunServerError
         remote_fn_service::streaming::setup();
     }
 
+    #[cfg(feature = "terminal")]
+    if let Some(session_holder) = server_config.with(|server| server.session_holder.clone()) {
+        let scrollback = |terminal_def: &crate::api::shared::terminal_schema::TerminalDef| {
+            let profile_name = terminal_def.profile.as_deref();
+            let profile = server_config.with(|server| server.launch_profile(profile_name));
+            profile.unwrap_or_default().scrollback_size()
+        };
+        crate::processes::session_holder::restore(session_holder, scrollback).await;
+    }
+
     let crash = crash
         .then(|crash| {
             let crash = crash
@@ -321,6 +341,14 @@ pub enum RunServerError {
 
     #[error("[{n}] {0}", n = self.name())]
     EnableTracing(#[from] EnableTracingError),
+
+    #[cfg(feature = "terminal")]
+    #[error("[{n}] --action session-holder requires --session-holder", n = self.name())]
+    SessionHolderNotConfigured,
+
+    #[cfg(feature = "terminal")]
+    #[error("[{n}] {0}", n = self.name())]
+    SessionHolder(#[from] crate::processes::session_holder::server::SessionHolderServerError),
 }
 
 #[cfg(feature = "logs-panel")]
@@ -7,6 +7,8 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::MutexGuard;
 use std::sync::OnceLock;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::Relaxed;
 use std::task::Poll;
 use std::task::ready;
 
@@ -16,20 +18,27 @@ use futures::channel::oneshot;
 use pin_project::pin_project;
 use pin_project::pinned_drop;
 use terrazzo_pty::lease::LeaseItem;
+use terrazzo_pty::lease::LeaseMode;
 use terrazzo_pty::lease::ProcessOutputLease;
 use tracing::debug;
 
 use crate::api::shared::terminal_schema::STREAMING_WINDOW_SIZE;
 use crate::terminal_id::TerminalId;
 
-fn streams() -> MutexGuard<'static, HashMap<TerminalId, Arc<Mutex<ThrottlingState>>>> {
-    static STREAMS: OnceLock<Mutex<HashMap<TerminalId, Arc<Mutex<ThrottlingState>>>>> =
+static NEXT_READER: AtomicUsize = AtomicUsize::new(1);
+
+/// Each reader of a terminal is throttled separately.
+type ReaderKey = (TerminalId, usize);
+
+fn streams() -> MutexGuard<'static, HashMap<ReaderKey, Arc<Mutex<ThrottlingState>>>> {
+    static STREAMS: OnceLock<Mutex<HashMap<ReaderKey, Arc<Mutex<ThrottlingState>>>>> =
         OnceLock::new();
     STREAMS.get_or_init(Mutex::default).lock().expect("streams")
 }
 
-pub fn ack(terminal_id: &TerminalId, ack: usize) {
-    let Some(throttling_state) = streams().get(terminal_id).cloned() else {
+pub fn ack(terminal_id: &TerminalId, reader: usize, ack: usize) {
+    let key = (terminal_id.clone(), reader);
+    let Some(throttling_state) = streams().get(&key).cloned() else {
         return;
     };
     let mut throttling_state = throttling_state.lock().expect("throttling_state");
@@ -43,16 +52,25 @@ pub fn ack(terminal_id: &TerminalId, ack: usize) {
     let _ = signal.send(());
 }
 
+/// The [LeaseMode] of a reader, if it is still streaming.
+pub fn lease_mode(terminal_id: &TerminalId, reader: usize) -> Option<LeaseMode> {
+    let key = (terminal_id.clone(), reader);
+    let throttling_state = streams().get(&key).cloned()?;
+    let throttling_state = throttling_state.lock().expect("throttling_state");
+    Some(throttling_state.lease_mode)
+}
+
 #[pin_project(PinnedDrop)]
 pub struct ThrottleProcessOutput {
     terminal_id: TerminalId,
+    reader: usize,
     state: Arc<Mutex<ThrottlingState>>,
     #[pin]
     stream: ProcessOutputLease,
 }
 
-#[derive(Default)]
 struct ThrottlingState {
+    lease_mode: LeaseMode,
     ack: usize,
     signal: Option<oneshot::Sender<()>>,
     throttled: Option<oneshot::Receiver<()>>,
@@ -61,20 +79,32 @@ struct ThrottlingState {
 #[pinned_drop]
 impl PinnedDrop for ThrottleProcessOutput {
     fn drop(self: Pin<&mut Self>) {
-        streams().remove(&self.terminal_id);
+        streams().remove(&(self.terminal_id.clone(), self.reader));
     }
 }
 
 impl ThrottleProcessOutput {
-    pub fn new(terminal_id: TerminalId, stream: ProcessOutputLease) -> Self {
-        let state: Arc<Mutex<ThrottlingState>> = Default::default();
-        streams().insert(terminal_id.clone(), state.clone());
+    pub fn new(terminal_id: TerminalId, lease_mode: LeaseMode, stream: ProcessOutputLease) -> Self {
+        let reader = NEXT_READER.fetch_add(1, Relaxed);
+        let state = Arc::new(Mutex::new(ThrottlingState {
+            lease_mode,
+            ack: 0,
+            signal: None,
+            throttled: None,
+        }));
+        streams().insert((terminal_id.clone(), reader), state.clone());
         Self {
             terminal_id,
+            reader,
             state,
             stream,
         }
     }
+
+    /// Identifies this reader, to [ack] the output it received.
+    pub fn reader(&self) -> usize {
+        self.reader
+    }
 }
 
 impl Stream for ThrottleProcessOutput {
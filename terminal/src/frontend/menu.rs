@@ -89,6 +89,13 @@ fn menu_items(
             show_menu_mut.clone(),
             hide_menu.clone(),
         ));
+        #[cfg(feature = "terminal")]
+        items.push(menu_item(
+            App::Replay,
+            tile.app.clone(),
+            show_menu_mut.clone(),
+            hide_menu.clone(),
+        ));
         #[cfg(feature = "text-editor")]
         items.push(menu_item(
             App::TextEditor,
@@ -192,6 +199,8 @@ impl App {
             App::Default => icons::menu(),
             #[cfg(feature = "terminal")]
             App::Terminal => icons::terminal(),
+            #[cfg(feature = "terminal")]
+            App::Replay => icons::replay(),
             #[cfg(feature = "text-editor")]
             App::TextEditor => icons::text_editor(),
             #[cfg(feature = "converter")]
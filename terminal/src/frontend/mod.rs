@@ -36,6 +36,11 @@ pub fn start() {
 }
 
 fn ui(main: XTemplate) {
+    #[cfg(feature = "terminal")]
+    if let Some(token) = crate::terminal::shared::share_token() {
+        let consumers = crate::terminal::shared::shared_terminal(main, token);
+        return std::mem::forget(consumers);
+    }
     let consumers = login(main, login::logged_in());
     std::mem::forget(consumers);
 }
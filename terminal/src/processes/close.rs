@@ -4,9 +4,15 @@ use terrazzo::http::StatusCode;
 use trz_gateway_common::http_error::IsHttpError;
 
 use super::get_processes;
+use super::recording;
+use super::session_holder;
+use super::shell_integration;
 use crate::terminal_id::TerminalId;
 
 pub fn close(terminal_id: &TerminalId) -> Result<(), CloseProcessError> {
+    session_holder::close_session(terminal_id);
+    let _not_recording = recording::stop(terminal_id);
+    shell_integration::stop(terminal_id);
     get_processes()
         .remove(terminal_id)
         .map(|_deleted_entry| ())
@@ -3,6 +3,9 @@
 
 use std::sync::Arc;
 use std::sync::OnceLock;
+use std::sync::atomic::AtomicI32;
+use std::sync::atomic::Ordering::SeqCst;
+use std::time::Duration;
 
 use dashmap::DashMap;
 use terrazzo_pty::lease::ProcessIoEntry;
@@ -12,9 +15,14 @@ use crate::api::shared::terminal_schema::TerminalDef;
 
 pub mod close;
 pub mod list;
+pub mod recording;
 pub mod resize;
+pub mod search;
+pub mod session_holder;
 pub mod set_title;
+pub mod shell_integration;
 pub mod stream;
+pub mod watch;
 pub mod write;
 
 pub fn get_processes() -> &'static DashMap<TerminalId, (TerminalDef, Arc<ProcessIoEntry>)> {
@@ -23,9 +31,31 @@ pub fn get_processes() -> &'static DashMap<TerminalId, (TerminalDef, Arc<Process
     PROCESSES.get_or_init(DashMap::new)
 }
 
+/// How long to wait for the shell to be reaped after its output was closed.
+pub const EXIT_STATUS_TIMEOUT: Duration = Duration::from_secs(1);
+
+static NEXT_TERMINAL_ID: AtomicI32 = AtomicI32::new(1);
+
 pub fn next_terminal_id() -> i32 {
-    use std::sync::atomic::AtomicI32;
-    use std::sync::atomic::Ordering::SeqCst;
-    static NEXT: AtomicI32 = AtomicI32::new(1);
-    NEXT.fetch_add(1, SeqCst)
+    NEXT_TERMINAL_ID.fetch_add(1, SeqCst)
+}
+
+/// Makes sure new terminals don't reuse the id of a restored terminal.
+fn bump_terminal_id(terminal_def: &TerminalDef) {
+    let id = terminal_def.address.id.to_string();
+    let id = id.rsplit('-').next().and_then(|id| id.parse().ok());
+    let next = id.unwrap_or(0).max(terminal_def.order) + 1;
+    NEXT_TERMINAL_ID.fetch_max(next, SeqCst);
+}
+
+/// A file name for the terminal, safe to use in any folder.
+pub fn file_name(terminal_id: &TerminalId) -> String {
+    terminal_id
+        .to_string()
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect()
 }
@@ -0,0 +1,339 @@
+//! Records terminals in asciicast v2 format.
+
+use std::fs::File;
+use std::io::LineWriter;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use dashmap::DashMap;
+use futures::StreamExt as _;
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+use terrazzo::http::StatusCode;
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+use trz_gateway_common::http_error::IsHttpError;
+
+use super::get_processes;
+use crate::api::shared::terminal_schema::RecordingInfo;
+use crate::api::shared::terminal_schema::Size;
+use crate::terminal::asciicast::Event;
+use crate::terminal::asciicast::EventKind;
+use crate::terminal::asciicast::Header;
+use crate::terminal::asciicast::VERSION;
+use crate::terminal_id::TerminalId;
+
+const EXTENSION: &str = "cast";
+
+fn get_recordings() -> &'static DashMap<TerminalId, Recording> {
+    static RECORDINGS: OnceLock<DashMap<TerminalId, Recording>> = OnceLock::new();
+    RECORDINGS.get_or_init(DashMap::new)
+}
+
+struct Recording {
+    events: mpsc::UnboundedSender<CastEvent>,
+    output: AbortHandle,
+}
+
+/// The events are written to the recording by a blocking task, in the order they were sent.
+enum CastEvent {
+    Output(Bytes),
+    Write(EventKind, String),
+}
+
+struct CastWriter {
+    file: LineWriter<File>,
+    start: Instant,
+
+    /// The end of the last output if it was cut in the middle of a UTF-8 character.
+    incomplete: Vec<u8>,
+}
+
+/// Starts recording a terminal into a new file in `folder`.
+pub fn start(
+    terminal_id: &TerminalId,
+    folder: &Path,
+    size: Size,
+    title: Option<String>,
+) -> Result<RecordingInfo, RecordingError> {
+    let entry = {
+        let Some(entry) = get_processes().get(terminal_id) else {
+            return Err(RecordingError::TerminalNotFound {
+                terminal_id: terminal_id.clone(),
+            });
+        };
+        entry.value().1.clone()
+    };
+    let recordings = get_recordings();
+    let dashmap::Entry::Vacant(vacant) = recordings.entry(terminal_id.clone()) else {
+        return Err(RecordingError::AlreadyRecording {
+            terminal_id: terminal_id.clone(),
+        });
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let name = format!("{}-{timestamp}.{EXTENSION}", super::file_name(terminal_id));
+    let path = folder.join(&name);
+    let header = Header {
+        version: VERSION,
+        width: size.cols,
+        height: size.rows,
+        timestamp: Some(timestamp),
+        title: title.clone(),
+    };
+    let writer = CastWriter::create(&path, &header).map_err(RecordingError::Create)?;
+    info!(%terminal_id, ?path, "Start recording");
+    let events = writer.spawn(terminal_id.clone(), path);
+
+    let output = entry.follow_output();
+    drop(entry);
+    let output = tokio::spawn({
+        let events = events.clone();
+        let terminal_id = terminal_id.clone();
+        async move {
+            let mut output = std::pin::pin!(output);
+            while let Some(data) = output.next().await {
+                let Ok(data) = data else { continue };
+                if events.send(CastEvent::Output(data)).is_err() {
+                    break;
+                }
+            }
+            debug!(%terminal_id, "The recorded terminal has ended");
+            get_recordings().remove_if(&terminal_id, |_, recording| {
+                recording.events.same_channel(&events)
+            });
+        }
+    });
+    vacant.insert(Recording {
+        events,
+        output: output.abort_handle(),
+    });
+    Ok(RecordingInfo {
+        name,
+        title,
+        timestamp: Some(timestamp),
+    })
+}
+
+/// Stops recording a terminal.
+pub fn stop(terminal_id: &TerminalId) -> Result<(), RecordingError> {
+    let Some((_, recording)) = get_recordings().remove(terminal_id) else {
+        return Err(RecordingError::NotRecording {
+            terminal_id: terminal_id.clone(),
+        });
+    };
+    recording.output.abort();
+    debug!(%terminal_id, "Stopping the recording");
+    Ok(())
+}
+
+/// Records an event, if the terminal is being recorded.
+pub fn record(terminal_id: &TerminalId, kind: EventKind, data: impl FnOnce() -> String) {
+    let Some(events) = get_recordings().get(terminal_id).map(|r| r.events.clone()) else {
+        return;
+    };
+    let _ = events.send(CastEvent::Write(kind, data()));
+}
+
+/// Lists the recordings in `folder`, most recent first.
+pub fn list(folder: &Path) -> Result<Vec<RecordingInfo>, RecordingError> {
+    let read_dir = match std::fs::read_dir(folder) {
+        Ok(read_dir) => read_dir,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(error) => return Err(RecordingError::List(error)),
+    };
+    let mut recordings = vec![];
+    for entry in read_dir {
+        let path = entry.map_err(RecordingError::List)?.path();
+        if path
+            .extension()
+            .is_none_or(|extension| extension != EXTENSION)
+        {
+            continue;
+        }
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let header = read_header(&path);
+        recordings.push(RecordingInfo {
+            name: name.to_owned(),
+            title: header.as_ref().and_then(|header| header.title.clone()),
+            timestamp: header.and_then(|header| header.timestamp),
+        });
+    }
+    recordings.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then(a.name.cmp(&b.name)));
+    Ok(recordings)
+}
+
+fn read_header(path: &Path) -> Option<Header> {
+    use std::io::BufRead as _;
+    let file = File::open(path).ok()?;
+    let mut line = String::new();
+    std::io::BufReader::new(file).read_line(&mut line).ok()?;
+    serde_json::from_str(&line).ok()
+}
+
+/// Reads a recording from `folder`.
+pub fn load(folder: &Path, name: &str) -> Result<String, RecordingError> {
+    let is_valid = Path::new(name).file_name() == Some(name.as_ref())
+        && Path::new(name).extension() == Some(EXTENSION.as_ref());
+    if !is_valid {
+        return Err(RecordingError::InvalidName(name.to_owned()));
+    }
+    std::fs::read_to_string(folder.join(name)).map_err(RecordingError::Load)
+}
+
+impl CastWriter {
+    fn create(path: &Path, header: &Header) -> std::io::Result<Self> {
+        if let Some(folder) = path.parent() {
+            std::fs::create_dir_all(folder)?;
+        }
+        let mut file = LineWriter::new(File::create_new(path)?);
+        serde_json::to_writer(&mut file, header)?;
+        file.write_all(b"\n")?;
+        Ok(Self {
+            file,
+            start: Instant::now(),
+            incomplete: vec![],
+        })
+    }
+
+    /// Writes the events until the recording is stopped, and the file is closed.
+    fn spawn(mut self, terminal_id: TerminalId, path: PathBuf) -> mpsc::UnboundedSender<CastEvent> {
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+        tokio::task::spawn_blocking(move || {
+            while let Some(event) = events_rx.blocking_recv() {
+                match event {
+                    CastEvent::Output(data) => self.output(&data),
+                    CastEvent::Write(kind, data) => self.write(kind, data),
+                }
+            }
+            if let Err(error) = self.file.flush() {
+                warn!(?path, "Failed to flush the recording: {error}");
+            }
+            info!(%terminal_id, ?path, "Stop recording");
+        });
+        events_tx
+    }
+
+    fn output(&mut self, data: &[u8]) {
+        let mut pending = std::mem::take(&mut self.incomplete);
+        pending.extend_from_slice(data);
+        let data = pending;
+        let valid = match std::str::from_utf8(&data) {
+            Ok(_) => data.len(),
+            Err(error) if error.error_len().is_none() => error.valid_up_to(),
+            Err(_) => data.len(),
+        };
+        self.incomplete = data[valid..].to_vec();
+        if valid > 0 {
+            self.write(
+                EventKind::Output,
+                String::from_utf8_lossy(&data[..valid]).into_owned(),
+            );
+        }
+    }
+
+    fn write(&mut self, kind: EventKind, data: String) {
+        let time = self.start.elapsed().as_micros() as f64 / 1_000_000.;
+        let result = serde_json::to_writer(&mut self.file, &Event(time, kind, data))
+            .map_err(std::io::Error::from)
+            .and_then(|()| self.file.write_all(b"\n"));
+        if let Err(error) = result {
+            warn!("Failed to record: {error}");
+        }
+    }
+}
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+pub enum RecordingError {
+    #[error("[{n}] Terminal not found {terminal_id}", n = self.name())]
+    TerminalNotFound { terminal_id: TerminalId },
+
+    #[error("[{n}] Terminal {terminal_id} is already being recorded", n = self.name())]
+    AlreadyRecording { terminal_id: TerminalId },
+
+    #[error("[{n}] Terminal {terminal_id} is not being recorded", n = self.name())]
+    NotRecording { terminal_id: TerminalId },
+
+    #[error("[{n}] Failed to create the recording: {0}", n = self.name())]
+    Create(std::io::Error),
+
+    #[error("[{n}] Failed to list the recordings: {0}", n = self.name())]
+    List(std::io::Error),
+
+    #[error("[{n}] Invalid recording name '{0}'", n = self.name())]
+    InvalidName(String),
+
+    #[error("[{n}] Failed to load the recording: {0}", n = self.name())]
+    Load(std::io::Error),
+}
+
+impl IsHttpError for RecordingError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::TerminalNotFound { .. } | Self::NotRecording { .. } => StatusCode::NOT_FOUND,
+            Self::AlreadyRecording { .. } => StatusCode::CONFLICT,
+            Self::InvalidName { .. } => StatusCode::BAD_REQUEST,
+            Self::Create { .. } | Self::List { .. } | Self::Load { .. } => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CastWriter;
+    use crate::terminal::asciicast::Event;
+    use crate::terminal::asciicast::EventKind;
+    use crate::terminal::asciicast::Header;
+
+    #[test]
+    fn asciicast_v2() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("T-1.cast");
+        let header = Header {
+            version: 2,
+            width: 80,
+            height: 24,
+            timestamp: Some(1700000000),
+            title: Some("Terminal".into()),
+        };
+        let mut writer = CastWriter::create(&path, &header).unwrap();
+        writer.output("é".as_bytes().split_at(1).0);
+        writer.output(b"\xa9\r\n");
+        writer.write(EventKind::Input, "ls\r".into());
+        writer.write(EventKind::Resize, "100x30".into());
+        drop(writer);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let mut lines = content.lines();
+        let actual: Header = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(header, actual);
+        let events = lines
+            .map(|line| serde_json::from_str::<Event>(line).unwrap())
+            .map(|Event(_, kind, data)| (kind, data))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (EventKind::Output, "é\r\n".to_owned()),
+                (EventKind::Input, "ls\r".to_owned()),
+                (EventKind::Resize, "100x30".to_owned()),
+            ],
+            events
+        );
+    }
+}
@@ -3,13 +3,14 @@ use std::time::Duration;
 use nameth::NamedEnumValues as _;
 use nameth::nameth;
 use terrazzo::http::StatusCode;
-use terrazzo_pty::ProcessInput;
 use terrazzo_pty::pty::PtyError;
 use terrazzo_pty::size::Size;
 use tracing::debug;
 use trz_gateway_common::http_error::IsHttpError;
 
 use super::get_processes;
+use super::recording;
+use crate::terminal::asciicast::EventKind;
 use crate::terminal_id::TerminalId;
 
 pub async fn resize(
@@ -29,7 +30,6 @@ pub async fn resize(
         entry.value().1.clone()
     };
     let input = entry.input().await;
-    let ProcessInput(input) = &*input;
     if force {
         debug!("Forcing resize");
         let () = input
@@ -40,6 +40,7 @@ pub async fn resize(
     let () = input
         .resize(Size::new(rows as u16, cols as u16))
         .map_err(ResizeError::Resize)?;
+    recording::record(terminal_id, EventKind::Resize, || format!("{cols}x{rows}"));
     debug!("Done");
     Ok(())
 }
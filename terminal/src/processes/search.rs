@@ -0,0 +1,159 @@
+//! Searches the scrollback of the terminals.
+
+use regex::Regex;
+
+use super::get_processes;
+use crate::api::shared::terminal_schema::ScrollbackMatch;
+use crate::api::shared::terminal_schema::TerminalAddress;
+
+/// The maximum number of matches returned for each terminal.
+const MAX_MATCHES: usize = 100;
+
+/// The number of lines returned before and after each match.
+const CONTEXT: usize = 2;
+
+/// How much of the history spilled to disk is searched, the most recent first.
+const MAX_SPILLED_HISTORY: u64 = 8 * 1024 * 1024;
+
+/// Searches the retained output of all the local terminals.
+pub fn search(regex: &Regex) -> Vec<ScrollbackMatch> {
+    let processes = get_processes()
+        .iter()
+        .map(|entry| entry.value().clone())
+        .collect::<Vec<_>>();
+    let mut matches = vec![];
+    for (terminal_def, process) in processes {
+        let text = strip_ansi(&process.scrollback(MAX_SPILLED_HISTORY).concat());
+        let title = terminal_def.title;
+        let title = title.override_title.unwrap_or(title.shell_title);
+        matches.extend(find(regex, &text, &terminal_def.address, &title));
+    }
+    matches
+}
+
+fn find(
+    regex: &Regex,
+    text: &str,
+    terminal: &TerminalAddress,
+    title: &str,
+) -> Vec<ScrollbackMatch> {
+    let lines = text.lines().collect::<Vec<_>>();
+    let to_strings = |lines: &[&str]| lines.iter().map(|line| line.to_string()).collect();
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| regex.is_match(line))
+        .take(MAX_MATCHES)
+        .map(|(i, line)| ScrollbackMatch {
+            terminal: terminal.clone(),
+            title: title.to_owned(),
+            line_number: i,
+            before: to_strings(&lines[i.saturating_sub(CONTEXT)..i]),
+            line: line.to_string(),
+            after: to_strings(&lines[i + 1..lines.len().min(i + 1 + CONTEXT)]),
+        })
+        .collect()
+}
+
+/// Removes escape sequences and control characters, and applies carriage returns.
+pub(super) fn strip_ansi(bytes: &[u8]) -> String {
+    #[derive(Clone, Copy)]
+    enum State {
+        Ground,
+        Escape,
+        /// The intermediate bytes of a two-byte sequence like `ESC ( B`.
+        Designate,
+        Csi,
+        /// OSC, DCS, APC, PM and SOS sequences, terminated by BEL or ST.
+        String,
+        StringEscape,
+    }
+
+    let mut text = Vec::with_capacity(bytes.len());
+    let mut state = State::Ground;
+    let mut line_start = 0;
+    let mut carriage_return = false;
+    for &byte in bytes {
+        state = match (state, byte) {
+            (State::Ground, 0x1b) => State::Escape,
+            (State::Ground, b'\n') => {
+                text.push(b'\n');
+                line_start = text.len();
+                carriage_return = false;
+                State::Ground
+            }
+            (State::Ground, b'\r') => {
+                carriage_return = true;
+                State::Ground
+            }
+            (State::Ground, 0x00..=0x08 | 0x0a..=0x1f | 0x7f) => State::Ground,
+            (State::Ground, _) => {
+                if carriage_return {
+                    text.truncate(line_start);
+                    carriage_return = false;
+                }
+                text.push(byte);
+                State::Ground
+            }
+            (State::Escape, b'[') => State::Csi,
+            (State::Escape, b']' | b'P' | b'X' | b'^' | b'_') => State::String,
+            (State::Escape | State::Designate, 0x20..=0x2f) => State::Designate,
+            (State::Escape | State::Designate, _) => State::Ground,
+            (State::Csi, 0x40..=0x7e) => State::Ground,
+            (State::Csi, _) => State::Csi,
+            (State::String, 0x07) => State::Ground,
+            (State::String, 0x1b) => State::StringEscape,
+            (State::String, _) => State::String,
+            (State::StringEscape, b'\\') => State::Ground,
+            (State::StringEscape, _) => State::String,
+        };
+    }
+    String::from_utf8_lossy(&text).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use super::find;
+    use super::strip_ansi;
+    use crate::api::client_address::ClientAddress;
+    use crate::api::shared::terminal_schema::TerminalAddress;
+
+    #[test]
+    fn strip_ansi_sequences() {
+        let output = b"\x1b]0;title\x07\x1b[1;32muser\x1b[0m:~$ ls\r\n\x1b(Bfile\r\n";
+        assert_eq!("user:~$ ls\nfile\n", strip_ansi(output));
+        assert_eq!("100%\n", strip_ansi(b" 10%\r 50%\r100%\r\n"));
+        assert_eq!(
+            "caf\u{e9}\tbar",
+            strip_ansi("caf\u{e9}\x07\tbar".as_bytes())
+        );
+    }
+
+    #[test]
+    fn find_with_context() {
+        let terminal = TerminalAddress {
+            id: "T1".into(),
+            via: ClientAddress::default(),
+        };
+        let regex = Regex::new("err(or)?").unwrap();
+        let matches = find(&regex, "a\nb\nerror 1\nc\nd\ne\nerr 2", &terminal, "Title");
+        let matches = matches
+            .iter()
+            .map(|m| (m.line_number, m.before.clone(), &*m.line, m.after.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (
+                    2,
+                    vec!["a".to_owned(), "b".to_owned()],
+                    "error 1",
+                    vec!["c".to_owned(), "d".to_owned()]
+                ),
+                (6, vec!["d".to_owned(), "e".to_owned()], "err 2", vec![]),
+            ],
+            matches
+        );
+    }
+}
@@ -0,0 +1,377 @@
+//! Keeps the shells alive across daemon restarts.
+//!
+//! When enabled, the ptys are owned by a detached session holder process, and
+//! the daemon reattaches to them over a Unix socket after it restarts.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::task::Poll;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::Stream;
+use futures::channel::oneshot;
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+use terrazzo_pty::ProcessIO;
+use terrazzo_pty::PtyInput;
+use terrazzo_pty::exit_status::ExitStatusRx;
+use terrazzo_pty::lease::ProcessIoEntry;
+use terrazzo_pty::pty::PtyError;
+use terrazzo_pty::size::Size;
+use tokio::io::AsyncWrite;
+use tokio::net::UnixStream;
+use tokio::net::unix::OwnedReadHalf;
+use tokio::net::unix::OwnedWriteHalf;
+use tokio::sync::mpsc;
+use tracing::Instrument as _;
+use tracing::debug;
+use tracing::info;
+use tracing::info_span;
+use tracing::warn;
+
+use self::protocol::Frame;
+use self::protocol::Request;
+use self::protocol::Response;
+use super::get_processes;
+use crate::api::shared::terminal_schema::ExitStatus;
+use crate::api::shared::terminal_schema::TerminalDef;
+use crate::backend::config::profile::LaunchProfile;
+use crate::terminal_id::TerminalId;
+
+mod protocol;
+pub mod server;
+#[cfg(test)]
+mod tests;
+
+/// How long to wait for a freshly spawned session holder to listen.
+const SPAWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Client of the session holder listening at `socket`.
+pub struct SessionHolder {
+    socket: Arc<Path>,
+}
+
+/// Returns the session holder, if sessions are persistent.
+pub fn get() -> Option<&'static SessionHolder> {
+    SESSION_HOLDER.get()
+}
+
+static SESSION_HOLDER: OnceLock<SessionHolder> = OnceLock::new();
+
+/// Enables persistent sessions and reattaches to the sessions that survived the last daemon.
+///
+/// The `scrollback` of each session is the one of its launch profile.
+pub async fn restore(socket: Arc<Path>, scrollback: impl Fn(&TerminalDef) -> usize) {
+    let session_holder = SESSION_HOLDER.get_or_init(|| SessionHolder::new(socket));
+    let terminal_defs = match session_holder.list().await {
+        Ok(terminal_defs) => terminal_defs,
+        Err(SessionHolderError::Connect(error)) => {
+            return debug!("No session holder to restore from: {error}");
+        }
+        Err(error) => return warn!("Failed to list sessions: {error}"),
+    };
+    for terminal_def in terminal_defs {
+        let terminal_id = terminal_def.address.id.clone();
+        let scrollback = scrollback(&terminal_def);
+        let process_io = session_holder.attach(&terminal_id, /* rewind = */ true, scrollback);
+        match process_io.await {
+            Ok(process_io) => {
+                info!(%terminal_id, "Restored session");
+                super::bump_terminal_id(&terminal_def);
+                let entry = ProcessIoEntry::new(process_io);
+                super::shell_integration::track(&terminal_id, &entry);
+                get_processes().insert(terminal_id, (terminal_def, entry));
+            }
+            Err(error) => warn!(%terminal_id, "Failed to restore session: {error}"),
+        }
+    }
+}
+
+/// Pushes the new title, order or tile of a terminal to the session holder.
+pub fn sync_terminal_def(terminal_def: &TerminalDef) {
+    let Some(session_holder) = get() else {
+        return;
+    };
+    let terminal_def = terminal_def.clone();
+    tokio::spawn(async move {
+        let terminal_id = terminal_def.address.id.clone();
+        if let Err(error) = session_holder.set_def(terminal_def).await {
+            warn!(%terminal_id, "Failed to sync the session: {error}");
+        }
+    });
+}
+
+/// Terminates the shell held by the session holder.
+pub fn close_session(terminal_id: &TerminalId) {
+    let Some(session_holder) = get() else {
+        return;
+    };
+    let terminal_id = terminal_id.clone();
+    tokio::spawn(async move {
+        if let Err(error) = session_holder.close(terminal_id.clone()).await {
+            warn!(%terminal_id, "Failed to close the session: {error}");
+        }
+    });
+}
+
+impl SessionHolder {
+    pub fn new(socket: Arc<Path>) -> Self {
+        Self { socket }
+    }
+
+    pub async fn list(&self) -> Result<Vec<TerminalDef>, SessionHolderError> {
+        let (_, response) = self.call(Request::List).await?;
+        match response {
+            Response::List(terminal_defs) => Ok(terminal_defs),
+            response => Err(SessionHolderError::UnexpectedResponse(format!(
+                "{response:?}"
+            ))),
+        }
+    }
+
+    /// Starts a new shell in the session holder, spawning the session holder if necessary.
+    pub async fn open(
+        &self,
+        terminal_def: TerminalDef,
+        scrollback: usize,
+        profile: LaunchProfile,
+    ) -> Result<ProcessIO, SessionHolderError> {
+        let terminal_id = terminal_def.address.id.clone();
+        let request = Request::Open {
+            terminal_def,
+            scrollback,
+            profile: Box::new(profile),
+        };
+        let stream = match self.connect().await {
+            Ok(stream) => stream,
+            Err(SessionHolderError::Connect(error)) => {
+                debug!("Spawning the session holder: {error}");
+                self.spawn().await?
+            }
+            Err(error) => return Err(error),
+        };
+        let (stream, response) = call(stream, request).await?;
+        self.process_io(terminal_id, stream, response, scrollback)
+    }
+
+    pub async fn attach(
+        &self,
+        terminal_id: &TerminalId,
+        rewind: bool,
+        scrollback: usize,
+    ) -> Result<ProcessIO, SessionHolderError> {
+        let request = Request::Attach {
+            terminal_id: terminal_id.clone(),
+            rewind,
+        };
+        let (stream, response) = self.call(request).await?;
+        self.process_io(terminal_id.clone(), stream, response, scrollback)
+    }
+
+    pub async fn set_def(&self, terminal_def: TerminalDef) -> Result<(), SessionHolderError> {
+        self.call_ok(Request::SetDef(terminal_def)).await
+    }
+
+    pub async fn close(&self, terminal_id: TerminalId) -> Result<(), SessionHolderError> {
+        self.call_ok(Request::Close(terminal_id)).await
+    }
+
+    async fn resize(&self, terminal_id: TerminalId, size: Size) -> Result<(), SessionHolderError> {
+        self.call_ok(Request::Resize {
+            terminal_id,
+            rows: size.rows(),
+            cols: size.cols(),
+        })
+        .await
+    }
+
+    fn process_io(
+        &self,
+        terminal_id: TerminalId,
+        stream: (OwnedReadHalf, OwnedWriteHalf),
+        response: Response,
+        scrollback: usize,
+    ) -> Result<ProcessIO, SessionHolderError> {
+        match response {
+            Response::Ok => {}
+            Response::Error(error) => return Err(SessionHolderError::Remote(error)),
+            response => {
+                return Err(SessionHolderError::UnexpectedResponse(format!(
+                    "{response:?}"
+                )));
+            }
+        }
+        let (reader, writer) = stream;
+        let (exit_status_tx, exit_status_rx) = oneshot::channel();
+        let exit_status = ExitStatusRx::spawn(async move {
+            let exit_status: ExitStatus = exit_status_rx.await.ok().flatten()?;
+            Some(exit_status.into())
+        });
+        let session_holder = SessionHolder::new(self.socket.clone());
+        let input = RemoteInput::new(session_holder, terminal_id, writer);
+        let output = read_output(reader, exit_status_tx);
+        Ok(ProcessIO::from_parts(
+            input,
+            output,
+            scrollback,
+            exit_status,
+        ))
+    }
+
+    async fn call_ok(&self, request: Request) -> Result<(), SessionHolderError> {
+        match self.call(request).await? {
+            (_, Response::Ok) => Ok(()),
+            (_, Response::Error(error)) => Err(SessionHolderError::Remote(error)),
+            (_, response) => Err(SessionHolderError::UnexpectedResponse(format!(
+                "{response:?}"
+            ))),
+        }
+    }
+
+    async fn call(
+        &self,
+        request: Request,
+    ) -> Result<((OwnedReadHalf, OwnedWriteHalf), Response), SessionHolderError> {
+        call(self.connect().await?, request).await
+    }
+
+    async fn connect(&self) -> Result<UnixStream, SessionHolderError> {
+        UnixStream::connect(&self.socket)
+            .await
+            .map_err(SessionHolderError::Connect)
+    }
+
+    /// Starts the session holder in its own process group so it outlives the daemon.
+    async fn spawn(&self) -> Result<UnixStream, SessionHolderError> {
+        let current_exe = std::env::current_exe().map_err(SessionHolderError::Spawn)?;
+        let mut command = tokio::process::Command::new(current_exe);
+        command
+            .args(["--action", "session-holder", "--session-holder"])
+            .arg(&*self.socket)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .process_group(0);
+        let _child = command.spawn().map_err(SessionHolderError::Spawn)?;
+        let deadline = tokio::time::Instant::now() + SPAWN_TIMEOUT;
+        loop {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            match self.connect().await {
+                Ok(stream) => return Ok(stream),
+                Err(error) if tokio::time::Instant::now() > deadline => return Err(error),
+                Err(_) => continue,
+            }
+        }
+    }
+}
+
+async fn call(
+    stream: UnixStream,
+    request: Request,
+) -> Result<((OwnedReadHalf, OwnedWriteHalf), Response), SessionHolderError> {
+    let (mut reader, mut writer) = stream.into_split();
+    Frame::Request(request).write(&mut writer).await?;
+    match Frame::read(&mut reader).await? {
+        Some(Frame::Response(response)) => Ok(((reader, writer), response)),
+        frame => Err(SessionHolderError::UnexpectedResponse(format!("{frame:?}"))),
+    }
+}
+
+/// Streams the output of a shell held by the session holder.
+fn read_output(
+    reader: OwnedReadHalf,
+    exit_status_tx: oneshot::Sender<Option<ExitStatus>>,
+) -> impl Stream<Item = std::io::Result<Bytes>> {
+    futures::stream::unfold(Some((reader, exit_status_tx)), |state| async move {
+        let (mut reader, exit_status_tx) = state?;
+        match Frame::read(&mut reader).await {
+            Ok(Some(Frame::Data(data))) => {
+                Some((Ok(Bytes::from(data)), Some((reader, exit_status_tx))))
+            }
+            Ok(Some(Frame::Exit(exit_status))) => {
+                let _ = exit_status_tx.send(exit_status);
+                None
+            }
+            Ok(None) => None,
+            Ok(Some(frame)) => {
+                let error = protocol::invalid_data(format!("Unexpected frame {frame:?}"));
+                Some((Err(error), None))
+            }
+            Err(error) => Some((Err(error), None)),
+        }
+    })
+}
+
+/// Writes to a shell held by the session holder.
+struct RemoteInput {
+    writer: OwnedWriteHalf,
+    resize_tx: mpsc::UnboundedSender<Size>,
+}
+
+impl RemoteInput {
+    fn new(session_holder: SessionHolder, terminal_id: TerminalId, writer: OwnedWriteHalf) -> Self {
+        let (resize_tx, mut resize_rx) = mpsc::unbounded_channel();
+        let task = async move {
+            while let Some(size) = resize_rx.recv().await {
+                if let Err(error) = session_holder.resize(terminal_id.clone(), size).await {
+                    warn!("Failed to resize: {error}");
+                }
+            }
+        };
+        tokio::spawn(task.instrument(info_span!("Resize")));
+        Self { writer, resize_tx }
+    }
+}
+
+impl PtyInput for RemoteInput {
+    fn resize(&self, size: Size) -> Result<(), PtyError> {
+        self.resize_tx
+            .send(size)
+            .map_err(|_| PtyError::Remote(std::io::Error::from(std::io::ErrorKind::BrokenPipe)))
+    }
+}
+
+impl AsyncWrite for RemoteInput {
+    fn poll_write(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        std::pin::Pin::new(&mut self.writer).poll_write(cx, buf)
+    }
+
+    fn poll_flush(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.writer).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.writer).poll_shutdown(cx)
+    }
+}
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+pub enum SessionHolderError {
+    #[error("[{n}] Failed to connect to the session holder: {0}", n = self.name())]
+    Connect(std::io::Error),
+
+    #[error("[{n}] Failed to spawn the session holder: {0}", n = self.name())]
+    Spawn(std::io::Error),
+
+    #[error("[{n}] {0}", n = self.name())]
+    IO(#[from] std::io::Error),
+
+    #[error("[{n}] {0}", n = self.name())]
+    Remote(String),
+
+    #[error("[{n}] Unexpected response: {0}", n = self.name())]
+    UnexpectedResponse(String),
+}
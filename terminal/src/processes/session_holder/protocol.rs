@@ -0,0 +1,121 @@
+//! The wire format between the daemon and the session holder.
+//!
+//! Every message is a frame: `[tag: u8][length: u32][payload]`.
+//!
+//! A connection starts with a [Request] and its [Response]. After a successful
+//! [Request::Open] or [Request::Attach], the daemon streams raw input bytes and
+//! the holder streams [Frame::Data] until the shell exits with [Frame::Exit].
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt as _;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt as _;
+
+use crate::api::shared::terminal_schema::ExitStatus;
+use crate::api::shared::terminal_schema::TerminalDef;
+use crate::backend::config::profile::LaunchProfile;
+use crate::terminal_id::TerminalId;
+
+/// Frames larger than this are rejected.
+const MAX_FRAME_SIZE: u32 = 16 * 1024 * 1024;
+
+const REQUEST: u8 = b'Q';
+const RESPONSE: u8 = b'A';
+const DATA: u8 = b'D';
+const EXIT: u8 = b'X';
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Request {
+    /// Lists the sessions held by the holder.
+    List,
+
+    /// Starts a new shell and attaches to it.
+    Open {
+        terminal_def: TerminalDef,
+        scrollback: usize,
+        profile: Box<LaunchProfile>,
+    },
+
+    /// Attaches to a running shell, revoking any previous attachment.
+    Attach {
+        terminal_id: TerminalId,
+        rewind: bool,
+    },
+
+    /// Updates the title, order or tile of a session.
+    SetDef(TerminalDef),
+
+    Resize {
+        terminal_id: TerminalId,
+        rows: u16,
+        cols: u16,
+    },
+
+    /// Terminates a shell.
+    Close(TerminalId),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Response {
+    Ok,
+    List(Vec<TerminalDef>),
+    Error(String),
+}
+
+#[derive(Debug)]
+pub enum Frame {
+    Request(Request),
+    Response(Response),
+    Data(Vec<u8>),
+    Exit(Option<ExitStatus>),
+}
+
+impl Frame {
+    pub async fn write(&self, writer: &mut (impl AsyncWrite + Unpin)) -> std::io::Result<()> {
+        let (tag, payload) = match self {
+            Self::Request(request) => (REQUEST, serde_json::to_vec(request)?),
+            Self::Response(response) => (RESPONSE, serde_json::to_vec(response)?),
+            Self::Data(data) => (DATA, data.clone()),
+            Self::Exit(exit_status) => (EXIT, serde_json::to_vec(exit_status)?),
+        };
+        let mut buffer = Vec::with_capacity(5 + payload.len());
+        buffer.push(tag);
+        buffer.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        buffer.extend_from_slice(&payload);
+        writer.write_all(&buffer).await?;
+        writer.flush().await
+    }
+
+    /// Reads the next frame, or `None` if the connection was closed.
+    pub async fn read(reader: &mut (impl AsyncRead + Unpin)) -> std::io::Result<Option<Self>> {
+        let tag = match reader.read_u8().await {
+            Ok(tag) => tag,
+            Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        let len = reader.read_u32().await?;
+        if len > MAX_FRAME_SIZE {
+            return Err(invalid_data(format!("Frame too large: {len}")));
+        }
+        let mut payload = vec![0; len as usize];
+        reader.read_exact(&mut payload).await?;
+        Ok(Some(match tag {
+            REQUEST => Self::Request(parse(&payload)?),
+            RESPONSE => Self::Response(parse(&payload)?),
+            DATA => Self::Data(payload),
+            EXIT => Self::Exit(parse(&payload)?),
+            tag => return Err(invalid_data(format!("Unknown frame tag {tag:#x}"))),
+        }))
+    }
+}
+
+fn parse<T: DeserializeOwned>(payload: &[u8]) -> std::io::Result<T> {
+    Ok(serde_json::from_slice(payload)?)
+}
+
+pub fn invalid_data(message: impl Into<String>) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.into())
+}
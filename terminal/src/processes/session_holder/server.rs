@@ -0,0 +1,271 @@
+//! The session holder: a detached process that owns the ptys.
+
+use std::os::unix::fs::DirBuilderExt as _;
+use std::os::unix::fs::PermissionsExt as _;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Weak;
+
+use futures::StreamExt as _;
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+use terrazzo_pty::ProcessIO;
+use terrazzo_pty::lease::LeaseItem;
+use terrazzo_pty::lease::LeaseMode;
+use terrazzo_pty::lease::ProcessIoEntry;
+use terrazzo_pty::lease::Rewind;
+use terrazzo_pty::size::Size;
+use tokio::io::AsyncReadExt as _;
+use tokio::io::AsyncWriteExt as _;
+use tokio::net::UnixListener;
+use tokio::net::UnixStream;
+use tokio::net::unix::OwnedReadHalf;
+use tokio::net::unix::OwnedWriteHalf;
+use tokio::sync::Notify;
+use tracing::Instrument as _;
+use tracing::debug;
+use tracing::info;
+use tracing::info_span;
+use tracing::warn;
+
+use super::protocol::Frame;
+use super::protocol::Request;
+use super::protocol::Response;
+use crate::api::client_address::ClientAddress;
+use crate::api::shared::terminal_schema::TerminalDef;
+use crate::backend::config::profile::LaunchProfile;
+use crate::processes::EXIT_STATUS_TIMEOUT;
+use crate::processes::get_processes;
+use crate::processes::list::list;
+use crate::terminal_id::TerminalId;
+
+/// Runs the session holder until its last session ends.
+#[tokio::main]
+pub async fn run(socket: &Path) -> Result<(), SessionHolderServerError> {
+    if UnixStream::connect(socket).await.is_ok() {
+        info!(?socket, "A session holder is already running");
+        return Ok(());
+    }
+    let _ = std::fs::remove_file(socket);
+    let listener = bind(socket).map_err(SessionHolderServerError::Bind)?;
+    info!(?socket, "Session holder started");
+    serve(listener).await;
+    let _ = std::fs::remove_file(socket);
+    info!("Session holder stopped");
+    Ok(())
+}
+
+/// Binds the socket in a private folder, then moves it in place once only the user can connect.
+pub(super) fn bind(socket: &Path) -> std::io::Result<UnixListener> {
+    let Some(name) = socket.file_name() else {
+        return Err(std::io::Error::other(format!(
+            "Invalid socket path {socket:?}"
+        )));
+    };
+    let name = name.to_string_lossy();
+    let private = socket.with_file_name(format!(".{name}.{}", std::process::id()));
+    std::fs::DirBuilder::new().mode(0o700).create(&private)?;
+    let staged = private.join(&*name);
+    let result = UnixListener::bind(&staged).and_then(|listener| {
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, socket)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_file(&staged);
+    let _ = std::fs::remove_dir(&private);
+    result
+}
+
+/// Accepts connections until the last session ends.
+pub async fn serve(listener: UnixListener) {
+    let empty = Arc::new(Notify::new());
+    loop {
+        let stream = tokio::select! {
+            stream = listener.accept() => stream,
+            () = empty.notified() => {
+                if get_processes().is_empty() {
+                    return;
+                }
+                continue;
+            }
+        };
+        match stream {
+            Ok((stream, _)) => {
+                let empty = empty.clone();
+                tokio::spawn(
+                    async move {
+                        if let Err(error) = handle_connection(stream, &empty).await {
+                            warn!("Connection failed: {error}");
+                        }
+                    }
+                    .instrument(info_span!("Connection")),
+                );
+            }
+            Err(error) => warn!("Failed to accept connection: {error}"),
+        }
+    }
+}
+
+async fn handle_connection(stream: UnixStream, empty: &Notify) -> std::io::Result<()> {
+    let (mut reader, mut writer) = stream.into_split();
+    let Some(Frame::Request(request)) = Frame::read(&mut reader).await? else {
+        return Err(super::protocol::invalid_data("Expected a request"));
+    };
+    debug!(?request, "Request");
+    let attach = match request {
+        Request::List => return respond(&mut writer, Response::List(list())).await,
+        Request::Open {
+            terminal_def,
+            scrollback,
+            profile,
+        } => {
+            let terminal_id = terminal_def.address.id.clone();
+            match open(terminal_def, scrollback, *profile).await {
+                Ok(entry) => (terminal_id, entry, Rewind::No),
+                Err(error) => {
+                    empty.notify_one();
+                    return respond(&mut writer, Response::Error(error)).await;
+                }
+            }
+        }
+        Request::Attach {
+            terminal_id,
+            rewind,
+        } => {
+            let entry = get_processes()
+                .get(&terminal_id)
+                .map(|entry| entry.1.clone());
+            match entry {
+                Some(entry) if rewind => (terminal_id, entry, Rewind::Scrollback),
+                Some(entry) => (terminal_id, entry, Rewind::No),
+                None => return respond(&mut writer, not_found(&terminal_id)).await,
+            }
+        }
+        Request::SetDef(terminal_def) => {
+            let response = match get_processes().get_mut(&terminal_def.address.id) {
+                Some(mut entry) => {
+                    entry.0 = terminal_def;
+                    Response::Ok
+                }
+                None => not_found(&terminal_def.address.id),
+            };
+            return respond(&mut writer, response).await;
+        }
+        Request::Resize {
+            terminal_id,
+            rows,
+            cols,
+        } => {
+            let response = match resize(&terminal_id, Size::new(rows, cols)).await {
+                Ok(()) => Response::Ok,
+                Err(error) => Response::Error(error),
+            };
+            return respond(&mut writer, response).await;
+        }
+        Request::Close(terminal_id) => {
+            let response = match get_processes().remove(&terminal_id) {
+                Some(_) => Response::Ok,
+                None => not_found(&terminal_id),
+            };
+            empty.notify_one();
+            return respond(&mut writer, response).await;
+        }
+    };
+
+    let (terminal_id, entry, rewind) = attach;
+    let lease = match entry.lease_output(LeaseMode::Exclusive, rewind).await {
+        Ok(lease) => lease,
+        Err(error) => return respond(&mut writer, Response::Error(error.to_string())).await,
+    };
+    respond(&mut writer, Response::Ok).await?;
+
+    let exit_status = entry.exit_status().clone();
+    let input = tokio::spawn(write_input(reader, Arc::downgrade(&entry)));
+    drop(entry);
+    let result = async {
+        let mut lease = std::pin::pin!(lease);
+        while let Some(next) = lease.next().await {
+            match next {
+                LeaseItem::Data(data) => Frame::Data(data.to_vec()).write(&mut writer).await?,
+                LeaseItem::Error(error) => warn!("Failed to read: {error}"),
+                LeaseItem::EOS => {
+                    let exit_status =
+                        tokio::time::timeout(EXIT_STATUS_TIMEOUT, exit_status.wait()).await;
+                    let exit_status = exit_status.ok().flatten().map(Into::into);
+                    info!(%terminal_id, ?exit_status, "The shell terminated");
+                    get_processes().remove(&terminal_id);
+                    empty.notify_one();
+                    Frame::Exit(exit_status).write(&mut writer).await?;
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+    .await;
+    input.abort();
+    result
+}
+
+async fn open(
+    mut terminal_def: TerminalDef,
+    scrollback: usize,
+    profile: LaunchProfile,
+) -> Result<Arc<ProcessIoEntry>, String> {
+    terminal_def.address.via = ClientAddress::default();
+    let terminal_id = terminal_def.address.id.clone();
+    if get_processes().contains_key(&terminal_id) {
+        return Err(format!("Terminal {terminal_id} already exists"));
+    }
+    let process = ProcessIO::open(None::<String>, scrollback, &profile.launch())
+        .await
+        .map_err(|error| error.to_string())?;
+    let entry = ProcessIoEntry::new(process);
+    get_processes().insert(terminal_id, (terminal_def, entry.clone()));
+    Ok(entry)
+}
+
+async fn resize(terminal_id: &TerminalId, size: Size) -> Result<(), String> {
+    let entry = {
+        let Some(entry) = get_processes().get(terminal_id) else {
+            return Err(format!("Terminal {terminal_id} not found"));
+        };
+        entry.1.clone()
+    };
+    let input = entry.input().await;
+    input.resize(size).map_err(|error| error.to_string())
+}
+
+/// Forwards the raw input bytes from the daemon to the shell.
+async fn write_input(mut reader: OwnedReadHalf, entry: Weak<ProcessIoEntry>) {
+    let mut buffer = vec![0; 8192];
+    loop {
+        let n = match reader.read(&mut buffer).await {
+            Ok(0) => return,
+            Ok(n) => n,
+            Err(error) => return warn!("Failed to read input: {error}"),
+        };
+        let Some(entry) = entry.upgrade() else {
+            return;
+        };
+        let mut input = entry.input().await;
+        if let Err(error) = input.write_all(&buffer[..n]).await {
+            return warn!("Failed to write input: {error}");
+        }
+    }
+}
+
+async fn respond(writer: &mut OwnedWriteHalf, response: Response) -> std::io::Result<()> {
+    Frame::Response(response).write(writer).await
+}
+
+fn not_found(terminal_id: &TerminalId) -> Response {
+    Response::Error(format!("Terminal {terminal_id} not found"))
+}
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+pub enum SessionHolderServerError {
+    #[error("[{n}] Failed to listen to the session holder socket: {0}", n = self.name())]
+    Bind(std::io::Error),
+}
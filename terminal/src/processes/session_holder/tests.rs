@@ -0,0 +1,145 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt as _;
+use terrazzo_pty::ProcessIO;
+use terrazzo_pty::exit_status::ExitStatus;
+use tokio::net::UnixListener;
+
+use super::SessionHolder;
+use super::server;
+use crate::api::client_address::ClientAddress;
+use crate::api::shared::terminal_schema::TabTitle;
+use crate::api::shared::terminal_schema::TerminalAddress;
+use crate::api::shared::terminal_schema::TerminalDef;
+use crate::backend::config::profile::LaunchProfile;
+use crate::tiles::id::TileId;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn start(tempdir: &tempfile::TempDir) -> SessionHolder {
+    let socket: Arc<Path> = tempdir.path().join("sessions.sock").into();
+    let listener = UnixListener::bind(&socket).unwrap();
+    tokio::spawn(server::serve(listener));
+    SessionHolder::new(socket)
+}
+
+fn terminal_def(id: &str) -> TerminalDef {
+    TerminalDef {
+        address: TerminalAddress {
+            id: id.into(),
+            via: ClientAddress::default(),
+        },
+        title: TabTitle {
+            shell_title: format!("Terminal {id}"),
+            override_title: None,
+        },
+        order: 1,
+        tile: TileId::for_test(1),
+        profile: None,
+    }
+}
+
+async fn read_until(process_io: ProcessIO, expected: &str) -> String {
+    let (_input, output) = process_io.split();
+    let mut output = std::pin::pin!(output);
+    let mut text = String::new();
+    tokio::time::timeout(TIMEOUT, async {
+        while !text.contains(expected) {
+            let Some(Ok(data)) = output.next().await else {
+                break;
+            };
+            text.push_str(&String::from_utf8_lossy(&data));
+        }
+    })
+    .await
+    .unwrap();
+    text
+}
+
+#[tokio::test]
+async fn reattach_replays_scrollback() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let session_holder = start(&tempdir);
+
+    let process_io = session_holder
+        .open(
+            terminal_def("T-holder-1"),
+            1000,
+            LaunchProfile::bash("echo hello-$((6*7)); sleep 30"),
+        )
+        .await
+        .unwrap();
+    assert!(
+        read_until(process_io, "hello-42")
+            .await
+            .contains("hello-42")
+    );
+
+    let process_io = session_holder
+        .attach(&"T-holder-1".into(), /* rewind = */ true, 1000)
+        .await
+        .unwrap();
+    assert!(
+        read_until(process_io, "hello-42")
+            .await
+            .contains("hello-42")
+    );
+
+    let mut renamed = terminal_def("T-holder-1");
+    renamed.title.override_title = Some("Renamed".into());
+    session_holder.set_def(renamed).await.unwrap();
+    let list = session_holder.list().await.unwrap();
+    let restored = list
+        .iter()
+        .find(|terminal_def| terminal_def.address.id == "T-holder-1".into())
+        .unwrap();
+    assert_eq!(restored.title.override_title.as_deref(), Some("Renamed"));
+
+    session_holder.close("T-holder-1".into()).await.unwrap();
+    assert!(
+        session_holder
+            .attach(&"T-holder-1".into(), /* rewind = */ true, 1000)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn exit_status() {
+    let tempdir = tempfile::tempdir().unwrap();
+    let session_holder = start(&tempdir);
+
+    let process_io = session_holder
+        .open(
+            terminal_def("T-holder-2"),
+            1000,
+            LaunchProfile::bash("exit 7"),
+        )
+        .await
+        .unwrap();
+    let exit_status = process_io.exit_status().clone();
+    let _output = read_until(process_io, "never printed").await;
+    let exit_status = tokio::time::timeout(TIMEOUT, exit_status.wait()).await;
+    assert_eq!(Some(ExitStatus::Code(7)), exit_status.unwrap());
+}
+
+#[tokio::test]
+async fn bind_private_socket() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let tempdir = tempfile::tempdir().unwrap();
+    let socket = tempdir.path().join("sessions.sock");
+    let _listener = server::bind(&socket).unwrap();
+    let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+    assert_eq!(0o600, mode & 0o777);
+    assert!(tokio::net::UnixStream::connect(&socket).await.is_ok());
+
+    // Only the socket is left, not the private folder it was bound in.
+    let entries: Vec<_> = std::fs::read_dir(tempdir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(vec![socket.file_name().unwrap().to_owned()], entries);
+}
@@ -5,6 +5,7 @@ use tracing::trace;
 use trz_gateway_common::http_error::IsHttpError;
 
 use super::get_processes;
+use super::session_holder;
 use crate::api::shared::terminal_schema::TabTitle;
 use crate::terminal_id::TerminalId;
 
@@ -20,6 +21,7 @@ pub fn set_title(
         });
     };
     entry.0.title = new_title;
+    session_holder::sync_terminal_def(&entry.0);
     Ok(())
 }
 
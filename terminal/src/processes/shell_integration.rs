@@ -0,0 +1,287 @@
+//! Tracks the current directory (OSC 7) and the commands (OSC 133) reported by the shell.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+
+use dashmap::DashMap;
+use futures::StreamExt as _;
+use terrazzo_pty::lease::ProcessIoEntry;
+use tokio::task::AbortHandle;
+use tracing::debug;
+
+use super::search::strip_ansi;
+use crate::api::shared::terminal_schema::ShellCommand;
+use crate::api::shared::terminal_schema::ShellState;
+use crate::terminal_id::TerminalId;
+
+/// The number of recent commands kept for each terminal.
+const MAX_COMMANDS: usize = 100;
+
+/// Longer OSC sequences are ignored.
+const MAX_PAYLOAD: usize = 4096;
+
+fn get_trackers() -> &'static DashMap<TerminalId, Tracking> {
+    static TRACKERS: OnceLock<DashMap<TerminalId, Tracking>> = OnceLock::new();
+    TRACKERS.get_or_init(DashMap::new)
+}
+
+struct Tracking {
+    tracker: Arc<Mutex<ShellTracker>>,
+    output: AbortHandle,
+}
+
+/// Follows the output of a new process.
+pub fn track(terminal_id: &TerminalId, entry: &ProcessIoEntry) {
+    let tracker = Arc::new(Mutex::new(ShellTracker::default()));
+    let trackers = get_trackers();
+    let slot = trackers.entry(terminal_id.clone());
+    let output = entry.follow_output();
+    let output = tokio::spawn({
+        let tracker = tracker.clone();
+        let terminal_id = terminal_id.clone();
+        async move {
+            let mut output = std::pin::pin!(output);
+            while let Some(data) = output.next().await {
+                let Ok(data) = data else { continue };
+                tracker.lock().expect("tracker").feed(&data);
+            }
+            debug!(%terminal_id, "Stop tracking the shell");
+            get_trackers().remove_if(&terminal_id, |_, tracking| {
+                Arc::ptr_eq(&tracking.tracker, &tracker)
+            });
+        }
+    });
+    let tracking = Tracking {
+        tracker,
+        output: output.abort_handle(),
+    };
+    match slot {
+        dashmap::Entry::Occupied(mut occupied) => occupied.insert(tracking).output.abort(),
+        dashmap::Entry::Vacant(vacant) => drop(vacant.insert(tracking)),
+    }
+}
+
+/// Stops following the output of a process, so it doesn't keep the process alive.
+pub fn stop(terminal_id: &TerminalId) {
+    if let Some((_, tracking)) = get_trackers().remove(terminal_id) {
+        debug!(%terminal_id, "Stop tracking the shell");
+        tracking.output.abort();
+    }
+}
+
+/// The current directory and recent commands of a terminal.
+pub fn shell_state(terminal_id: &TerminalId) -> ShellState {
+    let Some(tracker) = get_trackers()
+        .get(terminal_id)
+        .map(|tracking| tracking.tracker.clone())
+    else {
+        return ShellState::default();
+    };
+    let tracker = tracker.lock().expect("tracker");
+    ShellState {
+        cwd: tracker.cwd.clone(),
+        commands: tracker.commands.iter().cloned().collect(),
+    }
+}
+
+#[derive(Default)]
+struct ShellTracker {
+    state: State,
+    payload: Vec<u8>,
+    cwd: Option<String>,
+    commands: VecDeque<ShellCommand>,
+
+    /// The output echoed between the end of the prompt and the start of the command.
+    command_line: Option<Vec<u8>>,
+
+    /// The command that is currently running.
+    running: Option<ShellCommand>,
+}
+
+#[derive(Clone, Copy, Default)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    Osc,
+    OscEscape,
+}
+
+impl ShellTracker {
+    fn feed(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.state = match (self.state, byte) {
+                (State::Ground, 0x1b) => State::Escape,
+                (State::Ground, _) => {
+                    self.echo(&[byte]);
+                    State::Ground
+                }
+                (State::Escape, b']') => {
+                    self.payload.clear();
+                    State::Osc
+                }
+                (State::Escape, _) => {
+                    self.echo(&[0x1b, byte]);
+                    State::Ground
+                }
+                (State::Osc, 0x07) => {
+                    self.on_osc();
+                    State::Ground
+                }
+                (State::Osc, 0x1b) => State::OscEscape,
+                (State::Osc, _) => {
+                    if self.payload.len() < MAX_PAYLOAD {
+                        self.payload.push(byte);
+                    }
+                    State::Osc
+                }
+                (State::OscEscape, b'\\') => {
+                    self.on_osc();
+                    State::Ground
+                }
+                (State::OscEscape, _) => State::Osc,
+            }
+        }
+    }
+
+    fn echo(&mut self, bytes: &[u8]) {
+        if let Some(command_line) = &mut self.command_line
+            && command_line.len() < MAX_PAYLOAD
+        {
+            command_line.extend_from_slice(bytes);
+        }
+    }
+
+    fn on_osc(&mut self) {
+        if self.payload.len() >= MAX_PAYLOAD {
+            return;
+        }
+        let payload = String::from_utf8_lossy(&self.payload).into_owned();
+        let mut params = payload.split(';');
+        match params.next() {
+            Some("7") => {
+                if let Some(cwd) = payload.strip_prefix("7;").and_then(parse_file_url) {
+                    self.cwd = Some(cwd);
+                }
+            }
+            Some("133") => match params.next() {
+                Some("A") => self.command_line = None,
+                Some("B") => self.command_line = Some(vec![]),
+                Some("C") => {
+                    let command_line = self.command_line.take().unwrap_or_default();
+                    self.running = Some(ShellCommand {
+                        command: strip_ansi(&command_line).trim().to_owned(),
+                        cwd: self.cwd.clone(),
+                        exit_code: None,
+                    });
+                }
+                Some("D") => {
+                    let Some(mut command) = self.running.take() else {
+                        return;
+                    };
+                    command.exit_code = params.next().and_then(|code| code.parse().ok());
+                    if self.commands.len() == MAX_COMMANDS {
+                        self.commands.pop_front();
+                    }
+                    self.commands.push_back(command);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Extracts the path from `file://host/path`.
+fn parse_file_url(url: &str) -> Option<String> {
+    let path = url.strip_prefix("file://")?;
+    let path = &path[path.find('/')?..];
+    let mut bytes = vec![];
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = if byte == b'%' && tail.len() >= 2 {
+            std::str::from_utf8(&tail[..2])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        } else {
+            None
+        };
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use terrazzo_pty::ProcessIO;
+    use terrazzo_pty::launch::Launch;
+    use terrazzo_pty::lease::ProcessIoEntry;
+
+    use super::ShellTracker;
+    use super::parse_file_url;
+    use crate::terminal_id::TerminalId;
+
+    #[test]
+    fn file_url() {
+        assert_eq!(
+            Some("/home/user/My Documents".to_owned()),
+            parse_file_url("file://host/home/user/My%20Documents")
+        );
+        assert_eq!(Some("/".to_owned()), parse_file_url("file:///"));
+        assert_eq!(None, parse_file_url("http://host/path"));
+    }
+
+    #[test]
+    fn commands() {
+        let mut tracker = ShellTracker::default();
+        tracker.feed(b"\x1b]7;file://host/tmp\x07\x1b]133;A\x07$ \x1b]133;B\x07");
+        tracker.feed(b"l\x1b[1ms\x1b[0m\r\n\x1b]133;C\x1b\\file\r\n\x1b]13");
+        tracker.feed(b"3;D;0\x07\x1b]133;A\x07$ \x1b]133;B\x07false\r\n\x1b]133;C\x07");
+        assert_eq!(Some("/tmp"), tracker.cwd.as_deref());
+        assert_eq!(1, tracker.commands.len());
+        tracker.feed(b"\x1b]133;D;1\x07\x1b]7;file://host/home\x07\x1b]133;D\x07");
+        let commands = tracker
+            .commands
+            .iter()
+            .map(|c| (c.command.as_str(), c.cwd.as_deref(), c.exit_code))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("ls", Some("/tmp"), Some(0)),
+                ("false", Some("/tmp"), Some(1))
+            ],
+            commands
+        );
+        assert_eq!(Some("/home"), tracker.cwd.as_deref());
+    }
+
+    #[tokio::test]
+    async fn stop_releases_the_process() {
+        let terminal_id = TerminalId::from("T-shell-integration");
+        let process_io = ProcessIO::open(Option::<String>::None, 1000, &Launch::bash("sleep 30"))
+            .await
+            .unwrap();
+        let entry = ProcessIoEntry::new(process_io);
+        let exit_status = entry.exit_status().clone();
+        super::track(&terminal_id, &entry);
+        drop(entry);
+        super::stop(&terminal_id);
+        assert!(super::get_trackers().get(&terminal_id).is_none());
+        tokio::time::timeout(Duration::from_secs(5), exit_status.wait())
+            .await
+            .expect("The shell should end once it is no longer tracked");
+    }
+}
@@ -1,22 +1,28 @@
 use terrazzo_pty::OpenProcessError;
 use terrazzo_pty::ProcessIO;
+use terrazzo_pty::lease::LeaseMode;
 use terrazzo_pty::lease::LeaseProcessOutputError;
 use terrazzo_pty::lease::ProcessIoEntry;
 use terrazzo_pty::lease::ProcessOutputLease;
+use terrazzo_pty::lease::Rewind;
 use tracing::info;
 
 use super::get_processes;
+use super::session_holder::SessionHolderError;
+use super::shell_integration;
 use crate::api::client_address::ClientAddress;
 use crate::api::shared::terminal_schema::TerminalDef;
 use crate::terminal_id::TerminalId;
 
-pub async fn open_stream<F>(
+pub async fn open_stream<F, E>(
     mut terminal_def: TerminalDef,
-    rewind: bool,
+    lease_mode: LeaseMode,
+    rewind: Rewind,
     open_process: impl FnOnce(&TerminalId) -> F,
 ) -> Result<ProcessOutputLease, GetOrCreateProcessError>
 where
-    F: Future<Output = Result<ProcessIO, OpenProcessError>>,
+    F: Future<Output = Result<ProcessIO, E>>,
+    GetOrCreateProcessError: From<E>,
 {
     let processes = get_processes();
     terminal_def.address.via = ClientAddress::default();
@@ -26,21 +32,24 @@ where
             let entry = occupied_entry.get().1.clone();
             drop(occupied_entry);
             info!("Found");
-            if let Ok(lease) = entry.lease_output(rewind).await {
+            if let Ok(lease) = entry.lease_output(lease_mode, rewind).await {
                 return Ok(lease);
             }
             info!("Can't get a lease");
+            shell_integration::stop(terminal_id);
             let process = open_process(terminal_id).await?;
             let entry = ProcessIoEntry::new(process);
+            shell_integration::track(terminal_id, &entry);
             processes.insert(terminal_id.clone(), (terminal_def, entry.clone()));
-            return Ok(entry.lease_output(/* rewind = */ false).await?);
+            return Ok(entry.lease_output(LeaseMode::Exclusive, Rewind::No).await?);
         }
         dashmap::Entry::Vacant(vacant_entry) => {
             info!("Not found");
             let process = open_process(terminal_id).await?;
             let entry = ProcessIoEntry::new(process);
+            shell_integration::track(terminal_id, &entry);
             vacant_entry.insert((terminal_def, entry.clone()));
-            return Ok(entry.lease_output(/* rewind = */ false).await?);
+            return Ok(entry.lease_output(LeaseMode::Exclusive, Rewind::No).await?);
         }
     }
 }
@@ -52,4 +61,10 @@ pub enum GetOrCreateProcessError {
 
     #[error("LeaseProcessOutputError: {0}")]
     LeaseProcessOutputError(#[from] LeaseProcessOutputError),
+
+    #[error("SessionHolderError: {0}")]
+    SessionHolderError(#[from] SessionHolderError),
+
+    #[error("Unknown launch profile '{0}'")]
+    UnknownProfile(String),
 }
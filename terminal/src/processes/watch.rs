@@ -0,0 +1,38 @@
+//! Follows the output of a terminal without taking its lease.
+
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+use terrazzo::http::StatusCode;
+use terrazzo_pty::lease::SharedOutput;
+use trz_gateway_common::http_error::IsHttpError;
+
+use super::get_processes;
+use crate::terminal_id::TerminalId;
+
+/// Reads the scrollback and follows the output of the terminal.
+///
+/// Any number of watchers can follow the same terminal, alongside the lease of the tab.
+/// The output ends when the terminal is closed.
+pub fn watch(terminal_id: &TerminalId) -> Result<SharedOutput, WatchError> {
+    let Some(entry) = get_processes().get(terminal_id) else {
+        return Err(WatchError::TerminalNotFound {
+            terminal_id: terminal_id.clone(),
+        });
+    };
+    Ok(entry.value().1.watch_output())
+}
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+pub enum WatchError {
+    #[error("[{n}] Terminal not found {terminal_id}", n = self.name())]
+    TerminalNotFound { terminal_id: TerminalId },
+}
+
+impl IsHttpError for WatchError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::TerminalNotFound { .. } => StatusCode::NOT_FOUND,
+        }
+    }
+}
@@ -6,6 +6,8 @@ use tracing::trace;
 use trz_gateway_common::http_error::IsHttpError;
 
 use super::get_processes;
+use super::recording;
+use crate::terminal::asciicast::EventKind;
 use crate::terminal_id::TerminalId;
 
 pub async fn write(terminal_id: &TerminalId, data: &[u8]) -> Result<(), WriteError> {
@@ -19,6 +21,9 @@ pub async fn write(terminal_id: &TerminalId, data: &[u8]) -> Result<(), WriteErr
         };
         entry.value().1.clone()
     };
+    recording::record(terminal_id, EventKind::Input, || {
+        String::from_utf8_lossy(data).into_owned()
+    });
     let mut input = entry.input().await;
     return input.write_all(data).await.map_err(WriteError::Write);
 }
@@ -6,9 +6,14 @@ use server_fn::codec::TextStream;
 use terrazzo::server;
 
 use crate::api::client_address::ClientAddress;
+use crate::api::shared::terminal_schema::ExitStatus;
+use crate::api::shared::terminal_schema::RecordingInfo;
 use crate::api::shared::terminal_schema::RegisterTerminalMode;
 use crate::api::shared::terminal_schema::ResizeRequest;
+use crate::api::shared::terminal_schema::ScrollbackMatch;
 use crate::api::shared::terminal_schema::SetTitleRequest;
+use crate::api::shared::terminal_schema::ShellState;
+use crate::api::shared::terminal_schema::StartRecordingRequest;
 use crate::api::shared::terminal_schema::TerminalAddress;
 use crate::api::shared::terminal_schema::TerminalDef;
 use crate::terminal_id::TerminalId;
@@ -33,10 +38,16 @@ pub async fn set_tile_id(
 pub enum LeaseMessage {
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "I"))]
     Init,
+
+    /// Identifies the reader of the stream, to acknowledge the output it received.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "R"))]
+    Reader(usize),
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "B"))]
     Base64(String),
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "U"))]
     Utf8(String),
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "X"))]
+    Exit(ExitStatus),
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "E"))]
     Eos,
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "F"))]
@@ -49,8 +60,17 @@ pub async fn list() -> Result<Vec<TerminalDef>, ServerFnError> {
 }
 
 #[server(protocol = Http<Json, Json>)]
-pub async fn new_id(remote: ClientAddress, tile: TileId) -> Result<TerminalDef, ServerFnError> {
-    super::service::new_id::new_id(remote, tile).await
+pub async fn new_id(
+    remote: ClientAddress,
+    tile: TileId,
+    profile: Option<String>,
+) -> Result<TerminalDef, ServerFnError> {
+    super::service::new_id::new_id(remote, tile, profile).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn list_profiles(remote: ClientAddress) -> Result<Vec<String>, ServerFnError> {
+    super::service::profiles::list_profiles(remote).await
 }
 
 #[server(protocol = Http<Json, Json>)]
@@ -58,6 +78,14 @@ pub async fn write(terminal: TerminalAddress, data: String) -> Result<(), Server
     super::service::write::write(terminal, data).await
 }
 
+#[server(protocol = Http<Json, Json>)]
+pub async fn broadcast(
+    terminals: Vec<TerminalAddress>,
+    data: String,
+) -> Result<Vec<TerminalAddress>, ServerFnError> {
+    super::service::write::broadcast(terminals, data).await
+}
+
 #[server(protocol = Http<Json, Json>)]
 pub async fn resize(request: ResizeRequest) -> Result<(), ServerFnError> {
     super::service::resize::resize(request).await
@@ -79,8 +107,44 @@ pub async fn close(terminal: TerminalAddress) -> Result<(), ServerFnError> {
 }
 
 #[server(protocol = Http<Json, Json>)]
-pub async fn ack(terminal: TerminalAddress, ack: usize) -> Result<(), ServerFnError> {
-    super::service::ack::ack(terminal, ack).await
+pub async fn ack(
+    terminal: TerminalAddress,
+    reader: usize,
+    ack: usize,
+) -> Result<(), ServerFnError> {
+    super::service::ack::ack(terminal, reader, ack).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn start_recording(
+    request: StartRecordingRequest,
+) -> Result<RecordingInfo, ServerFnError> {
+    super::service::recording::start_recording(request).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn stop_recording(terminal: TerminalAddress) -> Result<(), ServerFnError> {
+    super::service::recording::stop_recording(terminal).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn list_recordings(remote: ClientAddress) -> Result<Vec<RecordingInfo>, ServerFnError> {
+    super::service::recording::list_recordings(remote).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn load_recording(remote: ClientAddress, name: String) -> Result<String, ServerFnError> {
+    super::service::recording::load_recording(remote, name).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn get_shell_state(terminal: TerminalAddress) -> Result<ShellState, ServerFnError> {
+    super::service::shell::shell_state(terminal).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn search_scrollback(pattern: String) -> Result<Vec<ScrollbackMatch>, ServerFnError> {
+    super::service::search::search_scrollback(pattern).await
 }
 
 #[cfg(feature = "client")]
@@ -0,0 +1,68 @@
+//! The [asciicast v2](https://docs.asciinema.org/manual/asciicast/v2/) file format.
+//!
+//! A recording is a JSON header on the first line, followed by one JSON event per line.
+
+use serde::Deserialize;
+use serde::Serialize;
+
+#[cfg(feature = "server")]
+pub const VERSION: u32 = 2;
+
+/// The first line of a recording.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Header {
+    pub version: u32,
+    pub width: i32,
+    pub height: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// An event: the number of seconds since the start of the recording, its kind and its data.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Event(pub f64, pub EventKind, pub String);
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EventKind {
+    #[serde(rename = "o")]
+    Output,
+
+    #[serde(rename = "i")]
+    Input,
+
+    /// The data is `{cols}x{rows}`.
+    #[serde(rename = "r")]
+    Resize,
+
+    #[serde(rename = "m")]
+    Marker,
+
+    #[serde(other)]
+    Unknown,
+}
+
+/// A parsed recording.
+#[cfg(feature = "client")]
+pub struct Cast {
+    pub header: Header,
+    pub events: Vec<Event>,
+}
+
+#[cfg(feature = "client")]
+impl Cast {
+    pub fn parse(content: &str) -> Result<Self, serde_json::Error> {
+        let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+        let header = serde_json::from_str(lines.next().unwrap_or_default())?;
+        let events = lines
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<Event>, _>>()?;
+        Ok(Self { header, events })
+    }
+
+    /// The time of the last event.
+    pub fn duration(&self) -> f64 {
+        self.events.last().map(|event| event.0).unwrap_or_default()
+    }
+}
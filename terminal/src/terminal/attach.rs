@@ -21,12 +21,15 @@ use self::diagnostics::info;
 use self::diagnostics::info_span;
 use self::diagnostics::span::Span;
 use self::diagnostics::warn;
+use super::broadcast;
 use super::client as terminal_api;
+use super::client::OnExit;
 use super::javascript::TerminalJs;
 use super::javascript::TerminalJsRc;
 use super::terminal_tab::TerminalTab;
 use super::ui::TerminalsState;
 use crate::api::shared::terminal_schema;
+use crate::api::shared::terminal_schema::ExitStatus;
 use crate::api::shared::terminal_schema::TabTitle;
 use crate::api::shared::terminal_schema::TerminalAddress;
 use crate::api::shared::terminal_schema::TerminalDef;
@@ -71,12 +74,20 @@ pub fn attach(
     xtermjs.open(&element);
     let (input_tx, input_rx) = mpsc::unbounded();
     let on_data = xtermjs.do_on_data(input_tx);
-    let on_resize = xtermjs.do_on_resize(terminal_address.clone());
+    let on_resize = xtermjs.do_on_resize(&terminal_tab);
     let on_title_change = xtermjs.do_on_title_change(terminal_tab.title.clone());
+    let replay_history = terminal_tab.replay_history.subscribe();
     let selected = terminal_tab.selected.get_value_untracked();
     let io = async move {
         let (initialized_tx, initialized_rx) = oneshot::channel();
-        let stream_loop = xtermjs.stream_loop(state, terminal_def, initialized_tx, notify_mouse);
+        let stream_loop = xtermjs.stream_loop(
+            state,
+            &terminal_tab,
+            terminal_def,
+            initialized_tx,
+            notify_mouse,
+            replay_history,
+        );
         let write_loop = write_loop(&terminal_address, input_rx, initialized_rx);
         let unsubscribe_resize_event = ResizeEvent::signal().add_subscriber({
             let xtermjs = xtermjs.clone();
@@ -140,27 +151,29 @@ impl TerminalJs {
         return on_data;
     }
 
-    fn do_on_resize(&self, terminal: TerminalAddress) -> Closure<dyn FnMut(JsValue)> {
+    fn do_on_resize(&self, terminal_tab: &TerminalTab) -> Closure<dyn FnMut(JsValue)> {
         let span = Span::current();
         let this = self.clone();
+        let terminal_tab = terminal_tab.clone();
         let mut first_resize = true;
         let on_resize: Closure<dyn FnMut(JsValue)> = Closure::new(move |data| {
             let _span = span.enter();
             let first_resize = std::mem::replace(&mut first_resize, false);
             debug!("Resize: {data:?} first_resize:{first_resize}");
-            let resize = this.clone().do_resize(terminal.clone(), first_resize);
+            let resize = this.clone().do_resize(terminal_tab.clone(), first_resize);
             spawn_local(resize.in_current_span());
         });
         self.on_resize(&on_resize);
         return on_resize;
     }
 
-    async fn do_resize(self, terminal: TerminalAddress, force: bool) {
+    async fn do_resize(self, terminal_tab: TerminalTab, force: bool) {
         let size = terminal_schema::Size {
             rows: self.rows().as_f64().or_throw("rows") as i32,
             cols: self.cols().as_f64().or_throw("cols") as i32,
         };
-        if let Err(error) = terminal_api::resize(&terminal, size, force).await {
+        let reader = *terminal_tab.reader.lock().or_throw("reader");
+        if let Err(error) = terminal_api::resize(&terminal_tab.address, size, force, reader).await {
             warn!("Failed to resize: {error}");
         }
     }
@@ -184,9 +197,11 @@ impl TerminalJs {
     async fn stream_loop(
         &self,
         state: TerminalsState,
+        terminal_tab: &TerminalTab,
         terminal_def: TerminalDef,
         initialized: oneshot::Sender<()>,
         notify_mouse: watch::WatchRx,
+        replay_history: watch::WatchRx,
     ) {
         let span = debug_span!("StreamLoop", terminal_address = %terminal_def.address);
         async {
@@ -196,9 +211,20 @@ impl TerminalJs {
                 let _ = initialized.send(());
                 ready(())
             };
-            let eos = terminal_api::stream(state, terminal_def, notify_mouse, on_init, |data| {
-                self.send(data)
-            })
+            let on_reader = |reader| *terminal_tab.reader.lock().or_throw("reader") = Some(reader);
+            let on_replay_history = || self.reset();
+            let on_exit = |exit_status| on_exit(terminal_tab, exit_status);
+            let eos = terminal_api::stream(
+                state,
+                terminal_def,
+                notify_mouse,
+                replay_history,
+                on_init,
+                on_reader,
+                on_replay_history,
+                |data| self.send(data),
+                on_exit,
+            )
             .await;
             match eos {
                 Ok(()) => info!("End"),
@@ -210,6 +236,17 @@ impl TerminalJs {
     }
 }
 
+/// Shows the exit banner and waits for the user to decide whether to respawn the shell.
+async fn on_exit(terminal_tab: &TerminalTab, exit_status: ExitStatus) -> OnExit {
+    let (on_exit_tx, on_exit_rx) = oneshot::channel();
+    *terminal_tab.on_exit.lock().or_throw("on_exit") = Some(on_exit_tx);
+    terminal_tab.recording.set(false);
+    terminal_tab.exit_status.set(Some(exit_status));
+    let on_exit = on_exit_rx.await.unwrap_or(OnExit::Close);
+    terminal_tab.exit_status.set(None);
+    return on_exit;
+}
+
 async fn write_loop(
     terminal: &TerminalAddress,
     input_rx: mpsc::UnboundedReceiver<String>,
@@ -225,7 +262,7 @@ async fn write_loop(
         let mut input_rx = input_rx.ready_chunks(10);
         while let Some(data) = &input_rx.next().await {
             let data = data.join("");
-            if let Err(error) = terminal_api::write(terminal, data).await {
+            if let Err(error) = broadcast::write(terminal, data).await {
                 error!("Failed to write to the terminal: {error}");
                 return;
             }
@@ -0,0 +1,66 @@
+//! Broadcasts the input typed in a terminal to a group of terminals.
+
+use std::sync::LazyLock;
+
+use server_fn::ServerFnError;
+use terrazzo::prelude::*;
+
+use self::diagnostics::warn;
+use super::client as terminal_api;
+use crate::api::shared::terminal_schema::TerminalAddress;
+use crate::terminal_id::TerminalId;
+
+/// The terminals that receive the input typed in any of them.
+static GROUP: LazyLock<XSignal<Vec<TerminalAddress>>> =
+    LazyLock::new(|| XSignal::new("broadcast-group", vec![]));
+
+pub fn group() -> XSignal<Vec<TerminalAddress>> {
+    GROUP.clone()
+}
+
+/// Adds the terminal to the group, or removes it if it is already part of it.
+pub fn toggle(terminal: &TerminalAddress) {
+    GROUP.update(|group| {
+        let mut group = group.clone();
+        if let Some(i) = group.iter().position(|t| t == terminal) {
+            group.remove(i);
+        } else {
+            group.push(terminal.clone());
+        }
+        Some(group)
+    });
+}
+
+pub fn leave(terminal_id: &TerminalId) {
+    GROUP.update(|group| {
+        let is_member = group.iter().any(|t| t.id == *terminal_id);
+        is_member.then(|| {
+            let group = group.iter().filter(|t| t.id != *terminal_id);
+            group.cloned().collect()
+        })
+    });
+}
+
+/// Writes to the terminal, and to the rest of the group if the terminal is part of it.
+pub async fn write(terminal: &TerminalAddress, data: String) -> Result<(), ServerFnError> {
+    let group = GROUP.get_value_untracked();
+    if !group.contains(terminal) {
+        return terminal_api::write(terminal, data).await;
+    }
+    let others = group.into_iter().filter(|t| t != terminal).collect();
+    let (result, failed) = futures::future::join(
+        terminal_api::write(terminal, data.clone()),
+        terminal_api::broadcast(others, data),
+    )
+    .await;
+    match failed {
+        Ok(failed) => {
+            for failed in failed {
+                warn!("Removing {} from the broadcast group", failed.id);
+                leave(&failed.id);
+            }
+        }
+        Err(error) => warn!("Failed to broadcast: {error}"),
+    }
+    return result;
+}
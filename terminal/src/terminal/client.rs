@@ -1,6 +1,8 @@
 use base64::Engine as _;
+use futures::FutureExt as _;
 use futures::StreamExt as _;
 use futures::channel::oneshot;
+use futures::select;
 use nameth::NamedEnumValues as _;
 use nameth::nameth;
 use server_fn::ServerFnError;
@@ -27,23 +29,40 @@ pub async fn list() -> Result<Vec<TerminalDef>, ServerFnError> {
     super::api::list().await
 }
 
-pub async fn new_id(address: ClientAddress, tile: TileId) -> Result<TerminalDef, ServerFnError> {
-    super::api::new_id(address, tile).await
+pub async fn new_id(
+    address: ClientAddress,
+    tile: TileId,
+    profile: Option<String>,
+) -> Result<TerminalDef, ServerFnError> {
+    super::api::new_id(address, tile, profile).await
+}
+
+pub async fn list_profiles(remote: ClientAddress) -> Result<Vec<String>, ServerFnError> {
+    super::api::list_profiles(remote).await
 }
 
 pub async fn write(terminal: &TerminalAddress, data: String) -> Result<(), ServerFnError> {
     super::api::write(terminal.clone(), data).await
 }
 
+pub async fn broadcast(
+    terminals: Vec<TerminalAddress>,
+    data: String,
+) -> Result<Vec<TerminalAddress>, ServerFnError> {
+    super::api::broadcast(terminals, data).await
+}
+
 pub async fn resize(
     terminal: &TerminalAddress,
     size: Size,
     force: bool,
+    reader: Option<usize>,
 ) -> Result<(), ServerFnError> {
     super::api::resize(ResizeRequest {
         terminal: terminal.clone(),
         size,
         force,
+        reader,
     })
     .await
 }
@@ -63,27 +82,93 @@ pub async fn set_order(tabs: Vec<TerminalAddress>) -> Result<(), ServerFnError>
     super::api::set_order(tabs).await
 }
 
-pub async fn stream<F, F0>(
+pub async fn start_recording(
+    terminal: &TerminalAddress,
+    size: Size,
+    title: Option<String>,
+) -> Result<RecordingInfo, ServerFnError> {
+    super::api::start_recording(StartRecordingRequest {
+        terminal: terminal.clone(),
+        size,
+        title,
+    })
+    .await
+}
+
+pub async fn stop_recording(terminal: &TerminalAddress) -> Result<(), ServerFnError> {
+    super::api::stop_recording(terminal.clone()).await
+}
+
+pub async fn list_recordings(remote: ClientAddress) -> Result<Vec<RecordingInfo>, ServerFnError> {
+    super::api::list_recordings(remote).await
+}
+
+pub async fn load_recording(remote: ClientAddress, name: String) -> Result<String, ServerFnError> {
+    super::api::load_recording(remote, name).await
+}
+
+pub async fn shell_state(terminal: &TerminalAddress) -> Result<ShellState, ServerFnError> {
+    super::api::get_shell_state(terminal.clone()).await
+}
+
+pub async fn search_scrollback(pattern: String) -> Result<Vec<ScrollbackMatch>, ServerFnError> {
+    super::api::search_scrollback(pattern).await
+}
+
+/// What to do after the shell of a terminal has exited.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnExit {
+    /// Start the same shell again in the same terminal.
+    Respawn,
+
+    /// Drop the terminal tab.
+    Close,
+}
+
+pub async fn stream<F, F0, FX>(
     state: TerminalsState,
     terminal_def: TerminalDef,
     notify_mouse: watch::WatchRx,
+    replay_history: watch::WatchRx,
     on_init: impl FnOnce() -> F0,
+    on_reader: impl Fn(usize),
+    on_replay_history: impl Fn(),
     on_data: impl Fn(JsValue) -> F,
+    on_exit: impl Fn(ExitStatus) -> FX,
 ) -> Result<(), StreamError>
 where
     F: Future<Output = ()>,
     F0: Future<Output = ()>,
+    FX: Future<Output = OnExit>,
 {
     let terminal_id = terminal_def.address.id.clone();
     let mut mode = RegisterTerminalMode::Create;
     let mut on_init = Some(on_init);
-    loop {
+    'reconnect: loop {
         let mut stream = super::api::stream(mode, terminal_def.clone())
             .await?
             .ready_chunks(100);
         let mut parser = NdjsonBuffer::<LeaseMessage>::default();
+        let mut reader = 0;
         let mut unacked = 0;
-        while let Some(chunks) = stream.next().await {
+        let mut exit_status = None;
+        loop {
+            let chunks = select! {
+                chunks = stream.next().fuse() => chunks,
+                replay = replay_history.notified().fuse() => {
+                    if let Err(oneshot::Canceled) = replay {
+                        info!("Terminal tab closed, disconnecting");
+                        return Err(StreamError::UxClosed);
+                    }
+                    info!("Reopening the terminal with its whole history");
+                    on_replay_history();
+                    mode = RegisterTerminalMode::History;
+                    continue 'reconnect;
+                }
+            };
+            let Some(chunks) = chunks else {
+                break;
+            };
             let mut buffer = vec![];
             for chunk in chunks {
                 let messages: Vec<Result<LeaseMessage, serde_json::Error>> =
@@ -95,13 +180,30 @@ where
                                 on_init().await;
                             }
                         }
+                        LeaseMessage::Reader(id) => {
+                            reader = id;
+                            on_reader(reader);
+                        }
+                        LeaseMessage::Exit(status) => {
+                            info!("The shell exited with {status}");
+                            exit_status = Some(status);
+                        }
                         LeaseMessage::Eos => {
-                            process_data(&terminal_def, &on_data, &mut unacked, buffer).await?;
+                            process_data(&terminal_def, &on_data, reader, &mut unacked, buffer)
+                                .await?;
+                            if let Some(exit_status) = exit_status
+                                && on_exit(exit_status).await == OnExit::Respawn
+                            {
+                                info!("Respawning the shell");
+                                mode = RegisterTerminalMode::Create;
+                                continue 'reconnect;
+                            }
                             state.on_eos(&terminal_id);
                             return Ok(());
                         }
                         LeaseMessage::Error(error) => {
-                            process_data(&terminal_def, &on_data, &mut unacked, buffer).await?;
+                            process_data(&terminal_def, &on_data, reader, &mut unacked, buffer)
+                                .await?;
                             state.on_eos(&terminal_id);
                             return Err(StreamError::ServerFn(error));
                         }
@@ -115,9 +217,14 @@ where
                     }
                 }
             }
-            process_data(&terminal_def, &on_data, &mut unacked, buffer).await?;
+            process_data(&terminal_def, &on_data, reader, &mut unacked, buffer).await?;
         }
         debug!("Terminal stream disconnected");
+        if mode != RegisterTerminalMode::Follow {
+            info!("Terminal stream revoked, following the new lease");
+            mode = RegisterTerminalMode::Follow;
+            continue 'reconnect;
+        }
         match notify_mouse.notified().await {
             Ok(()) => info!("Terminal stream reopening"),
             Err(oneshot::Canceled) => {
@@ -132,6 +239,7 @@ where
 async fn process_data<F>(
     terminal_def: &TerminalDefImpl<TabTitle<String>>,
     on_data: &impl Fn(JsValue) -> F,
+    reader: usize,
     unacked: &mut usize,
     data: Vec<u8>,
 ) -> Result<(), StreamError>
@@ -146,9 +254,13 @@ where
     value.copy_from(&data);
     on_data(value.into()).await;
     if *unacked >= STREAMING_WINDOW_SIZE / 2 {
-        super::api::ack(terminal_def.address.clone(), std::mem::take(unacked))
-            .await
-            .map_err(StreamError::from)?;
+        super::api::ack(
+            terminal_def.address.clone(),
+            reader,
+            std::mem::take(unacked),
+        )
+        .await
+        .map_err(StreamError::from)?;
     }
     Ok(())
 }
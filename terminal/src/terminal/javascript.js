@@ -2,11 +2,69 @@ class TerminalJs {
     terminal;
     fitAddon;
     webLinksAddon;
+    prompts = [];
+    runningCommand = null;
     constructor() {
-        this.terminal = new JsDeps.Terminal({});
+        this.terminal = new JsDeps.Terminal({ allowProposedApi: true });
         this.fitAddon = new JsDeps.FitAddon();
         this.webLinksAddon = new JsDeps.WebLinksAddon();
-        this.terminal.attachCustomKeyEventHandler((event) => this.handleClipboardShortcut(event));
+        this.terminal.attachCustomKeyEventHandler((event) =>
+            this.handlePromptShortcut(event) && this.handleClipboardShortcut(event));
+        this.terminal.parser.registerOscHandler(133, (data) => this.handleShellIntegration(data));
+    }
+    handleShellIntegration(data) {
+        const [kind, exitCode] = data.split(";");
+        if (kind === "A") {
+            const prompt = this.terminal.registerMarker(0);
+            if (prompt) {
+                this.prompts.push(prompt);
+                prompt.onDispose(() => this.prompts = this.prompts.filter((p) => p !== prompt));
+            }
+        } else if (kind === "C") {
+            this.runningCommand = this.prompts[this.prompts.length - 1] ?? null;
+        } else if (kind === "D" && this.runningCommand) {
+            const prompt = this.runningCommand;
+            this.runningCommand = null;
+            if (exitCode && exitCode !== "0" && !prompt.isDisposed) {
+                this.markFailedCommand(prompt, exitCode);
+            }
+        }
+        // Let xterm.js handle the sequence as well.
+        return false;
+    }
+    markFailedCommand(prompt, exitCode) {
+        const decoration = this.terminal.registerDecoration({ marker: prompt, width: 1 });
+        decoration?.onRender((element) => {
+            element.title = `Exit code ${exitCode}`;
+            element.style.borderLeft = "3px solid #e06c75";
+            element.style.boxSizing = "border-box";
+        });
+    }
+    handlePromptShortcut(event) {
+        if (event.type !== "keydown" || !event.ctrlKey || !event.shiftKey || event.altKey) {
+            return true;
+        }
+        if (event.key === "ArrowUp") {
+            this.scrollToPrompt(-1);
+        } else if (event.key === "ArrowDown") {
+            this.scrollToPrompt(1);
+        } else {
+            return true;
+        }
+        event.preventDefault();
+        return false;
+    }
+    scrollToPrompt(direction) {
+        const top = this.terminal.buffer.active.viewportY;
+        const lines = this.prompts.map((prompt) => prompt.line).filter((line) => line >= 0);
+        const line = direction < 0
+            ? lines.filter((line) => line < top).pop()
+            : lines.find((line) => line > top);
+        if (line !== undefined) {
+            this.terminal.scrollToLine(line);
+        } else if (direction > 0) {
+            this.terminal.scrollToBottom();
+        }
     }
     handleClipboardShortcut(event) {
         if (event.type !== "keydown" || !event.ctrlKey || event.altKey || event.metaKey) {
@@ -55,6 +113,12 @@ class TerminalJs {
     async pasteClipboard() {
         this.terminal.paste(await navigator.clipboard.readText());
     }
+    reset() {
+        this.terminal.reset();
+    }
+    resize(cols, rows) {
+        this.terminal.resize(cols, rows);
+    }
     rows() {
         return this.terminal.rows;
     }
@@ -43,6 +43,12 @@ extern "C" {
     #[wasm_bindgen(method)]
     pub fn focus(this: &TerminalJs);
 
+    #[wasm_bindgen(method)]
+    pub fn reset(this: &TerminalJs);
+
+    #[wasm_bindgen(method)]
+    pub fn resize(this: &TerminalJs, cols: u32, rows: u32);
+
     #[wasm_bindgen(method)]
     pub fn rows(this: &TerminalJs) -> JsValue;
 
@@ -1,14 +1,25 @@
 #![cfg(feature = "terminal")]
 
 pub(crate) mod api;
+pub mod asciicast;
 #[cfg(feature = "client")]
 mod attach;
 #[cfg(feature = "client")]
+mod broadcast;
+#[cfg(feature = "client")]
 pub(crate) mod client;
 #[cfg(feature = "client")]
 mod javascript;
+#[cfg(feature = "client")]
+pub mod replay;
+#[cfg(feature = "client")]
+mod scrollback_search;
 #[cfg(feature = "server")]
 mod service;
+#[cfg(feature = "server")]
+pub(crate) mod share;
+#[cfg(feature = "client")]
+pub(crate) mod shared;
 pub mod streams;
 #[cfg(feature = "client")]
 mod terminal_tab;
@@ -0,0 +1,397 @@
+#![cfg(feature = "client")]
+
+//! Replays terminal recordings.
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use terrazzo::autoclone;
+use terrazzo::html;
+use terrazzo::prelude::*;
+use terrazzo::template;
+use terrazzo::widgets::sleep::sleep;
+use wasm_bindgen::JsCast as _;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use web_sys::HtmlSelectElement;
+
+use self::diagnostics::Instrument as _;
+use self::diagnostics::warn;
+use super::asciicast::Cast;
+use super::asciicast::Event;
+use super::asciicast::EventKind;
+use super::client as terminal_api;
+use super::javascript::TerminalJs;
+use super::javascript::TerminalJsRc;
+use crate::api::shared::terminal_schema::RecordingInfo;
+use crate::assets::icons;
+use crate::frontend::menu::menu;
+use crate::frontend::remotes::Remote;
+use crate::frontend::remotes_ui::show_remote;
+use crate::tiles::signals::TilePtr;
+
+terrazzo_css::import_style!(style, "replay.scss");
+
+const XTERMJS_ATTR: &str = "data-xtermjs";
+const IS_ATTACHED: &str = "Y";
+
+/// The playback speeds to choose from.
+const SPEEDS: [f64; 5] = [0.5, 1., 2., 4., 8.];
+
+/// How often the position is updated during playback, in seconds.
+const TICK: f64 = 0.25;
+
+/// The UI for the replay app.
+#[autoclone]
+#[html]
+#[template(tag = div)]
+pub fn replay(tile: TilePtr) -> XElement {
+    let player = Player::new();
+    tag(
+        class = style::REPLAY,
+        div(
+            class = style::HEADER,
+            menu(tile.clone()),
+            show_remote(tile.remote.clone()),
+            show_recordings(player.clone(), tile.remote.clone()),
+        ),
+        div(
+            class = style::SCREEN,
+            div(move |template| {
+                autoclone!(player);
+                player.attach(template)
+            }),
+        ),
+        div(
+            class = style::CONTROLS,
+            play_button(player.clone(), player.playing.clone()),
+            show_seek(
+                player.clone(),
+                player.position.clone(),
+                player.duration.clone(),
+            ),
+            speed_select(player.clone()),
+        ),
+    )
+}
+
+#[autoclone]
+#[html]
+#[template(tag = div)]
+fn show_recordings(player: Player, #[signal] remote: Remote) -> XElement {
+    let recordings = XSignal::new("recordings", vec![]);
+    let list_task = async move {
+        autoclone!(remote, recordings);
+        match terminal_api::list_recordings(remote).await {
+            Ok(list) => recordings.set(list),
+            Err(error) => warn!("Failed to list recordings: {error}"),
+        }
+    };
+    spawn_local(list_task.in_current_span());
+    tag(
+        class = style::RECORDINGS,
+        recordings_select(player, remote, recordings),
+    )
+}
+
+#[autoclone]
+#[html]
+#[template(tag = select)]
+fn recordings_select(
+    player: Player,
+    remote: Remote,
+    #[signal] recordings: Vec<RecordingInfo>,
+) -> XElement {
+    let mut options = vec![];
+    options.push(option(value = "", "Select a recording"));
+    for recording in &recordings {
+        let name = &recording.name;
+        let label = recording.title.as_deref().unwrap_or(name);
+        options.push(option(value = name.clone(), "{label} ({name})"));
+    }
+    tag(
+        change = move |ev: web_sys::Event| {
+            autoclone!(player, remote);
+            let select = ev.target().or_throw("recording target");
+            let select: HtmlSelectElement = select.dyn_into().or_throw("recording select");
+            let name = select.value();
+            if !name.is_empty() {
+                player.load(remote.clone(), name);
+            }
+        },
+        options..,
+    )
+}
+
+#[autoclone]
+#[html]
+#[template(tag = img)]
+fn play_button(player: Player, #[signal] playing: bool) -> XElement {
+    tag(
+        class = style::PLAY_ICON,
+        src = if playing {
+            icons::pause()
+        } else {
+            icons::play()
+        },
+        click = move |_| {
+            autoclone!(player);
+            if playing {
+                player.pause();
+            } else {
+                player.play();
+            }
+        },
+    )
+}
+
+#[autoclone]
+#[html]
+#[template(tag = div)]
+fn show_seek(
+    player: Player,
+    #[signal] position: Duration,
+    #[signal] duration: Duration,
+) -> XElement {
+    let time = format!("{} / {}", format_time(position), format_time(duration));
+    tag(
+        class = style::SEEK,
+        input(
+            r#type = "range",
+            min = "0",
+            max = duration.as_secs_f64().to_string(),
+            step = "any",
+            after_render = move |input| {
+                let input: &HtmlInputElement = input.dyn_ref().or_throw("seek input");
+                input.set_value_as_number(position.as_secs_f64());
+            },
+            change = move |ev: web_sys::Event| {
+                autoclone!(player);
+                let input = ev.target().or_throw("seek target");
+                let input: HtmlInputElement = input.dyn_into().or_throw("seek input");
+                player.seek(input.value_as_number());
+            },
+        ),
+        span(class = style::TIME, "{time}"),
+    )
+}
+
+#[autoclone]
+#[html]
+fn speed_select(player: Player) -> XElement {
+    let options = SPEEDS.iter().map(|speed| {
+        option(
+            value = speed.to_string(),
+            selected = (*speed == 1.).then(|| "selected".to_owned()),
+            "{speed}x",
+        )
+    });
+    select(
+        class = style::SPEED,
+        change = move |ev: web_sys::Event| {
+            autoclone!(player);
+            let select = ev.target().or_throw("speed target");
+            let select: HtmlSelectElement = select.dyn_into().or_throw("speed select");
+            player.speed.set(select.value().parse().unwrap_or(1.));
+        },
+        options..,
+    )
+}
+
+fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[derive(Clone)]
+struct Player(Rc<PlayerInner>);
+
+struct PlayerInner {
+    xtermjs: RefCell<Option<TerminalJsRc>>,
+    cast: RefCell<Option<Rc<Cast>>>,
+    playing: XSignal<bool>,
+    position: XSignal<Duration>,
+    duration: XSignal<Duration>,
+    speed: Cell<f64>,
+
+    /// The index of the next event to play.
+    next: Cell<usize>,
+
+    /// Incremented to interrupt the playback.
+    generation: Cell<u64>,
+}
+
+impl std::ops::Deref for Player {
+    type Target = PlayerInner;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Player {
+    fn new() -> Self {
+        Self(Rc::new(PlayerInner {
+            xtermjs: RefCell::new(None),
+            cast: RefCell::new(None),
+            playing: XSignal::new("playing", false),
+            position: XSignal::new("position", Duration::ZERO),
+            duration: XSignal::new("duration", Duration::ZERO),
+            speed: Cell::new(1.),
+            next: Cell::new(0),
+            generation: Cell::new(0),
+        }))
+    }
+
+    fn attach(&self, template: XTemplate) -> Consumers {
+        let element = template.element();
+        if let Some(IS_ATTACHED) = element.get_attribute(XTERMJS_ATTR).as_deref() {
+            return Consumers::default();
+        }
+        element
+            .set_attribute(XTERMJS_ATTR, IS_ATTACHED)
+            .or_throw(XTERMJS_ATTR);
+        let xtermjs = TerminalJsRc::new();
+        xtermjs.open(&element);
+        *self.xtermjs.borrow_mut() = Some(xtermjs);
+        Consumers::default()
+    }
+
+    fn load(&self, remote: Remote, name: String) {
+        let this = self.clone();
+        let load_task = async move {
+            let content = match terminal_api::load_recording(remote, name).await {
+                Ok(content) => content,
+                Err(error) => return warn!("Failed to load the recording: {error}"),
+            };
+            let cast = match Cast::parse(&content) {
+                Ok(cast) => cast,
+                Err(error) => return warn!("Failed to parse the recording: {error}"),
+            };
+            this.pause();
+            this.duration.set(Duration::from_secs_f64(cast.duration()));
+            *this.cast.borrow_mut() = Some(Rc::new(cast));
+            this.render_until(0.).await;
+            this.play();
+        };
+        spawn_local(load_task.in_current_span());
+    }
+
+    fn play(&self) {
+        let generation = self.interrupt();
+        self.playing.set(true);
+        spawn_local(self.clone().run(generation).in_current_span());
+    }
+
+    fn pause(&self) {
+        self.interrupt();
+        self.playing.set(false);
+    }
+
+    fn seek(&self, time: f64) {
+        let was_playing = self.playing.get_value_untracked();
+        self.pause();
+        let generation = self.generation.get();
+        let this = self.clone();
+        let seek_task = async move {
+            this.render_until(time).await;
+            if was_playing && this.generation.get() == generation {
+                this.play();
+            }
+        };
+        spawn_local(seek_task.in_current_span());
+    }
+
+    fn interrupt(&self) -> u64 {
+        let generation = self.generation.get() + 1;
+        self.generation.set(generation);
+        generation
+    }
+
+    /// Resets the screen and replays the output up to `time` at once.
+    async fn render_until(&self, time: f64) {
+        let Some((cast, xtermjs)) = self.current() else {
+            return;
+        };
+        xtermjs.reset();
+        xtermjs.resize(cast.header.width as u32, cast.header.height as u32);
+        let mut output = String::new();
+        let mut next = 0;
+        for Event(at, kind, data) in &cast.events {
+            if *at > time {
+                break;
+            }
+            next += 1;
+            match kind {
+                EventKind::Output => output.push_str(data),
+                EventKind::Resize => {
+                    xtermjs.send(JsValue::from_str(&output)).await;
+                    output.clear();
+                    resize(&xtermjs, data);
+                }
+                EventKind::Input | EventKind::Marker | EventKind::Unknown => {}
+            }
+        }
+        xtermjs.send(JsValue::from_str(&output)).await;
+        self.next.set(next);
+        self.position.set(Duration::from_secs_f64(time));
+    }
+
+    async fn run(self, generation: u64) {
+        let Some((cast, xtermjs)) = self.current() else {
+            return self.playing.set(false);
+        };
+        if self.next.get() >= cast.events.len() {
+            self.render_until(0.).await;
+        }
+        while let Some(Event(at, kind, data)) = cast.events.get(self.next.get()) {
+            loop {
+                let position = self.position.get_value_untracked().as_secs_f64();
+                if *at <= position {
+                    break;
+                }
+                let speed = self.speed.get();
+                let step = (at - position).min(TICK * speed);
+                if let Err(error) = sleep(Duration::from_secs_f64(step / speed)).await {
+                    warn!("Playback interrupted: {error}");
+                    return self.playing.set(false);
+                }
+                if self.generation.get() != generation {
+                    return;
+                }
+                self.position.set(Duration::from_secs_f64(position + step));
+            }
+            match kind {
+                EventKind::Output => xtermjs.send(JsValue::from_str(data)).await,
+                EventKind::Resize => resize(&xtermjs, data),
+                EventKind::Input | EventKind::Marker | EventKind::Unknown => {}
+            }
+            if self.generation.get() != generation {
+                return;
+            }
+            self.next.set(self.next.get() + 1);
+        }
+        self.playing.set(false);
+    }
+
+    fn current(&self) -> Option<(Rc<Cast>, TerminalJsRc)> {
+        let cast = self.cast.borrow().clone()?;
+        let xtermjs = self.xtermjs.borrow().clone()?;
+        Some((cast, xtermjs))
+    }
+}
+
+fn resize(xtermjs: &TerminalJs, data: &str) {
+    let size = data.split_once('x').and_then(|(cols, rows)| {
+        let cols = cols.parse().ok()?;
+        let rows = rows.parse().ok()?;
+        Some((cols, rows))
+    });
+    match size {
+        Some((cols, rows)) => xtermjs.resize(cols, rows),
+        None => warn!("Invalid resize event '{data}'"),
+    }
+}
@@ -0,0 +1,163 @@
+#![cfg(feature = "client")]
+
+//! Searches the scrollback of all the terminals, on all the remotes.
+
+use std::rc::Rc;
+
+use terrazzo::autoclone;
+use terrazzo::html;
+use terrazzo::prelude::*;
+use terrazzo::template;
+use wasm_bindgen::JsCast as _;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use web_sys::KeyboardEvent;
+
+use self::diagnostics::Instrument as _;
+use super::client as terminal_api;
+use super::ui::TerminalsState;
+use crate::api::shared::terminal_schema::ScrollbackMatch;
+use crate::assets::icons;
+
+terrazzo_css::import_style!(style, "scrollback_search.scss");
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum SearchResults {
+    Empty,
+    Loading,
+    Done(Rc<Vec<ScrollbackMatch>>),
+    Failed(String),
+}
+
+/// The search icon and the panel with the results.
+#[autoclone]
+#[html]
+pub fn scrollback_search(state: TerminalsState) -> XElement {
+    let open = XSignal::new("scrollback-search", false);
+    let results = XSignal::new("scrollback-search-results", SearchResults::Empty);
+    div(
+        key = "scrollback-search",
+        class = style::SCROLLBACK_SEARCH,
+        div(
+            class = style::SEARCH_ICON,
+            img(src = icons::search()),
+            click = move |_| {
+                autoclone!(open);
+                open.update(|open| Some(!open));
+            },
+        ),
+        search_panel(state, results, open),
+    )
+}
+
+#[autoclone]
+#[html]
+#[template(tag = div)]
+fn search_panel(
+    state: TerminalsState,
+    results: XSignal<SearchResults>,
+    #[signal] mut open: bool,
+) -> XElement {
+    if !open {
+        return tag(style::visibility = "hidden", style::display = "none");
+    }
+    tag(
+        class = style::SEARCH_PANEL,
+        input(
+            r#type = "text",
+            placeholder = "Search all terminals (regex)",
+            after_render = |input| {
+                let input: &HtmlInputElement = input.dyn_ref().or_throw("search input");
+                let _ = input.focus();
+            },
+            keydown = move |event: KeyboardEvent| {
+                autoclone!(open_mut, results);
+                match event.key().as_str() {
+                    "Escape" => open_mut.set(false),
+                    "Enter" => {
+                        let input = event.target().or_throw("search target");
+                        let input: HtmlInputElement = input.dyn_into().or_throw("search input");
+                        search(input.value(), results.clone());
+                    }
+                    _ => {}
+                }
+            },
+        ),
+        show_results(state, open_mut, results),
+    )
+}
+
+fn search(pattern: String, results: XSignal<SearchResults>) {
+    if pattern.is_empty() {
+        return results.set(SearchResults::Empty);
+    }
+    results.set(SearchResults::Loading);
+    let search_task = async move {
+        results.set(match terminal_api::search_scrollback(pattern).await {
+            Ok(matches) => SearchResults::Done(matches.into()),
+            Err(error) => SearchResults::Failed(error.to_string()),
+        });
+    };
+    spawn_local(search_task.in_current_span());
+}
+
+#[html]
+#[template(tag = ul)]
+fn show_results(
+    state: TerminalsState,
+    open: MutableSignal<bool>,
+    #[signal] results: SearchResults,
+) -> XElement {
+    let matches = match results {
+        SearchResults::Empty => return tag(class = style::RESULTS),
+        SearchResults::Loading => return tag(class = style::RESULTS, li("Searching...")),
+        SearchResults::Failed(error) => return tag(class = style::RESULTS, li("{error}")),
+        SearchResults::Done(matches) if matches.is_empty() => {
+            return tag(class = style::RESULTS, li("No match"));
+        }
+        SearchResults::Done(matches) => matches,
+    };
+    let items = matches
+        .iter()
+        .map(|m| show_result(&state, &open, m))
+        .collect::<Vec<_>>();
+    tag(class = style::RESULTS, items..)
+}
+
+#[autoclone]
+#[html]
+fn show_result(
+    state: &TerminalsState,
+    open: &MutableSignal<bool>,
+    m: &ScrollbackMatch,
+) -> XElement {
+    let title = &m.title;
+    let location = if m.terminal.via.is_empty() {
+        format!("line {}", m.line_number + 1)
+    } else {
+        format!("line {} via {}", m.line_number + 1, m.terminal.via)
+    };
+    let line = &m.line;
+    let before = m.before.iter().map(|line| div("{line}"));
+    let after = m.after.iter().map(|line| div("{line}"));
+    let terminal = m.terminal.clone();
+    li(
+        class = style::RESULT,
+        div(
+            class = style::RESULT_TITLE,
+            span("{title}"),
+            span(class = style::LOCATION, "{location}"),
+        ),
+        div(
+            class = style::LINES,
+            before..,
+            div(class = style::MATCH, "{line}"),
+            after..,
+        ),
+        click = move |_| {
+            autoclone!(state, open);
+            open.set(false);
+            state.show_terminal(terminal.clone());
+        },
+    )
+}
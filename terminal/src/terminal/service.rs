@@ -3,8 +3,13 @@ pub mod close;
 pub mod list;
 pub mod new_id;
 pub mod order;
+pub mod profiles;
+pub mod recording;
 pub mod resize;
+pub mod search;
+pub mod shell;
 pub mod stream;
 pub mod tile_id;
 pub mod title;
+pub mod watch;
 pub mod write;
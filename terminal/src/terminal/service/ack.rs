@@ -5,19 +5,23 @@ use crate::api::shared::terminal_schema::TerminalAddress;
 use crate::backend::client_service::remote_fn_service;
 use crate::terminal_id::TerminalId;
 
-pub async fn ack(terminal: TerminalAddress, ack: usize) -> Result<(), ServerFnError> {
+pub async fn ack(
+    terminal: TerminalAddress,
+    reader: usize,
+    ack: usize,
+) -> Result<(), ServerFnError> {
     Ok(ACK_FN
-        .call(terminal.via.clone(), (terminal.id, ack))
+        .call(terminal.via.clone(), (terminal.id, reader, ack))
         .await?)
 }
 
 remote_fn_service::unary::declare_remote_fn!(
     ACK_FN,
     "terminal.ack",
-    (TerminalId, usize),
+    (TerminalId, usize, usize),
     (),
-    |_server, (terminal_id, ack)| async move {
-        crate::backend::throttling_stream::ack(&terminal_id, ack);
+    |_server, (terminal_id, reader, ack)| async move {
+        crate::backend::throttling_stream::ack(&terminal_id, reader, ack);
         Ok::<_, Status>(())
     }
 );
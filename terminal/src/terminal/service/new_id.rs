@@ -11,17 +11,24 @@ use crate::backend::client_service::remote_fn_service;
 use crate::processes;
 use crate::tiles::id::TileId;
 
-pub async fn new_id(remote: ClientAddress, tile: TileId) -> Result<TerminalDef, ServerFnError> {
-    let (next, client_name) = NEW_ID_FN.call(remote.clone(), ()).await?;
+pub async fn new_id(
+    remote: ClientAddress,
+    tile: TileId,
+    profile: Option<String>,
+) -> Result<TerminalDef, ServerFnError> {
+    let (next, client_name, profile_title) =
+        NEW_ID_FN.call(remote.clone(), profile.clone()).await?;
     let local_client_name = client_name.as_deref();
     let client_name = remote
         .last()
         .map(|name| name.as_ref())
         .or(local_client_name);
-    let title = client_name.map_or_else(
-        || format!("Terminal {next}"),
-        |name| format!("Terminal {name}:{next}"),
-    );
+    let title = profile_title.unwrap_or_else(|| {
+        client_name.map_or_else(
+            || format!("Terminal {next}"),
+            |name| format!("Terminal {name}:{next}"),
+        )
+    });
     let id = if cfg!(feature = "concise-traces") {
         Uuid::new_v4().to_string()
     } else if let Some(client_name) = client_name {
@@ -40,21 +47,28 @@ pub async fn new_id(remote: ClientAddress, tile: TileId) -> Result<TerminalDef,
         },
         order: next,
         tile,
+        profile,
     })
 }
 
-type NewIdResult = (i32, Option<ClientName>);
+/// The next terminal number, the client name and the title of the launch profile.
+type NewIdResult = (i32, Option<ClientName>, Option<String>);
 
 remote_fn_service::unary::declare_remote_fn!(
     NEW_ID_FN,
     "terminal.new_id",
-    (),
+    Option<String>,
     NewIdResult,
-    |server, ()| {
-        let client_name = server
-            .config()
+    |server, profile| {
+        let config = server.config();
+        let client_name = config
             .mesh
             .with(|mesh| Some(mesh.as_ref()?.client_name.as_str().into()));
-        async move { Ok::<_, Status>((processes::next_terminal_id(), client_name)) }
+        let profile_title = profile.and_then(|profile| {
+            config
+                .server
+                .with(|server| server.profiles.get(&profile)?.title.clone())
+        });
+        async move { Ok::<_, Status>((processes::next_terminal_id(), client_name, profile_title)) }
     }
 );
@@ -7,6 +7,7 @@ use crate::api::client_address::ClientAddress;
 use crate::api::shared::terminal_schema::TerminalAddress;
 use crate::backend::client_service::remote_fn_service;
 use crate::processes::get_processes;
+use crate::processes::session_holder;
 use crate::terminal_id::TerminalId;
 
 pub async fn set_order(terminals: Vec<TerminalAddress>) -> Result<(), ServerFnError> {
@@ -31,6 +32,7 @@ remote_fn_service::unary::declare_remote_fn!(
         for (terminal_id, order) in entries {
             if let Some(mut process) = get_processes().get_mut(&terminal_id) {
                 process.0.order = order;
+                session_holder::sync_terminal_def(&process.0);
             }
         }
         Ok::<_, Status>(())
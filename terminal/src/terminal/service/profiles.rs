@@ -0,0 +1,23 @@
+use server_fn::ServerFnError;
+use tonic::Status;
+
+use crate::api::client_address::ClientAddress;
+use crate::backend::client_service::remote_fn_service;
+
+pub async fn list_profiles(remote: ClientAddress) -> Result<Vec<String>, ServerFnError> {
+    Ok(LIST_PROFILES_FN.call(remote, ()).await?)
+}
+
+remote_fn_service::unary::declare_remote_fn!(
+    LIST_PROFILES_FN,
+    "terminal.profiles",
+    (),
+    Vec<String>,
+    |server, ()| {
+        let profiles = server
+            .config()
+            .server
+            .with(|config| config.profiles.keys().cloned().collect());
+        async move { Ok::<_, Status>(profiles) }
+    }
+);
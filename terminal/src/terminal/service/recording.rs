@@ -0,0 +1,88 @@
+use server_fn::ServerFnError;
+use tonic::Status;
+
+use crate::api::client_address::ClientAddress;
+use crate::api::shared::terminal_schema::RecordingInfo;
+use crate::api::shared::terminal_schema::StartRecordingRequest;
+use crate::api::shared::terminal_schema::TerminalAddress;
+use crate::backend::client_service::remote_fn_service;
+use crate::processes;
+
+pub async fn start_recording(
+    request: StartRecordingRequest,
+) -> Result<RecordingInfo, ServerFnError> {
+    Ok(START_RECORDING_FN
+        .call(request.terminal.via.clone(), request)
+        .await?)
+}
+
+remote_fn_service::unary::declare_remote_fn!(
+    START_RECORDING_FN,
+    "terminal.recording.start",
+    StartRecordingRequest,
+    RecordingInfo,
+    |server, request: StartRecordingRequest| {
+        let folder = server
+            .config()
+            .server
+            .with(|config| config.recordings.clone());
+        async move {
+            processes::recording::start(&request.terminal.id, &folder, request.size, request.title)
+                .map_err(|e| Status::internal(e.to_string()))
+        }
+    }
+);
+
+pub async fn stop_recording(terminal: TerminalAddress) -> Result<(), ServerFnError> {
+    Ok(STOP_RECORDING_FN
+        .call(terminal.via.clone(), terminal)
+        .await?)
+}
+
+remote_fn_service::unary::declare_remote_fn!(
+    STOP_RECORDING_FN,
+    "terminal.recording.stop",
+    TerminalAddress,
+    (),
+    |_server, terminal: TerminalAddress| async move {
+        processes::recording::stop(&terminal.id).map_err(|e| Status::not_found(e.to_string()))
+    }
+);
+
+pub async fn list_recordings(remote: ClientAddress) -> Result<Vec<RecordingInfo>, ServerFnError> {
+    Ok(LIST_RECORDINGS_FN.call(remote, ()).await?)
+}
+
+remote_fn_service::unary::declare_remote_fn!(
+    LIST_RECORDINGS_FN,
+    "terminal.recording.list",
+    (),
+    Vec<RecordingInfo>,
+    |server, ()| {
+        let folder = server
+            .config()
+            .server
+            .with(|config| config.recordings.clone());
+        async move { processes::recording::list(&folder).map_err(|e| Status::internal(e.to_string())) }
+    }
+);
+
+pub async fn load_recording(remote: ClientAddress, name: String) -> Result<String, ServerFnError> {
+    Ok(LOAD_RECORDING_FN.call(remote, name).await?)
+}
+
+remote_fn_service::unary::declare_remote_fn!(
+    LOAD_RECORDING_FN,
+    "terminal.recording.load",
+    String,
+    String,
+    |server, name: String| {
+        let folder = server
+            .config()
+            .server
+            .with(|config| config.recordings.clone());
+        async move {
+            processes::recording::load(&folder, &name).map_err(|e| Status::internal(e.to_string()))
+        }
+    }
+);
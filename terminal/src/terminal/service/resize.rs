@@ -1,8 +1,12 @@
 use server_fn::ServerFnError;
+use terrazzo_pty::lease::LeaseMode;
 use tonic::Status;
+use tracing::debug;
 
 use crate::api::shared::terminal_schema::ResizeRequest;
 use crate::backend::client_service::remote_fn_service;
+use crate::backend::config::server::SizePolicy;
+use crate::backend::throttling_stream;
 use crate::processes;
 
 pub async fn resize(request: ResizeRequest) -> Result<(), ServerFnError> {
@@ -16,14 +20,29 @@ remote_fn_service::unary::declare_remote_fn!(
     "terminal.resize",
     ResizeRequest,
     (),
-    |_server, request: ResizeRequest| async move {
-        processes::resize::resize(
-            &request.terminal.id,
-            request.size.rows,
-            request.size.cols,
-            request.force,
-        )
-        .await
-        .map_err(|e| Status::internal(e.to_string()))
+    |server, request: ResizeRequest| {
+        let size_policy = server.config().server.with(|config| config.size_policy);
+        let can_resize = match size_policy {
+            SizePolicy::Latest => true,
+            SizePolicy::Exclusive => request.reader.is_some_and(|reader| {
+                let lease_mode = throttling_stream::lease_mode(&request.terminal.id, reader);
+                lease_mode == Some(LeaseMode::Exclusive)
+            }),
+        };
+        async move {
+            if !can_resize {
+                let terminal = &request.terminal.id;
+                debug!(%terminal, "Ignoring resize without the exclusive lease");
+                return Ok(());
+            }
+            processes::resize::resize(
+                &request.terminal.id,
+                request.size.rows,
+                request.size.cols,
+                request.force,
+            )
+            .await
+            .map_err(|e| Status::internal(e.to_string()))
+        }
     }
 );
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use regex::Regex;
+use serde::Deserialize;
+use serde::Serialize;
+use server_fn::ServerFnError;
+use tonic::Status;
+use tracing::debug;
+use trz_gateway_common::id::ClientName;
+
+use crate::api::client_address::ClientAddress;
+use crate::api::shared::terminal_schema::ScrollbackMatch;
+use crate::backend::Server;
+use crate::backend::client_service::remote_fn_service;
+use crate::processes;
+
+pub async fn search_scrollback(pattern: String) -> Result<Vec<ScrollbackMatch>, ServerFnError> {
+    let request = SearchScrollbackRequest {
+        pattern,
+        visited: vec![],
+    };
+    Ok(SEARCH_SCROLLBACK_FN
+        .call(ClientAddress::default(), request)
+        .await?)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchScrollbackRequest {
+    pattern: String,
+    visited: Vec<ClientName>,
+}
+
+async fn search_scrollback_impl(
+    server: &Arc<Server>,
+    request: SearchScrollbackRequest,
+) -> Result<Vec<ScrollbackMatch>, Status> {
+    let regex = Regex::new(&request.pattern)
+        .map_err(|error| Status::invalid_argument(error.to_string()))?;
+    let mut response = tokio::task::spawn_blocking(move || processes::search::search(&regex))
+        .await
+        .map_err(|error| Status::internal(error.to_string()))?;
+    for client_name in server.connections().clients() {
+        if request.visited.iter().any(|name| name == &client_name) {
+            continue;
+        }
+        let mut visited = request.visited.clone();
+        visited.push(client_name.clone());
+        let address = ClientAddress::from(client_name.clone());
+        let remote_request = SearchScrollbackRequest {
+            pattern: request.pattern.clone(),
+            visited,
+        };
+        let Ok(mut matches) = SEARCH_SCROLLBACK_FN.call(address, remote_request).await else {
+            continue;
+        };
+        for m in &mut matches {
+            let mut via = m.terminal.via.to_vec();
+            via.push(client_name.clone());
+            m.terminal.via = via.into();
+        }
+        response.append(&mut matches);
+    }
+    debug!("Found {} matches", response.len());
+    Ok(response)
+}
+
+remote_fn_service::unary::declare_remote_fn!(
+    SEARCH_SCROLLBACK_FN,
+    "terminal.search",
+    SearchScrollbackRequest,
+    Vec<ScrollbackMatch>,
+    |server, request| {
+        let server = server.clone();
+        async move { search_scrollback_impl(&server, request).await }
+    }
+);
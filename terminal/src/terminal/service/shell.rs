@@ -0,0 +1,23 @@
+use server_fn::ServerFnError;
+use tonic::Status;
+
+use crate::api::shared::terminal_schema::ShellState;
+use crate::api::shared::terminal_schema::TerminalAddress;
+use crate::backend::client_service::remote_fn_service;
+use crate::processes;
+use crate::terminal_id::TerminalId;
+
+pub async fn shell_state(terminal: TerminalAddress) -> Result<ShellState, ServerFnError> {
+    Ok(SHELL_STATE_FN.call(terminal.via, terminal.id).await?)
+}
+
+remote_fn_service::unary::declare_remote_fn!(
+    SHELL_STATE_FN,
+    "terminal.shell_state",
+    TerminalId,
+    ShellState,
+    |_server, terminal_id: TerminalId| {
+        let shell_state = processes::shell_integration::shell_state(&terminal_id);
+        async move { Ok::<_, Status>(shell_state) }
+    }
+);
@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use base64::Engine as _;
 use futures::Stream;
 use futures::StreamExt as _;
@@ -7,17 +9,24 @@ use server_fn::codec::TextStream;
 use terrazzo_pty::OpenProcessError;
 use terrazzo_pty::ProcessIO;
 use terrazzo_pty::lease::LeaseItem;
+use terrazzo_pty::lease::LeaseMode;
+use terrazzo_pty::lease::Rewind;
 use tonic::Status;
 use tracing::debug;
+use tracing::warn;
 
+use crate::api::shared::terminal_schema::ExitStatus;
 use crate::api::shared::terminal_schema::RegisterTerminalMode;
-use crate::api::shared::terminal_schema::STREAMING_WINDOW_SIZE;
 use crate::api::shared::terminal_schema::TerminalDef;
 use crate::backend::client_service::remote_fn_service;
 use crate::backend::throttling_stream::ThrottleProcessOutput;
 use crate::processes;
+use crate::processes::EXIT_STATUS_TIMEOUT;
 use crate::processes::get_processes;
+use crate::processes::session_holder;
+use crate::processes::stream::GetOrCreateProcessError;
 use crate::terminal::api::LeaseMessage;
+use crate::terminal_id::TerminalId;
 use crate::utils::ndjson_utils::serialize_line;
 
 pub async fn stream(
@@ -45,35 +54,83 @@ remote_fn_service::streaming::declare_remote_fn!(
         let server = server.to_owned();
         let terminal_id = terminal_def.address.id.clone();
         let create = mode == RegisterTerminalMode::Create;
-        let stream = processes::stream::open_stream(terminal_def, create, move |_| async move {
-            if !create {
-                return Err(OpenProcessError::NotFound);
+        let (lease_mode, rewind) = match mode {
+            RegisterTerminalMode::Create => (LeaseMode::Exclusive, Rewind::Scrollback),
+            RegisterTerminalMode::Reopen => (LeaseMode::Exclusive, Rewind::No),
+            RegisterTerminalMode::History => (LeaseMode::Exclusive, Rewind::History),
+            RegisterTerminalMode::Follow => (LeaseMode::Shared, Rewind::No),
+        };
+        let open_process = {
+            let terminal_def = terminal_def.clone();
+            move |terminal_id: &TerminalId| {
+                let terminal_id = terminal_id.clone();
+                async move {
+                    if !create {
+                        return Err(OpenProcessError::NotFound.into());
+                    }
+                    let server_config = &server.config().server;
+                    let profile_name = terminal_def.profile.as_deref();
+                    let (profile, scrollback_spill, spill_limit) = server_config.with(|config| {
+                        (
+                            config.launch_profile(profile_name),
+                            config.scrollback_spill.clone(),
+                            config.scrollback_spill_limit,
+                        )
+                    });
+                    let Some(profile) = profile else {
+                        let profile_name = profile_name.unwrap_or_default().to_owned();
+                        return Err(GetOrCreateProcessError::UnknownProfile(profile_name));
+                    };
+                    let scrollback = profile.scrollback_size();
+                    let process = if let Some(session_holder) = session_holder::get() {
+                        session_holder
+                            .open(terminal_def, scrollback, profile)
+                            .await?
+                    } else {
+                        let launch = profile.launch();
+                        ProcessIO::open(None::<String>, scrollback, &launch).await?
+                    };
+                    if let Some(scrollback_spill) = scrollback_spill {
+                        let spill_limit = spill_limit.unwrap_or(SCROLLBACK_SPILL_LIMIT);
+                        spill(&process, &scrollback_spill, spill_limit, &terminal_id);
+                    }
+                    Ok::<_, GetOrCreateProcessError>(process)
+                }
             }
-            let server_config = &server.config().server;
-            let shell = server_config.with(|config| config.terminal_shell.clone());
-            ProcessIO::open(None::<String>, STREAMING_WINDOW_SIZE, shell).await
-        });
+        };
+        let stream = processes::stream::open_stream(terminal_def, lease_mode, rewind, open_process);
         let stream = {
             let terminal_id = terminal_id.clone();
             async move {
                 match stream.await {
-                    Ok(stream) => Ok(ThrottleProcessOutput::new(terminal_id, stream)),
+                    Ok(stream) => Ok(ThrottleProcessOutput::new(terminal_id, lease_mode, stream)),
                     Err(error) => Err(Status::internal(error.to_string())),
                 }
             }
         };
 
-        use futures::future::ready;
         use futures::stream::once;
         let stream = async move {
             let stream = stream.await?;
-            let stream = stream.inspect(move |next| {
-                if let LeaseItem::EOS = next {
-                    let _removed = get_processes().remove(&terminal_id);
-                }
-            });
-            let stream = stream.map(LeaseMessage::from);
-            Ok(once(ready(LeaseMessage::Init)).chain(stream).map(Ok))
+            let reader = LeaseMessage::Reader(stream.reader());
+            let stream = stream
+                .then(move |next| {
+                    let terminal_id = terminal_id.clone();
+                    async move {
+                        let LeaseItem::EOS = next else {
+                            return vec![LeaseMessage::from(next)];
+                        };
+                        match on_eos(&terminal_id).await {
+                            Some(exit_status) => {
+                                vec![LeaseMessage::Exit(exit_status), LeaseMessage::Eos]
+                            }
+                            None => vec![LeaseMessage::Eos],
+                        }
+                    }
+                })
+                .flat_map(futures::stream::iter);
+            let init = futures::stream::iter([LeaseMessage::Init, reader]);
+            Ok(init.chain(stream).map(Ok))
         };
 
         let stream = helpers::is_future_stream(stream);
@@ -84,6 +141,27 @@ remote_fn_service::streaming::declare_remote_fn!(
     }
 );
 
+/// How many bytes of spilled output are kept per terminal by default.
+const SCROLLBACK_SPILL_LIMIT: u64 = 64 * 1024 * 1024;
+
+/// Keeps the output that no longer fits in the scrollback in `<folder>/<terminal_id>.log`.
+fn spill(process: &ProcessIO, folder: &Path, limit: u64, terminal_id: &TerminalId) {
+    let path = folder.join(format!("{}.log", processes::file_name(terminal_id)));
+    let result = std::fs::create_dir_all(folder).and_then(|()| process.spill_to(&path, limit));
+    if let Err(error) = result {
+        warn!(?path, "Failed to spill the scrollback: {error}");
+    }
+}
+
+/// Drops the terminal and collects the exit status of its shell.
+async fn on_eos(terminal_id: &TerminalId) -> Option<ExitStatus> {
+    let (_, (_, entry)) = get_processes().remove(terminal_id)?;
+    let exit_status = tokio::time::timeout(EXIT_STATUS_TIMEOUT, entry.exit_status().wait()).await;
+    let exit_status = ExitStatus::from(exit_status.ok().flatten()?);
+    debug!(%terminal_id, "The shell terminated with {exit_status}");
+    Some(exit_status)
+}
+
 /// Helpers to make types more obvious
 mod helpers {
     use super::*;
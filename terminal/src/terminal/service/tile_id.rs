@@ -2,6 +2,7 @@ use tonic::Status;
 
 use crate::backend::client_service::remote_fn_service;
 use crate::processes::get_processes;
+use crate::processes::session_holder;
 use crate::terminal::api::SET_TILE_ID;
 use crate::terminal_id::TerminalId;
 use crate::tiles::id::TileId;
@@ -13,6 +14,7 @@ pub async fn set_tile_id(terminal_id: TerminalId, tile_id: TileId) -> Result<(),
         )));
     };
     entry.0.tile = tile_id;
+    session_holder::sync_terminal_def(&entry.0);
     Ok(())
 }
 
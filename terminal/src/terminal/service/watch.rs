@@ -0,0 +1,52 @@
+use futures::StreamExt as _;
+use futures::TryStreamExt as _;
+use futures::future::ready;
+use futures::stream::once;
+use server_fn::ServerFnError;
+use server_fn::codec::TextStream;
+use terrazzo_pty::lease::LeaseItem;
+use tonic::Status;
+use tracing::debug;
+
+use crate::api::shared::terminal_schema::TerminalAddress;
+use crate::backend::client_service::remote_fn_service;
+use crate::processes;
+use crate::terminal::api::LeaseMessage;
+use crate::utils::ndjson_utils::serialize_line;
+
+/// Streams the output of a terminal to a viewer, without taking the lease of its tab.
+pub async fn watch(terminal: TerminalAddress) -> Result<TextStream, ServerFnError> {
+    let remote = terminal.via.clone();
+    debug!(%remote, "Calling watch()");
+    let stream = WATCH_FN.call(remote, terminal).await?;
+    let stream = stream.map_ok(|item| {
+        serialize_line(&item).unwrap_or_else(|error| {
+            serialize_line(&LeaseMessage::Error(error.to_string()))
+                .expect("serializing a string cannot fail")
+        })
+    });
+    Ok(TextStream::new(stream.map_err(Into::into)))
+}
+
+remote_fn_service::streaming::declare_remote_fn!(
+    WATCH_FN,
+    "terminal.watch",
+    TerminalAddress,
+    LeaseMessage,
+    |_server, terminal: TerminalAddress| {
+        let output = processes::watch::watch(&terminal.id)
+            .map_err(|error| Status::not_found(error.to_string()));
+        let stream = match output {
+            Ok(output) => output
+                .map(|next| match next {
+                    Ok(data) => LeaseMessage::from(LeaseItem::Data(data)),
+                    Err(error) => LeaseMessage::Error(error.to_string()),
+                })
+                .chain(once(ready(LeaseMessage::Eos)))
+                .map(Ok)
+                .left_stream(),
+            Err(error) => once(ready(Err(error))).right_stream(),
+        };
+        once(ready(Ok(LeaseMessage::Init))).chain(stream)
+    }
+);
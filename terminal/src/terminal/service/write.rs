@@ -1,5 +1,6 @@
 use server_fn::ServerFnError;
 use tonic::Status;
+use tracing::warn;
 
 use crate::api::shared::terminal_schema::TerminalAddress;
 use crate::backend::client_service::remote_fn_service;
@@ -11,6 +12,23 @@ pub async fn write(terminal: TerminalAddress, data: String) -> Result<(), Server
         .await?)
 }
 
+/// Writes the same input to several terminals, returning the ones that could not be written.
+pub async fn broadcast(
+    terminals: Vec<TerminalAddress>,
+    data: String,
+) -> Result<Vec<TerminalAddress>, ServerFnError> {
+    let writes = terminals.into_iter().map(|terminal| {
+        let data = data.clone();
+        async move {
+            let error = write(terminal.clone(), data).await.err()?;
+            warn!(terminal = %terminal.id, "Failed to broadcast: {error}");
+            Some(terminal)
+        }
+    });
+    let failed = futures::future::join_all(writes).await;
+    Ok(failed.into_iter().flatten().collect())
+}
+
 remote_fn_service::unary::declare_remote_fn!(
     WRITE_FN,
     "terminal.write",
@@ -0,0 +1,96 @@
+//! Endpoints to share a single terminal with the holder of a share link.
+
+use std::time::Duration;
+
+use futures::StreamExt as _;
+use terrazzo::axum::Extension;
+use terrazzo::axum::Json;
+use terrazzo::axum::Router;
+use terrazzo::axum::body::Body;
+use terrazzo::axum::extract::State;
+use terrazzo::axum::response::IntoResponse;
+use terrazzo::axum::routing::get;
+use terrazzo::axum::routing::post;
+use terrazzo::http::StatusCode;
+use terrazzo::http::header;
+use trz_gateway_common::dynamic_config::DynamicConfig;
+use trz_gateway_common::dynamic_config::has_diff::DiffArc;
+use trz_gateway_common::dynamic_config::mode;
+
+use crate::api::shared::terminal_schema::ShareAccess;
+use crate::api::shared::terminal_schema::ShareRequest;
+use crate::backend::auth::AuthConfig;
+use crate::backend::auth::layer::AuthLayer;
+use crate::backend::auth::layer::ShareLayer;
+use crate::backend::auth::share::ShareClaims;
+
+type DynAuthConfig = DiffArc<DynamicConfig<DiffArc<AuthConfig>, mode::RO>>;
+
+/// - `/share` issues share links, it requires the session token.
+/// - `/shared/*` is only reachable with the token of a share link.
+pub(crate) fn share_routes(auth_config: &DynAuthConfig) -> Router {
+    let share = Router::new()
+        .route("/share", post(make_share_token))
+        .with_state(auth_config.clone())
+        .route_layer(AuthLayer {
+            auth_config: auth_config.clone(),
+        });
+    let shared = Router::new()
+        .nest(
+            "/shared",
+            Router::new()
+                .route("/terminal", get(shared_terminal))
+                .route("/stream", get(watch))
+                .route("/write", post(write)),
+        )
+        .route_layer(ShareLayer {
+            auth_config: auth_config.clone(),
+        });
+    share.merge(shared)
+}
+
+async fn make_share_token(
+    State(auth_config): State<DynAuthConfig>,
+    Json(request): Json<ShareRequest>,
+) -> Result<String, (StatusCode, String)> {
+    let lifetime = Duration::from_secs(request.lifetime);
+    auth_config
+        .with(|auth_config| {
+            auth_config.make_share_token(request.terminal, request.access, lifetime)
+        })
+        .map_err(|error| (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()))
+}
+
+async fn shared_terminal(Extension(claims): Extension<ShareClaims>) -> Json<ShareClaims> {
+    Json(claims)
+}
+
+async fn watch(
+    Extension(claims): Extension<ShareClaims>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let stream = super::service::watch::watch(claims.terminal)
+        .await
+        .map_err(|error| (StatusCode::NOT_FOUND, error.to_string()))?;
+    let stream = stream
+        .into_inner()
+        .map(|line| line.map_err(|error| std::io::Error::other(error.to_string())));
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(stream),
+    ))
+}
+
+async fn write(
+    Extension(claims): Extension<ShareClaims>,
+    data: String,
+) -> Result<(), (StatusCode, String)> {
+    if claims.access != ShareAccess::Input {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "The share link is view-only".to_owned(),
+        ));
+    }
+    super::service::write::write(claims.terminal, data)
+        .await
+        .map_err(|error| (StatusCode::INTERNAL_SERVER_ERROR, error.to_string()))
+}
@@ -0,0 +1,315 @@
+#![cfg(feature = "client")]
+
+//! Share links: the action to share a terminal tab, and the page that shows a shared terminal.
+
+use std::sync::Arc;
+
+use base64::Engine as _;
+use futures::StreamExt as _;
+use futures::channel::mpsc;
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+use serde::Deserialize;
+use terrazzo::autoclone;
+use terrazzo::html;
+use terrazzo::prelude::*;
+use terrazzo::template;
+use terrazzo::widgets::resize_event::ResizeEvent;
+use wasm_bindgen::JsCast as _;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::ReadableStreamDefaultReader;
+use web_sys::ReadableStreamReadResult;
+use web_sys::RequestInit;
+use web_sys::Response;
+use web_sys::js_sys::Uint8Array;
+
+use self::diagnostics::Instrument as _;
+use self::diagnostics::info;
+use self::diagnostics::warn;
+use super::api::LeaseMessage;
+use super::javascript::TerminalJs;
+use super::javascript::TerminalJsRc;
+use super::terminal_tab::TerminalTab;
+use crate::api::client::request::BASE_URL;
+use crate::api::client::request::Method;
+use crate::api::client::request::SendRequestError;
+use crate::api::client::request::send_request;
+use crate::api::client::request::set_headers;
+use crate::api::client::request::set_json_body;
+use crate::api::shared::terminal_schema::ShareAccess;
+use crate::api::shared::terminal_schema::ShareRequest;
+use crate::api::shared::terminal_schema::TerminalAddress;
+use crate::utils::ndjson::NdjsonBuffer;
+
+terrazzo_css::import_style!(style, "shared.scss");
+
+const XTERMJS_ATTR: &str = "data-xtermjs";
+const IS_ATTACHED: &str = "Y";
+
+/// Share links open the app with the share token in the fragment of the URL,
+/// so the token is never sent to the server as part of the URL.
+const SHARE_TOKEN: &str = "#shared=";
+
+/// The lifetimes to choose from, in seconds.
+pub const LIFETIMES: [(u64, &str); 3] = [
+    (3600, "1 hour"),
+    (3600 * 24, "1 day"),
+    (3600 * 24 * 7, "1 week"),
+];
+
+/// The state of the share banner of a terminal tab.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ShareStatus {
+    #[default]
+    Closed,
+    Open,
+    Copied(Arc<str>),
+    Failed(Arc<str>),
+}
+
+/// What the share token says about the shared terminal.
+#[derive(Deserialize)]
+struct SharedTerminal {
+    terminal: TerminalAddress,
+    access: ShareAccess,
+}
+
+/// Creates a share link, and copies it to the clipboard.
+pub async fn copy_link(
+    terminal_tab: &TerminalTab,
+    access: ShareAccess,
+    lifetime: u64,
+) -> ShareStatus {
+    let link = match make_link(&terminal_tab.address, access, lifetime).await {
+        Ok(link) => link,
+        Err(error) => {
+            warn!("Failed to create a share link: {error}");
+            return ShareStatus::Failed(error.to_string().into());
+        }
+    };
+    let window = web_sys::window().or_throw("window");
+    let promise = window.navigator().clipboard().write_text(&link);
+    if let Err(error) = JsFuture::from(promise).await {
+        warn!("Failed to copy the share link: {error:?}");
+    }
+    ShareStatus::Copied(link.into())
+}
+
+async fn make_link(
+    terminal: &TerminalAddress,
+    access: ShareAccess,
+    lifetime: u64,
+) -> Result<String, ShareError> {
+    let request = ShareRequest {
+        terminal: terminal.clone(),
+        access,
+        lifetime,
+    };
+    let url = format!("{BASE_URL}/share");
+    let response = send_request(Method::POST, url, set_json_body(&request)?).await?;
+    let token = text(&response).await?;
+    let location = web_sys::window().or_throw("window").location();
+    let origin = location.origin().map_err(ShareError::Js)?;
+    Ok(format!("{origin}/{SHARE_TOKEN}{token}"))
+}
+
+/// The token of the share link the page was opened with.
+pub fn share_token() -> Option<Arc<str>> {
+    let hash = web_sys::window()?.location().hash().ok()?;
+    let token = hash.strip_prefix(SHARE_TOKEN)?;
+    (!token.is_empty()).then(|| token.into())
+}
+
+/// The page of a share link: the output of the shared terminal, and its input if the link allows.
+#[autoclone]
+#[html]
+#[template]
+pub fn shared_terminal(token: Arc<str>) -> XElement {
+    let status = XSignal::new("shared-status", XString::from("Connecting..."));
+    div(
+        key = "shared",
+        class = style::SHARED,
+        div(class = style::HEADER, shared_status(status.clone())),
+        div(
+            class = style::SCREEN,
+            div(move |template| {
+                autoclone!(token, status);
+                attach_shared(template, token.clone(), status.clone())
+            }),
+        ),
+    )
+}
+
+#[html]
+#[template(tag = span)]
+fn shared_status(#[signal] status: XString) -> XElement {
+    tag("{status}")
+}
+
+fn attach_shared(template: XTemplate, token: Arc<str>, status: XSignal<XString>) -> Consumers {
+    let element = template.element();
+    if let Some(IS_ATTACHED) = element.get_attribute(XTERMJS_ATTR).as_deref() {
+        return Consumers::default();
+    }
+    element
+        .set_attribute(XTERMJS_ATTR, IS_ATTACHED)
+        .or_throw(XTERMJS_ATTR);
+    let xtermjs = TerminalJsRc::new();
+    xtermjs.open(&element);
+    xtermjs.fit();
+    let io = async move {
+        let unsubscribe_resize_event = ResizeEvent::signal().add_subscriber({
+            let xtermjs = xtermjs.clone();
+            move |_| xtermjs.fit()
+        });
+        match show_shared(&xtermjs, &token, &status).await {
+            Ok(()) => status.set("The terminal was closed"),
+            Err(error) => {
+                warn!("The shared terminal failed: {error}");
+                status.set(error.to_string());
+            }
+        }
+        drop(unsubscribe_resize_event);
+    };
+    spawn_local(io.in_current_span());
+    Consumers::default()
+}
+
+async fn show_shared(
+    xtermjs: &TerminalJs,
+    token: &Arc<str>,
+    status: &XSignal<XString>,
+) -> Result<(), ShareError> {
+    let response = send_request(Method::GET, shared_url("terminal"), authorize(token)).await?;
+    let SharedTerminal { terminal, access } = serde_json::from_str(&text(&response).await?)?;
+    let terminal_id = &terminal.id;
+    let _on_data = match access {
+        ShareAccess::View => {
+            status.set(format!("Terminal {terminal_id} (view only)"));
+            None
+        }
+        ShareAccess::Input => {
+            status.set(format!("Terminal {terminal_id}"));
+            let (input_tx, input_rx) = mpsc::unbounded();
+            spawn_local(write_loop(token.clone(), input_rx).in_current_span());
+            let on_data: Closure<dyn FnMut(JsValue)> = Closure::new(move |data: JsValue| {
+                let data = data.as_string().unwrap_or_default();
+                let _ = input_tx.unbounded_send(data);
+            });
+            xtermjs.on_data(&on_data);
+            Some(on_data)
+        }
+    };
+    xtermjs.focus();
+
+    let response = send_request(Method::GET, shared_url("stream"), authorize(token)).await?;
+    let body = response.body().ok_or(ShareError::MissingBody)?;
+    let reader: ReadableStreamDefaultReader = body.get_reader().unchecked_into();
+    let mut parser = NdjsonBuffer::<LeaseMessage>::default();
+    let mut pending = vec![];
+    loop {
+        let chunk = JsFuture::from(reader.read())
+            .await
+            .map_err(ShareError::Js)?;
+        let chunk: ReadableStreamReadResult = chunk.unchecked_into();
+        if chunk.get_done().unwrap_or(true) {
+            return Ok(());
+        }
+        pending.extend(Uint8Array::new(&chunk.get_value()).to_vec());
+
+        // Only parse complete lines, chunks can split multi-byte characters.
+        let Some(newline) = pending.iter().rposition(|b| *b == b'\n') else {
+            continue;
+        };
+        let lines = pending.drain(..=newline).collect::<Vec<_>>();
+        let mut buffer = vec![];
+        for message in parser.push_chunk(&String::from_utf8_lossy(&lines)) {
+            match message? {
+                LeaseMessage::Base64(data) => {
+                    buffer.extend(base64::engine::general_purpose::STANDARD.decode(data)?);
+                }
+                LeaseMessage::Utf8(data) => buffer.extend(data.as_bytes()),
+                LeaseMessage::Exit(exit_status) => info!("The shell exited with {exit_status}"),
+                LeaseMessage::Eos => {
+                    send(xtermjs, buffer).await;
+                    return Ok(());
+                }
+                LeaseMessage::Error(error) => return Err(ShareError::Stream(error)),
+                LeaseMessage::Init | LeaseMessage::Reader(_) => {}
+            }
+        }
+        send(xtermjs, buffer).await;
+    }
+}
+
+async fn send(xtermjs: &TerminalJs, data: Vec<u8>) {
+    if data.is_empty() {
+        return;
+    }
+    let value = Uint8Array::new_with_length(data.len() as u32);
+    value.copy_from(&data);
+    xtermjs.send(value.into()).await;
+}
+
+/// Sends the input one request at a time, so keystrokes can't be reordered.
+async fn write_loop(token: Arc<str>, input_rx: mpsc::UnboundedReceiver<String>) {
+    let mut input_rx = input_rx.ready_chunks(100);
+    while let Some(data) = input_rx.next().await {
+        let data = data.concat();
+        let authorize = authorize(&token);
+        let on_request = move |request: &RequestInit| {
+            authorize(request);
+            request.set_body(&JsValue::from_str(&data));
+        };
+        if let Err(error) = send_request(Method::POST, shared_url("write"), on_request).await {
+            return warn!("Failed to write to the shared terminal: {error}");
+        }
+    }
+}
+
+fn shared_url(path: &str) -> String {
+    format!("{BASE_URL}/shared/{path}")
+}
+
+/// Share tokens are sent as bearer tokens, they are not session cookies.
+fn authorize(token: &str) -> impl FnOnce(&RequestInit) {
+    let authorization = format!("Bearer {token}");
+    set_headers(move |headers| {
+        headers
+            .set("authorization", &authorization)
+            .or_throw("Set 'authorization'");
+    })
+}
+
+async fn text(response: &Response) -> Result<String, ShareError> {
+    let text = response.text().map_err(ShareError::Js)?;
+    let text = JsFuture::from(text).await.map_err(ShareError::Js)?;
+    text.as_string().ok_or(ShareError::InvalidBody)
+}
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+pub enum ShareError {
+    #[error("[{n}] {0}", n = self.name())]
+    SendRequest(#[from] SendRequestError),
+
+    #[error("[{n}] {0}", n = self.name())]
+    Json(#[from] serde_json::Error),
+
+    #[error("[{n}] {0}", n = self.name())]
+    Base64(#[from] base64::DecodeError),
+
+    #[error("[{n}] {0:?}", n = self.name())]
+    Js(JsValue),
+
+    #[error("[{n}] The response has no body", n = self.name())]
+    MissingBody,
+
+    #[error("[{n}] The response is not text", n = self.name())]
+    InvalidBody,
+
+    #[error("[{n}] {0}", n = self.name())]
+    Stream(String),
+}
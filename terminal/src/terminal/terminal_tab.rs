@@ -1,4 +1,6 @@
 use std::ops::Deref;
+#[cfg(feature = "text-editor")]
+use std::path::Path;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -14,8 +16,10 @@ use terrazzo::prelude::*;
 use terrazzo::template;
 use terrazzo::widgets::debounce::DoDebounce;
 use terrazzo::widgets::editable::editable;
+use terrazzo::widgets::element_capture::ElementCapture;
 use terrazzo::widgets::tabs::TabDescriptor;
 use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlSelectElement;
 
 use self::diagnostics::Instrument as _;
 use self::diagnostics::Level;
@@ -23,8 +27,15 @@ use self::diagnostics::debug;
 use self::diagnostics::enabled;
 use self::diagnostics::warn;
 use super::attach::attach;
+use super::broadcast;
+use super::client::OnExit;
 use super::javascript::TerminalJsRc;
+use super::shared;
+use super::shared::ShareStatus;
 use super::ui::TerminalsState;
+use crate::api::shared::terminal_schema::ExitStatus;
+use crate::api::shared::terminal_schema::ShareAccess;
+use crate::api::shared::terminal_schema::Size;
 use crate::api::shared::terminal_schema::TabTitle;
 use crate::api::shared::terminal_schema::TerminalAddress;
 use crate::api::shared::terminal_schema::TerminalDef;
@@ -34,6 +45,8 @@ use crate::terminal::client as terminal_api;
 use crate::terminal::client::LiveTerminalDef;
 use crate::terminal::ui::style;
 use crate::terminal_id::TerminalId;
+#[cfg(feature = "text-editor")]
+use crate::tiles::signals::TilePtr;
 use crate::utils::watch;
 
 #[nameth]
@@ -45,6 +58,22 @@ pub struct TerminalTabInner {
     pub selected: XSignal<bool>,
     pub xtermjs: Mutex<Option<WithGenerationId<TerminalJsRc>>>,
     pub attachment_cancel: Mutex<Option<oneshot::Sender<()>>>,
+
+    /// The reader of the terminal stream, to tell the server who asks for a new size.
+    pub reader: Mutex<Option<usize>>,
+
+    /// Set when the shell has exited, until the user decides what to do next.
+    pub exit_status: XSignal<Option<ExitStatus>>,
+    pub on_exit: Mutex<Option<oneshot::Sender<OnExit>>>,
+
+    /// Whether the terminal is being recorded in asciicast format.
+    pub recording: XSignal<bool>,
+
+    /// Asks the stream loop to reopen the terminal with its whole history.
+    pub replay_history: watch::WatchTx,
+
+    /// Whether the banner to create share links is open, and the last link that was created.
+    pub share: XSignal<ShareStatus>,
     #[expect(unused)]
     registrations: Consumers,
 }
@@ -61,6 +90,7 @@ impl TerminalTab {
             title,
             order,
             tile,
+            profile,
         } = terminal_definition;
         let terminal_id = &address.id;
         let selected = {
@@ -114,10 +144,17 @@ impl TerminalTab {
                 title,
                 order,
                 tile,
+                profile,
             },
             selected,
             xtermjs: Mutex::new(None),
             attachment_cancel: Mutex::new(None),
+            reader: Mutex::new(None),
+            exit_status: XSignal::new("exit_status", None),
+            on_exit: Mutex::new(None),
+            recording: XSignal::new("recording", false),
+            replay_history: watch::WatchTx::new(),
+            share: XSignal::new("share", ShareStatus::Closed),
             registrations,
         }))
     }
@@ -159,6 +196,9 @@ impl TabDescriptor for TerminalTab {
             autoclone!(id, title, selected_tab);
             print_editable_title(template, id.clone(), title.clone(), selected_tab.clone())
         });
+        let record_button = record_button(self.clone(), self.recording.clone());
+        let history_button = history_button(self.clone());
+        let share_button = share_button(self.clone());
         let close_button = img(
             key = "close-icon",
             class = style::CLOSE_ICON,
@@ -176,7 +216,12 @@ impl TabDescriptor for TerminalTab {
             },
         );
 
-        div([title_link, close_button]..)
+        let broadcast_button = broadcast_button(terminal.clone(), broadcast::group());
+        let mut buttons = vec![title_link, broadcast_button];
+        #[cfg(feature = "text-editor")]
+        buttons.push(cwd_button(self.clone(), state.tile.clone()));
+        buttons.extend([share_button, history_button, record_button, close_button]);
+        div(buttons..)
     }
 
     #[autoclone]
@@ -236,6 +281,9 @@ impl TabDescriptor for TerminalTab {
                     notify_mouse_rx.clone(),
                 )
             }),
+            exit_banner(this.clone(), this.exit_status.clone()),
+            broadcast_banner(this.address.clone(), broadcast::group()),
+            share_banner(this.clone(), this.share.clone()),
             input_overlay_html,
         )
     }
@@ -262,12 +310,60 @@ impl Deref for TerminalTabInner {
 }
 
 impl TerminalTabInner {
+    fn resolve_exit(&self, on_exit: OnExit) {
+        if let Some(on_exit_tx) = self.on_exit.lock().or_throw("on_exit").take() {
+            let _ = on_exit_tx.send(on_exit);
+        }
+    }
+
+    async fn toggle_recording(&self) {
+        let terminal = &self.address;
+        if self.recording.get_value_untracked() {
+            if let Err(error) = terminal_api::stop_recording(terminal).await {
+                warn!("Failed to stop recording: {error}");
+            }
+            self.recording.set(false);
+            return;
+        }
+        let xtermjs = self.xtermjs.lock().or_throw("xtermjs").clone();
+        let Some(xtermjs) = xtermjs else {
+            return warn!("Can't record a detached terminal");
+        };
+        let size = Size {
+            rows: xtermjs.rows().as_f64().or_throw("rows") as i32,
+            cols: xtermjs.cols().as_f64().or_throw("cols") as i32,
+        };
+        let title = self.title.get_value_untracked();
+        let title = title.override_title.unwrap_or(title.shell_title);
+        match terminal_api::start_recording(terminal, size, Some(title.to_string())).await {
+            Ok(recording) => {
+                debug!("Recording to {}", recording.name);
+                self.recording.set(true);
+            }
+            Err(error) => warn!("Failed to start recording: {error}"),
+        }
+    }
+
+    #[cfg(feature = "text-editor")]
+    async fn open_cwd(&self, tile: TilePtr) {
+        let shell_state = match terminal_api::shell_state(&self.address).await {
+            Ok(shell_state) => shell_state,
+            Err(error) => return warn!("Failed to get the shell state: {error}"),
+        };
+        let Some(cwd) = shell_state.cwd else {
+            return warn!("The shell did not report its current directory");
+        };
+        let remote = self.address.via.clone();
+        crate::text_editor::ui::open_folder(tile, remote, Path::new(&cwd).into()).await;
+    }
+
     pub fn to_terminal_def(&self) -> TerminalDef {
         TerminalDef {
             address: self.address.clone(),
             title: self.title.get_value_untracked().map(|t| t.to_string()),
             order: self.order,
             tile: self.tile,
+            profile: self.profile.clone(),
         }
     }
 }
@@ -308,6 +404,217 @@ fn print_editable_title(
     })
 }
 
+#[autoclone]
+#[html]
+#[template(tag = div)]
+fn exit_banner(terminal_tab: TerminalTab, #[signal] exit_status: Option<ExitStatus>) -> XElement {
+    let Some(exit_status) = exit_status else {
+        return tag(style::visibility = "hidden", style::display = "none");
+    };
+    tag(
+        class = style::EXIT_BANNER,
+        span("Process exited ({exit_status})"),
+        button(
+            "Respawn",
+            click = move |_| {
+                autoclone!(terminal_tab);
+                terminal_tab.resolve_exit(OnExit::Respawn);
+            },
+        ),
+        button(
+            "Close",
+            click = move |_| {
+                autoclone!(terminal_tab);
+                terminal_tab.resolve_exit(OnExit::Close);
+            },
+        ),
+    )
+}
+
+#[autoclone]
+#[html]
+#[template(tag = img)]
+fn broadcast_button(terminal: TerminalAddress, #[signal] group: Vec<TerminalAddress>) -> XElement {
+    let is_member = group.contains(&terminal);
+    tag(
+        key = "broadcast-icon",
+        class = style::BROADCAST_ICON,
+        class = is_member.then_some(style::BROADCASTING),
+        title = if is_member {
+            "Stop receiving broadcast input"
+        } else {
+            "Broadcast input"
+        },
+        src = icons::broadcast(),
+        click = move |ev: web_sys::MouseEvent| {
+            autoclone!(terminal);
+            ev.stop_propagation();
+            broadcast::toggle(&terminal);
+        },
+    )
+}
+
+#[autoclone]
+#[html]
+#[template(tag = div)]
+fn broadcast_banner(terminal: TerminalAddress, #[signal] group: Vec<TerminalAddress>) -> XElement {
+    if !group.contains(&terminal) {
+        return tag(style::visibility = "hidden", style::display = "none");
+    }
+    let count = group.len();
+    tag(
+        class = style::BROADCAST_BANNER,
+        span("Broadcasting input to {count} terminals"),
+        button(
+            "Leave",
+            click = move |_| {
+                autoclone!(terminal);
+                broadcast::leave(&terminal.id);
+            },
+        ),
+    )
+}
+
+#[autoclone]
+#[html]
+#[template(tag = img)]
+fn record_button(terminal_tab: TerminalTab, #[signal] recording: bool) -> XElement {
+    tag(
+        key = "record-icon",
+        class = style::RECORD_ICON,
+        class = recording.then_some(style::RECORDING),
+        title = if recording {
+            "Stop recording"
+        } else {
+            "Record"
+        },
+        src = if recording {
+            icons::stop_recording()
+        } else {
+            icons::record()
+        },
+        click = move |ev: web_sys::MouseEvent| {
+            autoclone!(terminal_tab);
+            ev.stop_propagation();
+            let toggle_task = async move {
+                autoclone!(terminal_tab);
+                terminal_tab.toggle_recording().await;
+            };
+            spawn_local(toggle_task.in_current_span());
+        },
+    )
+}
+
+#[autoclone]
+#[html]
+#[template(tag = div)]
+fn share_banner(terminal_tab: TerminalTab, #[signal] share: ShareStatus) -> XElement {
+    let status = match share {
+        ShareStatus::Closed => return tag(style::visibility = "hidden", style::display = "none"),
+        ShareStatus::Open => span("Share this terminal for"),
+        ShareStatus::Copied(link) => span(
+            "Copied ",
+            a(href = link.to_string(), target = "_blank", "{link}"),
+        ),
+        ShareStatus::Failed(error) => span(class = style::SHARE_ERROR, "{error}"),
+    };
+    let lifetime = ElementCapture::<HtmlSelectElement>::default();
+    let options = shared::LIFETIMES
+        .iter()
+        .map(|(seconds, label)| option(value = seconds.to_string(), "{label}"));
+    let copy_button = |access: ShareAccess| {
+        move |_| {
+            autoclone!(terminal_tab, lifetime);
+            let lifetime = lifetime.with(|select| select.value());
+            let lifetime = lifetime.parse().unwrap_or(shared::LIFETIMES[0].0);
+            let copy_task = async move {
+                autoclone!(terminal_tab);
+                let share = shared::copy_link(&terminal_tab, access, lifetime).await;
+                terminal_tab.share.set(share);
+            };
+            spawn_local(copy_task.in_current_span());
+        }
+    };
+    tag(
+        class = style::SHARE_BANNER,
+        status,
+        select(before_render = lifetime.capture(), options..),
+        button(
+            "Copy view-only link",
+            click = copy_button(ShareAccess::View),
+        ),
+        button(
+            "Copy read-write link",
+            click = copy_button(ShareAccess::Input),
+        ),
+        button(
+            "Close",
+            click = move |_| {
+                autoclone!(terminal_tab);
+                terminal_tab.share.set(ShareStatus::Closed);
+            },
+        ),
+    )
+}
+
+/// Opens the banner to create share links.
+#[autoclone]
+#[html]
+fn share_button(terminal_tab: TerminalTab) -> XElement {
+    img(
+        key = "share-icon",
+        class = style::SHARE_ICON,
+        title = "Share",
+        src = icons::share(),
+        click = move |ev: web_sys::MouseEvent| {
+            autoclone!(terminal_tab);
+            ev.stop_propagation();
+            terminal_tab.share.set(ShareStatus::Open);
+        },
+    )
+}
+
+/// Reopens the terminal and replays its whole history, including what was spilled to disk.
+#[autoclone]
+#[html]
+fn history_button(terminal_tab: TerminalTab) -> XElement {
+    img(
+        key = "history-icon",
+        class = style::HISTORY_ICON,
+        title = "Load the whole history",
+        src = icons::history(),
+        click = move |ev: web_sys::MouseEvent| {
+            autoclone!(terminal_tab);
+            ev.stop_propagation();
+            if terminal_tab.replay_history.notify(()).is_err() {
+                warn!("The terminal is not attached");
+            }
+        },
+    )
+}
+
+/// Opens the current directory of the shell in the text editor.
+#[cfg(feature = "text-editor")]
+#[autoclone]
+#[html]
+fn cwd_button(terminal_tab: TerminalTab, tile: TilePtr) -> XElement {
+    img(
+        key = "cwd-icon",
+        class = style::CWD_ICON,
+        title = "Open the current directory",
+        src = icons::folder(),
+        click = move |ev: web_sys::MouseEvent| {
+            autoclone!(terminal_tab, tile);
+            ev.stop_propagation();
+            let open_cwd_task = async move {
+                autoclone!(terminal_tab, tile);
+                terminal_tab.open_cwd(tile).await;
+            };
+            spawn_local(open_cwd_task.in_current_span());
+        },
+    )
+}
+
 #[html]
 #[template]
 fn print_title(#[signal] title: XString) -> XElement {
@@ -6,13 +6,13 @@ use terrazzo::prelude::*;
 use terrazzo::widgets::tabs::TabsDescriptor;
 use terrazzo::widgets::tabs::TabsState;
 
+use super::scrollback_search::scrollback_search;
 use super::terminal_tab::TerminalTab;
 use super::ui::TerminalsState;
 use crate::api::client_address::ClientAddress;
 use crate::assets::icons;
 use crate::frontend::menu::menu;
-use crate::frontend::remotes::RemotesState;
-use crate::terminal::terminal_tabs::add_tab::create_terminal;
+use crate::terminal::terminal_tabs::add_tab::LaunchMenu;
 use crate::terminal_id::TerminalId;
 
 mod add_tab;
@@ -47,37 +47,25 @@ impl TabsDescriptor for TerminalTabs {
     #[autoclone]
     #[html]
     fn after_titles(&self, state: &TerminalsState) -> impl IntoIterator<Item = impl Into<XNode>> {
-        let remotes_state = RemotesState::new();
-        [div(
+        let launch_menu = LaunchMenu::new();
+        let add_tab = div(
             key = "add-tab-icon",
             class = style::ADD_TAB_ICON,
             #[cfg(not(feature = "client-prod"))]
             class = "add-tab-icon",
             div(
-                class %= add_tab::active(remotes_state.remotes.clone()),
+                class %= add_tab::active(launch_menu.entries.clone()),
                 img(src = icons::add_tab()),
                 click = move |_| {
                     autoclone!(state);
-                    add_tab::create_terminal(state.clone(), ClientAddress::default())
+                    add_tab::create_terminal(state.clone(), ClientAddress::default(), None)
                 },
-                mouseenter = remotes_state.mouseenter(),
+                mouseenter = launch_menu.mouseenter(),
             ),
-            mouseleave = remotes_state.mouseleave(),
-            remotes_state.show_remotes_dropdown(
-                |remote| {
-                    let remote_name = if remote.is_empty() {
-                        "Local".into()
-                    } else {
-                        format!("{remote} ⏎")
-                    };
-                    (remote_name, None)
-                },
-                move |_, remote| {
-                    autoclone!(state);
-                    create_terminal(state.clone(), remote)
-                },
-            ),
-        )]
+            mouseleave = launch_menu.mouseleave(),
+            launch_menu.show_dropdown(state.clone()),
+        );
+        [add_tab, scrollback_search(state.clone())]
     }
 }
 
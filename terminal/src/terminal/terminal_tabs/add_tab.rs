@@ -1,35 +1,184 @@
+use std::iter::once;
+use std::rc::Rc;
+use std::time::Duration;
+
 use terrazzo::autoclone;
+use terrazzo::html;
 use terrazzo::prelude::*;
 use terrazzo::template;
+use terrazzo::widgets::cancellable::Cancellable;
+use terrazzo::widgets::debounce::DoDebounce as _;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::MouseEvent;
 
 use self::diagnostics::Instrument as _;
+use self::diagnostics::debug;
 use self::diagnostics::warn;
+use crate::api::client::remotes_api;
 use crate::api::client_address::ClientAddress;
-use crate::frontend::remotes::Remotes;
 use crate::terminal::client as terminal_api;
 use crate::terminal::terminal_tab::TerminalTab;
 use crate::terminal::ui::TerminalsState;
 
+/// The remotes and launch profiles offered by the "new tab" menu.
+#[derive(Clone)]
+pub struct LaunchMenu {
+    pub entries: XSignal<LaunchEntries>,
+    show_entries: Cancellable<()>,
+    hide_entries: Cancellable<Duration>,
+}
+
+pub type LaunchEntries = Option<Rc<Vec<LaunchEntry>>>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LaunchEntry {
+    remote: ClientAddress,
+    profile: Option<String>,
+}
+
 #[template(wrap = true)]
-pub fn active(#[signal] remotes: Remotes) -> XAttributeValue {
-    if let Remotes::Some { .. } = remotes {
+pub fn active(#[signal] entries: LaunchEntries) -> XAttributeValue {
+    if has_choices(&entries) {
         Some(super::style::ACTIVE)
     } else {
         None
     }
 }
 
+fn has_choices(entries: &LaunchEntries) -> bool {
+    entries.as_ref().is_some_and(|entries| entries.len() > 1)
+}
+
+impl LaunchMenu {
+    pub fn new() -> Self {
+        Self {
+            entries: XSignal::new("launch-entries", None),
+            show_entries: Cancellable::new(),
+            hide_entries: Duration::from_millis(250).cancellable(),
+        }
+    }
+
+    #[autoclone]
+    pub fn mouseenter(&self) -> impl Fn(MouseEvent) + 'static {
+        let launch_menu = self.clone();
+        move |_| {
+            let Self {
+                entries,
+                show_entries,
+                hide_entries,
+            } = &launch_menu;
+            show_entries.cancel();
+            let update_entries = show_entries.capture(move |new_entries| {
+                autoclone!(entries);
+                entries.set(new_entries)
+            });
+            hide_entries.cancel();
+            let fetch_entries = async move {
+                let entries = fetch_entries().await;
+                if update_entries(Some(entries.into())).is_none() {
+                    debug!("Updating launch entries was canceled");
+                }
+            };
+            spawn_local(fetch_entries.in_current_span());
+        }
+    }
+
+    #[autoclone]
+    pub fn mouseleave(&self) -> impl Fn(MouseEvent) + 'static {
+        let Self {
+            entries,
+            hide_entries,
+            ..
+        } = self;
+        hide_entries.wrap(move |_| {
+            autoclone!(entries);
+            entries.set(None);
+        })
+    }
+
+    pub fn show_dropdown(&self, state: TerminalsState) -> XElement {
+        show_dropdown(state, self.entries.clone(), self.hide_entries.clone())
+    }
+}
+
+async fn fetch_entries() -> Vec<LaunchEntry> {
+    let remotes = remotes_api::remotes().await.unwrap_or_else(|error| {
+        warn!("Failed to fetch remotes: {error}");
+        vec![]
+    });
+    let mut entries = vec![];
+    for remote in once(ClientAddress::default()).chain(remotes) {
+        let profiles = terminal_api::list_profiles(remote.clone())
+            .await
+            .unwrap_or_else(|error| {
+                warn!("Failed to fetch launch profiles of '{remote}': {error}");
+                vec![]
+            });
+        entries.push(LaunchEntry {
+            remote: remote.clone(),
+            profile: None,
+        });
+        entries.extend(profiles.into_iter().map(|profile| LaunchEntry {
+            remote: remote.clone(),
+            profile: Some(profile),
+        }));
+    }
+    entries
+}
+
 #[autoclone]
-pub fn create_terminal(state: TerminalsState, client_address: ClientAddress) {
+#[html]
+#[template(tag = ul)]
+fn show_dropdown(
+    state: TerminalsState,
+    #[signal] entries: LaunchEntries,
+    hide_entries: Cancellable<Duration>,
+) -> XElement {
+    let Some(entries) = entries.filter(|entries| entries.len() > 1) else {
+        return tag(style::visibility = "hidden", style::display = "none");
+    };
+    let items = entries.iter().map(|entry| {
+        let LaunchEntry { remote, profile } = entry.clone();
+        let remote_name = if remote.is_empty() {
+            "Local".to_owned()
+        } else {
+            remote.to_string()
+        };
+        let label = match &profile {
+            Some(profile) => format!("{remote_name} › {profile}"),
+            None => format!("{remote_name} ⏎"),
+        };
+        li(
+            "{label}",
+            mouseenter = move |_| {
+                autoclone!(hide_entries);
+                hide_entries.cancel();
+            },
+            click = move |_| {
+                autoclone!(state);
+                create_terminal(state.clone(), remote.clone(), profile.clone())
+            },
+        )
+    });
+    tag(class = super::style::LAUNCH_LIST, items..)
+}
+
+#[autoclone]
+pub fn create_terminal(
+    state: TerminalsState,
+    client_address: ClientAddress,
+    profile: Option<String>,
+) {
     let task = async move {
         autoclone!(state, client_address);
-        let terminal_def = match terminal_api::new_id(client_address.clone(), state.tile.id).await {
-            Ok(id) => id,
-            Err(error) => {
-                warn!("Failed to allocate new ID: {error}");
-                return;
-            }
-        };
+        let terminal_def =
+            match terminal_api::new_id(client_address.clone(), state.tile.id, profile).await {
+                Ok(id) => id,
+                Err(error) => {
+                    warn!("Failed to allocate new ID: {error}");
+                    return;
+                }
+            };
         let new_tab = TerminalTab::new(terminal_def, &state.selected_tab);
         let _batch = Batch::use_batch("add-tab");
         state.selected_tab.set(new_tab.address.id.clone());
@@ -20,8 +20,10 @@ use self::diagnostics::Instrument as _;
 use self::diagnostics::debug;
 use self::diagnostics::info;
 use self::diagnostics::warn;
+use super::broadcast;
 use super::terminal_tab::TerminalTab;
 use super::terminal_tabs::TerminalTabs;
+use crate::api::shared::terminal_schema::TerminalAddress;
 use crate::api::shared::terminal_schema::TerminalDef;
 use crate::terminal::api::selected_tab;
 use crate::terminal::client as terminal_api;
@@ -170,18 +172,7 @@ pub fn render_terminals(state: TerminalsState, #[signal] terminal_tabs: Terminal
                         warn!("Terminal '{terminal_id}' not found");
                         return;
                     };
-                    if let Err(error) = super::api::set_tile_id(
-                        terminal_def.address.via,
-                        terminal_id.clone(),
-                        state.tile.id,
-                    )
-                    .await
-                    {
-                        warn!("Failed to set terminal tile: {error}");
-                        return;
-                    }
-                    state.selected_tab.set(terminal_id);
-                    REFRESH.force(());
+                    state.show_terminal(terminal_def.address);
                 });
             },
         ),
@@ -286,6 +277,24 @@ async fn load_selected_terminal_tab(state: TerminalsState) {
 }
 
 impl TerminalsState {
+    /// Selects the tab of a terminal, moving it to this tile if it is shown in another tile.
+    pub fn show_terminal(&self, terminal: TerminalAddress) {
+        let terminal_tabs = self.terminal_tabs.get_value_untracked();
+        if terminal_tabs.lookup_tab(&terminal.id).is_some() {
+            return self.selected_tab.set(terminal.id);
+        }
+        let state = self.clone();
+        let show_terminal_task = async move {
+            let TerminalAddress { id, via } = terminal;
+            if let Err(error) = super::api::set_tile_id(via, id.clone(), state.tile.id).await {
+                return warn!("Failed to set terminal tile: {error}");
+            }
+            state.selected_tab.set(id);
+            REFRESH.force(());
+        };
+        spawn_local(show_terminal_task.in_current_span());
+    }
+
     /// Callback on end-of-stream to drop the terminal tab from the UI after the process is closed in the backend.
     pub fn on_eos(&self, terminal_id: &TerminalId) {
         debug!("Closing the terminal tab");
@@ -302,6 +311,7 @@ impl TerminalsState {
         }
         terminal_tabs
             .update_ne(|terminal_tabs| Some(terminal_tabs.clone().remove_tab(terminal_id)));
+        broadcast::leave(terminal_id);
     }
 }
 
@@ -0,0 +1,104 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use server_fn::Http;
+use server_fn::ServerFnError;
+use server_fn::codec::Json;
+use server_fn::codec::StreamingText;
+use server_fn::codec::TextStream;
+use terrazzo::server;
+
+use super::operation::Operation;
+use crate::api::client_address::ClientAddress;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio::CursorPosition;
+
+/// The edits that come from the file on disk.
+#[cfg_attr(feature = "client", allow(dead_code))]
+pub const DISK_CLIENT: usize = 0;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub enum CollabEvent {
+    /// The first event of the session, with the content at the current revision.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "I"))]
+    Init {
+        #[cfg_attr(not(feature = "diagnostics"), serde(rename = "c"))]
+        client: usize,
+        #[cfg_attr(not(feature = "diagnostics"), serde(rename = "r"))]
+        revision: usize,
+        #[cfg_attr(not(feature = "diagnostics"), serde(rename = "t"))]
+        content: Arc<str>,
+    },
+
+    /// An edit from another client, or from the file on disk.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "E"))]
+    Edit {
+        #[cfg_attr(not(feature = "diagnostics"), serde(rename = "c"))]
+        client: usize,
+        #[cfg_attr(not(feature = "diagnostics"), serde(rename = "r"))]
+        revision: usize,
+        #[cfg_attr(not(feature = "diagnostics"), serde(rename = "o"))]
+        operation: Operation,
+    },
+
+    /// The last edit of this client was applied as this revision.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "A"))]
+    Ack {
+        #[cfg_attr(not(feature = "diagnostics"), serde(rename = "r"))]
+        revision: usize,
+    },
+
+    /// The cursor of another client, in UTF-16 offsets.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "C"))]
+    Cursor {
+        #[cfg_attr(not(feature = "diagnostics"), serde(rename = "c"))]
+        client: usize,
+        #[cfg_attr(not(feature = "diagnostics"), serde(rename = "p"))]
+        position: CursorPosition,
+    },
+
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "L"))]
+    Left {
+        #[cfg_attr(not(feature = "diagnostics"), serde(rename = "c"))]
+        client: usize,
+    },
+}
+
+/// Joins the editing session of the file, streams [CollabEvent]s as ndjson.
+#[server(protocol = Http<Json, StreamingText>)]
+pub async fn join(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<TextStream, ServerFnError> {
+    use tracing::info_span;
+    use tracing_futures::Instrument as _;
+    let span = info_span!("Collab", ?path);
+    super::service::join(remote, path).instrument(span).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn edit(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    client: usize,
+    revision: usize,
+    operation: Operation,
+) -> Result<(), ServerFnError> {
+    super::service::edit(remote, path, client, revision, operation).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn cursor(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    client: usize,
+    position: CursorPosition,
+) -> Result<(), ServerFnError> {
+    super::service::cursor(remote, path, client, position).await
+}
+
+/// Merges the file on disk into the session, if it changed.
+#[server(protocol = Http<Json, Json>)]
+pub async fn reload(remote: ClientAddress, path: FilePath<Arc<Path>>) -> Result<(), ServerFnError> {
+    super::service::reload(remote, path).await
+}
@@ -0,0 +1,330 @@
+use std::path::Path;
+use std::rc::Weak;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use futures::StreamExt as _;
+use futures::future::AbortHandle;
+use futures::future::Abortable;
+use server_fn::codec::TextStream;
+use terrazzo::prelude::Ptr;
+use terrazzo::prelude::diagnostics;
+use wasm_bindgen_futures::spawn_local;
+
+use self::diagnostics::Instrument as _;
+use self::diagnostics::debug;
+use self::diagnostics::warn;
+use super::api::CollabEvent;
+use super::operation::Op;
+use super::operation::Operation;
+use super::operation::OperationError;
+use crate::frontend::remotes::Remote;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio::CursorPosition;
+use crate::utils::ndjson::NdjsonBuffer;
+
+/// Shows the changes of the other clients in the editor.
+pub trait CollabView: 'static {
+    /// The editor joined the session, and must show its content.
+    fn reset(&self, content: String);
+
+    /// Edits from other clients, `content` is the result.
+    fn apply(&self, changes: Vec<Change>, content: String);
+
+    /// The cursor of another client moved, or the client left.
+    fn cursor(&self, client: usize, position: Option<CursorPosition>);
+}
+
+/// A change in UTF-16 offsets of the text before the edit, like CodeMirror's `ChangeSpec`.
+#[derive(Debug, serde::Serialize)]
+pub struct Change {
+    pub from: u32,
+    pub to: u32,
+    pub insert: String,
+}
+
+/// A client of the editing session of a file.
+///
+/// Local edits are not sent to the session until it acknowledges the previous one,
+/// as in ot.js.
+pub struct CollabClient {
+    remote: Remote,
+    path: FilePath<Arc<Path>>,
+    view: Box<dyn CollabView>,
+    state: Mutex<Option<ClientState>>,
+    abort_handle: AbortHandle,
+}
+
+struct ClientState {
+    client: usize,
+    revision: usize,
+
+    /// The content of the editor.
+    content: String,
+
+    /// The edit sent to the session and not acknowledged yet.
+    outstanding: Option<Operation>,
+
+    /// The edits made while waiting for the acknowledgement.
+    buffer: Option<Operation>,
+}
+
+impl CollabClient {
+    pub fn new(remote: Remote, path: FilePath<Arc<Path>>, view: impl CollabView) -> Ptr<Self> {
+        let (abort_handle, abort_registration) = AbortHandle::new_pair();
+        let this = Ptr::new(Self {
+            remote: remote.clone(),
+            path: path.clone(),
+            view: Box::new(view),
+            state: Mutex::new(None),
+            abort_handle,
+        });
+        let consume_stream = {
+            let this = Ptr::downgrade(&this);
+            async move {
+                let Ok(stream) = super::api::join(remote, path)
+                    .await
+                    .inspect_err(|error| warn!("Failed to join the editing session: {error}"))
+                else {
+                    return;
+                };
+                consume_stream(&this, stream).await;
+                if let Some(this) = this.upgrade() {
+                    this.close();
+                }
+            }
+        };
+        spawn_local(
+            async move {
+                match Abortable::new(consume_stream, abort_registration).await {
+                    Ok(()) => debug!("Editing session finished"),
+                    Err(_) => debug!("Editing session aborted"),
+                }
+            }
+            .in_current_span(),
+        );
+        this
+    }
+
+    /// Sends the edit, unless the editor is not in the session.
+    pub fn edit(self: &Ptr<Self>, content: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let Some(state) = &mut *state else {
+            return false;
+        };
+        let operation = Operation::diff(&state.content, content);
+        if operation.is_noop() {
+            return true;
+        }
+        state.content = content.to_owned();
+        match (state.outstanding.is_some(), state.buffer.take()) {
+            (false, _) => {
+                self.send(state.client, state.revision, operation.clone());
+                state.outstanding = Some(operation);
+            }
+            (true, None) => state.buffer = Some(operation),
+            (true, Some(buffer)) => match buffer.compose(&operation) {
+                Ok(buffer) => state.buffer = Some(buffer),
+                Err(error) => {
+                    warn!("Failed to compose edits: {error}");
+                    return false;
+                }
+            },
+        }
+        true
+    }
+
+    /// Shows the cursor to the other clients.
+    pub fn cursor(&self, position: CursorPosition) {
+        let Some(client) = self
+            .state
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|state| state.client)
+        else {
+            return;
+        };
+        let (remote, path) = (self.remote.clone(), self.path.clone());
+        spawn_local(async move {
+            if let Err(error) = super::api::cursor(remote, path, client, position).await {
+                debug!("Failed to send the cursor: {error}");
+            }
+        });
+    }
+
+    /// Asks the session to merge the file on disk.
+    pub fn reload(&self) -> bool {
+        if self.state.lock().unwrap().is_none() {
+            return false;
+        }
+        let (remote, path) = (self.remote.clone(), self.path.clone());
+        spawn_local(async move {
+            if let Err(error) = super::api::reload(remote, path).await {
+                warn!("Failed to reload the file in the editing session: {error}");
+            }
+        });
+        true
+    }
+
+    fn send(self: &Ptr<Self>, client: usize, revision: usize, operation: Operation) {
+        let this = Ptr::downgrade(self);
+        let (remote, path) = (self.remote.clone(), self.path.clone());
+        spawn_local(async move {
+            if let Err(error) = super::api::edit(remote, path, client, revision, operation).await {
+                warn!("Failed to send the edit: {error}");
+                if let Some(this) = this.upgrade() {
+                    this.close();
+                }
+            }
+        });
+    }
+
+    fn on_event(self: &Ptr<Self>, event: CollabEvent) {
+        let mut guard = self.state.lock().unwrap();
+        match event {
+            CollabEvent::Init {
+                client,
+                revision,
+                content,
+            } => {
+                debug!("Joined the editing session as client {client} at revision {revision}");
+                *guard = Some(ClientState {
+                    client,
+                    revision,
+                    content: content.to_string(),
+                    outstanding: None,
+                    buffer: None,
+                });
+                drop(guard);
+                self.view.reset(content.to_string());
+            }
+            CollabEvent::Edit {
+                revision,
+                operation,
+                ..
+            } => {
+                let Some(state) = &mut *guard else { return };
+                let result = state
+                    .receive(revision, operation)
+                    .map(|changes| (changes, state.content.clone()));
+                drop(guard);
+                match result {
+                    Ok((changes, content)) => self.view.apply(changes, content),
+                    Err(error) => {
+                        warn!("Failed to apply the edit: {error}");
+                        self.close();
+                    }
+                }
+            }
+            CollabEvent::Ack { revision } => {
+                let Some(state) = &mut *guard else { return };
+                state.revision = revision;
+                state.outstanding = state.buffer.take();
+                if let Some(buffer) = &state.outstanding {
+                    self.send(state.client, revision, buffer.clone());
+                }
+            }
+            CollabEvent::Cursor { client, position } => {
+                drop(guard);
+                self.view.cursor(client, Some(position));
+            }
+            CollabEvent::Left { client } => {
+                drop(guard);
+                self.view.cursor(client, None);
+            }
+        }
+    }
+
+    /// Leaves the session, edits are saved directly.
+    fn close(&self) {
+        if self.state.lock().unwrap().take().is_some() {
+            debug!("Left the editing session");
+        }
+        self.abort_handle.abort();
+    }
+}
+
+impl Drop for CollabClient {
+    fn drop(&mut self) {
+        self.abort_handle.abort();
+    }
+}
+
+impl ClientState {
+    /// Transforms the edit of another client against the local edits.
+    fn receive(
+        &mut self,
+        revision: usize,
+        mut operation: Operation,
+    ) -> Result<Vec<Change>, OperationError> {
+        if let Some(outstanding) = &self.outstanding {
+            let (outstanding, transformed) = outstanding.transform(&operation)?;
+            self.outstanding = Some(outstanding);
+            operation = transformed;
+        }
+        if let Some(buffer) = &self.buffer {
+            let (buffer, transformed) = buffer.transform(&operation)?;
+            self.buffer = Some(buffer);
+            operation = transformed;
+        }
+        let changes = changes(&operation, &self.content);
+        self.content = operation.apply(&self.content)?;
+        self.revision = revision;
+        Ok(changes)
+    }
+}
+
+async fn consume_stream(this: &Weak<CollabClient>, stream: TextStream) {
+    let mut parser = NdjsonBuffer::<CollabEvent>::default();
+    let mut stream = stream.into_inner();
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(error) => {
+                warn!("Editing session failed: {error}");
+                return;
+            }
+        };
+        for event in parser.push_chunk(&chunk) {
+            let Some(this) = this.upgrade() else { return };
+            match event {
+                Ok(event) => this.on_event(event),
+                Err(error) => warn!("Failed to parse editing session event: {error}"),
+            }
+        }
+    }
+}
+
+/// Converts the operation on `text` to changes in UTF-16 offsets.
+fn changes(operation: &Operation, text: &str) -> Vec<Change> {
+    let mut changes: Vec<Change> = vec![];
+    let mut chars = text.chars();
+    let mut position = 0;
+    for op in operation.ops() {
+        match op {
+            Op::Retain(n) => {
+                position += chars.by_ref().take(*n).map(char::len_utf16).sum::<usize>() as u32;
+            }
+            Op::Insert(insert) => changes.push(Change {
+                from: position,
+                to: position,
+                insert: insert.clone(),
+            }),
+            Op::Delete(n) => {
+                let to =
+                    position + chars.by_ref().take(*n).map(char::len_utf16).sum::<usize>() as u32;
+                match changes.last_mut() {
+                    Some(last) if last.to == position => last.to = to,
+                    _ => changes.push(Change {
+                        from: position,
+                        to,
+                        insert: String::new(),
+                    }),
+                }
+                position = to;
+            }
+        }
+    }
+    changes
+}
@@ -0,0 +1,11 @@
+//! Real-time editing of the same file from several browsers.
+//!
+//! The server holds one session per file, and merges the edits of the clients with
+//! operational transforms. Only the server writes the file to disk.
+
+pub mod api;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod operation;
+#[cfg(feature = "server")]
+mod service;
@@ -0,0 +1,344 @@
+//! Operational transform of text edits, as in ot.js.
+//!
+//! Lengths and positions are counted in chars.
+
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Operation {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "o"))]
+    ops: Vec<Op>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "b"))]
+    base_len: usize,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "t"))]
+    target_len: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Op {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "R"))]
+    Retain(usize),
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "I"))]
+    Insert(String),
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "D"))]
+    Delete(usize),
+}
+
+impl Operation {
+    #[cfg_attr(feature = "server", allow(dead_code))]
+    pub fn ops(&self) -> &[Op] {
+        &self.ops
+    }
+
+    #[cfg(test)]
+    pub fn base_len(&self) -> usize {
+        self.base_len
+    }
+
+    #[cfg(test)]
+    pub fn target_len(&self) -> usize {
+        self.target_len
+    }
+
+    pub fn is_noop(&self) -> bool {
+        matches!(self.ops.as_slice(), [] | [Op::Retain(_)])
+    }
+
+    pub fn retain(&mut self, n: usize) -> &mut Self {
+        if n == 0 {
+            return self;
+        }
+        self.base_len += n;
+        self.target_len += n;
+        if let Some(Op::Retain(last)) = self.ops.last_mut() {
+            *last += n;
+        } else {
+            self.ops.push(Op::Retain(n));
+        }
+        self
+    }
+
+    pub fn insert(&mut self, text: &str) -> &mut Self {
+        if text.is_empty() {
+            return self;
+        }
+        self.target_len += text.chars().count();
+        // Inserts always go before deletes, so equivalent operations are equal.
+        match self.ops.as_mut_slice() {
+            [.., Op::Insert(last)] | [.., Op::Insert(last), Op::Delete(_)] => *last += text,
+            [.., Op::Delete(_)] => {
+                let index = self.ops.len() - 1;
+                self.ops.insert(index, Op::Insert(text.to_owned()));
+            }
+            _ => self.ops.push(Op::Insert(text.to_owned())),
+        }
+        self
+    }
+
+    pub fn delete(&mut self, n: usize) -> &mut Self {
+        if n == 0 {
+            return self;
+        }
+        self.base_len += n;
+        if let Some(Op::Delete(last)) = self.ops.last_mut() {
+            *last += n;
+        } else {
+            self.ops.push(Op::Delete(n));
+        }
+        self
+    }
+
+    /// The operation that changes `old` into `new`, replacing what is between
+    /// their common prefix and suffix.
+    pub fn diff(old: &str, new: &str) -> Self {
+        let prefix = old
+            .chars()
+            .zip(new.chars())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let old_len = old.chars().count();
+        let new_len = new.chars().count();
+        let suffix = old
+            .chars()
+            .rev()
+            .zip(new.chars().rev())
+            .take(old_len.min(new_len) - prefix)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let inserted: String = new
+            .chars()
+            .skip(prefix)
+            .take(new_len - prefix - suffix)
+            .collect();
+        let mut operation = Self::default();
+        operation
+            .retain(prefix)
+            .insert(&inserted)
+            .delete(old_len - prefix - suffix)
+            .retain(suffix);
+        operation
+    }
+
+    pub fn apply(&self, text: &str) -> Result<String, OperationError> {
+        let actual = text.chars().count();
+        if actual != self.base_len {
+            return Err(OperationError::BaseLength {
+                expected: self.base_len,
+                actual,
+            });
+        }
+        let mut result = String::with_capacity(text.len());
+        let mut chars = text.chars();
+        for op in &self.ops {
+            match op {
+                Op::Retain(n) => result.extend(chars.by_ref().take(*n)),
+                Op::Insert(inserted) => result += inserted,
+                Op::Delete(n) => {
+                    chars.by_ref().take(*n).for_each(drop);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// The operation that has the same effect as `self` followed by `other`.
+    #[cfg_attr(feature = "server", allow(dead_code))]
+    pub fn compose(&self, other: &Self) -> Result<Self, OperationError> {
+        if self.target_len != other.base_len {
+            return Err(OperationError::BaseLength {
+                expected: other.base_len,
+                actual: self.target_len,
+            });
+        }
+        let mut result = Self::default();
+        let mut ops1 = self.ops.iter().cloned();
+        let mut ops2 = other.ops.iter().cloned();
+        let mut op1 = ops1.next();
+        let mut op2 = ops2.next();
+        loop {
+            match (op1.take(), op2.take()) {
+                (None, None) => break,
+                (Some(Op::Delete(n)), next2) => {
+                    result.delete(n);
+                    (op1, op2) = (ops1.next(), next2);
+                }
+                (next1, Some(Op::Insert(text))) => {
+                    result.insert(&text);
+                    (op1, op2) = (next1, ops2.next());
+                }
+                (None, _) | (_, None) => return Err(OperationError::Incompatible),
+                (Some(Op::Retain(n1)), Some(Op::Retain(n2))) => {
+                    let n = n1.min(n2);
+                    result.retain(n);
+                    op1 = remaining(n1, n, Op::Retain).or_else(|| ops1.next());
+                    op2 = remaining(n2, n, Op::Retain).or_else(|| ops2.next());
+                }
+                (Some(Op::Retain(n1)), Some(Op::Delete(n2))) => {
+                    let n = n1.min(n2);
+                    result.delete(n);
+                    op1 = remaining(n1, n, Op::Retain).or_else(|| ops1.next());
+                    op2 = remaining(n2, n, Op::Delete).or_else(|| ops2.next());
+                }
+                (Some(Op::Insert(text)), Some(Op::Retain(n2))) => {
+                    let (head, tail) = split_chars(&text, n2);
+                    result.insert(head);
+                    let n = head.chars().count();
+                    op1 = (!tail.is_empty())
+                        .then(|| Op::Insert(tail.to_owned()))
+                        .or_else(|| ops1.next());
+                    op2 = remaining(n2, n, Op::Retain).or_else(|| ops2.next());
+                }
+                (Some(Op::Insert(text)), Some(Op::Delete(n2))) => {
+                    let (head, tail) = split_chars(&text, n2);
+                    let n = head.chars().count();
+                    op1 = (!tail.is_empty())
+                        .then(|| Op::Insert(tail.to_owned()))
+                        .or_else(|| ops1.next());
+                    op2 = remaining(n2, n, Op::Delete).or_else(|| ops2.next());
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Transforms two concurrent operations on the same text into `(self', other')`
+    /// such that `self` followed by `other'` is the same as `other` followed by `self'`.
+    ///
+    /// Inserts at the same position from `self` go first.
+    pub fn transform(&self, other: &Self) -> Result<(Self, Self), OperationError> {
+        if self.base_len != other.base_len {
+            return Err(OperationError::BaseLength {
+                expected: self.base_len,
+                actual: other.base_len,
+            });
+        }
+        let mut result1 = Self::default();
+        let mut result2 = Self::default();
+        let mut ops1 = self.ops.iter().cloned();
+        let mut ops2 = other.ops.iter().cloned();
+        let mut op1 = ops1.next();
+        let mut op2 = ops2.next();
+        loop {
+            match (op1.take(), op2.take()) {
+                (None, None) => break,
+                (Some(Op::Insert(text)), next2) => {
+                    result1.insert(&text);
+                    result2.retain(text.chars().count());
+                    (op1, op2) = (ops1.next(), next2);
+                }
+                (next1, Some(Op::Insert(text))) => {
+                    result1.retain(text.chars().count());
+                    result2.insert(&text);
+                    (op1, op2) = (next1, ops2.next());
+                }
+                (None, _) | (_, None) => return Err(OperationError::Incompatible),
+                (Some(Op::Retain(n1)), Some(Op::Retain(n2))) => {
+                    let n = n1.min(n2);
+                    result1.retain(n);
+                    result2.retain(n);
+                    op1 = remaining(n1, n, Op::Retain).or_else(|| ops1.next());
+                    op2 = remaining(n2, n, Op::Retain).or_else(|| ops2.next());
+                }
+                (Some(Op::Delete(n1)), Some(Op::Delete(n2))) => {
+                    let n = n1.min(n2);
+                    op1 = remaining(n1, n, Op::Delete).or_else(|| ops1.next());
+                    op2 = remaining(n2, n, Op::Delete).or_else(|| ops2.next());
+                }
+                (Some(Op::Delete(n1)), Some(Op::Retain(n2))) => {
+                    let n = n1.min(n2);
+                    result1.delete(n);
+                    op1 = remaining(n1, n, Op::Delete).or_else(|| ops1.next());
+                    op2 = remaining(n2, n, Op::Retain).or_else(|| ops2.next());
+                }
+                (Some(Op::Retain(n1)), Some(Op::Delete(n2))) => {
+                    let n = n1.min(n2);
+                    result2.delete(n);
+                    op1 = remaining(n1, n, Op::Retain).or_else(|| ops1.next());
+                    op2 = remaining(n2, n, Op::Delete).or_else(|| ops2.next());
+                }
+            }
+        }
+        Ok((result1, result2))
+    }
+}
+
+fn remaining(total: usize, consumed: usize, op: impl FnOnce(usize) -> Op) -> Option<Op> {
+    (total > consumed).then(|| op(total - consumed))
+}
+
+#[cfg_attr(feature = "server", allow(dead_code))]
+fn split_chars(text: &str, n: usize) -> (&str, &str) {
+    match text.char_indices().nth(n) {
+        Some((index, _)) => text.split_at(index),
+        None => (text, ""),
+    }
+}
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+pub enum OperationError {
+    #[error("[{n}] The operation applies to {expected} chars but got {actual}", n = self.name())]
+    BaseLength { expected: usize, actual: usize },
+
+    #[error("[{n}] The operations are incompatible", n = self.name())]
+    Incompatible,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Operation;
+
+    #[test]
+    fn diff_and_apply() {
+        let operation = Operation::diff("hello world", "hello brave world");
+        assert_eq!(11, operation.base_len());
+        assert_eq!(17, operation.target_len());
+        assert_eq!("hello brave world", operation.apply("hello world").unwrap());
+        assert!(Operation::diff("same", "same").is_noop());
+        assert_eq!("é", Operation::diff("àé", "é").apply("àé").unwrap());
+    }
+
+    #[test]
+    fn apply_rejects_other_base() {
+        let operation = Operation::diff("abc", "abcd");
+        assert!(operation.apply("ab").is_err());
+    }
+
+    #[test]
+    fn compose() {
+        let text = "the quick fox";
+        let a = Operation::diff(text, "the quick brown fox");
+        let b = Operation::diff("the quick brown fox", "the brown fox jumps");
+        let composed = a.compose(&b).unwrap();
+        assert_eq!("the brown fox jumps", composed.apply(text).unwrap());
+    }
+
+    #[test]
+    fn transform_converges() {
+        let text = "the quick fox";
+        let cases = [
+            ("the quick brown fox", "the slow fox"),
+            ("the fox", "the quick red fox"),
+            ("a quick fox", "the quick fox!"),
+            ("the quick fox", ""),
+            ("XXthe quick fox", "YYthe quick fox"),
+        ];
+        for (left, right) in cases {
+            let a = Operation::diff(text, left);
+            let b = Operation::diff(text, right);
+            let (a2, b2) = a.transform(&b).unwrap();
+            let via_a = b2.apply(&a.apply(text).unwrap()).unwrap();
+            let via_b = a2.apply(&b.apply(text).unwrap()).unwrap();
+            assert_eq!(via_a, via_b, "{left:?} vs {right:?}");
+        }
+    }
+
+    #[test]
+    fn transform_puts_own_inserts_first() {
+        let a = Operation::diff("", "a");
+        let b = Operation::diff("", "b");
+        let (a2, _) = a.transform(&b).unwrap();
+        assert_eq!("ab", a2.apply("b").unwrap());
+    }
+}
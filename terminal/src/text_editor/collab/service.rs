@@ -0,0 +1,487 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::future::ready;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::OnceLock;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::Relaxed;
+use std::time::Duration;
+
+use futures::Stream;
+use futures::StreamExt as _;
+use futures::TryStreamExt as _;
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+use server_fn::ServerFnError;
+use server_fn::codec::TextStream;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::Span;
+use tracing::debug;
+use tracing::warn;
+use tracing_futures::Instrument as _;
+
+use super::api::CollabEvent;
+use super::api::DISK_CLIENT;
+use super::operation::Operation;
+use super::operation::OperationError;
+use crate::api::client_address::ClientAddress;
+use crate::backend::client_service::grpc_error::GrpcError;
+use crate::backend::client_service::grpc_error::IsGrpcError;
+use crate::backend::client_service::remote_fn_service;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio::CursorPosition;
+use crate::text_editor::fsio::FileMetadata;
+use crate::text_editor::fsio::encoding;
+use crate::text_editor::fsio::service::FsioError;
+use crate::text_editor::fsio::service::store_file;
+use crate::utils::ndjson_utils::serialize_line;
+
+static NEXT_CLIENT: AtomicUsize = AtomicUsize::new(DISK_CLIENT + 1);
+
+/// Clients that are further behind must join again.
+const MAX_HISTORY: usize = 1000;
+
+/// Edits are written to disk once they stop for that long.
+const SAVE_DELAY: Duration = Duration::from_secs(1);
+
+type Sessions = HashMap<FilePath<Arc<Path>>, Arc<Mutex<Session>>>;
+
+fn sessions() -> MutexGuard<'static, Sessions> {
+    static SESSIONS: OnceLock<Mutex<Sessions>> = OnceLock::new();
+    SESSIONS
+        .get_or_init(Mutex::default)
+        .lock()
+        .expect("sessions")
+}
+
+/// The authoritative state of a file edited by several clients.
+struct Session {
+    path: FilePath<Arc<Path>>,
+    content: String,
+    first_revision: usize,
+    history: VecDeque<Operation>,
+    clients: HashMap<usize, mpsc::UnboundedSender<CollabEvent>>,
+    disk: Disk,
+    save_scheduled: bool,
+
+    /// Serializes reads and writes of the file.
+    io: Arc<tokio::sync::Mutex<()>>,
+}
+
+/// The file as it was last read or written.
+struct Disk {
+    content: Arc<str>,
+    metadata: Arc<FileMetadata>,
+}
+
+pub async fn join(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<TextStream, ServerFnError> {
+    debug!(%remote, "Calling join({path:?})");
+    let stream = JOIN_FN.call(remote, path).await?;
+    let stream = stream.filter_map(|item| {
+        let item = item.map(|item| {
+            serialize_line(&item)
+                .inspect_err(|error| warn!("Failed to serialize: {error}"))
+                .ok()
+        });
+        let item = item.transpose();
+        return ready(item);
+    });
+    Ok(TextStream::new(
+        stream.map_err(Into::into).instrument(Span::current()),
+    ))
+}
+
+pub async fn edit(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    client: usize,
+    revision: usize,
+    operation: Operation,
+) -> Result<(), ServerFnError> {
+    Ok(EDIT_FN
+        .call(remote, (path, client, revision, operation))
+        .await?)
+}
+
+pub async fn cursor(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    client: usize,
+    position: CursorPosition,
+) -> Result<(), ServerFnError> {
+    Ok(CURSOR_FN.call(remote, (path, client, position)).await?)
+}
+
+pub async fn reload(remote: ClientAddress, path: FilePath<Arc<Path>>) -> Result<(), ServerFnError> {
+    Ok(RELOAD_FN.call(remote, path).await?)
+}
+
+remote_fn_service::streaming::declare_remote_fn!(
+    JOIN_FN,
+    "texteditor.collab.join",
+    FilePath<Arc<Path>>,
+    CollabEvent,
+    |_server, path| {
+        futures::stream::once(join_impl(path))
+            .map(|events| match events {
+                Ok(events) => events.map(Ok).left_stream(),
+                Err(error) => futures::stream::once(ready(Err(error))).right_stream(),
+            })
+            .flatten()
+            .map_err(GrpcError::from)
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    EDIT_FN,
+    "texteditor.collab.edit",
+    (FilePath<Arc<Path>>, usize, usize, Operation),
+    (),
+    |_server, (path, client, revision, operation)| async move {
+        let session = get_session(&path)?;
+        let mut session_lock = session.lock().expect("session");
+        session_lock.apply(client, revision, operation)?;
+        schedule_save(&session, &mut session_lock);
+        Ok::<_, GrpcError<CollabError>>(())
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    CURSOR_FN,
+    "texteditor.collab.cursor",
+    (FilePath<Arc<Path>>, usize, CursorPosition),
+    (),
+    |_server, (path, client, position)| async move {
+        let session = get_session(&path)?;
+        let session = session.lock().expect("session");
+        session.broadcast(client, CollabEvent::Cursor { client, position });
+        Ok::<_, GrpcError<CollabError>>(())
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    RELOAD_FN,
+    "texteditor.collab.reload",
+    FilePath<Arc<Path>>,
+    (),
+    |_server, path| async move {
+        let session = get_session(&path)?;
+        let io = session.lock().expect("session").io.clone();
+        let _io = io.lock().await;
+        reconcile(&session).await?;
+        Ok::<_, GrpcError<CollabError>>(())
+    }
+);
+
+async fn join_impl(
+    path: FilePath<Arc<Path>>,
+) -> Result<impl Stream<Item = CollabEvent>, CollabError> {
+    let client = NEXT_CLIENT.fetch_add(1, Relaxed);
+    let (tx, rx) = mpsc::unbounded_channel();
+    let mut disk = None;
+    loop {
+        {
+            let mut sessions = sessions();
+            if let Some(session) = sessions.get(&path) {
+                session.lock().expect("session").subscribe(client, tx);
+                break;
+            }
+            if let Some(disk) = disk.take() {
+                let mut session = Session::new(path.clone(), disk);
+                session.subscribe(client, tx);
+                sessions.insert(path.clone(), Arc::new(Mutex::new(session)));
+                break;
+            }
+        }
+        // The session is created with the file on disk, unless another client created it meanwhile.
+        disk = Some(read_disk(&path).await?);
+    }
+    debug!(client, "Joined");
+
+    let leave = scopeguard::guard(path, move |path| leave(&path, client));
+    Ok(UnboundedReceiverStream::new(rx).map(move |event| {
+        let _leave = &leave;
+        event
+    }))
+}
+
+fn leave(path: &FilePath<Arc<Path>>, client: usize) {
+    debug!(client, "Left");
+    let mut sessions = sessions();
+    let Some(session) = sessions.get(path).cloned() else {
+        return;
+    };
+    let mut session_lock = session.lock().expect("session");
+    session_lock.clients.remove(&client);
+    if session_lock.clients.is_empty() {
+        sessions.remove(path);
+        drop(session_lock);
+        tokio::spawn(save(session));
+    } else {
+        session_lock.broadcast(client, CollabEvent::Left { client });
+    }
+}
+
+fn get_session(path: &FilePath<Arc<Path>>) -> Result<Arc<Mutex<Session>>, CollabError> {
+    sessions()
+        .get(path)
+        .cloned()
+        .ok_or_else(|| CollabError::SessionNotFound {
+            path: path.full_path(),
+        })
+}
+
+impl Session {
+    fn new(path: FilePath<Arc<Path>>, disk: Disk) -> Self {
+        Self {
+            path,
+            content: disk.content.to_string(),
+            first_revision: 0,
+            history: VecDeque::new(),
+            clients: HashMap::new(),
+            disk,
+            save_scheduled: false,
+            io: Default::default(),
+        }
+    }
+
+    fn revision(&self) -> usize {
+        self.first_revision + self.history.len()
+    }
+
+    fn subscribe(&mut self, client: usize, tx: mpsc::UnboundedSender<CollabEvent>) {
+        let _ = tx.send(CollabEvent::Init {
+            client,
+            revision: self.revision(),
+            content: self.content.as_str().into(),
+        });
+        self.clients.insert(client, tx);
+    }
+
+    fn broadcast(&self, from: usize, event: CollabEvent) {
+        for (_, tx) in self.clients.iter().filter(|(id, _)| **id != from) {
+            let _ = tx.send(event.clone());
+        }
+    }
+
+    /// Applies the operation of a client that was based on the given revision.
+    fn apply(
+        &mut self,
+        client: usize,
+        revision: usize,
+        mut operation: Operation,
+    ) -> Result<(), CollabError> {
+        let last = self.revision();
+        if revision < self.first_revision || revision > last {
+            return Err(CollabError::Revision {
+                revision,
+                first: self.first_revision,
+                last,
+            });
+        }
+        for concurrent in self.history.range(revision - self.first_revision..) {
+            (operation, _) = operation.transform(concurrent)?;
+        }
+        self.content = operation.apply(&self.content)?;
+        self.history.push_back(operation.clone());
+        if self.history.len() > MAX_HISTORY {
+            self.history.pop_front();
+            self.first_revision += 1;
+        }
+
+        let revision = self.revision();
+        if let Some(tx) = self.clients.get(&client) {
+            let _ = tx.send(CollabEvent::Ack { revision });
+        }
+        self.broadcast(
+            client,
+            CollabEvent::Edit {
+                client,
+                revision,
+                operation,
+            },
+        );
+        Ok(())
+    }
+}
+
+fn schedule_save(session: &Arc<Mutex<Session>>, session_lock: &mut Session) {
+    if std::mem::replace(&mut session_lock.save_scheduled, true) {
+        return;
+    }
+    let session = session.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(SAVE_DELAY).await;
+        save(session).await
+    });
+}
+
+/// Writes the content of the session, merging the file first if it changed on disk.
+async fn save(session: Arc<Mutex<Session>>) {
+    let io = session.lock().expect("session").io.clone();
+    let _io = io.lock().await;
+    let (path, content, expected) = {
+        let mut session = session.lock().expect("session");
+        session.save_scheduled = false;
+        if session.content == *session.disk.content {
+            return;
+        }
+        (
+            session.path.clone(),
+            session.content.clone(),
+            session.disk.metadata.clone(),
+        )
+    };
+    let result = match store_file(path.clone(), content.clone(), Some(expected)).await {
+        Ok(metadata) => {
+            session.lock().expect("session").disk = Disk {
+                content: content.into(),
+                metadata,
+            };
+            Ok(())
+        }
+        Err(FsioError::FileChanged { .. }) => reconcile(&session).await,
+        Err(error) => Err(error.into()),
+    };
+    if let Err(error) = result {
+        warn!("Failed to save {path:?}: {error}");
+    }
+}
+
+/// Merges the changes on disk into the session, as an edit from [DISK_CLIENT].
+///
+/// Must be called while holding the `io` lock.
+async fn reconcile(session: &Arc<Mutex<Session>>) -> Result<(), CollabError> {
+    let path = session.lock().expect("session").path.clone();
+    let disk = read_disk(&path).await?;
+    let mut session_lock = session.lock().expect("session");
+    if disk.metadata.same_version(&session_lock.disk.metadata) {
+        return Ok(());
+    }
+    debug!("File changed on disk: {path:?}");
+    let external = Operation::diff(&session_lock.disk.content, &disk.content);
+    let unsaved = Operation::diff(&session_lock.disk.content, &session_lock.content);
+    let (external, _) = external.transform(&unsaved)?;
+    session_lock.disk = disk;
+    if !external.is_noop() {
+        let revision = session_lock.revision();
+        session_lock.apply(DISK_CLIENT, revision, external)?;
+    }
+    if session_lock.content != *session_lock.disk.content {
+        schedule_save(session, &mut session_lock);
+    }
+    Ok(())
+}
+
+async fn read_disk(path: &FilePath<Arc<Path>>) -> Result<Disk, CollabError> {
+    let full_path = path.full_path();
+    let metadata = tokio::fs::metadata(&full_path).await?;
+    let content = tokio::fs::read(&full_path).await?;
+    let encoding = encoding::detect(&content).unwrap_or_default();
+    Ok(Disk {
+        content: encoding::decode(&content, encoding).into(),
+        metadata: FileMetadata::single(&full_path, &metadata).into(),
+    })
+}
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+pub enum CollabError {
+    #[error("[{n}] {0}", n = self.name())]
+    IO(#[from] std::io::Error),
+
+    #[error("[{n}] {0}", n = self.name())]
+    Fsio(#[from] FsioError),
+
+    #[error("[{n}] No editing session for {path:?}", n = self.name())]
+    SessionNotFound { path: PathBuf },
+
+    #[error("[{n}] Revision {revision} is not between {first} and {last}", n = self.name())]
+    Revision {
+        revision: usize,
+        first: usize,
+        last: usize,
+    },
+
+    #[error("[{n}] {0}", n = self.name())]
+    Operation(#[from] OperationError),
+}
+
+impl IsGrpcError for CollabError {
+    fn code(&self) -> tonic::Code {
+        match self {
+            Self::IO { .. } => tonic::Code::FailedPrecondition,
+            Self::Fsio(error) => error.code(),
+            Self::SessionNotFound { .. } => tonic::Code::NotFound,
+            Self::Revision { .. } => tonic::Code::OutOfRange,
+            Self::Operation { .. } => tonic::Code::InvalidArgument,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use tokio::sync::mpsc;
+
+    use super::CollabEvent;
+    use super::Disk;
+    use super::Operation;
+    use super::Session;
+    use crate::text_editor::file_path::FilePath;
+
+    #[test]
+    fn concurrent_edits_converge() {
+        let path = FilePath {
+            base: Arc::<Path>::from(Path::new("/tmp")),
+            file: Arc::<Path>::from(Path::new("file.txt")),
+        };
+        let disk = Disk {
+            content: "hello world".into(),
+            metadata: Default::default(),
+        };
+        let mut session = Session::new(path, disk);
+        let (tx1, mut rx1) = mpsc::unbounded_channel();
+        let (tx2, mut rx2) = mpsc::unbounded_channel();
+        session.subscribe(1, tx1);
+        session.subscribe(2, tx2);
+        assert!(matches!(
+            rx1.try_recv(),
+            Ok(CollabEvent::Init { revision: 0, .. })
+        ));
+        assert!(matches!(
+            rx2.try_recv(),
+            Ok(CollabEvent::Init { revision: 0, .. })
+        ));
+
+        // Both clients edit revision 0.
+        let edit1 = Operation::diff("hello world", "hello brave world");
+        let edit2 = Operation::diff("hello world", "hello world!");
+        session.apply(1, 0, edit1).unwrap();
+        session.apply(2, 0, edit2).unwrap();
+        assert_eq!("hello brave world!", session.content);
+
+        assert!(matches!(
+            rx1.try_recv(),
+            Ok(CollabEvent::Ack { revision: 1 })
+        ));
+        let Ok(CollabEvent::Edit { operation, .. }) = rx1.try_recv() else {
+            panic!()
+        };
+        assert_eq!(
+            "hello brave world!",
+            operation.apply("hello brave world").unwrap()
+        );
+        assert!(session.apply(2, 3, Operation::default()).is_err());
+    }
+}
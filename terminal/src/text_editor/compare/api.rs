@@ -0,0 +1,40 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use server_fn::Http;
+use server_fn::ServerFnError;
+use server_fn::codec::Json;
+use terrazzo::server;
+
+use crate::api::client_address::ClientAddress;
+use crate::text_editor::file_path::FilePath;
+
+/// What the current file is compared to.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum CompareWith {
+    /// A commit, a branch, or any revision that git understands.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "R"))]
+    Revision(String),
+
+    /// The version staged in the git index.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "I"))]
+    Index,
+
+    /// The latest copy of the file in the trash.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "T"))]
+    Trash,
+
+    /// Another file.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "F"))]
+    File(FilePath<Arc<Path>>),
+}
+
+/// Loads the content the file at `path` is compared to.
+#[server(protocol = Http<Json, Json>)]
+pub async fn load_original(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    with: CompareWith,
+) -> Result<Arc<str>, ServerFnError> {
+    super::service::load_original(remote, path, with).await
+}
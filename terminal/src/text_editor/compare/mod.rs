@@ -0,0 +1,7 @@
+//! Compares the current file with a revision, the index, the trash, or another file.
+
+pub mod api;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "client")]
+pub mod ui;
@@ -0,0 +1,163 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+use server_fn::ServerFnError;
+use tracing::debug;
+
+use super::api::CompareWith;
+use crate::api::client_address::ClientAddress;
+use crate::backend::client_service::grpc_error::GrpcError;
+use crate::backend::client_service::grpc_error::IsGrpcError;
+use crate::backend::client_service::remote_fn_service;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio::git;
+use crate::text_editor::fsio::service::find_in_trash;
+
+pub async fn load_original(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    with: CompareWith,
+) -> Result<Arc<str>, ServerFnError> {
+    debug!(%remote, "Calling load_original({path:?}, {with:?})");
+    Ok(LOAD_ORIGINAL_FN.call(remote, (path, with)).await?)
+}
+
+remote_fn_service::unary::declare_remote_fn!(
+    LOAD_ORIGINAL_FN,
+    "texteditor.compare.load_original",
+    (FilePath<Arc<Path>>, CompareWith),
+    Arc<str>,
+    |server, (path, with)| {
+        let server = server.clone();
+        async move {
+            let (trash, git_trash) = server
+                .config()
+                .server
+                .with(|server| (server.trash.clone(), server.git_trash.clone()));
+            let result = load_original_impl(path, with, trash, git_trash).await;
+            result.map_err(GrpcError::from)
+        }
+    }
+);
+
+async fn load_original_impl(
+    path: FilePath<Arc<Path>>,
+    with: CompareWith,
+    trash: impl AsRef<Path>,
+    git_trash: Option<impl AsRef<Path>>,
+) -> Result<Arc<str>, CompareError> {
+    let path = path.full_path();
+    let content = match with {
+        CompareWith::Revision(revision) => {
+            if revision.is_empty() || revision.starts_with('-') {
+                return Err(CompareError::InvalidRevision { revision });
+            }
+            git::file_content_at_commit(&path, &revision)?
+        }
+        // An empty revision names the version in the index.
+        CompareWith::Index => git::file_content_at_commit(&path, "")?,
+        CompareWith::Trash => {
+            let Some(copy) = find_in_trash(&path, trash, git_trash) else {
+                return Err(CompareError::NotInTrash { path });
+            };
+            tokio::fs::read_to_string(copy).await?
+        }
+        CompareWith::File(other) => tokio::fs::read_to_string(other.full_path()).await?,
+    };
+    Ok(content.into())
+}
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+pub enum CompareError {
+    #[error("[{n}] {0}", n = self.name())]
+    IO(#[from] std::io::Error),
+
+    #[error("[{n}] Invalid revision: {revision:?}", n = self.name())]
+    InvalidRevision { revision: String },
+
+    #[error("[{n}] No copy of {path:?} in the trash", n = self.name())]
+    NotInTrash { path: PathBuf },
+}
+
+impl IsGrpcError for CompareError {
+    fn code(&self) -> tonic::Code {
+        match self {
+            Self::IO { .. } => tonic::Code::FailedPrecondition,
+            Self::InvalidRevision { .. } => tonic::Code::InvalidArgument,
+            Self::NotInTrash { .. } => tonic::Code::NotFound,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::process::Command;
+    use std::sync::Arc;
+
+    use super::CompareWith;
+    use crate::text_editor::file_path::FilePath;
+
+    #[tokio::test]
+    async fn load_original() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let repo = tempdir.path().join("repo");
+        let trash = tempdir.path().join("trash");
+        std::fs::create_dir(&repo).unwrap();
+        git(&repo, &["init", "-q", "-b", "main"]);
+        git(&repo, &["config", "user.email", "test@example.com"]);
+        git(&repo, &["config", "user.name", "Test User"]);
+        std::fs::write(repo.join("file.txt"), "first").unwrap();
+        git(&repo, &["add", "file.txt"]);
+        git(&repo, &["commit", "-q", "-m", "first"]);
+        git(&repo, &["branch", "old"]);
+        std::fs::write(repo.join("file.txt"), "second").unwrap();
+        git(&repo, &["commit", "-q", "-am", "second"]);
+        std::fs::write(repo.join("file.txt"), "staged").unwrap();
+        git(&repo, &["add", "file.txt"]);
+        std::fs::write(repo.join("file.txt"), "current").unwrap();
+        std::fs::write(repo.join("other.txt"), "other").unwrap();
+
+        let path = |file: &str| FilePath {
+            base: Arc::from(repo.as_path()),
+            file: Arc::from(Path::new(file)),
+        };
+        let load = async |with| {
+            super::load_original_impl(path("file.txt"), with, &trash, None::<&Path>)
+                .await
+                .map(|content| content.to_string())
+        };
+        assert_eq!(
+            "second",
+            load(CompareWith::Revision("HEAD".into())).await.unwrap()
+        );
+        assert_eq!(
+            "first",
+            load(CompareWith::Revision("old".into())).await.unwrap()
+        );
+        assert_eq!("staged", load(CompareWith::Index).await.unwrap());
+        assert_eq!(
+            "other",
+            load(CompareWith::File(path("other.txt"))).await.unwrap()
+        );
+        assert!(load(CompareWith::Revision("--help".into())).await.is_err());
+        assert!(load(CompareWith::Trash).await.is_err());
+
+        std::fs::create_dir(&trash).unwrap();
+        std::fs::write(trash.join("file.txt"), "deleted").unwrap();
+        assert_eq!("deleted", load(CompareWith::Trash).await.unwrap());
+    }
+
+    fn git(repo: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(repo)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?} failed");
+    }
+}
@@ -0,0 +1,171 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use terrazzo::autoclone;
+use terrazzo::html;
+use terrazzo::prelude::*;
+use terrazzo::template;
+use wasm_bindgen::JsCast as _;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlSelectElement;
+
+use self::diagnostics::debug;
+use self::diagnostics::warn;
+use super::api::CompareWith;
+use crate::assets::icons;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio;
+use crate::text_editor::manager::EditorDataState;
+use crate::text_editor::manager::EditorState;
+use crate::text_editor::manager::TextEditorManager;
+use crate::text_editor::style;
+
+impl TextEditorManager {
+    /// The header controls to choose what the current file is compared to.
+    pub fn compare_selector(self: &Ptr<Self>) -> XElement {
+        compare_selector(self.clone(), self.editor_state.clone())
+    }
+
+    /// Replaces the original of the current file, and shows the diff.
+    pub fn compare_with(self: &Ptr<Self>, with: CompareWith) {
+        let EditorState::Data(EditorDataState { path, .. }) =
+            self.editor_state.get_value_untracked()
+        else {
+            return;
+        };
+        let this = self.clone();
+        spawn_local(async move {
+            let original =
+                match super::api::load_original(this.remote.clone(), path.clone(), with).await {
+                    Ok(original) => original,
+                    Err(error) => return warn!("Failed to load the file to compare with: {error}"),
+                };
+            let batch = Batch::use_batch("compare-with");
+            this.editor_state.update(|editor_state| {
+                let EditorState::Data(editor_state) = editor_state else {
+                    return None;
+                };
+                if editor_state.path != path {
+                    debug!("Ignoring stale comparison for {path:?}");
+                    return None;
+                }
+                let fsio::File::TextFile {
+                    metadata, content, ..
+                } = &*editor_state.data
+                else {
+                    return None;
+                };
+                Some(EditorState::Data(EditorDataState {
+                    data: Arc::new(fsio::File::TextFile {
+                        metadata: metadata.clone(),
+                        original: Some(original),
+                        content: content.clone(),
+                    }),
+                    ..editor_state.clone()
+                }))
+            });
+            this.show_editor_diff.set(true);
+            drop(batch);
+        });
+    }
+}
+
+#[autoclone]
+#[html]
+#[template(tag = span)]
+fn compare_selector(
+    manager: Ptr<TextEditorManager>,
+    #[signal] editor_state: EditorState,
+) -> XElement {
+    let EditorState::Data(EditorDataState { data, path, .. }) = &editor_state else {
+        return tag(style::display = "none", style::visibility = "hidden");
+    };
+    let fsio::File::TextFile { .. } = **data else {
+        return tag(style::display = "none", style::visibility = "hidden");
+    };
+    let base = path.base.clone();
+    tag(
+        select(
+            class = style::COMPARE_SELECTOR,
+            #[cfg(not(feature = "client-prod"))]
+            class = "compare-selector",
+            title = "Compare with…",
+            change = move |ev: web_sys::Event| {
+                autoclone!(manager, base);
+                let select = ev.target().or_throw("compare target");
+                let select: HtmlSelectElement = select.dyn_into().or_throw("compare select");
+                let value = select.value();
+                select.set_value("");
+                if let Some(with) = parse_compare_with(&value, &base) {
+                    manager.compare_with(with);
+                }
+            },
+            option(value = "", "Compare with…"),
+            option(value = "HEAD", "HEAD"),
+            option(value = "index", "Index"),
+            option(value = "trash", "Trash"),
+            option(value = "revision", "Revision…"),
+            option(value = "file", "File…"),
+        ),
+        toggle_inline_diff(
+            manager.show_editor_diff.clone(),
+            manager.inline_diff.clone(),
+        ),
+    )
+}
+
+fn parse_compare_with(value: &str, base: &Arc<Path>) -> Option<CompareWith> {
+    Some(match value {
+        "HEAD" => CompareWith::Revision("HEAD".to_owned()),
+        "index" => CompareWith::Index,
+        "trash" => CompareWith::Trash,
+        "revision" => CompareWith::Revision(prompt("Compare with the commit or branch")?),
+        "file" => CompareWith::File(FilePath {
+            base: base.clone(),
+            file: Arc::from(Path::new(&prompt(&format!(
+                "Compare with the file, relative to {}",
+                base.display()
+            ))?)),
+        }),
+        _ => return None,
+    })
+}
+
+fn prompt(message: &str) -> Option<String> {
+    let window = web_sys::window().or_throw("window");
+    let value = window.prompt_with_message(message).ok()??;
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_owned())
+}
+
+#[html]
+#[template(tag = span)]
+fn toggle_inline_diff(#[signal] show_editor_diff: bool, inline_diff: XSignal<bool>) -> XElement {
+    if !show_editor_diff {
+        return tag(style::display = "none", style::visibility = "hidden");
+    }
+
+    #[template(wrap = true)]
+    fn make_class(#[signal] inline_diff: bool) -> XAttributeValue {
+        inline_diff.then_some(style::ACTIVE)
+    }
+
+    #[template(wrap = true)]
+    fn make_title(#[signal] inline_diff: bool) -> XAttributeValue {
+        if inline_diff {
+            "Show side by side"
+        } else {
+            "Show inline"
+        }
+    }
+
+    img(
+        class = style::TOGGLE_INLINE_DIFF,
+        class %= make_class(inline_diff.clone()),
+        #[cfg(not(feature = "client-prod"))]
+        class = "toggle-inline-diff",
+        src = icons::inline_diff(),
+        title %= make_title(inline_diff.clone()),
+        click = move |_| inline_diff.update(|inline| Some(!inline)),
+    )
+}
@@ -10,6 +10,7 @@ use server_fn::ServerFnError;
 use server_fn::codec::Json;
 use terrazzo::server;
 
+use self::encoding::Encoding;
 use super::file_path::FilePath;
 use super::side::SideViewNode;
 use crate::api::client_address::ClientAddress;
@@ -20,14 +21,19 @@ pub mod api;
 pub mod client;
 #[cfg(feature = "server")]
 mod cursor_positions;
+pub mod encoding;
 #[cfg(feature = "server")]
 mod fsmetadata;
 #[cfg(feature = "server")]
 pub(super) mod git;
 #[cfg(feature = "server")]
+mod properties;
+#[cfg(feature = "server")]
 mod remote;
 #[cfg(feature = "server")]
-mod service;
+pub(super) mod service;
+#[cfg(feature = "server")]
+mod transfer;
 #[cfg(feature = "client")]
 pub mod ux;
 
@@ -40,6 +46,23 @@ pub struct CursorPosition {
     pub head: u32,
 }
 
+/// What to do when a copied or moved entry already exists in the destination folder.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum OnConflict {
+    /// Don't copy or move the entry.
+    #[default]
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "F"))]
+    Fail,
+
+    /// Replace the existing files, and merge into the existing folders.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "O"))]
+    Overwrite,
+
+    /// Keep the existing files, and merge into the existing folders.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "S"))]
+    Skip,
+}
+
 #[nameth]
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub enum File {
@@ -54,6 +77,21 @@ pub enum File {
         metadata: Arc<FileMetadata>,
         base64: Arc<str>,
     },
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "I"))]
+    ImageFile {
+        metadata: Arc<FileMetadata>,
+        mime: Arc<str>,
+        base64: Arc<str>,
+    },
+    /// Shown with a hex viewer that downloads one page at a time.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "B"))]
+    BinaryFile { metadata: Arc<FileMetadata> },
+    /// A text file too large to edit, shown one page at a time.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "L"))]
+    LargeFile {
+        metadata: Arc<FileMetadata>,
+        encoding: Encoding,
+    },
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "F"))]
     Folder(Arc<Vec<FileMetadata>>),
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "E"))]
@@ -80,6 +118,36 @@ pub struct FileMetadata {
     pub user: Option<Arc<str>>,
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "g"))]
     pub group: Option<Arc<str>>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "l"))]
+    #[serde(default)]
+    pub is_symlink: bool,
+}
+
+/// The details of a file shown in the properties panel.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct FileProperties {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "m"))]
+    pub metadata: Arc<FileMetadata>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "t"))]
+    pub symlink_target: Option<Arc<str>>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "i"))]
+    pub inode: u64,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "d"))]
+    pub device: u64,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "n"))]
+    pub hard_links: u64,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "u"))]
+    pub uid: u32,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "g"))]
+    pub gid: u32,
+
+    /// The exact timestamps, in RFC 3339 with nanoseconds.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "ts"))]
+    pub timestamps: Vec<(Arc<str>, Arc<str>)>,
+
+    /// The extended attributes, with binary values in hexadecimal.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "x"))]
+    pub xattrs: Vec<(Arc<str>, Arc<str>)>,
 }
 
 impl std::fmt::Debug for File {
@@ -88,6 +156,10 @@ impl std::fmt::Debug for File {
         match self {
             Self::TextFile { content, .. } => f.field(&content.len()),
             Self::PdfFile { base64, .. } => f.field(&base64.len()),
+            Self::ImageFile { base64, .. } => f.field(&base64.len()),
+            Self::BinaryFile { metadata } | Self::LargeFile { metadata, .. } => {
+                f.field(&metadata.size)
+            }
             Self::Folder(folder) => f.field(&folder.len()),
             Self::Error(error) => f.field(error),
         }
@@ -181,6 +253,7 @@ async fn move_file(
     remote: ClientAddress,
     source: FilePath<Arc<Path>>,
     destination_folder: FilePath<Arc<Path>>,
+    on_conflict: OnConflict,
 ) -> Result<(), ServerFnError> {
     Ok(remote::MOVE_FILE_REMOTE_FN
         .call(
@@ -188,11 +261,137 @@ async fn move_file(
             remote::MoveFileRequest {
                 source,
                 destination_folder,
+                on_conflict,
+            },
+        )
+        .await?)
+}
+
+#[server(protocol = Http<Json, Json>)]
+#[nameth]
+async fn copy_file(
+    remote: ClientAddress,
+    source: FilePath<Arc<Path>>,
+    destination_folder: FilePath<Arc<Path>>,
+    on_conflict: OnConflict,
+) -> Result<(), ServerFnError> {
+    Ok(remote::COPY_FILE_REMOTE_FN
+        .call(
+            remote,
+            remote::MoveFileRequest {
+                source,
+                destination_folder,
+                on_conflict,
             },
         )
         .await?)
 }
 
+#[server(protocol = Http<Json, Json>)]
+#[nameth]
+async fn duplicate_file(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<Arc<Path>, ServerFnError> {
+    Ok(remote::DUPLICATE_FILE_REMOTE_FN
+        .call(remote, remote::DuplicateFileRequest { path })
+        .await?)
+}
+
+#[server(protocol = Http<Json, Json>)]
+#[nameth]
+async fn rename_file(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    name: String,
+) -> Result<Arc<Path>, ServerFnError> {
+    Ok(remote::RENAME_FILE_REMOTE_FN
+        .call(remote, remote::CreateEntryRequest { path, name })
+        .await?)
+}
+
+/// Copies or moves an entry to another remote.
+#[server(protocol = Http<Json, Json>)]
+#[nameth]
+async fn transfer_file(
+    source_remote: ClientAddress,
+    source: FilePath<Arc<Path>>,
+    destination_remote: ClientAddress,
+    destination_folder: FilePath<Arc<Path>>,
+    on_conflict: OnConflict,
+    remove_source: bool,
+) -> Result<(), ServerFnError> {
+    Ok(transfer::transfer_file(
+        (source_remote, source),
+        (destination_remote, destination_folder),
+        on_conflict,
+        remove_source,
+    )
+    .await?)
+}
+
+#[server(protocol = Http<Json, Json>)]
+#[nameth]
+async fn load_file_properties(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<FileProperties, ServerFnError> {
+    Ok(remote::LOAD_FILE_PROPERTIES_REMOTE_FN
+        .call(remote, remote::FilePropertiesRequest { path })
+        .await?)
+}
+
+#[server(protocol = Http<Json, Json>)]
+#[nameth]
+async fn set_permissions(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    mode: u32,
+) -> Result<(), ServerFnError> {
+    Ok(remote::SET_PERMISSIONS_REMOTE_FN
+        .call(remote, remote::SetPermissionsRequest { path, mode })
+        .await?)
+}
+
+/// Changes the user and/or the group, given by name or by id.
+#[server(protocol = Http<Json, Json>)]
+#[nameth]
+async fn set_owner(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    user: Option<String>,
+    group: Option<String>,
+) -> Result<(), ServerFnError> {
+    Ok(remote::SET_OWNER_REMOTE_FN
+        .call(remote, remote::SetOwnerRequest { path, user, group })
+        .await?)
+}
+
+#[server(protocol = Http<Json, Json>)]
+#[nameth]
+async fn create_symlink(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    name: String,
+    target: String,
+) -> Result<(), ServerFnError> {
+    Ok(remote::CREATE_SYMLINK_REMOTE_FN
+        .call(remote, remote::CreateSymlinkRequest { path, name, target })
+        .await?)
+}
+
+/// Returns the path of the file the symlink points to.
+#[server(protocol = Http<Json, Json>)]
+#[nameth]
+async fn follow_symlink(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<FilePath<Arc<Path>>, ServerFnError> {
+    Ok(remote::FOLLOW_SYMLINK_REMOTE_FN
+        .call(remote, remote::FollowSymlinkRequest { path })
+        .await?)
+}
+
 #[server(protocol = Http<Json, Json>)]
 #[nameth]
 async fn delete_file(
@@ -204,18 +403,38 @@ async fn delete_file(
         .await?)
 }
 
+/// Returns `None` if the file changed since `expected` was loaded.
 #[server(protocol = Http<Json, Json>)]
 #[nameth]
 async fn store_file_impl(
     remote: ClientAddress,
     path: FilePath<Arc<Path>>,
     content: String,
-) -> Result<(), ServerFnError> {
+    expected: Option<Arc<FileMetadata>>,
+) -> Result<Option<Arc<FileMetadata>>, ServerFnError> {
     #[cfg(debug_assertions)]
     tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-    Ok(remote::STORE_FILE_REMOTE_FN
-        .call(remote, remote::StoreFileRequest { path, content })
-        .await?)
+    let stored = remote::STORE_FILE_REMOTE_FN
+        .call(
+            remote,
+            remote::StoreFileRequest {
+                path,
+                content,
+                expected,
+            },
+        )
+        .await;
+    match stored {
+        Ok(metadata) => Ok(Some(metadata)),
+        Err(error) => {
+            // Only FsioError::FileChanged is reported as aborted.
+            let status = tonic::Status::from(error);
+            if status.code() == tonic::Code::Aborted {
+                return Ok(None);
+            }
+            Err(status.into())
+        }
+    }
 }
 
 #[server(protocol = Http<Json, Json>)]
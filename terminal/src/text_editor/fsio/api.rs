@@ -22,7 +22,9 @@ use crate::backend::Server;
 use crate::backend::auth::AuthConfig;
 use crate::backend::auth::layer::AuthLayer;
 use crate::backend::client_service::text_editor_service;
+use crate::backend::client_service::text_editor_service::DownloadOptions;
 use crate::backend::client_service::text_editor_service::TextEditorFsioError;
+use crate::backend::client_service::text_editor_service::UploadOptions;
 use crate::text_editor::file_path::FilePath;
 
 pub(crate) fn fsio_routes(
@@ -44,10 +46,16 @@ pub(crate) fn fsio_routes(
 
 async fn download_file(
     Query(path): Query<ApiFilePath>,
+    Query(options): Query<DownloadOptions>,
     State(server): State<Arc<Server>>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     let (remote, path) = path.into_remote_and_file_path();
-    let content = text_editor_service::download(&server, &remote, path)
+    let content_type = if options.archive {
+        "application/x-tar"
+    } else {
+        "application/octet-stream"
+    };
+    let content = text_editor_service::download(&server, &remote, path, options)
         .await
         .map_err(api_error)?;
     let content = content.map(|chunk| {
@@ -56,13 +64,14 @@ async fn download_file(
             .map_err(|error| std::io::Error::other(error.to_string()))
     });
     Ok((
-        [(header::CONTENT_TYPE, "application/octet-stream")],
+        [(header::CONTENT_TYPE, content_type)],
         Body::from_stream(content),
     ))
 }
 
 async fn upload_file(
     Query(path): Query<ApiFilePath>,
+    Query(options): Query<UploadOptions>,
     State(server): State<Arc<Server>>,
     content: Body,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
@@ -70,7 +79,7 @@ async fn upload_file(
     let content = content.into_data_stream().map(|chunk| {
         chunk.map_err(|error| TextEditorFsioError::IO(std::io::Error::other(error.to_string())))
     });
-    text_editor_service::upload(&server, &remote, path, content)
+    text_editor_service::upload(&server, &remote, path, options, content)
         .await
         .map_err(api_error)?;
     Ok(StatusCode::NO_CONTENT)
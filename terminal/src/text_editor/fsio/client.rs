@@ -12,6 +12,7 @@ use wasm_bindgen_futures::spawn_local;
 
 use self::diagnostics::warn;
 use super::CursorPosition;
+use super::OnConflict;
 use crate::frontend::remotes::Remote;
 use crate::text_editor::file_path::FilePath;
 use crate::text_editor::side::SideViewNode;
@@ -74,8 +75,91 @@ pub async fn move_file(
     remote: Remote,
     source: FilePath<Arc<Path>>,
     destination_folder: FilePath<Arc<Path>>,
+    on_conflict: OnConflict,
 ) -> Result<(), ServerFnError> {
-    super::move_file(remote, source, destination_folder).await
+    super::move_file(remote, source, destination_folder, on_conflict).await
+}
+
+pub async fn copy_file(
+    remote: Remote,
+    source: FilePath<Arc<Path>>,
+    destination_folder: FilePath<Arc<Path>>,
+    on_conflict: OnConflict,
+) -> Result<(), ServerFnError> {
+    super::copy_file(remote, source, destination_folder, on_conflict).await
+}
+
+pub async fn duplicate_file(
+    remote: Remote,
+    path: FilePath<Arc<Path>>,
+) -> Result<Arc<Path>, ServerFnError> {
+    super::duplicate_file(remote, path).await
+}
+
+pub async fn rename_file(
+    remote: Remote,
+    path: FilePath<Arc<Path>>,
+    name: String,
+) -> Result<Arc<Path>, ServerFnError> {
+    super::rename_file(remote, path, name).await
+}
+
+/// Copies or moves the entry to the folder of another remote.
+pub async fn transfer_file(
+    (source_remote, source): (Remote, FilePath<Arc<Path>>),
+    (destination_remote, destination_folder): (Remote, FilePath<Arc<Path>>),
+    on_conflict: OnConflict,
+    remove_source: bool,
+) -> Result<(), ServerFnError> {
+    super::transfer_file(
+        source_remote,
+        source,
+        destination_remote,
+        destination_folder,
+        on_conflict,
+        remove_source,
+    )
+    .await
+}
+
+pub async fn file_properties(
+    remote: Remote,
+    path: FilePath<Arc<Path>>,
+) -> Result<super::FileProperties, ServerFnError> {
+    super::load_file_properties(remote, path).await
+}
+
+pub async fn set_permissions(
+    remote: Remote,
+    path: FilePath<Arc<Path>>,
+    mode: u32,
+) -> Result<(), ServerFnError> {
+    super::set_permissions(remote, path, mode).await
+}
+
+pub async fn set_owner(
+    remote: Remote,
+    path: FilePath<Arc<Path>>,
+    user: Option<String>,
+    group: Option<String>,
+) -> Result<(), ServerFnError> {
+    super::set_owner(remote, path, user, group).await
+}
+
+pub async fn create_symlink(
+    remote: Remote,
+    path: FilePath<Arc<Path>>,
+    name: String,
+    target: String,
+) -> Result<(), ServerFnError> {
+    super::create_symlink(remote, path, name, target).await
+}
+
+pub async fn follow_symlink(
+    remote: Remote,
+    path: FilePath<Arc<Path>>,
+) -> Result<FilePath<Arc<Path>>, ServerFnError> {
+    super::follow_symlink(remote, path).await
 }
 
 pub async fn delete_file(remote: Remote, path: FilePath<Arc<Path>>) -> Result<(), ServerFnError> {
@@ -84,13 +168,28 @@ pub async fn delete_file(remote: Remote, path: FilePath<Arc<Path>>) -> Result<()
 
 static STORE_FILE_STATE: LazyLock<Mutex<StoreFileState>> = LazyLock::new(Mutex::default);
 
+/// The version of the file on disk that the content of an editor is based on.
+#[derive(Clone, Default)]
+pub struct FileVersion {
+    pub metadata: Arc<super::FileMetadata>,
+    pub content: Arc<str>,
+}
+
+/// The save was rejected because the file changed on disk since it was loaded.
+#[derive(Clone, Copy, Debug)]
+pub struct FileChanged;
+
+/// Stores the file if it is still at the given `version` on disk.
+///
+/// On success, the `version` is updated to the stored content.
 pub async fn store_file<B: Send + 'static, A: Send + 'static>(
     remote: Remote,
     path: FilePath<Arc<Path>>,
     content: String,
+    version: Arc<Mutex<FileVersion>>,
     before: B,
     after: A,
-) {
+) -> Result<(), FileChanged> {
     assert!(std::mem::needs_drop::<B>());
     assert!(std::mem::needs_drop::<A>());
     let (done_tx, done_rx) = oneshot::channel();
@@ -107,12 +206,14 @@ pub async fn store_file<B: Send + 'static, A: Send + 'static>(
             .find(|pending| pending.remote == remote && pending.path == path)
         {
             pending.content = content;
+            pending.version = version;
             pending.waiters.push(waiter);
         } else {
             state.pending.push(PendingStoreFile {
                 remote,
                 path,
                 content,
+                version,
                 waiters: vec![waiter],
             });
         }
@@ -123,7 +224,7 @@ pub async fn store_file<B: Send + 'static, A: Send + 'static>(
     if schedule {
         spawn_local(flush_pending_store_files());
     }
-    let _ = done_rx.await;
+    done_rx.await.unwrap_or(Ok(()))
 }
 
 async fn flush_pending_store_files() {
@@ -132,13 +233,13 @@ async fn flush_pending_store_files() {
     }
     let pending = {
         let mut state = STORE_FILE_STATE.lock().expect("store_file_state");
-        state.scheduled = false;
         mem::take(&mut state.pending)
     };
     for PendingStoreFile {
         remote,
         path,
         content,
+        version,
         waiters,
     } in pending
     {
@@ -156,14 +257,37 @@ async fn flush_pending_store_files() {
             )
             .unzip();
         drop(before);
-        let () = super::store_file_impl(remote, path, content)
-            .await
-            .unwrap_or_else(|error| warn!("Failed to store file: {error}"));
+        let expected = version.lock().expect("version").metadata.clone();
+        let result =
+            match super::store_file_impl(remote, path, content.clone(), Some(expected)).await {
+                Ok(Some(metadata)) => {
+                    *version.lock().expect("version") = FileVersion {
+                        metadata,
+                        content: content.into(),
+                    };
+                    Ok(())
+                }
+                Ok(None) => Err(FileChanged),
+                Err(error) => {
+                    warn!("Failed to store file: {error}");
+                    Ok(())
+                }
+            };
         for (after, done) in after {
             drop(after);
-            let _ = done.send(());
+            let _ = done.send(result);
         }
     }
+
+    // Saves are not concurrent, the next save expects the version stored by this one.
+    let schedule = {
+        let mut state = STORE_FILE_STATE.lock().expect("store_file_state");
+        state.scheduled = !state.pending.is_empty();
+        state.scheduled
+    };
+    if schedule {
+        spawn_local(flush_pending_store_files());
+    }
 }
 
 #[derive(Default)]
@@ -176,13 +300,14 @@ struct PendingStoreFile {
     remote: Remote,
     path: FilePath<Arc<Path>>,
     content: String,
+    version: Arc<Mutex<FileVersion>>,
     waiters: Vec<StoreFileWaiter>,
 }
 
 struct StoreFileWaiter {
     before: Box<dyn Send>,
     after: Box<dyn Send>,
-    done: oneshot::Sender<()>,
+    done: oneshot::Sender<Result<(), FileChanged>>,
 }
 
 static STORE_CURSOR_POSITION_STATE: LazyLock<Mutex<StoreCursorPositionState>> =
@@ -0,0 +1,294 @@
+//! The encoding of text files, detected when they are loaded and kept when they are stored.
+
+#[cfg_attr(feature = "client", allow(dead_code))]
+const UTF16_LE_BOM: [u8; 2] = [0xFF, 0xFE];
+#[cfg_attr(feature = "client", allow(dead_code))]
+const UTF16_BE_BOM: [u8; 2] = [0xFE, 0xFF];
+const BOM: char = '\u{FEFF}';
+
+/// How many bytes after the end of a page are needed to decode its last character.
+#[cfg_attr(feature = "server", allow(dead_code))]
+pub const PAGE_LOOKAHEAD: u64 = 4;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Encoding {
+    #[default]
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "8"))]
+    Utf8,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "1"))]
+    Latin1,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "L"))]
+    Utf16Le,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "B"))]
+    Utf16Be,
+}
+
+/// Returns the encoding of the text, or `None` if the bytes look binary.
+#[cfg_attr(feature = "client", allow(dead_code))]
+pub fn detect(bytes: &[u8]) -> Option<Encoding> {
+    detect_impl(bytes, false)
+}
+
+/// Like [detect], for the beginning of a file: a truncated character at the end is fine.
+#[cfg_attr(feature = "client", allow(dead_code))]
+pub fn detect_prefix(bytes: &[u8]) -> Option<Encoding> {
+    detect_impl(bytes, true)
+}
+
+#[cfg_attr(feature = "client", allow(dead_code))]
+fn detect_impl(bytes: &[u8], is_prefix: bool) -> Option<Encoding> {
+    if bytes.starts_with(&UTF16_LE_BOM) {
+        return Some(Encoding::Utf16Le);
+    }
+    if bytes.starts_with(&UTF16_BE_BOM) {
+        return Some(Encoding::Utf16Be);
+    }
+    // Like git, text files never contain NUL bytes.
+    if bytes.contains(&0) {
+        return None;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => return Some(Encoding::Utf8),
+        Err(error) if is_prefix && error.error_len().is_none() => return Some(Encoding::Utf8),
+        Err(_) => {}
+    }
+    let controls = bytes.iter().filter(|b| is_control(**b)).count();
+    (controls * 32 <= bytes.len()).then_some(Encoding::Latin1)
+}
+
+#[cfg_attr(feature = "client", allow(dead_code))]
+fn is_control(b: u8) -> bool {
+    (b < 0x20 && !b"\t\n\x0C\r\x1B".contains(&b)) || b == 0x7F
+}
+
+pub fn decode(bytes: &[u8], encoding: Encoding) -> String {
+    match encoding {
+        Encoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+        Encoding::Latin1 => bytes.iter().map(|b| *b as char).collect(),
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            let text = decode_utf16(&utf16_units(bytes, encoding));
+            match text.strip_prefix(BOM) {
+                Some(text) => text.to_owned(),
+                None => text,
+            }
+        }
+    }
+}
+
+/// Encodes the text back, or returns `None` if the encoding can't represent it.
+#[cfg_attr(feature = "client", allow(dead_code))]
+pub fn encode(text: &str, encoding: Encoding) -> Option<Vec<u8>> {
+    match encoding {
+        Encoding::Utf8 => Some(text.as_bytes().to_vec()),
+        Encoding::Latin1 => text.chars().map(|c| u8::try_from(c).ok()).collect(),
+        Encoding::Utf16Le => Some(
+            std::iter::once(BOM)
+                .chain(text.chars())
+                .collect::<String>()
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes)
+                .collect(),
+        ),
+        Encoding::Utf16Be => Some(
+            std::iter::once(BOM)
+                .chain(text.chars())
+                .collect::<String>()
+                .encode_utf16()
+                .flat_map(u16::to_be_bytes)
+                .collect(),
+        ),
+    }
+}
+
+/// Decodes the page of a file that starts at `offset` and spans `length` bytes.
+///
+/// `bytes` may continue past the page by up to [PAGE_LOOKAHEAD] bytes: characters
+/// that start in the page are decoded whole, characters that started in the previous
+/// page are skipped.
+#[cfg_attr(feature = "server", allow(dead_code))]
+pub fn decode_page(bytes: &[u8], offset: u64, length: usize, encoding: Encoding) -> String {
+    let length = length.min(bytes.len());
+    match encoding {
+        Encoding::Utf8 => {
+            let is_continuation = |b: &u8| b & 0xC0 == 0x80;
+            let start = if offset == 0 {
+                0
+            } else {
+                bytes[..length]
+                    .iter()
+                    .take(3)
+                    .take_while(|b| is_continuation(b))
+                    .count()
+            };
+            let end = length
+                + bytes[length..]
+                    .iter()
+                    .take_while(|b| is_continuation(b))
+                    .count();
+            String::from_utf8_lossy(&bytes[start..end]).into_owned()
+        }
+        Encoding::Latin1 => decode(&bytes[..length], encoding),
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            let units = utf16_units(bytes, encoding);
+            let is_high = |unit: u16| (0xD800..0xDC00).contains(&unit);
+            let is_low = |unit: u16| (0xDC00..0xE000).contains(&unit);
+            let mut end = (length / 2).min(units.len());
+            if end > 0 && end < units.len() && is_high(units[end - 1]) {
+                end += 1;
+            }
+            let start = usize::from(offset != 0 && end > 0 && is_low(units[0]));
+            let text = decode_utf16(&units[start..end]);
+            match text.strip_prefix(BOM).filter(|_| offset == 0) {
+                Some(text) => text.to_owned(),
+                None => text,
+            }
+        }
+    }
+}
+
+/// Decodes the next chunk of a stream, like a file that is being appended to.
+///
+/// The bytes of a character truncated at the end of the chunk are kept in `carry`
+/// and decoded with the next chunk.
+#[cfg_attr(feature = "client", allow(dead_code))]
+pub fn decode_stream(carry: &mut Vec<u8>, bytes: &[u8], encoding: Encoding) -> String {
+    carry.extend_from_slice(bytes);
+    let end = match encoding {
+        Encoding::Utf8 => {
+            let start = carry.len().saturating_sub(3);
+            (start..carry.len())
+                .rev()
+                .find(|i| carry[*i] & 0xC0 != 0x80)
+                .filter(|i| {
+                    let width = match carry[*i] {
+                        0xC0..=0xDF => 2,
+                        0xE0..=0xEF => 3,
+                        0xF0..=0xF7 => 4,
+                        _ => 1,
+                    };
+                    i + width > carry.len()
+                })
+                .unwrap_or(carry.len())
+        }
+        Encoding::Latin1 => carry.len(),
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            let units = utf16_units(carry, encoding);
+            match units.last() {
+                Some(unit) if (0xD800..0xDC00).contains(unit) => units.len() * 2 - 2,
+                _ => units.len() * 2,
+            }
+        }
+    };
+    let text = decode(&carry[..end], encoding);
+    carry.drain(..end);
+    text
+}
+
+fn utf16_units(bytes: &[u8], encoding: Encoding) -> Vec<u16> {
+    bytes
+        .chunks_exact(2)
+        .map(|pair| {
+            let pair = [pair[0], pair[1]];
+            if encoding == Encoding::Utf16Be {
+                u16::from_be_bytes(pair)
+            } else {
+                u16::from_le_bytes(pair)
+            }
+        })
+        .collect()
+}
+
+fn decode_utf16(units: &[u16]) -> String {
+    char::decode_utf16(units.iter().copied())
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Encoding;
+
+    #[test]
+    fn detect() {
+        assert_eq!(Some(Encoding::Utf8), super::detect(b"fn main() {}\n"));
+        assert_eq!(Some(Encoding::Utf8), super::detect("caf\u{e9}".as_bytes()));
+        assert_eq!(
+            Some(Encoding::Utf8),
+            super::detect_prefix(&"caf\u{e9}".as_bytes()[..4])
+        );
+        assert_eq!(Some(Encoding::Latin1), super::detect(b"caf\xE9"));
+        assert_eq!(Some(Encoding::Latin1), super::detect(b"caf\xE9 cr\xE8me\n"));
+        assert_eq!(Some(Encoding::Utf16Le), super::detect(b"\xFF\xFEa\0b\0"));
+        assert_eq!(Some(Encoding::Utf16Be), super::detect(b"\xFE\xFF\0a\0b"));
+        assert_eq!(None, super::detect(b"\x7FELF\x02\x01\x01\0\0\0"));
+        assert_eq!(None, super::detect(b"\x89PNG\r\n\x1A\n\x02\x03\x04\xE9"));
+    }
+
+    #[test]
+    fn round_trip() {
+        for encoding in [
+            Encoding::Utf8,
+            Encoding::Latin1,
+            Encoding::Utf16Le,
+            Encoding::Utf16Be,
+        ] {
+            let text = "caf\u{e9} cr\u{e8}me\n";
+            let bytes = super::encode(text, encoding).unwrap();
+            assert_eq!(Some(encoding), super::detect(&bytes));
+            assert_eq!(text, super::decode(&bytes, encoding));
+        }
+        assert_eq!(None, super::encode("\u{1F600}", Encoding::Latin1));
+        let bytes = super::encode("\u{1F600}", Encoding::Utf16Be).unwrap();
+        assert_eq!("\u{1F600}", super::decode(&bytes, Encoding::Utf16Be));
+    }
+
+    #[test]
+    fn decode_page_utf8() {
+        let bytes = "ab\u{e9}cd\u{e9}f".as_bytes();
+        // The page [0, 3) ends inside the first 'é'.
+        assert_eq!("ab\u{e9}", super::decode_page(bytes, 0, 3, Encoding::Utf8));
+        // The page [3, 6) starts inside the first 'é'.
+        assert_eq!("cd", super::decode_page(&bytes[3..], 3, 3, Encoding::Utf8));
+        assert_eq!(
+            "\u{e9}f",
+            super::decode_page(&bytes[6..], 6, 3, Encoding::Utf8)
+        );
+    }
+
+    #[test]
+    fn decode_stream() {
+        let mut carry = vec![];
+        let bytes = "a\u{e9}\u{1F600}".as_bytes();
+        let chunks = bytes.chunks(2).collect::<Vec<_>>();
+        let text = chunks
+            .iter()
+            .map(|chunk| super::decode_stream(&mut carry, chunk, Encoding::Utf8))
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["a", "\u{e9}", "", "\u{1F600}"], text);
+        assert!(carry.is_empty());
+
+        let bytes = super::encode("a\u{1F600}", Encoding::Utf16Be).unwrap();
+        assert_eq!(
+            "a",
+            super::decode_stream(&mut carry, &bytes[..5], Encoding::Utf16Be)
+        );
+        assert_eq!(
+            "\u{1F600}",
+            super::decode_stream(&mut carry, &bytes[5..], Encoding::Utf16Be)
+        );
+    }
+
+    #[test]
+    fn decode_page_utf16() {
+        let bytes = super::encode("a\u{1F600}b", Encoding::Utf16Le).unwrap();
+        // BOM, 'a' and the high surrogate.
+        assert_eq!(
+            "a\u{1F600}",
+            super::decode_page(&bytes, 0, 6, Encoding::Utf16Le)
+        );
+        assert_eq!(
+            "b",
+            super::decode_page(&bytes[6..], 6, 4, Encoding::Utf16Le)
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::ffi::CStr;
+use std::ffi::CString;
 use std::fs::Metadata;
 use std::os::unix::fs::MetadataExt as _;
 use std::path::Path;
@@ -7,6 +8,8 @@ use std::sync::Arc;
 use std::time::SystemTime;
 
 use libc::getgrgid;
+use libc::getgrnam;
+use libc::getpwnam;
 use libc::getpwuid;
 
 use crate::text_editor::fsio::FileMetadata;
@@ -24,6 +27,11 @@ impl FileMetadata {
         )
     }
 
+    /// Whether both metadata describe the same content of a file.
+    pub fn same_version(&self, other: &Self) -> bool {
+        self.size == other.size && self.modified == other.modified
+    }
+
     pub fn of(
         file: std::fs::DirEntry,
         gids: &mut HashMap<u32, Option<Arc<str>>>,
@@ -64,6 +72,7 @@ impl FileMetadata {
                     .or_insert_with(|| gid_to_groupname(m.gid()))
                     .clone()
             }),
+            is_symlink: metadata.map(|m| m.is_symlink()).unwrap_or_default(),
         }
     }
 }
@@ -100,3 +109,27 @@ fn gid_to_groupname(gid: u32) -> Option<Arc<str>> {
         name.to_str().ok().map(|s| s.to_owned().into())
     }
 }
+
+/// Convert username to UID
+pub(super) fn username_to_uid(name: &str) -> Option<u32> {
+    let name = CString::new(name).ok()?;
+    unsafe {
+        let pw = getpwnam(name.as_ptr());
+        if pw.is_null() {
+            return None;
+        }
+        Some((*pw).pw_uid)
+    }
+}
+
+/// Convert group name to GID
+pub(super) fn groupname_to_gid(name: &str) -> Option<u32> {
+    let name = CString::new(name).ok()?;
+    unsafe {
+        let gr = getgrnam(name.as_ptr());
+        if gr.is_null() {
+            return None;
+        }
+        Some((*gr).gr_gid)
+    }
+}
@@ -0,0 +1,417 @@
+//! Permissions, ownership and symlinks, and the details shown in the properties panel.
+
+use std::os::unix::fs::MetadataExt as _;
+use std::os::unix::fs::PermissionsExt as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use chrono::DateTime;
+use chrono::SecondsFormat;
+use chrono::Utc;
+use tracing::debug;
+use tracing::warn;
+
+use super::FileMetadata;
+use super::FileProperties;
+use super::fsmetadata::groupname_to_gid;
+use super::fsmetadata::username_to_uid;
+use super::service::FsioError;
+use super::service::create_entry_path;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::search::service::reconcile_touched_path;
+
+pub async fn file_properties(path: FilePath<Arc<Path>>) -> Result<FileProperties, FsioError> {
+    let path = existing_path(path)?;
+    tokio::task::spawn_blocking(move || properties_sync(&path))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+fn properties_sync(path: &Path) -> Result<FileProperties, FsioError> {
+    let metadata = std::fs::symlink_metadata(path).map_err(|error| {
+        not_permitted(error, path, |path| FsioError::PropertiesNotPermitted {
+            path,
+        })
+    })?;
+    let symlink_target = if metadata.is_symlink() {
+        let target = std::fs::read_link(path)?;
+        Some(target.to_string_lossy().into())
+    } else {
+        None
+    };
+    let timestamps = [
+        ("Accessed", metadata.accessed().ok()),
+        ("Modified", metadata.modified().ok()),
+        ("Changed", Some(ctime(&metadata))),
+        ("Created", metadata.created().ok()),
+    ];
+    let timestamps = timestamps
+        .into_iter()
+        .filter_map(|(name, time)| Some((name.into(), format_timestamp(time?).into())))
+        .collect();
+    let xattrs = xattrs::list(path).unwrap_or_else(|error| {
+        warn!("Failed to list the extended attributes of {path:?}: {error}");
+        vec![]
+    });
+    Ok(FileProperties {
+        metadata: FileMetadata::single(path, &metadata).into(),
+        symlink_target,
+        inode: metadata.ino(),
+        device: metadata.dev(),
+        hard_links: metadata.nlink(),
+        uid: metadata.uid(),
+        gid: metadata.gid(),
+        timestamps,
+        xattrs,
+    })
+}
+
+/// The time of the last change of the content or of the metadata.
+fn ctime(metadata: &std::fs::Metadata) -> SystemTime {
+    let since_epoch = Duration::new(metadata.ctime().max(0) as u64, metadata.ctime_nsec() as u32);
+    SystemTime::UNIX_EPOCH + since_epoch
+}
+
+fn format_timestamp(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Nanos, true)
+}
+
+pub async fn set_permissions(path: FilePath<Arc<Path>>, mode: u32) -> Result<(), FsioError> {
+    if mode > 0o7777 {
+        return Err(FsioError::InvalidMode { mode });
+    }
+    let path = existing_path(path)?;
+    debug!("Setting the permissions of {path:?} to {mode:o}");
+    let permissions = std::fs::Permissions::from_mode(mode);
+    tokio::fs::set_permissions(&path, permissions)
+        .await
+        .map_err(|error| {
+            not_permitted(error, &path, |path| FsioError::ChmodNotPermitted { path })
+        })?;
+    reconcile_touched_path(&path);
+    Ok(())
+}
+
+/// Changes the owner of the file, or of the symlink itself.
+pub async fn set_owner(
+    path: FilePath<Arc<Path>>,
+    user: Option<String>,
+    group: Option<String>,
+) -> Result<(), FsioError> {
+    let path = existing_path(path)?;
+    let uid = match user.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(user) => Some(
+            user.parse()
+                .ok()
+                .or_else(|| username_to_uid(user))
+                .ok_or_else(|| FsioError::UserNotFound { name: user.into() })?,
+        ),
+    };
+    let gid = match group.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(group) => Some(
+            group
+                .parse()
+                .ok()
+                .or_else(|| groupname_to_gid(group))
+                .ok_or_else(|| FsioError::GroupNotFound { name: group.into() })?,
+        ),
+    };
+    debug!("Setting the owner of {path:?} to {uid:?}:{gid:?}");
+    let result = {
+        let path = path.clone();
+        tokio::task::spawn_blocking(move || std::os::unix::fs::lchown(path, uid, gid)).await
+    };
+    result.map_err(std::io::Error::other)?.map_err(|error| {
+        not_permitted(error, &path, |path| FsioError::ChownNotPermitted { path })
+    })?;
+    reconcile_touched_path(&path);
+    Ok(())
+}
+
+/// Creates a symlink named `name` in the folder, the target is kept as it is typed.
+pub async fn create_symlink(
+    folder: FilePath<Arc<Path>>,
+    name: String,
+    target: String,
+) -> Result<(), FsioError> {
+    let path = create_entry_path(folder, name.trim())?;
+    if target.is_empty() {
+        return Err(FsioError::InvalidEntryName { name: target });
+    }
+    if path.symlink_metadata().is_ok() {
+        return Err(FsioError::DestinationExists { path });
+    }
+    debug!("Creating the symlink {path:?} to {target:?}");
+    tokio::fs::symlink(&target, &path).await.map_err(|error| {
+        not_permitted(error, &path, |path| FsioError::SymlinkNotPermitted { path })
+    })?;
+    reconcile_touched_path(&path);
+    Ok(())
+}
+
+/// Returns the path of the target, relative to the same base if the target is inside it.
+pub async fn follow_symlink(path: FilePath<Arc<Path>>) -> Result<FilePath<Arc<Path>>, FsioError> {
+    let full_path = existing_path(path.clone())?;
+    if !full_path.is_symlink() {
+        return Err(FsioError::NotSymlink { path: full_path });
+    }
+    let target = match tokio::fs::canonicalize(&full_path).await {
+        Ok(target) => target,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            let target = tokio::fs::read_link(&full_path).await?;
+            return Err(FsioError::BrokenSymlink {
+                path: full_path,
+                target,
+            });
+        }
+        Err(error) => {
+            return Err(not_permitted(error, &full_path, |path| {
+                FsioError::FollowNotPermitted { path }
+            }));
+        }
+    };
+    let base = tokio::fs::canonicalize(&path.base).await?;
+    Ok(match target.strip_prefix(&base) {
+        Ok(file) => FilePath {
+            base: path.base,
+            file: file.into(),
+        },
+        Err(_) => FilePath {
+            base: Path::new("/").into(),
+            file: target.strip_prefix("/").unwrap_or(&target).into(),
+        },
+    })
+}
+
+fn existing_path(path: FilePath<Arc<Path>>) -> Result<PathBuf, FsioError> {
+    let path = path.full_path();
+    if path.symlink_metadata().is_err() {
+        return Err(FsioError::PathNotFound { path });
+    }
+    Ok(path)
+}
+
+/// Reports the action that was not permitted, the other errors as they are.
+fn not_permitted(
+    error: std::io::Error,
+    path: &Path,
+    action: impl FnOnce(PathBuf) -> FsioError,
+) -> FsioError {
+    if error.kind() == std::io::ErrorKind::PermissionDenied {
+        action(path.to_owned())
+    } else {
+        error.into()
+    }
+}
+
+/// Reads the extended attributes, without following symlinks.
+mod xattrs {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt as _;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    pub fn list(path: &Path) -> std::io::Result<Vec<(Arc<str>, Arc<str>)>> {
+        let path = CString::new(path.as_os_str().as_bytes())?;
+        let names = match read(|buffer, size| unsafe { sys::list(&path, buffer, size) }) {
+            Ok(names) => names,
+            Err(error) if error.raw_os_error() == Some(libc::ENOTSUP) => return Ok(vec![]),
+            Err(error) => return Err(error),
+        };
+        let mut xattrs = vec![];
+        for name in names.split(|b| *b == 0).filter(|name| !name.is_empty()) {
+            let name = CString::new(name)?;
+            let value = read(|buffer, size| unsafe { sys::get(&path, &name, buffer, size) })?;
+            let value = match std::str::from_utf8(&value) {
+                Ok(value) if !value.contains(|c: char| c.is_control() && c != '\0') => {
+                    value.trim_end_matches('\0').into()
+                }
+                _ => format!(
+                    "0x{}",
+                    value.iter().map(|b| format!("{b:02x}")).collect::<String>()
+                ),
+            };
+            xattrs.push((name.to_string_lossy().into(), value.into()));
+        }
+        Ok(xattrs)
+    }
+
+    /// Calls `f` once to get the size, then to fill the buffer.
+    fn read(f: impl Fn(*mut u8, usize) -> isize) -> std::io::Result<Vec<u8>> {
+        loop {
+            let size = f(std::ptr::null_mut(), 0);
+            if size < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            let mut buffer = vec![0; size as usize];
+            let len = f(buffer.as_mut_ptr(), buffer.len());
+            if len < 0 {
+                let error = std::io::Error::last_os_error();
+                // The value grew between the two calls.
+                if error.raw_os_error() == Some(libc::ERANGE) {
+                    continue;
+                }
+                return Err(error);
+            }
+            buffer.truncate(len as usize);
+            return Ok(buffer);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    mod sys {
+        use std::ffi::CStr;
+
+        pub unsafe fn list(path: &CStr, buffer: *mut u8, size: usize) -> isize {
+            unsafe { libc::llistxattr(path.as_ptr(), buffer.cast(), size) }
+        }
+
+        pub unsafe fn get(path: &CStr, name: &CStr, buffer: *mut u8, size: usize) -> isize {
+            unsafe { libc::lgetxattr(path.as_ptr(), name.as_ptr(), buffer.cast(), size) }
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    mod sys {
+        use std::ffi::CStr;
+
+        pub unsafe fn list(path: &CStr, buffer: *mut u8, size: usize) -> isize {
+            let options = libc::XATTR_NOFOLLOW;
+            unsafe { libc::listxattr(path.as_ptr(), buffer.cast(), size, options) }
+        }
+
+        pub unsafe fn get(path: &CStr, name: &CStr, buffer: *mut u8, size: usize) -> isize {
+            let options = libc::XATTR_NOFOLLOW;
+            unsafe {
+                libc::getxattr(
+                    path.as_ptr(),
+                    name.as_ptr(),
+                    buffer.cast(),
+                    size,
+                    0,
+                    options,
+                )
+            }
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    mod sys {
+        use std::ffi::CStr;
+
+        pub unsafe fn list(_path: &CStr, _buffer: *mut u8, _size: usize) -> isize {
+            0
+        }
+
+        pub unsafe fn get(_path: &CStr, _name: &CStr, _buffer: *mut u8, _size: usize) -> isize {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::PermissionsExt as _;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use crate::text_editor::file_path::FilePath;
+    use crate::text_editor::fsio::service::FsioError;
+
+    fn file_path(base: &Path, file: &str) -> FilePath<Arc<Path>> {
+        FilePath {
+            base: Arc::from(base),
+            file: Arc::from(file.as_ref()),
+        }
+    }
+
+    #[tokio::test]
+    async fn set_permissions() {
+        let tempdir = tempfile::tempdir().unwrap();
+        std::fs::write(tempdir.path().join("file.txt"), "").unwrap();
+
+        super::set_permissions(file_path(tempdir.path(), "file.txt"), 0o640)
+            .await
+            .unwrap();
+        let error = super::set_permissions(file_path(tempdir.path(), "file.txt"), 0o10000)
+            .await
+            .unwrap_err();
+
+        let metadata = std::fs::metadata(tempdir.path().join("file.txt")).unwrap();
+        assert_eq!(0o640, metadata.permissions().mode() & 0o7777);
+        assert!(matches!(error, FsioError::InvalidMode { .. }));
+    }
+
+    #[tokio::test]
+    async fn set_unknown_owner() {
+        let tempdir = tempfile::tempdir().unwrap();
+        std::fs::write(tempdir.path().join("file.txt"), "").unwrap();
+
+        let error = super::set_owner(
+            file_path(tempdir.path(), "file.txt"),
+            Some("no-such-user-for-tests".into()),
+            None,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(error, FsioError::UserNotFound { .. }));
+    }
+
+    #[tokio::test]
+    async fn create_and_follow_symlink() {
+        let tempdir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tempdir.path().join("folder")).unwrap();
+        std::fs::write(tempdir.path().join("folder/file.txt"), "").unwrap();
+
+        super::create_symlink(
+            file_path(tempdir.path(), ""),
+            "link".into(),
+            "folder/file.txt".into(),
+        )
+        .await
+        .unwrap();
+        let target = super::follow_symlink(file_path(tempdir.path(), "link"))
+            .await
+            .unwrap();
+        let properties = super::file_properties(file_path(tempdir.path(), "link"))
+            .await
+            .unwrap();
+
+        assert_eq!(Path::new("folder/file.txt"), &*target.file);
+        assert_eq!(
+            Some("folder/file.txt"),
+            properties.symlink_target.as_deref()
+        );
+        assert!(properties.metadata.is_symlink);
+        assert_eq!(1, properties.hard_links);
+        assert!(
+            properties
+                .timestamps
+                .iter()
+                .any(|(name, _)| &**name == "Changed")
+        );
+
+        let error = super::follow_symlink(file_path(tempdir.path(), "folder"))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, FsioError::NotSymlink { .. }));
+    }
+
+    #[tokio::test]
+    async fn follow_broken_symlink() {
+        let tempdir = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink("missing", tempdir.path().join("link")).unwrap();
+
+        let error = super::follow_symlink(file_path(tempdir.path(), "link"))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, FsioError::BrokenSymlink { .. }));
+    }
+}
@@ -4,6 +4,9 @@ use std::sync::Arc;
 
 use super::CursorPosition;
 use super::File;
+use super::FileMetadata;
+use super::FileProperties;
+use super::OnConflict;
 use crate::backend::client_service::grpc_error::GrpcError;
 use crate::backend::client_service::remote_fn_service;
 use crate::text_editor::file_path::FilePath;
@@ -41,6 +44,8 @@ pub struct StoreFileRequest {
     pub path: FilePath<Arc<Path>>,
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "c"))]
     pub content: String,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "m"))]
+    pub expected: Option<Arc<FileMetadata>>,
 }
 
 #[derive(Debug, serde::Serialize, serde:: Deserialize)]
@@ -71,6 +76,55 @@ pub struct MoveFileRequest {
     pub source: FilePath<Arc<Path>>,
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "d"))]
     pub destination_folder: FilePath<Arc<Path>>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "c"))]
+    #[serde(default)]
+    pub on_conflict: OnConflict,
+}
+
+#[derive(Debug, serde::Serialize, serde:: Deserialize)]
+pub struct FilePropertiesRequest {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "p"))]
+    pub path: FilePath<Arc<Path>>,
+}
+
+#[derive(Debug, serde::Serialize, serde:: Deserialize)]
+pub struct SetPermissionsRequest {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "p"))]
+    pub path: FilePath<Arc<Path>>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "m"))]
+    pub mode: u32,
+}
+
+#[derive(Debug, serde::Serialize, serde:: Deserialize)]
+pub struct SetOwnerRequest {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "p"))]
+    pub path: FilePath<Arc<Path>>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "u"))]
+    pub user: Option<String>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "g"))]
+    pub group: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize, serde:: Deserialize)]
+pub struct CreateSymlinkRequest {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "p"))]
+    pub path: FilePath<Arc<Path>>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "n"))]
+    pub name: String,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "t"))]
+    pub target: String,
+}
+
+#[derive(Debug, serde::Serialize, serde:: Deserialize)]
+pub struct FollowSymlinkRequest {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "p"))]
+    pub path: FilePath<Arc<Path>>,
+}
+
+#[derive(Debug, serde::Serialize, serde:: Deserialize)]
+pub struct DuplicateFileRequest {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "p"))]
+    pub path: FilePath<Arc<Path>>,
 }
 
 #[derive(Debug, serde::Serialize, serde:: Deserialize)]
@@ -161,8 +215,43 @@ remote_fn_service::unary::declare_remote_fn!(
     super::MOVE_FILE,
     MoveFileRequest,
     (),
-    |_server, arg: MoveFileRequest| async {
-        let result = super::service::move_file(arg.source, arg.destination_folder).await;
+    |_server, arg: MoveFileRequest| async move {
+        let result =
+            super::service::move_file(arg.source, arg.destination_folder, arg.on_conflict).await;
+        result.map_err(GrpcError::from)
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    COPY_FILE_REMOTE_FN,
+    super::COPY_FILE,
+    MoveFileRequest,
+    (),
+    |_server, arg: MoveFileRequest| async move {
+        let result =
+            super::service::copy_file(arg.source, arg.destination_folder, arg.on_conflict).await;
+        result.map_err(GrpcError::from)
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    DUPLICATE_FILE_REMOTE_FN,
+    super::DUPLICATE_FILE,
+    DuplicateFileRequest,
+    Arc<Path>,
+    |_server, arg: DuplicateFileRequest| async {
+        let result = super::service::duplicate_file(arg.path).await;
+        result.map_err(GrpcError::from)
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    RENAME_FILE_REMOTE_FN,
+    super::RENAME_FILE,
+    CreateEntryRequest,
+    Arc<Path>,
+    |_server, arg: CreateEntryRequest| async {
+        let result = super::service::rename_file(arg.path, arg.name).await;
         result.map_err(GrpcError::from)
     }
 );
@@ -189,9 +278,9 @@ remote_fn_service::unary::declare_remote_fn!(
     STORE_FILE_REMOTE_FN,
     super::STORE_FILE_IMPL,
     StoreFileRequest,
-    (),
+    Arc<FileMetadata>,
     |_server, arg: StoreFileRequest| async {
-        let result = super::service::store_file(arg.path, arg.content).await;
+        let result = super::service::store_file(arg.path, arg.content, arg.expected).await;
         result.map_err(GrpcError::from)
     }
 );
@@ -216,3 +305,58 @@ remote_fn_service::unary::declare_remote_fn!(
         Ok::<_, GrpcError<Infallible>>(())
     }
 );
+
+remote_fn_service::unary::declare_remote_fn!(
+    LOAD_FILE_PROPERTIES_REMOTE_FN,
+    super::LOAD_FILE_PROPERTIES,
+    FilePropertiesRequest,
+    FileProperties,
+    |_server, arg: FilePropertiesRequest| async {
+        let result = super::properties::file_properties(arg.path).await;
+        result.map_err(GrpcError::from)
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    SET_PERMISSIONS_REMOTE_FN,
+    super::SET_PERMISSIONS,
+    SetPermissionsRequest,
+    (),
+    |_server, arg: SetPermissionsRequest| async move {
+        let result = super::properties::set_permissions(arg.path, arg.mode).await;
+        result.map_err(GrpcError::from)
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    SET_OWNER_REMOTE_FN,
+    super::SET_OWNER,
+    SetOwnerRequest,
+    (),
+    |_server, arg: SetOwnerRequest| async {
+        let result = super::properties::set_owner(arg.path, arg.user, arg.group).await;
+        result.map_err(GrpcError::from)
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    CREATE_SYMLINK_REMOTE_FN,
+    super::CREATE_SYMLINK,
+    CreateSymlinkRequest,
+    (),
+    |_server, arg: CreateSymlinkRequest| async {
+        let result = super::properties::create_symlink(arg.path, arg.name, arg.target).await;
+        result.map_err(GrpcError::from)
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    FOLLOW_SYMLINK_REMOTE_FN,
+    super::FOLLOW_SYMLINK,
+    FollowSymlinkRequest,
+    FilePath<Arc<Path>>,
+    |_server, arg: FollowSymlinkRequest| async {
+        let result = super::properties::follow_symlink(arg.path).await;
+        result.map_err(GrpcError::from)
+    }
+);
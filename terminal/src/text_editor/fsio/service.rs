@@ -1,9 +1,11 @@
 use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::ffi::OsString;
+use std::fs::Permissions;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use std::time::SystemTime;
 
 use base64::Engine as _;
@@ -12,6 +14,7 @@ use chrono::DateTime;
 use chrono::Utc;
 use nameth::NamedEnumValues as _;
 use nameth::nameth;
+use tokio::io::AsyncReadExt as _;
 use tokio::io::AsyncWriteExt as _;
 use tonic::Code;
 use tracing::debug;
@@ -19,6 +22,9 @@ use tracing::warn;
 
 use super::File;
 use super::FileMetadata;
+use super::OnConflict;
+use super::encoding;
+use super::encoding::Encoding;
 use super::git;
 use crate::backend::client_service::grpc_error::IsGrpcError;
 use crate::text_editor::file_path::FilePath;
@@ -30,6 +36,11 @@ use crate::text_editor::side::SvnItem;
 
 const MAX_FILES_SORTED: usize = 5000;
 const MAX_FILES_RETURNED: usize = 1000;
+const MAX_IMAGE_FILE_SIZE: u64 = 16 * 1024 * 1024;
+const MAX_TEXT_FILE_SIZE: u64 = 8 * 1024 * 1024;
+
+/// How much of a large file is read to tell text from binary.
+const SNIFF_SIZE: u64 = 64 * 1024;
 
 pub async fn load_file(path: FilePath<Arc<Path>>) -> Result<Option<File>, FsioError> {
     let path = path.full_path();
@@ -45,8 +56,41 @@ pub async fn load_file(path: FilePath<Arc<Path>>) -> Result<Option<File>, FsioEr
                     base64,
                 }));
             }
+            if let Some(mime) = image_mime(&path)
+                && metadata.len() <= MAX_IMAGE_FILE_SIZE
+            {
+                debug!("Loading image file {path:?}");
+                let data = tokio::fs::read(&path).await?;
+                let base64 = BASE64_STANDARD.encode(data).into();
+                reconcile_touched_path(&path);
+                return Ok(Some(File::ImageFile {
+                    metadata: FileMetadata::single(&path, &metadata).into(),
+                    mime: mime.into(),
+                    base64,
+                }));
+            }
+            if metadata.len() > MAX_TEXT_FILE_SIZE {
+                debug!("Loading large file {path:?}");
+                let mut head = Vec::with_capacity(SNIFF_SIZE as usize);
+                let file = tokio::fs::File::open(&path).await?;
+                file.take(SNIFF_SIZE).read_to_end(&mut head).await?;
+                let metadata = FileMetadata::single(&path, &metadata).into();
+                reconcile_touched_path(&path);
+                return Ok(Some(match encoding::detect_prefix(&head) {
+                    Some(encoding) => File::LargeFile { metadata, encoding },
+                    None => File::BinaryFile { metadata },
+                }));
+            }
             debug!("Loading text file {path:?}");
-            let content: Arc<str> = tokio::fs::read_to_string(&path).await?.into();
+            let data = tokio::fs::read(&path).await?;
+            let Some(encoding) = encoding::detect(&data) else {
+                debug!("Loaded binary file {path:?}");
+                reconcile_touched_path(&path);
+                return Ok(Some(File::BinaryFile {
+                    metadata: FileMetadata::single(&path, &metadata).into(),
+                }));
+            };
+            let content: Arc<str> = encoding::decode(&data, encoding).into();
             let original = git::git_repo_root(&path)
                 .is_some()
                 .then(|| {
@@ -100,6 +144,15 @@ pub async fn load_file_metadata(path: FilePath<Arc<Path>>) -> Result<Option<File
                     base64: Arc::from(""),
                 }));
             }
+            if let Some(mime) = image_mime(&path)
+                && metadata.len() <= MAX_IMAGE_FILE_SIZE
+            {
+                return Ok(Some(File::ImageFile {
+                    metadata: file_metadata,
+                    mime: mime.into(),
+                    base64: Arc::from(""),
+                }));
+            }
             return Ok(Some(File::TextFile {
                 metadata: file_metadata,
                 original: None,
@@ -114,6 +167,21 @@ pub async fn load_file_metadata(path: FilePath<Arc<Path>>) -> Result<Option<File
     Ok(None)
 }
 
+/// The images shown in the browser, SVG files are edited as text.
+fn image_mime(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "avif" => "image/avif",
+        "bmp" => "image/bmp",
+        "gif" => "image/gif",
+        "ico" => "image/x-icon",
+        "jpeg" | "jpg" => "image/jpeg",
+        "png" => "image/png",
+        "webp" => "image/webp",
+        _ => return None,
+    })
+}
+
 pub async fn list_folder(
     path: FilePath<Arc<Path>>,
 ) -> Result<Option<Arc<Vec<FileMetadata>>>, FsioError> {
@@ -178,15 +246,110 @@ fn prune_side_view_rec(
     Some((node, changed))
 }
 
-pub async fn store_file(path: FilePath<Arc<Path>>, content: String) -> Result<(), FsioError> {
+/// Stores the file, unless it changed on disk since the `expected` version was loaded.
+///
+/// Returns the metadata of the stored file, to be expected by the next save.
+pub async fn store_file(
+    path: FilePath<Arc<Path>>,
+    content: String,
+    expected: Option<Arc<FileMetadata>>,
+) -> Result<Arc<FileMetadata>, FsioError> {
     let path = path.full_path();
-    return if path.exists() {
-        let () = tokio::fs::write(&path, content).await?;
-        reconcile_touched_path(&path);
-        Ok(())
-    } else {
-        Err(FsioError::PathNotFound { path })
+    let Ok(metadata) = tokio::fs::metadata(&path).await else {
+        return Err(FsioError::PathNotFound { path });
     };
+    if let Some(expected) = expected
+        && !expected.same_version(&FileMetadata::single(&path, &metadata))
+    {
+        return Err(FsioError::FileChanged { path });
+    }
+    let content = encode_like(&path, content).await?;
+    write_atomic(&path, metadata.permissions(), content).await?;
+    reconcile_touched_path(&path);
+    let metadata = tokio::fs::metadata(&path).await?;
+    Ok(FileMetadata::single(&path, &metadata).into())
+}
+
+/// Encodes the content like the file it replaces, or in UTF-8 if that encoding can't represent it.
+async fn encode_like(path: &Path, content: String) -> Result<Vec<u8>, FsioError> {
+    let previous = tokio::fs::read(path).await?;
+    match encoding::detect(&previous).unwrap_or_default() {
+        Encoding::Utf8 => Ok(content.into_bytes()),
+        encoding => Ok(encoding::encode(&content, encoding).unwrap_or_else(|| {
+            warn!("Storing {path:?} in UTF-8 instead of {encoding:?}");
+            content.into_bytes()
+        })),
+    }
+}
+
+/// Writes a temp file in the same folder and renames it over the file,
+/// so readers never see a partially written file.
+async fn write_atomic(
+    path: &Path,
+    permissions: Permissions,
+    content: Vec<u8>,
+) -> Result<(), FsioError> {
+    stage_file(path, permissions, content).await?.commit().await
+}
+
+/// A temp file with the new content of a file, see [stage_file].
+pub struct StagedFile {
+    temp: PathBuf,
+    target: PathBuf,
+}
+
+/// Writes the content to a temp file in the same folder as the file.
+///
+/// The file is only replaced by [StagedFile::commit], so several files can be staged
+/// before any of them is replaced.
+pub async fn stage_file(
+    path: &Path,
+    permissions: Permissions,
+    content: Vec<u8>,
+) -> Result<StagedFile, FsioError> {
+    // Replace the target of symlinks, not the symlinks themselves.
+    let target = tokio::fs::canonicalize(path).await?;
+    let temp = temp_sibling(&target)?;
+    let result = async {
+        tokio::fs::write(&temp, content).await?;
+        tokio::fs::set_permissions(&temp, permissions).await
+    }
+    .await;
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&temp).await;
+    }
+    result?;
+    Ok(StagedFile { temp, target })
+}
+
+/// A hidden path next to the target, for content that isn't ready to replace it yet.
+fn temp_sibling(target: &Path) -> Result<PathBuf, FsioError> {
+    let Some(file_name) = target.file_name() else {
+        return Err(FsioError::MissingFileName {
+            path: target.to_owned(),
+        });
+    };
+    Ok(target.with_file_name(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        uuid::Uuid::new_v4().simple()
+    )))
+}
+
+impl StagedFile {
+    /// Renames the temp file over the file.
+    pub async fn commit(self) -> Result<(), FsioError> {
+        let result = tokio::fs::rename(&self.temp, &self.target).await;
+        if result.is_err() {
+            self.discard().await;
+        }
+        Ok(result?)
+    }
+
+    /// Removes the temp file, the file is left unchanged.
+    pub async fn discard(self) {
+        let _ = tokio::fs::remove_file(&self.temp).await;
+    }
 }
 
 pub async fn create_file(path: FilePath<Arc<Path>>, name: String) -> Result<(), FsioError> {
@@ -213,9 +376,82 @@ pub async fn create_folder(path: FilePath<Arc<Path>>, name: String) -> Result<()
 pub async fn move_file(
     source: FilePath<Arc<Path>>,
     destination_folder: FilePath<Arc<Path>>,
+    on_conflict: OnConflict,
 ) -> Result<(), FsioError> {
+    let (source, destination) = transfer_paths(source, destination_folder)?;
+    if source == destination {
+        return Ok(());
+    }
+    check_destination(&source, &destination, on_conflict)?;
+    let result = {
+        let (source, destination) = (source.clone(), destination.clone());
+        tokio::task::spawn_blocking(move || move_tree(&source, &destination, on_conflict)).await
+    };
+    result.map_err(std::io::Error::other)??;
+    reconcile_touched_path(&source);
+    reconcile_touched_path(&destination);
+    Ok(())
+}
+
+/// Copies the file or the folder into the folder, or duplicates it if it is already there.
+pub async fn copy_file(
+    source: FilePath<Arc<Path>>,
+    destination_folder: FilePath<Arc<Path>>,
+    on_conflict: OnConflict,
+) -> Result<(), FsioError> {
+    let (source, destination) = transfer_paths(source, destination_folder)?;
+    let destination = if source == destination {
+        available_copy_path(&source)?
+    } else {
+        check_destination(&source, &destination, on_conflict)?;
+        destination
+    };
+    copy_blocking(source, destination, on_conflict).await
+}
+
+/// Copies the file or the folder next to it, and returns the path of the copy.
+pub async fn duplicate_file(path: FilePath<Arc<Path>>) -> Result<Arc<Path>, FsioError> {
+    let source = path.full_path();
+    if source.symlink_metadata().is_err() {
+        return Err(FsioError::PathNotFound { path: source });
+    }
+    let destination = available_copy_path(&source)?;
+    let file = sibling_path(&path.file, destination.file_name().unwrap_or_default());
+    copy_blocking(source, destination, OnConflict::Fail).await?;
+    Ok(file)
+}
+
+/// Renames the file or the folder in place, and returns its new path.
+pub async fn rename_file(path: FilePath<Arc<Path>>, name: String) -> Result<Arc<Path>, FsioError> {
+    let name = name.trim();
+    let source = path.full_path();
+    if source.symlink_metadata().is_err() {
+        return Err(FsioError::PathNotFound { path: source });
+    }
+    let parent = FilePath {
+        base: path.base.clone(),
+        file: Arc::from(path.file.parent().unwrap_or(Path::new(""))),
+    };
+    let destination = create_entry_path(parent, name)?;
+    if destination == source {
+        return Ok(path.file);
+    }
+    if destination.symlink_metadata().is_ok() {
+        return Err(FsioError::DestinationExists { path: destination });
+    }
+    tokio::fs::rename(&source, &destination).await?;
+    reconcile_touched_path(&source);
+    reconcile_touched_path(&destination);
+    Ok(sibling_path(&path.file, name.as_ref()))
+}
+
+/// The full paths of the entry, and of where it goes in the destination folder.
+fn transfer_paths(
+    source: FilePath<Arc<Path>>,
+    destination_folder: FilePath<Arc<Path>>,
+) -> Result<(PathBuf, PathBuf), FsioError> {
     let source = source.full_path();
-    if !source.exists() {
+    if source.symlink_metadata().is_err() {
         return Err(FsioError::PathNotFound { path: source });
     }
     let Some(file_name) = source.file_name() else {
@@ -229,17 +465,232 @@ pub async fn move_file(
         });
     }
     let destination = destination_folder.join(file_name);
-    if source == destination {
-        return Ok(());
+    Ok((source, destination))
+}
+
+fn check_destination(
+    source: &Path,
+    destination: &Path,
+    on_conflict: OnConflict,
+) -> Result<(), FsioError> {
+    if destination.starts_with(source) {
+        return Err(FsioError::DestinationInsideSource {
+            path: destination.to_owned(),
+        });
+    }
+    if on_conflict == OnConflict::Fail && destination.symlink_metadata().is_ok() {
+        return Err(FsioError::DestinationExists {
+            path: destination.to_owned(),
+        });
     }
+    Ok(())
+}
 
-    tokio::fs::rename(&source, &destination).await?;
-    reconcile_touched_path(&source);
+async fn copy_blocking(
+    source: PathBuf,
+    destination: PathBuf,
+    on_conflict: OnConflict,
+) -> Result<(), FsioError> {
+    let result = {
+        let destination = destination.clone();
+        tokio::task::spawn_blocking(move || copy_tree(&source, &destination, on_conflict)).await
+    };
+    result.map_err(std::io::Error::other)??;
     reconcile_touched_path(&destination);
     Ok(())
 }
 
-fn create_entry_path(path: FilePath<Arc<Path>>, name: &str) -> Result<PathBuf, FsioError> {
+/// Copies the file, the symlink, or the folder and its content.
+fn copy_tree(source: &Path, destination: &Path, on_conflict: OnConflict) -> Result<(), FsioError> {
+    let metadata = std::fs::symlink_metadata(source)?;
+    if let Ok(existing) = std::fs::symlink_metadata(destination) {
+        let is_merge = metadata.is_dir() && existing.is_dir();
+        if !is_merge {
+            return overwrite(
+                destination,
+                &existing,
+                on_conflict,
+                |staged| copy_tree(source, staged, on_conflict),
+                remove_staged,
+            );
+        }
+    }
+    if metadata.is_dir() {
+        if !destination.is_dir() {
+            std::fs::create_dir(destination)?;
+        }
+        for entry in std::fs::read_dir(source)? {
+            let entry = entry?;
+            copy_tree(
+                &entry.path(),
+                &destination.join(entry.file_name()),
+                on_conflict,
+            )?;
+        }
+        // Set last, in case the folder is read-only.
+        std::fs::set_permissions(destination, metadata.permissions())?;
+    } else if metadata.is_symlink() {
+        std::os::unix::fs::symlink(std::fs::read_link(source)?, destination)?;
+    } else {
+        std::fs::copy(source, destination)?;
+    }
+    Ok(())
+}
+
+/// Moves the entry, and merges folders that exist on both sides.
+///
+/// Entries that are skipped stay in the source folder.
+fn move_tree(source: &Path, destination: &Path, on_conflict: OnConflict) -> Result<(), FsioError> {
+    let metadata = std::fs::symlink_metadata(source)?;
+    if let Ok(existing) = std::fs::symlink_metadata(destination) {
+        if metadata.is_dir() && existing.is_dir() {
+            for entry in std::fs::read_dir(source)? {
+                let entry = entry?;
+                move_tree(
+                    &entry.path(),
+                    &destination.join(entry.file_name()),
+                    on_conflict,
+                )?;
+            }
+            return match std::fs::remove_dir(source) {
+                Err(error) if error.kind() == std::io::ErrorKind::DirectoryNotEmpty => Ok(()),
+                result => Ok(result?),
+            };
+        }
+        return overwrite(
+            destination,
+            &existing,
+            on_conflict,
+            |staged| move_tree(source, staged, on_conflict),
+            |staged| {
+                if let Err(error) = move_tree(staged, source, OnConflict::Fail) {
+                    warn!("Failed to move {staged:?} back to {source:?}: {error}");
+                }
+            },
+        );
+    }
+    match std::fs::rename(source, destination) {
+        // Folders can't be renamed to another file system.
+        Err(error) if error.kind() == std::io::ErrorKind::CrossesDevices => {
+            debug!("Copying {source:?} to another device");
+            copy_tree(source, destination, on_conflict)?;
+            remove_entry(source, &metadata)?;
+            Ok(())
+        }
+        result => Ok(result?),
+    }
+}
+
+/// Copies or moves the entry over the existing destination, depending on `on_conflict`.
+///
+/// The entry is transferred to a temp path next to the destination first,
+/// and the destination is only replaced once the transfer succeeded.
+/// If the destination can't be replaced, `restore` gets the entry back from the temp path.
+fn overwrite(
+    destination: &Path,
+    existing: &std::fs::Metadata,
+    on_conflict: OnConflict,
+    transfer: impl FnOnce(&Path) -> Result<(), FsioError>,
+    restore: impl FnOnce(&Path),
+) -> Result<(), FsioError> {
+    match on_conflict {
+        OnConflict::Fail => {
+            return Err(FsioError::DestinationExists {
+                path: destination.to_owned(),
+            });
+        }
+        OnConflict::Skip => return Ok(()),
+        OnConflict::Overwrite => {}
+    }
+    let staged = temp_sibling(destination)?;
+    if let Err(error) = transfer(&staged) {
+        remove_staged(&staged);
+        return Err(error);
+    }
+    if let Err(error) = replace_entry(&staged, destination, existing) {
+        restore(&staged);
+        return Err(error.into());
+    }
+    Ok(())
+}
+
+/// Renames the staged entry over the destination.
+fn replace_entry(
+    staged: &Path,
+    destination: &Path,
+    existing: &std::fs::Metadata,
+) -> std::io::Result<()> {
+    if !existing.is_dir() && !std::fs::symlink_metadata(staged)?.is_dir() {
+        return std::fs::rename(staged, destination);
+    }
+    // Folders can't be renamed over, the existing entry is set aside until it is replaced.
+    let mut aside = staged.as_os_str().to_owned();
+    aside.push(".old");
+    let aside = PathBuf::from(aside);
+    std::fs::rename(destination, &aside)?;
+    if let Err(error) = std::fs::rename(staged, destination) {
+        let _ = std::fs::rename(&aside, destination);
+        return Err(error);
+    }
+    if let Err(error) = remove_entry(&aside, existing) {
+        warn!("Failed to remove the replaced entry {aside:?}: {error}");
+    }
+    Ok(())
+}
+
+fn remove_staged(staged: &Path) {
+    if let Ok(metadata) = std::fs::symlink_metadata(staged) {
+        let _ = remove_entry(staged, &metadata);
+    }
+}
+
+fn remove_entry(path: &Path, metadata: &std::fs::Metadata) -> std::io::Result<()> {
+    if metadata.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// `name copy.ext`, or `name copy 2.ext` if it already exists, and so on.
+fn available_copy_path(source: &Path) -> Result<PathBuf, FsioError> {
+    let (Some(folder), Some(file_name)) = (source.parent(), source.file_name()) else {
+        return Err(FsioError::MissingFileName {
+            path: source.to_owned(),
+        });
+    };
+    let file_name = file_name
+        .to_str()
+        .ok_or_else(|| FsioError::NonUnicodeFileName {
+            file_name: file_name.into(),
+        })?;
+    let (name, extension) = if source.is_dir() {
+        (file_name, "")
+    } else {
+        split_archive_extension(file_name)
+    };
+    for suffix in std::iter::once(" copy".to_owned()).chain((2..).map(|i| format!(" copy {i}"))) {
+        let candidate = if extension.is_empty() {
+            format!("{name}{suffix}")
+        } else {
+            format!("{name}{suffix}.{extension}")
+        };
+        let candidate = folder.join(candidate);
+        if candidate.symlink_metadata().is_err() {
+            return Ok(candidate);
+        }
+    }
+    unreachable!()
+}
+
+fn sibling_path(file: &Path, name: &std::ffi::OsStr) -> Arc<Path> {
+    file.parent().unwrap_or(Path::new("")).join(name).into()
+}
+
+pub(super) fn create_entry_path(
+    path: FilePath<Arc<Path>>,
+    name: &str,
+) -> Result<PathBuf, FsioError> {
     if name.is_empty() || Path::new(name).components().count() != 1 {
         return Err(FsioError::InvalidEntryName {
             name: name.to_owned(),
@@ -274,29 +725,30 @@ pub async fn delete_file(
     );
     if source.starts_with(&trash) {
         if source.is_dir() {
-            tokio::fs::remove_dir_all(source).await?;
+            tokio::fs::remove_dir_all(&source).await?;
         } else {
-            tokio::fs::remove_file(source).await?;
+            tokio::fs::remove_file(&source).await?;
         }
+        remove_trash_info(&source).await?;
         return Ok(());
     }
     tokio::fs::create_dir_all(&trash).await?;
-    let destination = trash.join(file_name);
+    let mut destination = trash.join(file_name);
     if destination.exists() {
         let metadata = destination.metadata()?;
         let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
         let renamed_destination = available_trash_path(&trash, file_name, modified)?;
-        tokio::fs::rename(&destination, renamed_destination).await?;
-
-        let new_destination = available_trash_path(&trash, file_name, SystemTime::now())?;
-        tokio::fs::rename(source, new_destination).await?;
-    } else {
-        tokio::fs::rename(source, destination).await?;
+        tokio::fs::rename(&destination, &renamed_destination).await?;
+        move_trash_info(&destination, &renamed_destination).await?;
+        destination = available_trash_path(&trash, file_name, SystemTime::now())?;
     }
+    tokio::fs::rename(&source, &destination).await?;
+    write_trash_info(&destination, &source).await?;
     Ok(())
 }
 
-fn delete_trash_path(source: &Path, trash: &Path, git_trash: Option<&Path>) -> PathBuf {
+/// The folder where deleted files go: the git trash of their repo, or the global trash.
+pub fn delete_trash_path(source: &Path, trash: &Path, git_trash: Option<&Path>) -> PathBuf {
     if let Some(git_trash) = git_trash
         && let Some(repo_root) = git::git_repo_root(source)
     {
@@ -331,7 +783,115 @@ fn available_trash_path(
     unreachable!()
 }
 
-fn split_archive_extension(file_name: &str) -> (&str, &str) {
+/// The most recently modified copy of the file in the trash, under its name or a dated name.
+pub fn find_in_trash(
+    source: &Path,
+    trash: impl AsRef<Path>,
+    git_trash: Option<impl AsRef<Path>>,
+) -> Option<PathBuf> {
+    let file_name = source.file_name()?.to_str()?;
+    let trash = delete_trash_path(
+        source,
+        trash.as_ref(),
+        git_trash.as_ref().map(AsRef::as_ref),
+    );
+    let (name, extension) = split_archive_extension(file_name);
+    let is_copy = |entry: &str| {
+        if entry == file_name {
+            return true;
+        }
+        let Some(dated) = entry.strip_prefix(name).and_then(|e| e.strip_prefix('_')) else {
+            return false;
+        };
+        let dated = if extension.is_empty() {
+            Some(dated)
+        } else {
+            dated
+                .strip_suffix(extension)
+                .and_then(|d| d.strip_suffix('.'))
+        };
+        let Some(dated) = dated else {
+            return false;
+        };
+        let (date, suffix) = dated.split_at_checked(10).unwrap_or((dated, ""));
+        date.parse::<chrono::NaiveDate>().is_ok()
+            && (suffix.is_empty()
+                || suffix
+                    .strip_prefix('-')
+                    .is_some_and(|index| index.parse::<u32>().is_ok()))
+    };
+    trash
+        .read_dir()
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_str().is_some_and(is_copy))
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// The folder, inside a trash folder, that records where its entries came from.
+pub const TRASH_INFO_FOLDER: &str = ".trashinfo";
+
+/// Where a trash entry came from, and when it was deleted.
+#[derive(Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TrashInfo {
+    pub path: PathBuf,
+    pub deleted: Duration,
+}
+
+fn trash_info_path(entry: &Path) -> Option<PathBuf> {
+    let mut info_name = entry.file_name()?.to_owned();
+    info_name.push(".json");
+    Some(entry.parent()?.join(TRASH_INFO_FOLDER).join(info_name))
+}
+
+/// Reads where the trash entry came from, if it was recorded when the entry was deleted.
+pub async fn read_trash_info(entry: &Path) -> Option<TrashInfo> {
+    let info = tokio::fs::read(trash_info_path(entry)?).await.ok()?;
+    serde_json::from_slice(&info)
+        .inspect_err(|error| warn!("Invalid trash info for {entry:?}: {error}"))
+        .ok()
+}
+
+async fn write_trash_info(entry: &Path, original: &Path) -> std::io::Result<()> {
+    let Some(info_path) = trash_info_path(entry) else {
+        return Ok(());
+    };
+    let info = TrashInfo {
+        path: original.to_owned(),
+        deleted: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default(),
+    };
+    if let Some(info_folder) = info_path.parent() {
+        tokio::fs::create_dir_all(info_folder).await?;
+    }
+    tokio::fs::write(info_path, serde_json::to_vec(&info)?).await
+}
+
+async fn move_trash_info(from: &Path, to: &Path) -> std::io::Result<()> {
+    let (Some(from), Some(to)) = (trash_info_path(from), trash_info_path(to)) else {
+        return Ok(());
+    };
+    match tokio::fs::rename(from, to).await {
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// Forgets where the trash entry came from, once it was restored or purged.
+pub async fn remove_trash_info(entry: &Path) -> std::io::Result<()> {
+    let Some(info_path) = trash_info_path(entry) else {
+        return Ok(());
+    };
+    match tokio::fs::remove_file(info_path).await {
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+pub fn split_archive_extension(file_name: &str) -> (&str, &str) {
     match file_name
         .char_indices()
         .find(|(index, c)| *index > 0 && *c == '.')
@@ -364,6 +924,45 @@ pub enum FsioError {
 
     #[error("[{n}] Base folder does not exist: {base:?}", n = self.name())]
     BaseFolderNotFound { base: Arc<Path> },
+
+    #[error("[{n}] File changed on disk since it was loaded: {path:?}", n = self.name())]
+    FileChanged { path: PathBuf },
+
+    #[error("[{n}] Destination already exists: {path:?}", n = self.name())]
+    DestinationExists { path: PathBuf },
+
+    #[error("[{n}] Can't copy or move a folder inside itself: {path:?}", n = self.name())]
+    DestinationInsideSource { path: PathBuf },
+
+    #[error("[{n}] Not permitted to read the properties of {path:?}", n = self.name())]
+    PropertiesNotPermitted { path: PathBuf },
+
+    #[error("[{n}] Not permitted to change the permissions of {path:?}", n = self.name())]
+    ChmodNotPermitted { path: PathBuf },
+
+    #[error("[{n}] Not permitted to change the owner of {path:?}", n = self.name())]
+    ChownNotPermitted { path: PathBuf },
+
+    #[error("[{n}] Not permitted to create the symlink {path:?}", n = self.name())]
+    SymlinkNotPermitted { path: PathBuf },
+
+    #[error("[{n}] Not permitted to follow the symlink {path:?}", n = self.name())]
+    FollowNotPermitted { path: PathBuf },
+
+    #[error("[{n}] Invalid permissions: {mode:o}", n = self.name())]
+    InvalidMode { mode: u32 },
+
+    #[error("[{n}] User not found: {name:?}", n = self.name())]
+    UserNotFound { name: String },
+
+    #[error("[{n}] Group not found: {name:?}", n = self.name())]
+    GroupNotFound { name: String },
+
+    #[error("[{n}] Not a symlink: {path:?}", n = self.name())]
+    NotSymlink { path: PathBuf },
+
+    #[error("[{n}] The target of {path:?} doesn't exist: {target:?}", n = self.name())]
+    BrokenSymlink { path: PathBuf, target: PathBuf },
 }
 
 impl IsGrpcError for FsioError {
@@ -376,6 +975,18 @@ impl IsGrpcError for FsioError {
             Self::ParentNotFolder { .. } => Code::FailedPrecondition,
             Self::NonUnicodeFileName { .. } => Code::InvalidArgument,
             Self::BaseFolderNotFound { .. } => Code::NotFound,
+            Self::FileChanged { .. } => Code::Aborted,
+            Self::DestinationExists { .. } => Code::AlreadyExists,
+            Self::DestinationInsideSource { .. } => Code::InvalidArgument,
+            Self::PropertiesNotPermitted { .. }
+            | Self::ChmodNotPermitted { .. }
+            | Self::ChownNotPermitted { .. }
+            | Self::SymlinkNotPermitted { .. }
+            | Self::FollowNotPermitted { .. } => Code::PermissionDenied,
+            Self::InvalidMode { .. } => Code::InvalidArgument,
+            Self::UserNotFound { .. } | Self::GroupNotFound { .. } => Code::NotFound,
+            Self::NotSymlink { .. } => Code::FailedPrecondition,
+            Self::BrokenSymlink { .. } => Code::NotFound,
         }
     }
 }
@@ -393,6 +1004,8 @@ mod tests {
     use std::sync::Arc;
 
     use crate::text_editor::file_path::FilePath;
+    use crate::text_editor::fsio::FileMetadata;
+    use crate::text_editor::fsio::OnConflict;
 
     #[tokio::test]
     async fn create_file_in_folder() {
@@ -444,6 +1057,7 @@ mod tests {
                 base,
                 file: Arc::from("destination".as_ref()),
             },
+            OnConflict::Fail,
         )
         .await
         .unwrap();
@@ -480,6 +1094,7 @@ mod tests {
                 base,
                 file: Arc::from("destination".as_ref()),
             },
+            OnConflict::Fail,
         )
         .await
         .unwrap();
@@ -493,6 +1108,184 @@ mod tests {
         );
     }
 
+    fn file_path(base: &Arc<Path>, file: &str) -> FilePath<Arc<Path>> {
+        FilePath {
+            base: base.clone(),
+            file: Arc::from(file.as_ref()),
+        }
+    }
+
+    #[tokio::test]
+    async fn move_file_conflict() {
+        let tempdir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tempdir.path().join("destination/source")).unwrap();
+        std::fs::write(tempdir.path().join("destination/source/a.txt"), "old a").unwrap();
+        std::fs::write(tempdir.path().join("destination/source/b.txt"), "old b").unwrap();
+        std::fs::create_dir(tempdir.path().join("source")).unwrap();
+        std::fs::write(tempdir.path().join("source/a.txt"), "new a").unwrap();
+        std::fs::write(tempdir.path().join("source/c.txt"), "new c").unwrap();
+        let base: Arc<Path> = Arc::from(tempdir.path());
+        let source = file_path(&base, "source");
+        let destination = file_path(&base, "destination");
+
+        let error = super::move_file(source.clone(), destination.clone(), OnConflict::Fail)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, super::FsioError::DestinationExists { .. }));
+
+        super::move_file(source, destination, OnConflict::Skip)
+            .await
+            .unwrap();
+        let read = |file: &str| std::fs::read_to_string(tempdir.path().join(file)).unwrap();
+        assert_eq!("old a", read("destination/source/a.txt"));
+        assert_eq!("old b", read("destination/source/b.txt"));
+        assert_eq!("new c", read("destination/source/c.txt"));
+        assert_eq!("new a", read("source/a.txt"));
+        assert!(!tempdir.path().join("source/c.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn failed_overwrite_keeps_destination() {
+        let tempdir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tempdir.path().join("destination")).unwrap();
+        std::fs::write(tempdir.path().join("destination/source"), "old").unwrap();
+        std::fs::create_dir(tempdir.path().join("source")).unwrap();
+        std::fs::write(tempdir.path().join("source/a.txt"), "new a").unwrap();
+        // Sockets can't be copied.
+        let _socket =
+            std::os::unix::net::UnixListener::bind(tempdir.path().join("source/socket")).unwrap();
+        let base: Arc<Path> = Arc::from(tempdir.path());
+
+        super::copy_file(
+            file_path(&base, "source"),
+            file_path(&base, "destination"),
+            OnConflict::Overwrite,
+        )
+        .await
+        .unwrap_err();
+
+        let read = |file: &str| std::fs::read_to_string(tempdir.path().join(file)).unwrap();
+        assert_eq!("old", read("destination/source"));
+        assert_eq!(
+            1,
+            std::fs::read_dir(tempdir.path().join("destination"))
+                .unwrap()
+                .count()
+        );
+    }
+
+    #[tokio::test]
+    async fn move_folder_into_itself() {
+        let tempdir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(tempdir.path().join("source/child")).unwrap();
+        let base: Arc<Path> = Arc::from(tempdir.path());
+
+        let error = super::move_file(
+            file_path(&base, "source"),
+            file_path(&base, "source/child"),
+            OnConflict::Overwrite,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            super::FsioError::DestinationInsideSource { .. }
+        ));
+        assert!(tempdir.path().join("source/child").is_dir());
+    }
+
+    #[tokio::test]
+    async fn copy_folder_into_folder() {
+        let tempdir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tempdir.path().join("destination")).unwrap();
+        std::fs::create_dir(tempdir.path().join("source")).unwrap();
+        std::fs::write(tempdir.path().join("source/child.txt"), "hello").unwrap();
+        std::os::unix::fs::symlink("child.txt", tempdir.path().join("source/link")).unwrap();
+        let base: Arc<Path> = Arc::from(tempdir.path());
+
+        super::copy_file(
+            file_path(&base, "source"),
+            file_path(&base, "destination"),
+            OnConflict::Fail,
+        )
+        .await
+        .unwrap();
+
+        let copy = tempdir.path().join("destination/source");
+        assert_eq!(
+            "hello",
+            std::fs::read_to_string(copy.join("child.txt")).unwrap()
+        );
+        assert_eq!(
+            Path::new("child.txt"),
+            std::fs::read_link(copy.join("link")).unwrap()
+        );
+        assert!(tempdir.path().join("source/child.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn copy_file_overwrite() {
+        let tempdir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tempdir.path().join("destination")).unwrap();
+        std::fs::write(tempdir.path().join("destination/file.txt"), "old").unwrap();
+        std::fs::write(tempdir.path().join("file.txt"), "new").unwrap();
+        let base: Arc<Path> = Arc::from(tempdir.path());
+
+        super::copy_file(
+            file_path(&base, "file.txt"),
+            file_path(&base, "destination"),
+            OnConflict::Overwrite,
+        )
+        .await
+        .unwrap();
+
+        let copy = tempdir.path().join("destination/file.txt");
+        assert_eq!("new", std::fs::read_to_string(copy).unwrap());
+    }
+
+    #[tokio::test]
+    async fn duplicate_file() {
+        let tempdir = tempfile::tempdir().unwrap();
+        std::fs::write(tempdir.path().join("archive.tar.gz"), "hello").unwrap();
+        let base: Arc<Path> = Arc::from(tempdir.path());
+
+        let first = super::duplicate_file(file_path(&base, "archive.tar.gz"))
+            .await
+            .unwrap();
+        let second = super::copy_file(
+            file_path(&base, "archive.tar.gz"),
+            file_path(&base, ""),
+            OnConflict::Fail,
+        )
+        .await;
+
+        assert_eq!(Path::new("archive copy.tar.gz"), &*first);
+        assert!(second.is_ok());
+        assert!(tempdir.path().join("archive copy 2.tar.gz").is_file());
+    }
+
+    #[tokio::test]
+    async fn rename_file() {
+        let tempdir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(tempdir.path().join("folder")).unwrap();
+        std::fs::write(tempdir.path().join("folder/old.txt"), "hello").unwrap();
+        std::fs::write(tempdir.path().join("folder/taken.txt"), "").unwrap();
+        let base: Arc<Path> = Arc::from(tempdir.path());
+
+        let error = super::rename_file(file_path(&base, "folder/old.txt"), "taken.txt".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, super::FsioError::DestinationExists { .. }));
+
+        let renamed = super::rename_file(file_path(&base, "folder/old.txt"), " new.txt ".into())
+            .await
+            .unwrap();
+        assert_eq!(Path::new("folder/new.txt"), &*renamed);
+        assert!(!tempdir.path().join("folder/old.txt").exists());
+        assert!(tempdir.path().join("folder/new.txt").is_file());
+    }
+
     #[tokio::test]
     async fn create_entry_rejects_nested_names() {
         let tempdir = tempfile::tempdir().unwrap();
@@ -534,13 +1327,124 @@ mod tests {
             file: Arc::from("missing.txt".as_ref()),
         };
 
-        let error = super::store_file(path, "content".to_owned())
+        let error = super::store_file(path, "content".to_owned(), None)
             .await
             .unwrap_err();
 
         assert!(matches!(error, super::FsioError::PathNotFound { .. }));
     }
 
+    #[tokio::test]
+    async fn store_file_rejects_changed_file() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let file = tempdir.path().join("file.txt");
+        tokio::fs::write(&file, "loaded").await.unwrap();
+        let path = FilePath {
+            base: Arc::from(tempdir.path()),
+            file: Arc::from("file.txt".as_ref()),
+        };
+        let loaded = Arc::new(FileMetadata::single(
+            &file,
+            &tokio::fs::metadata(&file).await.unwrap(),
+        ));
+
+        let stored = super::store_file(path.clone(), "saved".to_owned(), Some(loaded.clone()))
+            .await
+            .unwrap();
+        let error = super::store_file(path.clone(), "stale".to_owned(), Some(loaded))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, super::FsioError::FileChanged { .. }));
+
+        super::store_file(path, "saved again".to_owned(), Some(stored))
+            .await
+            .unwrap();
+        assert_eq!(
+            "saved again",
+            tokio::fs::read_to_string(&file).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn store_file_replaces_file_atomically() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let tempdir = tempfile::tempdir().unwrap();
+        let file = tempdir.path().join("script.sh");
+        tokio::fs::write(&file, "echo old").await.unwrap();
+        tokio::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o750))
+            .await
+            .unwrap();
+        std::os::unix::fs::symlink(&file, tempdir.path().join("link.sh")).unwrap();
+        let path = FilePath {
+            base: Arc::from(tempdir.path()),
+            file: Arc::from("link.sh".as_ref()),
+        };
+
+        super::store_file(path, "echo new".to_owned(), None)
+            .await
+            .unwrap();
+
+        assert_eq!("echo new", tokio::fs::read_to_string(&file).await.unwrap());
+        let metadata = tokio::fs::metadata(&file).await.unwrap();
+        assert_eq!(0o750, metadata.permissions().mode() & 0o777);
+        assert!(
+            tokio::fs::symlink_metadata(tempdir.path().join("link.sh"))
+                .await
+                .unwrap()
+                .is_symlink()
+        );
+        let entries = std::fs::read_dir(tempdir.path()).unwrap().count();
+        assert_eq!(2, entries, "The temp file should be gone");
+    }
+
+    #[tokio::test]
+    async fn store_file_keeps_encoding() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let file = tempdir.path().join("latin1.txt");
+        tokio::fs::write(&file, b"caf\xE9").await.unwrap();
+        let path = FilePath {
+            base: Arc::from(tempdir.path()),
+            file: Arc::from("latin1.txt".as_ref()),
+        };
+
+        let Some(super::File::TextFile { content, .. }) =
+            super::load_file(path.clone()).await.unwrap()
+        else {
+            panic!("Expected a text file");
+        };
+        assert_eq!("caf\u{e9}", content.as_ref());
+
+        super::store_file(path.clone(), "cr\u{e8}me".to_owned(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            b"cr\xE8me".as_slice(),
+            tokio::fs::read(&file).await.unwrap()
+        );
+
+        // Latin-1 can't represent the emoji.
+        super::store_file(path, "\u{1F600}".to_owned(), None)
+            .await
+            .unwrap();
+        assert_eq!("\u{1F600}", tokio::fs::read_to_string(&file).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn load_binary_file() {
+        let tempdir = tempfile::tempdir().unwrap();
+        tokio::fs::write(tempdir.path().join("data.bin"), b"\x7FELF\x02\x01\0\0")
+            .await
+            .unwrap();
+        let path = FilePath {
+            base: Arc::from(tempdir.path()),
+            file: Arc::from("data.bin".as_ref()),
+        };
+
+        let file = super::load_file(path).await.unwrap();
+        assert!(matches!(file, Some(super::File::BinaryFile { .. })));
+    }
+
     #[tokio::test]
     async fn trash_conflicts_date_existing_and_new_entries() {
         let tempdir = tempfile::tempdir().unwrap();
@@ -584,6 +1488,36 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn find_in_trash_picks_the_latest_copy() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let source = tempdir.path().join("file.tar.gz");
+        let trash = tempdir.path().join("trash");
+        tokio::fs::create_dir(&trash).await.unwrap();
+        let write = |name: &str, age: u64| {
+            let path = trash.join(name);
+            std::fs::write(&path, name).unwrap();
+            let modified = std::time::SystemTime::now() - std::time::Duration::from_secs(age);
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        };
+
+        assert_eq!(None, super::find_in_trash(&source, &trash, None::<&Path>));
+        write("file.tar.gz", 30);
+        write("file_2024-01-02-1.tar.gz", 10);
+        write("file_2024-01-02.tar.gz", 20);
+        write("file_other.tar.gz", 0);
+        write("other.tar.gz", 0);
+        assert_eq!(
+            Some(trash.join("file_2024-01-02-1.tar.gz")),
+            super::find_in_trash(&source, &trash, None::<&Path>)
+        );
+    }
+
     #[test]
     fn split_archive_extension_keeps_tar_gz_together() {
         assert_eq!(
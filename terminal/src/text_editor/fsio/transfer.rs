@@ -0,0 +1,80 @@
+//! Copies or moves entries between remotes.
+//!
+//! The source is downloaded as a tar archive, and extracted into the destination folder while it
+//! is uploaded.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use futures::FutureExt as _;
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+use tracing::debug;
+
+use super::OnConflict;
+use super::remote;
+use crate::api::client_address::ClientAddress;
+use crate::backend::client_service::remote_fn_service::RemoteFnServerError;
+use crate::backend::client_service::remote_fn_service::remote_fn_server;
+use crate::backend::client_service::remote_fn_service::unary::RemoteFnError;
+use crate::backend::client_service::text_editor_service;
+use crate::backend::client_service::text_editor_service::DownloadOptions;
+use crate::backend::client_service::text_editor_service::TextEditorFsioError;
+use crate::backend::client_service::text_editor_service::UploadOptions;
+use crate::text_editor::file_path::FilePath;
+
+/// Copies the entry into the folder of the other remote, then moves the source to the trash if
+/// `remove_source` is set.
+pub async fn transfer_file(
+    (source_remote, source): (ClientAddress, FilePath<Arc<Path>>),
+    (destination_remote, destination_folder): (ClientAddress, FilePath<Arc<Path>>),
+    on_conflict: OnConflict,
+    remove_source: bool,
+) -> Result<(), TransferError> {
+    let server = remote_fn_server()?;
+    debug!("Transferring {source:?} to {destination_folder:?}");
+    let options = DownloadOptions {
+        archive: true,
+        ..DownloadOptions::default()
+    };
+    let archive = text_editor_service::download(
+        &server,
+        &source_remote,
+        source.clone().map(|path| path.to_path_buf()),
+        options,
+    )
+    .await?;
+    let options = UploadOptions {
+        offset: 0,
+        extract: Some(on_conflict),
+    };
+    // Boxed, otherwise the server fn can't prove that the upload is Send for all lifetimes.
+    text_editor_service::upload(
+        &server,
+        &destination_remote,
+        destination_folder.map(|path| path.to_path_buf()),
+        options,
+        archive,
+    )
+    .boxed()
+    .await?;
+    if remove_source {
+        remote::DELETE_FILE_REMOTE_FN
+            .call(source_remote, remote::DeleteFileRequest { path: source })
+            .await?;
+    }
+    Ok(())
+}
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+pub enum TransferError {
+    #[error("[{n}] {0}", n = self.name())]
+    Server(#[from] RemoteFnServerError),
+
+    #[error("[{n}] {0}", n = self.name())]
+    Fsio(#[from] TextEditorFsioError),
+
+    #[error("[{n}] Copied, but failed to remove the source: {0}", n = self.name())]
+    RemoveSource(#[from] RemoteFnError),
+}
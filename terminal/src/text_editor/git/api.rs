@@ -0,0 +1,198 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use server_fn::Http;
+use server_fn::ServerFnError;
+use server_fn::codec::Json;
+use terrazzo::server;
+
+use crate::api::client_address::ClientAddress;
+use crate::text_editor::file_path::FilePath;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct GitStatus {
+    /// The current branch, unless the HEAD is detached.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "b"))]
+    pub branch: Option<String>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "e"))]
+    pub entries: Vec<GitStatusEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct GitStatusEntry {
+    /// The path relative to the root of the repository.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "p"))]
+    pub path: String,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "s"))]
+    pub staged: Option<GitChange>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "u"))]
+    pub unstaged: Option<GitChange>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum GitChange {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "A"))]
+    Added,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "M"))]
+    Modified,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "D"))]
+    Deleted,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "R"))]
+    Renamed,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "C"))]
+    Copied,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "T"))]
+    TypeChanged,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "U"))]
+    Untracked,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "X"))]
+    Conflicted,
+}
+
+impl GitChange {
+    #[cfg_attr(feature = "server", allow(dead_code))]
+    pub fn letter(self) -> &'static str {
+        match self {
+            Self::Added => "A",
+            Self::Modified => "M",
+            Self::Deleted => "D",
+            Self::Renamed => "R",
+            Self::Copied => "C",
+            Self::TypeChanged => "T",
+            Self::Untracked => "?",
+            Self::Conflicted => "!",
+        }
+    }
+}
+
+/// A hunk of `git diff`, the lines keep their ' ', '+' or '-' prefix.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct GitHunk {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "h"))]
+    pub header: String,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "l"))]
+    pub lines: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct GitBranch {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "n"))]
+    pub name: String,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "c"))]
+    pub current: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct GitCommit {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "i"))]
+    pub id: String,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "a"))]
+    pub author: String,
+    /// Seconds since the epoch.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "t"))]
+    pub time: u64,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "s"))]
+    pub summary: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct GitBlame {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "c"))]
+    pub commits: Vec<GitCommit>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "l"))]
+    pub lines: Vec<GitBlameLine>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct GitBlameLine {
+    /// The index of the commit in [GitBlame::commits].
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "c"))]
+    pub commit: usize,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "l"))]
+    pub line: String,
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn status(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<GitStatus, ServerFnError> {
+    super::service::status(remote, path).await
+}
+
+/// The hunks of a file, in the index if `staged`, in the worktree otherwise.
+#[server(protocol = Http<Json, Json>)]
+pub async fn diff(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    file: String,
+    staged: bool,
+) -> Result<Vec<GitHunk>, ServerFnError> {
+    super::service::diff(remote, path, file, staged).await
+}
+
+/// Stages the files, or unstages them if not `stage`.
+#[server(protocol = Http<Json, Json>)]
+pub async fn stage(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    files: Vec<String>,
+    stage: bool,
+) -> Result<(), ServerFnError> {
+    super::service::stage(remote, path, files, stage).await
+}
+
+/// Stages a hunk from the worktree, or unstages a hunk from the index if not `stage`.
+#[server(protocol = Http<Json, Json>)]
+pub async fn stage_hunk(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    file: String,
+    hunk: GitHunk,
+    stage: bool,
+) -> Result<(), ServerFnError> {
+    super::service::stage_hunk(remote, path, file, hunk, stage).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn commit(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    message: String,
+) -> Result<(), ServerFnError> {
+    super::service::commit(remote, path, message).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn branches(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<Vec<GitBranch>, ServerFnError> {
+    super::service::branches(remote, path).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn switch_branch(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    branch: String,
+) -> Result<(), ServerFnError> {
+    super::service::switch_branch(remote, path, branch).await
+}
+
+/// The last commits that changed the file.
+#[server(protocol = Http<Json, Json>)]
+pub async fn log(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<Vec<GitCommit>, ServerFnError> {
+    super::service::log(remote, path).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn blame(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<GitBlame, ServerFnError> {
+    super::service::blame(remote, path).await
+}
@@ -0,0 +1,9 @@
+//! A git panel for the repository of the base path.
+
+pub mod api;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "client")]
+pub mod state;
+#[cfg(feature = "client")]
+pub mod ui;
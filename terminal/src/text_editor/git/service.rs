@@ -0,0 +1,740 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+use server_fn::ServerFnError;
+use tokio::io::AsyncWriteExt as _;
+use tracing::debug;
+
+use super::api::GitBlame;
+use super::api::GitBlameLine;
+use super::api::GitBranch;
+use super::api::GitChange;
+use super::api::GitCommit;
+use super::api::GitHunk;
+use super::api::GitStatus;
+use super::api::GitStatusEntry;
+use crate::api::client_address::ClientAddress;
+use crate::backend::client_service::grpc_error::GrpcError;
+use crate::backend::client_service::grpc_error::IsGrpcError;
+use crate::backend::client_service::remote_fn_service;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio::git::git_repo_root;
+
+const MAX_LOG: usize = 100;
+
+pub async fn status(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<GitStatus, ServerFnError> {
+    Ok(STATUS_FN.call(remote, path).await?)
+}
+
+pub async fn diff(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    file: String,
+    staged: bool,
+) -> Result<Vec<GitHunk>, ServerFnError> {
+    Ok(DIFF_FN.call(remote, (path, file, staged)).await?)
+}
+
+pub async fn stage(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    files: Vec<String>,
+    stage: bool,
+) -> Result<(), ServerFnError> {
+    Ok(STAGE_FN.call(remote, (path, files, stage)).await?)
+}
+
+pub async fn stage_hunk(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    file: String,
+    hunk: GitHunk,
+    stage: bool,
+) -> Result<(), ServerFnError> {
+    Ok(STAGE_HUNK_FN
+        .call(remote, (path, file, hunk, stage))
+        .await?)
+}
+
+pub async fn commit(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    message: String,
+) -> Result<(), ServerFnError> {
+    Ok(COMMIT_FN.call(remote, (path, message)).await?)
+}
+
+pub async fn branches(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<Vec<GitBranch>, ServerFnError> {
+    Ok(BRANCHES_FN.call(remote, path).await?)
+}
+
+pub async fn switch_branch(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+    branch: String,
+) -> Result<(), ServerFnError> {
+    Ok(SWITCH_BRANCH_FN.call(remote, (path, branch)).await?)
+}
+
+pub async fn log(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<Vec<GitCommit>, ServerFnError> {
+    Ok(LOG_FN.call(remote, path).await?)
+}
+
+pub async fn blame(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<GitBlame, ServerFnError> {
+    Ok(BLAME_FN.call(remote, path).await?)
+}
+
+remote_fn_service::unary::declare_remote_fn!(
+    STATUS_FN,
+    "texteditor.git.status",
+    FilePath<Arc<Path>>,
+    GitStatus,
+    |_server, path| async move { status_impl(path).await.map_err(GrpcError::from) }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    DIFF_FN,
+    "texteditor.git.diff",
+    (FilePath<Arc<Path>>, String, bool),
+    Vec<GitHunk>,
+    |_server, (path, file, staged)| async move {
+        diff_impl(path, file, staged).await.map_err(GrpcError::from)
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    STAGE_FN,
+    "texteditor.git.stage",
+    (FilePath<Arc<Path>>, Vec<String>, bool),
+    (),
+    |_server, (path, files, stage)| async move {
+        stage_impl(path, files, stage)
+            .await
+            .map_err(GrpcError::from)
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    STAGE_HUNK_FN,
+    "texteditor.git.stage_hunk",
+    (FilePath<Arc<Path>>, String, GitHunk, bool),
+    (),
+    |_server, (path, file, hunk, stage)| async move {
+        stage_hunk_impl(path, file, hunk, stage)
+            .await
+            .map_err(GrpcError::from)
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    COMMIT_FN,
+    "texteditor.git.commit",
+    (FilePath<Arc<Path>>, String),
+    (),
+    |_server, (path, message)| async move { commit_impl(path, message).await.map_err(GrpcError::from) }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    BRANCHES_FN,
+    "texteditor.git.branches",
+    FilePath<Arc<Path>>,
+    Vec<GitBranch>,
+    |_server, path| async move { branches_impl(path).await.map_err(GrpcError::from) }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    SWITCH_BRANCH_FN,
+    "texteditor.git.switch_branch",
+    (FilePath<Arc<Path>>, String),
+    (),
+    |_server, (path, branch)| async move {
+        switch_branch_impl(path, branch)
+            .await
+            .map_err(GrpcError::from)
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    LOG_FN,
+    "texteditor.git.log",
+    FilePath<Arc<Path>>,
+    Vec<GitCommit>,
+    |_server, path| async move { log_impl(path).await.map_err(GrpcError::from) }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    BLAME_FN,
+    "texteditor.git.blame",
+    FilePath<Arc<Path>>,
+    GitBlame,
+    |_server, path| async move { blame_impl(path).await.map_err(GrpcError::from) }
+);
+
+async fn status_impl(path: FilePath<Arc<Path>>) -> Result<GitStatus, GitError> {
+    let repo = repo_root(&path)?;
+    let output = git(&repo, ["status", "--porcelain=v1", "--branch", "-z"], None).await?;
+    Ok(parse_status(&output))
+}
+
+async fn diff_impl(
+    path: FilePath<Arc<Path>>,
+    file: String,
+    staged: bool,
+) -> Result<Vec<GitHunk>, GitError> {
+    let repo = repo_root(&path)?;
+    let mut args = vec!["diff", "--no-color", "--no-ext-diff"];
+    if staged {
+        args.push("--cached");
+    }
+    args.extend(["--", &file]);
+    let output = git(&repo, args, None).await?;
+    Ok(parse_diff(&output))
+}
+
+async fn stage_impl(
+    path: FilePath<Arc<Path>>,
+    files: Vec<String>,
+    stage: bool,
+) -> Result<(), GitError> {
+    let repo = repo_root(&path)?;
+    let mut args = if stage {
+        vec!["add", "--all"]
+    } else if git(&repo, ["rev-parse", "--verify", "-q", "HEAD"], None)
+        .await
+        .is_ok()
+    {
+        vec!["reset", "-q"]
+    } else {
+        // There is no HEAD to reset to before the first commit.
+        vec!["rm", "--cached", "-r", "-q"]
+    };
+    args.push("--");
+    args.extend(files.iter().map(String::as_str));
+    git(&repo, args, None).await?;
+    Ok(())
+}
+
+async fn stage_hunk_impl(
+    path: FilePath<Arc<Path>>,
+    file: String,
+    hunk: GitHunk,
+    stage: bool,
+) -> Result<(), GitError> {
+    let repo = repo_root(&path)?;
+    let mut patch = format!(
+        "diff --git a/{file} b/{file}\n--- a/{file}\n+++ b/{file}\n{}\n",
+        hunk.header
+    );
+    for line in &hunk.lines {
+        patch += line;
+        patch += "\n";
+    }
+    let mut args = vec!["apply", "--cached", "--recount"];
+    if !stage {
+        args.push("--reverse");
+    }
+    args.push("-");
+    git(&repo, args, Some(&patch)).await?;
+    Ok(())
+}
+
+async fn commit_impl(path: FilePath<Arc<Path>>, message: String) -> Result<(), GitError> {
+    if message.trim().is_empty() {
+        return Err(GitError::EmptyCommitMessage);
+    }
+    let repo = repo_root(&path)?;
+    git(&repo, ["commit", "-q", "-F", "-"], Some(&message)).await?;
+    Ok(())
+}
+
+async fn branches_impl(path: FilePath<Arc<Path>>) -> Result<Vec<GitBranch>, GitError> {
+    let repo = repo_root(&path)?;
+    let output = git(
+        &repo,
+        [
+            "for-each-ref",
+            "--format=%(HEAD)%00%(refname:short)",
+            "refs/heads/",
+        ],
+        None,
+    )
+    .await?;
+    Ok(output
+        .lines()
+        .filter_map(|line| line.split_once('\0'))
+        .map(|(head, name)| GitBranch {
+            name: name.to_owned(),
+            current: head == "*",
+        })
+        .collect())
+}
+
+async fn switch_branch_impl(path: FilePath<Arc<Path>>, branch: String) -> Result<(), GitError> {
+    if branch.is_empty() || branch.starts_with('-') {
+        return Err(GitError::InvalidBranch { branch });
+    }
+    let repo = repo_root(&path)?;
+    git(&repo, ["switch", "--no-guess", &branch], None).await?;
+    Ok(())
+}
+
+async fn log_impl(path: FilePath<Arc<Path>>) -> Result<Vec<GitCommit>, GitError> {
+    let repo = repo_root(&path)?;
+    let file = relative_path(&repo, &path)?;
+    let max_count = format!("--max-count={MAX_LOG}");
+    let output = git(
+        &repo,
+        [
+            OsStr::new("log"),
+            max_count.as_ref(),
+            "--format=%H%x00%an%x00%at%x00%s".as_ref(),
+            "--".as_ref(),
+            file.as_ref(),
+        ],
+        None,
+    )
+    .await?;
+    Ok(output.lines().filter_map(parse_commit).collect())
+}
+
+async fn blame_impl(path: FilePath<Arc<Path>>) -> Result<GitBlame, GitError> {
+    let repo = repo_root(&path)?;
+    let file = relative_path(&repo, &path)?;
+    let output = git(
+        &repo,
+        [
+            OsStr::new("blame"),
+            "--porcelain".as_ref(),
+            "--".as_ref(),
+            file.as_ref(),
+        ],
+        None,
+    )
+    .await?;
+    Ok(parse_blame(&output))
+}
+
+fn repo_root(path: &FilePath<Arc<Path>>) -> Result<Arc<Path>, GitError> {
+    let full_path = path.full_path();
+    git_repo_root(&full_path).ok_or(GitError::NotGit { path: full_path })
+}
+
+fn relative_path(repo: &Path, path: &FilePath<Arc<Path>>) -> Result<std::path::PathBuf, GitError> {
+    let full_path = path.full_path();
+    match full_path.strip_prefix(repo) {
+        Ok(relative) if relative.as_os_str().is_empty() => Ok(".".into()),
+        Ok(relative) => Ok(relative.to_owned()),
+        Err(_) => Err(GitError::NotGit { path: full_path }),
+    }
+}
+
+async fn git<I, S>(repo: &Path, args: I, input: Option<&str>) -> Result<String, GitError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut command = tokio::process::Command::new("git");
+    command
+        .current_dir(repo)
+        .args(args)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    debug!("Running {command:?}");
+    let mut process = command.spawn()?;
+    if let Some(input) = input
+        && let Some(mut stdin) = process.stdin.take()
+    {
+        stdin.write_all(input.as_bytes()).await?;
+    }
+    let output = process.wait_with_output().await?;
+    if !output.status.success() {
+        return Err(GitError::Git {
+            message: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parses `git status --porcelain=v1 --branch -z`.
+fn parse_status(output: &str) -> GitStatus {
+    let mut status = GitStatus::default();
+    let mut fields = output.split('\0').filter(|field| !field.is_empty());
+    while let Some(field) = fields.next() {
+        if let Some(branch) = field.strip_prefix("## ") {
+            let branch = branch.strip_prefix("No commits yet on ").unwrap_or(branch);
+            let branch = branch.split("...").next().unwrap_or(branch);
+            let branch = branch.split(' ').next().unwrap_or(branch);
+            status.branch = (branch != "HEAD").then(|| branch.to_owned());
+            continue;
+        }
+        let Some((xy, path)) = field.split_at_checked(3) else {
+            continue;
+        };
+        let (x, y) = (xy.as_bytes()[0], xy.as_bytes()[1]);
+        if matches!(x, b'R' | b'C') {
+            // The path the file was renamed or copied from.
+            fields.next();
+        }
+        let (staged, unstaged) = match (x, y) {
+            (b'?', b'?') => (None, Some(GitChange::Untracked)),
+            (b'U', _) | (_, b'U') | (b'A', b'A') | (b'D', b'D') => {
+                (Some(GitChange::Conflicted), Some(GitChange::Conflicted))
+            }
+            (x, y) => (parse_change(x), parse_change(y)),
+        };
+        status.entries.push(GitStatusEntry {
+            path: path.to_owned(),
+            staged,
+            unstaged,
+        });
+    }
+    status
+}
+
+fn parse_change(change: u8) -> Option<GitChange> {
+    Some(match change {
+        b'A' => GitChange::Added,
+        b'M' => GitChange::Modified,
+        b'D' => GitChange::Deleted,
+        b'R' => GitChange::Renamed,
+        b'C' => GitChange::Copied,
+        b'T' => GitChange::TypeChanged,
+        _ => return None,
+    })
+}
+
+/// Parses the hunks of `git diff` for a single file.
+fn parse_diff(output: &str) -> Vec<GitHunk> {
+    let mut hunks: Vec<GitHunk> = vec![];
+    for line in output.lines() {
+        if line.starts_with("@@") {
+            hunks.push(GitHunk {
+                header: line.to_owned(),
+                lines: vec![],
+            });
+        } else if let Some(hunk) = hunks.last_mut() {
+            hunk.lines.push(line.to_owned());
+        }
+    }
+    hunks
+}
+
+/// Parses a commit of `git log --format=%H%x00%an%x00%at%x00%s`.
+fn parse_commit(line: &str) -> Option<GitCommit> {
+    let mut fields = line.splitn(4, '\0');
+    Some(GitCommit {
+        id: fields.next()?.to_owned(),
+        author: fields.next()?.to_owned(),
+        time: fields.next()?.parse().ok()?,
+        summary: fields.next()?.to_owned(),
+    })
+}
+
+/// Parses `git blame --porcelain`.
+fn parse_blame(output: &str) -> GitBlame {
+    let mut blame = GitBlame::default();
+    let mut indexes = HashMap::new();
+    let mut current = None;
+    for line in output.lines() {
+        if let Some(line) = line.strip_prefix('\t') {
+            if let Some(commit) = current {
+                blame.lines.push(GitBlameLine {
+                    commit,
+                    line: line.to_owned(),
+                });
+            }
+            continue;
+        }
+        let Some(commit) = current.map(|commit: usize| &mut blame.commits[commit]) else {
+            current = header_commit(line, &mut indexes, &mut blame.commits);
+            continue;
+        };
+        match line.split_once(' ') {
+            Some(("author", author)) => commit.author = author.to_owned(),
+            Some(("author-time", time)) => commit.time = time.parse().unwrap_or_default(),
+            Some(("summary", summary)) => commit.summary = summary.to_owned(),
+            _ => {
+                if let Some(next) = header_commit(line, &mut indexes, &mut blame.commits) {
+                    current = Some(next);
+                }
+            }
+        }
+    }
+    blame
+}
+
+/// Matches the `<sha> <orig-line> <final-line> [<count>]` line that starts each blamed line.
+fn header_commit(
+    line: &str,
+    indexes: &mut HashMap<String, usize>,
+    commits: &mut Vec<GitCommit>,
+) -> Option<usize> {
+    let id = line.split(' ').next()?;
+    if !matches!(id.len(), 40 | 64) || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(*indexes.entry(id.to_owned()).or_insert_with(|| {
+        commits.push(GitCommit {
+            id: id.to_owned(),
+            author: String::new(),
+            time: 0,
+            summary: String::new(),
+        });
+        commits.len() - 1
+    }))
+}
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+pub enum GitError {
+    #[error("[{n}] {0}", n = self.name())]
+    IO(#[from] std::io::Error),
+
+    #[error("[{n}] Not in a git repository: {path:?}", n = self.name())]
+    NotGit { path: std::path::PathBuf },
+
+    #[error("[{n}] {message}", n = self.name())]
+    Git { message: String },
+
+    #[error("[{n}] The commit message is empty", n = self.name())]
+    EmptyCommitMessage,
+
+    #[error("[{n}] Invalid branch name: {branch:?}", n = self.name())]
+    InvalidBranch { branch: String },
+}
+
+impl IsGrpcError for GitError {
+    fn code(&self) -> tonic::Code {
+        match self {
+            Self::IO { .. } => tonic::Code::Internal,
+            Self::NotGit { .. } => tonic::Code::FailedPrecondition,
+            Self::Git { .. } => tonic::Code::FailedPrecondition,
+            Self::EmptyCommitMessage => tonic::Code::InvalidArgument,
+            Self::InvalidBranch { .. } => tonic::Code::InvalidArgument,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::process::Command;
+    use std::sync::Arc;
+
+    use super::GitChange;
+    use super::GitStatusEntry;
+    use crate::text_editor::file_path::FilePath;
+
+    const LINES: &str = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n";
+
+    #[tokio::test]
+    async fn stage_hunks_and_commit() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let repo = tempdir.path();
+        init_repo(repo);
+        let path = FilePath {
+            base: Arc::from(repo),
+            file: Arc::from(Path::new("")),
+        };
+
+        std::fs::write(
+            repo.join("a.txt"),
+            LINES
+                .replacen("1\n", "one\n", 1)
+                .replace("12\n", "twelve\n"),
+        )
+        .unwrap();
+        std::fs::write(repo.join("b.txt"), "new").unwrap();
+        let status = super::status_impl(path.clone()).await.unwrap();
+        assert_eq!(Some("main"), status.branch.as_deref());
+        assert_eq!(
+            vec![
+                entry("a.txt", None, Some(GitChange::Modified)),
+                entry("b.txt", None, Some(GitChange::Untracked)),
+            ],
+            status.entries
+        );
+
+        // Stage the first of the two hunks.
+        let hunks = super::diff_impl(path.clone(), "a.txt".into(), false)
+            .await
+            .unwrap();
+        assert_eq!(2, hunks.len());
+        super::stage_hunk_impl(path.clone(), "a.txt".into(), hunks[0].clone(), true)
+            .await
+            .unwrap();
+        let staged = super::diff_impl(path.clone(), "a.txt".into(), true)
+            .await
+            .unwrap();
+        assert_eq!(1, staged.len());
+        assert!(staged[0].lines.contains(&"+one".to_owned()));
+        let status = super::status_impl(path.clone()).await.unwrap();
+        assert_eq!(
+            entry(
+                "a.txt",
+                Some(GitChange::Modified),
+                Some(GitChange::Modified)
+            ),
+            status.entries[0]
+        );
+
+        // Unstage it, then stage everything.
+        super::stage_hunk_impl(path.clone(), "a.txt".into(), staged[0].clone(), false)
+            .await
+            .unwrap();
+        assert!(
+            super::diff_impl(path.clone(), "a.txt".into(), true)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        super::stage_impl(path.clone(), vec!["a.txt".into(), "b.txt".into()], true)
+            .await
+            .unwrap();
+        super::stage_impl(path.clone(), vec!["b.txt".into()], false)
+            .await
+            .unwrap();
+        super::commit_impl(path.clone(), "second".into())
+            .await
+            .unwrap();
+        let status = super::status_impl(path.clone()).await.unwrap();
+        assert_eq!(
+            vec![entry("b.txt", None, Some(GitChange::Untracked))],
+            status.entries
+        );
+
+        let file = FilePath {
+            base: path.base.clone(),
+            file: Arc::from(Path::new("a.txt")),
+        };
+        let log = super::log_impl(file.clone()).await.unwrap();
+        assert_eq!(
+            vec!["second", "first"],
+            log.iter().map(|c| c.summary.as_str()).collect::<Vec<_>>()
+        );
+        let blame = super::blame_impl(file).await.unwrap();
+        assert_eq!(12, blame.lines.len());
+        assert_eq!("one", blame.lines[0].line);
+        assert_eq!("second", blame.commits[blame.lines[0].commit].summary);
+        assert_eq!("first", blame.commits[blame.lines[1].commit].summary);
+        assert_eq!("Test User", blame.commits[blame.lines[1].commit].author);
+    }
+
+    #[tokio::test]
+    async fn switch_branches() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let repo = tempdir.path();
+        init_repo(repo);
+        git(repo, &["branch", "dev"]);
+        let path = FilePath {
+            base: Arc::from(repo),
+            file: Arc::from(Path::new("")),
+        };
+
+        let current = |branches: Vec<super::GitBranch>| {
+            branches
+                .into_iter()
+                .map(|branch| (branch.name, branch.current))
+                .collect::<Vec<_>>()
+        };
+        let branches = super::branches_impl(path.clone()).await.unwrap();
+        assert_eq!(
+            vec![("dev".to_owned(), false), ("main".to_owned(), true)],
+            current(branches)
+        );
+        super::switch_branch_impl(path.clone(), "dev".into())
+            .await
+            .unwrap();
+        let branches = super::branches_impl(path.clone()).await.unwrap();
+        assert_eq!(
+            vec![("dev".to_owned(), true), ("main".to_owned(), false)],
+            current(branches)
+        );
+        assert!(
+            super::switch_branch_impl(path, "--orphan".into())
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn parse_status() {
+        let status = super::parse_status(
+            "## main...origin/main [ahead 1]\0R  new.txt\0old.txt\0 D gone.txt\0UU both.txt\0",
+        );
+        assert_eq!(Some("main"), status.branch.as_deref());
+        assert_eq!(
+            vec![
+                entry("new.txt", Some(GitChange::Renamed), None),
+                entry("gone.txt", None, Some(GitChange::Deleted)),
+                entry(
+                    "both.txt",
+                    Some(GitChange::Conflicted),
+                    Some(GitChange::Conflicted)
+                ),
+            ],
+            status.entries
+        );
+        assert_eq!(None, super::parse_status("## HEAD (no branch)\0").branch);
+        assert_eq!(
+            Some("main"),
+            super::parse_status("## No commits yet on main\0")
+                .branch
+                .as_deref()
+        );
+    }
+
+    fn entry(path: &str, staged: Option<GitChange>, unstaged: Option<GitChange>) -> GitStatusEntry {
+        GitStatusEntry {
+            path: path.to_owned(),
+            staged,
+            unstaged,
+        }
+    }
+
+    fn init_repo(repo: &Path) {
+        git(repo, &["init", "-q", "-b", "main"]);
+        git(repo, &["config", "user.email", "test@example.com"]);
+        git(repo, &["config", "user.name", "Test User"]);
+        std::fs::write(repo.join("a.txt"), LINES).unwrap();
+        git(repo, &["add", "a.txt"]);
+        git(repo, &["commit", "-q", "-m", "first"]);
+    }
+
+    fn git(repo: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(repo)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    }
+}
@@ -0,0 +1,25 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use nameth::NamedType as _;
+use nameth::nameth;
+
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::manager::EditorState;
+
+#[derive(Clone)]
+#[nameth]
+pub struct EditorGitState {
+    pub(super) prev: Box<EditorState>,
+
+    /// The file to show the log and blame of.
+    pub file: Option<FilePath<Arc<Path>>>,
+}
+
+impl std::fmt::Debug for EditorGitState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(EditorGitState::type_name())
+            .field("file", &self.file)
+            .finish()
+    }
+}
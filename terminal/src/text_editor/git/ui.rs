@@ -0,0 +1,510 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use server_fn::ServerFnError;
+use terrazzo::autoclone;
+use terrazzo::html;
+use terrazzo::prelude::*;
+use terrazzo::template;
+use terrazzo::widgets::element_capture::ElementCapture;
+use wasm_bindgen::JsCast as _;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlSelectElement;
+use web_sys::HtmlTextAreaElement;
+use web_sys::MouseEvent;
+
+use self::diagnostics::warn;
+use super::api::GitBlame;
+use super::api::GitBranch;
+use super::api::GitChange;
+use super::api::GitCommit;
+use super::api::GitHunk;
+use super::api::GitStatus;
+use super::api::GitStatusEntry;
+use super::state::EditorGitState;
+use crate::assets::icons;
+use crate::frontend::remotes::Remote;
+use crate::frontend::timestamp::datetime::DateTime;
+use crate::frontend::timestamp::display_timestamp;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio;
+use crate::text_editor::fsio::ROOT_FILE_PATH;
+use crate::text_editor::manager::EditorState;
+use crate::text_editor::manager::TextEditorManager;
+use crate::text_editor::style;
+use crate::text_editor::ui::folder::timestamp;
+
+terrazzo_css::import_style!(git_style, "git.scss");
+
+impl TextEditorManager {
+    /// The header button that opens and closes the git panel.
+    #[html]
+    pub fn git_toggle(self: &Ptr<Self>) -> XElement {
+        let manager = self.clone();
+
+        #[template(wrap = true)]
+        fn make_class(#[signal] editor_state: EditorState) -> XAttributeValue {
+            matches!(editor_state, EditorState::Git(_)).then_some(style::ACTIVE)
+        }
+
+        img(
+            class = style::TOGGLE_GIT_PANEL,
+            class %= make_class(self.editor_state.clone()),
+            #[cfg(not(feature = "client-prod"))]
+            class = "toggle-git-panel",
+            src = icons::git(),
+            title = "Git",
+            click = move |_| toggle_git_panel(&manager),
+        )
+    }
+}
+
+fn toggle_git_panel(manager: &TextEditorManager) {
+    manager.editor_state.update(|editor_state| {
+        if let EditorState::Git(EditorGitState { prev, .. }) = editor_state {
+            return Some(prev.as_ref().clone());
+        }
+        let file = match editor_state {
+            EditorState::Data(editor_state) => match &*editor_state.data {
+                fsio::File::TextFile { .. } => Some(editor_state.path.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        Some(EditorState::Git(EditorGitState {
+            prev: Box::new(editor_state.clone()),
+            file,
+        }))
+    });
+}
+
+/// The signals of the git panel, reloaded after each operation.
+struct GitPanel {
+    remote: Remote,
+    base: FilePath<Arc<Path>>,
+    file: Option<FilePath<Arc<Path>>>,
+    status: XSignal<Option<Arc<GitStatus>>>,
+    branches: XSignal<Arc<Vec<GitBranch>>>,
+    selected: XSignal<Option<Arc<SelectedFile>>>,
+    log: XSignal<Arc<Vec<GitCommit>>>,
+    blame: XSignal<Option<Arc<GitBlame>>>,
+    error: XSignal<Option<Arc<str>>>,
+}
+
+/// The file whose hunks are shown.
+#[derive(Debug, PartialEq, Eq)]
+struct SelectedFile {
+    path: String,
+    staged: bool,
+    hunks: Vec<GitHunk>,
+}
+
+impl GitPanel {
+    fn new(manager: &TextEditorManager, file: Option<FilePath<Arc<Path>>>) -> Ptr<Self> {
+        Self {
+            remote: manager.remote.clone(),
+            base: FilePath {
+                base: manager.path.base.get_value_untracked(),
+                file: ROOT_FILE_PATH.clone(),
+            },
+            file,
+            status: XSignal::new("git-status", None),
+            branches: XSignal::new("git-branches", Default::default()),
+            selected: XSignal::new("git-selected", None),
+            log: XSignal::new("git-log", Default::default()),
+            blame: XSignal::new("git-blame", None),
+            error: XSignal::new("git-error", None),
+        }
+        .into()
+    }
+
+    /// Loads the status, the branches, and the history of the file.
+    fn refresh(self: &Ptr<Self>) {
+        let this = self.clone();
+        spawn_local(async move {
+            let (remote, base) = (this.remote.clone(), this.base.clone());
+            let (status, branches) = futures::future::join(
+                super::api::status(remote.clone(), base.clone()),
+                super::api::branches(remote.clone(), base.clone()),
+            )
+            .await;
+            let Some((status, branches)) = this.check(status.and_then(|s| Ok((s, branches?))))
+            else {
+                return;
+            };
+            let selected = match this.selected.get_value_untracked() {
+                Some(selected) => super::api::diff(
+                    remote.clone(),
+                    base.clone(),
+                    selected.path.clone(),
+                    selected.staged,
+                )
+                .await
+                .map(|hunks| {
+                    Some(Arc::new(SelectedFile {
+                        path: selected.path.clone(),
+                        staged: selected.staged,
+                        hunks,
+                    }))
+                }),
+                None => Ok(None),
+            };
+            let history = match &this.file {
+                Some(file) => {
+                    let (log, blame) = futures::future::join(
+                        super::api::log(remote.clone(), file.clone()),
+                        super::api::blame(remote.clone(), file.clone()),
+                    )
+                    .await;
+                    log.map(|log| (log, blame.ok()))
+                }
+                None => Ok(Default::default()),
+            };
+            let batch = Batch::use_batch("git-refresh");
+            this.status.set(Some(Arc::new(status)));
+            this.branches.set(Arc::new(branches));
+            if let Some(selected) = this.check(selected) {
+                this.selected.set(selected.filter(|s| !s.hunks.is_empty()));
+            }
+            if let Some((log, blame)) = this.check(history) {
+                this.log.set(Arc::new(log));
+                this.blame.set(blame.map(Arc::new));
+            }
+            drop(batch);
+        });
+    }
+
+    /// Runs a git operation, then refreshes the panel.
+    fn run(
+        self: &Ptr<Self>,
+        operation: impl Future<Output = Result<(), ServerFnError>> + 'static,
+        on_success: impl FnOnce() + 'static,
+    ) {
+        let this = self.clone();
+        spawn_local(async move {
+            if this.check(operation.await).is_some() {
+                this.error.set(None);
+                on_success();
+            }
+            this.refresh();
+        });
+    }
+
+    fn check<T>(&self, result: Result<T, ServerFnError>) -> Option<T> {
+        result
+            .inspect_err(|error| {
+                warn!("Git operation failed: {error}");
+                self.error.set(Some(error.to_string().into()));
+            })
+            .ok()
+    }
+
+    fn select(self: &Ptr<Self>, path: String, staged: bool) {
+        if let Some(selected) = self.selected.get_value_untracked()
+            && selected.path == path
+            && selected.staged == staged
+        {
+            self.selected.set(None);
+            return;
+        }
+        let this = self.clone();
+        spawn_local(async move {
+            let hunks =
+                super::api::diff(this.remote.clone(), this.base.clone(), path.clone(), staged)
+                    .await;
+            if let Some(hunks) = this.check(hunks) {
+                this.selected.set(Some(Arc::new(SelectedFile {
+                    path,
+                    staged,
+                    hunks,
+                })));
+            }
+        });
+    }
+}
+
+#[autoclone]
+#[html]
+pub fn git_panel(manager: Ptr<TextEditorManager>, git_state: EditorGitState) -> XElement {
+    let panel = GitPanel::new(&manager, git_state.file);
+    let message: ElementCapture<HtmlTextAreaElement> = ElementCapture::default();
+    div(
+        class = git_style::GIT_PANEL,
+        #[cfg(not(feature = "client-prod"))]
+        class = "git-panel",
+        div(
+            class = git_style::GIT_TOOLBAR,
+            show_branches(panel.clone(), panel.branches.clone()),
+            button(
+                "Refresh",
+                click = move |_| {
+                    autoclone!(panel);
+                    panel.refresh()
+                },
+            ),
+        ),
+        show_error(panel.error.clone()),
+        div(
+            class = git_style::GIT_COMMIT,
+            textarea(
+                before_render = message.capture(),
+                placeholder = "Commit message",
+            ),
+            button(
+                "Commit",
+                click = move |_| {
+                    autoclone!(panel, message);
+                    let text = message.with(|m| m.value());
+                    let operation =
+                        super::api::commit(panel.remote.clone(), panel.base.clone(), text);
+                    let message = message.clone();
+                    panel.run(operation, move || message.with(|m| m.set_value("")));
+                },
+            ),
+        ),
+        show_status(panel.clone(), panel.status.clone(), panel.selected.clone()),
+        show_history(panel.log.clone(), panel.blame.clone()),
+        after_render = move |_| {
+            autoclone!(panel);
+            panel.refresh()
+        },
+    )
+}
+
+#[autoclone]
+#[html]
+#[template(tag = select)]
+fn show_branches(panel: Ptr<GitPanel>, #[signal] branches: Arc<Vec<GitBranch>>) -> XElement {
+    let options = branches.iter().map(|branch| {
+        let name = &branch.name;
+        option(
+            value = name.clone(),
+            selected = branch.current.then(|| "selected".to_owned()),
+            "{name}",
+        )
+    });
+    tag(
+        class = git_style::GIT_BRANCHES,
+        title = "Switch branch",
+        change = move |ev: web_sys::Event| {
+            autoclone!(panel);
+            let select = ev.target().or_throw("branch target");
+            let select: HtmlSelectElement = select.dyn_into().or_throw("branch select");
+            let operation =
+                super::api::switch_branch(panel.remote.clone(), panel.base.clone(), select.value());
+            panel.run(operation, || {});
+        },
+        options..,
+    )
+}
+
+#[html]
+#[template(tag = div)]
+fn show_error(#[signal] error: Option<Arc<str>>) -> XElement {
+    let Some(error) = error else {
+        return tag(style::display = "none", style::visibility = "hidden");
+    };
+    tag(class = git_style::GIT_ERROR, "{error}")
+}
+
+#[html]
+#[template(tag = div)]
+fn show_status(
+    panel: Ptr<GitPanel>,
+    #[signal] status: Option<Arc<GitStatus>>,
+    #[signal] selected: Option<Arc<SelectedFile>>,
+) -> XElement {
+    let Some(status) = status else {
+        return tag(class = git_style::GIT_STATUS, "Loading...");
+    };
+    let selected = selected.as_deref();
+    let staged = status.entries.iter().filter_map(|entry| {
+        let change = entry.staged?;
+        Some(status_row(&panel, entry, change, true, selected))
+    });
+    let unstaged = status
+        .entries
+        .iter()
+        .filter_map(|entry| match entry.unstaged {
+            Some(GitChange::Untracked) | None => None,
+            Some(change) => Some(status_row(&panel, entry, change, false, selected)),
+        });
+    let untracked = status
+        .entries
+        .iter()
+        .filter_map(|entry| match entry.unstaged {
+            Some(change @ GitChange::Untracked) => {
+                Some(status_row(&panel, entry, change, false, selected))
+            }
+            _ => None,
+        });
+    let branch = status.branch.as_deref().unwrap_or("(detached HEAD)");
+    tag(
+        class = git_style::GIT_STATUS,
+        div(class = git_style::GIT_SECTION, "On {branch}"),
+        div(class = git_style::GIT_SECTION, "Staged changes"),
+        staged..,
+        div(class = git_style::GIT_SECTION, "Changes"),
+        unstaged..,
+        div(class = git_style::GIT_SECTION, "Untracked files"),
+        untracked..,
+    )
+}
+
+#[autoclone]
+#[html]
+fn status_row(
+    panel: &Ptr<GitPanel>,
+    entry: &GitStatusEntry,
+    change: GitChange,
+    staged: bool,
+    selected: Option<&SelectedFile>,
+) -> XElement {
+    let path = entry.path.clone();
+    let letter = change.letter();
+    let label = if staged { "Unstage" } else { "Stage" };
+    let hunks = match selected {
+        Some(selected)
+            if selected.path == path
+                && selected.staged == staged
+                && change != GitChange::Untracked =>
+        {
+            selected
+                .hunks
+                .iter()
+                .map(|hunk| show_hunk(panel, &path, hunk, staged))
+                .collect()
+        }
+        _ => vec![],
+    };
+    let panel = panel.clone();
+    div(
+        div(
+            class = git_style::GIT_FILE,
+            click = move |_| {
+                autoclone!(panel, path);
+                if change != GitChange::Untracked {
+                    panel.select(path.clone(), staged)
+                }
+            },
+            span(class = git_style::GIT_CHANGE, "{letter}"),
+            span(class = git_style::GIT_PATH, "{path}"),
+            button(
+                "{label}",
+                click = move |event: MouseEvent| {
+                    autoclone!(panel, path);
+                    event.stop_propagation();
+                    let operation = super::api::stage(
+                        panel.remote.clone(),
+                        panel.base.clone(),
+                        vec![path.clone()],
+                        !staged,
+                    );
+                    panel.run(operation, || {});
+                },
+            ),
+        ),
+        hunks..,
+    )
+}
+
+#[autoclone]
+#[html]
+fn show_hunk(panel: &Ptr<GitPanel>, path: &str, hunk: &GitHunk, staged: bool) -> XElement {
+    let lines = hunk.lines.iter().map(|line| {
+        let class = match line.as_bytes().first() {
+            Some(b'+') => Some(git_style::GIT_ADDED),
+            Some(b'-') => Some(git_style::GIT_REMOVED),
+            _ => None,
+        };
+        div(class = class, "{line}")
+    });
+    let panel = panel.clone();
+    let path = path.to_owned();
+    let hunk = hunk.clone();
+    let header = &hunk.header;
+    let label = if staged { "Unstage hunk" } else { "Stage hunk" };
+    div(
+        class = git_style::GIT_HUNK,
+        div(
+            class = git_style::GIT_HUNK_HEADER,
+            span("{header}"),
+            button(
+                "{label}",
+                click = move |_| {
+                    autoclone!(panel, path, hunk);
+                    let operation = super::api::stage_hunk(
+                        panel.remote.clone(),
+                        panel.base.clone(),
+                        path.clone(),
+                        hunk.clone(),
+                        !staged,
+                    );
+                    panel.run(operation, || {});
+                },
+            ),
+        ),
+        lines..,
+    )
+}
+
+#[html]
+#[template(tag = div)]
+fn show_history(
+    #[signal] log: Arc<Vec<GitCommit>>,
+    #[signal] blame: Option<Arc<GitBlame>>,
+) -> XElement {
+    if log.is_empty() && blame.is_none() {
+        return tag(style::display = "none", style::visibility = "hidden");
+    }
+    let commits = log.iter().map(|commit| {
+        let GitCommit {
+            id,
+            author,
+            summary,
+            ..
+        } = commit;
+        let id = &id[..id.len().min(8)];
+        div(
+            class = git_style::GIT_COMMIT_ROW,
+            span(class = git_style::GIT_COMMIT_ID, "{id}"),
+            commit_time(commit),
+            span("{author}"),
+            span("{summary}"),
+        )
+    });
+    let blame_lines = blame.iter().flat_map(|blame| {
+        blame.lines.iter().enumerate().map(|(i, line)| {
+            let GitCommit {
+                id,
+                author,
+                summary,
+                ..
+            } = &blame.commits[line.commit];
+            let id = &id[..id.len().min(8)];
+            let number = i + 1;
+            let line = &line.line;
+            div(
+                class = git_style::GIT_BLAME_ROW,
+                title = format!("{author}: {summary}"),
+                span(class = git_style::GIT_COMMIT_ID, "{id}"),
+                span(class = git_style::GIT_LINE_NUMBER, "{number}"),
+                span("{line}"),
+            )
+        })
+    });
+    tag(
+        class = git_style::GIT_HISTORY,
+        div(class = git_style::GIT_SECTION, "Log"),
+        commits..,
+        div(class = git_style::GIT_SECTION, "Blame"),
+        blame_lines..,
+    )
+}
+
+fn commit_time(commit: &GitCommit) -> XElement {
+    timestamp(display_timestamp(DateTime::from_utc(Duration::from_secs(
+        commit.time,
+    ))))
+}
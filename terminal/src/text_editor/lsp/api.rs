@@ -0,0 +1,132 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use server_fn::Http;
+use server_fn::ServerFnError;
+use server_fn::codec::Json;
+use terrazzo::server;
+
+use crate::api::client_address::ClientAddress;
+use crate::text_editor::file_path::FilePath;
+
+/// The file being edited, and its content in the editor.
+///
+/// The content may not be saved yet, so it is sent to the language server first.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct LspDocument {
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "p"))]
+    pub path: FilePath<Arc<Path>>,
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "c"))]
+    pub content: Arc<str>,
+}
+
+/// A position in a document, as the LSP counts them: 0-based lines, and UTF-16 code units.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LspPosition {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LspRange {
+    pub start: LspPosition,
+    pub end: LspPosition,
+}
+
+/// A location in a file, for example the definition of a symbol.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LspLocation {
+    pub path: Arc<Path>,
+    pub range: LspRange,
+
+    /// The start of the range, as a UTF-16 offset in the file on disk.
+    pub offset: u32,
+
+    /// The line of the location, to show in a list of references.
+    pub preview: Arc<str>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LspDiagnostic {
+    pub range: LspRange,
+
+    /// One of the CodeMirror severities: `error`, `warning`, `info` or `hint`.
+    pub severity: Arc<str>,
+    pub message: Arc<str>,
+    pub source: Option<Arc<str>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LspCompletion {
+    pub label: Arc<str>,
+    pub detail: Option<Arc<str>>,
+
+    /// One of the CodeMirror completion types, like `function` or `variable`.
+    pub kind: Option<Arc<str>>,
+    pub insert_text: Arc<str>,
+
+    /// The range the completion replaces, if the language server chose one.
+    pub range: Option<LspRange>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct LspTextEdit {
+    pub range: LspRange,
+    pub new_text: Arc<str>,
+}
+
+/// The diagnostics of the document, or `None` if no language server handles it.
+#[server(protocol = Http<Json, Json>)]
+pub async fn diagnostics(
+    remote: ClientAddress,
+    document: LspDocument,
+) -> Result<Option<Vec<LspDiagnostic>>, ServerFnError> {
+    super::service::diagnostics(remote, document).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn hover(
+    remote: ClientAddress,
+    document: LspDocument,
+    position: LspPosition,
+) -> Result<Option<Arc<str>>, ServerFnError> {
+    super::service::hover(remote, document, position).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn completion(
+    remote: ClientAddress,
+    document: LspDocument,
+    position: LspPosition,
+) -> Result<Vec<LspCompletion>, ServerFnError> {
+    super::service::completion(remote, document, position).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn definition(
+    remote: ClientAddress,
+    document: LspDocument,
+    position: LspPosition,
+) -> Result<Vec<LspLocation>, ServerFnError> {
+    super::service::definition(remote, document, position).await
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn references(
+    remote: ClientAddress,
+    document: LspDocument,
+    position: LspPosition,
+) -> Result<Vec<LspLocation>, ServerFnError> {
+    super::service::references(remote, document, position).await
+}
+
+/// Renames the symbol: other files are edited on disk, and the edits of the document are returned.
+#[server(protocol = Http<Json, Json>)]
+pub async fn rename(
+    remote: ClientAddress,
+    document: LspDocument,
+    position: LspPosition,
+    new_name: String,
+) -> Result<Vec<LspTextEdit>, ServerFnError> {
+    super::service::rename(remote, document, position, new_name).await
+}
@@ -0,0 +1,479 @@
+//! A client of a language server, talking JSON-RPC over the stdin and stdout of its process.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::SeqCst;
+use std::time::Duration;
+
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+use serde_json::Value;
+use serde_json::json;
+use tokio::io::AsyncBufRead;
+use tokio::io::AsyncBufReadExt as _;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt as _;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt as _;
+use tokio::io::BufReader;
+use tokio::process::Command;
+use tokio::sync::oneshot;
+use tokio::sync::watch;
+use tonic::Code;
+use tracing::Instrument as _;
+use tracing::debug;
+use tracing::info_span;
+use tracing::trace;
+use tracing::warn;
+
+use crate::backend::client_service::grpc_error::IsGrpcError;
+use crate::backend::config::language_server::LanguageServer;
+use crate::text_editor::fsio::service::FsioError;
+
+/// How long to wait for the language server to answer a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct LspClient {
+    language: String,
+    root: PathBuf,
+    writer: tokio::sync::Mutex<Pin<Box<dyn AsyncWrite + Send>>>,
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<u64, oneshot::Sender<Result<Value, LspError>>>>,
+    alive: AtomicBool,
+
+    /// The version and content of the documents sent to the language server, by URI.
+    documents: tokio::sync::Mutex<HashMap<String, (i32, Arc<str>)>>,
+
+    /// The last diagnostics published for each URI, and how many times they were published.
+    diagnostics: Mutex<HashMap<String, (u64, Arc<Vec<Value>>)>>,
+
+    /// Notified when diagnostics are published, to wait for the next ones.
+    published: watch::Sender<()>,
+}
+
+impl LspClient {
+    /// Starts the language server in the workspace `root`, and initializes it.
+    pub async fn start(
+        language: &str,
+        config: &LanguageServer,
+        root: &Path,
+    ) -> Result<Arc<Self>, LspError> {
+        let span = info_span!("LSP", language, ?root);
+        async move {
+            debug!("Starting {} {:?}", config.command, config.args);
+            let mut child = Command::new(&config.command)
+                .args(&config.args)
+                .current_dir(root)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .kill_on_drop(true)
+                .spawn()
+                .map_err(|error| LspError::Spawn {
+                    command: config.command.clone(),
+                    error,
+                })?;
+            let stdin = child.stdin.take().ok_or(LspError::MissingPipe)?;
+            let stdout = child.stdout.take().ok_or(LspError::MissingPipe)?;
+            Self::connect(language, root, stdout, stdin, child).await
+        }
+        .instrument(span)
+        .await
+    }
+
+    /// Initializes a language server that reads from `writer` and writes to `reader`.
+    ///
+    /// The `guard` lives until the language server closes its output.
+    pub async fn connect(
+        language: &str,
+        root: &Path,
+        reader: impl AsyncRead + Send + Unpin + 'static,
+        writer: impl AsyncWrite + Send + Unpin + 'static,
+        guard: impl Send + 'static,
+    ) -> Result<Arc<Self>, LspError> {
+        let client = Arc::new(Self {
+            language: language.to_owned(),
+            root: root.to_owned(),
+            writer: tokio::sync::Mutex::new(Box::pin(writer)),
+            next_id: AtomicU64::new(1),
+            pending: Mutex::default(),
+            alive: AtomicBool::new(true),
+            documents: Default::default(),
+            diagnostics: Mutex::default(),
+            published: watch::Sender::new(()),
+        });
+        let weak = Arc::downgrade(&client);
+        tokio::spawn(
+            async move {
+                read_loop(BufReader::new(reader), weak).await;
+                // Closing stdin makes the language server exit, and then the process is reaped.
+                drop(guard);
+            }
+            .in_current_span(),
+        );
+        client.initialize().await?;
+        Ok(client)
+    }
+
+    async fn initialize(&self) -> Result<(), LspError> {
+        let root_uri = path_to_uri(&self.root);
+        let name = self
+            .root
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let params = json!({
+            "processId": std::process::id(),
+            "rootUri": root_uri,
+            "rootPath": self.root,
+            "workspaceFolders": [{ "uri": root_uri, "name": name }],
+            "capabilities": {
+                "textDocument": {
+                    "synchronization": { "dynamicRegistration": false },
+                    "hover": { "contentFormat": ["plaintext", "markdown"] },
+                    "completion": { "completionItem": { "snippetSupport": false } },
+                    "definition": { "linkSupport": true },
+                    "references": {},
+                    "rename": { "prepareSupport": false },
+                    "publishDiagnostics": { "relatedInformation": false },
+                },
+                "workspace": {
+                    "workspaceFolders": true,
+                    "configuration": true,
+                    "workspaceEdit": { "documentChanges": true },
+                },
+            },
+        });
+        self.request("initialize", params).await?;
+        self.notify("initialized", json!({})).await
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.alive.load(SeqCst)
+    }
+
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, LspError> {
+        let id = self.next_id.fetch_add(1, SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if let Err(error) = self.send(&message).await {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(error);
+        }
+        match tokio::time::timeout(REQUEST_TIMEOUT, rx).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => Err(LspError::Exited),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                Err(LspError::Timeout {
+                    method: method.to_owned(),
+                })
+            }
+        }
+    }
+
+    pub async fn notify(&self, method: &str, params: Value) -> Result<(), LspError> {
+        self.send(&json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+            .await
+    }
+
+    async fn send(&self, message: &Value) -> Result<(), LspError> {
+        if !self.is_alive() {
+            return Err(LspError::Exited);
+        }
+        trace!("Send {message}");
+        let mut writer = self.writer.lock().await;
+        write_message(&mut *writer, message).await?;
+        Ok(())
+    }
+
+    /// Opens the document, or sends its new content if it changed.
+    ///
+    /// Returns whether the language server got new content.
+    pub async fn sync(&self, path: &Path, content: &Arc<str>) -> Result<bool, LspError> {
+        let uri = path_to_uri(path);
+        let mut documents = self.documents.lock().await;
+        match documents.get_mut(&uri) {
+            None => {
+                let params = json!({
+                    "textDocument": {
+                        "uri": uri,
+                        "languageId": self.language,
+                        "version": 1,
+                        "text": content.as_ref(),
+                    },
+                });
+                self.notify("textDocument/didOpen", params).await?;
+                documents.insert(uri, (1, content.clone()));
+            }
+            Some((_, synced)) if synced == content => return Ok(false),
+            Some((version, synced)) => {
+                *version += 1;
+                *synced = content.clone();
+                let params = json!({
+                    "textDocument": { "uri": uri, "version": *version },
+                    "contentChanges": [{ "text": content.as_ref() }],
+                });
+                self.notify("textDocument/didChange", params).await?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Syncs the document and returns its diagnostics.
+    ///
+    /// If the content changed, waits a bit for the language server to publish new ones.
+    pub async fn diagnostics(
+        &self,
+        path: &Path,
+        content: &Arc<str>,
+        wait: Duration,
+    ) -> Result<Arc<Vec<Value>>, LspError> {
+        let uri = path_to_uri(path);
+        let current = || self.diagnostics.lock().unwrap().get(&uri).cloned();
+        let mut published = self.published.subscribe();
+        let generation = current().map(|(generation, _)| generation);
+        let changed = self.sync(path, content).await?;
+        if !changed && let Some((_, diagnostics)) = current() {
+            return Ok(diagnostics);
+        }
+        let _ = tokio::time::timeout(wait, async {
+            while published.changed().await.is_ok() && self.is_alive() {
+                if current().map(|(generation, _)| generation) != generation {
+                    return;
+                }
+            }
+        })
+        .await;
+        Ok(current()
+            .map(|(_, diagnostics)| diagnostics)
+            .unwrap_or_default())
+    }
+
+    fn on_message(&self, message: Value) -> Option<Value> {
+        let method = message.get("method").and_then(Value::as_str);
+        let id = message.get("id").cloned();
+        match (method, id) {
+            (None, Some(id)) => {
+                let id = id.as_u64()?;
+                let Some(pending) = self.pending.lock().unwrap().remove(&id) else {
+                    debug!("Unexpected response {id}");
+                    return None;
+                };
+                let response = match message.get("error") {
+                    Some(error) => Err(LspError::Response {
+                        code: error
+                            .get("code")
+                            .and_then(Value::as_i64)
+                            .unwrap_or_default(),
+                        message: error
+                            .get("message")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_owned(),
+                    }),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = pending.send(response);
+                None
+            }
+            (Some(method), Some(id)) => {
+                // Requests from the server: only answer what is needed to keep it going.
+                let result = match method {
+                    "workspace/configuration" => {
+                        let items = message["params"]["items"].as_array().map(Vec::len);
+                        Value::Array(vec![Value::Null; items.unwrap_or_default()])
+                    }
+                    _ => Value::Null,
+                };
+                Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+            }
+            (Some("textDocument/publishDiagnostics"), None) => {
+                let params = &message["params"];
+                if let Some(uri) = params["uri"].as_str() {
+                    let diagnostics = params["diagnostics"].as_array().cloned();
+                    let diagnostics = Arc::new(diagnostics.unwrap_or_default());
+                    let mut all = self.diagnostics.lock().unwrap();
+                    let generation = all.get(uri).map(|(generation, _)| generation + 1);
+                    all.insert(uri.to_owned(), (generation.unwrap_or(1), diagnostics));
+                    drop(all);
+                    self.published.send_replace(());
+                }
+                None
+            }
+            (Some(method), None) => {
+                trace!("Ignored notification {method}");
+                None
+            }
+            (None, None) => {
+                debug!("Invalid message {message}");
+                None
+            }
+        }
+    }
+
+    fn on_exit(&self) {
+        self.alive.store(false, SeqCst);
+        for (_, pending) in self.pending.lock().unwrap().drain() {
+            let _ = pending.send(Err(LspError::Exited));
+        }
+        self.published.send_replace(());
+    }
+}
+
+async fn read_loop(mut reader: impl AsyncBufRead + Unpin, client: Weak<LspClient>) {
+    loop {
+        let message = match read_message(&mut reader).await {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(error) => {
+                warn!("Failed to read from the language server: {error}");
+                break;
+            }
+        };
+        trace!("Received {message}");
+        let Some(client) = client.upgrade() else {
+            return;
+        };
+        if let Some(response) = client.on_message(message)
+            && let Err(error) = client.send(&response).await
+        {
+            warn!("Failed to answer the language server: {error}");
+        }
+    }
+    debug!("The language server exited");
+    if let Some(client) = client.upgrade() {
+        client.on_exit();
+    }
+}
+
+/// Reads a message framed with a `Content-Length` header, or `None` at the end of the stream.
+pub async fn read_message(
+    reader: &mut (impl AsyncBufRead + Unpin),
+) -> std::io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("Content-Length")
+        {
+            content_length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let Some(content_length) = content_length else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Missing Content-Length header",
+        ));
+    };
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content).await?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+pub async fn write_message(
+    writer: &mut (impl AsyncWrite + Unpin),
+    message: &Value,
+) -> std::io::Result<()> {
+    let content = serde_json::to_vec(message)?;
+    let header = format!("Content-Length: {}\r\n\r\n", content.len());
+    writer.write_all(header.as_bytes()).await?;
+    writer.write_all(&content).await?;
+    writer.flush().await
+}
+
+/// The `file://` URI of an absolute path.
+pub fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    uri
+}
+
+/// The path of a `file://` URI.
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%'
+            && let Some(hex) = tail.get(..2)
+            && let Ok(decoded) = u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16)
+        {
+            bytes.push(decoded);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    Some(PathBuf::from(String::from_utf8(bytes).ok()?))
+}
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+pub enum LspError {
+    #[error("[{n}] {0}", n = self.name())]
+    IO(#[from] std::io::Error),
+
+    #[error("[{n}] Failed to start {command:?}: {error}", n = self.name())]
+    Spawn {
+        command: String,
+        error: std::io::Error,
+    },
+
+    #[error("[{n}] The language server has no stdin or stdout", n = self.name())]
+    MissingPipe,
+
+    #[error("[{n}] The language server exited", n = self.name())]
+    Exited,
+
+    #[error("[{n}] The language server didn't answer {method}", n = self.name())]
+    Timeout { method: String },
+
+    #[error("[{n}] The language server failed with {code}: {message}", n = self.name())]
+    Response { code: i64, message: String },
+
+    #[error("[{n}] {0}", n = self.name())]
+    Start(Arc<LspError>),
+
+    #[error("[{n}] {0}", n = self.name())]
+    Fsio(#[from] FsioError),
+}
+
+impl IsGrpcError for LspError {
+    fn code(&self) -> Code {
+        match self {
+            Self::IO { .. } => Code::Internal,
+            Self::Spawn { .. } => Code::FailedPrecondition,
+            Self::MissingPipe => Code::Internal,
+            Self::Exited => Code::Unavailable,
+            Self::Timeout { .. } => Code::DeadlineExceeded,
+            Self::Response { .. } => Code::Internal,
+            Self::Start(error) => error.code(),
+            Self::Fsio(error) => error.code(),
+        }
+    }
+}
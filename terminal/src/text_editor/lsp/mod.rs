@@ -0,0 +1,10 @@
+//! Proxies hover, go-to-definition, completion, references, rename and diagnostics
+//! from the editor to the configured language servers.
+
+pub mod api;
+#[cfg(feature = "server")]
+mod client;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "client")]
+pub mod ui;
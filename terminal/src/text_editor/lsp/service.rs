@@ -0,0 +1,772 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use futures::FutureExt as _;
+use futures::future::BoxFuture;
+use futures::future::Shared;
+use serde_json::Value;
+use serde_json::json;
+use server_fn::ServerFnError;
+use tracing::debug;
+
+use super::api::LspCompletion;
+use super::api::LspDiagnostic;
+use super::api::LspDocument;
+use super::api::LspLocation;
+use super::api::LspPosition;
+use super::api::LspRange;
+use super::api::LspTextEdit;
+use super::client::LspClient;
+use super::client::LspError;
+use super::client::path_to_uri;
+use super::client::uri_to_path;
+use crate::api::client_address::ClientAddress;
+use crate::backend::Server;
+use crate::backend::client_service::grpc_error::GrpcError;
+use crate::backend::client_service::remote_fn_service;
+use crate::backend::config::language_server::LanguageServer;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio::FileMetadata;
+use crate::text_editor::fsio::ROOT_BASE_PATH;
+use crate::text_editor::fsio::git::git_repo_root;
+use crate::text_editor::fsio::service::store_file;
+
+/// How long to wait for the language server to publish diagnostics after a change.
+const DIAGNOSTICS_WAIT: Duration = Duration::from_secs(2);
+
+/// How many completions are sent to the editor.
+const MAX_COMPLETIONS: usize = 200;
+
+type SharedClientFuture = Shared<BoxFuture<'static, Result<Arc<LspClient>, Arc<LspError>>>>;
+
+/// The running language servers, by workspace root and language.
+///
+/// Servers start in a shared future, so other workspaces don't wait while one is starting.
+static CLIENTS: LazyLock<Mutex<HashMap<(PathBuf, String), SharedClientFuture>>> =
+    LazyLock::new(Default::default);
+
+pub async fn diagnostics(
+    remote: ClientAddress,
+    document: LspDocument,
+) -> Result<Option<Vec<LspDiagnostic>>, ServerFnError> {
+    debug!(%remote, "Calling diagnostics({:?})", document.path);
+    Ok(DIAGNOSTICS_FN.call(remote, document).await?)
+}
+
+pub async fn hover(
+    remote: ClientAddress,
+    document: LspDocument,
+    position: LspPosition,
+) -> Result<Option<Arc<str>>, ServerFnError> {
+    debug!(%remote, "Calling hover({:?}, {position:?})", document.path);
+    Ok(HOVER_FN.call(remote, (document, position)).await?)
+}
+
+pub async fn completion(
+    remote: ClientAddress,
+    document: LspDocument,
+    position: LspPosition,
+) -> Result<Vec<LspCompletion>, ServerFnError> {
+    debug!(%remote, "Calling completion({:?}, {position:?})", document.path);
+    Ok(COMPLETION_FN.call(remote, (document, position)).await?)
+}
+
+pub async fn definition(
+    remote: ClientAddress,
+    document: LspDocument,
+    position: LspPosition,
+) -> Result<Vec<LspLocation>, ServerFnError> {
+    debug!(%remote, "Calling definition({:?}, {position:?})", document.path);
+    Ok(DEFINITION_FN.call(remote, (document, position)).await?)
+}
+
+pub async fn references(
+    remote: ClientAddress,
+    document: LspDocument,
+    position: LspPosition,
+) -> Result<Vec<LspLocation>, ServerFnError> {
+    debug!(%remote, "Calling references({:?}, {position:?})", document.path);
+    Ok(REFERENCES_FN.call(remote, (document, position)).await?)
+}
+
+pub async fn rename(
+    remote: ClientAddress,
+    document: LspDocument,
+    position: LspPosition,
+    new_name: String,
+) -> Result<Vec<LspTextEdit>, ServerFnError> {
+    debug!(%remote, "Calling rename({:?}, {position:?}, {new_name:?})", document.path);
+    Ok(RENAME_FN
+        .call(remote, (document, position, new_name))
+        .await?)
+}
+
+fn language_servers(server: &Server) -> BTreeMap<String, LanguageServer> {
+    let language_servers = server
+        .config()
+        .server
+        .with(|server| server.language_servers.clone());
+    if language_servers.is_empty() {
+        LanguageServer::defaults()
+    } else {
+        language_servers
+    }
+}
+
+remote_fn_service::unary::declare_remote_fn!(
+    DIAGNOSTICS_FN,
+    "texteditor.lsp.diagnostics",
+    LspDocument,
+    Option<Vec<LspDiagnostic>>,
+    |server, document| {
+        let servers = language_servers(server);
+        async move {
+            let client = match find_client(&servers, &document).await {
+                Ok(Some(client)) => client,
+                Ok(None) => return Ok(None),
+                Err(LspError::Spawn { command, error }) => {
+                    debug!("The language server {command:?} is not available: {error}");
+                    return Ok(None);
+                }
+                Err(error) => return Err(GrpcError::from(error)),
+            };
+            let diagnostics = diagnostics_impl(&client, document).await;
+            diagnostics.map(Some).map_err(GrpcError::from)
+        }
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    HOVER_FN,
+    "texteditor.lsp.hover",
+    (LspDocument, LspPosition),
+    Option<Arc<str>>,
+    |server, (document, position)| {
+        let servers = language_servers(server);
+        async move {
+            let Some(client) = find_client(&servers, &document).await? else {
+                return Ok(None);
+            };
+            let hover = hover_impl(&client, document, position).await;
+            hover.map_err(GrpcError::from)
+        }
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    COMPLETION_FN,
+    "texteditor.lsp.completion",
+    (LspDocument, LspPosition),
+    Vec<LspCompletion>,
+    |server, (document, position)| {
+        let servers = language_servers(server);
+        async move {
+            let Some(client) = find_client(&servers, &document).await? else {
+                return Ok(vec![]);
+            };
+            let completion = completion_impl(&client, document, position).await;
+            completion.map_err(GrpcError::from)
+        }
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    DEFINITION_FN,
+    "texteditor.lsp.definition",
+    (LspDocument, LspPosition),
+    Vec<LspLocation>,
+    |server, (document, position)| {
+        let servers = language_servers(server);
+        async move {
+            let Some(client) = find_client(&servers, &document).await? else {
+                return Ok(vec![]);
+            };
+            let definition = locations_impl(&client, document, position, "definition").await;
+            definition.map_err(GrpcError::from)
+        }
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    REFERENCES_FN,
+    "texteditor.lsp.references",
+    (LspDocument, LspPosition),
+    Vec<LspLocation>,
+    |server, (document, position)| {
+        let servers = language_servers(server);
+        async move {
+            let Some(client) = find_client(&servers, &document).await? else {
+                return Ok(vec![]);
+            };
+            let references = locations_impl(&client, document, position, "references").await;
+            references.map_err(GrpcError::from)
+        }
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    RENAME_FN,
+    "texteditor.lsp.rename",
+    (LspDocument, LspPosition, String),
+    Vec<LspTextEdit>,
+    |server, (document, position, new_name)| {
+        let servers = language_servers(server);
+        async move {
+            let Some(client) = find_client(&servers, &document).await? else {
+                return Ok(vec![]);
+            };
+            let rename = rename_impl(&client, document, position, new_name).await;
+            rename.map_err(GrpcError::from)
+        }
+    }
+);
+
+/// The language server of the document, started if needed.
+async fn find_client(
+    servers: &BTreeMap<String, LanguageServer>,
+    document: &LspDocument,
+) -> Result<Option<Arc<LspClient>>, LspError> {
+    let path = document.path.full_path();
+    let Some(extension) = path.extension().and_then(|extension| extension.to_str()) else {
+        return Ok(None);
+    };
+    let Some((language, config)) = servers
+        .iter()
+        .find(|(_, config)| config.extensions.iter().any(|e| e == extension))
+    else {
+        return Ok(None);
+    };
+    let root = workspace_root(&path, &config.root_markers);
+    let key = (root, language.clone());
+    let client = {
+        let mut clients = CLIENTS.lock().unwrap();
+        let running = clients.get(&key).filter(|client| match client.peek() {
+            None => true,
+            Some(Ok(client)) => client.is_alive(),
+            Some(Err(_)) => false,
+        });
+        match running {
+            Some(client) => client.clone(),
+            None => {
+                let (language, config, root) = (language.clone(), config.clone(), key.0.clone());
+                let client = async move {
+                    LspClient::start(&language, &config, &root)
+                        .await
+                        .map_err(Arc::new)
+                }
+                .boxed()
+                .shared();
+                clients.insert(key, client.clone());
+                client
+            }
+        }
+    };
+    Ok(Some(client.await.map_err(LspError::Start)?))
+}
+
+/// The outermost folder with a root marker, like the root of a Cargo workspace.
+///
+/// The search stops at the root of the git repo, which is also the fallback.
+fn workspace_root(path: &Path, root_markers: &[String]) -> PathBuf {
+    let git_root = git_repo_root(path);
+    let mut root = None;
+    for folder in path.ancestors().skip(1) {
+        if root_markers
+            .iter()
+            .any(|marker| folder.join(marker).exists())
+        {
+            root = Some(folder);
+        }
+        if git_root.as_deref() == Some(folder) {
+            break;
+        }
+    }
+    root.or(git_root.as_deref())
+        .or(path.parent())
+        .unwrap_or(path)
+        .to_owned()
+}
+
+/// Sends the content of the document, and returns the `TextDocumentPositionParams`.
+async fn sync(
+    client: &LspClient,
+    document: &LspDocument,
+    position: LspPosition,
+) -> Result<Value, LspError> {
+    let path = document.path.full_path();
+    client.sync(&path, &document.content).await?;
+    Ok(json!({
+        "textDocument": { "uri": path_to_uri(&path) },
+        "position": position,
+    }))
+}
+
+async fn diagnostics_impl(
+    client: &LspClient,
+    document: LspDocument,
+) -> Result<Vec<LspDiagnostic>, LspError> {
+    let path = document.path.full_path();
+    let diagnostics = client
+        .diagnostics(&path, &document.content, DIAGNOSTICS_WAIT)
+        .await?;
+    Ok(diagnostics
+        .iter()
+        .filter_map(|diagnostic| {
+            let severity = match diagnostic["severity"].as_u64() {
+                Some(2) => "warning",
+                Some(3) => "info",
+                Some(4) => "hint",
+                _ => "error",
+            };
+            Some(LspDiagnostic {
+                range: parse_range(&diagnostic["range"])?,
+                severity: severity.into(),
+                message: diagnostic["message"].as_str()?.into(),
+                source: diagnostic["source"].as_str().map(Arc::from),
+            })
+        })
+        .collect())
+}
+
+async fn hover_impl(
+    client: &LspClient,
+    document: LspDocument,
+    position: LspPosition,
+) -> Result<Option<Arc<str>>, LspError> {
+    let params = sync(client, &document, position).await?;
+    let hover = client.request("textDocument/hover", params).await?;
+    let text = hover_text(&hover["contents"]);
+    let text = text.trim();
+    Ok((!text.is_empty()).then(|| text.into()))
+}
+
+/// Flattens `MarkupContent`, `MarkedString` and arrays of `MarkedString`.
+fn hover_text(contents: &Value) -> String {
+    match contents {
+        Value::String(text) => text.clone(),
+        Value::Array(contents) => contents
+            .iter()
+            .map(hover_text)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        Value::Object(contents) => contents
+            .get("value")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned(),
+        _ => String::default(),
+    }
+}
+
+async fn completion_impl(
+    client: &LspClient,
+    document: LspDocument,
+    position: LspPosition,
+) -> Result<Vec<LspCompletion>, LspError> {
+    let params = sync(client, &document, position).await?;
+    let completion = client.request("textDocument/completion", params).await?;
+    let items = match &completion {
+        Value::Array(items) => items,
+        Value::Object(list) => match list.get("items") {
+            Some(Value::Array(items)) => items,
+            _ => return Ok(vec![]),
+        },
+        _ => return Ok(vec![]),
+    };
+    Ok(items
+        .iter()
+        .filter_map(|item| {
+            let label: Arc<str> = item["label"].as_str()?.into();
+            let text_edit = &item["textEdit"];
+            let insert_text = text_edit["newText"]
+                .as_str()
+                .or_else(|| item["insertText"].as_str())
+                .map(Arc::from)
+                .unwrap_or_else(|| label.clone());
+            let range =
+                parse_range(&text_edit["range"]).or_else(|| parse_range(&text_edit["replace"]));
+            Some(LspCompletion {
+                detail: item["detail"].as_str().map(Arc::from),
+                kind: item["kind"]
+                    .as_u64()
+                    .and_then(completion_kind)
+                    .map(Arc::from),
+                insert_text,
+                range,
+                label,
+            })
+        })
+        .take(MAX_COMPLETIONS)
+        .collect())
+}
+
+/// Maps the LSP `CompletionItemKind` to the CodeMirror completion types.
+fn completion_kind(kind: u64) -> Option<&'static str> {
+    Some(match kind {
+        1 => "text",
+        2 => "method",
+        3 | 4 => "function",
+        5 | 10 => "property",
+        6 | 12 | 18 => "variable",
+        7 | 22 => "class",
+        8 => "interface",
+        9 => "namespace",
+        13 | 20 => "enum",
+        14 => "keyword",
+        21 => "constant",
+        25 => "type",
+        _ => return None,
+    })
+}
+
+async fn locations_impl(
+    client: &LspClient,
+    document: LspDocument,
+    position: LspPosition,
+    request: &str,
+) -> Result<Vec<LspLocation>, LspError> {
+    let mut params = sync(client, &document, position).await?;
+    if request == "references" {
+        params["context"] = json!({ "includeDeclaration": true });
+    }
+    let method = format!("textDocument/{request}");
+    let locations = match client.request(&method, params).await? {
+        Value::Array(locations) => locations,
+        Value::Null => vec![],
+        location => vec![location],
+    };
+    let current = document.path.full_path();
+    let mut result = vec![];
+    let mut contents = HashMap::new();
+    for location in &locations {
+        // Either a `Location` or a `LocationLink`.
+        let uri = location["uri"].as_str().or(location["targetUri"].as_str());
+        let range = parse_range(&location["range"])
+            .or_else(|| parse_range(&location["targetSelectionRange"]));
+        let (Some(path), Some(range)) = (uri.and_then(uri_to_path), range) else {
+            continue;
+        };
+        if !contents.contains_key(&path) {
+            let content: Option<Arc<str>> = if path == current {
+                Some(document.content.clone())
+            } else {
+                tokio::fs::read_to_string(&path).await.ok().map(Arc::from)
+            };
+            contents.insert(path.clone(), content);
+        }
+        let content = contents[&path].as_deref().unwrap_or_default();
+        let line = content.lines().nth(range.start.line as usize);
+        result.push(LspLocation {
+            offset: utf16_offset(content, range.start),
+            preview: line.unwrap_or_default().trim().into(),
+            path: path.into(),
+            range,
+        });
+    }
+    Ok(result)
+}
+
+async fn rename_impl(
+    client: &LspClient,
+    document: LspDocument,
+    position: LspPosition,
+    new_name: String,
+) -> Result<Vec<LspTextEdit>, LspError> {
+    let mut params = sync(client, &document, position).await?;
+    params["newName"] = new_name.into();
+    let workspace_edit = client.request("textDocument/rename", params).await?;
+
+    let mut edits_by_uri: Vec<(&str, &Value)> = vec![];
+    if let Some(changes) = workspace_edit["changes"].as_object() {
+        edits_by_uri.extend(changes.iter().map(|(uri, edits)| (uri.as_str(), edits)));
+    }
+    if let Some(document_changes) = workspace_edit["documentChanges"].as_array() {
+        // Only `TextDocumentEdit`, resource operations have a `kind`.
+        edits_by_uri.extend(document_changes.iter().filter_map(|change| {
+            Some((change["textDocument"]["uri"].as_str()?, &change["edits"]))
+        }));
+    }
+
+    let current = document.path.full_path();
+    let mut result = vec![];
+    for (uri, edits) in edits_by_uri {
+        let Some(path) = uri_to_path(uri) else {
+            continue;
+        };
+        let edits = parse_text_edits(edits);
+        if path == current {
+            result.extend(edits);
+            continue;
+        }
+        debug!("Renaming in {path:?}");
+        let expected = FileMetadata::single(&path, &tokio::fs::metadata(&path).await?);
+        let content = tokio::fs::read_to_string(&path).await?;
+        let content: Arc<str> = apply_text_edits(&content, &edits).into();
+        let file = FilePath {
+            base: ROOT_BASE_PATH.clone(),
+            file: Arc::from(path.as_path()),
+        };
+        store_file(file, content.to_string(), Some(expected.into())).await?;
+        client.sync(&path, &content).await?;
+    }
+    Ok(result)
+}
+
+fn parse_text_edits(edits: &Value) -> Vec<LspTextEdit> {
+    let Some(edits) = edits.as_array() else {
+        return vec![];
+    };
+    edits
+        .iter()
+        .filter_map(|edit| {
+            Some(LspTextEdit {
+                range: parse_range(&edit["range"])?,
+                new_text: edit["newText"].as_str()?.into(),
+            })
+        })
+        .collect()
+}
+
+fn parse_range(range: &Value) -> Option<LspRange> {
+    serde_json::from_value(range.clone()).ok()
+}
+
+/// Applies the edits, whose ranges all refer to the original content.
+fn apply_text_edits(content: &str, edits: &[LspTextEdit]) -> String {
+    let mut edits = edits
+        .iter()
+        .map(|edit| {
+            let start = byte_offset(content, edit.range.start);
+            let end = byte_offset(content, edit.range.end).max(start);
+            (start, end, edit.new_text.as_ref())
+        })
+        .collect::<Vec<_>>();
+    edits.sort_by_key(|(start, end, _)| std::cmp::Reverse((*start, *end)));
+    let mut content = content.to_owned();
+    for (start, end, new_text) in edits {
+        content.replace_range(start..end, new_text);
+    }
+    content
+}
+
+/// The byte offset of a position, clamped to the end of its line.
+fn byte_offset(content: &str, position: LspPosition) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match content[line_start..].find('\n') {
+            Some(end) => line_start += end + 1,
+            None => return content.len(),
+        }
+    }
+    let line = &content[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    let mut utf16 = 0;
+    for (offset, char) in line.char_indices() {
+        if utf16 >= position.character as usize {
+            return line_start + offset;
+        }
+        utf16 += char.len_utf16();
+    }
+    line_start + line.len()
+}
+
+/// The UTF-16 offset of a position, as CodeMirror counts them.
+fn utf16_offset(content: &str, position: LspPosition) -> u32 {
+    let offset = byte_offset(content, position);
+    content[..offset].encode_utf16().count() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use serde_json::json;
+    use tokio::io::BufReader;
+    use tokio::io::DuplexStream;
+
+    use super::LspClient;
+    use super::LspDocument;
+    use super::LspPosition;
+    use super::LspRange;
+    use super::LspTextEdit;
+    use super::path_to_uri;
+    use crate::text_editor::file_path::FilePath;
+    use crate::text_editor::lsp::client::read_message;
+    use crate::text_editor::lsp::client::write_message;
+
+    #[tokio::test]
+    async fn fake_language_server() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        let main = root.join("main.rs");
+        let lib = root.join("lib.rs");
+        std::fs::write(&main, "fn main() {\n    foo();\n}\n").unwrap();
+        std::fs::write(&lib, "pub fn foo() {}\n").unwrap();
+
+        let (client_write, server_read) = tokio::io::duplex(64 * 1024);
+        let (server_write, client_read) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(fake_server(server_read, server_write, path_to_uri(&lib)));
+        let client = LspClient::connect("rust", root, client_read, client_write, ())
+            .await
+            .unwrap();
+
+        let document = |content: &str| LspDocument {
+            path: FilePath {
+                base: Arc::from(root),
+                file: Arc::from(Path::new("main.rs")),
+            },
+            content: content.into(),
+        };
+        let position = LspPosition {
+            line: 1,
+            character: 5,
+        };
+
+        let diagnostics = super::diagnostics_impl(&client, document("fn main() {}"))
+            .await
+            .unwrap();
+        assert_eq!(1, diagnostics.len());
+        assert_eq!("warning", diagnostics[0].severity.as_ref());
+        assert_eq!("12 chars", diagnostics[0].message.as_ref());
+
+        let content = "fn main() {\n    foo();\n}\n";
+        let hover = super::hover_impl(&client, document(content), position)
+            .await
+            .unwrap();
+        assert_eq!(Some("fn foo()\n\nDoes foo"), hover.as_deref());
+
+        let diagnostics = super::diagnostics_impl(&client, document(content))
+            .await
+            .unwrap();
+        assert_eq!("25 chars", diagnostics[0].message.as_ref());
+
+        let completions = super::completion_impl(&client, document(content), position)
+            .await
+            .unwrap();
+        assert_eq!(1, completions.len());
+        assert_eq!("foo", completions[0].label.as_ref());
+        assert_eq!("foo()", completions[0].insert_text.as_ref());
+        assert_eq!(Some("function"), completions[0].kind.as_deref());
+
+        let definition = super::locations_impl(&client, document(content), position, "definition")
+            .await
+            .unwrap();
+        assert_eq!(1, definition.len());
+        assert_eq!(lib.as_path(), definition[0].path.as_ref());
+        assert_eq!(7, definition[0].offset);
+        assert_eq!("pub fn foo() {}", definition[0].preview.as_ref());
+
+        let edits = super::rename_impl(&client, document(content), position, "bar".into())
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![LspTextEdit {
+                range: range((1, 4), (1, 7)),
+                new_text: "bar".into()
+            }],
+            edits
+        );
+        assert_eq!("pub fn bar() {}\n", std::fs::read_to_string(&lib).unwrap());
+
+        drop(client);
+        tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    /// Answers a few requests like a language server would.
+    async fn fake_server(read: DuplexStream, mut write: DuplexStream, lib_uri: String) {
+        let mut read = BufReader::new(read);
+        while let Some(message) = read_message(&mut read).await.unwrap() {
+            let params = &message["params"];
+            let uri = params["textDocument"]["uri"].clone();
+            let result = match message["method"].as_str().unwrap_or_default() {
+                "initialize" => json!({ "capabilities": {} }),
+                "textDocument/didOpen" | "textDocument/didChange" => {
+                    let text = params["textDocument"]["text"]
+                        .as_str()
+                        .or(params["contentChanges"][0]["text"].as_str())
+                        .unwrap();
+                    let diagnostics = json!({
+                        "uri": uri,
+                        "diagnostics": [{
+                            "range": range((0, 0), (0, 2)),
+                            "severity": 2,
+                            "message": format!("{} chars", text.len()),
+                        }],
+                    });
+                    let notification = json!({
+                        "jsonrpc": "2.0",
+                        "method": "textDocument/publishDiagnostics",
+                        "params": diagnostics,
+                    });
+                    write_message(&mut write, &notification).await.unwrap();
+                    continue;
+                }
+                "textDocument/hover" => json!({
+                    "contents": [{ "language": "rust", "value": "fn foo()" }, "Does foo"],
+                }),
+                "textDocument/completion" => json!({
+                    "isIncomplete": false,
+                    "items": [{ "label": "foo", "kind": 3, "insertText": "foo()" }],
+                }),
+                "textDocument/definition" => json!([{
+                    "targetUri": lib_uri,
+                    "targetRange": range((0, 0), (0, 15)),
+                    "targetSelectionRange": range((0, 7), (0, 10)),
+                }]),
+                "textDocument/rename" => json!({
+                    "changes": {
+                        uri.as_str().unwrap(): [
+                            { "range": range((1, 4), (1, 7)), "newText": "bar" }
+                        ],
+                        lib_uri.as_str(): [{ "range": range((0, 7), (0, 10)), "newText": "bar" }],
+                    },
+                }),
+                _ => continue,
+            };
+            let response = json!({ "jsonrpc": "2.0", "id": message["id"], "result": result });
+            write_message(&mut write, &response).await.unwrap();
+        }
+    }
+
+    fn range((start_line, start): (u32, u32), (end_line, end): (u32, u32)) -> LspRange {
+        LspRange {
+            start: LspPosition {
+                line: start_line,
+                character: start,
+            },
+            end: LspPosition {
+                line: end_line,
+                character: end,
+            },
+        }
+    }
+
+    #[test]
+    fn apply_text_edits() {
+        let edit = |start, end, new_text: &str| LspTextEdit {
+            range: range(start, end),
+            new_text: new_text.into(),
+        };
+        let content = "let é = 1;\nlet b = é;\n";
+        let edits = [edit((0, 4), (0, 5), "a"), edit((1, 8), (1, 9), "a")];
+        assert_eq!(
+            "let a = 1;\nlet b = a;\n",
+            super::apply_text_edits(content, &edits)
+        );
+        assert_eq!(
+            "x\n",
+            super::apply_text_edits("\n", &[edit((0, 0), (0, 99), "x")])
+        );
+    }
+}
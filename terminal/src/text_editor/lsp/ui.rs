@@ -0,0 +1,191 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use server_fn::ServerFnError;
+use terrazzo::html;
+use terrazzo::prelude::*;
+use terrazzo::template;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::MouseEvent;
+
+use self::diagnostics::debug;
+use self::diagnostics::warn;
+use super::api::LspDocument;
+use super::api::LspLocation;
+use super::api::LspPosition;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::manager::TextEditorManager;
+
+terrazzo_css::import_style!(lsp_style, "lsp.scss");
+
+/// The editor of the file, as the language server features need it.
+pub trait LspView: 'static {
+    /// Resolves the pending request `id` of the editor.
+    fn respond(&self, id: u32, result: JsValue);
+
+    /// Moves the cursor to the UTF-16 offset.
+    fn set_cursor(&self, offset: u32);
+}
+
+/// A request from the editor, see `CodeMirrorJsImpl.lsp()`.
+#[derive(serde::Deserialize)]
+struct LspRequest {
+    id: u32,
+    method: String,
+    line: u32,
+    character: u32,
+    content: String,
+    new_name: Option<String>,
+}
+
+/// Forwards the requests of an editor to the language server of the file.
+#[derive(Clone)]
+pub struct LspEditor {
+    manager: Ptr<TextEditorManager>,
+    path: FilePath<Arc<Path>>,
+    view: Ptr<dyn LspView>,
+    references: XSignal<Option<Arc<Vec<LspLocation>>>>,
+}
+
+impl LspEditor {
+    pub fn new(
+        manager: &Ptr<TextEditorManager>,
+        path: &FilePath<Arc<Path>>,
+        view: impl LspView,
+    ) -> Self {
+        Self {
+            manager: manager.clone(),
+            path: path.clone(),
+            view: Ptr::new(view),
+            references: XSignal::new("lsp-references", None),
+        }
+    }
+
+    /// The callback of the editor for language server requests.
+    pub fn on_lsp(&self) -> Closure<dyn FnMut(JsValue)> {
+        let this = self.clone();
+        Closure::new(move |request: JsValue| {
+            let request: LspRequest = match serde_wasm_bindgen::from_value(request) {
+                Ok(request) => request,
+                Err(error) => return warn!("Invalid language server request: {error}"),
+            };
+            let this = this.clone();
+            spawn_local(async move {
+                let id = request.id;
+                let result = this.handle(request).await.unwrap_or_else(|error| {
+                    warn!("Language server request failed: {error}");
+                    JsValue::null()
+                });
+                this.view.respond(id, result);
+            });
+        })
+    }
+
+    async fn handle(&self, request: LspRequest) -> Result<JsValue, ServerFnError> {
+        let remote = self.manager.remote.clone();
+        let document = LspDocument {
+            path: self.path.clone(),
+            content: request.content.into(),
+        };
+        let position = LspPosition {
+            line: request.line,
+            character: request.character,
+        };
+        debug!("Language server request {} at {position:?}", request.method);
+        Ok(match request.method.as_str() {
+            "diagnostics" => to_js(&super::api::diagnostics(remote, document).await?),
+            "hover" => to_js(&super::api::hover(remote, document, position).await?),
+            "completion" => to_js(&super::api::completion(remote, document, position).await?),
+            "definition" => {
+                let definition = super::api::definition(remote, document, position).await?;
+                if let Some(location) = definition.first() {
+                    self.go_to(location);
+                }
+                JsValue::null()
+            }
+            "references" => {
+                let references = super::api::references(remote, document, position).await?;
+                self.references.set(Some(Arc::new(references)));
+                JsValue::null()
+            }
+            "rename" => {
+                let new_name = request.new_name.unwrap_or_default();
+                to_js(&super::api::rename(remote, document, position, new_name).await?)
+            }
+            method => {
+                warn!("Unknown language server request {method}");
+                JsValue::null()
+            }
+        })
+    }
+
+    /// Moves the cursor to the location, opening its file if needed.
+    fn go_to(&self, location: &LspLocation) {
+        if *location.path == self.path.full_path() {
+            self.view.set_cursor(location.offset);
+            return;
+        }
+        self.manager.open_file_at(&location.path, location.offset);
+    }
+
+    /// The list of references found by the last request.
+    pub fn references(&self) -> XElement {
+        references_list(self.clone(), self.references.clone())
+    }
+}
+
+fn to_js<T: serde::Serialize>(value: &T) -> JsValue {
+    serde_wasm_bindgen::to_value(value).unwrap_or_else(|error| {
+        warn!("Failed to convert the language server response: {error}");
+        JsValue::null()
+    })
+}
+
+#[html]
+#[template(tag = div)]
+fn references_list(
+    lsp: LspEditor,
+    #[signal] references: Option<Arc<Vec<LspLocation>>>,
+) -> XElement {
+    let Some(references) = references else {
+        return tag(style::display = "none", style::visibility = "hidden");
+    };
+    let count = references.len();
+    let close = {
+        let lsp = lsp.clone();
+        move |_: MouseEvent| lsp.references.set(None)
+    };
+    let full_path = lsp.path.full_path();
+    let rows = references.iter().map(|location| {
+        let lsp = lsp.clone();
+        let line = location.range.start.line + 1;
+        let place = if *location.path == full_path {
+            format!("Line {line}")
+        } else {
+            lsp.path.with_base_path(|base| {
+                let file = location.path.strip_prefix(base).unwrap_or(&location.path);
+                format!("{}:{line}", file.display())
+            })
+        };
+        let preview = location.preview.clone();
+        let location = location.clone();
+        div(
+            class = lsp_style::LSP_REFERENCE,
+            click = move |_| lsp.go_to(&location),
+            span(class = lsp_style::LSP_REFERENCE_PATH, "{place}"),
+            span(class = lsp_style::LSP_REFERENCE_PREVIEW, "{preview}"),
+        )
+    });
+    tag(
+        class = lsp_style::LSP_REFERENCES,
+        #[cfg(not(feature = "client-prod"))]
+        class = "lsp-references",
+        div(
+            class = lsp_style::LSP_REFERENCES_HEADER,
+            span("{count} references"),
+            button(click = close, "Close"),
+        ),
+        rows..,
+    )
+}
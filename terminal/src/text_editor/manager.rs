@@ -4,14 +4,25 @@ use std::path::Path;
 use std::sync::Arc;
 
 use terrazzo::prelude::*;
+use wasm_bindgen_futures::spawn_local;
 
+use self::diagnostics::warn;
 use super::file_path::FilePath;
 use super::fsio;
+use super::git::state::EditorGitState;
 use super::notify::ui::NotifyService;
+use super::problems::state::EditorProblemsState;
+use super::search::state::EditorReplaceState;
 use super::search::state::EditorSearchState;
 use super::search::state::SearchState;
 use super::side::SideViewNode;
+use super::side::ui::SideViewMenu;
 use super::synchronized_state::SynchronizedState;
+use super::tail::state::EditorTailState;
+use super::tasks::state::EditorTasksState;
+use super::trash::state::EditorTrashState;
+use super::ui::properties::PropertiesPanel;
+use super::ui::upload::Upload;
 use crate::frontend::mousemove::MousemoveManager;
 use crate::frontend::remotes::Remote;
 use crate::tiles::signals::TilePtr;
@@ -23,22 +34,61 @@ pub(super) struct TextEditorManager {
     pub force_edit_path: XSignal<bool>,
     pub editor_state: XSignal<EditorState>,
     pub show_editor_diff: XSignal<bool>,
+    pub inline_diff: XSignal<bool>,
     pub show_html_preview: XSignal<PreviewMode>,
     pub synchronized_state: XSignal<SynchronizedState>,
     pub side_view: XSignal<Option<Arc<SideViewNode>>>,
+    pub side_view_menu: XSignal<Option<Arc<SideViewMenu>>>,
     pub notify_service: Ptr<NotifyService>,
     pub search: Ptr<SearchState>,
     pub side_view_resize_manager: MousemoveManager,
+    pub uploads: XSignal<Vec<Upload>>,
+    pub properties: XSignal<Option<PropertiesPanel>>,
 }
 
 #[derive(Clone, Debug, Default)]
 pub(super) enum EditorState {
     Data(EditorDataState),
     Search(EditorSearchState),
+    Replace(EditorReplaceState),
+    Git(EditorGitState),
+    Trash(EditorTrashState),
+    Problems(EditorProblemsState),
+    Tasks(EditorTasksState),
+    Tail(EditorTailState),
     #[default]
     Empty,
 }
 
+impl TextEditorManager {
+    /// Opens the file with the cursor at the UTF-16 offset.
+    pub(super) fn open_file_at(self: &Ptr<Self>, full_path: &Path, offset: u32) {
+        let base = self.path.base.get_value_untracked();
+        let root = FilePath {
+            base: base.clone(),
+            file: fsio::ROOT_FILE_PATH.clone(),
+        };
+        let Ok(file) = full_path.strip_prefix(root.full_path()) else {
+            return warn!("{full_path:?} is outside of {base:?}");
+        };
+        let path = FilePath {
+            base,
+            file: Arc::from(file),
+        };
+        let position = fsio::CursorPosition {
+            anchor: offset,
+            head: offset,
+        };
+        let this = self.clone();
+        spawn_local(async move {
+            let remote = this.remote.clone();
+            fsio::client::store_cursor_position(remote, path.clone(), position).await;
+            // Forced, to move the cursor when the file is already open.
+            this.path.file.force(path.file);
+        });
+    }
+}
+
 impl EditorState {
     pub(super) fn is_html(&self) -> bool {
         matches!(self, Self::Data(editor_state) if editor_state.is_html())
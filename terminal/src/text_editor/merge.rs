@@ -0,0 +1,222 @@
+#![cfg(feature = "client")]
+
+//! Line-based three-way merge, to reconcile edits with a file that changed on disk.
+
+use std::ops::Range;
+
+/// Beyond this, the lines that differ are considered as a single change.
+const MAX_DIFF_CELLS: usize = 16 * 1024 * 1024;
+
+const OURS_MARKER: &str = "<<<<<<< editor\n";
+const SEPARATOR_MARKER: &str = "=======\n";
+const THEIRS_MARKER: &str = ">>>>>>> disk\n";
+
+pub struct Merge {
+    pub text: String,
+    pub conflicts: usize,
+}
+
+/// Merges the changes from `base` to `ours` with the changes from `base` to `theirs`.
+///
+/// Overlapping changes are kept side-by-side between conflict markers.
+pub fn merge3(base: &str, ours: &str, theirs: &str) -> Merge {
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let ours: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let ours_hunks = diff(&base, &ours);
+    let theirs_hunks = diff(&base, &theirs);
+
+    let mut merge = Merge {
+        text: String::new(),
+        conflicts: 0,
+    };
+    let (mut i, mut j) = (0, 0);
+    let mut position = 0;
+    loop {
+        let start = match (ours_hunks.get(i), theirs_hunks.get(j)) {
+            (None, None) => break,
+            (Some(a), Some(b)) => a.base.start.min(b.base.start),
+            (Some(a), None) => a.base.start,
+            (None, Some(b)) => b.base.start,
+        };
+        merge.text.extend(base[position..start].iter().copied());
+
+        // Grow the region as long as a change from either side touches it.
+        let (first_ours, first_theirs) = (i, j);
+        let mut end = start;
+        loop {
+            if let Some(hunk) = ours_hunks.get(i)
+                && hunk.base.start <= end
+            {
+                end = end.max(hunk.base.end);
+                i += 1;
+            } else if let Some(hunk) = theirs_hunks.get(j)
+                && hunk.base.start <= end
+            {
+                end = end.max(hunk.base.end);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        let ours_region = side_region(&ours_hunks[first_ours..i], start..end);
+        let theirs_region = side_region(&theirs_hunks[first_theirs..j], start..end);
+        match (ours_region, theirs_region) {
+            (Some(ours_region), None) => merge.text.extend(ours[ours_region].iter().copied()),
+            (None, Some(theirs_region)) => merge.text.extend(theirs[theirs_region].iter().copied()),
+            (Some(ours_region), Some(theirs_region))
+                if ours[ours_region.clone()] == theirs[theirs_region.clone()] =>
+            {
+                merge.text.extend(ours[ours_region].iter().copied())
+            }
+            (Some(ours_region), Some(theirs_region)) => {
+                merge.conflicts += 1;
+                push_section(&mut merge.text, OURS_MARKER, &ours[ours_region]);
+                push_section(&mut merge.text, SEPARATOR_MARKER, &theirs[theirs_region]);
+                merge.text += THEIRS_MARKER;
+            }
+            (None, None) => unreachable!(),
+        }
+        position = end;
+    }
+    merge.text.extend(base[position..].iter().copied());
+    merge
+}
+
+/// A range of lines in the base that was replaced by a range of lines in the side.
+#[derive(Debug)]
+struct Hunk {
+    base: Range<usize>,
+    side: Range<usize>,
+}
+
+fn diff(base: &[&str], side: &[&str]) -> Vec<Hunk> {
+    let prefix = base.iter().zip(side).take_while(|(a, b)| a == b).count();
+    let suffix = base[prefix..]
+        .iter()
+        .rev()
+        .zip(side[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let base = &base[prefix..base.len() - suffix];
+    let side = &side[prefix..side.len() - suffix];
+    if base.is_empty() && side.is_empty() {
+        return vec![];
+    }
+    let shift = |hunk: Hunk| Hunk {
+        base: hunk.base.start + prefix..hunk.base.end + prefix,
+        side: hunk.side.start + prefix..hunk.side.end + prefix,
+    };
+    if base.len().saturating_mul(side.len()) > MAX_DIFF_CELLS {
+        return vec![shift(Hunk {
+            base: 0..base.len(),
+            side: 0..side.len(),
+        })];
+    }
+
+    // lcs[i * width + j] is the length of the longest common subsequence of base[i..] and side[j..].
+    let width = side.len() + 1;
+    let mut lcs = vec![0u32; (base.len() + 1) * width];
+    for i in (0..base.len()).rev() {
+        for j in (0..side.len()).rev() {
+            lcs[i * width + j] = if base[i] == side[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut hunks = vec![];
+    let (mut i, mut j) = (0, 0);
+    let (mut hunk_i, mut hunk_j) = (0, 0);
+    while i < base.len() && j < side.len() {
+        if base[i] == side[j] {
+            if (hunk_i, hunk_j) != (i, j) {
+                hunks.push(shift(Hunk {
+                    base: hunk_i..i,
+                    side: hunk_j..j,
+                }));
+            }
+            i += 1;
+            j += 1;
+            (hunk_i, hunk_j) = (i, j);
+        } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    if (hunk_i, hunk_j) != (base.len(), side.len()) {
+        hunks.push(shift(Hunk {
+            base: hunk_i..base.len(),
+            side: hunk_j..side.len(),
+        }));
+    }
+    hunks
+}
+
+/// The lines of the side that replace the `region` of the base, if the side changed it.
+fn side_region(hunks: &[Hunk], region: Range<usize>) -> Option<Range<usize>> {
+    let first = hunks.first()?;
+    let last = hunks.last()?;
+    Some(
+        first.side.start - (first.base.start - region.start)
+            ..last.side.end + (region.end - last.base.end),
+    )
+}
+
+fn push_section(text: &mut String, marker: &str, lines: &[&str]) {
+    *text += marker;
+    text.extend(lines.iter().copied());
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::merge3;
+
+    #[test]
+    fn merge_independent_changes() {
+        let merge = merge3("a\nb\nc\nd\ne\n", "A\nb\nc\nd\ne\n", "a\nb\nc\nd\nE\n");
+        assert_eq!("A\nb\nc\nd\nE\n", merge.text);
+        assert_eq!(0, merge.conflicts);
+    }
+
+    #[test]
+    fn merge_same_change() {
+        let merge = merge3("a\nb\nc\n", "a\nB\nc\n", "a\nB\nc\n");
+        assert_eq!("a\nB\nc\n", merge.text);
+        assert_eq!(0, merge.conflicts);
+    }
+
+    #[test]
+    fn merge_insertions_and_deletions() {
+        let merge = merge3("a\nb\nc\nd\n", "a\nx\nb\nc\nd\n", "a\nb\nc\n");
+        assert_eq!("a\nx\nb\nc\n", merge.text);
+        assert_eq!(0, merge.conflicts);
+    }
+
+    #[test]
+    fn merge_conflicting_changes() {
+        let merge = merge3("a\nb\nc\n", "a\nmine\nc\n", "a\ntheirs\nc\n");
+        assert_eq!(
+            "a\n<<<<<<< editor\nmine\n=======\ntheirs\n>>>>>>> disk\nc\n",
+            merge.text
+        );
+        assert_eq!(1, merge.conflicts);
+    }
+
+    #[test]
+    fn merge_conflict_without_trailing_newline() {
+        let merge = merge3("a\nb", "a\nmine", "a\ntheirs");
+        assert_eq!(
+            "a\n<<<<<<< editor\nmine\n=======\ntheirs\n>>>>>>> disk\n",
+            merge.text
+        );
+        assert_eq!(1, merge.conflicts);
+    }
+}
@@ -1,16 +1,25 @@
 #![cfg(feature = "text-editor")]
 
 mod autocomplete;
+mod collab;
+mod compare;
 pub mod file_path;
 pub mod fsio;
+mod git;
+mod lsp;
 mod manager;
+mod merge;
 pub mod notify;
 mod path_selector;
+mod problems;
 mod rust_lang;
 mod search;
 mod side;
 mod state;
 mod synchronized_state;
+mod tail;
+mod tasks;
+mod trash;
 pub mod ui;
 
 #[cfg(feature = "client")]
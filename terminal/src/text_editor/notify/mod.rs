@@ -1,6 +1,6 @@
-mod event_handler;
+pub(super) mod event_handler;
 pub mod manager;
 pub mod server_fn;
 pub mod service;
 pub mod ui;
-mod watcher;
+pub(super) mod watcher;
@@ -1,5 +1,6 @@
 #![cfg(feature = "server")]
 
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use futures::FutureExt as _;
@@ -15,6 +16,7 @@ use tokio::sync::mpsc;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::Instrument as _;
 use tracing::debug;
+use tracing::warn;
 
 use super::event_handler::make_event_handler;
 use super::server_fn::NotifyRequest;
@@ -30,10 +32,23 @@ pub fn notify(
     let request_task = async move {
         let mut request = request;
         let mut watcher = None;
-        while let Some(request) = request.next().await {
-            if let Err(error) = process_request(request, &mut watcher, &tx) {
-                let _ = eos_tx.send(error.into());
-                return;
+        let (replaced_tx, mut replaced_rx) = mpsc::unbounded_channel();
+        loop {
+            tokio::select! {
+                next = request.next() => {
+                    let Some(next) = next else { return };
+                    if let Err(error) = process_request(next, &mut watcher, &tx, &replaced_tx) {
+                        let _ = eos_tx.send(error.into());
+                        return;
+                    }
+                }
+                Some(full_path) = replaced_rx.recv() => {
+                    if let Some(watcher) = &mut watcher
+                        && let Err(error) = watcher.rewatch(&full_path)
+                    {
+                        warn!("Failed to watch replaced file {full_path:?}: {error}");
+                    }
+                }
             }
         }
     };
@@ -55,12 +70,13 @@ fn process_request(
     request: Result<NotifyRequest, ServerFnError>,
     watcher: &mut Option<ExtendedWatcher>,
     tx: &mpsc::UnboundedSender<Result<NotifyResponse, ServerFnError>>,
+    replaced: &mpsc::UnboundedSender<PathBuf>,
 ) -> Result<(), NotifyError> {
     debug!("Notify request: {request:?}");
     match request.map_err(NotifyError::BadRequest)? {
         NotifyRequest::Start { remote: _ } => {
             *watcher = Some(
-                ExtendedWatcher::new(tx.clone(), make_event_handler)
+                ExtendedWatcher::new(tx.clone(), replaced.clone(), make_event_handler)
                     .map_err(NotifyError::CreateWatcher)?,
             );
         }
@@ -1,8 +1,10 @@
 #![cfg(feature = "server")]
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::hash_map;
 use std::path::Path;
+use std::path::PathBuf;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -30,6 +32,7 @@ use crate::utils::async_throttle::Throttle;
 pub struct ExtendedWatcher {
     inotify: RecommendedWatcher,
     cargo_workspaces: CargoWorkspaces,
+    watched: HashSet<PathBuf>,
 }
 
 #[derive(Clone, Default)]
@@ -48,17 +51,25 @@ type CargoCheckResult = Result<Vec<SyntheticDiagnostic>, GrpcError<CargoCheckErr
 type EventSender = mpsc::UnboundedSender<Result<NotifyResponse, ServerFnError>>;
 
 impl ExtendedWatcher {
-    pub fn new<F, H>(tx: EventSender, make_event_handler: F) -> notify::Result<Self>
+    /// Paths that are removed but still exist were replaced, they are sent to `replaced`
+    /// so they can be watched again with [ExtendedWatcher::rewatch].
+    pub fn new<F, H>(
+        tx: EventSender,
+        replaced: mpsc::UnboundedSender<PathBuf>,
+        make_event_handler: F,
+    ) -> notify::Result<Self>
     where
         F: Fn(EventSender) -> H,
         H: EventHandler,
     {
         let cargo_workspaces = CargoWorkspaces::default();
         Ok(Self {
-            inotify: notify::recommended_watcher(
-                cargo_workspaces.enrich_cargo_workspace(tx.clone(), make_event_handler(tx)),
-            )?,
+            inotify: notify::recommended_watcher(cargo_workspaces.enrich_cargo_workspace(
+                tx.clone(),
+                detect_replaced_files(replaced, make_event_handler(tx)),
+            ))?,
             cargo_workspaces,
+            watched: HashSet::new(),
         })
     }
 
@@ -88,7 +99,28 @@ impl ExtendedWatcher {
         let full_path = path.full_path();
         debug!("Start watching {full_path:?}");
         self.inotify
-            .watch(&full_path, notify::RecursiveMode::NonRecursive)
+            .watch(&full_path, notify::RecursiveMode::NonRecursive)?;
+        self.watched.insert(full_path);
+        Ok(())
+    }
+
+    /// Watches a single file, without running `cargo check` when it changes.
+    pub fn watch_file(&mut self, full_path: &Path) -> notify::Result<()> {
+        debug!("Start watching file {full_path:?}");
+        self.inotify
+            .watch(full_path, notify::RecursiveMode::NonRecursive)?;
+        self.watched.insert(full_path.to_owned());
+        Ok(())
+    }
+
+    /// Files replaced by a rename, like atomic saves, are no longer watched by inotify.
+    pub fn rewatch(&mut self, full_path: &Path) -> notify::Result<()> {
+        if !self.watched.contains(full_path) {
+            return Ok(());
+        }
+        debug!("Watching replaced file {full_path:?}");
+        self.inotify
+            .watch(full_path, notify::RecursiveMode::NonRecursive)
     }
 
     pub fn unwatch(
@@ -107,10 +139,27 @@ impl ExtendedWatcher {
         }
         let full_path = path.full_path();
         debug!("Stop watching {full_path:?}");
+        self.watched.remove(&full_path);
         self.inotify.unwatch(&full_path)
     }
 }
 
+fn detect_replaced_files(
+    replaced: mpsc::UnboundedSender<PathBuf>,
+    mut event_handler: impl EventHandler,
+) -> impl EventHandler {
+    move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = &event
+            && let notify::EventKind::Remove { .. } = event.kind
+        {
+            for path in event.paths.iter().filter(|path| path.exists()) {
+                let _ = replaced.send(path.clone());
+            }
+        }
+        event_handler.handle_event(event)
+    }
+}
+
 fn make_run_cargo_check(dir: &Path) -> Box<dyn Fn(()) -> CargoCheckFuture + Send + Sync> {
     let dir: Arc<Path> = Arc::from(dir);
     Box::new(move |()| {
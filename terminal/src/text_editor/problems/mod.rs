@@ -0,0 +1,6 @@
+//! Lists the errors and warnings of cargo check in the workspace, grouped by file.
+
+#[cfg(feature = "client")]
+pub mod state;
+#[cfg(feature = "client")]
+pub mod ui;
@@ -0,0 +1,17 @@
+use nameth::NamedType as _;
+use nameth::nameth;
+
+use crate::text_editor::manager::EditorState;
+
+#[derive(Clone)]
+#[nameth]
+pub struct EditorProblemsState {
+    /// The editor state to go back to when the problems panel is closed.
+    pub(super) prev: Box<EditorState>,
+}
+
+impl std::fmt::Debug for EditorProblemsState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(EditorProblemsState::type_name()).finish()
+    }
+}
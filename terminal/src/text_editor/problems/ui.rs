@@ -0,0 +1,352 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use terrazzo::autoclone;
+use terrazzo::html;
+use terrazzo::prelude::*;
+use terrazzo::template;
+use wasm_bindgen_futures::spawn_local;
+
+use self::diagnostics::warn;
+use super::state::EditorProblemsState;
+use crate::assets::icons;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio::ROOT_FILE_PATH;
+use crate::text_editor::manager::EditorState;
+use crate::text_editor::manager::TextEditorManager;
+use crate::text_editor::notify::server_fn::EventKind;
+use crate::text_editor::rust_lang::cargo_check;
+use crate::text_editor::rust_lang::synthetic::SyntheticDiagnostic;
+use crate::text_editor::style;
+
+terrazzo_css::import_style!(problems_style, "problems.scss");
+
+impl TextEditorManager {
+    /// The header button that opens and closes the problems panel.
+    #[html]
+    pub fn problems_toggle(self: &Ptr<Self>) -> XElement {
+        let manager = self.clone();
+
+        #[template(wrap = true)]
+        fn make_class(#[signal] editor_state: EditorState) -> XAttributeValue {
+            matches!(editor_state, EditorState::Problems(_)).then_some(style::ACTIVE)
+        }
+
+        img(
+            class = style::TOGGLE_PROBLEMS_PANEL,
+            class %= make_class(self.editor_state.clone()),
+            #[cfg(not(feature = "client-prod"))]
+            class = "toggle-problems-panel",
+            src = icons::problems(),
+            title = "Problems",
+            click = move |_| toggle_problems_panel(&manager),
+        )
+    }
+}
+
+fn toggle_problems_panel(manager: &TextEditorManager) {
+    manager.editor_state.update(|editor_state| {
+        if let EditorState::Problems(EditorProblemsState { prev }) = editor_state {
+            return Some(prev.as_ref().clone());
+        }
+        Some(EditorState::Problems(EditorProblemsState {
+            prev: Box::new(editor_state.clone()),
+        }))
+    });
+}
+
+/// An error or a warning, at its primary span.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Problem {
+    line: u32,
+    column: u32,
+    offset: u32,
+    level: String,
+    message: String,
+}
+
+/// The problems of each file.
+pub type Problems = Vec<(Arc<Path>, Vec<Problem>)>;
+
+pub fn group_by_file(diagnostics: &[SyntheticDiagnostic]) -> Problems {
+    let mut problems: BTreeMap<Arc<Path>, Vec<Problem>> = BTreeMap::new();
+    for diagnostic in diagnostics {
+        // The help and note children of a diagnostic are shown in the editor.
+        if diagnostic.level != "error" && diagnostic.level != "warning" {
+            continue;
+        }
+        let spans = &diagnostic.spans;
+        let Some(span) = spans.iter().find(|span| span.is_primary).or(spans.first()) else {
+            continue;
+        };
+        problems
+            .entry(Path::new(&span.full_path).into())
+            .or_default()
+            .push(Problem {
+                line: span.line_start,
+                column: span.column_start,
+                offset: span.offset_start,
+                level: diagnostic.level.clone(),
+                message: diagnostic.message.clone(),
+            });
+    }
+    problems
+        .into_iter()
+        .map(|(path, mut problems)| {
+            // The same file can be checked for several targets.
+            problems.sort();
+            problems.dedup();
+            (path, problems)
+        })
+        .collect()
+}
+
+/// The signals of the problems panel.
+struct ProblemsPanel {
+    manager: Ptr<TextEditorManager>,
+    root: FilePath<Arc<Path>>,
+    problems: XSignal<Option<Arc<Problems>>>,
+    checking: XSignal<bool>,
+}
+
+impl ProblemsPanel {
+    fn new(manager: &Ptr<TextEditorManager>) -> Ptr<Self> {
+        Self {
+            manager: manager.clone(),
+            root: FilePath {
+                base: manager.path.base.get_value_untracked(),
+                file: ROOT_FILE_PATH.clone(),
+            },
+            problems: XSignal::new("problems", None),
+            checking: XSignal::new("problems-checking", false),
+        }
+        .into()
+    }
+
+    fn check(self: &Ptr<Self>) {
+        if self.checking.get_value_untracked() {
+            return;
+        }
+        self.checking.set(true);
+        let this = self.clone();
+        spawn_local(async move {
+            let base_path = this.root.full_path().to_string_lossy().into();
+            let remote = this.manager.remote.clone();
+            let diagnostics = cargo_check(remote, base_path, vec![]).await;
+            let batch = Batch::use_batch("problems-check");
+            match diagnostics {
+                Ok(diagnostics) => this.set(&diagnostics),
+                Err(error) => warn!("Cargo check failed: {error}"),
+            }
+            this.checking.set(false);
+            drop(batch);
+        });
+    }
+
+    fn set(&self, diagnostics: &[SyntheticDiagnostic]) {
+        self.problems
+            .set(Some(Arc::new(group_by_file(diagnostics))));
+    }
+}
+
+#[autoclone]
+#[html]
+pub fn problems_panel(
+    manager: Ptr<TextEditorManager>,
+    _problems_state: EditorProblemsState,
+) -> XElement {
+    let panel = ProblemsPanel::new(&manager);
+
+    // Cargo check runs again when files change.
+    let registration = manager
+        .notify_service
+        .watch_file(&panel.root, move |event| {
+            autoclone!(panel);
+            if let EventKind::CargoCheck(diagnostics) = &event.kind {
+                panel.set(diagnostics);
+            }
+        });
+
+    div(
+        class = problems_style::PROBLEMS_PANEL,
+        #[cfg(not(feature = "client-prod"))]
+        class = "problems-panel",
+        div(
+            class = problems_style::PROBLEMS_TOOLBAR,
+            button(
+                "Run cargo check",
+                click = move |_| {
+                    autoclone!(panel);
+                    panel.check()
+                },
+            ),
+            show_summary(panel.problems.clone(), panel.checking.clone()),
+        ),
+        show_problems(
+            manager.clone(),
+            panel.root.full_path().into(),
+            panel.problems.clone(),
+        ),
+        after_render = move |_| {
+            autoclone!(panel);
+            let _moved = &registration;
+            panel.check()
+        },
+    )
+}
+
+#[html]
+#[template(tag = span)]
+fn show_summary(#[signal] problems: Option<Arc<Problems>>, #[signal] checking: bool) -> XElement {
+    if checking {
+        return tag("Checking...");
+    }
+    let Some(problems) = problems else {
+        return tag(style::display = "none", style::visibility = "hidden");
+    };
+    let count = |level: &str| {
+        let problems = problems.iter().flat_map(|(_, problems)| problems);
+        problems.filter(|problem| problem.level == level).count()
+    };
+    let errors = count("error");
+    let warnings = count("warning");
+    tag("{errors} errors, {warnings} warnings")
+}
+
+/// The problems grouped by file, with paths relative to `base`.
+#[html]
+#[template(tag = div)]
+pub fn show_problems(
+    manager: Ptr<TextEditorManager>,
+    base: Arc<Path>,
+    #[signal] problems: Option<Arc<Problems>>,
+) -> XElement {
+    let Some(problems) = problems else {
+        return tag(class = problems_style::PROBLEMS_FILES);
+    };
+    if problems.is_empty() {
+        return tag(class = problems_style::PROBLEMS_FILES, "No problems");
+    }
+    let files = problems.iter().map(|(path, problems)| {
+        let name = path
+            .strip_prefix(&base)
+            .unwrap_or(path)
+            .display()
+            .to_string();
+        let count = problems.len();
+        let rows = problems
+            .iter()
+            .map(|problem| problem_row(&manager, path, problem));
+        div(
+            class = problems_style::PROBLEMS_FILE,
+            div(
+                class = problems_style::PROBLEMS_FILE_NAME,
+                "{name} ({count})",
+            ),
+            rows..,
+        )
+    });
+    tag(class = problems_style::PROBLEMS_FILES, files..)
+}
+
+#[html]
+fn problem_row(manager: &Ptr<TextEditorManager>, path: &Arc<Path>, problem: &Problem) -> XElement {
+    let manager = manager.clone();
+    let path = path.clone();
+    let offset = problem.offset;
+    let Problem {
+        line,
+        column,
+        level,
+        message,
+        ..
+    } = problem;
+    let level_class = match level.as_str() {
+        "error" => problems_style::ERROR,
+        _ => problems_style::WARNING,
+    };
+    div(
+        class = problems_style::PROBLEM,
+        click = move |_| manager.open_file_at(&path, offset),
+        span(
+            class = problems_style::PROBLEM_LEVEL,
+            class = level_class,
+            "{level}",
+        ),
+        span(class = problems_style::PROBLEM_MESSAGE, "{message}"),
+        span(class = problems_style::PROBLEM_LOCATION, "{line}:{column}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use super::Problem;
+    use crate::text_editor::rust_lang::synthetic::SyntheticDiagnostic;
+    use crate::text_editor::rust_lang::synthetic::SyntheticDiagnosticSpan;
+
+    #[test]
+    fn group_by_file() {
+        let span = |full_path: &str, line, is_primary| SyntheticDiagnosticSpan {
+            file_name: full_path.trim_start_matches("/ws/").into(),
+            full_path: full_path.into(),
+            byte_start: line * 10,
+            byte_end: line * 10 + 5,
+            offset_start: line * 10,
+            offset_end: line * 10 + 5,
+            line_start: line,
+            line_end: line,
+            column_start: 1,
+            column_end: 6,
+            is_primary,
+            suggested_replacement: None,
+            suggestion_applicability: None,
+        };
+        let diagnostic = |level: &str, message: &str, spans| SyntheticDiagnostic {
+            base_path: "/ws".into(),
+            file_path: "/ws/src/lib.rs".into(),
+            level: level.into(),
+            message: message.into(),
+            code: None,
+            spans,
+        };
+        let diagnostics = [
+            diagnostic(
+                "warning",
+                "unused",
+                vec![
+                    span("/ws/src/b.rs", 3, false),
+                    span("/ws/src/b.rs", 2, true),
+                ],
+            ),
+            diagnostic("help", "remove it", vec![span("/ws/src/b.rs", 2, true)]),
+            diagnostic("error", "missing", vec![span("/ws/src/a.rs", 7, true)]),
+            diagnostic("error", "missing", vec![span("/ws/src/a.rs", 7, true)]),
+            diagnostic("error", "aborting due to 1 error", vec![]),
+        ];
+        let problems = super::group_by_file(&diagnostics);
+        let problem = |line, level: &str, message: &str| Problem {
+            line,
+            column: 1,
+            offset: line * 10,
+            level: level.into(),
+            message: message.into(),
+        };
+        assert_eq!(
+            vec![
+                (
+                    Arc::from(Path::new("/ws/src/a.rs")),
+                    vec![problem(7, "error", "missing")]
+                ),
+                (
+                    Arc::from(Path::new("/ws/src/b.rs")),
+                    vec![problem(2, "warning", "unused")]
+                ),
+            ],
+            problems
+        );
+    }
+}
@@ -16,7 +16,7 @@ pub mod synthetic;
 
 #[server(protocol = Http<Json, Json>)]
 #[nameth]
-async fn cargo_check(
+pub async fn cargo_check(
     remote: ClientAddress,
     base_path: Arc<str>,
     features: Vec<String>,
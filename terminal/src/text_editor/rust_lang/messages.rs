@@ -72,7 +72,7 @@ pub struct Diagnostic<'a> {
     #[serde(borrow)]
     pub children: Vec<Diagnostic<'a>>,
 
-    #[cfg(debug_assertions)]
+    /// The message as rustc prints it in the terminal.
     #[serde(borrow)]
     pub rendered: Option<Cow<'a, str>>,
 }
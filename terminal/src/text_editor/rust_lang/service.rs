@@ -86,29 +86,50 @@ pub fn cargo_check(
                 continue;
             }
 
-            let Ok(message) = serde_json::from_str::<super::messages::CargoCheckMessage>(next_line)
-                .inspect_err(|error| trace!("Invalid cargo check JSON: {error}: {next_line}"))
-            else {
+            let Some(CompilerMessage { diagnostics, .. }) = compiler_message(next_line) else {
                 continue;
             };
-            if message.reason != "compiler-message" {
-                continue;
-            }
-
-            results.extend(SyntheticDiagnostic::new(&message));
+            results.extend(diagnostics);
         }
+        SyntheticDiagnostic::resolve_offsets(&mut results).await;
         Ok(results)
     }
     .in_current_span()
 }
 
 #[cfg(not(feature = "bazel"))]
+/// A line of `cargo --message-format=json` from rustc or clippy.
+pub struct CompilerMessage {
+    pub diagnostics: Vec<SyntheticDiagnostic>,
+
+    /// The message as rustc prints it in the terminal.
+    pub rendered: Option<String>,
+}
+
+/// Parses a line of `cargo --message-format=json`, if it's a compiler message.
+///
+/// The byte offsets of the spans still have to be converted with
+/// [SyntheticDiagnostic::resolve_offsets].
+pub fn compiler_message(line: &str) -> Option<CompilerMessage> {
+    let message = serde_json::from_str::<super::messages::CargoCheckMessage>(line)
+        .inspect_err(|error| trace!("Invalid cargo check JSON: {error}: {line}"))
+        .ok()?;
+    if message.reason != "compiler-message" {
+        return None;
+    }
+    Some(CompilerMessage {
+        diagnostics: SyntheticDiagnostic::new(&message),
+        rendered: message.message.rendered.as_deref().map(str::to_owned),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
 
     use trz_gateway_common::tracing::test_utils::enable_tracing_for_tests;
 
+    use super::super::synthetic::Applicability;
     use super::super::synthetic::SyntheticDiagnosticCode;
     use super::super::synthetic::SyntheticDiagnosticSpan;
 
@@ -133,12 +154,16 @@ mod tests {
         assert_eq!(
             &SyntheticDiagnosticSpan {
                 file_name: "src/main.rs".into(),
+                full_path: base_path.join("src/main.rs").to_string_lossy().into_owned(),
                 byte_start: 88,
                 byte_end: 106,
+                offset_start: 88,
+                offset_end: 106,
                 line_start: 6,
                 line_end: 6,
                 column_start: 4,
                 column_end: 22,
+                is_primary: true,
                 suggested_replacement: None,
                 suggestion_applicability: None
             },
@@ -159,6 +184,27 @@ mod tests {
         assert!(result[0].message.contains("no method named `unwrap2`"));
     }
 
+    #[tokio::test]
+    async fn unused_mut() {
+        enable_tracing_for_tests();
+        let base_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(RUST_LANG_CHECKS);
+        let result = super::cargo_check(&base_path, &["unused_mut"])
+            .await
+            .unwrap();
+        let fix = result
+            .iter()
+            .flat_map(|diagnostic| &diagnostic.spans)
+            .find(|span| span.suggestion_applicability == Some(Applicability::MachineApplicable))
+            .unwrap();
+        assert_eq!(
+            base_path.join("src/unused_mut.rs"),
+            PathBuf::from(&fix.full_path)
+        );
+        assert_eq!(Some(""), fix.suggested_replacement.as_deref());
+        assert_eq!((110, 114), (fix.byte_start, fix.byte_end));
+        assert_eq!((107, 111), (fix.offset_start, fix.offset_end));
+    }
+
     #[tokio::test]
     #[ignore = "Not a good idea to compile the current project"]
     async fn terminal() {
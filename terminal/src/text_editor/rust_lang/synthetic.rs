@@ -1,4 +1,4 @@
-#[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub struct SyntheticDiagnostic {
     pub base_path: String,
     pub file_path: String,
@@ -8,7 +8,7 @@ pub struct SyntheticDiagnostic {
     pub spans: Vec<SyntheticDiagnosticSpan>,
 }
 
-#[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub struct SyntheticDiagnosticCode {
     pub code: String,
     pub explanation: Option<String>,
@@ -17,9 +17,17 @@ pub struct SyntheticDiagnosticCode {
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 pub struct SyntheticDiagnosticSpan {
     pub file_name: String,
+
+    /// The absolute path of the file, resolved from the package.
+    pub full_path: String,
+
     pub byte_start: u32,
     pub byte_end: u32,
 
+    /// UTF-16 offsets, as CodeMirror counts them.
+    pub offset_start: u32,
+    pub offset_end: u32,
+
     /// 1-based.
     pub line_start: u32,
     pub line_end: u32,
@@ -28,6 +36,9 @@ pub struct SyntheticDiagnosticSpan {
     pub column_start: u32,
     pub column_end: u32,
 
+    /// The point where the error actually occurred.
+    pub is_primary: bool,
+
     pub suggested_replacement: Option<String>,
     pub suggestion_applicability: Option<Applicability>,
 }
@@ -41,9 +52,24 @@ pub enum Applicability {
     Unspecified,
 }
 
+impl SyntheticDiagnostic {
+    /// The replacements in the file that rustc considers safe to apply automatically.
+    pub fn machine_applicable_fixes<'t>(
+        &'t self,
+        full_path: &'t str,
+    ) -> impl Iterator<Item = &'t SyntheticDiagnosticSpan> {
+        self.spans.iter().filter(move |span| {
+            span.full_path == full_path
+                && span.suggested_replacement.is_some()
+                && span.suggestion_applicability == Some(Applicability::MachineApplicable)
+        })
+    }
+}
+
 #[cfg(feature = "server")]
 mod convert {
     use std::borrow::Cow;
+    use std::collections::HashMap;
     use std::path::Path;
 
     use super::super::messages;
@@ -85,12 +111,16 @@ mod convert {
                     .iter()
                     .map(|span| SyntheticDiagnosticSpan {
                         file_name: span.file_name.to_string(),
+                        full_path: resolve_file_name(base_path, &span.file_name),
                         byte_start: span.byte_start,
                         byte_end: span.byte_end,
+                        offset_start: span.byte_start,
+                        offset_end: span.byte_end,
                         line_start: span.line_start,
                         line_end: span.line_end,
                         column_start: span.column_start,
                         column_end: span.column_end,
+                        is_primary: span.is_primary,
                         suggested_replacement: span
                             .suggested_replacement
                             .as_ref()
@@ -130,5 +160,40 @@ mod convert {
                 spans,
             });
         }
+
+        /// Converts the byte offsets of the spans to UTF-16 offsets, reading each file once.
+        pub async fn resolve_offsets(diagnostics: &mut [Self]) {
+            let mut contents: HashMap<String, Option<String>> = HashMap::new();
+            for span in diagnostics.iter_mut().flat_map(|d| d.spans.iter_mut()) {
+                if !contents.contains_key(&span.full_path) {
+                    let content = tokio::fs::read_to_string(&span.full_path).await.ok();
+                    contents.insert(span.full_path.clone(), content);
+                }
+                let Some(content) = &contents[&span.full_path] else {
+                    continue;
+                };
+                let utf16_offset = |byte: u32| {
+                    let prefix = content.get(..byte as usize)?;
+                    u32::try_from(prefix.encode_utf16().count()).ok()
+                };
+                if let (Some(start), Some(end)) =
+                    (utf16_offset(span.byte_start), utf16_offset(span.byte_end))
+                {
+                    span.offset_start = start;
+                    span.offset_end = end;
+                }
+            }
+        }
+    }
+
+    /// Cargo reports paths relative to the root of the workspace, which contains the package.
+    fn resolve_file_name(base_path: &str, file_name: &str) -> String {
+        let base_path = Path::new(base_path);
+        let full_path = base_path
+            .ancestors()
+            .map(|ancestor| ancestor.join(file_name))
+            .find(|full_path| full_path.exists())
+            .unwrap_or_else(|| base_path.join(file_name));
+        full_path.to_string_lossy().into_owned()
     }
 }
@@ -6,3 +6,4 @@ fn main() {
 fn some_unused_method() {}
 
 mod method_does_not_exist;
+mod unused_mut;
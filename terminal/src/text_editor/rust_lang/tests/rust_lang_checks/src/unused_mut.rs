@@ -0,0 +1,7 @@
+#[cfg(feature = "unused_mut")]
+#[allow(dead_code)]
+fn unused_mut() -> &'static str {
+    // Café ☕
+    let mut coffee = "☕";
+    coffee
+}
@@ -12,6 +12,53 @@ use crate::api::client_address::ClientAddress;
 use crate::text_editor::file_path::FilePath;
 use crate::text_editor::fsio::CursorPosition;
 
+/// A project-wide replace of the `pattern` regex.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReplaceQuery {
+    /// The search query of the files to look into, or all the files of the repository if empty.
+    pub query: String,
+    pub pattern: String,
+
+    /// The replacement text, where `$1` or `${name}` are the groups captured by the pattern.
+    pub replacement: String,
+}
+
+/// The matches of a file, with paths relative to the base folder.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReplaceFile {
+    pub path: Arc<Path>,
+
+    /// The hash of the content the matches were found in.
+    pub version: u64,
+    pub matches: Vec<ReplaceMatch>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReplaceMatch {
+    /// The byte offset of the match, which identifies it when the replace is applied.
+    pub start: usize,
+
+    /// The UTF-16 offset of the match, to open the file at.
+    pub offset: u32,
+    pub line: u32,
+
+    /// The text of the line around the match.
+    pub before: String,
+    pub matched: String,
+    pub after: String,
+
+    /// The matched text after the groups are expanded in the replacement.
+    pub replacement: String,
+}
+
+/// The matches of a file that were accepted in the preview.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ReplaceEdit {
+    pub path: Arc<Path>,
+    pub version: u64,
+    pub starts: Vec<usize>,
+}
+
 #[server(protocol = Http<Json, Json>)]
 pub async fn get_highlight_ranges(
     remote: ClientAddress,
@@ -21,6 +68,29 @@ pub async fn get_highlight_ranges(
     super::service::get_highlight_ranges(remote, path, input).await
 }
 
+/// Lists the matches of the replace, the files are not changed.
+#[server(protocol = Http<Json, Json>)]
+pub async fn preview_replace(
+    remote: ClientAddress,
+    base: Arc<Path>,
+    query: ReplaceQuery,
+) -> Result<Vec<ReplaceFile>, ServerFnError> {
+    super::service::preview_replace(remote, base, query).await
+}
+
+/// Replaces the accepted matches, unless one of the files changed since the preview.
+///
+/// Returns the number of files that were changed.
+#[server(protocol = Http<Json, Json>)]
+pub async fn apply_replace(
+    remote: ClientAddress,
+    base: Arc<Path>,
+    query: ReplaceQuery,
+    edits: Vec<ReplaceEdit>,
+) -> Result<usize, ServerFnError> {
+    super::service::apply_replace(remote, base, query, edits).await
+}
+
 #[server(protocol = Http<Json, StreamingText>)]
 pub async fn search(
     remote: ClientAddress,
@@ -1,6 +1,8 @@
 pub mod api;
 #[cfg(feature = "client")]
 pub mod client;
+#[cfg(feature = "client")]
+pub mod replace;
 #[cfg(feature = "server")]
 pub mod service;
 #[cfg(feature = "client")]
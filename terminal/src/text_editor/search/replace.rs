@@ -0,0 +1,343 @@
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use server_fn::ServerFnError;
+use terrazzo::autoclone;
+use terrazzo::html;
+use terrazzo::prelude::*;
+use terrazzo::template;
+use terrazzo::widgets::element_capture::ElementCapture;
+use wasm_bindgen::JsCast as _;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use web_sys::KeyboardEvent;
+
+use self::diagnostics::warn;
+use super::api::ReplaceEdit;
+use super::api::ReplaceFile;
+use super::api::ReplaceMatch;
+use super::api::ReplaceQuery;
+use super::state::EditorReplaceState;
+use crate::assets::icons;
+use crate::text_editor::manager::EditorState;
+use crate::text_editor::manager::TextEditorManager;
+use crate::text_editor::style;
+
+terrazzo_css::import_style!(replace_style, "replace.scss");
+
+impl TextEditorManager {
+    /// The header button that opens and closes the replace panel.
+    #[html]
+    pub fn replace_toggle(self: &Ptr<Self>) -> XElement {
+        let manager = self.clone();
+
+        #[template(wrap = true)]
+        fn make_class(#[signal] editor_state: EditorState) -> XAttributeValue {
+            matches!(editor_state, EditorState::Replace(_)).then_some(style::ACTIVE)
+        }
+
+        img(
+            class = style::TOGGLE_REPLACE_PANEL,
+            class %= make_class(self.editor_state.clone()),
+            #[cfg(not(feature = "client-prod"))]
+            class = "toggle-replace-panel",
+            src = icons::replace(),
+            title = "Replace in files",
+            click = move |_| toggle_replace_panel(&manager),
+        )
+    }
+}
+
+fn toggle_replace_panel(manager: &TextEditorManager) {
+    manager.editor_state.update(|editor_state| {
+        if let EditorState::Replace(EditorReplaceState { prev, .. }) = editor_state {
+            return Some(prev.as_ref().clone());
+        }
+        Some(EditorState::Replace(EditorReplaceState {
+            prev: Box::new(editor_state.clone()),
+            query: manager.search.query.get_value_untracked(),
+        }))
+    });
+}
+
+/// The matches of the last preview, and the ones that were unchecked.
+#[derive(Debug)]
+struct ReplacePreview {
+    query: ReplaceQuery,
+    files: Vec<ReplaceFile>,
+    excluded: Mutex<HashSet<(Arc<Path>, usize)>>,
+}
+
+impl ReplacePreview {
+    /// The matches that are still checked.
+    fn edits(&self) -> Vec<ReplaceEdit> {
+        let excluded = self.excluded.lock().unwrap();
+        self.files
+            .iter()
+            .map(|file| ReplaceEdit {
+                path: file.path.clone(),
+                version: file.version,
+                starts: file
+                    .matches
+                    .iter()
+                    .map(|m| m.start)
+                    .filter(|start| !excluded.contains(&(file.path.clone(), *start)))
+                    .collect(),
+            })
+            .filter(|edit| !edit.starts.is_empty())
+            .collect()
+    }
+}
+
+/// The signals of the replace panel.
+struct ReplacePanel {
+    manager: Ptr<TextEditorManager>,
+    base: Arc<Path>,
+    preview: XSignal<Option<Arc<ReplacePreview>>>,
+    status: XSignal<Option<Arc<str>>>,
+    busy: Cell<bool>,
+}
+
+impl ReplacePanel {
+    fn new(manager: &Ptr<TextEditorManager>) -> Ptr<Self> {
+        Self {
+            manager: manager.clone(),
+            base: manager.path.base.get_value_untracked(),
+            preview: XSignal::new("replace-preview", None),
+            status: XSignal::new("replace-status", None),
+            busy: Cell::new(false),
+        }
+        .into()
+    }
+
+    fn preview(self: &Ptr<Self>, query: ReplaceQuery) {
+        if query.pattern.is_empty() || self.busy.replace(true) {
+            return;
+        }
+        self.status.set(Some("Searching...".into()));
+        let this = self.clone();
+        spawn_local(async move {
+            let remote = this.manager.remote.clone();
+            let files = super::api::preview_replace(remote, this.base.clone(), query.clone()).await;
+            let batch = Batch::use_batch("replace-preview");
+            match files {
+                Ok(files) => {
+                    let count = files.iter().map(|file| file.matches.len()).sum::<usize>();
+                    let status = format!("{count} matches in {} files", files.len());
+                    this.status.set(Some(status.into()));
+                    this.preview.force(Some(Arc::new(ReplacePreview {
+                        query,
+                        files,
+                        excluded: Default::default(),
+                    })));
+                }
+                Err(error) => this.fail(error),
+            }
+            this.busy.set(false);
+            drop(batch);
+        });
+    }
+
+    fn apply(self: &Ptr<Self>) {
+        let Some(preview) = self.preview.get_value_untracked() else {
+            return;
+        };
+        let edits = preview.edits();
+        if edits.is_empty() || self.busy.replace(true) {
+            return;
+        }
+        self.status.set(Some("Replacing...".into()));
+        let this = self.clone();
+        spawn_local(async move {
+            let remote = this.manager.remote.clone();
+            let query = preview.query.clone();
+            let count = super::api::apply_replace(remote, this.base.clone(), query, edits).await;
+            let batch = Batch::use_batch("replace-apply");
+            match count {
+                Ok(count) => {
+                    this.status
+                        .set(Some(format!("Replaced in {count} files").into()));
+                    this.preview.force(None);
+                }
+                Err(error) => this.fail(error),
+            }
+            this.busy.set(false);
+            drop(batch);
+        });
+    }
+
+    fn fail(&self, error: ServerFnError) {
+        warn!("Replace failed: {error}");
+        self.status.set(Some(error.to_string().into()));
+    }
+}
+
+/// The fields of the replace panel.
+#[derive(Clone, Default)]
+struct ReplaceInputs {
+    query: ElementCapture<HtmlInputElement>,
+    pattern: ElementCapture<HtmlInputElement>,
+    replacement: ElementCapture<HtmlInputElement>,
+}
+
+impl ReplaceInputs {
+    fn get(&self) -> ReplaceQuery {
+        ReplaceQuery {
+            query: self.query.with(|input| input.value()),
+            pattern: self.pattern.with(|input| input.value()),
+            replacement: self.replacement.with(|input| input.value()),
+        }
+    }
+}
+
+#[autoclone]
+#[html]
+pub fn replace_panel(
+    manager: Ptr<TextEditorManager>,
+    replace_state: EditorReplaceState,
+) -> XElement {
+    let panel = ReplacePanel::new(&manager);
+    let inputs = ReplaceInputs::default();
+    let keydown = move |event: KeyboardEvent| {
+        autoclone!(panel, inputs);
+        if event.key() == "Enter" {
+            event.prevent_default();
+            panel.preview(inputs.get());
+        }
+    };
+    div(
+        class = replace_style::REPLACE_PANEL,
+        #[cfg(not(feature = "client-prod"))]
+        class = "replace-panel",
+        div(
+            class = replace_style::REPLACE_FORM,
+            input(
+                before_render = inputs.query.capture(),
+                r#type = "text",
+                value = replace_state.query.to_string(),
+                placeholder = "Search query of the files, or all files if empty",
+                keydown = keydown.clone(),
+            ),
+            input(
+                before_render = inputs.pattern.capture(),
+                r#type = "text",
+                placeholder = "Regular expression",
+                keydown = keydown.clone(),
+            ),
+            input(
+                before_render = inputs.replacement.capture(),
+                r#type = "text",
+                placeholder = "Replacement, where $1 is the first captured group",
+                keydown = keydown,
+            ),
+        ),
+        div(
+            class = replace_style::REPLACE_TOOLBAR,
+            button(
+                "Preview",
+                click = move |_| {
+                    autoclone!(panel, inputs);
+                    panel.preview(inputs.get())
+                },
+            ),
+            button(
+                "Replace",
+                click = move |_| {
+                    autoclone!(panel);
+                    panel.apply()
+                },
+            ),
+            show_status(panel.status.clone()),
+        ),
+        show_preview(panel.clone(), panel.preview.clone()),
+    )
+}
+
+#[html]
+#[template(tag = span)]
+fn show_status(#[signal] status: Option<Arc<str>>) -> XElement {
+    let Some(status) = status else {
+        return tag(style::display = "none", style::visibility = "hidden");
+    };
+    tag("{status}")
+}
+
+#[html]
+#[template(tag = div)]
+fn show_preview(
+    panel: Ptr<ReplacePanel>,
+    #[signal] preview: Option<Arc<ReplacePreview>>,
+) -> XElement {
+    let Some(preview) = preview else {
+        return tag(class = replace_style::REPLACE_FILES);
+    };
+    let files = preview.files.iter().map(|file| {
+        let name = file.path.display().to_string();
+        let count = file.matches.len();
+        let rows = file
+            .matches
+            .iter()
+            .map(|m| match_row(&panel, &preview, &file.path, m));
+        div(
+            class = replace_style::REPLACE_FILE,
+            div(class = replace_style::REPLACE_FILE_NAME, "{name} ({count})"),
+            rows..,
+        )
+    });
+    tag(class = replace_style::REPLACE_FILES, files..)
+}
+
+#[html]
+fn match_row(
+    panel: &Ptr<ReplacePanel>,
+    preview: &Arc<ReplacePreview>,
+    path: &Arc<Path>,
+    m: &ReplaceMatch,
+) -> XElement {
+    let key = (path.clone(), m.start);
+    let checked = !preview.excluded.lock().unwrap().contains(&key);
+    let toggle = {
+        let preview = preview.clone();
+        move |event: web_sys::Event| {
+            let target = event.target().or_throw("target for toggle");
+            let target: HtmlInputElement = target.dyn_into().or_throw("input for toggle");
+            let mut excluded = preview.excluded.lock().unwrap();
+            if target.checked() {
+                excluded.remove(&key);
+            } else {
+                excluded.insert(key.clone());
+            }
+        }
+    };
+    let manager = panel.manager.clone();
+    let full_path = panel.base.join(path);
+    let offset = m.offset;
+    let ReplaceMatch {
+        line,
+        before,
+        matched,
+        after,
+        replacement,
+        ..
+    } = m;
+    div(
+        class = replace_style::REPLACE_MATCH,
+        input(
+            r#type = "checkbox",
+            change = toggle,
+            checked = checked.then(|| "checked".to_owned()),
+        ),
+        div(
+            class = replace_style::REPLACE_MATCH_TEXT,
+            click = move |_| manager.open_file_at(&full_path, offset),
+            span(class = replace_style::REPLACE_LINE, "{line}"),
+            span("{before}"),
+            span(class = replace_style::REPLACE_OLD, "{matched}"),
+            span(class = replace_style::REPLACE_NEW, "{replacement}"),
+            span("{after}"),
+        ),
+    )
+}
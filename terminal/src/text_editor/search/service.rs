@@ -11,7 +11,6 @@ use futures::StreamExt as _;
 use futures::TryStreamExt as _;
 use nameth::NamedEnumValues as _;
 use nameth::nameth;
-use regex::Regex;
 use server_fn::ServerFnError;
 use server_fn::codec::TextStream;
 use terrazzo::autoclone;
@@ -21,6 +20,7 @@ use tracing::info_span;
 use tracing::warn;
 use tracing_futures::Instrument as _;
 
+use self::query::SearchQuery;
 use self::tantivy::IndexSettings;
 use self::tantivy::SearchIndexError;
 use crate::api::client_address::ClientAddress;
@@ -29,6 +29,7 @@ use crate::backend::client_service::grpc_error::IsGrpcError;
 use crate::backend::client_service::remote_fn_service;
 use crate::text_editor::fsio::FileMetadata;
 use crate::text_editor::fsio::git::git_repo_root;
+use crate::text_editor::fsio::service::FsioError;
 use crate::utils::ndjson_utils::serialize_line;
 
 static MAX_RESULTS: usize = 1000;
@@ -36,10 +37,14 @@ static MAX_QUERY_TIME: Duration = Duration::from_secs(10);
 
 mod filenames;
 mod highlights;
+mod query;
+mod replace;
 mod tantivy;
 mod utils;
 
 pub use self::highlights::get_highlight_ranges;
+pub use self::replace::apply_replace;
+pub use self::replace::preview_replace;
 pub use self::tantivy::reconcile_touched_path;
 
 pub async fn search(
@@ -103,6 +108,18 @@ pub enum SearchError {
 
     #[error("[{n}] {0}", n = self.name())]
     SearchIndex(Arc<SearchIndexError>),
+
+    #[error("[{n}] Failed to read {0:?}: {1}", n = self.name())]
+    ReadFile(PathBuf, std::io::Error),
+
+    #[error("[{n}] Failed to write {0:?}: {1}", n = self.name())]
+    WriteFile(PathBuf, std::io::Error),
+
+    #[error("[{n}] {0}", n = self.name())]
+    Fsio(#[from] FsioError),
+
+    #[error("[{n}] The file {0:?} changed since the preview", n = self.name())]
+    FileChanged(PathBuf),
 }
 
 impl IsGrpcError for SearchError {
@@ -113,6 +130,10 @@ impl IsGrpcError for SearchError {
             Self::InvalidRepoRootPrefix { .. } => tonic::Code::InvalidArgument,
             Self::GitLsFilesError { .. } => tonic::Code::FailedPrecondition,
             Self::SearchIndex { .. } => tonic::Code::Internal,
+            Self::ReadFile { .. } => tonic::Code::Internal,
+            Self::WriteFile { .. } => tonic::Code::Internal,
+            Self::Fsio(error) => error.code(),
+            Self::FileChanged { .. } => tonic::Code::Aborted,
         }
     }
 }
@@ -123,10 +144,11 @@ async fn search_impl(
     settings: IndexSettings,
 ) -> Result<impl Stream<Item = Result<FileMetadata, SearchError>>, SearchError> {
     let repo_root = git_repo_root(base.clone()).ok_or_else(|| SearchError::NotGit(base.clone()))?;
+    let query = Arc::new(SearchQuery::parse(&input)?);
 
     let filename_search = futures::stream::once(filename_search(
         base.clone(),
-        input.clone(),
+        query.clone(),
         repo_root.clone(),
     ))
     .map(|filename_search| match filename_search {
@@ -139,7 +161,7 @@ async fn search_impl(
     .flatten()
     .boxed();
 
-    let tantivy_search = futures::stream::once(tantivy_search(base, input, settings, repo_root))
+    let tantivy_search = futures::stream::once(tantivy_search(base, query, settings, repo_root))
         .map(|tantivy_search| match tantivy_search {
             Ok(tantivy_search) => tantivy_search.boxed(),
             Err(error) => {
@@ -159,36 +181,38 @@ async fn search_impl(
 #[autoclone]
 async fn filename_search(
     base: Arc<Path>,
-    input: String,
+    query: Arc<SearchQuery>,
     repo_root: Arc<Path>,
 ) -> Result<impl Stream<Item = Result<FileMetadata, SearchError>> + Send, SearchError> {
-    let regex =
-        Arc::new(Regex::new(&input).map_err(|error| SearchError::Regex(input.clone(), error))?);
     Ok(self::utils::git_files(repo_root.clone(), base.clone())
         .await?
         .filter_map(move |path| {
             autoclone!(base);
-            filenames::process_path(base.clone(), path, regex.clone())
+            filenames::process_path(base.clone(), path, query.clone())
                 .map(|maybe| maybe.transpose())
         }))
 }
 
 async fn tantivy_search(
     base: Arc<Path>,
-    input: String,
+    query: Arc<SearchQuery>,
     settings: IndexSettings,
     repo_root: Arc<Path>,
 ) -> Result<impl Stream<Item = Result<FileMetadata, SearchError>> + Send, SearchError> {
     let index = tantivy::repository_index(repo_root.clone(), settings)
         .await
         .map_err(SearchError::SearchIndex)?;
+    let base_from_root = base
+        .strip_prefix(&repo_root)
+        .map_err(SearchError::InvalidRepoRootPrefix)?;
     let content_paths = index
-        .search(&input, MAX_RESULTS)
+        .search(&query, base_from_root, MAX_RESULTS)
         .map_err(|error| SearchError::SearchIndex(Arc::new(error)))?;
     index.refresh_if_stale();
     let content_matches =
         futures::stream::iter(content_paths.into_iter().map(Ok)).filter_map(move |path| {
-            process_index_path(repo_root.clone(), base.clone(), path).map(|maybe| maybe.transpose())
+            process_index_path(repo_root.clone(), base.clone(), query.clone(), path)
+                .map(|maybe| maybe.transpose())
         });
     Ok(content_matches)
 }
@@ -196,6 +220,7 @@ async fn tantivy_search(
 async fn process_index_path(
     repo_root: Arc<Path>,
     base: Arc<Path>,
+    query: Arc<SearchQuery>,
     path: Result<PathBuf, SearchError>,
 ) -> Result<Option<FileMetadata>, SearchError> {
     let path = path?;
@@ -210,6 +235,14 @@ async fn process_index_path(
         reconcile_touched_path(&full_path);
         return Ok(None);
     };
+    if query.needs_content() {
+        let Ok(content) = tokio::fs::read_to_string(&full_path).await else {
+            return Ok(None);
+        };
+        if !query.matches_content(&content) {
+            return Ok(None);
+        }
+    }
     let result = FileMetadata::make(
         path_from_base.display().to_string().into(),
         Ok(&metadata),
@@ -3,19 +3,19 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use regex::Regex;
 use tracing::debug;
 
 use super::SearchError;
+use super::query::SearchQuery;
 use crate::text_editor::fsio::FileMetadata;
 
 pub async fn process_path(
     base: Arc<Path>,
     path: Result<PathBuf, SearchError>,
-    regex: Arc<Regex>,
+    query: Arc<SearchQuery>,
 ) -> Result<Option<FileMetadata>, SearchError> {
     let path = path?;
-    if !regex.is_match(&path.to_string_lossy()) {
+    if !query.matches_path(&path) {
         debug!("Not match: {path:?}");
         return Ok(None);
     }
@@ -8,6 +8,8 @@ use tracing::debug;
 
 use super::IndexSettings;
 use super::SearchError;
+use super::query::Clause;
+use super::query::SearchQuery;
 use crate::api::client_address::ClientAddress;
 use crate::backend::client_service::grpc_error::GrpcError;
 use crate::backend::client_service::remote_fn_service;
@@ -47,6 +49,7 @@ async fn get_highlight_ranges_impl(
     input: String,
     settings: IndexSettings,
 ) -> Result<Vec<CursorPosition>, SearchError> {
+    let query = SearchQuery::parse(&input)?;
     let full_path = path.full_path();
     let repo_root =
         git_repo_root(&full_path).ok_or_else(|| SearchError::NotGit(path.base.clone()))?;
@@ -56,22 +59,29 @@ async fn get_highlight_ranges_impl(
     let text = tokio::fs::read_to_string(full_path)
         .await
         .map_err(|error| SearchError::SearchIndex(Arc::new(error.into())))?;
-    highlight_ranges(&index, &input, &text)
+    highlight_ranges(&index, &query, &text)
         .map_err(|error| SearchError::SearchIndex(Arc::new(error)))
 }
 
 fn highlight_ranges(
     repository: &super::tantivy::RepositoryIndex,
-    input: &str,
+    query: &SearchQuery,
     text: &str,
 ) -> Result<Vec<CursorPosition>, super::tantivy::SearchIndexError> {
-    highlight_ranges_in_index(
+    let mut ranges = highlight_ranges_in_index(
         &repository.index,
         &repository.reader,
         repository.fields.body,
-        input,
+        &SearchQuery::text(&query.include),
         text,
-    )
+    )?;
+    for clause in &query.include {
+        if let Clause::Regex(regex) = clause {
+            let matches = regex.find_iter(text).filter(|m| !m.is_empty());
+            ranges.extend(matches.filter_map(|m| byte_range_to_cursor_position(text, m.range())));
+        }
+    }
+    Ok(ranges)
 }
 
 fn highlight_ranges_in_index(
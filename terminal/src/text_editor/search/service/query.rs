@@ -0,0 +1,443 @@
+//! The syntax of search queries.
+//!
+//! - `word` and `"exact phrase"` match the content of files,
+//! - `/regex/` matches the content with a regular expression,
+//! - `path:src/**/*.rs` keeps the files under a folder or file that matches the glob,
+//! - `lang:rust` or `ext:rs` keeps the files with one of the extensions of the language,
+//! - `case:yes` makes the words, phrases and regular expressions case sensitive,
+//! - `-` before a clause excludes the files that match it.
+
+use std::path::Path;
+
+use regex::Regex;
+use regex::RegexBuilder;
+
+use super::SearchError;
+
+#[derive(Debug, Default)]
+pub struct SearchQuery {
+    pub include: Vec<Clause>,
+    pub exclude: Vec<Clause>,
+    pub case_sensitive: bool,
+}
+
+#[derive(Debug)]
+pub enum Clause {
+    /// A word or an exact phrase, with the regex that matches it in paths and content.
+    Text {
+        text: String,
+        phrase: bool,
+        regex: Regex,
+    },
+    Regex(Regex),
+    Path(PathGlob),
+    Extensions(Vec<String>),
+}
+
+/// A glob of `path:`, relative to the base folder of the search.
+#[derive(Debug)]
+pub struct PathGlob {
+    /// The glob as a regex without anchors, as Tantivy expects.
+    pattern: String,
+    anchored: bool,
+    regex: Regex,
+}
+
+/// A clause as written in the query.
+#[derive(Debug, PartialEq, Eq)]
+struct Token<'t> {
+    negated: bool,
+    key: Option<&'t str>,
+    value: &'t str,
+    quoted: bool,
+    slashed: bool,
+}
+
+impl Clause {
+    /// Words, phrases and regexes match the content, other clauses only match the path.
+    pub fn is_content(&self) -> bool {
+        matches!(self, Self::Text { .. } | Self::Regex(_))
+    }
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Result<Self, SearchError> {
+        let tokens = tokenize(input);
+        let case_sensitive = tokens
+            .iter()
+            .rev()
+            .find(|token| token.key == Some("case"))
+            .is_some_and(|token| matches!(token.value, "yes" | "true" | "on"));
+        let mut query = Self {
+            case_sensitive,
+            ..Self::default()
+        };
+        for token in tokens {
+            let Some(clause) = query.clause(&token)? else {
+                continue;
+            };
+            if token.negated {
+                query.exclude.push(clause);
+            } else {
+                query.include.push(clause);
+            }
+        }
+        Ok(query)
+    }
+
+    fn clause(&self, token: &Token) -> Result<Option<Clause>, SearchError> {
+        let value = token.value;
+        if value.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(match token.key {
+            Some("case") => return Ok(None),
+            Some("path") => Clause::Path(PathGlob::new(value)?),
+            Some("ext") => Clause::Extensions(vec![value.trim_start_matches('.').to_owned()]),
+            Some("lang") => Clause::Extensions(language_extensions(value)),
+            _ if token.slashed => Clause::Regex(self.regex(value)?),
+            _ if token.quoted => Clause::Text {
+                text: value.to_owned(),
+                phrase: true,
+                regex: self.regex(&regex::escape(value))?,
+            },
+            _ => Clause::Text {
+                text: value.to_owned(),
+                phrase: false,
+                regex: self
+                    .regex(value)
+                    .or_else(|_| self.regex(&regex::escape(value)))?,
+            },
+        }))
+    }
+
+    fn regex(&self, pattern: &str) -> Result<Regex, SearchError> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(!self.case_sensitive)
+            .build()
+            .map_err(|error| SearchError::Regex(pattern.to_owned(), error))
+    }
+
+    /// The words and phrases of the content, in the syntax of the Tantivy query parser.
+    pub fn text(clauses: &[Clause]) -> String {
+        let texts = clauses.iter().filter_map(|clause| match clause {
+            Clause::Text {
+                text, phrase: true, ..
+            } => Some(format!("\"{}\"", text.replace('"', " "))),
+            Clause::Text { text, .. } => Some(text.clone()),
+            _ => None,
+        });
+        texts.collect::<Vec<_>>().join(" ")
+    }
+
+    /// Whether the files must be read to check the clauses the index can't, like regexes.
+    pub fn needs_content(&self) -> bool {
+        let needs_content = |clause: &Clause| match clause {
+            Clause::Text { .. } => self.case_sensitive,
+            Clause::Regex(_) => true,
+            Clause::Path(_) | Clause::Extensions(_) => false,
+        };
+        self.include.iter().any(needs_content) || self.exclude.iter().any(needs_content)
+    }
+
+    /// Checks the clauses that the index can't, see [Self::needs_content].
+    pub fn matches_content(&self, content: &str) -> bool {
+        let is_match = |clause: &Clause| match clause {
+            Clause::Text { regex, .. } if self.case_sensitive => Some(regex.is_match(content)),
+            Clause::Regex(regex) => Some(regex.is_match(content)),
+            _ => None,
+        };
+        self.include
+            .iter()
+            .all(|clause| is_match(clause) != Some(false))
+            && self
+                .exclude
+                .iter()
+                .all(|clause| is_match(clause) != Some(true))
+    }
+
+    /// Matches the path of a file relative to the base folder, for the search by file name.
+    pub fn matches_path(&self, path: &Path) -> bool {
+        let path_str = path.to_string_lossy();
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy());
+        let is_match = |clause: &Clause| match clause {
+            Clause::Text { regex, .. } | Clause::Regex(regex) => regex.is_match(&path_str),
+            Clause::Path(glob) => glob.regex.is_match(&path_str),
+            Clause::Extensions(extensions) => extension
+                .as_ref()
+                .is_some_and(|extension| extensions.iter().any(|e| e == extension)),
+        };
+        // Files match any of the included paths and any of the included extensions.
+        let any_match = |filter: fn(&Clause) -> bool| {
+            let mut clauses = self
+                .include
+                .iter()
+                .filter(|clause| filter(clause))
+                .peekable();
+            clauses.peek().is_none() || clauses.any(is_match)
+        };
+        self.include
+            .iter()
+            .filter(|clause| clause.is_content())
+            .all(is_match)
+            && any_match(|clause| matches!(clause, Clause::Path(_)))
+            && any_match(|clause| matches!(clause, Clause::Extensions(_)))
+            && !self.exclude.iter().any(is_match)
+    }
+}
+
+impl PathGlob {
+    fn new(glob: &str) -> Result<Self, SearchError> {
+        // Globs without a slash match a file or folder at any depth, like in .gitignore.
+        let anchored = glob.trim_end_matches('/').contains('/');
+        let glob = glob.trim_matches('/');
+        let mut pattern = String::new();
+        let mut chars = glob.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    if chars.next_if_eq(&'/').is_some() {
+                        pattern.push_str("(.*/)?");
+                    } else {
+                        pattern.push_str(".*");
+                    }
+                }
+                '*' => pattern.push_str("[^/]*"),
+                '?' => pattern.push_str("[^/]"),
+                c => pattern.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        // The files under a matching folder match too.
+        pattern.push_str("(/.*)?");
+        let regex = if anchored {
+            format!("^{pattern}$")
+        } else {
+            format!("^(.*/)?{pattern}$")
+        };
+        let regex =
+            Regex::new(&regex).map_err(|error| SearchError::Regex(glob.to_owned(), error))?;
+        Ok(Self {
+            pattern,
+            anchored,
+            regex,
+        })
+    }
+
+    /// The pattern of paths relative to the root of the repository.
+    pub fn index_pattern(&self, base_from_root: &Path) -> String {
+        let base = base_from_root.to_string_lossy();
+        let base = if base.is_empty() {
+            String::default()
+        } else {
+            format!("{}/", regex::escape(&base))
+        };
+        if self.anchored {
+            format!("{base}{}", self.pattern)
+        } else {
+            format!("{base}(.*/)?{}", self.pattern)
+        }
+    }
+}
+
+fn language_extensions(language: &str) -> Vec<String> {
+    let extensions: &[&str] = match language.to_lowercase().as_str() {
+        "rust" => &["rs"],
+        "python" => &["py", "pyi"],
+        "javascript" | "js" => &["js", "mjs", "cjs", "jsx"],
+        "typescript" | "ts" => &["ts", "mts", "cts", "tsx"],
+        "c" => &["c", "h"],
+        "cpp" | "c++" => &["cpp", "cc", "cxx", "hpp", "hh", "hxx", "h"],
+        "java" => &["java"],
+        "go" => &["go"],
+        "shell" | "sh" | "bash" => &["sh", "bash"],
+        "markdown" | "md" => &["md", "markdown"],
+        "html" => &["html", "htm"],
+        "css" => &["css", "scss"],
+        "yaml" | "yml" => &["yaml", "yml"],
+        "toml" => &["toml"],
+        "json" => &["json"],
+        _ => return vec![language.to_owned()],
+    };
+    extensions
+        .iter()
+        .map(|extension| extension.to_string())
+        .collect()
+}
+
+fn tokenize(input: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut rest = input;
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return tokens;
+        }
+        let negated = rest
+            .strip_prefix('-')
+            .and_then(|rest| rest.chars().next())
+            .is_some_and(|c| !c.is_whitespace());
+        if negated {
+            rest = &rest[1..];
+        }
+        let key = rest
+            .split_once(':')
+            .map(|(key, _)| key)
+            .filter(|key| matches!(*key, "path" | "lang" | "ext" | "case"));
+        if let Some(key) = key {
+            rest = &rest[key.len() + 1..];
+        }
+        let (value, quoted, slashed);
+        if let Some(quoted_value) = rest.strip_prefix('"') {
+            let end = quoted_value.find('"').unwrap_or(quoted_value.len());
+            (value, quoted, slashed) = (&quoted_value[..end], true, false);
+            rest = quoted_value.get(end + 1..).unwrap_or_default();
+        } else if let Some(regex) = rest.strip_prefix('/').filter(|_| key.is_none())
+            && let Some(end) = regex_end(regex)
+        {
+            (value, quoted, slashed) = (&regex[..end], false, true);
+            rest = &regex[end + 1..];
+        } else {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            (value, quoted, slashed) = (&rest[..end], false, false);
+            rest = &rest[end..];
+        }
+        tokens.push(Token {
+            negated,
+            key,
+            value,
+            quoted,
+            slashed,
+        });
+    }
+}
+
+/// The closing slash of a regex is followed by a space or the end of the query.
+fn regex_end(regex: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in regex.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '/' if regex[i + 1..]
+                .chars()
+                .next()
+                .is_none_or(char::is_whitespace) =>
+            {
+                return Some(i);
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::Clause;
+    use super::SearchQuery;
+    use super::Token;
+
+    #[test]
+    fn tokenize() {
+        let token = |negated, key, value, quoted, slashed| Token {
+            negated,
+            key,
+            value,
+            quoted,
+            slashed,
+        };
+        assert_eq!(
+            vec![
+                token(false, None, "std::io", false, false),
+                token(true, Some("path"), "tests/**", false, false),
+                token(false, None, "exact phrase", true, false),
+                token(true, None, r"fn \w+/", false, true),
+                token(false, Some("case"), "yes", false, false),
+                token(false, None, "/usr/bin", false, false),
+                token(false, None, "-", false, false),
+                token(false, None, "x", false, false),
+            ],
+            super::tokenize(
+                r#"std::io -path:tests/** "exact phrase" -/fn \w+// case:yes /usr/bin - x"#
+            )
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let query = SearchQuery::parse(r#"Foo "a b" -/x+/ lang:rust -ext:.md case:yes"#).unwrap();
+        assert!(query.case_sensitive);
+        assert!(matches!(
+            query.include.as_slice(),
+            [
+                Clause::Text { text: foo, phrase: false, .. },
+                Clause::Text { text: ab, phrase: true, .. },
+                Clause::Extensions(rs),
+            ] if foo == "Foo" && ab == "a b" && rs == &["rs"]
+        ));
+        assert!(matches!(
+            query.exclude.as_slice(),
+            [Clause::Regex(x), Clause::Extensions(md)] if x.as_str() == "x+" && md == &["md"]
+        ));
+        assert_eq!(r#"Foo "a b""#, SearchQuery::text(&query.include));
+        assert!(query.needs_content());
+        assert!(query.matches_content("Foo a b"));
+        assert!(!query.matches_content("foo a b"));
+        assert!(!query.matches_content("Foo a b x"));
+
+        let query = SearchQuery::parse("foo").unwrap();
+        assert!(!query.needs_content());
+        assert!(SearchQuery::parse("/(/").is_err());
+    }
+
+    #[test]
+    fn path_glob() {
+        let matches = |glob: &str, path: &str| {
+            let query = SearchQuery::parse(&format!("path:{glob}")).unwrap();
+            query.matches_path(Path::new(path))
+        };
+        assert!(matches("src", "src/lib.rs"));
+        assert!(matches("src", "a/src/lib.rs"));
+        assert!(!matches("src", "a/srcs/lib.rs"));
+        assert!(matches("src/*.rs", "src/lib.rs"));
+        assert!(!matches("src/*.rs", "src/a/lib.rs"));
+        assert!(!matches("src/*.rs", "a/src/lib.rs"));
+        assert!(matches("src/**/*.rs", "src/lib.rs"));
+        assert!(matches("src/**/*.rs", "src/a/b/lib.rs"));
+        assert!(matches("*.rs", "a/b/lib.rs"));
+        assert!(matches("/lib.r?", "lib.rs"));
+        assert!(!matches("/lib.r?", "a/lib.rs"));
+
+        let query = SearchQuery::parse("path:src/*.rs").unwrap();
+        let [Clause::Path(glob)] = query.include.as_slice() else {
+            panic!()
+        };
+        assert_eq!(
+            r"a/b/src/[^/]*\.rs(/.*)?",
+            glob.index_pattern(Path::new("a/b"))
+        );
+        assert_eq!(r"src/[^/]*\.rs(/.*)?", glob.index_pattern(Path::new("")));
+    }
+
+    #[test]
+    fn matches_path() {
+        let matches = |query: &str, path: &str| {
+            let query = SearchQuery::parse(query).unwrap();
+            query.matches_path(Path::new(path))
+        };
+        assert!(matches("", "src/lib.rs"));
+        assert!(matches("LIB", "src/lib.rs"));
+        assert!(!matches("LIB case:yes", "src/lib.rs"));
+        assert!(matches("li.", "src/lib.rs"));
+        assert!(matches("lib lang:rust", "src/lib.rs"));
+        assert!(!matches("lib lang:python", "src/lib.rs"));
+        assert!(matches("lang:python lang:rust", "src/lib.rs"));
+        assert!(!matches("lib -path:src", "src/lib.rs"));
+        assert!(matches("lib(", "src/lib(1).rs"));
+    }
+}
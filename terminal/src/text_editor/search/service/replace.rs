@@ -0,0 +1,426 @@
+use std::collections::HashSet;
+use std::hash::DefaultHasher;
+use std::hash::Hash as _;
+use std::hash::Hasher as _;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use futures::TryStreamExt as _;
+use regex::Regex;
+use server_fn::ServerFnError;
+use tracing::debug;
+
+use super::IndexSettings;
+use super::MAX_RESULTS;
+use super::SearchError;
+use super::query::Clause;
+use super::query::SearchQuery;
+use super::reconcile_touched_path;
+use crate::api::client_address::ClientAddress;
+use crate::backend::client_service::grpc_error::GrpcError;
+use crate::backend::client_service::remote_fn_service;
+use crate::text_editor::fsio::git::git_repo_root;
+use crate::text_editor::fsio::service::StagedFile;
+use crate::text_editor::fsio::service::stage_file;
+use crate::text_editor::search::api::ReplaceEdit;
+use crate::text_editor::search::api::ReplaceFile;
+use crate::text_editor::search::api::ReplaceMatch;
+use crate::text_editor::search::api::ReplaceQuery;
+
+/// The preview stops listing files after that many matches.
+static MAX_MATCHES: usize = 10_000;
+
+/// The number of characters of the line shown before and after a match.
+static MAX_CONTEXT: usize = 80;
+
+pub async fn preview_replace(
+    remote: ClientAddress,
+    base: Arc<Path>,
+    query: ReplaceQuery,
+) -> Result<Vec<ReplaceFile>, ServerFnError> {
+    debug!(%remote, "Calling preview_replace({base:?}, {query:?})");
+    Ok(PREVIEW_REPLACE_FN.call(remote, (base, query)).await?)
+}
+
+remote_fn_service::unary::declare_remote_fn!(
+    PREVIEW_REPLACE_FN,
+    "texteditor.search.preview_replace",
+    (Arc<Path>, ReplaceQuery),
+    Vec<ReplaceFile>,
+    |server, (base, query)| {
+        let settings = server.config().server.with(|server| IndexSettings {
+            cache_dir: server.tantivy_cache.clone(),
+            refresh_interval: server.search_index_refresh,
+            stale_after: server.search_index_stale_after,
+        });
+        async move {
+            preview_replace_impl(base, query, settings)
+                .await
+                .map_err(GrpcError::from)
+        }
+    }
+);
+
+pub async fn apply_replace(
+    remote: ClientAddress,
+    base: Arc<Path>,
+    query: ReplaceQuery,
+    edits: Vec<ReplaceEdit>,
+) -> Result<usize, ServerFnError> {
+    debug!(%remote, "Calling apply_replace({base:?}, {query:?}, {} files)", edits.len());
+    Ok(APPLY_REPLACE_FN.call(remote, (base, query, edits)).await?)
+}
+
+remote_fn_service::unary::declare_remote_fn!(
+    APPLY_REPLACE_FN,
+    "texteditor.search.apply_replace",
+    (Arc<Path>, ReplaceQuery, Vec<ReplaceEdit>),
+    usize,
+    |_server, (base, query, edits)| async move {
+        apply_replace_impl(base, query, edits)
+            .await
+            .map_err(GrpcError::from)
+    }
+);
+
+async fn preview_replace_impl(
+    base: Arc<Path>,
+    query: ReplaceQuery,
+    settings: IndexSettings,
+) -> Result<Vec<ReplaceFile>, SearchError> {
+    let regex = make_regex(&query.pattern)?;
+    let search_query = SearchQuery::parse(&query.query)?;
+    let mut paths = candidate_files(&base, &search_query, settings).await?;
+    paths.sort();
+    let mut files = vec![];
+    let mut count = 0;
+    for path in paths {
+        let Some(content) = read_text(&base.join(&path)).await? else {
+            continue;
+        };
+        if search_query.needs_content() && !search_query.matches_content(&content) {
+            continue;
+        }
+        let matches = find_matches(&regex, &query.replacement, &content);
+        if matches.is_empty() {
+            continue;
+        }
+        count += matches.len();
+        files.push(ReplaceFile {
+            path: path.into(),
+            version: version(&content),
+            matches,
+        });
+        if count >= MAX_MATCHES {
+            break;
+        }
+    }
+    Ok(files)
+}
+
+async fn apply_replace_impl(
+    base: Arc<Path>,
+    query: ReplaceQuery,
+    edits: Vec<ReplaceEdit>,
+) -> Result<usize, SearchError> {
+    let regex = make_regex(&query.pattern)?;
+
+    // Nothing is written unless all the files are still at the version of the preview.
+    let mut changes = vec![];
+    for ReplaceEdit {
+        path,
+        version: expected,
+        starts,
+    } in edits
+    {
+        if starts.is_empty() {
+            continue;
+        }
+        let path = base.join(&path);
+        let Some(content) = read_text(&path).await? else {
+            return Err(SearchError::FileChanged(path));
+        };
+        if version(&content) != expected {
+            return Err(SearchError::FileChanged(path));
+        }
+        let starts = starts.into_iter().collect::<HashSet<_>>();
+        let (content, replaced) = replace_matches(&regex, &query.replacement, &content, &starts);
+        if replaced != starts.len() {
+            return Err(SearchError::FileChanged(path));
+        }
+        changes.push((path, content));
+    }
+
+    // The new contents are all written to temp files before any file is replaced.
+    let mut staged = vec![];
+    for (path, content) in changes {
+        match stage(&path, content).await {
+            Ok(file) => staged.push((path, file)),
+            Err(error) => {
+                discard(staged).await;
+                return Err(error);
+            }
+        }
+    }
+
+    let count = staged.len();
+    let mut staged = staged.into_iter();
+    while let Some((path, file)) = staged.next() {
+        if let Err(error) = file.commit().await {
+            discard(staged).await;
+            return Err(error.into());
+        }
+        reconcile_touched_path(&path);
+    }
+    Ok(count)
+}
+
+/// The files to look into, relative to `base`.
+async fn candidate_files(
+    base: &Arc<Path>,
+    query: &SearchQuery,
+    settings: IndexSettings,
+) -> Result<Vec<PathBuf>, SearchError> {
+    let repo_root = git_repo_root(base).ok_or_else(|| SearchError::NotGit(base.clone()))?;
+    if !query.include.iter().any(Clause::is_content) {
+        let paths: Vec<PathBuf> = super::utils::git_files(repo_root, base.clone())
+            .await?
+            .try_collect()
+            .await?;
+        return Ok(paths
+            .into_iter()
+            .filter(|path| query.matches_path(path))
+            .collect());
+    }
+    let index = super::tantivy::repository_index(repo_root.clone(), settings)
+        .await
+        .map_err(SearchError::SearchIndex)?;
+    let base_from_root = base
+        .strip_prefix(&repo_root)
+        .map_err(SearchError::InvalidRepoRootPrefix)?;
+    let paths = index
+        .search(query, base_from_root, MAX_RESULTS)
+        .map_err(|error| SearchError::SearchIndex(Arc::new(error)))?;
+    index.refresh_if_stale();
+    Ok(paths
+        .into_iter()
+        .filter_map(|path| Some(path.strip_prefix(base_from_root).ok()?.to_owned()))
+        .collect())
+}
+
+fn make_regex(pattern: &str) -> Result<Regex, SearchError> {
+    Regex::new(pattern).map_err(|error| SearchError::Regex(pattern.to_owned(), error))
+}
+
+/// Reads the file, or returns `None` if it is gone or isn't text.
+async fn read_text(path: &Path) -> Result<Option<String>, SearchError> {
+    let content = match tokio::fs::read(path).await {
+        Ok(content) => content,
+        Err(error) if error.kind() == ErrorKind::NotFound => {
+            reconcile_touched_path(path);
+            return Ok(None);
+        }
+        Err(error) => return Err(SearchError::ReadFile(path.to_owned(), error)),
+    };
+    Ok(String::from_utf8(content).ok())
+}
+
+fn version(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn find_matches(regex: &Regex, replacement: &str, content: &str) -> Vec<ReplaceMatch> {
+    let mut matches = vec![];
+    let mut offset = 0;
+    let mut line = 1;
+    let mut last = 0;
+    for captures in regex.captures_iter(content) {
+        let Some(matched) = captures.get(0) else {
+            continue;
+        };
+        let skipped = &content[last..matched.start()];
+        offset += skipped.encode_utf16().count() as u32;
+        line += skipped.matches('\n').count() as u32;
+        last = matched.start();
+
+        let line_start = content[..matched.start()].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = content[matched.end()..]
+            .find('\n')
+            .map_or(content.len(), |i| matched.end() + i);
+        let before = &content[line_start..matched.start()];
+        let after = content[matched.end()..line_end].trim_end_matches('\r');
+        let mut expanded = String::new();
+        captures.expand(replacement, &mut expanded);
+        matches.push(ReplaceMatch {
+            start: matched.start(),
+            offset,
+            line,
+            before: context_before(before).to_owned(),
+            matched: matched.as_str().to_owned(),
+            after: context_after(after).to_owned(),
+            replacement: expanded,
+        });
+    }
+    matches
+}
+
+/// The end of the line before a match.
+fn context_before(text: &str) -> &str {
+    let start = text.char_indices().rev().nth(MAX_CONTEXT - 1);
+    &text[start.map_or(0, |(i, _)| i)..]
+}
+
+/// The start of the line after a match.
+fn context_after(text: &str) -> &str {
+    let end = text.char_indices().nth(MAX_CONTEXT);
+    &text[..end.map_or(text.len(), |(i, _)| i)]
+}
+
+/// Replaces the matches that start at one of the `starts`.
+///
+/// Returns the new content and the number of matches that were replaced.
+fn replace_matches(
+    regex: &Regex,
+    replacement: &str,
+    content: &str,
+    starts: &HashSet<usize>,
+) -> (String, usize) {
+    let mut result = String::with_capacity(content.len());
+    let mut replaced = 0;
+    let mut last = 0;
+    for captures in regex.captures_iter(content) {
+        let Some(matched) = captures.get(0) else {
+            continue;
+        };
+        if !starts.contains(&matched.start()) {
+            continue;
+        }
+        result.push_str(&content[last..matched.start()]);
+        captures.expand(replacement, &mut result);
+        last = matched.end();
+        replaced += 1;
+    }
+    result.push_str(&content[last..]);
+    (result, replaced)
+}
+
+/// Writes the content to a temp file next to the file, with the same permissions.
+async fn stage(path: &Path, content: String) -> Result<StagedFile, SearchError> {
+    let permissions = tokio::fs::metadata(path)
+        .await
+        .map_err(|error| SearchError::WriteFile(path.to_owned(), error))?
+        .permissions();
+    Ok(stage_file(path, permissions, content.into_bytes()).await?)
+}
+
+async fn discard(staged: impl IntoIterator<Item = (PathBuf, StagedFile)>) {
+    for (_, file) in staged {
+        file.discard().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::Arc;
+
+    use regex::Regex;
+
+    use super::super::SearchError;
+    use crate::text_editor::search::api::ReplaceEdit;
+    use crate::text_editor::search::api::ReplaceQuery;
+
+    #[test]
+    fn find_matches() {
+        let regex = Regex::new(r"(\w+)\.unwrap\(\)").unwrap();
+        let content = "let a = 🦀;\r\nlet b = x.unwrap() + y.unwrap();\n";
+        let matches = super::find_matches(&regex, "$1?", content);
+        let actual = matches
+            .iter()
+            .map(|m| {
+                let range = (m.start, m.offset, m.line);
+                (range, &*m.before, &*m.matched, &*m.after, &*m.replacement)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                (
+                    (23, 21, 2),
+                    "let b = ",
+                    "x.unwrap()",
+                    " + y.unwrap();",
+                    "x?"
+                ),
+                (
+                    (36, 34, 2),
+                    "let b = x.unwrap() + ",
+                    "y.unwrap()",
+                    ";",
+                    "y?"
+                ),
+            ],
+            actual
+        );
+    }
+
+    #[test]
+    fn replace_matches() {
+        let regex = Regex::new(r"(\w+)\.unwrap\(\)").unwrap();
+        let content = "a.unwrap(); b.unwrap(); c.unwrap();";
+        let starts = [0, 24].into();
+        let actual = super::replace_matches(&regex, "$1?", content, &starts);
+        assert_eq!(("a?; b.unwrap(); c?;".to_owned(), 2), actual);
+    }
+
+    #[tokio::test]
+    async fn apply_replace() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let base: Arc<Path> = Arc::from(tempdir.path());
+        std::fs::write(tempdir.path().join("a.txt"), "one two one").unwrap();
+        std::fs::write(tempdir.path().join("b.txt"), "one").unwrap();
+        let query = ReplaceQuery {
+            query: String::default(),
+            pattern: "one".into(),
+            replacement: "1".into(),
+        };
+        let edit = |path: &str, content: &str, starts: Vec<usize>| ReplaceEdit {
+            path: Arc::from(Path::new(path)),
+            version: super::version(content),
+            starts,
+        };
+
+        let count = super::apply_replace_impl(
+            base.clone(),
+            query.clone(),
+            vec![
+                edit("a.txt", "one two one", vec![8]),
+                edit("b.txt", "one", vec![0]),
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(2, count);
+        let read = |name| std::fs::read_to_string(tempdir.path().join(name)).unwrap();
+        assert_eq!("one two 1", read("a.txt"));
+        assert_eq!("1", read("b.txt"));
+
+        // The second edit is stale, so neither file is changed.
+        let error = super::apply_replace_impl(
+            base,
+            query,
+            vec![
+                edit("a.txt", "one two 1", vec![0]),
+                edit("b.txt", "one", vec![0]),
+            ],
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(error, SearchError::FileChanged { .. }));
+        assert_eq!("one two 1", read("a.txt"));
+        assert_eq!(2, std::fs::read_dir(tempdir.path()).unwrap().count());
+    }
+}
@@ -31,8 +31,14 @@ use tantivy::collector::TopDocs;
 use tantivy::directory::MmapDirectory;
 use tantivy::doc;
 use tantivy::query::AllQuery;
+use tantivy::query::BooleanQuery;
+use tantivy::query::Occur;
+use tantivy::query::Query;
 use tantivy::query::QueryParser;
+use tantivy::query::RegexQuery;
+use tantivy::query::TermQuery;
 use tantivy::schema::Field;
+use tantivy::schema::IndexRecordOption;
 use tantivy::schema::STORED;
 use tantivy::schema::STRING;
 use tantivy::schema::Schema;
@@ -46,6 +52,9 @@ use tracing::info;
 use tracing::info_span;
 use tracing::warn;
 
+use super::query::Clause;
+use super::query::PathGlob;
+use super::query::SearchQuery;
 use crate::text_editor::fsio;
 
 const INDEX_WRITER_MEMORY_BUDGET: usize = 50_000_000;
@@ -69,6 +78,7 @@ pub struct IndexSettings {
 pub(super) struct IndexFields {
     path: Field,
     pub(super) body: Field,
+    extension: Field,
     size: Field,
     modified: Field,
 }
@@ -184,8 +194,7 @@ impl RepositoryIndex {
         tokio::fs::create_dir_all(&cache_dir).await?;
 
         let (schema, fields) = make_schema();
-        let directory = MmapDirectory::open(&cache_dir)?;
-        let index = Index::open_or_create(directory, schema)?;
+        let index = open_index(&cache_dir, schema).await?;
         let reader = index.reader()?;
         let fingerprints = load_fingerprints(&reader, fields)?;
         let writer = index.writer(INDEX_WRITER_MEMORY_BUDGET)?;
@@ -226,15 +235,31 @@ impl RepositoryIndex {
         Ok(repository)
     }
 
-    pub fn search(&self, input: &str, limit: usize) -> Result<Vec<PathBuf>, SearchIndexError> {
-        let searcher = self.reader.searcher();
-        let mut parser = QueryParser::for_index(&self.index, vec![self.fields.body]);
-        parser.set_conjunction_by_default();
-        let (query, errors) = parser.parse_query_lenient(input);
-        for error in errors {
-            debug!(%error, "Ignoring Tantivy query parser error");
+    /// The paths of the files whose content matches the query, relative to the root.
+    ///
+    /// The paths of the query are relative to `base_from_root`. If the query
+    /// [needs the content](SearchQuery::needs_content), all the files that may match are returned.
+    pub fn search(
+        &self,
+        query: &SearchQuery,
+        base_from_root: &Path,
+        limit: usize,
+    ) -> Result<Vec<PathBuf>, SearchIndexError> {
+        // Files are matched by name when the query has no words nor regexes.
+        if !query.include.iter().any(Clause::is_content) {
+            return Ok(vec![]);
         }
-        let documents = searcher.search(&query, &TopDocs::with_limit(limit).order_by_score())?;
+        let searcher = self.reader.searcher();
+        let index_query = make_query(&self.index, self.fields, query, base_from_root)?;
+        let limit = if query.needs_content() {
+            searcher.num_docs() as usize
+        } else {
+            limit
+        };
+        let documents = searcher.search(
+            &index_query,
+            &TopDocs::with_limit(limit.max(1)).order_by_score(),
+        )?;
         documents
             .into_iter()
             .map(|(_, address)| {
@@ -413,10 +438,28 @@ fn watch_directories(root: &Path, git_files: &HashSet<PathBuf>) -> HashSet<PathB
     directories
 }
 
+/// Opens the index, or creates it again if it was created with an older schema.
+async fn open_index(cache_dir: &Path, schema: Schema) -> Result<Index, SearchIndexError> {
+    let directory = MmapDirectory::open(cache_dir)?;
+    let exists = Index::exists(&directory).map_err(tantivy::TantivyError::from)?;
+    if exists && Index::open(directory.clone())?.schema() != schema {
+        info!(
+            ?cache_dir,
+            "The schema changed, rebuilding the search index"
+        );
+        drop(directory);
+        tokio::fs::remove_dir_all(cache_dir).await?;
+        tokio::fs::create_dir_all(cache_dir).await?;
+        return Ok(Index::create_in_dir(cache_dir, schema)?);
+    }
+    Ok(Index::open_or_create(directory, schema)?)
+}
+
 fn make_schema() -> (Schema, IndexFields) {
     let mut schema = Schema::builder();
     let path = schema.add_text_field("path", STRING | STORED);
     let body = schema.add_text_field("body", TEXT);
+    let extension = schema.add_text_field("extension", STRING);
     let size = schema.add_u64_field("size", STORED);
     let modified = schema.add_u64_field("modified", STORED);
     (
@@ -424,12 +467,83 @@ fn make_schema() -> (Schema, IndexFields) {
         IndexFields {
             path,
             body,
+            extension,
             size,
             modified,
         },
     )
 }
 
+fn make_query(
+    index: &Index,
+    fields: IndexFields,
+    query: &SearchQuery,
+    base_from_root: &Path,
+) -> Result<Box<dyn Query>, SearchIndexError> {
+    let mut parser = QueryParser::for_index(index, vec![fields.body]);
+    parser.set_conjunction_by_default();
+    let parse = |clauses: &[Clause]| {
+        let (query, errors) = parser.parse_query_lenient(&SearchQuery::text(clauses));
+        for error in errors {
+            debug!(%error, "Ignoring Tantivy query parser error");
+        }
+        query
+    };
+    let path_query = |glob: &PathGlob| -> Result<Box<dyn Query>, SearchIndexError> {
+        let pattern = glob.index_pattern(base_from_root);
+        Ok(Box::new(RegexQuery::from_pattern(&pattern, fields.path)?))
+    };
+    let extensions_query = |extensions: &[String]| -> Box<dyn Query> {
+        let extensions = extensions.iter().map(|extension| {
+            let term = Term::from_field_text(fields.extension, extension);
+            let query: Box<dyn Query> = Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+            (Occur::Should, query)
+        });
+        Box::new(BooleanQuery::new(extensions.collect()))
+    };
+
+    let mut clauses = vec![];
+    if query
+        .include
+        .iter()
+        .any(|clause| matches!(clause, Clause::Text { .. }))
+    {
+        clauses.push((Occur::Must, parse(&query.include)));
+    }
+    // Files match any of the included paths and any of the included extensions.
+    let mut paths = vec![];
+    let mut extensions = vec![];
+    for clause in &query.include {
+        match clause {
+            Clause::Path(glob) => paths.push((Occur::Should, path_query(glob)?)),
+            Clause::Extensions(clause) => {
+                extensions.push((Occur::Should, extensions_query(clause)))
+            }
+            Clause::Text { .. } | Clause::Regex(_) => {}
+        }
+    }
+    for alternatives in [paths, extensions] {
+        if !alternatives.is_empty() {
+            clauses.push((Occur::Must, Box::new(BooleanQuery::new(alternatives))));
+        }
+    }
+    for clause in &query.exclude {
+        let excluded = match clause {
+            // The index ignores the case, so the content is checked instead.
+            Clause::Text { .. } if query.case_sensitive => continue,
+            Clause::Text { .. } => parse(std::slice::from_ref(clause)),
+            Clause::Regex(_) => continue,
+            Clause::Path(glob) => path_query(glob)?,
+            Clause::Extensions(clause) => extensions_query(clause),
+        };
+        clauses.push((Occur::MustNot, excluded));
+    }
+    if !clauses.iter().any(|(occur, _)| *occur == Occur::Must) {
+        clauses.push((Occur::Must, Box::new(AllQuery)));
+    }
+    Ok(Box::new(BooleanQuery::new(clauses)))
+}
+
 fn load_fingerprints(
     reader: &IndexReader,
     fields: IndexFields,
@@ -678,12 +792,16 @@ async fn reconcile_one(
         Err(error) => return Err(error.into()),
     };
     writer.delete_term(Term::from_field_text(fields.path, &path.to_string_lossy()));
-    writer.add_document(tantivy::doc!(
+    let mut document = tantivy::doc!(
         fields.path => path.to_string_lossy().into_owned(),
         fields.body => body,
         fields.size => fingerprint.size,
         fields.modified => fingerprint.modified,
-    ))?;
+    );
+    if let Some(extension) = path.extension() {
+        document.add_text(fields.extension, extension.to_string_lossy());
+    }
+    writer.add_document(document)?;
     fingerprints.insert(path.to_owned(), fingerprint);
     Ok(true)
 }
@@ -716,6 +834,13 @@ mod tests {
     use std::path::Path;
     use std::path::PathBuf;
 
+    use tantivy::TantivyDocument;
+    use tantivy::collector::TopDocs;
+    use tantivy::doc;
+    use tantivy::schema::Value as _;
+
+    use super::super::query::SearchQuery;
+
     #[test]
     fn watch_only_directories_containing_git_files() {
         let git_files = HashSet::from([
@@ -733,4 +858,76 @@ mod tests {
             watch_directories(Path::new("/repo"), &git_files),
         );
     }
+
+    #[test]
+    fn query_filters() {
+        let (schema, fields) = super::make_schema();
+        let index = tantivy::Index::create_in_ram(schema);
+        let mut writer: tantivy::IndexWriter =
+            index.writer_with_num_threads(1, 20_000_000).unwrap();
+        for (path, body) in [
+            ("ws/src/lib.rs", "fn main() { Foo }"),
+            ("ws/src/a/b.rs", "fn main() { foo }"),
+            ("ws/README.md", "fn main"),
+            ("ws/tests/t.rs", "fn main"),
+            ("other/x.rs", "fn main"),
+        ] {
+            let mut document = tantivy::doc!(
+                fields.path => path,
+                fields.body => body,
+                fields.size => 0u64,
+                fields.modified => 0u64,
+            );
+            if let Some(extension) = Path::new(path).extension() {
+                document.add_text(fields.extension, extension.to_string_lossy());
+            }
+            writer.add_document(document).unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let search = |query: &str| {
+            let query = SearchQuery::parse(query).unwrap();
+            let query = super::make_query(&index, fields, &query, Path::new("ws")).unwrap();
+            let documents = searcher
+                .search(&query, &TopDocs::with_limit(10).order_by_score())
+                .unwrap();
+            let mut paths = documents
+                .into_iter()
+                .map(|(_, address)| {
+                    let document: TantivyDocument = searcher.doc(address).unwrap();
+                    let path = document.get_first(fields.path).and_then(|v| v.as_str());
+                    path.unwrap().to_owned()
+                })
+                .collect::<Vec<_>>();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(
+            vec![
+                "other/x.rs",
+                "ws/src/a/b.rs",
+                "ws/src/lib.rs",
+                "ws/tests/t.rs"
+            ],
+            search("main lang:rust")
+        );
+        assert_eq!(
+            vec!["ws/src/a/b.rs", "ws/src/lib.rs"],
+            search("main path:src")
+        );
+        assert_eq!(vec!["ws/src/lib.rs"], search("main path:src/*.rs"));
+        assert_eq!(
+            vec!["other/x.rs", "ws/tests/t.rs"],
+            search("main -path:src -lang:md")
+        );
+        assert_eq!(vec!["ws/README.md"], search(r#""fn main" ext:md"#));
+        assert_eq!(
+            vec!["other/x.rs", "ws/README.md", "ws/tests/t.rs"],
+            search("main -foo")
+        );
+        // Case sensitive words and regexes are checked on the content.
+        assert_eq!(5, search("main -Foo case:yes").len());
+        assert_eq!(vec!["ws/tests/t.rs"], search("/x/ path:tests"));
+    }
 }
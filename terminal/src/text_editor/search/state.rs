@@ -21,6 +21,22 @@ impl std::fmt::Debug for EditorSearchState {
     }
 }
 
+#[derive(Clone)]
+#[nameth]
+pub struct EditorReplaceState {
+    /// The editor state to go back to when the replace panel is closed.
+    pub(super) prev: Box<EditorState>,
+
+    /// The search query the replace starts with.
+    pub(super) query: Arc<str>,
+}
+
+impl std::fmt::Debug for EditorReplaceState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(EditorReplaceState::type_name()).finish()
+    }
+}
+
 pub struct SearchState {
     pub query: XSignal<Arc<str>>,
     pub is_active: XSignal<bool>,
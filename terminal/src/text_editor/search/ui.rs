@@ -92,6 +92,7 @@ fn search_selector_input(
             before_render = input.capture(),
             r#type = "text",
             class = style::PATH_SELECTOR_FIELD,
+            title = "Words, phrases in quotes, /regex/, path:glob, lang:rust, case:yes, -exclude",
             keydown = move |event: KeyboardEvent| {
                 autoclone!(manager, input, do_search);
                 if event.key() == "Escape" {
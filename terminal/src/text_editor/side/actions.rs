@@ -0,0 +1,212 @@
+#![cfg(feature = "client")]
+
+//! The actions of the side-view context menu: rename, duplicate, copy, cut, paste and symlink.
+//!
+//! The clipboard is shared by all the text editors, so entries can be pasted on another remote.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::OnceLock;
+
+use server_fn::ServerFnError;
+use terrazzo::prelude::*;
+
+use self::diagnostics::debug;
+use self::diagnostics::warn;
+use crate::frontend::remotes::Remote;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio;
+use crate::text_editor::fsio::OnConflict;
+use crate::text_editor::manager::TextEditorManager;
+
+/// The entry that was copied or cut.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Clipboard {
+    pub remote: Remote,
+    pub path: FilePath<Arc<Path>>,
+
+    /// The entry is moved when it is pasted.
+    pub cut: bool,
+}
+
+pub fn clipboard() -> XSignal<Option<Arc<Clipboard>>> {
+    static CLIPBOARD: OnceLock<XSignal<Option<Arc<Clipboard>>>> = OnceLock::new();
+    CLIPBOARD
+        .get_or_init(|| XSignal::new("side-view-clipboard", None))
+        .clone()
+}
+
+impl TextEditorManager {
+    pub(super) fn copy_to_clipboard(&self, path: FilePath<Arc<Path>>, cut: bool) {
+        debug!("Copy {path:?} cut={cut}");
+        clipboard().set(Some(Arc::new(Clipboard {
+            remote: self.remote.clone(),
+            path,
+            cut,
+        })));
+    }
+
+    /// Copies or moves the entry of the clipboard into the folder.
+    pub(super) async fn paste(self: Ptr<Self>, destination_folder: FilePath<Arc<Path>>) {
+        let Some(entry) = clipboard().get_value_untracked() else {
+            return;
+        };
+        let result = resolve_conflicts(|on_conflict| {
+            let remote = self.remote.clone();
+            let source = entry.path.clone();
+            let destination_folder = destination_folder.clone();
+            let is_same_remote = entry.remote == remote;
+            let (source_remote, cut) = (entry.remote.clone(), entry.cut);
+            async move {
+                match (is_same_remote, cut) {
+                    (true, true) => {
+                        fsio::client::move_file(remote, source, destination_folder, on_conflict)
+                            .await
+                    }
+                    (true, false) => {
+                        fsio::client::copy_file(remote, source, destination_folder, on_conflict)
+                            .await
+                    }
+                    (false, remove_source) => {
+                        fsio::client::transfer_file(
+                            (source_remote, source),
+                            (remote, destination_folder),
+                            on_conflict,
+                            remove_source,
+                        )
+                        .await
+                    }
+                }
+            }
+        })
+        .await;
+        match result {
+            Some(Ok(())) => {}
+            Some(Err(error)) => {
+                warn!("Failed to paste {:?}: {error}", entry.path);
+                return;
+            }
+            None => return,
+        }
+        if entry.cut {
+            // A cut entry can only be pasted once.
+            clipboard().update(|clipboard| {
+                let is_pasted = clipboard.as_ref() == Some(&entry);
+                is_pasted.then_some(None)
+            });
+        }
+        self.refresh_folder(&destination_folder.file);
+    }
+
+    pub(super) async fn duplicate(self: Ptr<Self>, path: FilePath<Arc<Path>>) {
+        match fsio::client::duplicate_file(self.remote.clone(), path.clone()).await {
+            Ok(copy) => debug!("Duplicated {path:?} to {copy:?}"),
+            Err(error) => {
+                warn!("Failed to duplicate {path:?}: {error}");
+                return;
+            }
+        }
+        self.refresh_folder(path.file.parent().unwrap_or(Path::new("")));
+    }
+
+    pub(super) async fn rename(self: Ptr<Self>, path: FilePath<Arc<Path>>) {
+        let window = web_sys::window().or_throw("window");
+        let name = path.file.file_name().unwrap_or_default().to_string_lossy();
+        let new_name = window.prompt_with_message_and_default("Rename to:", &name);
+        let Some(new_name) = new_name.ok().flatten() else {
+            return;
+        };
+        if new_name.trim().is_empty() || new_name.trim() == name {
+            return;
+        }
+        let result = fsio::client::rename_file(self.remote.clone(), path.clone(), new_name).await;
+        let renamed = match result {
+            Ok(renamed) => renamed,
+            Err(error) => {
+                warn!("Failed to rename {path:?}: {error}");
+                return;
+            }
+        };
+        let current = self.path.file.get_value_untracked();
+        if let Ok(rest) = current.strip_prefix(&path.file) {
+            // The open file or one of its folders was renamed.
+            let current = if rest.as_os_str().is_empty() {
+                renamed
+            } else {
+                renamed.join(rest).into()
+            };
+            self.path.file.set(current);
+        } else {
+            self.refresh_folder(path.file.parent().unwrap_or(Path::new("")));
+        }
+    }
+
+    /// Creates a symlink in the folder, the target is relative to the folder unless it is absolute.
+    pub(super) async fn create_symlink(self: Ptr<Self>, folder: FilePath<Arc<Path>>) {
+        let window = web_sys::window().or_throw("window");
+        let name = window.prompt_with_message("Name of the symlink:");
+        let Some(name) = name.ok().flatten().filter(|name| !name.trim().is_empty()) else {
+            return;
+        };
+        let target = window.prompt_with_message("Target of the symlink:");
+        let Some(target) = target.ok().flatten().filter(|target| !target.is_empty()) else {
+            return;
+        };
+        let result =
+            fsio::client::create_symlink(self.remote.clone(), folder.clone(), name, target).await;
+        if let Err(error) = result {
+            warn!("Failed to create a symlink in {folder:?}: {error}");
+            return;
+        }
+        self.refresh_folder(&folder.file);
+    }
+
+    /// Lists the folder again if it is the one that is open.
+    pub(in crate::text_editor) fn refresh_folder(&self, folder: &Path) {
+        let current = self.path.file.get_value_untracked();
+        if *current == *folder {
+            self.path.file.force(current);
+        }
+    }
+}
+
+/// Runs the operation, and asks what to do with the existing entries if there are any.
+///
+/// Returns `None` if the user cancels.
+pub(in crate::text_editor) async fn resolve_conflicts<F>(
+    operation: impl Fn(OnConflict) -> F,
+) -> Option<Result<(), ServerFnError>>
+where
+    F: Future<Output = Result<(), ServerFnError>>,
+{
+    let mut on_conflict = OnConflict::Fail;
+    loop {
+        match operation(on_conflict).await {
+            Err(error) if on_conflict == OnConflict::Fail && is_conflict(&error) => {
+                on_conflict = ask_on_conflict(&error)?;
+            }
+            result => return Some(result),
+        }
+    }
+}
+
+/// Matches the name of the `DestinationExists` errors returned by the server.
+fn is_conflict(error: &ServerFnError) -> bool {
+    error.to_string().contains("[DestinationExists]")
+}
+
+fn ask_on_conflict(error: &ServerFnError) -> Option<OnConflict> {
+    let window = web_sys::window().or_throw("window");
+    let confirm = |message: String| window.confirm_with_message(&message).unwrap_or(false);
+    if confirm(format!(
+        "{error}\n\nReplace the existing files? They are deleted, not moved to the trash."
+    )) {
+        Some(OnConflict::Overwrite)
+    } else if confirm(format!(
+        "{error}\n\nKeep the existing files, and only add the other ones?"
+    )) {
+        Some(OnConflict::Skip)
+    } else {
+        None
+    }
+}
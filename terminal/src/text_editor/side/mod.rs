@@ -4,8 +4,9 @@ use std::sync::Arc;
 
 use super::fsio::FileMetadata;
 
+pub(super) mod actions;
 mod mutation;
-mod ui;
+pub(super) mod ui;
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 #[serde(bound(
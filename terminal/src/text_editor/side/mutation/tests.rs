@@ -57,6 +57,7 @@ fn add_file() {
                 mode: None,
                 user: None,
                 group: None,
+                is_symlink: false,
             }),
         },
     };
@@ -228,6 +229,7 @@ fn remove_file() {
                 mode: None,
                 user: None,
                 group: None,
+                is_symlink: false,
             }),
         },
     };
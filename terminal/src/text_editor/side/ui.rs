@@ -18,26 +18,41 @@ use super::SideViewNode;
 use super::SvnItem;
 use super::SvnProperties;
 use super::SvnStatus;
+use super::actions::clipboard;
 use super::mutation::filter_active_folder_content;
 use super::mutation::show_folder_content;
 use crate::assets::icons;
 use crate::frontend::mousemove::Position;
+use crate::text_editor::compare::api::CompareWith;
 use crate::text_editor::file_path::FilePath;
 use crate::text_editor::fsio::FileMetadata;
 use crate::text_editor::fsio::ROOT_BASE_PATH;
 use crate::text_editor::fsio::ROOT_FILE_PATH;
 use crate::text_editor::fsio::client::list_folder;
+use crate::text_editor::manager::EditorDataState;
+use crate::text_editor::manager::EditorState;
 use crate::text_editor::manager::TextEditorManager;
 use crate::text_editor::ui::RemoveBehavior;
 use crate::text_editor::ui::drag::on_move_dragover;
 use crate::text_editor::ui::drag::on_move_dragstart;
 use crate::text_editor::ui::drag::on_move_drop;
+use crate::text_editor::ui::folder::archive_url;
+use crate::text_editor::ui::folder::download_url;
 
 terrazzo_css::import_style!(style, "side.scss");
 
 #[cfg(not(feature = "client-prod"))]
 use crate::utils::more_path::MorePath as _;
 
+/// The context menu of a file in the side view.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SideViewMenu {
+    x: i32,
+    y: i32,
+    path: FilePath<Arc<Path>>,
+    is_dir: bool,
+}
+
 impl TextEditorManager {
     pub fn show_side_view(self: &Ptr<TextEditorManager>) -> XElement {
         show_side_view(self.clone(), self.path.base.clone(), self.side_view.clone())
@@ -72,6 +87,7 @@ fn show_side_view(
                 &side_view,
             )
         })..,
+        show_side_view_menu(manager.clone(), manager.side_view_menu.clone()),
     );
 
     #[template(wrap = true)]
@@ -156,6 +172,16 @@ fn show_side_view_folder(
                     manager.path.file.set(path.file.clone())
                 },
                 dblclick = expand_folder(manager, path),
+                contextmenu = move |event: MouseEvent| {
+                    autoclone!(manager, path);
+                    event.prevent_default();
+                    manager.side_view_menu.set(Some(Arc::new(SideViewMenu {
+                        x: event.client_x(),
+                        y: event.client_y(),
+                        path: path.clone(),
+                        is_dir: true,
+                    })));
+                },
                 span("{name_display}", class = name_display_class(properties)),
             ),
             folder_expand_icon(manager, path, is_expanded),
@@ -211,11 +237,167 @@ fn show_side_view_file(
                 autoclone!(manager, path);
                 manager.path.file.force(path.file.clone())
             },
+            contextmenu = move |event: MouseEvent| {
+                autoclone!(manager, path);
+                event.prevent_default();
+                manager.side_view_menu.set(Some(Arc::new(SideViewMenu {
+                    x: event.client_x(),
+                    y: event.client_y(),
+                    path: path.clone(),
+                    is_dir: false,
+                })));
+            },
         ),
         close_icon(manager, path, RemoveBehavior::Hard),
     )
 }
 
+#[autoclone]
+#[html]
+#[template(tag = div)]
+fn show_side_view_menu(
+    manager: Ptr<TextEditorManager>,
+    #[signal] menu: Option<Arc<SideViewMenu>>,
+) -> XElement {
+    let Some(menu) = menu else {
+        return tag(style::display = "none", style::visibility = "hidden");
+    };
+    let path = menu.path.clone();
+    let can_compare = !menu.is_dir
+        && matches!(
+            manager.editor_state.get_value_untracked(),
+            EditorState::Data(EditorDataState { path: current, .. }) if current != path
+        );
+    let compare = can_compare.then(|| {
+        li(
+            "Compare with the open file",
+            click = move |_| {
+                autoclone!(manager, path);
+                manager.side_view_menu.set(None);
+                manager.compare_with(CompareWith::File(path.clone()));
+            },
+        )
+    });
+    tag(
+        class = style::CONTEXT_MENU,
+        #[cfg(not(feature = "client-prod"))]
+        class = "side-view-context-menu",
+        style::left = format!("{}px", menu.x),
+        style::top = format!("{}px", menu.y),
+        mouseleave = move |_| {
+            autoclone!(manager);
+            manager.side_view_menu.set(None)
+        },
+        ul(
+            li(
+                "Open",
+                click = move |_| {
+                    autoclone!(manager, path);
+                    manager.side_view_menu.set(None);
+                    manager.path.file.force(path.file.clone());
+                },
+            ),
+            compare..,
+            edit_menu_items(&manager, &menu)..,
+            download_menu_item(&manager, &menu),
+        ),
+    )
+}
+
+#[autoclone]
+#[html]
+fn edit_menu_items(manager: &Ptr<TextEditorManager>, menu: &SideViewMenu) -> Vec<XElement> {
+    let path = menu.path.clone();
+    let mut items = vec![];
+    if !path.file.as_os_str().is_empty() {
+        items.push(li(
+            "Rename…",
+            click = move |_| {
+                autoclone!(manager, path);
+                manager.side_view_menu.set(None);
+                spawn_local(manager.clone().rename(path.clone()));
+            },
+        ));
+        items.push(li(
+            "Duplicate",
+            click = move |_| {
+                autoclone!(manager, path);
+                manager.side_view_menu.set(None);
+                spawn_local(manager.clone().duplicate(path.clone()));
+            },
+        ));
+        for (label, cut) in [("Copy", false), ("Cut", true)] {
+            items.push(li(
+                "{label}",
+                click = move |_| {
+                    autoclone!(manager, path);
+                    manager.side_view_menu.set(None);
+                    manager.copy_to_clipboard(path.clone(), cut);
+                },
+            ));
+        }
+    }
+    if let Some(entry) = clipboard().get_value_untracked()
+        && menu.is_dir
+    {
+        let name = entry
+            .path
+            .file
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let label = format!("Paste {name}");
+        items.push(li(
+            "{label}",
+            click = move |_| {
+                autoclone!(manager, path);
+                manager.side_view_menu.set(None);
+                spawn_local(manager.clone().paste(path.clone()));
+            },
+        ));
+    }
+    if menu.is_dir {
+        items.push(li(
+            "New symlink…",
+            click = move |_| {
+                autoclone!(manager, path);
+                manager.side_view_menu.set(None);
+                spawn_local(manager.clone().create_symlink(path.clone()));
+            },
+        ));
+    }
+    items.push(li(
+        "Properties…",
+        click = move |_| {
+            autoclone!(manager, path);
+            manager.side_view_menu.set(None);
+            manager.open_properties(path.clone());
+        },
+    ));
+    items
+}
+
+#[autoclone]
+#[html]
+fn download_menu_item(manager: &Ptr<TextEditorManager>, menu: &SideViewMenu) -> XElement {
+    let full_path = menu.path.full_path();
+    let name = full_path.file_name().unwrap_or_default().to_string_lossy();
+    let (href, download) = if menu.is_dir {
+        (archive_url(manager, &menu.path), format!("{name}.tar"))
+    } else {
+        (download_url(manager, &menu.path), name.into_owned())
+    };
+    li(a(
+        href = href,
+        download = download,
+        click = move |_| {
+            autoclone!(manager);
+            manager.side_view_menu.set(None);
+        },
+        "Download",
+    ))
+}
+
 #[template(wrap = true)]
 fn selected_item(#[signal] file_path: Arc<Path>, path: Arc<Path>) -> XAttributeValue {
     if file_path == path {
@@ -0,0 +1,42 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use server_fn::Http;
+use server_fn::ServerFnError;
+use server_fn::codec::Json;
+use server_fn::codec::StreamingText;
+use server_fn::codec::TextStream;
+use terrazzo::server;
+
+use crate::api::client_address::ClientAddress;
+use crate::text_editor::file_path::FilePath;
+
+/// What happens to a followed file, streamed as it happens.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub enum TailEvent {
+    /// Text was appended, and the file is now `size` bytes long.
+    Append { text: String, size: u64 },
+
+    /// The file was truncated, it is followed from its start.
+    Truncated,
+
+    /// The file was replaced, like by a log rotation, the new file is followed from its start.
+    Rotated,
+
+    /// The file was removed, it is followed again if it comes back.
+    Removed,
+}
+
+/// Streams the end of the file, then what is appended to it.
+#[server(protocol = Http<Json, StreamingText>)]
+pub async fn tail_file(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<TextStream, ServerFnError> {
+    use tracing::info_span;
+    use tracing_futures::Instrument as _;
+    let span = info_span!("Tail", ?path);
+    super::service::tail_file(remote, path)
+        .instrument(span)
+        .await
+}
@@ -0,0 +1,31 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use futures::Stream;
+use futures::StreamExt as _;
+use server_fn::ServerFnError;
+
+use super::api::TailEvent;
+use crate::api::client_address::ClientAddress;
+use crate::text_editor::file_path::FilePath;
+use crate::utils::ndjson::NdjsonBuffer;
+
+pub async fn tail_file(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<impl Stream<Item = Result<TailEvent, ServerFnError>>, ServerFnError> {
+    let stream = super::api::tail_file(remote, path).await?.into_inner();
+    let mut parser = NdjsonBuffer::<TailEvent>::default();
+    Ok(stream.flat_map(move |item| match item {
+        Ok(chunk) => {
+            let messages = parser.push_chunk(&chunk);
+            futures::stream::iter(
+                messages
+                    .into_iter()
+                    .map(|row| row.map_err(ServerFnError::from))
+                    .collect::<Vec<_>>(),
+            )
+        }
+        Err(error) => futures::stream::iter(vec![Err(error)]),
+    }))
+}
@@ -0,0 +1,11 @@
+//! Follows a file that grows, like `tail -f`, and streams what is appended to it.
+
+pub mod api;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "server")]
+pub mod service;
+#[cfg(feature = "client")]
+pub mod state;
+#[cfg(feature = "client")]
+pub mod ui;
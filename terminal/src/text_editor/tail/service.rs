@@ -0,0 +1,337 @@
+use std::future::ready;
+use std::io::ErrorKind;
+use std::io::SeekFrom;
+use std::os::unix::fs::MetadataExt as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::Stream;
+use futures::StreamExt as _;
+use futures::TryStreamExt as _;
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+use server_fn::ServerFnError;
+use server_fn::codec::TextStream;
+use tokio::io::AsyncReadExt as _;
+use tokio::io::AsyncSeekExt as _;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::Span;
+use tracing::debug;
+use tracing::warn;
+use tracing_futures::Instrument as _;
+
+use super::api::TailEvent;
+use crate::api::client_address::ClientAddress;
+use crate::backend::client_service::grpc_error::GrpcError;
+use crate::backend::client_service::grpc_error::IsGrpcError;
+use crate::backend::client_service::remote_fn_service;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio::encoding;
+use crate::text_editor::fsio::encoding::Encoding;
+use crate::text_editor::notify::event_handler::make_event_handler;
+use crate::text_editor::notify::watcher::ExtendedWatcher;
+use crate::utils::ndjson_utils::serialize_line;
+
+/// How much of the end of the file is sent when it starts being followed.
+const INITIAL_TAIL_SIZE: u64 = 64 * 1024;
+
+/// The largest chunk of appended bytes sent at once.
+const MAX_APPEND_SIZE: u64 = 256 * 1024;
+
+/// How many bytes at the start of the file are used to detect its encoding.
+const SNIFF_SIZE: u64 = 64 * 1024;
+
+/// Rotations create a new file that inotify doesn't report, so the file is also polled.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub async fn tail_file(
+    remote: ClientAddress,
+    path: FilePath<Arc<Path>>,
+) -> Result<TextStream, ServerFnError> {
+    debug!(%remote, "Calling tail_file({path:?})");
+    let stream = TAIL_FILE_FN.call(remote, path).await?;
+    let stream = stream.filter_map(|item| {
+        let item = item.map(|item| {
+            serialize_line(&item)
+                .inspect_err(|error| warn!("Failed to serialize: {error}"))
+                .ok()
+        });
+        ready(item.transpose())
+    });
+    Ok(TextStream::new(
+        stream.map_err(Into::into).instrument(Span::current()),
+    ))
+}
+
+remote_fn_service::streaming::declare_remote_fn!(
+    TAIL_FILE_FN,
+    "texteditor.tail.follow",
+    FilePath<Arc<Path>>,
+    TailEvent,
+    |_server, path| follow(path.full_path()).map_err(GrpcError::from)
+);
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+pub enum TailError {
+    #[error("[{n}] The file {0:?} doesn't exist", n = self.name())]
+    NotFound(PathBuf),
+
+    #[error("[{n}] The file {0:?} is not a text file", n = self.name())]
+    Binary(PathBuf),
+
+    #[error("[{n}] Failed to read {0:?}: {1}", n = self.name())]
+    Read(PathBuf, std::io::Error),
+
+    #[error("[{n}] {0}", n = self.name())]
+    Watch(notify::Error),
+}
+
+impl IsGrpcError for TailError {
+    fn code(&self) -> tonic::Code {
+        match self {
+            Self::NotFound { .. } => tonic::Code::NotFound,
+            Self::Binary { .. } => tonic::Code::FailedPrecondition,
+            Self::Read { .. } => tonic::Code::Internal,
+            Self::Watch { .. } => tonic::Code::Internal,
+        }
+    }
+}
+
+type TailSender = mpsc::UnboundedSender<Result<TailEvent, TailError>>;
+
+/// Follows the file until the stream is dropped.
+fn follow(full_path: PathBuf) -> impl Stream<Item = Result<TailEvent, TailError>> + Send {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let task = async move {
+        if let Err(error) = follow_impl(&full_path, &tx).await {
+            let _ = tx.send(Err(error));
+        }
+        debug!("Stop following {full_path:?}");
+    };
+    tokio::spawn(task.in_current_span());
+    UnboundedReceiverStream::new(rx)
+}
+
+async fn follow_impl(full_path: &Path, tx: &TailSender) -> Result<(), TailError> {
+    let mut follower = Follower::open(full_path).await?;
+    let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+    let (replaced_tx, mut replaced_rx) = mpsc::unbounded_channel();
+    let mut watcher = ExtendedWatcher::new(events_tx, replaced_tx, make_event_handler)
+        .map_err(TailError::Watch)?;
+    watcher.watch_file(full_path).map_err(TailError::Watch)?;
+    let mut poll = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        for event in follower.check().await? {
+            if let TailEvent::Rotated = event
+                && let Err(error) = watcher.rewatch(full_path)
+            {
+                warn!("Failed to watch rotated file {full_path:?}: {error}");
+            }
+            if tx.send(Ok(event)).is_err() {
+                return Ok(());
+            }
+        }
+        tokio::select! {
+            () = tx.closed() => return Ok(()),
+            Some(_) = events_rx.recv() => {}
+            Some(full_path) = replaced_rx.recv() => {
+                if let Err(error) = watcher.rewatch(&full_path) {
+                    warn!("Failed to watch replaced file {full_path:?}: {error}");
+                }
+            }
+            _ = poll.tick() => {}
+        }
+    }
+}
+
+/// The position in the followed file, and how to tell it was truncated or replaced.
+struct Follower {
+    full_path: PathBuf,
+
+    /// The inode of the file, or `None` after it was removed.
+    inode: Option<u64>,
+    position: u64,
+    encoding: Encoding,
+
+    /// The bytes of a character that was only partially appended.
+    carry: Vec<u8>,
+
+    /// The first line is skipped when the file isn't followed from its start.
+    skip_line: bool,
+}
+
+impl Follower {
+    async fn open(full_path: &Path) -> Result<Self, TailError> {
+        let metadata = match tokio::fs::metadata(full_path).await {
+            Ok(metadata) => metadata,
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                return Err(TailError::NotFound(full_path.to_owned()));
+            }
+            Err(error) => return Err(TailError::Read(full_path.to_owned(), error)),
+        };
+        let encoding = detect_encoding(full_path)
+            .await?
+            .ok_or_else(|| TailError::Binary(full_path.to_owned()))?;
+        let mut position = metadata.len().saturating_sub(INITIAL_TAIL_SIZE);
+        if let Encoding::Utf16Le | Encoding::Utf16Be = encoding {
+            position -= position % 2;
+        }
+        Ok(Self {
+            full_path: full_path.to_owned(),
+            inode: Some(metadata.ino()),
+            position,
+            encoding,
+            carry: vec![],
+            skip_line: position > 0,
+        })
+    }
+
+    /// Returns what happened to the file since the last check.
+    async fn check(&mut self) -> Result<Vec<TailEvent>, TailError> {
+        let metadata = match tokio::fs::metadata(&self.full_path).await {
+            Ok(metadata) => metadata,
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                let removed = self.inode.take().map(|_| TailEvent::Removed);
+                return Ok(removed.into_iter().collect());
+            }
+            Err(error) => return Err(TailError::Read(self.full_path.clone(), error)),
+        };
+        let mut events = vec![];
+        if self.inode != Some(metadata.ino()) {
+            debug!("The file {:?} was replaced", self.full_path);
+            self.inode = Some(metadata.ino());
+            self.restart();
+            if let Some(encoding) = detect_encoding(&self.full_path).await? {
+                self.encoding = encoding;
+            }
+            events.push(TailEvent::Rotated);
+        } else if metadata.len() < self.position {
+            debug!("The file {:?} was truncated", self.full_path);
+            self.restart();
+            events.push(TailEvent::Truncated);
+        }
+        while self.position < metadata.len() {
+            let length = (metadata.len() - self.position).min(MAX_APPEND_SIZE);
+            let bytes = self.read(length).await?;
+            if bytes.is_empty() {
+                break;
+            }
+            self.position += bytes.len() as u64;
+            let mut text = encoding::decode_stream(&mut self.carry, &bytes, self.encoding);
+            if self.skip_line {
+                let Some(newline) = text.find('\n') else {
+                    continue;
+                };
+                text.drain(..=newline);
+                self.skip_line = false;
+            }
+            if !text.is_empty() {
+                events.push(TailEvent::Append {
+                    text,
+                    size: self.position,
+                });
+            }
+        }
+        Ok(events)
+    }
+
+    async fn read(&self, length: u64) -> Result<Vec<u8>, TailError> {
+        let read = async {
+            let mut file = tokio::fs::File::open(&self.full_path).await?;
+            file.seek(SeekFrom::Start(self.position)).await?;
+            let mut bytes = vec![];
+            file.take(length).read_to_end(&mut bytes).await?;
+            Ok::<_, std::io::Error>(bytes)
+        };
+        read.await
+            .map_err(|error| TailError::Read(self.full_path.clone(), error))
+    }
+
+    /// Follows the file from its start.
+    fn restart(&mut self) {
+        self.position = 0;
+        self.carry.clear();
+        self.skip_line = false;
+    }
+}
+
+/// Returns the encoding of the file, or `None` if it looks binary.
+async fn detect_encoding(full_path: &Path) -> Result<Option<Encoding>, TailError> {
+    let read = async {
+        let file = tokio::fs::File::open(full_path).await?;
+        let mut head = vec![];
+        file.take(SNIFF_SIZE).read_to_end(&mut head).await?;
+        Ok::<_, std::io::Error>(head)
+    };
+    let head = read
+        .await
+        .map_err(|error| TailError::Read(full_path.to_owned(), error))?;
+    Ok(encoding::detect_prefix(&head))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use super::super::api::TailEvent;
+    use super::Follower;
+
+    fn appended(events: &[TailEvent]) -> String {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                TailEvent::Append { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn follow() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        std::fs::write(&path, "one\ntwo\n").unwrap();
+        let mut follower = Follower::open(&path).await.unwrap();
+        assert_eq!("one\ntwo\n", appended(&follower.check().await.unwrap()));
+        assert!(follower.check().await.unwrap().is_empty());
+
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"three\nfo").unwrap();
+        assert_eq!("three\nfo", appended(&follower.check().await.unwrap()));
+
+        std::fs::write(&path, "new\n").unwrap();
+        let events = follower.check().await.unwrap();
+        assert!(matches!(events[0], TailEvent::Truncated));
+        assert_eq!("new\n", appended(&events));
+
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+        let events = follower.check().await.unwrap();
+        assert!(matches!(events[..], [TailEvent::Removed]));
+        assert!(follower.check().await.unwrap().is_empty());
+
+        std::fs::write(&path, "rotated\n").unwrap();
+        let events = follower.check().await.unwrap();
+        assert!(matches!(events[0], TailEvent::Rotated));
+        assert_eq!("rotated\n", appended(&events));
+    }
+
+    #[tokio::test]
+    async fn follow_from_the_end() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        let line = "x".repeat(99) + "\n";
+        std::fs::write(&path, line.repeat(1000)).unwrap();
+        let mut follower = Follower::open(&path).await.unwrap();
+        let text = appended(&follower.check().await.unwrap());
+        assert!(text.starts_with(&line));
+        assert_eq!(0, text.len() % line.len());
+        assert!(text.len() < super::INITIAL_TAIL_SIZE as usize);
+    }
+}
@@ -0,0 +1,24 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use nameth::NamedType as _;
+use nameth::nameth;
+
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::manager::EditorState;
+
+#[derive(Clone)]
+#[nameth]
+pub struct EditorTailState {
+    /// The editor state to go back to when the file is no longer followed.
+    pub(super) prev: Box<EditorState>,
+    pub(super) path: FilePath<Arc<Path>>,
+}
+
+impl std::fmt::Debug for EditorTailState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(EditorTailState::type_name())
+            .field("path", &self.path)
+            .finish()
+    }
+}
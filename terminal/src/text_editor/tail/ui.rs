@@ -0,0 +1,407 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::path::Path;
+use std::sync::Arc;
+
+use futures::StreamExt as _;
+use futures::channel::oneshot;
+use regex::Regex;
+use scopeguard::guard;
+use server_fn::ServerFnError;
+use terrazzo::autoclone;
+use terrazzo::html;
+use terrazzo::prelude::*;
+use terrazzo::template;
+use terrazzo::widgets::element_capture::ElementCapture;
+use wasm_bindgen::JsCast as _;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::Element;
+use web_sys::HtmlInputElement;
+use web_sys::InputEvent;
+use web_sys::UiEvent;
+
+use self::diagnostics::warn;
+use super::api::TailEvent;
+use super::state::EditorTailState;
+use crate::assets::icons;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio;
+use crate::text_editor::manager::EditorDataState;
+use crate::text_editor::manager::EditorState;
+use crate::text_editor::manager::TextEditorManager;
+use crate::text_editor::style;
+
+terrazzo_css::import_style!(tail_style, "tail.scss");
+
+/// The followed file is truncated to its last lines.
+const MAX_LINES: usize = 10_000;
+
+/// How close to the bottom, in pixels, the view stays pinned to it.
+const PIN_THRESHOLD: i32 = 16;
+
+impl TextEditorManager {
+    /// The header button that follows the current file, like `tail -f`.
+    pub fn tail_toggle(self: &Ptr<Self>) -> XElement {
+        tail_toggle(self.clone(), self.editor_state.clone())
+    }
+}
+
+#[html]
+#[template(tag = span)]
+fn tail_toggle(manager: Ptr<TextEditorManager>, #[signal] editor_state: EditorState) -> XElement {
+    let is_active = matches!(editor_state, EditorState::Tail(_));
+    if !is_active && tail_path(&editor_state).is_none() {
+        return tag(style::display = "none", style::visibility = "hidden");
+    }
+    let title = if is_active {
+        "Stop following"
+    } else {
+        "Follow"
+    };
+    return img(
+        class = style::TOGGLE_TAIL_PANEL,
+        class = is_active.then_some(style::ACTIVE),
+        #[cfg(not(feature = "client-prod"))]
+        class = "toggle-tail-panel",
+        src = icons::tail(),
+        title = title,
+        click = move |_| toggle_tail_panel(&manager),
+    );
+}
+
+fn toggle_tail_panel(manager: &TextEditorManager) {
+    manager.editor_state.update(|editor_state| {
+        if let EditorState::Tail(EditorTailState { prev, .. }) = editor_state {
+            return Some(prev.as_ref().clone());
+        }
+        Some(EditorState::Tail(EditorTailState {
+            prev: Box::new(editor_state.clone()),
+            path: tail_path(editor_state)?,
+        }))
+    });
+}
+
+/// The file that can be followed: the open text file, even if it is too large to edit.
+fn tail_path(editor_state: &EditorState) -> Option<FilePath<Arc<Path>>> {
+    let EditorState::Data(EditorDataState { path, data, .. }) = editor_state else {
+        return None;
+    };
+    let is_text = matches!(
+        **data,
+        fsio::File::TextFile { .. } | fsio::File::LargeFile { .. }
+    );
+    is_text.then(|| path.clone())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn class(self) -> &'static str {
+        match self {
+            Self::Error => tail_style::ERROR,
+            Self::Warn => tail_style::WARN,
+            Self::Info => tail_style::INFO,
+            Self::Debug => tail_style::DEBUG,
+            Self::Trace => tail_style::TRACE,
+        }
+    }
+}
+
+/// The level of a log line is the first upper case word that names one.
+fn log_level(line: &str) -> Option<LogLevel> {
+    line.split(|c: char| !c.is_ascii_alphabetic())
+        .find_map(|word| match word {
+            "ERROR" | "FATAL" | "CRITICAL" | "SEVERE" => Some(LogLevel::Error),
+            "WARN" | "WARNING" => Some(LogLevel::Warn),
+            "INFO" => Some(LogLevel::Info),
+            "DEBUG" => Some(LogLevel::Debug),
+            "TRACE" => Some(LogLevel::Trace),
+            _ => None,
+        })
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum TailLine {
+    Text {
+        text: String,
+        level: Option<LogLevel>,
+
+        /// The last line is incomplete until a newline is appended.
+        complete: bool,
+    },
+
+    /// What happened to the file, or why it can't be followed.
+    Notice(String),
+}
+
+/// Appends the text to the lines, completing the last line if it didn't end with a newline.
+fn append_text(lines: &mut Vec<TailLine>, text: &str) {
+    let mut text = text.to_owned();
+    if let Some(TailLine::Text {
+        text: last,
+        complete: false,
+        ..
+    }) = lines.last()
+    {
+        text.insert_str(0, last);
+        lines.pop();
+    }
+    for line in text.split_inclusive('\n') {
+        let (line, complete) = match line.strip_suffix('\n') {
+            Some(line) => (line.strip_suffix('\r').unwrap_or(line), true),
+            None => (line, false),
+        };
+        lines.push(TailLine::Text {
+            text: line.to_owned(),
+            level: log_level(line),
+            complete,
+        });
+    }
+}
+
+/// The signals of the tail panel, updated as the followed file grows.
+struct TailPanel {
+    manager: Ptr<TextEditorManager>,
+    path: FilePath<Arc<Path>>,
+    lines: XSignal<Arc<Vec<TailLine>>>,
+    filter: XSignal<Option<Arc<Regex>>>,
+    status: XSignal<Option<Arc<str>>>,
+
+    /// Whether new lines scroll the view to the bottom.
+    pinned: Cell<bool>,
+
+    /// Dropped to stop following the file.
+    cancel: RefCell<Option<oneshot::Sender<()>>>,
+}
+
+impl TailPanel {
+    fn new(manager: &Ptr<TextEditorManager>, path: FilePath<Arc<Path>>) -> Ptr<Self> {
+        Self {
+            manager: manager.clone(),
+            path,
+            lines: XSignal::new("tail-lines", Arc::default()),
+            filter: XSignal::new("tail-filter", None),
+            status: XSignal::new("tail-status", None),
+            pinned: Cell::new(true),
+            cancel: RefCell::default(),
+        }
+        .into()
+    }
+
+    fn start(self: &Ptr<Self>) {
+        if self.cancel.borrow().is_some() {
+            return;
+        }
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        *self.cancel.borrow_mut() = Some(cancel_tx);
+        self.status.set(Some("Following".into()));
+
+        let this = self.clone();
+        spawn_local(async move {
+            let remote = this.manager.remote.clone();
+            match super::client::tail_file(remote, this.path.clone()).await {
+                Ok(events) => {
+                    let mut events = events.ready_chunks(100).take_until(cancel_rx);
+                    while let Some(events) = events.next().await {
+                        this.apply(events);
+                    }
+                }
+                Err(error) => {
+                    warn!("Failed to follow {:?}: {error}", this.path);
+                    this.status.set(Some(error.to_string().into()));
+                    return;
+                }
+            }
+            if this.cancel.borrow_mut().take().is_some() {
+                this.status.set(Some("Stopped following".into()));
+            }
+        });
+    }
+
+    fn stop(&self) {
+        self.cancel.borrow_mut().take();
+    }
+
+    fn apply(&self, events: Vec<Result<TailEvent, ServerFnError>>) {
+        let mut lines = self.lines.get_value_untracked().as_ref().clone();
+        let batch = Batch::use_batch("tail-events");
+        for event in events {
+            let notice = match event {
+                Ok(TailEvent::Append { text, .. }) => {
+                    append_text(&mut lines, &text);
+                    continue;
+                }
+                Ok(TailEvent::Truncated) => "The file was truncated".to_owned(),
+                Ok(TailEvent::Rotated) => {
+                    self.status.set(Some("Following".into()));
+                    "The file was replaced".to_owned()
+                }
+                Ok(TailEvent::Removed) => {
+                    self.status
+                        .set(Some("Waiting for the file to come back".into()));
+                    "The file was removed".to_owned()
+                }
+                Err(error) => error.to_string(),
+            };
+            lines.push(TailLine::Notice(notice));
+        }
+        let excess = lines.len().saturating_sub(MAX_LINES);
+        lines.drain(..excess);
+        self.lines.set(Arc::new(lines));
+        drop(batch);
+    }
+
+    fn set_filter(&self, pattern: &str) {
+        if pattern.is_empty() {
+            self.filter.force(None);
+            return;
+        }
+        match Regex::new(pattern) {
+            Ok(filter) => {
+                self.filter.force(Some(Arc::new(filter)));
+                self.status.set(Some("Following".into()));
+            }
+            Err(error) => self
+                .status
+                .set(Some(format!("Invalid filter: {error}").into())),
+        }
+    }
+}
+
+#[autoclone]
+#[html]
+pub fn tail_panel(manager: Ptr<TextEditorManager>, tail_state: EditorTailState) -> XElement {
+    let panel = TailPanel::new(&manager, tail_state.path);
+    let stop_on_close = guard(panel.clone(), |panel| panel.stop());
+    let filter = ElementCapture::<HtmlInputElement>::default();
+    let name = panel.path.file.display().to_string();
+    div(
+        class = tail_style::TAIL_PANEL,
+        #[cfg(not(feature = "client-prod"))]
+        class = "tail-panel",
+        div(
+            class = tail_style::TAIL_TOOLBAR,
+            span(class = tail_style::TAIL_FILE, "{name}"),
+            input(
+                before_render = filter.capture(),
+                r#type = "text",
+                placeholder = "Filter with a regular expression",
+                input = move |_: InputEvent| {
+                    autoclone!(panel, filter);
+                    panel.set_filter(&filter.with(|input| input.value()))
+                },
+            ),
+            button(
+                "Clear",
+                click = move |_| {
+                    autoclone!(panel);
+                    panel.lines.set(Arc::default())
+                },
+            ),
+            show_status(panel.status.clone()),
+        ),
+        show_lines(panel.clone(), panel.lines.clone(), panel.filter.clone()),
+        after_render = move |_| {
+            autoclone!(panel);
+            let _moved = &stop_on_close;
+            panel.start()
+        },
+    )
+}
+
+#[html]
+#[template(tag = span)]
+fn show_status(#[signal] status: Option<Arc<str>>) -> XElement {
+    let Some(status) = status else {
+        return tag(style::display = "none", style::visibility = "hidden");
+    };
+    tag(class = tail_style::TAIL_STATUS, "{status}")
+}
+
+#[autoclone]
+#[html]
+#[template(tag = pre)]
+fn show_lines(
+    panel: Ptr<TailPanel>,
+    #[signal] lines: Arc<Vec<TailLine>>,
+    #[signal] filter: Option<Arc<Regex>>,
+) -> XElement {
+    let lines = lines
+        .iter()
+        .filter(|line| match (&filter, line) {
+            (Some(filter), TailLine::Text { text, .. }) => filter.is_match(text),
+            _ => true,
+        })
+        .map(|line| match line {
+            TailLine::Text { text, level, .. } => {
+                span(class = level.map(LogLevel::class), "{text}\n")
+            }
+            TailLine::Notice(notice) => span(class = tail_style::NOTICE, "{notice}\n"),
+        });
+    tag(
+        class = tail_style::TAIL_OUTPUT,
+        lines..,
+        scroll = move |event: UiEvent| {
+            autoclone!(panel);
+            let output = event.target().or_throw("target for scroll");
+            let output: Element = output.dyn_into().or_throw("element for scroll");
+            let gap = output.scroll_height() - output.client_height() - output.scroll_top();
+            panel.pinned.set(gap <= PIN_THRESHOLD);
+        },
+        after_render = move |output: &Element| {
+            if panel.pinned.get() {
+                output.set_scroll_top(output.scroll_height())
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LogLevel;
+    use super::TailLine;
+
+    #[test]
+    fn log_level() {
+        let level = super::log_level;
+        assert_eq!(
+            Some(LogLevel::Error),
+            level("2026-10-17T07:02:50Z ERROR server: failed")
+        );
+        assert_eq!(Some(LogLevel::Warn), level("[WARNING] disk is full"));
+        assert_eq!(Some(LogLevel::Info), level(" INFO app::main > started"));
+        assert_eq!(Some(LogLevel::Debug), level("level=DEBUG msg=ok"));
+        assert_eq!(None, level("no level, not even INFORMATION or error"));
+    }
+
+    #[test]
+    fn append_text() {
+        let text = |text: &str, complete| TailLine::Text {
+            text: text.to_owned(),
+            level: super::log_level(text),
+            complete,
+        };
+        let mut lines = vec![];
+        super::append_text(&mut lines, "INFO one\r\nWARN tw");
+        assert_eq!(vec![text("INFO one", true), text("WARN tw", false)], lines);
+        super::append_text(&mut lines, "o\nthree");
+        assert_eq!(
+            vec![
+                text("INFO one", true),
+                text("WARN two", true),
+                text("three", false)
+            ],
+            lines
+        );
+        lines.push(TailLine::Notice("The file was truncated".to_owned()));
+        super::append_text(&mut lines, "\n");
+        assert_eq!(Some(&text("", true)), lines.last());
+    }
+}
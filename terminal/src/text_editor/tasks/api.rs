@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use server_fn::Http;
+use server_fn::ServerFnError;
+use server_fn::codec::Json;
+use server_fn::codec::StreamingText;
+use server_fn::codec::TextStream;
+use terrazzo::server;
+
+use crate::api::client_address::ClientAddress;
+use crate::text_editor::rust_lang::synthetic::SyntheticDiagnostic;
+
+/// A command that runs in the workspace, configured in `.terrazzo/tasks.toml`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct TaskDefinition {
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+
+    /// Extra environment variables of the command.
+    pub env: BTreeMap<String, String>,
+}
+
+/// What a running task reports, streamed as it happens.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub enum TaskEvent {
+    /// A line of output that isn't a JSON message.
+    Output { line: String, stderr: bool },
+
+    /// A message of rustc or clippy.
+    Diagnostics {
+        diagnostics: Vec<SyntheticDiagnostic>,
+        rendered: Option<String>,
+    },
+
+    /// A test of libtest started or finished.
+    Test(TestEvent),
+
+    /// The exit code of the command, or none if it was killed by a signal.
+    Exit { code: Option<i32> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TestEvent {
+    /// The path of the test, like `module::tests::name`.
+    pub name: String,
+    pub status: TestStatus,
+
+    /// The captured output of a failed test.
+    pub stdout: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum TestStatus {
+    Running,
+    Passed,
+    Failed,
+    Ignored,
+}
+
+#[server(protocol = Http<Json, Json>)]
+pub async fn list_tasks(
+    remote: ClientAddress,
+    base: Arc<Path>,
+) -> Result<Vec<TaskDefinition>, ServerFnError> {
+    super::service::list_tasks(remote, base).await
+}
+
+/// Runs the task, or only the `test` with its exact name if set.
+#[server(protocol = Http<Json, StreamingText>)]
+pub async fn run_task(
+    remote: ClientAddress,
+    base: Arc<Path>,
+    name: String,
+    test: Option<String>,
+) -> Result<TextStream, ServerFnError> {
+    use tracing::info_span;
+    use tracing_futures::Instrument as _;
+    let span = info_span!("Task", ?base, %name, ?test);
+    super::service::run_task(remote, base, name, test)
+        .instrument(span)
+        .await
+}
@@ -0,0 +1,34 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use futures::Stream;
+use futures::StreamExt as _;
+use server_fn::ServerFnError;
+
+use super::api::TaskEvent;
+use crate::api::client_address::ClientAddress;
+use crate::utils::ndjson::NdjsonBuffer;
+
+pub async fn run_task(
+    remote: ClientAddress,
+    base: Arc<Path>,
+    name: String,
+    test: Option<String>,
+) -> Result<impl Stream<Item = Result<TaskEvent, ServerFnError>>, ServerFnError> {
+    let stream = super::api::run_task(remote, base, name, test)
+        .await?
+        .into_inner();
+    let mut parser = NdjsonBuffer::<TaskEvent>::default();
+    Ok(stream.flat_map(move |item| match item {
+        Ok(chunk) => {
+            let messages = parser.push_chunk(&chunk);
+            futures::stream::iter(
+                messages
+                    .into_iter()
+                    .map(|row| row.map_err(ServerFnError::from))
+                    .collect::<Vec<_>>(),
+            )
+        }
+        Err(error) => futures::stream::iter(vec![Err(error)]),
+    }))
+}
@@ -0,0 +1,97 @@
+//! Parses the JSON output of libtest, `cargo test -- -Z unstable-options --format=json`.
+
+use super::api::TestEvent;
+use super::api::TestStatus;
+
+/// https://github.com/rust-lang/rust/blob/1.87.0/library/test/src/formatters/json.rs
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum LibtestMessage {
+    Suite {},
+    Test {
+        event: String,
+        name: String,
+        stdout: Option<String>,
+    },
+    Bench {},
+}
+
+/// Parses a line of libtest output.
+///
+/// Returns `None` if the line isn't from libtest, and `Some(None)` for the messages
+/// about whole test suites and benchmarks.
+pub fn parse(line: &str) -> Option<Option<TestEvent>> {
+    let message = serde_json::from_str::<LibtestMessage>(line).ok()?;
+    let LibtestMessage::Test {
+        event,
+        name,
+        stdout,
+    } = message
+    else {
+        return Some(None);
+    };
+    let status = match event.as_str() {
+        "started" | "timeout" => TestStatus::Running,
+        "ok" => TestStatus::Passed,
+        "failed" => TestStatus::Failed,
+        "ignored" => TestStatus::Ignored,
+        _ => return Some(None),
+    };
+    Some(Some(TestEvent {
+        name,
+        status,
+        stdout,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::api::TestEvent;
+    use super::super::api::TestStatus;
+
+    #[test]
+    fn parse() {
+        let test = |name: &str, status, stdout: Option<&str>| {
+            Some(Some(TestEvent {
+                name: name.into(),
+                status,
+                stdout: stdout.map(str::to_owned),
+            }))
+        };
+        assert_eq!(
+            Some(None),
+            super::parse(r#"{ "type": "suite", "event": "started", "test_count": 2 }"#)
+        );
+        assert_eq!(
+            test("tests::ok", TestStatus::Running, None),
+            super::parse(r#"{ "type": "test", "event": "started", "name": "tests::ok" }"#)
+        );
+        assert_eq!(
+            test("tests::ok", TestStatus::Passed, None),
+            super::parse(
+                r#"{ "type": "test", "name": "tests::ok", "event": "ok", "exec_time": 0.001 }"#
+            )
+        );
+        assert_eq!(
+            test("tests::ko", TestStatus::Failed, Some("assertion failed\n")),
+            super::parse(
+                r#"{ "type": "test", "name": "tests::ko", "event": "failed", "stdout": "assertion failed\n" }"#
+            )
+        );
+        assert_eq!(
+            test("tests::skip", TestStatus::Ignored, None),
+            super::parse(r#"{ "type": "test", "event": "ignored", "name": "tests::skip" }"#)
+        );
+        assert_eq!(
+            Some(None),
+            super::parse(
+                r#"{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1, "measured": 0, "filtered_out": 0, "exec_time": 0.01 }"#
+            )
+        );
+        assert_eq!(None, super::parse("running 3 tests"));
+        assert_eq!(
+            None,
+            super::parse(r#"{ "reason": "build-finished", "success": true }"#)
+        );
+    }
+}
@@ -0,0 +1,13 @@
+//! Runs the tasks of a workspace, like `cargo clippy` or `cargo test`, and streams their output.
+
+pub mod api;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(feature = "server")]
+mod libtest;
+#[cfg(feature = "server")]
+pub mod service;
+#[cfg(feature = "client")]
+pub mod state;
+#[cfg(feature = "client")]
+pub mod ui;
@@ -0,0 +1,302 @@
+use std::future::ready;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+
+use futures::Stream;
+use futures::StreamExt as _;
+use futures::TryStreamExt as _;
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+use server_fn::ServerFnError;
+use server_fn::codec::TextStream;
+use tokio::io::AsyncBufReadExt as _;
+use tokio::io::BufReader;
+use tokio::process::Command;
+use tokio_stream::wrappers::LinesStream;
+use tracing::Span;
+use tracing::debug;
+use tracing::warn;
+use tracing_futures::Instrument as _;
+
+use super::api::TaskDefinition;
+use super::api::TaskEvent;
+use crate::api::client_address::ClientAddress;
+use crate::backend::client_service::grpc_error::GrpcError;
+use crate::backend::client_service::grpc_error::IsGrpcError;
+use crate::backend::client_service::remote_fn_service;
+use crate::text_editor::rust_lang::service::CompilerMessage;
+use crate::text_editor::rust_lang::service::compiler_message;
+use crate::text_editor::rust_lang::synthetic::SyntheticDiagnostic;
+use crate::utils::ndjson_utils::serialize_line;
+
+/// The tasks of a workspace, relative to its base folder.
+static TASKS_FILE: &str = ".terrazzo/tasks.toml";
+
+pub async fn list_tasks(
+    remote: ClientAddress,
+    base: Arc<Path>,
+) -> Result<Vec<TaskDefinition>, ServerFnError> {
+    debug!(%remote, "Calling list_tasks({base:?})");
+    Ok(LIST_TASKS_FN.call(remote, base).await?)
+}
+
+remote_fn_service::unary::declare_remote_fn!(
+    LIST_TASKS_FN,
+    "texteditor.tasks.list",
+    Arc<Path>,
+    Vec<TaskDefinition>,
+    |_server, base| async move { load_tasks(&base).await.map_err(GrpcError::from) }
+);
+
+pub async fn run_task(
+    remote: ClientAddress,
+    base: Arc<Path>,
+    name: String,
+    test: Option<String>,
+) -> Result<TextStream, ServerFnError> {
+    debug!(%remote, "Calling run_task({base:?}, {name:?}, {test:?})");
+    let stream = RUN_TASK_FN.call(remote, (base, name, test)).await?;
+    let stream = stream.filter_map(|item| {
+        let item = item.map(|item| {
+            serialize_line(&item)
+                .inspect_err(|error| warn!("Failed to serialize: {error}"))
+                .ok()
+        });
+        ready(item.transpose())
+    });
+    Ok(TextStream::new(
+        stream.map_err(Into::into).instrument(Span::current()),
+    ))
+}
+
+remote_fn_service::streaming::declare_remote_fn!(
+    RUN_TASK_FN,
+    "texteditor.tasks.run",
+    (Arc<Path>, String, Option<String>),
+    TaskEvent,
+    |_server, (base, name, test)| {
+        futures::stream::once(async move {
+            match run_task_impl(base, name, test).await {
+                Ok(stream) => stream.left_stream(),
+                Err(error) => futures::stream::once(ready(Err(error))).right_stream(),
+            }
+        })
+        .flatten()
+        .map_err(GrpcError::from)
+    }
+);
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+pub enum TasksError {
+    #[error("[{n}] Failed to read {0:?}: {1}", n = self.name())]
+    ReadConfig(PathBuf, std::io::Error),
+
+    #[error("[{n}] Failed to parse {0:?}: {1}", n = self.name())]
+    ParseConfig(PathBuf, toml::de::Error),
+
+    #[error("[{n}] The task '{0}' doesn't exist", n = self.name())]
+    NotFound(String),
+
+    #[error("[{n}] Failed to run '{0}': {1}", n = self.name())]
+    SpawnProcess(String, std::io::Error),
+
+    #[error("[{n}] The process doesn't have an stdout or an stderr", n = self.name())]
+    MissingOutput,
+
+    #[error("[{n}] {0}", n = self.name())]
+    Output(std::io::Error),
+}
+
+impl IsGrpcError for TasksError {
+    fn code(&self) -> tonic::Code {
+        match self {
+            Self::ReadConfig { .. } => tonic::Code::Internal,
+            Self::ParseConfig { .. } => tonic::Code::InvalidArgument,
+            Self::NotFound { .. } => tonic::Code::NotFound,
+            Self::SpawnProcess { .. } => tonic::Code::FailedPrecondition,
+            Self::MissingOutput => tonic::Code::Internal,
+            Self::Output { .. } => tonic::Code::Internal,
+        }
+    }
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+struct TasksFile {
+    tasks: Vec<TaskDefinition>,
+}
+
+async fn load_tasks(base: &Path) -> Result<Vec<TaskDefinition>, TasksError> {
+    let path = base.join(TASKS_FILE);
+    let content = match tokio::fs::read_to_string(&path).await {
+        Ok(content) => content,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(default_tasks()),
+        Err(error) => return Err(TasksError::ReadConfig(path, error)),
+    };
+    let file: TasksFile =
+        toml::from_str(&content).map_err(|error| TasksError::ParseConfig(path, error))?;
+    Ok(file.tasks)
+}
+
+/// The tasks of workspaces that don't configure any.
+fn default_tasks() -> Vec<TaskDefinition> {
+    let cargo = |name: &str, args: &[&str]| TaskDefinition {
+        name: name.to_owned(),
+        command: "cargo".to_owned(),
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+        ..TaskDefinition::default()
+    };
+    vec![
+        cargo("check", &["check", "--message-format=json"]),
+        cargo("clippy", &["clippy", "--message-format=json"]),
+        cargo("build", &["build", "--message-format=json"]),
+        TaskDefinition {
+            // libtest only prints JSON with unstable options.
+            env: [("RUSTC_BOOTSTRAP".to_owned(), "1".to_owned())].into(),
+            ..cargo(
+                "test",
+                &[
+                    "test",
+                    "--message-format=json",
+                    "--",
+                    "-Z",
+                    "unstable-options",
+                    "--format=json",
+                ],
+            )
+        },
+    ]
+}
+
+async fn run_task_impl(
+    base: Arc<Path>,
+    name: String,
+    test: Option<String>,
+) -> Result<impl Stream<Item = Result<TaskEvent, TasksError>> + Send, TasksError> {
+    let tasks = load_tasks(&base).await?;
+    let task = tasks
+        .into_iter()
+        .find(|task| task.name == name)
+        .ok_or(TasksError::NotFound(name))?;
+
+    let mut command = Command::new(&task.command);
+    command
+        .current_dir(&base)
+        .args(&task.args)
+        .envs(&task.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(test) = test {
+        // The filter goes to the test binary.
+        if !task.args.iter().any(|arg| arg == "--") {
+            command.arg("--");
+        }
+        command.arg(test).arg("--exact");
+    }
+
+    debug!("Spawn {command:?}");
+    let mut child = command
+        .spawn()
+        .map_err(|error| TasksError::SpawnProcess(task.command, error))?;
+    let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+        return Err(TasksError::MissingOutput);
+    };
+
+    let stdout = LinesStream::new(BufReader::new(stdout).lines())
+        .then(|line| async move {
+            match line {
+                Ok(line) => stdout_event(line).await,
+                Err(error) => Err(TasksError::Output(error)),
+            }
+        })
+        .filter_map(|event| ready(event.transpose()));
+    let stderr = LinesStream::new(BufReader::new(stderr).lines()).map(|line| match line {
+        Ok(line) => Ok(TaskEvent::Output { line, stderr: true }),
+        Err(error) => Err(TasksError::Output(error)),
+    });
+
+    // Dropping the stream kills the process.
+    let exit = futures::stream::once(async move {
+        let status = child.wait().await.map_err(TasksError::Output)?;
+        debug!("End: {status}");
+        Ok::<_, TasksError>(TaskEvent::Exit {
+            code: status.code(),
+        })
+    });
+    Ok(futures::stream::select(stdout, stderr).chain(exit))
+}
+
+/// Parses the JSON messages of cargo and libtest, other lines are printed as is.
+async fn stdout_event(line: String) -> Result<Option<TaskEvent>, TasksError> {
+    if let Some(CompilerMessage {
+        mut diagnostics,
+        rendered,
+    }) = compiler_message(&line)
+    {
+        SyntheticDiagnostic::resolve_offsets(&mut diagnostics).await;
+        return Ok(Some(TaskEvent::Diagnostics {
+            diagnostics,
+            rendered,
+        }));
+    }
+    if let Some(test) = super::libtest::parse(&line) {
+        return Ok(test.map(TaskEvent::Test));
+    }
+    if is_cargo_message(&line) {
+        return Ok(None);
+    }
+    Ok(Some(TaskEvent::Output {
+        line,
+        stderr: false,
+    }))
+}
+
+/// The other messages of cargo, like `compiler-artifact` and `build-finished`.
+fn is_cargo_message(line: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(line)
+        .is_ok_and(|message| message.get("reason").is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::super::api::TaskEvent;
+    use super::super::api::TestStatus;
+
+    #[tokio::test]
+    async fn stdout_event() {
+        let event = super::stdout_event("Hello".into()).await.unwrap();
+        assert!(matches!(
+            event,
+            Some(TaskEvent::Output { line, stderr: false }) if line == "Hello"
+        ));
+
+        let event = r#"{ "type": "test", "event": "failed", "name": "a::b", "stdout": "boom" }"#;
+        let event = super::stdout_event(event.into()).await.unwrap();
+        assert!(matches!(
+            event,
+            Some(TaskEvent::Test(test)) if test.name == "a::b" && test.status == TestStatus::Failed
+        ));
+
+        let event = r#"{ "reason": "build-finished", "success": true }"#;
+        let event = super::stdout_event(event.into()).await.unwrap();
+        assert!(event.is_none());
+    }
+
+    #[tokio::test]
+    async fn default_tasks() {
+        let tasks = super::load_tasks(Path::new("/nonexistent")).await.unwrap();
+        let names = tasks
+            .iter()
+            .map(|task| task.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["check", "clippy", "build", "test"], names);
+    }
+}
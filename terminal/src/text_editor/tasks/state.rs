@@ -0,0 +1,17 @@
+use nameth::NamedType as _;
+use nameth::nameth;
+
+use crate::text_editor::manager::EditorState;
+
+#[derive(Clone)]
+#[nameth]
+pub struct EditorTasksState {
+    /// The editor state to go back to when the tasks panel is closed.
+    pub(super) prev: Box<EditorState>,
+}
+
+impl std::fmt::Debug for EditorTasksState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct(EditorTasksState::type_name()).finish()
+    }
+}
@@ -0,0 +1,546 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use futures::StreamExt as _;
+use futures::channel::oneshot;
+use scopeguard::guard;
+use server_fn::ServerFnError;
+use terrazzo::autoclone;
+use terrazzo::html;
+use terrazzo::prelude::*;
+use terrazzo::template;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::Element;
+use web_sys::MouseEvent;
+
+use self::diagnostics::warn;
+use super::api::TaskDefinition;
+use super::api::TaskEvent;
+use super::api::TestStatus;
+use super::state::EditorTasksState;
+use crate::assets::icons;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio::ROOT_FILE_PATH;
+use crate::text_editor::manager::EditorState;
+use crate::text_editor::manager::TextEditorManager;
+use crate::text_editor::problems::ui::Problems;
+use crate::text_editor::problems::ui::group_by_file;
+use crate::text_editor::problems::ui::show_problems;
+use crate::text_editor::rust_lang::synthetic::SyntheticDiagnostic;
+use crate::text_editor::style;
+
+terrazzo_css::import_style!(tasks_style, "tasks.scss");
+
+/// The output of a task is truncated to its last lines.
+const MAX_OUTPUT_LINES: usize = 10_000;
+
+impl TextEditorManager {
+    /// The header button that opens and closes the tasks panel.
+    #[html]
+    pub fn tasks_toggle(self: &Ptr<Self>) -> XElement {
+        let manager = self.clone();
+
+        #[template(wrap = true)]
+        fn make_class(#[signal] editor_state: EditorState) -> XAttributeValue {
+            matches!(editor_state, EditorState::Tasks(_)).then_some(style::ACTIVE)
+        }
+
+        img(
+            class = style::TOGGLE_TASKS_PANEL,
+            class %= make_class(self.editor_state.clone()),
+            #[cfg(not(feature = "client-prod"))]
+            class = "toggle-tasks-panel",
+            src = icons::tasks(),
+            title = "Tasks",
+            click = move |_| toggle_tasks_panel(&manager),
+        )
+    }
+}
+
+fn toggle_tasks_panel(manager: &TextEditorManager) {
+    manager.editor_state.update(|editor_state| {
+        if let EditorState::Tasks(EditorTasksState { prev }) = editor_state {
+            return Some(prev.as_ref().clone());
+        }
+        Some(EditorState::Tasks(EditorTasksState {
+            prev: Box::new(editor_state.clone()),
+        }))
+    });
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct OutputLine {
+    text: String,
+    stderr: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TestResult {
+    status: TestStatus,
+    stdout: Option<String>,
+}
+
+/// The results of the tests, by name.
+type TestResults = BTreeMap<String, TestResult>;
+
+/// The signals of the tasks panel, updated as the output of the running task streams in.
+struct TasksPanel {
+    manager: Ptr<TextEditorManager>,
+    base: Arc<Path>,
+    tasks: XSignal<Option<Arc<Vec<TaskDefinition>>>>,
+    running: XSignal<Option<Arc<str>>>,
+    status: XSignal<Option<Arc<str>>>,
+    output: XSignal<Arc<Vec<OutputLine>>>,
+
+    /// The task that ran the tests, to rerun them.
+    tests_task: RefCell<Option<Arc<str>>>,
+    tests: XSignal<Arc<TestResults>>,
+    selected_test: XSignal<Option<Arc<str>>>,
+
+    diagnostics: RefCell<Vec<SyntheticDiagnostic>>,
+    problems: XSignal<Option<Arc<Problems>>>,
+
+    /// Dropped to stop the running task.
+    cancel: RefCell<Option<oneshot::Sender<()>>>,
+    generation: Cell<u32>,
+}
+
+impl TasksPanel {
+    fn new(manager: &Ptr<TextEditorManager>) -> Ptr<Self> {
+        let root = FilePath {
+            base: manager.path.base.get_value_untracked(),
+            file: ROOT_FILE_PATH.clone(),
+        };
+        Self {
+            manager: manager.clone(),
+            base: root.full_path().into(),
+            tasks: XSignal::new("tasks", None),
+            running: XSignal::new("tasks-running", None),
+            status: XSignal::new("tasks-status", None),
+            output: XSignal::new("tasks-output", Arc::default()),
+            tests_task: RefCell::default(),
+            tests: XSignal::new("tasks-tests", Arc::default()),
+            selected_test: XSignal::new("tasks-selected-test", None),
+            diagnostics: RefCell::default(),
+            problems: XSignal::new("tasks-problems", None),
+            cancel: RefCell::default(),
+            generation: Cell::default(),
+        }
+        .into()
+    }
+
+    fn load(self: &Ptr<Self>) {
+        let this = self.clone();
+        spawn_local(async move {
+            let remote = this.manager.remote.clone();
+            match super::api::list_tasks(remote, this.base.clone()).await {
+                Ok(tasks) => this.tasks.set(Some(Arc::new(tasks))),
+                Err(error) => {
+                    warn!("Failed to list the tasks: {error}");
+                    this.status.set(Some(error.to_string().into()));
+                }
+            }
+        });
+    }
+
+    /// Runs the task, or only one of its tests.
+    fn run(self: &Ptr<Self>, name: Arc<str>, test: Option<String>) {
+        self.stop();
+        let (cancel_tx, cancel_rx) = oneshot::channel();
+        *self.cancel.borrow_mut() = Some(cancel_tx);
+        let generation = self.generation.get();
+
+        let batch = Batch::use_batch("tasks-run");
+        self.running.set(Some(name.clone()));
+        self.status.set(Some(format!("Running {name}...").into()));
+        self.output.set(Arc::default());
+        if test.is_none() {
+            *self.tests_task.borrow_mut() = Some(name.clone());
+            self.tests.set(Arc::default());
+            self.selected_test.set(None);
+            self.diagnostics.borrow_mut().clear();
+            self.problems.set(None);
+        }
+        drop(batch);
+
+        let this = self.clone();
+        spawn_local(async move {
+            let remote = this.manager.remote.clone();
+            let events = super::client::run_task(remote, this.base.clone(), name.to_string(), test);
+            match events.await {
+                Ok(events) => {
+                    let mut events = events.ready_chunks(100).take_until(cancel_rx);
+                    while let Some(events) = events.next().await {
+                        this.apply(&name, events);
+                    }
+                }
+                Err(error) => {
+                    warn!("Failed to run {name}: {error}");
+                    this.status.set(Some(error.to_string().into()));
+                }
+            }
+            if this.generation.get() == generation {
+                this.cancel.borrow_mut().take();
+                this.running.set(None);
+            }
+        });
+    }
+
+    fn stop(&self) {
+        self.generation.set(self.generation.get() + 1);
+        if self.cancel.borrow_mut().take().is_none() {
+            return;
+        }
+        let batch = Batch::use_batch("tasks-stop");
+        if let Some(name) = self.running.get_value_untracked() {
+            self.status.set(Some(format!("Stopped {name}").into()));
+        }
+        self.running.set(None);
+        drop(batch);
+    }
+
+    fn apply(&self, name: &str, events: Vec<Result<TaskEvent, ServerFnError>>) {
+        let mut output = self.output.get_value_untracked().as_ref().clone();
+        let mut tests = None;
+        let mut has_diagnostics = false;
+        let batch = Batch::use_batch("tasks-events");
+        for event in events {
+            match event {
+                Ok(TaskEvent::Output { line, stderr }) => {
+                    output.push(OutputLine { text: line, stderr });
+                }
+                Ok(TaskEvent::Diagnostics {
+                    diagnostics,
+                    rendered,
+                }) => {
+                    let rendered = rendered.iter().flat_map(|rendered| rendered.lines());
+                    output.extend(rendered.map(|line| OutputLine {
+                        text: line.to_owned(),
+                        stderr: false,
+                    }));
+                    self.diagnostics.borrow_mut().extend(diagnostics);
+                    has_diagnostics = true;
+                }
+                Ok(TaskEvent::Test(test)) => {
+                    let tests = tests
+                        .get_or_insert_with(|| self.tests.get_value_untracked().as_ref().clone());
+                    let result = TestResult {
+                        status: test.status,
+                        stdout: test.stdout,
+                    };
+                    tests.insert(test.name, result);
+                }
+                Ok(TaskEvent::Exit { code }) => {
+                    let status = match code {
+                        Some(0) => format!("{name} succeeded"),
+                        Some(code) => format!("{name} failed with exit code {code}"),
+                        None => format!("{name} was killed"),
+                    };
+                    self.status.set(Some(status.into()));
+                }
+                Err(error) => output.push(OutputLine {
+                    text: error.to_string(),
+                    stderr: true,
+                }),
+            }
+        }
+        let excess = output.len().saturating_sub(MAX_OUTPUT_LINES);
+        output.drain(..excess);
+        self.output.set(Arc::new(output));
+        if let Some(tests) = tests {
+            self.tests.set(Arc::new(tests));
+        }
+        if has_diagnostics {
+            let problems = group_by_file(&self.diagnostics.borrow());
+            self.problems.set(Some(Arc::new(problems)));
+        }
+        drop(batch);
+    }
+
+    fn rerun(self: &Ptr<Self>, test: &str) {
+        let Some(task) = self.tests_task.borrow().clone() else {
+            return;
+        };
+        self.run(task, Some(test.to_owned()));
+    }
+}
+
+#[autoclone]
+#[html]
+pub fn tasks_panel(manager: Ptr<TextEditorManager>, _tasks_state: EditorTasksState) -> XElement {
+    let panel = TasksPanel::new(&manager);
+    let stop_on_close = guard(panel.clone(), |panel| panel.stop());
+    div(
+        class = tasks_style::TASKS_PANEL,
+        #[cfg(not(feature = "client-prod"))]
+        class = "tasks-panel",
+        show_toolbar(panel.clone(), panel.tasks.clone(), panel.running.clone()),
+        show_status(panel.status.clone()),
+        show_tests(
+            panel.clone(),
+            panel.tests.clone(),
+            panel.selected_test.clone(),
+        ),
+        show_test_output(panel.tests.clone(), panel.selected_test.clone()),
+        show_problems(manager.clone(), panel.base.clone(), panel.problems.clone()),
+        show_output(panel.output.clone()),
+        after_render = move |_| {
+            autoclone!(panel);
+            let _moved = &stop_on_close;
+            panel.load()
+        },
+    )
+}
+
+#[html]
+#[template(tag = div)]
+fn show_toolbar(
+    panel: Ptr<TasksPanel>,
+    #[signal] tasks: Option<Arc<Vec<TaskDefinition>>>,
+    #[signal] running: Option<Arc<str>>,
+) -> XElement {
+    let Some(tasks) = tasks else {
+        return tag(class = tasks_style::TASKS_TOOLBAR, "Loading...");
+    };
+    let buttons = tasks.iter().map(|task| {
+        let panel = panel.clone();
+        let name: Arc<str> = task.name.as_str().into();
+        let label = name.clone();
+        let command = std::iter::once(&task.command).chain(&task.args);
+        let command = command.map(String::as_str).collect::<Vec<_>>().join(" ");
+        button(
+            class = (running.as_ref() == Some(&name)).then_some(tasks_style::ACTIVE),
+            title = command,
+            click = move |_| panel.run(name.clone(), None),
+            "{label}",
+        )
+    });
+    let stop = running.is_some().then(|| {
+        let panel = panel.clone();
+        button(click = move |_: MouseEvent| panel.stop(), "Stop")
+    });
+    tag(class = tasks_style::TASKS_TOOLBAR, buttons.., stop..)
+}
+
+#[html]
+#[template(tag = div)]
+fn show_status(#[signal] status: Option<Arc<str>>) -> XElement {
+    let Some(status) = status else {
+        return tag(style::display = "none", style::visibility = "hidden");
+    };
+    tag(class = tasks_style::TASKS_STATUS, "{status}")
+}
+
+/// The tests grouped by module.
+#[derive(Default)]
+struct TestNode<'t> {
+    children: BTreeMap<&'t str, TestNode<'t>>,
+    test: Option<(&'t str, &'t TestResult)>,
+}
+
+impl<'t> TestNode<'t> {
+    fn new(tests: &'t TestResults) -> Self {
+        let mut root = Self::default();
+        for (name, result) in tests {
+            let mut node = &mut root;
+            for component in name.split("::") {
+                node = node.children.entry(component).or_default();
+            }
+            node.test = Some((name, result));
+        }
+        root
+    }
+
+    /// The number of passed and failed tests.
+    fn count(&self, status: TestStatus) -> usize {
+        let this = self.test.filter(|(_, result)| result.status == status);
+        let children = self.children.values().map(|child| child.count(status));
+        this.iter().count() + children.sum::<usize>()
+    }
+}
+
+#[html]
+#[template(tag = div)]
+fn show_tests(
+    panel: Ptr<TasksPanel>,
+    #[signal] tests: Arc<TestResults>,
+    #[signal] selected_test: Option<Arc<str>>,
+) -> XElement {
+    if tests.is_empty() {
+        return tag(style::display = "none", style::visibility = "hidden");
+    }
+    let root = TestNode::new(&tests);
+    let passed = root.count(TestStatus::Passed);
+    let failed = root.count(TestStatus::Failed);
+    let children = root
+        .children
+        .iter()
+        .map(|(name, node)| show_test_node(&panel, name, node, selected_test.as_deref()));
+    tag(
+        class = tasks_style::TASKS_TESTS,
+        div("{passed} passed, {failed} failed"),
+        children..,
+    )
+}
+
+fn show_test_node(
+    panel: &Ptr<TasksPanel>,
+    name: &str,
+    node: &TestNode,
+    selected_test: Option<&str>,
+) -> XElement {
+    if let Some((full_name, result)) = node.test
+        && node.children.is_empty()
+    {
+        return show_test(
+            panel,
+            name,
+            full_name,
+            result,
+            selected_test == Some(full_name),
+        );
+    }
+    show_test_module(panel, name, node, selected_test)
+}
+
+#[html]
+fn show_test_module(
+    panel: &Ptr<TasksPanel>,
+    name: &str,
+    node: &TestNode,
+    selected_test: Option<&str>,
+) -> XElement {
+    let passed = node.count(TestStatus::Passed);
+    let failed = node.count(TestStatus::Failed);
+    let children = node
+        .children
+        .iter()
+        .map(|(name, child)| show_test_node(panel, name, child, selected_test));
+    details(
+        class = tasks_style::TEST_MODULE,
+        open = "open",
+        summary(
+            class = test_status_class((failed > 0).then_some(TestStatus::Failed)),
+            "{name} ({passed} passed, {failed} failed)",
+        ),
+        children..,
+    )
+}
+
+#[autoclone]
+#[html]
+fn show_test(
+    panel: &Ptr<TasksPanel>,
+    name: &str,
+    full_name: &str,
+    result: &TestResult,
+    is_selected: bool,
+) -> XElement {
+    let full_name: Arc<str> = full_name.into();
+    let icon = match result.status {
+        TestStatus::Running => "…",
+        TestStatus::Passed => "✓",
+        TestStatus::Failed => "✗",
+        TestStatus::Ignored => "-",
+    };
+    let panel = panel.clone();
+    div(
+        class = tasks_style::TEST,
+        class = is_selected.then_some(tasks_style::ACTIVE),
+        class = test_status_class(Some(result.status)),
+        click = move |_| {
+            autoclone!(panel, full_name);
+            let selected = (panel.selected_test.get_value_untracked() != Some(full_name.clone()))
+                .then_some(full_name.clone());
+            panel.selected_test.set(selected)
+        },
+        span(class = tasks_style::TEST_ICON, "{icon}"),
+        span(class = tasks_style::TEST_NAME, "{name}"),
+        button(
+            "Rerun",
+            click = move |event: MouseEvent| {
+                autoclone!(panel, full_name);
+                event.stop_propagation();
+                panel.rerun(&full_name)
+            },
+        ),
+    )
+}
+
+fn test_status_class(status: Option<TestStatus>) -> Option<&'static str> {
+    match status? {
+        TestStatus::Running => Some(tasks_style::RUNNING),
+        TestStatus::Passed => Some(tasks_style::PASSED),
+        TestStatus::Failed => Some(tasks_style::FAILED),
+        TestStatus::Ignored => Some(tasks_style::IGNORED),
+    }
+}
+
+#[html]
+#[template(tag = pre)]
+fn show_test_output(
+    #[signal] tests: Arc<TestResults>,
+    #[signal] selected_test: Option<Arc<str>>,
+) -> XElement {
+    let stdout = selected_test
+        .and_then(|selected_test| tests.get(selected_test.as_ref()))
+        .and_then(|result| result.stdout.clone());
+    let Some(stdout) = stdout else {
+        return tag(style::display = "none", style::visibility = "hidden");
+    };
+    tag(class = tasks_style::TEST_OUTPUT, "{stdout}")
+}
+
+#[html]
+#[template(tag = pre)]
+fn show_output(#[signal] output: Arc<Vec<OutputLine>>) -> XElement {
+    let lines = output.iter().map(|OutputLine { text, stderr }| {
+        span(class = stderr.then_some(tasks_style::STDERR), "{text}\n")
+    });
+    tag(
+        class = tasks_style::TASKS_OUTPUT,
+        lines..,
+        after_render = |output: &Element| output.set_scroll_top(output.scroll_height()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TestNode;
+    use super::TestResult;
+    use super::TestResults;
+    use super::TestStatus;
+
+    #[test]
+    fn test_tree() {
+        let result = |status| TestResult {
+            status,
+            stdout: None,
+        };
+        let tests: TestResults = [
+            ("a::tests::ok".to_owned(), result(TestStatus::Passed)),
+            ("a::tests::ko".to_owned(), result(TestStatus::Failed)),
+            ("b::ok".to_owned(), result(TestStatus::Passed)),
+            ("top".to_owned(), result(TestStatus::Ignored)),
+        ]
+        .into();
+        let root = TestNode::new(&tests);
+        assert_eq!(
+            vec!["a", "b", "top"],
+            root.children.keys().collect::<Vec<_>>()
+        );
+        let a = &root.children["a"];
+        assert_eq!(1, a.count(TestStatus::Passed));
+        assert_eq!(1, a.count(TestStatus::Failed));
+        assert_eq!(2, root.count(TestStatus::Passed));
+        assert_eq!(
+            Some("a::tests::ko"),
+            a.children["tests"].children["ko"]
+                .test
+                .map(|(name, _)| name)
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use server_fn::Http;
+use server_fn::ServerFnError;
+use server_fn::codec::Json;
+use terrazzo::server;
+
+use crate::api::client_address::ClientAddress;
+use crate::text_editor::file_path::FilePath;
+
+/// A file or folder in the trash.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TrashEntry {
+    /// Where the entry is in the trash.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "p"))]
+    pub path: Arc<Path>,
+
+    /// Where the entry was before it was deleted, if it was recorded.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "o"))]
+    pub original: Option<Arc<Path>>,
+
+    /// When the entry was deleted, since the epoch.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "t"))]
+    pub deleted: Duration,
+
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "d"))]
+    pub is_dir: bool,
+
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "s"))]
+    pub size: Option<u64>,
+}
+
+/// What to do when a file already exists where a trash entry is restored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum RestoreConflict {
+    /// Don't restore the entry.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "F"))]
+    Fail,
+
+    /// Restore the entry under another name next to the existing file.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "K"))]
+    KeepBoth,
+
+    /// Move the existing file to the trash, then restore the entry.
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "R"))]
+    Replace,
+}
+
+/// Lists the global trash, and the git trash of the repo that contains `base`.
+#[server(protocol = Http<Json, Json>)]
+pub async fn list(
+    remote: ClientAddress,
+    base: FilePath<Arc<Path>>,
+) -> Result<Vec<TrashEntry>, ServerFnError> {
+    super::service::list(remote, base).await
+}
+
+/// Loads the beginning of a trash file, or the names of the files in a trash folder.
+#[server(protocol = Http<Json, Json>)]
+pub async fn preview(remote: ClientAddress, entry: Arc<Path>) -> Result<Arc<str>, ServerFnError> {
+    super::service::preview(remote, entry).await
+}
+
+/// Moves a trash entry back to where it was deleted from, and returns where it was restored.
+#[server(protocol = Http<Json, Json>)]
+pub async fn restore(
+    remote: ClientAddress,
+    entry: Arc<Path>,
+    on_conflict: RestoreConflict,
+) -> Result<Arc<Path>, ServerFnError> {
+    super::service::restore(remote, entry, on_conflict).await
+}
+
+/// Deletes the trash entries older than the configured `trash_max_age`, and returns how many.
+#[server(protocol = Http<Json, Json>)]
+pub async fn purge(
+    remote: ClientAddress,
+    base: FilePath<Arc<Path>>,
+) -> Result<usize, ServerFnError> {
+    super::service::purge(remote, base).await
+}
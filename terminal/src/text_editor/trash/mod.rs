@@ -0,0 +1,9 @@
+//! Lists, previews, restores, and purges the files deleted from the text editor.
+
+pub mod api;
+#[cfg(feature = "server")]
+mod service;
+#[cfg(feature = "client")]
+pub mod state;
+#[cfg(feature = "client")]
+pub mod ui;
@@ -0,0 +1,450 @@
+use std::os::unix::fs::MetadataExt as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+use server_fn::ServerFnError;
+use tokio::io::AsyncReadExt as _;
+use tracing::debug;
+
+use super::api::RestoreConflict;
+use super::api::TrashEntry;
+use crate::api::client_address::ClientAddress;
+use crate::backend::Server;
+use crate::backend::client_service::grpc_error::GrpcError;
+use crate::backend::client_service::grpc_error::IsGrpcError;
+use crate::backend::client_service::remote_fn_service;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio::ROOT_BASE_PATH;
+use crate::text_editor::fsio::service::FsioError;
+use crate::text_editor::fsio::service::TRASH_INFO_FOLDER;
+use crate::text_editor::fsio::service::delete_file;
+use crate::text_editor::fsio::service::delete_trash_path;
+use crate::text_editor::fsio::service::read_trash_info;
+use crate::text_editor::fsio::service::remove_trash_info;
+use crate::text_editor::fsio::service::split_archive_extension;
+use crate::text_editor::search::service::reconcile_touched_path;
+
+/// How much of a trash file is previewed.
+const MAX_PREVIEW_LEN: u64 = 64 * 1024;
+
+pub async fn list(
+    remote: ClientAddress,
+    base: FilePath<Arc<Path>>,
+) -> Result<Vec<TrashEntry>, ServerFnError> {
+    debug!(%remote, "Calling list({base:?})");
+    Ok(LIST_FN.call(remote, base).await?)
+}
+
+pub async fn preview(remote: ClientAddress, entry: Arc<Path>) -> Result<Arc<str>, ServerFnError> {
+    debug!(%remote, "Calling preview({entry:?})");
+    Ok(PREVIEW_FN.call(remote, entry).await?)
+}
+
+pub async fn restore(
+    remote: ClientAddress,
+    entry: Arc<Path>,
+    on_conflict: RestoreConflict,
+) -> Result<Arc<Path>, ServerFnError> {
+    debug!(%remote, "Calling restore({entry:?}, {on_conflict:?})");
+    Ok(RESTORE_FN.call(remote, (entry, on_conflict)).await?)
+}
+
+pub async fn purge(
+    remote: ClientAddress,
+    base: FilePath<Arc<Path>>,
+) -> Result<usize, ServerFnError> {
+    debug!(%remote, "Calling purge({base:?})");
+    Ok(PURGE_FN.call(remote, base).await?)
+}
+
+/// Where deleted files go, and how long they stay there.
+#[derive(Clone)]
+struct TrashSettings {
+    trash: Arc<Path>,
+    git_trash: Option<Arc<Path>>,
+    max_age: Duration,
+}
+
+fn trash_settings(server: &Server) -> TrashSettings {
+    server.config().server.with(|server| TrashSettings {
+        trash: server.trash.clone(),
+        git_trash: server.git_trash.clone(),
+        max_age: server.trash_max_age,
+    })
+}
+
+remote_fn_service::unary::declare_remote_fn!(
+    LIST_FN,
+    "texteditor.trash.list",
+    FilePath<Arc<Path>>,
+    Vec<TrashEntry>,
+    |server, base| {
+        let settings = trash_settings(server);
+        async move { list_impl(base, settings).await.map_err(GrpcError::from) }
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    PREVIEW_FN,
+    "texteditor.trash.preview",
+    Arc<Path>,
+    Arc<str>,
+    |server, entry| {
+        let settings = trash_settings(server);
+        async move { preview_impl(entry, settings).await.map_err(GrpcError::from) }
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    RESTORE_FN,
+    "texteditor.trash.restore",
+    (Arc<Path>, RestoreConflict),
+    Arc<Path>,
+    |server, (entry, on_conflict)| {
+        let settings = trash_settings(server);
+        async move {
+            restore_impl(entry, on_conflict, settings)
+                .await
+                .map_err(GrpcError::from)
+        }
+    }
+);
+
+remote_fn_service::unary::declare_remote_fn!(
+    PURGE_FN,
+    "texteditor.trash.purge",
+    FilePath<Arc<Path>>,
+    usize,
+    |server, base| {
+        let settings = trash_settings(server);
+        async move { purge_impl(base, settings).await.map_err(GrpcError::from) }
+    }
+);
+
+impl TrashSettings {
+    /// The global trash, and the git trash of the repo that contains `base`.
+    fn folders(&self, base: &Path) -> Vec<PathBuf> {
+        let mut folders = vec![self.trash.to_path_buf()];
+        let git_trash = delete_trash_path(base, &self.trash, self.git_trash.as_deref());
+        if !folders.contains(&git_trash) {
+            folders.push(git_trash);
+        }
+        folders
+    }
+
+    /// Checks that the entry is directly inside a trash folder.
+    fn check_entry(&self, entry: &Path) -> Result<(), TrashError> {
+        let is_entry = entry
+            .file_name()
+            .is_some_and(|name| name != TRASH_INFO_FOLDER)
+            && entry
+                .parent()
+                .is_some_and(|parent| self.folders(parent).iter().any(|f| f == parent));
+        if is_entry && entry.symlink_metadata().is_ok() {
+            Ok(())
+        } else {
+            Err(TrashError::NotInTrash {
+                path: entry.to_owned(),
+            })
+        }
+    }
+}
+
+async fn list_impl(
+    base: FilePath<Arc<Path>>,
+    settings: TrashSettings,
+) -> Result<Vec<TrashEntry>, TrashError> {
+    let mut entries = vec![];
+    for folder in settings.folders(&base.full_path()) {
+        let Ok(mut read_dir) = tokio::fs::read_dir(&folder).await else {
+            continue;
+        };
+        while let Some(file) = read_dir.next_entry().await? {
+            if file.file_name() == TRASH_INFO_FOLDER {
+                continue;
+            }
+            let path = file.path();
+            let metadata = tokio::fs::symlink_metadata(&path).await?;
+            let info = read_trash_info(&path).await;
+            entries.push(TrashEntry {
+                deleted: deleted_time(info.as_ref().map(|info| info.deleted), &metadata),
+                original: info.map(|info| info.path.into()),
+                is_dir: metadata.is_dir(),
+                size: metadata.is_file().then_some(metadata.len()),
+                path: path.into(),
+            });
+        }
+    }
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.deleted));
+    Ok(entries)
+}
+
+/// When the entry was deleted, or when it was last moved if that wasn't recorded.
+fn deleted_time(recorded: Option<Duration>, metadata: &std::fs::Metadata) -> Duration {
+    recorded.unwrap_or_else(|| Duration::from_secs(metadata.ctime().max(0) as u64))
+}
+
+async fn preview_impl(entry: Arc<Path>, settings: TrashSettings) -> Result<Arc<str>, TrashError> {
+    settings.check_entry(&entry)?;
+    if entry.is_dir() {
+        let mut names = vec![];
+        let mut read_dir = tokio::fs::read_dir(&entry).await?;
+        while let Some(file) = read_dir.next_entry().await? {
+            let name = file.file_name().to_string_lossy().into_owned();
+            names.push(if file.file_type().await?.is_dir() {
+                name + "/"
+            } else {
+                name
+            });
+        }
+        names.sort();
+        return Ok(names.join("\n").into());
+    }
+    let mut content = vec![];
+    tokio::fs::File::open(&entry)
+        .await?
+        .take(MAX_PREVIEW_LEN)
+        .read_to_end(&mut content)
+        .await?;
+    Ok(String::from_utf8_lossy(&content).into())
+}
+
+async fn restore_impl(
+    entry: Arc<Path>,
+    on_conflict: RestoreConflict,
+    settings: TrashSettings,
+) -> Result<Arc<Path>, TrashError> {
+    settings.check_entry(&entry)?;
+    let Some(info) = read_trash_info(&entry).await else {
+        return Err(TrashError::UnknownOriginal {
+            path: entry.to_path_buf(),
+        });
+    };
+    let mut destination = info.path;
+    let mut replaced = None;
+    if destination.symlink_metadata().is_ok() {
+        match on_conflict {
+            RestoreConflict::Fail => {
+                return Err(TrashError::RestoreConflict { path: destination });
+            }
+            RestoreConflict::KeepBoth => destination = restored_path(&destination)?,
+            // Deleting the existing file renames the entry if it goes to the same trash
+            // under the same name: the entry is restored next to the existing file first.
+            RestoreConflict::Replace => {
+                let restored = restored_path(&destination)?;
+                replaced = Some(std::mem::replace(&mut destination, restored));
+            }
+        }
+    }
+    if let Some(parent) = destination.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::rename(&entry, &destination).await?;
+    remove_trash_info(&entry).await?;
+    if let Some(replaced) = replaced {
+        let existing = FilePath {
+            base: ROOT_BASE_PATH.clone(),
+            file: Arc::from(replaced.as_path()),
+        };
+        delete_file(existing, &settings.trash, settings.git_trash.as_deref()).await?;
+        tokio::fs::rename(&destination, &replaced).await?;
+        destination = replaced;
+    }
+    reconcile_touched_path(&destination);
+    Ok(destination.into())
+}
+
+/// A free name next to `path` for the restored copy.
+fn restored_path(path: &Path) -> Result<PathBuf, TrashError> {
+    let file_name = path.file_name().and_then(|name| name.to_str());
+    let Some(file_name) = file_name else {
+        return Err(TrashError::UnknownOriginal {
+            path: path.to_owned(),
+        });
+    };
+    let (name, extension) = split_archive_extension(file_name);
+    for suffix in std::iter::once(String::new()).chain((1..).map(|index| format!("-{index}"))) {
+        let candidate = if extension.is_empty() {
+            format!("{name}_restored{suffix}")
+        } else {
+            format!("{name}_restored{suffix}.{extension}")
+        };
+        let candidate = path.with_file_name(candidate);
+        if candidate.symlink_metadata().is_err() {
+            return Ok(candidate);
+        }
+    }
+    unreachable!()
+}
+
+async fn purge_impl(
+    base: FilePath<Arc<Path>>,
+    settings: TrashSettings,
+) -> Result<usize, TrashError> {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    let mut purged = 0;
+    for entry in list_impl(base, settings.clone()).await? {
+        if now.saturating_sub(entry.deleted) < settings.max_age {
+            continue;
+        }
+        debug!("Purging {:?}", entry.path);
+        if entry.is_dir {
+            tokio::fs::remove_dir_all(&entry.path).await?;
+        } else {
+            tokio::fs::remove_file(&entry.path).await?;
+        }
+        remove_trash_info(&entry.path).await?;
+        purged += 1;
+    }
+    Ok(purged)
+}
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+pub enum TrashError {
+    #[error("[{n}] {0}", n = self.name())]
+    IO(#[from] std::io::Error),
+
+    #[error("[{n}] {0}", n = self.name())]
+    Fsio(#[from] FsioError),
+
+    #[error("[{n}] Not in the trash: {path:?}", n = self.name())]
+    NotInTrash { path: PathBuf },
+
+    #[error("[{n}] Unknown original location of {path:?}", n = self.name())]
+    UnknownOriginal { path: PathBuf },
+
+    #[error("[{n}] Already exists: {path:?}", n = self.name())]
+    RestoreConflict { path: PathBuf },
+}
+
+impl IsGrpcError for TrashError {
+    fn code(&self) -> tonic::Code {
+        match self {
+            Self::IO { .. } => tonic::Code::FailedPrecondition,
+            Self::Fsio(error) => error.code(),
+            Self::NotInTrash { .. } => tonic::Code::InvalidArgument,
+            Self::UnknownOriginal { .. } => tonic::Code::FailedPrecondition,
+            Self::RestoreConflict { .. } => tonic::Code::AlreadyExists,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::RestoreConflict;
+    use super::TrashError;
+    use super::TrashSettings;
+    use crate::text_editor::file_path::FilePath;
+    use crate::text_editor::fsio::ROOT_FILE_PATH;
+    use crate::text_editor::fsio::service::delete_file;
+
+    #[tokio::test]
+    async fn delete_list_restore_and_purge() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let source = tempdir.path().join("source");
+        let trash: Arc<Path> = tempdir.path().join("trash").into();
+        std::fs::create_dir(&source).unwrap();
+        let settings = |max_age| TrashSettings {
+            trash: trash.clone(),
+            git_trash: None,
+            max_age,
+        };
+        let base: FilePath<Arc<Path>> = FilePath {
+            base: Arc::from(source.as_path()),
+            file: ROOT_FILE_PATH.clone(),
+        };
+        let delete = async |content: &str| {
+            std::fs::write(source.join("file.txt"), content).unwrap();
+            let path = FilePath {
+                base: base.base.clone(),
+                file: Arc::from(Path::new("file.txt")),
+            };
+            delete_file(path, &trash, None::<&Path>).await.unwrap();
+        };
+        let list = async || {
+            super::list_impl(base.clone(), settings(Duration::MAX))
+                .await
+                .unwrap()
+        };
+
+        delete("first").await;
+        delete("second").await;
+        let entries = list().await;
+        assert_eq!(2, entries.len());
+        for entry in &entries {
+            assert_eq!(
+                Some(source.join("file.txt").as_path()),
+                entry.original.as_deref()
+            );
+        }
+        let preview = async |path| {
+            super::preview_impl(path, settings(Duration::MAX))
+                .await
+                .map(|preview| preview.to_string())
+        };
+        assert_eq!("second", preview(entries[0].path.clone()).await.unwrap());
+        assert_eq!("first", preview(entries[1].path.clone()).await.unwrap());
+        assert!(matches!(
+            preview(source.join("file.txt").into()).await,
+            Err(TrashError::NotInTrash { .. })
+        ));
+
+        let restore = async |path, on_conflict| {
+            super::restore_impl(path, on_conflict, settings(Duration::MAX)).await
+        };
+        let restored = restore(entries[0].path.clone(), RestoreConflict::Fail)
+            .await
+            .unwrap();
+        assert_eq!(source.join("file.txt").as_path(), &*restored);
+        assert!(matches!(
+            restore(entries[1].path.clone(), RestoreConflict::Fail).await,
+            Err(TrashError::RestoreConflict { .. })
+        ));
+        let restored = restore(entries[1].path.clone(), RestoreConflict::KeepBoth)
+            .await
+            .unwrap();
+        assert_eq!(source.join("file_restored.txt").as_path(), &*restored);
+        assert_eq!("first", std::fs::read_to_string(&restored).unwrap());
+        assert!(list().await.is_empty());
+
+        delete("third").await;
+        std::fs::write(source.join("file.txt"), "fourth").unwrap();
+        let entries = list().await;
+        let restored = restore(entries[0].path.clone(), RestoreConflict::Replace)
+            .await
+            .unwrap();
+        assert_eq!(source.join("file.txt").as_path(), &*restored);
+        assert_eq!("third", std::fs::read_to_string(&restored).unwrap());
+        let entries = list().await;
+        assert_eq!(1, entries.len());
+        assert_eq!("fourth", preview(entries[0].path.clone()).await.unwrap());
+
+        let purge = async |max_age| {
+            super::purge_impl(base.clone(), settings(max_age))
+                .await
+                .unwrap()
+        };
+        assert_eq!(0, purge(Duration::from_secs(3600)).await);
+        assert_eq!(1, list().await.len());
+        assert_eq!(1, purge(Duration::ZERO).await);
+        assert!(list().await.is_empty());
+        assert_eq!(
+            0,
+            std::fs::read_dir(trash.join(super::TRASH_INFO_FOLDER))
+                .unwrap()
+                .count()
+        );
+    }
+}
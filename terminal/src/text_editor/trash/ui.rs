@@ -0,0 +1,305 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use server_fn::ServerFnError;
+use terrazzo::autoclone;
+use terrazzo::html;
+use terrazzo::prelude::*;
+use terrazzo::template;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::MouseEvent;
+
+use self::diagnostics::warn;
+use super::api::RestoreConflict;
+use super::api::TrashEntry;
+use super::state::EditorTrashState;
+use crate::assets::icons;
+use crate::frontend::remotes::Remote;
+use crate::frontend::timestamp::datetime::DateTime;
+use crate::frontend::timestamp::display_timestamp;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio::ROOT_FILE_PATH;
+use crate::text_editor::manager::EditorState;
+use crate::text_editor::manager::TextEditorManager;
+use crate::text_editor::style;
+use crate::text_editor::ui::folder::timestamp;
+
+terrazzo_css::import_style!(trash_style, "trash.scss");
+
+impl TextEditorManager {
+    /// The header button that opens and closes the trash panel.
+    #[html]
+    pub fn trash_toggle(self: &Ptr<Self>) -> XElement {
+        let manager = self.clone();
+
+        #[template(wrap = true)]
+        fn make_class(#[signal] editor_state: EditorState) -> XAttributeValue {
+            matches!(editor_state, EditorState::Trash(_)).then_some(style::ACTIVE)
+        }
+
+        img(
+            class = style::TOGGLE_TRASH_PANEL,
+            class %= make_class(self.editor_state.clone()),
+            #[cfg(not(feature = "client-prod"))]
+            class = "toggle-trash-panel",
+            src = icons::trash_bin(),
+            title = "Trash",
+            click = move |_| toggle_trash_panel(&manager),
+        )
+    }
+}
+
+fn toggle_trash_panel(manager: &TextEditorManager) {
+    manager.editor_state.update(|editor_state| {
+        if let EditorState::Trash(EditorTrashState { prev }) = editor_state {
+            return Some(prev.as_ref().clone());
+        }
+        Some(EditorState::Trash(EditorTrashState {
+            prev: Box::new(editor_state.clone()),
+        }))
+    });
+}
+
+/// The signals of the trash panel, reloaded after each operation.
+struct TrashPanel {
+    remote: Remote,
+    base: FilePath<Arc<Path>>,
+    entries: XSignal<Option<Arc<Vec<TrashEntry>>>>,
+    selected: XSignal<Option<Arc<Path>>>,
+    preview: XSignal<Option<Arc<str>>>,
+    status: XSignal<Option<Arc<str>>>,
+}
+
+impl TrashPanel {
+    fn new(manager: &TextEditorManager) -> Ptr<Self> {
+        Self {
+            remote: manager.remote.clone(),
+            base: FilePath {
+                base: manager.path.base.get_value_untracked(),
+                file: ROOT_FILE_PATH.clone(),
+            },
+            entries: XSignal::new("trash-entries", None),
+            selected: XSignal::new("trash-selected", None),
+            preview: XSignal::new("trash-preview", None),
+            status: XSignal::new("trash-status", None),
+        }
+        .into()
+    }
+
+    fn refresh(self: &Ptr<Self>) {
+        let this = self.clone();
+        spawn_local(async move {
+            let entries = super::api::list(this.remote.clone(), this.base.clone()).await;
+            let Some(entries) = this.check(entries) else {
+                return;
+            };
+            let batch = Batch::use_batch("trash-refresh");
+            if let Some(selected) = this.selected.get_value_untracked()
+                && !entries.iter().any(|entry| entry.path == selected)
+            {
+                this.selected.set(None);
+                this.preview.set(None);
+            }
+            this.entries.set(Some(Arc::new(entries)));
+            drop(batch);
+        });
+    }
+
+    fn check<T>(&self, result: Result<T, ServerFnError>) -> Option<T> {
+        result
+            .inspect_err(|error| {
+                warn!("Trash operation failed: {error}");
+                self.status.set(Some(error.to_string().into()));
+            })
+            .ok()
+    }
+
+    fn select(self: &Ptr<Self>, path: Arc<Path>) {
+        if self.selected.get_value_untracked().as_ref() == Some(&path) {
+            let batch = Batch::use_batch("trash-unselect");
+            self.selected.set(None);
+            self.preview.set(None);
+            drop(batch);
+            return;
+        }
+        self.selected.set(Some(path.clone()));
+        let this = self.clone();
+        spawn_local(async move {
+            let preview = super::api::preview(this.remote.clone(), path.clone()).await;
+            if let Some(preview) = this.check(preview)
+                && this.selected.get_value_untracked() == Some(path)
+            {
+                this.preview.set(Some(preview));
+            }
+        });
+    }
+
+    fn restore(self: &Ptr<Self>, entry: Arc<Path>) {
+        let this = self.clone();
+        spawn_local(async move {
+            let mut on_conflict = RestoreConflict::Fail;
+            let restored = loop {
+                match super::api::restore(this.remote.clone(), entry.clone(), on_conflict).await {
+                    Err(error) if on_conflict == RestoreConflict::Fail && is_conflict(&error) => {
+                        let Some(choice) = ask_on_conflict(&error) else {
+                            return;
+                        };
+                        on_conflict = choice;
+                    }
+                    result => break result,
+                }
+            };
+            if let Some(restored) = this.check(restored) {
+                let restored = restored.display();
+                this.status.set(Some(format!("Restored {restored}").into()));
+            }
+            this.refresh();
+        });
+    }
+
+    fn purge(self: &Ptr<Self>) {
+        let window = web_sys::window().or_throw("window");
+        let confirmed = window.confirm_with_message("Permanently delete the old trash entries?");
+        if !confirmed.unwrap_or(false) {
+            return;
+        }
+        let this = self.clone();
+        spawn_local(async move {
+            let purged = super::api::purge(this.remote.clone(), this.base.clone()).await;
+            if let Some(purged) = this.check(purged) {
+                this.status
+                    .set(Some(format!("Purged {purged} entries").into()));
+            }
+            this.refresh();
+        });
+    }
+}
+
+/// Matches the name of the `TrashError::RestoreConflict` error returned by the server.
+fn is_conflict(error: &ServerFnError) -> bool {
+    error.to_string().contains("[RestoreConflict]")
+}
+
+fn ask_on_conflict(error: &ServerFnError) -> Option<RestoreConflict> {
+    let window = web_sys::window().or_throw("window");
+    let confirm = |message: String| window.confirm_with_message(&message).unwrap_or(false);
+    if confirm(format!(
+        "{error}\n\nReplace it? The existing file is moved to the trash."
+    )) {
+        Some(RestoreConflict::Replace)
+    } else if confirm(format!("{error}\n\nRestore a copy next to it?")) {
+        Some(RestoreConflict::KeepBoth)
+    } else {
+        None
+    }
+}
+
+#[autoclone]
+#[html]
+pub fn trash_panel(manager: Ptr<TextEditorManager>, _trash_state: EditorTrashState) -> XElement {
+    let panel = TrashPanel::new(&manager);
+    div(
+        class = trash_style::TRASH_PANEL,
+        #[cfg(not(feature = "client-prod"))]
+        class = "trash-panel",
+        div(
+            class = trash_style::TRASH_TOOLBAR,
+            button(
+                "Refresh",
+                click = move |_| {
+                    autoclone!(panel);
+                    panel.refresh()
+                },
+            ),
+            button(
+                "Purge old entries",
+                click = move |_| {
+                    autoclone!(panel);
+                    panel.purge()
+                },
+            ),
+        ),
+        show_status(panel.status.clone()),
+        show_entries(panel.clone(), panel.entries.clone(), panel.selected.clone()),
+        show_preview(panel.preview.clone()),
+        after_render = move |_| {
+            autoclone!(panel);
+            panel.refresh()
+        },
+    )
+}
+
+#[html]
+#[template(tag = div)]
+fn show_status(#[signal] status: Option<Arc<str>>) -> XElement {
+    let Some(status) = status else {
+        return tag(style::display = "none", style::visibility = "hidden");
+    };
+    tag(class = trash_style::TRASH_STATUS, "{status}")
+}
+
+#[html]
+#[template(tag = div)]
+fn show_entries(
+    panel: Ptr<TrashPanel>,
+    #[signal] entries: Option<Arc<Vec<TrashEntry>>>,
+    #[signal] selected: Option<Arc<Path>>,
+) -> XElement {
+    let Some(entries) = entries else {
+        return tag(class = trash_style::TRASH_ENTRIES, "Loading...");
+    };
+    if entries.is_empty() {
+        return tag(class = trash_style::TRASH_ENTRIES, "The trash is empty");
+    }
+    let rows = entries
+        .iter()
+        .map(|entry| entry_row(&panel, entry, selected.as_ref() == Some(&entry.path)));
+    tag(class = trash_style::TRASH_ENTRIES, rows..)
+}
+
+#[autoclone]
+#[html]
+fn entry_row(panel: &Ptr<TrashPanel>, entry: &TrashEntry, is_selected: bool) -> XElement {
+    let path = entry.path.clone();
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = if entry.is_dir { name + "/" } else { name };
+    let original = match &entry.original {
+        Some(original) => original.display().to_string(),
+        None => "Unknown location".to_owned(),
+    };
+    let restore = entry.original.is_some().then(|| {
+        button(
+            "Restore",
+            click = move |event: MouseEvent| {
+                autoclone!(panel, path);
+                event.stop_propagation();
+                panel.restore(path.clone())
+            },
+        )
+    });
+    let panel = panel.clone();
+    div(
+        class = trash_style::TRASH_ENTRY,
+        class = is_selected.then_some(trash_style::ACTIVE),
+        click = move |_| {
+            autoclone!(panel, path);
+            panel.select(path.clone())
+        },
+        span(class = trash_style::TRASH_NAME, "{name}"),
+        span(class = trash_style::TRASH_ORIGINAL, "{original}"),
+        timestamp(display_timestamp(DateTime::from_utc(entry.deleted))),
+        restore..,
+    )
+}
+
+#[html]
+#[template(tag = pre)]
+fn show_preview(#[signal] preview: Option<Arc<str>>) -> XElement {
+    let Some(preview) = preview else {
+        return tag(style::display = "none", style::visibility = "hidden");
+    };
+    tag(class = trash_style::TRASH_PREVIEW, "{preview}")
+}
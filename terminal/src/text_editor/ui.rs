@@ -1,5 +1,6 @@
 #![cfg(feature = "client")]
 
+use std::path::Path;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
@@ -20,16 +21,22 @@ use self::diagnostics::warn;
 use self::editor::EditorDocument;
 use self::editor::editor;
 use self::folder::folder;
+use self::image_viewer::image_viewer;
+use self::paged_viewer::PageFormat;
+use self::paged_viewer::paged_viewer;
 use super::file_path::FilePath;
 use super::fsio;
 use super::fsio::ROOT_BASE_PATH;
 use super::fsio::ROOT_FILE_PATH;
+use super::git::ui::git_panel;
 use super::manager::EditorDataState;
 use super::manager::EditorState;
 use super::manager::PreviewMode;
 use super::manager::TextEditorManager;
 use super::notify::manager::SideViewNotify;
 use super::notify::ui::NotifyService;
+use super::problems::ui::problems_panel;
+use super::search::replace::replace_panel;
 use super::search::state::EditorSearchState;
 use super::search::state::SearchState;
 use super::side::SideViewNode;
@@ -40,22 +47,30 @@ use super::state;
 use super::style;
 use super::synchronized_state::SynchronizedState;
 use super::synchronized_state::show_synchronized_state;
+use super::tail::ui::tail_panel;
+use super::tasks::ui::tasks_panel;
+use super::trash::ui::trash_panel;
 use crate::assets::icons;
 use crate::frontend::menu::menu;
 use crate::frontend::mousemove::MousemoveManager;
 use crate::frontend::remotes::Remote;
 use crate::frontend::remotes_ui::show_remote;
 use crate::frontend::resize_bar::resize_bar_horz;
+use crate::tiles::app::App;
 use crate::tiles::id::TileId;
 use crate::tiles::signals::TilePtr;
 
 mod code_mirror;
 pub mod drag;
 mod editor;
-mod folder;
+pub(super) mod folder;
 mod html_viewer;
+mod image_viewer;
 mod milkdown;
+mod paged_viewer;
 mod pdf_viewer;
+pub(super) mod properties;
+pub(super) mod upload;
 
 pub(super) const STORE_FILE_DEBOUNCE_DELAY: Duration = if cfg!(debug_assertions) {
     Duration::from_millis(1500)
@@ -73,6 +88,23 @@ pub fn text_editor(tile: TilePtr) -> XElement {
     )
 }
 
+/// Switches a tile to the text editor, browsing `folder` on `remote`.
+pub async fn open_folder(tile: TilePtr, remote: Remote, folder: Arc<Path>) {
+    let tile_id = Some(tile.id);
+    let (base_path, file_path, side_view) = futures::future::join3(
+        state::base_path::set(tile_id, remote.clone(), folder),
+        state::file_path::set(tile_id, remote.clone(), ROOT_FILE_PATH.clone()),
+        state::side_view::set(tile_id, remote.clone(), None),
+    )
+    .await;
+    if let Err(error) = base_path.and(file_path).and(side_view) {
+        return warn!("Failed to open the folder: {error}");
+    }
+    let _batch = Batch::use_batch("open-folder");
+    tile.remote.set(remote);
+    tile.app.set(App::TextEditor);
+}
+
 #[html]
 #[template(tag = div)]
 fn text_editor_impl(tile: TilePtr, #[signal] remote: Remote) -> XElement {
@@ -86,12 +118,16 @@ fn text_editor_impl(tile: TilePtr, #[signal] remote: Remote) -> XElement {
         force_edit_path: XSignal::new("force-edit-path", false),
         editor_state: XSignal::new("editor-state", EditorState::default()),
         show_editor_diff: XSignal::new("show-editor-diff", false),
+        inline_diff: XSignal::new("inline-diff", false),
         show_html_preview: XSignal::new("show-html-preview", PreviewMode::default()),
         synchronized_state: XSignal::new("synchronized-state", SynchronizedState::Sync),
         side_view: XSignal::new("side-view", None),
+        side_view_menu: XSignal::new("side-view-menu", None),
         notify_service: Ptr::new(NotifyService::new(remote)),
         search: SearchState::new(),
         side_view_resize_manager: MousemoveManager::new(),
+        uploads: XSignal::new("uploads", vec![]),
+        properties: XSignal::new("properties", None),
     });
 
     let consumers = Arc::default();
@@ -122,6 +158,13 @@ fn text_editor_impl(tile: TilePtr, #[signal] remote: Remote) -> XElement {
                 manager.show_editor_diff.clone(),
                 manager.show_html_preview.clone(),
             ),
+            manager.compare_selector(),
+            manager.tail_toggle(),
+            manager.replace_toggle(),
+            manager.git_toggle(),
+            manager.trash_toggle(),
+            manager.problems_toggle(),
+            manager.tasks_toggle(),
             manager.refresh_editor(),
             show_synchronized_state(manager.synchronized_state.clone()),
             show_remote(manager.tile.remote.clone()),
@@ -160,8 +203,11 @@ fn editor_body(manager: Ptr<TextEditorManager>) -> XElement {
             manager.clone(),
             manager.editor_state.clone(),
             manager.show_editor_diff.clone(),
+            manager.inline_diff.clone(),
             manager.show_html_preview.clone(),
         ),
+        manager.show_uploads(),
+        manager.show_properties(),
     )
 }
 
@@ -258,14 +304,18 @@ fn editor_container(
     manager: Ptr<TextEditorManager>,
     #[signal] editor_state: EditorState,
     #[signal] show_editor_diff: bool,
+    #[signal] inline_diff: bool,
     #[signal] show_html_preview: PreviewMode,
 ) -> XElement {
     let body = match editor_state {
         EditorState::Data(editor_state) => match &*editor_state.data {
             fsio::File::TextFile {
-                original, content, ..
+                metadata,
+                original,
+                content,
             } => {
                 let editor_document = EditorDocument::Text {
+                    metadata: metadata.clone(),
                     original: original.clone(),
                     content: content.clone(),
                 };
@@ -274,6 +324,7 @@ fn editor_container(
                     editor_state,
                     editor_document,
                     show_editor_diff,
+                    inline_diff,
                     show_html_preview,
                 )
             }
@@ -284,9 +335,25 @@ fn editor_container(
                     editor_state,
                     EditorDocument::Pdf(base64),
                     show_editor_diff,
+                    inline_diff,
                     show_html_preview,
                 )
             }
+            fsio::File::ImageFile { mime, base64, .. } => {
+                image_viewer(mime.clone(), base64.clone())
+            }
+            fsio::File::BinaryFile { metadata } => paged_viewer(
+                manager,
+                editor_state.path.clone(),
+                metadata.size.unwrap_or_default(),
+                PageFormat::Hex,
+            ),
+            fsio::File::LargeFile { metadata, encoding } => paged_viewer(
+                manager,
+                editor_state.path.clone(),
+                metadata.size.unwrap_or_default(),
+                PageFormat::Text(*encoding),
+            ),
             fsio::File::Folder(list) => {
                 let list = list.clone();
                 folder(manager, Some(editor_state), list)
@@ -304,6 +371,12 @@ fn editor_container(
             let results = results.clone();
             folder(manager, None, results)
         }
+        EditorState::Replace(replace_state) => replace_panel(manager, replace_state),
+        EditorState::Git(git_state) => git_panel(manager, git_state),
+        EditorState::Trash(trash_state) => trash_panel(manager, trash_state),
+        EditorState::Problems(problems_state) => problems_panel(manager, problems_state),
+        EditorState::Tasks(tasks_state) => tasks_panel(manager, tasks_state),
+        EditorState::Tail(tail_state) => tail_panel(manager, tail_state),
         EditorState::Empty => {
             return tag(
                 class = super::style::EDITOR_CONTAINER,
@@ -464,7 +537,11 @@ impl TextEditorManager {
                 }
 
                 if let Some(
-                    fsio::File::TextFile { metadata, .. } | fsio::File::PdfFile { metadata, .. },
+                    fsio::File::TextFile { metadata, .. }
+                    | fsio::File::PdfFile { metadata, .. }
+                    | fsio::File::ImageFile { metadata, .. }
+                    | fsio::File::BinaryFile { metadata }
+                    | fsio::File::LargeFile { metadata, .. },
                 ) = data.as_deref()
                 {
                     let metadata = metadata.clone();
@@ -4,6 +4,15 @@ class CodeMirrorJsImpl {
     reloadFromDisk; // Set to true when the file is updated from disk
     basePath;
     fullPath;
+    onlsp; // Forwards language server requests, if the file has a language server
+    lspRequests = new Map();
+    lspNextId = 1;
+    lspDiagnosticsTimeout = null;
+    lspDisabled = false;
+    cargoLints = [];
+    cargoFixes = []; // The machine-applicable suggestions of cargo check
+    cargoChanges = []; // The edits since cargo check, to map its offsets
+    lspLints = [];
     constructor(
         element,
         original,
@@ -13,11 +22,17 @@ class CodeMirrorJsImpl {
         cursorPosition,
         basePath,
         fullPath,
+        inlineDiff,
+        onlsp,
     ) {
         this.basePath = basePath;
         this.fullPath = fullPath;
+        this.onlsp = onlsp;
         this.reloadFromDisk = true;
         const updateListener = JsDeps.EditorView.updateListener.of((update) => {
+            if (update.docChanged) {
+                this.cargoChanges.push(update.changes);
+            }
             if (!this.reloadFromDisk && update.docChanged) {
                 const content = update.state.doc.toString();
                 onchange(content);
@@ -43,9 +58,18 @@ class CodeMirrorJsImpl {
         if (language) {
             extensions.push(language());
         }
+        if (onlsp) {
+            extensions.push(...this.lspExtensions());
+        }
 
         const selection = selectionFromCursorPosition(cursorPosition, content.length);
-        if (original) {
+        if (original && inlineDiff) {
+            extensions.push(JsDeps.unifiedMergeView({
+                original,
+                mergeControls: false,
+            }));
+        }
+        if (original && !inlineDiff) {
             const mergePaneExtensions = [
                 JsDeps.EditorView.theme({
                     "&": {
@@ -113,9 +137,17 @@ class CodeMirrorJsImpl {
         });
         this.editorView.focus();
         this.reloadFromDisk = false;
+        if (onlsp) {
+            this.requestLspDiagnostics();
+        }
     }
 
     destroy() {
+        clearTimeout(this.lspDiagnosticsTimeout);
+        for (const resolve of this.lspRequests.values()) {
+            resolve(null);
+        }
+        this.lspRequests.clear();
         this.rootView.destroy();
         console.debug(`CodeMirror at path "${this.fullPath}" is destroyed.`);
     }
@@ -144,6 +176,27 @@ class CodeMirrorJsImpl {
         }
     }
 
+    apply_changes(changes) {
+        this.reloadFromDisk = true;
+        try {
+            this.editorView.dispatch({ changes });
+        } finally {
+            this.reloadFromDisk = false;
+        }
+    }
+
+    set_remote_cursors(cursors) {
+        const { field, effect, theme } = remoteCursors();
+        if (this.editorView.state.field(field, false) === undefined) {
+            this.editorView.dispatch({
+                effects: JsDeps.StateEffect.appendConfig.of([field, theme]),
+            });
+        }
+        this.editorView.dispatch({
+            effects: effect.of(cursors),
+        });
+    }
+
     insert_text(text) {
         const selection = this.editorView.state.selection.main;
         this.editorView.dispatch({
@@ -166,31 +219,325 @@ class CodeMirrorJsImpl {
 
     cargo_check(diagnostics) {
         const lints = [];
-        let docLength = this.editorView.state.doc.length;
+        const fixes = [];
+        const docLength = this.editorView.state.doc.length;
         for (const diagnostic of diagnostics) {
-            if (diagnostic.file_path != this.fullPath)
-                continue;
-            for (const span of diagnostic.spans) {
-                if (!this.fullPath.endsWith(span.file_name))
-                    continue;
-                if (span.byte_end >= docLength)
-                    continue;
-                const severity = diagnostic.level == "error" || diagnostic.level == "warning"
-                    ? diagnostic.level
-                    : "info";
+            const spans = diagnostic.spans.filter((span) =>
+                span.full_path == this.fullPath && span.offset_end <= docLength);
+            const suggestions = spans.filter((span) => span.suggested_replacement != null);
+            const severity = diagnostic.level == "error" || diagnostic.level == "warning"
+                ? diagnostic.level
+                : "info";
+            for (const span of spans) {
                 const lint = {
-                    from: span.byte_start,
-                    to: span.byte_end,
+                    from: span.offset_start,
+                    to: span.offset_end,
                     severity,
                     source: "cargo-check",
                     message: diagnostic.message,
                 };
+                if (suggestions.length > 0) {
+                    lint.actions = [{
+                        name: "Apply fix",
+                        apply: (view) => this.applyFixes(view, suggestions),
+                    }];
+                }
                 lints.push(lint);
             }
+            fixes.push(...suggestions.filter((span) =>
+                span.suggestion_applicability == "MachineApplicable"));
+        }
+        this.cargoLints = lints;
+        this.cargoFixes = fixes;
+        this.cargoChanges = [];
+        this.updateLints();
+    }
+
+    /** Applies all the machine-applicable suggestions of the last cargo check. */
+    apply_fixes() {
+        const fixes = this.cargoFixes;
+        this.cargoFixes = [];
+        this.applyFixes(this.editorView, fixes);
+        this.focus();
+    }
+
+    /** Replaces the spans, mapped through the edits made since cargo check. */
+    applyFixes(view, spans) {
+        const changes = [];
+        for (const span of spans) {
+            const from = this.cargoMapPos(span.offset_start, 1);
+            const to = Math.max(from, this.cargoMapPos(span.offset_end, -1));
+            const insert = span.suggested_replacement;
+            const duplicate = changes.some((change) =>
+                change.from == from && change.to == to && change.insert == insert);
+            // The same fix is reported once per target that compiles the file.
+            if (duplicate) continue;
+            const overlaps = changes.some((change) => change.from < to && from < change.to);
+            if (overlaps) continue;
+            changes.push({ from, to, insert });
         }
+        if (changes.length > 0) {
+            view.dispatch({ changes, scrollIntoView: true });
+        }
+        return true;
+    }
+
+    cargoMapPos(pos, assoc) {
+        for (const changes of this.cargoChanges) {
+            pos = changes.mapPos(pos, assoc);
+        }
+        return pos;
+    }
+
+    /** Shows the diagnostics of cargo check and of the language server together. */
+    updateLints() {
+        const lints = [...this.cargoLints, ...this.lspLints];
         const setLintsTransaction = JsDeps.setDiagnostics(this.editorView.state, lints);
         this.editorView.dispatch(setLintsTransaction);
     }
+
+    lspExtensions() {
+        const hover = JsDeps.hoverTooltip(async (view, pos) => {
+            const text = await this.lsp("hover", view.state, pos);
+            if (!text) return null;
+            return {
+                pos,
+                above: true,
+                create() {
+                    const dom = document.createElement("pre");
+                    dom.className = "cm-lspHover";
+                    dom.textContent = text;
+                    return { dom };
+                },
+            };
+        });
+        const completion = JsDeps.EditorState.languageData.of(() => [{
+            autocomplete: async (context) => {
+                const word = context.matchBefore(/[\w$]*/);
+                if (!context.explicit && word.from === word.to) return null;
+                const items = await this.lsp("completion", context.state, context.pos);
+                if (!items || items.length === 0) return null;
+                const range = items[0].range;
+                return {
+                    from: range ? lspOffset(context.state.doc, range.start) : word.from,
+                    options: items.map((item) => ({
+                        label: item.label,
+                        detail: item.detail ?? undefined,
+                        type: item.kind ?? undefined,
+                        apply: item.insert_text,
+                    })),
+                };
+            },
+        }]);
+        const keys = JsDeps.Prec.high(JsDeps.keymap.of([
+            {
+                key: "F12",
+                run: (view) => this.lspAtCursor(view, "definition"),
+            },
+            {
+                key: "Shift-F12",
+                run: (view) => this.lspAtCursor(view, "references"),
+            },
+            {
+                key: "F2",
+                run: (view) => this.lspRename(view),
+            },
+        ]));
+        const goToDefinition = JsDeps.EditorView.domEventHandlers({
+            mousedown: (event, view) => {
+                if (!event.ctrlKey && !event.metaKey) return false;
+                const pos = view.posAtCoords({ x: event.clientX, y: event.clientY });
+                if (pos === null) return false;
+                this.lsp("definition", view.state, pos);
+                event.preventDefault();
+                return true;
+            },
+        });
+        const diagnostics = JsDeps.EditorView.updateListener.of((update) => {
+            if (update.docChanged) {
+                this.requestLspDiagnostics();
+            }
+        });
+        const theme = JsDeps.EditorView.baseTheme({
+            ".cm-lspHover": {
+                margin: "0",
+                padding: "2px 6px",
+                maxWidth: "60em",
+                maxHeight: "20em",
+                overflow: "auto",
+                whiteSpace: "pre-wrap",
+            },
+        });
+        return [hover, completion, keys, goToDefinition, diagnostics, theme];
+    }
+
+    /** Sends a request about the position to the language server, resolved by `lsp_response`. */
+    lsp(method, state, pos, newName) {
+        if (this.lspDisabled) {
+            return Promise.resolve(null);
+        }
+        const line = state.doc.lineAt(pos);
+        const id = this.lspNextId++;
+        return new Promise((resolve) => {
+            this.lspRequests.set(id, resolve);
+            this.onlsp({
+                id,
+                method,
+                line: line.number - 1,
+                character: pos - line.from,
+                content: state.doc.toString(),
+                new_name: newName ?? null,
+            });
+        });
+    }
+
+    lsp_response(id, result) {
+        const resolve = this.lspRequests.get(id);
+        if (!resolve) return;
+        this.lspRequests.delete(id);
+        resolve(result);
+    }
+
+    lspAtCursor(view, method) {
+        this.lsp(method, view.state, view.state.selection.main.head);
+        return true;
+    }
+
+    lspRename(view) {
+        const state = view.state;
+        const pos = state.selection.main.head;
+        const word = state.wordAt(pos);
+        const current = word ? state.sliceDoc(word.from, word.to) : "";
+        const newName = window.prompt("Rename to", current);
+        if (!newName || newName === current) return true;
+        this.lsp("rename", state, pos, newName).then((edits) => {
+            // The edits are relative to the content sent with the request.
+            if (!edits || this.editorView.state.doc !== state.doc) return;
+            const changes = edits.map((edit) => ({
+                from: lspOffset(state.doc, edit.range.start),
+                to: lspOffset(state.doc, edit.range.end),
+                insert: edit.new_text,
+            }));
+            this.editorView.dispatch({ changes });
+        });
+        return true;
+    }
+
+    /** Debounces the diagnostics requests while typing. */
+    requestLspDiagnostics() {
+        if (this.lspDisabled) return;
+        clearTimeout(this.lspDiagnosticsTimeout);
+        this.lspDiagnosticsTimeout = setTimeout(async () => {
+            const state = this.editorView.state;
+            const diagnostics = await this.lsp("diagnostics", state, 0);
+            if (diagnostics == null) {
+                // No language server for this file.
+                this.lspDisabled = true;
+                return;
+            }
+            if (this.editorView.state.doc !== state.doc) return;
+            this.lspLints = diagnostics.map((diagnostic) => ({
+                from: lspOffset(state.doc, diagnostic.range.start),
+                to: lspOffset(state.doc, diagnostic.range.end),
+                severity: diagnostic.severity,
+                source: diagnostic.source ?? "lsp",
+                message: diagnostic.message,
+            }));
+            this.updateLints();
+        }, 500);
+    }
+
+    set_cursor(offset) {
+        const anchor = clampCursorOffset(offset, this.editorView.state.doc.length);
+        this.editorView.dispatch({
+            selection: { anchor },
+            scrollIntoView: true,
+        });
+        this.focus();
+    }
+}
+
+/** The offset of an LSP position: a 0-based line, and UTF-16 code units like CodeMirror. */
+function lspOffset(doc, position) {
+    if (position.line >= doc.lines) {
+        return doc.length;
+    }
+    const line = doc.line(position.line + 1);
+    return Math.min(line.from + position.character, line.to);
+}
+
+let remoteCursorsExtension = null;
+
+/** Decorations for the selections and carets of the other clients editing the file. */
+function remoteCursors() {
+    if (remoteCursorsExtension) {
+        return remoteCursorsExtension;
+    }
+
+    class RemoteCaret extends JsDeps.WidgetType {
+        constructor(color) {
+            super();
+            this.color = color;
+        }
+
+        eq(other) {
+            return other.color === this.color;
+        }
+
+        toDOM() {
+            const caret = document.createElement("span");
+            caret.className = "cm-remoteCaret";
+            caret.style.borderLeftColor = this.color;
+            return caret;
+        }
+    }
+
+    const effect = JsDeps.StateEffect.define();
+    const field = JsDeps.StateField.define({
+        create() {
+            return JsDeps.Decoration.none;
+        },
+        update(decorations, transaction) {
+            decorations = decorations.map(transaction.changes);
+            for (const e of transaction.effects) {
+                if (e.is(effect)) {
+                    decorations = remoteCursorDecorations(e.value, transaction.state.doc.length, RemoteCaret);
+                }
+            }
+            return decorations;
+        },
+        provide: (field) => JsDeps.EditorView.decorations.from(field),
+    });
+    const theme = JsDeps.EditorView.baseTheme({
+        ".cm-remoteCaret": {
+            borderLeft: "2px solid",
+            marginLeft: "-1px",
+            marginRight: "-1px",
+        },
+    });
+    remoteCursorsExtension = { field, effect, theme };
+    return remoteCursorsExtension;
+}
+
+function remoteCursorDecorations(cursors, docLength, RemoteCaret) {
+    const decorations = [];
+    for (const cursor of cursors) {
+        const hue = (cursor.client * 137) % 360;
+        const anchor = clampCursorOffset(cursor.anchor, docLength);
+        const head = clampCursorOffset(cursor.head, docLength);
+        const from = Math.min(anchor, head);
+        const to = Math.max(anchor, head);
+        if (from < to) {
+            decorations.push(JsDeps.Decoration.mark({
+                attributes: { style: `background-color: hsla(${hue}, 70%, 60%, 0.3)` },
+            }).range(from, to));
+        }
+        decorations.push(JsDeps.Decoration.widget({
+            widget: new RemoteCaret(`hsl(${hue}, 70%, 60%)`),
+            side: 1,
+        }).range(head));
+    }
+    return JsDeps.Decoration.set(decorations, true);
 }
 
 function getLanguage(fileName) {
@@ -9,6 +9,7 @@ pub struct CodeMirrorJs {
     inner: CodeMirrorJsImpl,
     _onchange: Closure<dyn FnMut(JsValue)>,
     _oncursor: Closure<dyn FnMut(JsValue)>,
+    _onlsp: Option<Closure<dyn FnMut(JsValue)>>,
 }
 
 impl Drop for CodeMirrorJs {
@@ -35,7 +36,11 @@ impl CodeMirrorJs {
         cursor_position: JsValue,
         base_path: String,
         full_path: String,
+        inline_diff: bool,
+        onlsp: Option<Closure<dyn FnMut(JsValue)>>,
     ) -> Self {
+        let null = JsValue::null();
+        let onlsp_js: &JsValue = onlsp.as_ref().map_or(&null, |onlsp| onlsp.as_ref());
         Self {
             inner: CodeMirrorJsImpl::new(
                 element,
@@ -46,9 +51,12 @@ impl CodeMirrorJs {
                 cursor_position,
                 base_path,
                 full_path,
+                inline_diff,
+                onlsp_js,
             ),
             _onchange: onchange,
             _oncursor: oncursor,
+            _onlsp: onlsp,
         }
     }
 
@@ -67,6 +75,26 @@ impl CodeMirrorJs {
     pub fn cargo_check(&self, diagnostics: JsValue) {
         self.inner.cargo_check(diagnostics);
     }
+
+    pub fn apply_fixes(&self) {
+        self.inner.apply_fixes();
+    }
+
+    pub fn apply_changes(&self, changes: JsValue) {
+        self.inner.apply_changes(changes);
+    }
+
+    pub fn set_remote_cursors(&self, cursors: JsValue) {
+        self.inner.set_remote_cursors(cursors);
+    }
+
+    pub fn lsp_response(&self, id: u32, result: JsValue) {
+        self.inner.lsp_response(id, result);
+    }
+
+    pub fn set_cursor(&self, offset: u32) {
+        self.inner.set_cursor(offset);
+    }
 }
 
 impl EditorBody for CodeMirrorJs {
@@ -85,6 +113,26 @@ impl EditorBody for CodeMirrorJs {
     fn cargo_check(&self, diagnostics: JsValue) {
         self.cargo_check(diagnostics);
     }
+
+    fn apply_fixes(&self) {
+        self.apply_fixes();
+    }
+
+    fn apply_changes(&self, changes: JsValue, _content: String) {
+        self.apply_changes(changes);
+    }
+
+    fn set_remote_cursors(&self, cursors: JsValue) {
+        self.set_remote_cursors(cursors);
+    }
+
+    fn lsp_response(&self, id: u32, result: JsValue) {
+        self.lsp_response(id, result);
+    }
+
+    fn set_cursor(&self, offset: u32) {
+        self.set_cursor(offset);
+    }
 }
 
 #[wasm_bindgen(module = "/src/text_editor/ui/code_mirror.js")]
@@ -102,6 +150,8 @@ extern "C" {
         cursor_position: JsValue,
         base_path: String,
         full_path: String,
+        inline_diff: bool,
+        onlsp: &JsValue,
     ) -> CodeMirrorJsImpl;
 
     #[wasm_bindgen(method)]
@@ -118,4 +168,19 @@ extern "C" {
 
     #[wasm_bindgen(method)]
     pub fn cargo_check(this: &CodeMirrorJsImpl, diagnostics: JsValue);
+
+    #[wasm_bindgen(method)]
+    pub fn apply_fixes(this: &CodeMirrorJsImpl);
+
+    #[wasm_bindgen(method)]
+    pub fn apply_changes(this: &CodeMirrorJsImpl, changes: JsValue);
+
+    #[wasm_bindgen(method)]
+    pub fn set_remote_cursors(this: &CodeMirrorJsImpl, cursors: JsValue);
+
+    #[wasm_bindgen(method)]
+    pub fn lsp_response(this: &CodeMirrorJsImpl, id: u32, result: JsValue);
+
+    #[wasm_bindgen(method)]
+    pub fn set_cursor(this: &CodeMirrorJsImpl, offset: u32);
 }
@@ -3,22 +3,19 @@ use std::sync::Arc;
 
 use terrazzo::autoclone;
 use terrazzo::prelude::*;
-use wasm_bindgen::JsValue;
 use wasm_bindgen_futures::spawn_local;
 use web_sys::DragEvent;
-use web_sys::File;
 
 use self::diagnostics::Instrument as _;
 use self::diagnostics::debug;
 use self::diagnostics::debug_span;
 use self::diagnostics::error;
-use self::diagnostics::warn;
-use crate::api::client::request;
-use crate::api::client::request::Method;
+use super::upload::start_upload;
 use crate::text_editor::file_path::FilePath;
 use crate::text_editor::fsio;
 use crate::text_editor::fsio::ROOT_BASE_PATH;
 use crate::text_editor::manager::TextEditorManager;
+use crate::text_editor::side::actions::resolve_conflicts;
 
 const MOVE_FILE_KEY: &str = "text-editor-move-file";
 
@@ -140,10 +137,7 @@ pub(in crate::text_editor) fn on_move_drop(
                 let Some(file) = files.get(i) else {
                     continue;
                 };
-                spawn_local(
-                    upload_dropped_file(manager.clone(), destination_folder.clone(), file)
-                        .in_current_span(),
-                );
+                start_upload(&manager, &destination_folder, file);
             }
         }
     }
@@ -154,58 +148,17 @@ async fn move_side_view_node(
     source: FilePath<Arc<Path>>,
     destination_folder: FilePath<Arc<Path>>,
 ) {
-    let result = fsio::client::move_file(manager.remote.clone(), source, destination_folder).await;
-    if let Err(error) = result {
-        error!("Failed to move side-view entry: {error}");
-        return;
-    }
-    debug!("Moved!")
-}
-
-async fn upload_dropped_file(
-    manager: Ptr<TextEditorManager>,
-    destination_folder: FilePath<Arc<Path>>,
-    file: File,
-) {
-    let file_name = file.name();
-    if file_name.is_empty() {
-        debug!("Dropped file has no name; skipping upload");
-        return;
-    }
-    let destination_file = FilePath {
-        base: destination_folder.base.clone(),
-        file: Arc::from(destination_folder.file.as_ref().join(&file_name)),
-    };
-    debug!("Uploading dropped file to {destination_file:?}");
-
-    let Some(url) = upload_url(&manager, &destination_file) else {
-        return;
-    };
-    let result = request::send_request(Method::POST, url, move |request| {
-        request.set_body(JsValue::from(file).as_ref());
+    let result = resolve_conflicts(|on_conflict| {
+        let remote = manager.remote.clone();
+        let (source, destination_folder) = (source.clone(), destination_folder.clone());
+        fsio::client::move_file(remote, source, destination_folder, on_conflict)
     })
     .await;
-    if let Err(error) = result {
-        error!("Failed to upload dropped file: {error}");
-        return;
+    match result {
+        Some(Ok(())) => debug!("Moved!"),
+        Some(Err(error)) => error!("Failed to move side-view entry: {error}"),
+        None => debug!("Move canceled"),
     }
-    debug!("Uploaded dropped file");
-    if manager.path.file.get_value_untracked() == destination_folder.file {
-        manager.path.file.force(destination_folder.file);
-    }
-}
-
-fn upload_url(manager: &TextEditorManager, file_path: &FilePath<Arc<Path>>) -> Option<String> {
-    let remote = serde_json::to_string(&manager.remote)
-        .inspect_err(|error| warn!("Failed to JSON serialize the remote: {error}"))
-        .ok()?;
-    format!(
-        "/api/text_editor/fsio/upload?base={}&file={}&remote={}",
-        encode_query_path(file_path.base.as_ref()),
-        encode_query_path(file_path.file.as_ref()),
-        encode_query(&remote),
-    )
-    .into()
 }
 
 pub fn encode_query_path(path: &Path) -> String {
@@ -1,4 +1,7 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::path::Path;
+use std::rc::Weak;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::atomic::AtomicU32;
@@ -18,26 +21,39 @@ use self::diagnostics::debug_span;
 use self::diagnostics::warn;
 use super::code_mirror::CodeMirrorJs;
 use super::fsio;
+use super::fsio::CursorPosition;
+use super::fsio::FileMetadata;
+use super::fsio::client::FileChanged;
+use super::fsio::client::FileVersion;
 use super::fsio::client::store_file;
 use super::milkdown::MilkdownJs;
 use super::pdf_viewer::PdfJs;
 use super::style;
 use crate::frontend::input_overlay::InputOverlay;
+use crate::text_editor::collab::client::Change;
+use crate::text_editor::collab::client::CollabClient;
+use crate::text_editor::collab::client::CollabView;
 use crate::text_editor::file_path::FilePath;
+use crate::text_editor::lsp::ui::LspEditor;
+use crate::text_editor::lsp::ui::LspView;
 use crate::text_editor::manager::EditorDataState;
 use crate::text_editor::manager::PreviewMode;
 use crate::text_editor::manager::TextEditorManager;
+use crate::text_editor::merge::merge3;
 use crate::text_editor::notify::server_fn::EventKind;
 use crate::text_editor::notify::server_fn::FileEventKind;
 use crate::text_editor::notify::server_fn::NotifyResponse;
+use crate::text_editor::rust_lang::synthetic::SyntheticDiagnostic;
 use crate::text_editor::synchronized_state::SynchronizedState;
 use crate::text_editor::ui::ROOT_FILE_PATH;
 use crate::utils::more_path::MorePath as _;
 use web_sys::Element;
+use web_sys::MouseEvent;
 
 #[derive(Clone)]
 pub(super) enum EditorDocument {
     Text {
+        metadata: Arc<FileMetadata>,
         original: Option<Arc<str>>,
         content: Arc<str>,
     },
@@ -52,6 +68,20 @@ pub(super) trait EditorBody {
     fn focus(&self) {}
 
     fn cargo_check(&self, _diagnostics: JsValue) {}
+
+    /// Applies the machine-applicable fixes of the last cargo check.
+    fn apply_fixes(&self) {}
+
+    /// Applies the edits of other clients, `content` is the result.
+    fn apply_changes(&self, _changes: JsValue, content: String) {
+        self.set_content(content);
+    }
+
+    fn set_remote_cursors(&self, _cursors: JsValue) {}
+
+    fn lsp_response(&self, _id: u32, _result: JsValue) {}
+
+    fn set_cursor(&self, _offset: u32) {}
 }
 
 struct HtmlEditorBody {
@@ -76,6 +106,27 @@ impl EditorBody for HtmlEditorBody {
     fn cargo_check(&self, diagnostics: JsValue) {
         self.source.cargo_check(diagnostics);
     }
+
+    fn apply_fixes(&self) {
+        self.source.apply_fixes();
+    }
+
+    fn apply_changes(&self, changes: JsValue, content: String) {
+        self.source.apply_changes(changes);
+        let _ = self.preview.set_attribute("srcdoc", &content);
+    }
+
+    fn set_remote_cursors(&self, cursors: JsValue) {
+        self.source.set_remote_cursors(cursors);
+    }
+
+    fn lsp_response(&self, id: u32, result: JsValue) {
+        self.source.lsp_response(id, result);
+    }
+
+    fn set_cursor(&self, offset: u32) {
+        self.source.set_cursor(offset);
+    }
 }
 
 #[autoclone]
@@ -91,6 +142,7 @@ pub fn editor(
     editor_state: EditorDataState,
     document: EditorDocument,
     show_editor_diff: bool,
+    inline_diff: bool,
     show_html_preview: PreviewMode,
 ) -> XElement {
     let is_html = editor_state.is_html();
@@ -138,7 +190,58 @@ pub fn editor(
     // out of causal order, so don't refresh CodeMirror while local edits are pending.
     let writing = Arc::new(AtomicU32::new(0));
 
+    // The version on disk that edits are based on, and the last save it rejected.
+    let version = Arc::new(Mutex::new(match &document {
+        EditorDocument::Text {
+            metadata, content, ..
+        } => FileVersion {
+            metadata: metadata.clone(),
+            content: content.clone(),
+        },
+        EditorDocument::Pdf(_) => FileVersion::default(),
+    }));
+    let conflict: XSignal<Option<FileConflict>> = XSignal::new("file-conflict", None);
+    let save = Saver {
+        manager: manager.clone(),
+        path: path.clone(),
+        writing: writing.clone(),
+        version: version.clone(),
+        conflict: conflict.clone(),
+    };
+
     let editor_body: Ptr<Mutex<Option<Box<dyn EditorBody>>>> = Ptr::new(Mutex::new(None));
+
+    // Edits go through the editing session of the file, shared with other browsers.
+    let collab = matches!(document, EditorDocument::Text { .. }).then(|| {
+        CollabClient::new(
+            manager.remote.clone(),
+            path.clone(),
+            CollabEditorView {
+                editor_body: editor_body.clone(),
+                cursors: Mutex::default(),
+            },
+        )
+    });
+    let weak_collab = collab.as_ref().map(Ptr::downgrade).unwrap_or_default();
+
+    // Source code goes to the language server of the file, if there is one.
+    let lsp = matches!(
+        (&document, editor_type),
+        (
+            EditorDocument::Text { .. },
+            EditorType::Text | EditorType::Html
+        )
+    )
+    .then(|| {
+        LspEditor::new(
+            &manager,
+            &path,
+            LspEditorView {
+                editor_body: Ptr::downgrade(&editor_body),
+            },
+        )
+    });
+    let lsp_references = lsp.as_ref().map(LspEditor::references);
     let focus_editor: Ptr<dyn Fn()> = Ptr::new(move || {
         autoclone!(editor_body);
         if let Some(editor_body) = &*editor_body.lock().unwrap() {
@@ -168,15 +271,16 @@ pub fn editor(
 
     let edits_notify_registration = manager.notify_service.watch_file(
         &path,
-        make_edits_notify_handler(&manager, &editor_body, &path, &writing),
+        make_edits_notify_handler(&manager, &editor_body, &path, &save, &weak_collab),
     );
     let base_path = FilePath {
         base: path.base.clone(),
         file: ROOT_FILE_PATH.clone(),
     };
+    let cargo_fixes = XSignal::new("cargo-fixes", 0);
     let diagnostics_notify_registration = manager.notify_service.watch_file(
         &base_path,
-        make_diagnostics_notify_handler(&editor_body, &base_path),
+        make_diagnostics_notify_handler(&editor_body, &path, &cargo_fixes),
     );
 
     tag(
@@ -194,6 +298,9 @@ pub fn editor(
         preview_pane..,
         source_pane..,
         input_overlay_html..,
+        conflict_banner(conflict.clone(), editor_body.clone(), save.clone()),
+        fixes_button(cargo_fixes.clone(), editor_body.clone()),
+        lsp_references..,
         mouseenter = move |_| {
             if let Some((is_input_overlay_open, input_overlay_textarea)) = &input_overlay
                 && is_input_overlay_open.get_value_untracked()
@@ -211,12 +318,14 @@ pub fn editor(
             autoclone!(path);
             let _moved = &edits_notify_registration;
             let _moved = &diagnostics_notify_registration;
+            let _moved = &collab;
             let body: Option<Box<dyn EditorBody>> = match &document {
-                EditorDocument::Text { original, content }
-                    if matches!(
-                        editor_type,
-                        EditorType::Text | EditorType::Markdown | EditorType::Html
-                    ) =>
+                EditorDocument::Text {
+                    original, content, ..
+                } if matches!(
+                    editor_type,
+                    EditorType::Text | EditorType::Markdown | EditorType::Html
+                ) =>
                 {
                     let original = if show_editor_diff {
                         original
@@ -253,8 +362,8 @@ pub fn editor(
                             source_pane,
                             original,
                             content.as_ref().into(),
-                            make_on_change(&manager, &path, &writing, None),
-                            make_on_cursor_position_change(&manager, &path),
+                            make_on_change(&save, &weak_collab, None),
+                            make_on_cursor_position_change(&manager, &path, &weak_collab),
                             cursor_position,
                             base_path,
                             full_path,
@@ -284,11 +393,13 @@ pub fn editor(
                             source_element,
                             original,
                             content.as_ref().into(),
-                            make_on_change(&manager, &path, &writing, preview.clone()),
-                            make_on_cursor_position_change(&manager, &path),
+                            make_on_change(&save, &weak_collab, preview.clone()),
+                            make_on_cursor_position_change(&manager, &path, &weak_collab),
                             cursor_position,
                             base_path,
                             full_path,
+                            inline_diff,
+                            lsp.as_ref().map(LspEditor::on_lsp),
                         );
                         if let Some(preview) = preview {
                             Some(Box::new(HtmlEditorBody { source, preview }))
@@ -348,55 +459,283 @@ impl EditorType {
     }
 }
 
-#[autoclone]
-fn make_on_change(
-    manager: &Ptr<TextEditorManager>,
-    path: &FilePath<Arc<Path>>,
-    writing: &Arc<AtomicU32>,
-    html_preview: Option<Element>,
-) -> Closure<dyn FnMut(JsValue)> {
-    Closure::new(move |content: JsValue| {
-        autoclone!(manager, path, writing);
-        let Some(content) = content.as_string() else {
-            debug!("Changed content is not a string");
-            return;
-        };
-        if let Some(html_preview) = &html_preview {
-            let _ = html_preview.set_attribute("srcdoc", &content);
-        }
+/// Stores the edits of an editor, as long as the file didn't change on disk.
+#[derive(Clone)]
+struct Saver {
+    manager: Ptr<TextEditorManager>,
+    path: FilePath<Arc<Path>>,
+    writing: Arc<AtomicU32>,
+    version: Arc<Mutex<FileVersion>>,
+    conflict: XSignal<Option<FileConflict>>,
+}
+
+/// A save that was rejected because the file changed on disk.
+#[derive(Clone)]
+struct FileConflict {
+    ours: Arc<str>,
+    disk: FileVersion,
+}
+
+impl std::fmt::Debug for FileConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileConflict")
+            .field("ours", &self.ours.len())
+            .field("disk", &self.disk.content.len())
+            .finish()
+    }
+}
+
+impl Saver {
+    fn save(&self, content: String) {
+        let Self {
+            manager,
+            path,
+            writing,
+            version,
+            conflict,
+        } = self.clone();
         writing.fetch_add(1, SeqCst);
         let writing_done = guard((), move |()| {
-            autoclone!(writing);
             writing.fetch_sub(1, SeqCst);
         });
         let write = async move {
-            autoclone!(manager, path);
             let synchronized_state_done =
                 SynchronizedState::enqueue(manager.synchronized_state.clone());
-            let () = store_file(
+            let result = store_file(
                 manager.remote.clone(),
-                path,
-                content,
+                path.clone(),
+                content.clone(),
+                version,
                 guard((), move |()| ()),
                 (writing_done, synchronized_state_done),
             )
             .await;
+            if let Err(FileChanged) = result {
+                debug!("The file changed on disk, loading it to merge");
+                match fsio::client::load_file(manager.remote.clone(), path).await {
+                    Ok(Some(fsio::File::TextFile {
+                        metadata,
+                        content: disk,
+                        ..
+                    })) => conflict.force(Some(FileConflict {
+                        ours: content.into(),
+                        disk: FileVersion {
+                            metadata,
+                            content: disk,
+                        },
+                    })),
+                    Ok(_) => warn!("The changed file is no longer a text file"),
+                    Err(error) => warn!("Failed to load the changed file: {error}"),
+                }
+            }
         };
         spawn_local(write.in_current_span());
+    }
+
+    /// Resolves the conflict by basing the editor on the version on disk.
+    fn resolve(&self, conflict: &FileConflict) {
+        *self.version.lock().unwrap() = conflict.disk.clone();
+        self.conflict.force(None);
+    }
+}
+
+fn make_on_change(
+    save: &Saver,
+    collab: &Weak<CollabClient>,
+    html_preview: Option<Element>,
+) -> Closure<dyn FnMut(JsValue)> {
+    let save = save.clone();
+    let collab = collab.clone();
+    Closure::new(move |content: JsValue| {
+        let Some(content) = content.as_string() else {
+            debug!("Changed content is not a string");
+            return;
+        };
+        if let Some(html_preview) = &html_preview {
+            let _ = html_preview.set_attribute("srcdoc", &content);
+        }
+        if let Some(collab) = collab.upgrade()
+            && collab.edit(&content)
+        {
+            return;
+        }
+        save.save(content);
     })
 }
 
+/// Shows the edits and cursors of the other clients of the editing session.
+struct CollabEditorView {
+    editor_body: Ptr<Mutex<Option<Box<dyn EditorBody>>>>,
+    cursors: Mutex<BTreeMap<usize, CursorPosition>>,
+}
+
+#[derive(serde::Serialize)]
+struct RemoteCursor {
+    client: usize,
+    anchor: u32,
+    head: u32,
+}
+
+impl CollabView for CollabEditorView {
+    fn reset(&self, content: String) {
+        if let Some(editor_body) = &*self.editor_body.lock().unwrap() {
+            editor_body.set_content(content);
+        }
+    }
+
+    fn apply(&self, changes: Vec<Change>, content: String) {
+        let Some(editor_body) = &*self.editor_body.lock().unwrap() else {
+            return;
+        };
+        match serde_wasm_bindgen::to_value(&changes) {
+            Ok(changes) => editor_body.apply_changes(changes, content),
+            Err(error) => {
+                warn!("Failed to convert changes: {error}");
+                editor_body.set_content(content);
+            }
+        }
+    }
+
+    fn cursor(&self, client: usize, position: Option<CursorPosition>) {
+        let cursors = {
+            let mut cursors = self.cursors.lock().unwrap();
+            match position {
+                Some(position) => cursors.insert(client, position),
+                None => cursors.remove(&client),
+            };
+            cursors
+                .iter()
+                .map(|(&client, position)| RemoteCursor {
+                    client,
+                    anchor: position.anchor,
+                    head: position.head,
+                })
+                .collect::<Vec<_>>()
+        };
+        if let Ok(cursors) = serde_wasm_bindgen::to_value(&cursors)
+            && let Some(editor_body) = &*self.editor_body.lock().unwrap()
+        {
+            editor_body.set_remote_cursors(cursors);
+        }
+    }
+}
+
+/// Answers the language server requests of the editor.
+struct LspEditorView {
+    editor_body: std::rc::Weak<Mutex<Option<Box<dyn EditorBody>>>>,
+}
+
+impl LspView for LspEditorView {
+    fn respond(&self, id: u32, result: JsValue) {
+        if let Some(editor_body) = self.editor_body.upgrade()
+            && let Some(editor_body) = &*editor_body.lock().unwrap()
+        {
+            editor_body.lsp_response(id, result);
+        }
+    }
+
+    fn set_cursor(&self, offset: u32) {
+        if let Some(editor_body) = self.editor_body.upgrade()
+            && let Some(editor_body) = &*editor_body.lock().unwrap()
+        {
+            editor_body.set_cursor(offset);
+        }
+    }
+}
+
+#[html]
+#[template(tag = div)]
+fn conflict_banner(
+    #[signal] conflict: Option<FileConflict>,
+    editor_body: Ptr<Mutex<Option<Box<dyn EditorBody>>>>,
+    save: Saver,
+) -> XElement {
+    let Some(conflict) = conflict else {
+        return tag(style::display = "none", style::visibility = "hidden");
+    };
+    let merge = {
+        let (conflict, editor_body, save) = (conflict.clone(), editor_body.clone(), save.clone());
+        move |_: MouseEvent| {
+            let base = save.version.lock().unwrap().content.clone();
+            let merge = merge3(&base, &conflict.ours, &conflict.disk.content);
+            debug!(
+                "Merged with the file on disk: {} conflicts",
+                merge.conflicts
+            );
+            save.resolve(&conflict);
+            if let Some(editor_body) = &*editor_body.lock().unwrap() {
+                editor_body.set_content(merge.text.clone());
+            }
+            save.save(merge.text);
+        }
+    };
+    let overwrite = {
+        let (conflict, save) = (conflict.clone(), save.clone());
+        move |_: MouseEvent| {
+            save.resolve(&conflict);
+            save.save(conflict.ours.to_string());
+        }
+    };
+    let reload = move |_: MouseEvent| {
+        save.resolve(&conflict);
+        if let Some(editor_body) = &*editor_body.lock().unwrap() {
+            editor_body.set_content(conflict.disk.content.to_string());
+        }
+    };
+    tag(
+        class = style::FILE_CONFLICT,
+        #[cfg(not(feature = "client-prod"))]
+        class = "file-conflict",
+        span("The file changed on disk since it was loaded."),
+        button(click = merge, "Merge"),
+        button(click = overwrite, "Overwrite"),
+        button(click = reload, "Reload"),
+    )
+}
+
+#[html]
+#[template(tag = div)]
+fn fixes_button(
+    #[signal] mut cargo_fixes: usize,
+    editor_body: Ptr<Mutex<Option<Box<dyn EditorBody>>>>,
+) -> XElement {
+    if cargo_fixes == 0 {
+        return tag(style::display = "none", style::visibility = "hidden");
+    }
+    let apply = move |_: MouseEvent| {
+        if let Some(editor_body) = &*editor_body.lock().unwrap() {
+            editor_body.apply_fixes();
+        }
+        cargo_fixes_mut.set(0usize);
+    };
+    let label = if cargo_fixes == 1 { "fix" } else { "fixes" };
+    tag(
+        class = style::CARGO_FIXES,
+        #[cfg(not(feature = "client-prod"))]
+        class = "cargo-fixes",
+        button(
+            click = apply,
+            title = "Apply the machine-applicable suggestions of cargo check",
+            "Apply {cargo_fixes} {label}",
+        ),
+    )
+}
+
 #[autoclone]
 fn make_on_cursor_position_change(
     manager: &Ptr<TextEditorManager>,
     path: &FilePath<Arc<Path>>,
+    collab: &Weak<CollabClient>,
 ) -> Closure<dyn FnMut(JsValue)> {
     Closure::new(move |cursor_position: JsValue| {
-        autoclone!(manager, path);
+        autoclone!(manager, path, collab);
         let Ok(cursor_position) = serde_wasm_bindgen::from_value(cursor_position) else {
             debug!("Changed cursor position is invalid");
             return;
         };
+        if let Some(collab) = collab.upgrade() {
+            collab.cursor(cursor_position);
+        }
         let write = async move {
             autoclone!(manager, path);
             let synchronized_state_done =
@@ -415,23 +754,23 @@ fn make_edits_notify_handler(
     manager: &Ptr<TextEditorManager>,
     editor_body: &Ptr<Mutex<Option<Box<dyn EditorBody>>>>,
     path: &FilePath<Arc<Path>>,
-    writing: &Arc<AtomicU32>,
+    save: &Saver,
+    collab: &Weak<CollabClient>,
 ) -> impl Fn(&NotifyResponse) + 'static {
     move |event| {
-        autoclone!(manager, editor_body, path, writing);
+        autoclone!(manager, editor_body, path, save, collab);
         let _span = debug_span!("Editor notifier", ?path).entered();
         let EventKind::File(FileEventKind::Create | FileEventKind::Modify) = event.kind else {
             return;
         };
-        spawn_local(
-            notify_edit(
-                manager.clone(),
-                editor_body.clone(),
-                path.clone(),
-                writing.clone(),
-            )
-            .in_current_span(),
+        let notify = notify_edit(
+            manager.clone(),
+            editor_body.clone(),
+            path.clone(),
+            save.clone(),
+            collab.clone(),
         );
+        spawn_local(notify.in_current_span());
     }
 }
 
@@ -439,23 +778,32 @@ async fn notify_edit(
     manager: Ptr<TextEditorManager>,
     editor_body: Ptr<Mutex<Option<Box<dyn EditorBody>>>>,
     path: FilePath<Arc<Path>>,
-    writing: Arc<AtomicU32>,
+    save: Saver,
+    collab: Weak<CollabClient>,
 ) {
     debug!("Loading modified file");
     match fsio::client::load_file(manager.remote.clone(), path.clone()).await {
         Ok(Some(fsio::File::TextFile {
-            metadata: _,
+            metadata,
             original: _,
             content,
         })) => {
             debug!("Loaded modified file");
+            // The editing session merges the file on disk with the edits of its clients.
+            if let Some(collab) = collab.upgrade()
+                && collab.reload()
+            {
+                return;
+            }
             let Some(editor_body) = &*editor_body.lock().unwrap() else {
                 debug!("The modified file has no mutable editor body, force reload");
                 manager.path.file.force(path.file);
                 return;
             };
-            if writing.load(SeqCst) == 0 {
+            // Unsaved edits are kept until the conflict is resolved.
+            if save.writing.load(SeqCst) == 0 && save.conflict.get_value_untracked().is_none() {
                 editor_body.set_content(content.to_string());
+                *save.version.lock().unwrap() = FileVersion { metadata, content };
             }
         }
         Ok(Some(fsio::File::PdfFile { base64, .. })) => {
@@ -476,6 +824,14 @@ async fn notify_edit(
             debug!("The modified file is a folder, force reload");
             manager.path.file.force(path.file);
         }
+        Ok(Some(
+            fsio::File::ImageFile { .. }
+            | fsio::File::BinaryFile { .. }
+            | fsio::File::LargeFile { .. },
+        )) => {
+            debug!("The modified file can't be edited anymore, force reload");
+            manager.path.file.force(path.file);
+        }
         Ok(Some(fsio::File::Error(error))) => {
             warn!("Loading file returned {error}");
         }
@@ -489,13 +845,17 @@ async fn notify_edit(
 fn make_diagnostics_notify_handler(
     editor_body: &Ptr<Mutex<Option<Box<dyn EditorBody>>>>,
     path: &FilePath<Arc<Path>>,
+    cargo_fixes: &XSignal<usize>,
 ) -> impl Fn(&NotifyResponse) + 'static {
+    let full_path = path.as_deref().full_path().to_owned_string();
+    let cargo_fixes = cargo_fixes.clone();
     move |event| {
         autoclone!(editor_body, path);
         let _span = debug_span!("Diagnostics notifier", ?path).entered();
         let EventKind::CargoCheck(diagnostics) = &event.kind else {
             return;
         };
+        cargo_fixes.set(count_fixes(diagnostics, &full_path));
         if let Ok(diagnostics) = serde_wasm_bindgen::to_value(diagnostics)
             && let Some(editor_body) = &*editor_body.lock().unwrap()
         {
@@ -503,3 +863,18 @@ fn make_diagnostics_notify_handler(
         }
     }
 }
+
+/// The same fix is reported once per target that compiles the file.
+fn count_fixes(diagnostics: &[SyntheticDiagnostic], full_path: &str) -> usize {
+    let fixes = diagnostics
+        .iter()
+        .flat_map(|diagnostic| diagnostic.machine_applicable_fixes(full_path))
+        .map(|span| {
+            (
+                span.offset_start,
+                span.offset_end,
+                &span.suggested_replacement,
+            )
+        });
+    fixes.collect::<BTreeSet<_>>().len()
+}
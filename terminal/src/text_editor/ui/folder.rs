@@ -64,15 +64,14 @@ pub fn folder(
             .unwrap_or_else(|| span("-"));
         let user = file.user.clone().unwrap_or_default();
         let group = file.group.clone().unwrap_or_default();
+        let kind = match (file.is_symlink, is_dir) {
+            (true, _) => 'l',
+            (false, true) => 'd',
+            (false, false) => '-',
+        };
         let permissions = file
             .mode
-            .map(|m| {
-                format!(
-                    "{}{}",
-                    if is_dir { 'd' } else { '-' },
-                    mode_to_permissions(m)
-                )
-            })
+            .map(|m| format!("{kind}{}", mode_to_permissions(m)))
             .unwrap_or_default();
         let file_path = FilePath {
             base: manager.path.base.get_value_untracked(),
@@ -100,6 +99,7 @@ pub fn folder(
             td("{permissions}"),
             td(
                 class = style::FOLDER_ACTIONS,
+                properties_action(manager.clone(), file_path.clone(), name.clone()),
                 download_action(&manager, file_path.clone(), name.clone(), is_dir),
                 trash_action(
                     manager.clone(),
@@ -163,6 +163,28 @@ pub fn folder(
     )
 }
 
+#[html]
+fn properties_action(
+    manager: Ptr<TextEditorManager>,
+    file_path: FilePath<Arc<Path>>,
+    name: Arc<str>,
+) -> XElement {
+    if &*name == ".." {
+        return span();
+    }
+    img(
+        class = style::PROPERTIES_ACTION,
+        #[cfg(not(feature = "client-prod"))]
+        class = "folder-properties-icon",
+        src = icons::properties(),
+        title = "Properties",
+        click = move |event: MouseEvent| {
+            event.stop_propagation();
+            manager.open_properties(file_path.clone());
+        },
+    )
+}
+
 #[html]
 fn download_action(
     manager: &TextEditorManager,
@@ -170,12 +192,22 @@ fn download_action(
     name: Arc<str>,
     is_dir: bool,
 ) -> XElement {
-    if is_dir || &*name == ".." {
+    if &*name == ".." {
         return span();
     }
+    let (href, download, title) = if is_dir {
+        let href = archive_url(manager, &file_path);
+        (href, format!("{name}.tar"), "Download as a tar archive")
+    } else {
+        (
+            download_url(manager, &file_path),
+            name.to_string(),
+            "Download",
+        )
+    };
     a(
-        href = download_url(manager, &file_path),
-        download = name,
+        href = href,
+        download = download,
         click = move |event: MouseEvent| {
             event.stop_propagation();
         },
@@ -184,7 +216,7 @@ fn download_action(
             #[cfg(not(feature = "client-prod"))]
             class = "folder-download-icon",
             src = icons::download(),
-            title = "Download",
+            title = title,
         ),
     )
 }
@@ -216,7 +248,10 @@ fn trash_action(
     )
 }
 
-fn download_url(manager: &TextEditorManager, file_path: &FilePath<Arc<Path>>) -> Option<String> {
+pub(in crate::text_editor) fn download_url(
+    manager: &TextEditorManager,
+    file_path: &FilePath<Arc<Path>>,
+) -> Option<String> {
     let remote = serde_json::to_string(&manager.remote)
         .inspect_err(|error| warn!("Failed to JSON serialize the remote: {error}"))
         .ok()?;
@@ -229,6 +264,14 @@ fn download_url(manager: &TextEditorManager, file_path: &FilePath<Arc<Path>>) ->
     .into()
 }
 
+/// Downloads the folder as a tar archive, built by the server that has the folder.
+pub(in crate::text_editor) fn archive_url(
+    manager: &TextEditorManager,
+    folder_path: &FilePath<Arc<Path>>,
+) -> Option<String> {
+    Some(download_url(manager, folder_path)? + "&archive=true")
+}
+
 async fn delete_file(
     manager: Ptr<TextEditorManager>,
     folder_path: Arc<Path>,
@@ -343,7 +386,7 @@ fn process_folder_state(
 
 #[html]
 #[template(tag = span)]
-fn timestamp(#[signal] mut t: Box<timestamp::Timestamp>) -> XElement {
+pub fn timestamp(#[signal] mut t: Box<timestamp::Timestamp>) -> XElement {
     tag(
         "{t}",
         before_render = move |_| {
@@ -372,7 +415,7 @@ fn mode_to_permissions(mode: u32) -> String {
         .collect()
 }
 
-fn print_size(size: u64) -> String {
+pub(super) fn print_size(size: u64) -> String {
     if size < 1000 {
         return format!("{size}b");
     }
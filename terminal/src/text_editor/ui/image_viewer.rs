@@ -0,0 +1,19 @@
+use std::sync::Arc;
+
+use terrazzo::html;
+use terrazzo::prelude::*;
+
+terrazzo_css::import_style!(pub(super) style, "image_viewer.scss");
+
+#[html]
+pub(super) fn image_viewer(mime: Arc<str>, base64: Arc<str>) -> XElement {
+    div(
+        class = style::IMAGE_VIEWER,
+        #[cfg(not(feature = "client-prod"))]
+        class = "image-viewer",
+        img(
+            src = format!("data:{mime};base64,{base64}"),
+            alt = "Image preview",
+        ),
+    )
+}
@@ -0,0 +1,221 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Arc;
+
+use nameth::NamedEnumValues as _;
+use nameth::nameth;
+use terrazzo::autoclone;
+use terrazzo::html;
+use terrazzo::prelude::*;
+use terrazzo::template;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::MouseEvent;
+use web_sys::js_sys::Uint8Array;
+
+use self::diagnostics::warn;
+use super::folder::download_url;
+use super::folder::print_size;
+use crate::api::client::request;
+use crate::api::client::request::Method;
+use crate::api::client::request::SendRequestError;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio::encoding;
+use crate::text_editor::fsio::encoding::Encoding;
+use crate::text_editor::manager::TextEditorManager;
+
+terrazzo_css::import_style!(pub(super) style, "paged_viewer.scss");
+
+const TEXT_PAGE_SIZE: u64 = 256 * 1024;
+const HEX_PAGE_SIZE: u64 = 16 * 1024;
+const HEX_ROW_SIZE: usize = 16;
+
+/// How the pages of a file are shown.
+#[derive(Clone, Copy, Debug)]
+pub(super) enum PageFormat {
+    Hex,
+    Text(Encoding),
+}
+
+impl PageFormat {
+    fn page_size(self) -> u64 {
+        match self {
+            Self::Hex => HEX_PAGE_SIZE,
+            Self::Text(_) => TEXT_PAGE_SIZE,
+        }
+    }
+
+    fn lookahead(self) -> u64 {
+        match self {
+            Self::Hex => 0,
+            Self::Text(_) => encoding::PAGE_LOOKAHEAD,
+        }
+    }
+
+    fn render(self, offset: u64, bytes: &[u8]) -> String {
+        let length = (self.page_size() as usize).min(bytes.len());
+        match self {
+            Self::Hex => hex_dump(offset, &bytes[..length]),
+            Self::Text(encoding) => encoding::decode_page(bytes, offset, length, encoding),
+        }
+    }
+}
+
+/// A read-only viewer that downloads the page of the file it shows.
+struct PagedViewer {
+    manager: Ptr<TextEditorManager>,
+    path: FilePath<Arc<Path>>,
+    format: PageFormat,
+    size: u64,
+    page: XSignal<u64>,
+    content: XSignal<Option<Arc<str>>>,
+}
+
+impl PagedViewer {
+    fn pages(&self) -> u64 {
+        self.size.div_ceil(self.format.page_size()).max(1)
+    }
+
+    fn load(self: &Ptr<Self>, page: u64) {
+        let page = page.min(self.pages() - 1);
+        if page == self.page.get_value_untracked() && self.content.get_value_untracked().is_some() {
+            return;
+        }
+        self.page.set(page);
+        let this = self.clone();
+        spawn_local(async move {
+            let offset = page * this.format.page_size();
+            let content = match this.fetch(offset).await {
+                Ok(bytes) => this.format.render(offset, &bytes),
+                Err(error) => {
+                    warn!("Failed to load page {page}: {error}");
+                    error.to_string()
+                }
+            };
+            // Pages can load out of order.
+            if this.page.get_value_untracked() == page {
+                this.content.set(Some(content.into()));
+            }
+        });
+    }
+
+    async fn fetch(&self, offset: u64) -> Result<Vec<u8>, FetchPageError> {
+        let url = download_url(&self.manager, &self.path).ok_or(FetchPageError::InvalidRemote)?;
+        let length = self.format.page_size() + self.format.lookahead();
+        let url = format!("{url}&offset={offset}&length={length}");
+        let response = request::send_request(Method::GET, url, |_| {}).await?;
+        let buffer = response.array_buffer().map_err(FetchPageError::Body)?;
+        let buffer = JsFuture::from(buffer).await.map_err(FetchPageError::Body)?;
+        Ok(Uint8Array::new(&buffer).to_vec())
+    }
+}
+
+#[nameth]
+#[derive(thiserror::Error, Debug)]
+enum FetchPageError {
+    #[error("[{n}] {0}", n = self.name())]
+    Request(#[from] SendRequestError),
+
+    #[error("[{n}] Failed to read the page: {0:?}", n = self.name())]
+    Body(JsValue),
+
+    #[error("[{n}] Failed to serialize the remote", n = self.name())]
+    InvalidRemote,
+}
+
+#[autoclone]
+#[html]
+pub(super) fn paged_viewer(
+    manager: Ptr<TextEditorManager>,
+    path: FilePath<Arc<Path>>,
+    size: u64,
+    format: PageFormat,
+) -> XElement {
+    let viewer = Ptr::new(PagedViewer {
+        manager,
+        path,
+        format,
+        size,
+        page: XSignal::new("paged-viewer-page", 0),
+        content: XSignal::new("paged-viewer-content", None),
+    });
+    let pages = viewer.pages();
+    let go_to = |target: fn(u64, u64) -> u64| {
+        move |_: MouseEvent| {
+            autoclone!(viewer);
+            let page = viewer.page.get_value_untracked();
+            viewer.load(target(page, pages))
+        }
+    };
+    div(
+        class = style::PAGED_VIEWER,
+        #[cfg(not(feature = "client-prod"))]
+        class = "paged-viewer",
+        div(
+            class = style::PAGED_TOOLBAR,
+            button("First", click = go_to(|_, _| 0)),
+            button("Previous", click = go_to(|page, _| page.saturating_sub(1))),
+            show_position(viewer.page.clone(), pages, size),
+            button("Next", click = go_to(|page, _| page + 1)),
+            button("Last", click = go_to(|_, pages| pages - 1)),
+        ),
+        show_page(viewer.content.clone()),
+        after_render = move |_| {
+            autoclone!(viewer);
+            viewer.load(0)
+        },
+    )
+}
+
+#[html]
+#[template(tag = span)]
+fn show_position(#[signal] page: u64, pages: u64, size: u64) -> XElement {
+    let page = page + 1;
+    let size = print_size(size);
+    tag("Page {page} of {pages} ({size})")
+}
+
+#[html]
+#[template(tag = pre)]
+fn show_page(#[signal] content: Option<Arc<str>>) -> XElement {
+    let content = content.as_deref().unwrap_or("Loading...").to_owned();
+    tag(class = style::PAGED_CONTENT, "{content}")
+}
+
+/// Prints rows of offsets, hex bytes and printable characters.
+fn hex_dump(offset: u64, bytes: &[u8]) -> String {
+    let mut dump = String::new();
+    for (i, row) in bytes.chunks(HEX_ROW_SIZE).enumerate() {
+        let address = offset + (i * HEX_ROW_SIZE) as u64;
+        let hex = row
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let ascii = row
+            .iter()
+            .map(|b| match b {
+                b' '..=b'~' => *b as char,
+                _ => '.',
+            })
+            .collect::<String>();
+        let _ = writeln!(dump, "{address:08x}  {hex:<47}  |{ascii}|");
+    }
+    dump
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn hex_dump() {
+        let bytes = b"\x7FELF\x02\x01\x01\0\0\0\0\0\0\0\0\0\x03\0>\0";
+        assert_eq!(
+            "\
+00001000  7f 45 4c 46 02 01 01 00 00 00 00 00 00 00 00 00  |.ELF............|
+00001010  03 00 3e 00                                      |..>.|
+",
+            super::hex_dump(0x1000, bytes)
+        );
+    }
+}
@@ -0,0 +1,330 @@
+//! The properties panel: the details of an entry, its permissions, its owner and its symlink.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use server_fn::ServerFnError;
+use terrazzo::autoclone;
+use terrazzo::html;
+use terrazzo::prelude::*;
+use terrazzo::template;
+use terrazzo::widgets::element_capture::ElementCapture;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::HtmlInputElement;
+use web_sys::KeyboardEvent;
+use web_sys::MouseEvent;
+
+use self::diagnostics::Instrument as _;
+use self::diagnostics::debug;
+use self::diagnostics::warn;
+use super::folder::print_size;
+use crate::assets::icons;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio;
+use crate::text_editor::fsio::FileProperties;
+use crate::text_editor::manager::TextEditorManager;
+
+terrazzo_css::import_style!(style, "properties.scss");
+
+#[derive(Clone, Debug)]
+pub(in crate::text_editor) struct PropertiesPanel {
+    path: FilePath<Arc<Path>>,
+
+    /// Kept while the properties are loaded again after a change.
+    properties: Option<Arc<FileProperties>>,
+    error: Option<Arc<str>>,
+}
+
+impl TextEditorManager {
+    /// Opens the properties panel of the entry.
+    pub(in crate::text_editor) fn open_properties(self: &Ptr<Self>, path: FilePath<Arc<Path>>) {
+        debug!("Show properties of {path:?}");
+        self.properties.force(Some(PropertiesPanel {
+            path: path.clone(),
+            properties: None,
+            error: None,
+        }));
+        spawn_local(load_properties(self.clone(), path).in_current_span());
+    }
+
+    /// The properties of an entry, shown above the editor until the panel is closed.
+    pub(in crate::text_editor) fn show_properties(self: &Ptr<Self>) -> XElement {
+        properties_panel(self.clone(), self.properties.clone())
+    }
+}
+
+async fn load_properties(manager: Ptr<TextEditorManager>, path: FilePath<Arc<Path>>) {
+    match fsio::client::file_properties(manager.remote.clone(), path.clone()).await {
+        Ok(properties) => update_panel(&manager, &path, |panel| {
+            panel.properties = Some(properties.into());
+        }),
+        Err(error) => fail(&manager, &path, error),
+    }
+}
+
+/// Runs the change, then shows the new properties or the error.
+async fn apply(
+    manager: Ptr<TextEditorManager>,
+    path: FilePath<Arc<Path>>,
+    change: impl Future<Output = Result<(), ServerFnError>>,
+) {
+    update_panel(&manager, &path, |panel| panel.error = None);
+    if let Err(error) = change.await {
+        return fail(&manager, &path, error);
+    }
+    load_properties(manager.clone(), path.clone()).await;
+    manager.refresh_folder(path.file.parent().unwrap_or(Path::new("")));
+}
+
+async fn follow(manager: Ptr<TextEditorManager>, path: FilePath<Arc<Path>>) {
+    let target = match fsio::client::follow_symlink(manager.remote.clone(), path.clone()).await {
+        Ok(target) => target,
+        Err(error) => return fail(&manager, &path, error),
+    };
+    debug!("Following {path:?} to {target:?}");
+    let _batch = Batch::use_batch("follow-symlink");
+    manager.properties.force(None);
+    if manager.path.base.get_value_untracked() != target.base {
+        manager.path.base.force(target.base);
+    }
+    manager.path.file.force(target.file);
+}
+
+fn fail(manager: &TextEditorManager, path: &FilePath<Arc<Path>>, error: ServerFnError) {
+    warn!("Failed to update the properties of {path:?}: {error}");
+    update_panel(manager, path, |panel| {
+        panel.error = Some(error.to_string().into())
+    });
+}
+
+/// Updates the panel, unless it was closed or shows another entry.
+fn update_panel(
+    manager: &TextEditorManager,
+    path: &FilePath<Arc<Path>>,
+    f: impl FnOnce(&mut PropertiesPanel),
+) {
+    manager.properties.update_mut(|panel| {
+        let mut panel = std::mem::take(panel);
+        if let Some(panel) = &mut panel
+            && panel.path == *path
+        {
+            f(panel);
+        }
+        panel
+    });
+}
+
+/// The fields of the properties panel.
+#[derive(Clone, Default)]
+struct PropertiesInputs {
+    mode: ElementCapture<HtmlInputElement>,
+    user: ElementCapture<HtmlInputElement>,
+    group: ElementCapture<HtmlInputElement>,
+}
+
+impl PropertiesInputs {
+    fn set_permissions(&self, manager: &Ptr<TextEditorManager>, path: &FilePath<Arc<Path>>) {
+        let mode = self.mode.with(|input| input.value());
+        let Ok(mode) = u32::from_str_radix(mode.trim(), 8) else {
+            let error = format!("Invalid mode '{mode}', expected octal digits like 0644");
+            return update_panel(manager, path, |panel| panel.error = Some(error.into()));
+        };
+        let change = fsio::client::set_permissions(manager.remote.clone(), path.clone(), mode);
+        spawn_local(apply(manager.clone(), path.clone(), change).in_current_span());
+    }
+
+    fn set_owner(&self, manager: &Ptr<TextEditorManager>, path: &FilePath<Arc<Path>>) {
+        let value = |input: &ElementCapture<HtmlInputElement>| {
+            let value = input.with(|input| input.value());
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.to_owned())
+        };
+        let (user, group) = (value(&self.user), value(&self.group));
+        if user.is_none() && group.is_none() {
+            return;
+        }
+        let change = fsio::client::set_owner(manager.remote.clone(), path.clone(), user, group);
+        spawn_local(apply(manager.clone(), path.clone(), change).in_current_span());
+    }
+}
+
+#[autoclone]
+#[html]
+#[template(tag = div)]
+fn properties_panel(
+    manager: Ptr<TextEditorManager>,
+    #[signal] panel: Option<PropertiesPanel>,
+) -> XElement {
+    let Some(PropertiesPanel {
+        path,
+        properties,
+        error,
+    }) = panel
+    else {
+        return tag(style::display = "none", style::visibility = "hidden");
+    };
+    let full_path = path.full_path();
+    let full_path = full_path.display();
+    let close = img(
+        class = style::ACTION,
+        src = icons::close_tab(),
+        title = "Close",
+        click = move |_: MouseEvent| {
+            autoclone!(manager);
+            manager.properties.force(None);
+        },
+    );
+    let error = match error {
+        Some(error) => div(class = style::PROPERTIES_ERROR, "{error}"),
+        None => span(),
+    };
+    let details = match properties {
+        Some(properties) => properties_details(&manager, &path, &properties),
+        None => div(class = style::PROPERTIES_LOADING, "Loading..."),
+    };
+    tag(
+        class = style::PROPERTIES,
+        #[cfg(not(feature = "client-prod"))]
+        class = "properties-panel",
+        div(
+            class = style::PROPERTIES_HEADER,
+            span(
+                class = style::PROPERTIES_PATH,
+                title = full_path.to_string(),
+                "{full_path}",
+            ),
+            close,
+        ),
+        error,
+        details,
+    )
+}
+
+#[autoclone]
+#[html]
+fn properties_details(
+    manager: &Ptr<TextEditorManager>,
+    path: &FilePath<Arc<Path>>,
+    properties: &FileProperties,
+) -> XElement {
+    let metadata = &properties.metadata;
+    let kind = if metadata.is_symlink {
+        "Symbolic link"
+    } else if metadata.is_dir {
+        "Folder"
+    } else {
+        "File"
+    };
+    let size = metadata
+        .size
+        .map(print_size)
+        .unwrap_or_else(|| "-".to_owned());
+    let mode = metadata.mode.map(|mode| format!("{:04o}", mode & 0o7777));
+    let user = metadata.user.as_deref().unwrap_or_default();
+    let group = metadata.group.as_deref().unwrap_or_default();
+    let (uid, gid) = (properties.uid, properties.gid);
+
+    let mut rows = vec![
+        property("Type", kind.to_owned()),
+        property("Size", size),
+        property("Inode", properties.inode.to_string()),
+        property("Device", properties.device.to_string()),
+        property("Hard links", properties.hard_links.to_string()),
+        property("Owner", format!("{user} ({uid}) : {group} ({gid})")),
+    ];
+    if let Some(target) = &properties.symlink_target {
+        let open_target = img(
+            class = style::ACTION,
+            src = icons::symlink(),
+            title = "Open the target",
+            click = move |_: MouseEvent| {
+                autoclone!(manager, path);
+                spawn_local(follow(manager.clone(), path.clone()).in_current_span());
+            },
+        );
+        rows.push(tr(td("Target"), td(span("{target}"), open_target)));
+    }
+    for (name, timestamp) in &properties.timestamps {
+        rows.push(property(name, timestamp.to_string()));
+    }
+
+    let inputs = PropertiesInputs::default();
+    let keydown = |apply: fn(&PropertiesInputs, &Ptr<TextEditorManager>, &FilePath<Arc<Path>>)| {
+        move |event: KeyboardEvent| {
+            autoclone!(inputs, manager, path);
+            if event.key() == "Enter" {
+                event.prevent_default();
+                apply(&inputs, &manager, &path);
+            }
+        }
+    };
+    rows.push(tr(
+        td("Permissions"),
+        td(
+            input(
+                before_render = inputs.mode.capture(),
+                r#type = "text",
+                value = mode.unwrap_or_default(),
+                placeholder = "Octal mode",
+                keydown = keydown(PropertiesInputs::set_permissions),
+            ),
+            button(
+                "Apply",
+                click = move |_| {
+                    autoclone!(inputs, manager, path);
+                    inputs.set_permissions(&manager, &path);
+                },
+            ),
+        ),
+    ));
+    rows.push(tr(
+        td("Change owner"),
+        td(
+            input(
+                before_render = inputs.user.capture(),
+                r#type = "text",
+                value = user.to_owned(),
+                placeholder = "User",
+                keydown = keydown(PropertiesInputs::set_owner),
+            ),
+            input(
+                before_render = inputs.group.capture(),
+                r#type = "text",
+                value = group.to_owned(),
+                placeholder = "Group",
+                keydown = keydown(PropertiesInputs::set_owner),
+            ),
+            button(
+                "Apply",
+                click = move |_| {
+                    autoclone!(inputs, manager, path);
+                    inputs.set_owner(&manager, &path);
+                },
+            ),
+        ),
+    ));
+
+    let xattrs = if properties.xattrs.is_empty() {
+        vec![property("Extended attributes", "-".to_owned())]
+    } else {
+        properties
+            .xattrs
+            .iter()
+            .map(|(name, value)| property(name, value.to_string()))
+            .collect()
+    };
+    div(
+        class = style::PROPERTIES_DETAILS,
+        table(tbody(rows..)),
+        table(
+            class = style::PROPERTIES_XATTRS,
+            thead(tr(th("Attribute"), th("Value"))),
+            tbody(xattrs..),
+        ),
+    )
+}
+
+#[html]
+fn property(name: &str, value: String) -> XElement {
+    tr(td("{name}"), td(title = value.clone(), "{value}"))
+}
@@ -0,0 +1,300 @@
+//! Uploads dropped files one chunk at a time, and shows their progress.
+//!
+//! A failed upload can be resumed: it continues after the last chunk the server received.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::Relaxed;
+use std::time::Duration;
+
+use terrazzo::autoclone;
+use terrazzo::html;
+use terrazzo::prelude::*;
+use terrazzo::template;
+use terrazzo::widgets::sleep::sleep;
+use wasm_bindgen_futures::spawn_local;
+use web_sys::Blob;
+use web_sys::File;
+use web_sys::MouseEvent;
+
+use self::diagnostics::Instrument as _;
+use self::diagnostics::debug;
+use self::diagnostics::warn;
+use super::drag::encode_query;
+use super::drag::encode_query_path;
+use super::folder::print_size;
+use crate::api::client::request;
+use crate::api::client::request::Method;
+use crate::api::client::request::SendRequestError;
+use crate::assets::icons;
+use crate::text_editor::file_path::FilePath;
+use crate::text_editor::fsio;
+use crate::text_editor::manager::TextEditorManager;
+
+terrazzo_css::import_style!(style, "upload.scss");
+
+/// Each chunk is a request, so a failed upload only sends its last chunk again.
+const CHUNK_SIZE: u64 = 4 * 1024 * 1024;
+
+/// How many times a chunk is sent before the upload fails.
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+static NEXT_UPLOAD_ID: AtomicU32 = AtomicU32::new(0);
+
+#[derive(Clone, Debug)]
+pub(in crate::text_editor) struct Upload {
+    id: u32,
+    file: File,
+    folder: FilePath<Arc<Path>>,
+
+    /// The bytes the server acknowledged.
+    sent: u64,
+    status: UploadStatus,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum UploadStatus {
+    Running,
+    Done,
+    Failed(Arc<str>),
+}
+
+impl Upload {
+    fn destination(&self) -> FilePath<Arc<Path>> {
+        FilePath {
+            base: self.folder.base.clone(),
+            file: Arc::from(self.folder.file.join(self.file.name())),
+        }
+    }
+}
+
+/// Uploads the file into the folder, replacing the file with the same name.
+pub(in crate::text_editor) fn start_upload(
+    manager: &Ptr<TextEditorManager>,
+    folder: &FilePath<Arc<Path>>,
+    file: File,
+) {
+    if file.name().is_empty() {
+        debug!("Dropped file has no name; skipping upload");
+        return;
+    }
+    let upload = Upload {
+        id: NEXT_UPLOAD_ID.fetch_add(1, Relaxed),
+        file,
+        folder: folder.clone(),
+        sent: 0,
+        status: UploadStatus::Running,
+    };
+    debug!("Uploading dropped file to {:?}", upload.destination());
+    manager.uploads.update_mut(|uploads| {
+        let mut uploads = std::mem::take(uploads);
+        uploads.push(upload.clone());
+        uploads
+    });
+    spawn_local(run_upload(manager.clone(), upload).in_current_span());
+}
+
+async fn run_upload(manager: Ptr<TextEditorManager>, upload: Upload) {
+    let id = upload.id;
+    let size = upload.file.size() as u64;
+    let Some(url) = upload_url(&manager, &upload.destination()) else {
+        return set_status(&manager, id, UploadStatus::Failed("Invalid remote".into()));
+    };
+    let mut offset = upload.sent;
+    loop {
+        let end = (offset + CHUNK_SIZE).min(size);
+        let chunk = match upload
+            .file
+            .slice_with_f64_and_f64(offset as f64, end as f64)
+        {
+            Ok(chunk) => chunk,
+            Err(error) => {
+                let error = format!("Failed to read the file: {error:?}");
+                return set_status(&manager, id, UploadStatus::Failed(error.into()));
+            }
+        };
+        if let Err(error) = upload_chunk(&url, offset, chunk).await {
+            warn!("Failed to upload {:?}: {error}", upload.destination());
+            return set_status(&manager, id, UploadStatus::Failed(error.to_string().into()));
+        }
+        offset = end;
+        update_upload(&manager, id, |upload| upload.sent = offset);
+        // An empty file is still uploaded once, to create it.
+        if offset >= size {
+            break;
+        }
+    }
+    debug!("Uploaded {:?}", upload.destination());
+    set_status(&manager, id, UploadStatus::Done);
+    if manager.path.file.get_value_untracked() == upload.folder.file {
+        manager.path.file.force(upload.folder.file.clone());
+    }
+}
+
+async fn upload_chunk(url: &str, offset: u64, chunk: Blob) -> Result<(), SendRequestError> {
+    let url = format!("{url}&offset={offset}");
+    let mut attempt = 1;
+    loop {
+        let chunk = chunk.clone();
+        let result = request::send_request(Method::POST, url.clone(), move |request| {
+            request.set_body(&chunk);
+        })
+        .await;
+        match result {
+            Ok(_) => return Ok(()),
+            Err(error) if attempt < MAX_ATTEMPTS => {
+                debug!("Attempt {attempt} to upload at {offset} failed: {error}");
+                attempt += 1;
+                let _ = sleep(RETRY_DELAY).await;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// Resumes after the bytes that were acknowledged and are still on the server.
+async fn resume_upload(manager: Ptr<TextEditorManager>, id: u32) {
+    let uploads = manager.uploads.get_value_untracked();
+    let Some(mut upload) = uploads.into_iter().find(|upload| upload.id == id) else {
+        return;
+    };
+    let name = upload.file.name();
+    let list = fsio::client::list_folder(manager.remote.clone(), upload.folder.clone()).await;
+    let uploaded = match list {
+        Ok(list) => list
+            .iter()
+            .flat_map(|list| list.iter())
+            .find(|metadata| *metadata.name == *name)
+            .and_then(|metadata| metadata.size)
+            .unwrap_or(0),
+        Err(error) => {
+            warn!("Failed to get the uploaded size of {name}: {error}");
+            0
+        }
+    };
+    upload.sent = upload.sent.min(uploaded);
+    upload.status = UploadStatus::Running;
+    debug!("Resuming the upload of {name} at {}", upload.sent);
+    update_upload(&manager, id, |current| *current = upload.clone());
+    run_upload(manager, upload).await
+}
+
+fn set_status(manager: &TextEditorManager, id: u32, status: UploadStatus) {
+    update_upload(manager, id, |upload| upload.status = status);
+}
+
+fn update_upload(manager: &TextEditorManager, id: u32, f: impl FnOnce(&mut Upload)) {
+    manager.uploads.update_mut(|uploads| {
+        let mut uploads = std::mem::take(uploads);
+        if let Some(upload) = uploads.iter_mut().find(|upload| upload.id == id) {
+            f(upload);
+        }
+        uploads
+    });
+}
+
+fn upload_url(manager: &TextEditorManager, file_path: &FilePath<Arc<Path>>) -> Option<String> {
+    let remote = serde_json::to_string(&manager.remote)
+        .inspect_err(|error| warn!("Failed to JSON serialize the remote: {error}"))
+        .ok()?;
+    format!(
+        "/api/text_editor/fsio/upload?base={}&file={}&remote={}",
+        encode_query_path(file_path.base.as_ref()),
+        encode_query_path(file_path.file.as_ref()),
+        encode_query(&remote),
+    )
+    .into()
+}
+
+impl TextEditorManager {
+    /// The progress of the uploads, shown above the editor until they are dismissed.
+    pub(in crate::text_editor) fn show_uploads(self: &Ptr<Self>) -> XElement {
+        uploads_panel(self.clone(), self.uploads.clone())
+    }
+}
+
+#[html]
+#[template(tag = div)]
+fn uploads_panel(manager: Ptr<TextEditorManager>, #[signal] uploads: Vec<Upload>) -> XElement {
+    if uploads.is_empty() {
+        return tag(style::display = "none", style::visibility = "hidden");
+    }
+    let rows: Vec<_> = uploads
+        .iter()
+        .map(|upload| upload_row(manager.clone(), upload))
+        .collect();
+    tag(
+        class = style::UPLOADS,
+        #[cfg(not(feature = "client-prod"))]
+        class = "uploads-panel",
+        rows..,
+    )
+}
+
+#[autoclone]
+#[html]
+fn upload_row(manager: Ptr<TextEditorManager>, upload: &Upload) -> XElement {
+    let id = upload.id;
+    let name = upload.file.name();
+    let size = upload.file.size() as u64;
+    let percent = (upload.sent * 100).checked_div(size).unwrap_or(100);
+    let (status, is_failed) = match &upload.status {
+        UploadStatus::Running => (
+            format!("{} / {}", print_size(upload.sent), print_size(size)),
+            false,
+        ),
+        UploadStatus::Done => (print_size(size), false),
+        UploadStatus::Failed(error) => (error.to_string(), true),
+    };
+    let resume = if is_failed {
+        img(
+            class = style::ACTION,
+            src = icons::refresh(),
+            title = "Resume",
+            click = move |_: MouseEvent| {
+                autoclone!(manager);
+                spawn_local(resume_upload(manager.clone(), id).in_current_span());
+            },
+        )
+    } else {
+        span()
+    };
+    let dismiss = if upload.status == UploadStatus::Running {
+        span()
+    } else {
+        img(
+            class = style::ACTION,
+            src = icons::close_tab(),
+            title = "Dismiss",
+            click = move |_: MouseEvent| {
+                autoclone!(manager);
+                manager.uploads.update_mut(|uploads| {
+                    let mut uploads = std::mem::take(uploads);
+                    uploads.retain(|upload| upload.id != id);
+                    uploads
+                });
+            },
+        )
+    };
+    div(
+        class = style::UPLOAD,
+        class = is_failed.then_some(style::FAILED),
+        div(
+            class = style::UPLOAD_HEADER,
+            span(class = style::UPLOAD_NAME, "{name}"),
+            span(
+                class = style::UPLOAD_STATUS,
+                title = status.clone(),
+                "{status}",
+            ),
+            resume,
+            dismiss,
+        ),
+        div(
+            class = style::PROGRESS_BAR,
+            div(style::width = format!("{percent}%")),
+        ),
+    )
+}
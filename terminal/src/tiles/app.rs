@@ -8,6 +8,10 @@ pub enum App {
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "T"))]
     Terminal,
 
+    #[cfg(feature = "terminal")]
+    #[cfg_attr(not(feature = "diagnostics"), serde(rename = "R"))]
+    Replay,
+
     #[cfg(feature = "text-editor")]
     #[cfg_attr(not(feature = "terminal"), default)]
     #[cfg_attr(not(feature = "diagnostics"), serde(rename = "E"))]
@@ -33,6 +37,8 @@ impl std::fmt::Display for App {
             App::Default => "Default",
             #[cfg(feature = "terminal")]
             App::Terminal => "Terminal",
+            #[cfg(feature = "terminal")]
+            App::Replay => "Replay",
             #[cfg(feature = "text-editor")]
             App::TextEditor => "Text editor",
             #[cfg(feature = "converter")]
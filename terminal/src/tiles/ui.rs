@@ -258,6 +258,9 @@ fn show_app(tile: TilePtr, #[signal] app: App) -> XElement {
             #[cfg(feature = "terminal")]
             App::Terminal => div(move |t| crate::terminal::ui::terminals(t, tile.clone())),
 
+            #[cfg(feature = "terminal")]
+            App::Replay => crate::terminal::replay::replay(tile),
+
             #[cfg(feature = "text-editor")]
             App::TextEditor => crate::text_editor::ui::text_editor(tile),
 
@@ -38,18 +38,19 @@ PORT_FORWARD_CLIENT_DEPS = CLIENT_DEPS + PORT_FORWARD_DEPS + REMOTES_UI_DEPS + [
 PORT_FORWARD_CLIENT_FEATURES = CLIENT_FEATURES + PORT_FORWARD_FEATURES + REMOTES_UI_FEATURES + ["port-forward-client"]
 TERMINAL_DEPS = []
 TERMINAL_FEATURES = ["terminal"]
-TERMINAL_CLIENT_DEPS = CLIENT_DEPS + TERMINAL_DEPS + TILES_STATE_CLIENT_DEPS + [
+TERMINAL_CLIENT_DEPS = CLIENT_DEPS + REMOTES_UI_DEPS + TERMINAL_DEPS + TILES_STATE_CLIENT_DEPS + [
     "@crates//:base64",
     "@crates//:futures",
     "@crates//:scopeguard",
     "@crates//:web-sys",
 ]
-TERMINAL_CLIENT_FEATURES = CLIENT_FEATURES + TERMINAL_FEATURES + TILES_STATE_CLIENT_FEATURES + ["terminal-client"]
+TERMINAL_CLIENT_FEATURES = CLIENT_FEATURES + REMOTES_UI_FEATURES + TERMINAL_FEATURES + TILES_STATE_CLIENT_FEATURES + ["terminal-client"]
 TEXT_EDITOR_DEPS = []
 TEXT_EDITOR_FEATURES = ["text-editor"]
 TEXT_EDITOR_CLIENT_DEPS = CLIENT_DEPS + REMOTES_UI_DEPS + TEXT_EDITOR_DEPS + TILES_STATE_CLIENT_DEPS + [
     "@crates//:base64",
     "@crates//:futures",
+    "@crates//:regex",
     "@crates//:scopeguard",
     "@crates//:serde-wasm-bindgen",
     "@crates//:web-sys",
@@ -132,8 +133,10 @@ PORT_FORWARD_SERVER_DEPS = PORT_FORWARD_DEPS + REMOTE_FN_UNARY_DEPS + SERVER_DEP
 PORT_FORWARD_SERVER_FEATURES = PORT_FORWARD_FEATURES + REMOTE_FN_UNARY_FEATURES + SERVER_FEATURES + ["port-forward-server"]
 TERMINAL_SERVER_DEPS = REMOTE_FN_STREAMING_DEPS + REMOTE_FN_UNARY_DEPS + SERVER_DEPS + TERMINAL_DEPS + TILES_STATE_SERVER_DEPS + [
     "//pty",
+    "@crates//:bytes",
     "@crates//:dashmap",
     "@crates//:pin-project",
+    "@crates//:regex",
     "@crates//:tracing-futures",
 ]
 TERMINAL_SERVER_FEATURES = REMOTE_FN_STREAMING_FEATURES + REMOTE_FN_UNARY_FEATURES + SERVER_FEATURES + TERMINAL_FEATURES + TILES_STATE_SERVER_FEATURES + ["terminal-server"]
@@ -180,16 +183,16 @@ _EXCLUSION_MAP = [
     {"feature": "tiles-state", "delta": []},
     {"feature": "tiles-state-client", "delta": []},
     {"feature": "tiles-state-server", "delta": []},
-    {"feature": "remote-fn-streaming", "delta": [90, 9]},
-    {"feature": "remote-fn", "delta": [89, 108, 6, 519]},
-    {"feature": "remote-fn-unary", "delta": [-517, -106, 10]},
-    {"feature": "converter", "delta": [-118, 6, 168, 2, 174, 15]},
-    {"feature": "logs-panel", "delta": [-202, 15, -170, 2, 232, 4, 242, 5]},
-    {"feature": "port-forward", "delta": [-250, 5, -238, 4, 74, 7, 252, 3, 260, 4]},
-    {"feature": "terminal", "delta": [-266, 4, -256, 3, -86, 7, 268, 2, 274, 4, 284, 4, 294, 21, 521]},
-    {"feature": "text-editor", "delta": [-519, -334, 21, -290, 4, -280, 4, -270, 2, 52, 11, 128, 3, 224, 3, 338, 17, 374, 16, 410, 28]},
-    {"feature": "client", "delta": [-450, 4, -432, 2, -424, 6, -409, -404, 16, -369, -366, 5, -354, 9, -132, 3, -72, 11, 3, 6, 2, 204, 3, 212, 6, 233, 237, 286, 3, 316, 3, 326, 5, 503, 515, 521]},
-    {"feature": "server", "delta": [-519, -513, -501, -464, 7, -442, 5, -428, 2, -411, -367, -355, -334, 5, -320, 3, -290, 3, -235, -231, -228, 9, -208, 3, -8, 2, -1, 16, 3, 34, 61, 158, 5, 176, 13, 255, 268, 2, 274, 4, 294, 11, 325, 347, 355, 358, 5, 416, 5, 517]},
+    {"feature": "remote-fn-streaming", "delta": [92, 9]},
+    {"feature": "remote-fn", "delta": [91, 110, 6, 671]},
+    {"feature": "remote-fn-unary", "delta": [-669, -108, 10]},
+    {"feature": "converter", "delta": [-120, 6, 176, 2, 182, 15]},
+    {"feature": "logs-panel", "delta": [-210, 15, -178, 2, 240, 4, 250, 5]},
+    {"feature": "port-forward", "delta": [-258, 5, -246, 4, 76, 7, 260, 3, 268, 4]},
+    {"feature": "terminal", "delta": [-274, 4, -264, 3, -88, 7, 276, 2, 282, 12, 308, 6, 322, 30, 673]},
+    {"feature": "text-editor", "delta": [-671, -380, 30, -318, 6, -304, 12, -278, 2, 54, 11, 130, 4, 232, 3, 384, 40, 466, 19, 510, 54]},
+    {"feature": "server", "delta": [-616, 14, -586, 4, -575, -572, 4, -562, 16, -516, 4, -502, 19, -462, 3, -453, -450, 3, -442, 3, -423, -419, -416, 3, -408, 2, -402, 5, -390, 4, -236, 3, 16, 3, 34, 10, 76, 27, 138, 13, 166, 5, 184, 13, 263, 276, 2, 282, 12, 326, 17, 371, 669]},
+    {"feature": "client", "delta": [-667, -587, -577, -573, -563, -530, 7, -455, -451, -443, -436, 6, -421, -417, -409, -403, -391, -369, -358, 17, -304, 12, -278, 2, -261, -208, 13, -174, 5, -162, 65, -20, 3, 3, 6, 2, 212, 3, 220, 9, 241, 245, 312, 4, 322, 2, 360, 4, 372, 5, 399, 413, 421, 439, 446, 2, 459, 490, 2, 513, 517, 532, 2, 542, 5, 561, 566, 2, 573, 580, 2, 590, 2, 596, 11, 655, 667, 673]},
 ]
 
 def compute_srcs(features):